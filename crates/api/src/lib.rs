@@ -3,7 +3,18 @@
 
 pub mod v1;
 
-use serde::{de::Unexpected, Deserialize, Serialize};
+use serde::{Deserialize, Serialize};
+
+/// Represents a version of the Warg REST API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiVersion {
+    /// Version 1 of the API.
+    V1,
+    /// A version that is not known to this version of the API.
+    #[serde(other)]
+    Unknown,
+}
 
 /// Relative URL path for the `WellKnownConfig`.
 pub const WELL_KNOWN_PATH: &str = ".well-known/wasm-pkg/registry.json";
@@ -21,31 +32,62 @@ pub struct WellKnownConfig {
     pub warg_url: Option<String>,
 }
 
-/// A utility type for serializing and deserializing constant status codes.
-struct Status<const CODE: u16>;
+/// Relative URL path for the `DiscoveryDocument`.
+pub const DISCOVERY_PATH: &str = ".well-known/warg.json";
 
-impl<const CODE: u16> Serialize for Status<CODE> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_u16(CODE)
-    }
+/// This document allows a domain to describe the Warg registry it hosts.
+///
+/// Unknown fields are ignored so that the document may be extended.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryDocument {
+    /// The base URL of the registry API.
+    ///
+    /// If not set, the API is hosted by the domain serving the document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    /// The hash algorithms supported by the registry (e.g. `sha256`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hash_algorithms: Vec<String>,
+    /// The signature algorithms supported by the registry (e.g. `ecdsa-p256`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signature_algorithms: Vec<String>,
+    /// Hints for namespaces that are hosted by other registries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub federation: Vec<FederationHint>,
+    /// The layout of the registry.
+    #[serde(default, skip_serializing_if = "RegistryLayout::is_api")]
+    pub layout: RegistryLayout,
 }
 
-impl<'de, const CODE: u16> Deserialize<'de> for Status<CODE> {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let code = u16::deserialize(deserializer)?;
-        if code == CODE {
-            Ok(Status::<CODE>)
-        } else {
-            Err(serde::de::Error::invalid_value(
-                Unexpected::Unsigned(code as u64),
-                &"a matching status code",
-            ))
-        }
+/// Represents how a registry is served.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RegistryLayout {
+    /// The registry is served by the registry API.
+    #[default]
+    Api,
+    /// The registry is a read-only set of files laid out as described by
+    /// [`v1::static_registry`].
+    Static,
+    /// A layout that is not known to this version of the API.
+    #[serde(other)]
+    Unknown,
+}
+
+impl RegistryLayout {
+    /// Determines if the registry is served by the registry API.
+    pub fn is_api(&self) -> bool {
+        matches!(self, Self::Api)
     }
 }
+
+/// A hint that a namespace is hosted by another registry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationHint {
+    /// The namespace.
+    pub namespace: String,
+    /// The domain of the registry hosting the namespace.
+    pub registry: String,
+}
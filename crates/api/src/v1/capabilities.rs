@@ -0,0 +1,113 @@
+//! Types relating to the capabilities API.
+
+use crate::ApiVersion;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// Represents a protocol for uploading content to a registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UploadProtocol {
+    /// Content is uploaded with an HTTP `POST` request.
+    HttpPost,
+    /// Content is uploaded with an HTTP `PUT` request.
+    HttpPut,
+    /// A protocol that is not known to this version of the API.
+    #[serde(other)]
+    Unknown,
+}
+
+impl UploadProtocol {
+    /// Gets the upload protocol for the given HTTP method.
+    pub fn from_method(method: &str) -> Self {
+        match method {
+            "POST" => Self::HttpPost,
+            "PUT" => Self::HttpPut,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Represents a compression encoding for content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Compression {
+    /// The `gzip` encoding.
+    Gzip,
+    /// The `zstd` encoding.
+    Zstd,
+    /// An encoding that is not known to this version of the API.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Represents a mode of authenticating with a registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthMode {
+    /// Requests are authenticated with a bearer token.
+    Bearer,
+    /// A mode that is not known to this version of the API.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Represents the response of a capabilities request.
+///
+/// Each capability is optional; a registry that does not list a capability
+/// does not support it. Capabilities that are not known to this version of
+/// the API are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilitiesResponse {
+    /// The API versions supported by each API of the registry, keyed by
+    /// the first path segment of the API (e.g. `fetch`).
+    ///
+    /// An API that is not listed only supports version 1.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub api_versions: IndexMap<String, Vec<ApiVersion>>,
+    /// The protocols supported for uploading content.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upload_protocols: Vec<UploadProtocol>,
+    /// The compression encodings supported for content.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compression: Vec<Compression>,
+    /// Whether or not the registry supports searching packages.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub search: bool,
+    /// Whether or not the registry supports subscribing to events.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub events: bool,
+    /// Whether or not the registry supports staging package records to be
+    /// committed later.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub staged_publishes: bool,
+    /// Whether or not the registry supports embargoing the content of
+    /// package records until a given time.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub embargoed_releases: bool,
+    /// Whether or not the registry serves signed snapshots of the state of
+    /// package logs.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub package_snapshots: bool,
+    /// Whether or not the registry serves inclusion proofs as compact
+    /// multi-proofs.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub multi_proofs: bool,
+    /// Whether or not the registry serves the locations of the custom
+    /// sections of content, with content sources accepting range requests.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub content_sections: bool,
+    /// Whether or not the registry accepts content sources in requests to
+    /// publish records, fetching the content from the sources rather than
+    /// requiring it to be uploaded.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub content_sources: bool,
+    /// The modes supported for authenticating with the registry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auth_modes: Vec<AuthMode>,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
@@ -1,11 +1,9 @@
 //! Types relating to the content API.
 
+use super::problem::{ProblemCode, ProblemDetails};
 pub use super::ContentSource;
-use crate::Status;
 use indexmap::IndexMap;
-use serde::{de::Unexpected, Deserialize, Serialize, Serializer};
-use std::borrow::Cow;
-use std::str::FromStr;
+use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error;
 use warg_crypto::hash::AnyHash;
 
@@ -18,6 +16,29 @@ pub struct ContentSourcesResponse {
     pub content_sources: IndexMap<AnyHash, Vec<ContentSource>>,
 }
 
+/// Represents a custom section of WebAssembly content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentSection {
+    /// The name of the custom section.
+    pub name: String,
+    /// The offset, in bytes, of the data of the custom section within the
+    /// content.
+    pub offset: u64,
+    /// The size, in bytes, of the data of the custom section.
+    pub size: u64,
+}
+
+/// Represents a response for the custom sections of content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentSectionsResponse {
+    /// The custom sections at the top level of the content, in content order.
+    ///
+    /// Content that is not WebAssembly has no custom sections.
+    pub sections: Vec<ContentSection>,
+}
+
 /// Represents a content API error.
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -45,46 +66,17 @@ impl ContentError {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-enum EntityType {
-    ContentDigest,
-}
-
-#[derive(Serialize, Deserialize)]
-#[serde(untagged, rename_all = "camelCase")]
-enum RawError<'a, T>
-where
-    T: Clone + ToOwned,
-    <T as ToOwned>::Owned: Serialize + for<'b> Deserialize<'b>,
-{
-    NotFound {
-        status: Status<404>,
-        #[serde(rename = "type")]
-        ty: EntityType,
-        id: Cow<'a, T>,
-    },
-    Message {
-        status: u16,
-        message: Cow<'a, str>,
-    },
-}
-
 impl Serialize for ContentError {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         match self {
-            Self::ContentDigestNotFound(digest) => RawError::NotFound {
-                status: Status::<404>,
-                ty: EntityType::ContentDigest,
-                id: Cow::Borrowed(digest),
+            Self::ContentDigestNotFound(digest) => {
+                ProblemDetails::new(ProblemCode::ContentNotFound, self.to_string()).with_id(digest)
             }
-            .serialize(serializer),
-            Self::Message { status, message } => RawError::Message::<()> {
-                status: *status,
-                message: Cow::Borrowed(message),
+            Self::Message { status, message } => {
+                ProblemDetails::from_status(*status, message.as_str())
             }
-            .serialize(serializer),
         }
+        .serialize(serializer)
     }
 }
 
@@ -93,18 +85,15 @@ impl<'de> Deserialize<'de> for ContentError {
     where
         D: serde::Deserializer<'de>,
     {
-        match RawError::<String>::deserialize(deserializer)? {
-            RawError::NotFound { status: _, ty, id } => match ty {
-                EntityType::ContentDigest => Ok(Self::ContentDigestNotFound(
-                    AnyHash::from_str(&id).map_err(|_| {
-                        serde::de::Error::invalid_value(Unexpected::Str(&id), &"a valid digest")
-                    })?,
-                )),
+        let details = ProblemDetails::deserialize(deserializer)?;
+        Ok(match details.code {
+            ProblemCode::ContentNotFound => {
+                Self::ContentDigestNotFound(details.parse_id("a valid digest")?)
+            }
+            _ => Self::Message {
+                status: details.status,
+                message: details.into_detail(),
             },
-            RawError::Message { status, message } => Ok(Self::Message {
-                status,
-                message: message.into_owned(),
-            }),
-        }
+        })
     }
 }
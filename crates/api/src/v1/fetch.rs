@@ -1,10 +1,9 @@
 //! Types relating to the fetch API.
 
-use crate::Status;
+use super::problem::{ProblemCode, ProblemDetails};
 use indexmap::IndexMap;
-use serde::{de::Unexpected, Deserialize, Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use std::borrow::Cow;
-use std::str::FromStr;
 use thiserror::Error;
 use warg_crypto::hash::AnyHash;
 use warg_protocol::{
@@ -41,7 +40,7 @@ pub struct FetchLogsRequest<'a> {
 }
 
 /// Represents a fetch logs response.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FetchLogsResponse {
     /// Whether there are more records to fetch.
@@ -58,6 +57,42 @@ pub struct FetchLogsResponse {
     pub warnings: Vec<FetchWarning>,
 }
 
+/// The content type of a streamed fetch logs response.
+///
+/// A client requests a streamed response by including this content type in
+/// the `Accept` header of a fetch logs request; a registry that does not
+/// stream responses responds with a [`FetchLogsResponse`] instead.
+pub const FETCH_LOGS_STREAM_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Represents an item of a streamed fetch logs response.
+///
+/// A streamed response is a sequence of newline-delimited JSON items. The
+/// records of each log are streamed consecutively, and the last item of the
+/// response is either an `end` or an `error` item.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FetchLogsStreamItem {
+    /// An operator record appended since the last known operator record.
+    Operator(PublishedRecord),
+    /// A package record appended since the last known package record.
+    #[serde(rename_all = "camelCase")]
+    Package {
+        /// The log identifier of the package.
+        log_id: LogId,
+        /// The package record.
+        record: PublishedRecord,
+    },
+    /// A warning message.
+    Warning(FetchWarning),
+    /// The end of the response.
+    End {
+        /// Whether there are more records to fetch.
+        more: bool,
+    },
+    /// An error that ended the response.
+    Error(FetchError),
+}
+
 /// Represents a fetch package names request.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -116,66 +151,22 @@ impl FetchError {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-enum EntityType {
-    LogLength,
-    Log,
-    FetchToken,
-}
-
-#[derive(Serialize, Deserialize)]
-#[serde(untagged, rename_all = "camelCase")]
-enum RawError<'a, T>
-where
-    T: Clone + ToOwned,
-    <T as ToOwned>::Owned: Serialize + for<'b> Deserialize<'b>,
-{
-    CheckpointNotFound {
-        status: Status<404>,
-        #[serde(rename = "type")]
-        ty: EntityType,
-        id: RegistryLen,
-    },
-    NotFound {
-        status: Status<404>,
-        #[serde(rename = "type")]
-        ty: EntityType,
-        id: Cow<'a, T>,
-    },
-    Message {
-        status: u16,
-        message: Cow<'a, T>,
-    },
-}
-
 impl Serialize for FetchError {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let problem = |code| ProblemDetails::new(code, self.to_string());
         match self {
-            Self::CheckpointNotFound(log_length) => RawError::CheckpointNotFound::<RegistryLen> {
-                status: Status::<404>,
-                ty: EntityType::LogLength,
-                id: *log_length,
-            }
-            .serialize(serializer),
-            Self::LogNotFound(log_id) => RawError::NotFound {
-                status: Status::<404>,
-                ty: EntityType::Log,
-                id: Cow::Borrowed(log_id),
+            Self::CheckpointNotFound(log_length) => {
+                problem(ProblemCode::CheckpointNotFound).with_id(log_length)
             }
-            .serialize(serializer),
-            Self::FetchTokenNotFound(token) => RawError::NotFound {
-                status: Status::<404>,
-                ty: EntityType::FetchToken,
-                id: Cow::Borrowed(token),
+            Self::LogNotFound(log_id) => problem(ProblemCode::LogNotFound).with_id(log_id),
+            Self::FetchTokenNotFound(token) => {
+                problem(ProblemCode::FetchTokenNotFound).with_id(token)
             }
-            .serialize(serializer),
-            Self::Message { status, message } => RawError::Message {
-                status: *status,
-                message: Cow::Borrowed(message),
+            Self::Message { status, message } => {
+                ProblemDetails::from_status(*status, message.as_str())
             }
-            .serialize(serializer),
         }
+        .serialize(serializer)
     }
 }
 
@@ -184,26 +175,21 @@ impl<'de> Deserialize<'de> for FetchError {
     where
         D: serde::Deserializer<'de>,
     {
-        match RawError::<String>::deserialize(deserializer)? {
-            RawError::CheckpointNotFound { id, .. } => Ok(Self::CheckpointNotFound(id)),
-            RawError::NotFound { status: _, ty, id } => match ty {
-                EntityType::Log => Ok(Self::LogNotFound(
-                    AnyHash::from_str(&id)
-                        .map_err(|_| {
-                            serde::de::Error::invalid_value(Unexpected::Str(&id), &"a valid log id")
-                        })?
-                        .into(),
-                )),
-                EntityType::FetchToken => Ok(Self::FetchTokenNotFound(id.into_owned())),
-                _ => Err(serde::de::Error::invalid_value(
-                    Unexpected::Str(&id),
-                    &"a valid log length",
-                )),
+        let details = ProblemDetails::deserialize(deserializer)?;
+        Ok(match details.code {
+            ProblemCode::CheckpointNotFound => {
+                Self::CheckpointNotFound(details.parse_id("a valid log length")?)
+            }
+            ProblemCode::LogNotFound => {
+                Self::LogNotFound(details.parse_id::<AnyHash, _>("a valid log id")?.into())
+            }
+            ProblemCode::FetchTokenNotFound => {
+                Self::FetchTokenNotFound(details.parse_id("a fetch token")?)
+            }
+            _ => Self::Message {
+                status: details.status,
+                message: details.into_detail(),
             },
-            RawError::Message { status, message } => Ok(Self::Message {
-                status,
-                message: message.into_owned(),
-            }),
-        }
+        })
     }
 }
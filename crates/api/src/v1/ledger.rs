@@ -1,7 +1,7 @@
 //! Types relating to the ledger API.
 
+use super::problem::ProblemDetails;
 use serde::{Deserialize, Serialize, Serializer};
-use std::borrow::Cow;
 use thiserror::Error;
 use warg_crypto::hash::HashAlgorithm;
 use warg_protocol::registry::RegistryIndex;
@@ -80,20 +80,12 @@ impl LedgerError {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(untagged, rename_all = "camelCase")]
-enum RawError<'a> {
-    Message { status: u16, message: Cow<'a, str> },
-}
-
 impl Serialize for LedgerError {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         match self {
-            Self::Message { status, message } => RawError::Message {
-                status: *status,
-                message: Cow::Borrowed(message),
+            Self::Message { status, message } => {
+                ProblemDetails::from_status(*status, message.as_str()).serialize(serializer)
             }
-            .serialize(serializer),
         }
     }
 }
@@ -103,11 +95,10 @@ impl<'de> Deserialize<'de> for LedgerError {
     where
         D: serde::Deserializer<'de>,
     {
-        match RawError::deserialize(deserializer)? {
-            RawError::Message { status, message } => Ok(Self::Message {
-                status,
-                message: message.into_owned(),
-            }),
-        }
+        let details = ProblemDetails::deserialize(deserializer)?;
+        Ok(Self::Message {
+            status: details.status,
+            message: details.into_detail(),
+        })
     }
 }
@@ -1,12 +1,16 @@
 //! Types representing v1 of the Warg REST API.
 
+pub mod capabilities;
 pub mod content;
 pub mod fetch;
 pub mod ledger;
 pub mod monitor;
+pub mod namespace;
 pub mod package;
 pub mod paths;
+pub mod problem;
 pub mod proof;
+pub mod static_registry;
 
 use serde::{Deserialize, Serialize};
 
@@ -0,0 +1,101 @@
+//! Types relating to the namespace API.
+
+use super::problem::ProblemDetails;
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+
+/// Represents the state of a namespace of a registry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum NamespaceState {
+    /// The namespace is defined by the registry for its own packages.
+    Defined,
+    /// The namespace is imported from another registry.
+    #[serde(rename_all = "camelCase")]
+    Imported {
+        /// The registry the namespace is imported from.
+        registry: String,
+    },
+}
+
+/// Represents the suspension of a namespace.
+///
+/// Packages of a suspended namespace cannot be published and are hidden
+/// from listings, but their logs are preserved.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceSuspension {
+    /// The reason the namespace was suspended.
+    pub reason: String,
+    /// The time the namespace was suspended, in seconds since the Unix epoch.
+    pub suspended_at: u64,
+}
+
+/// Represents a namespace of a registry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Namespace {
+    /// The name of the namespace.
+    pub name: String,
+    /// The state of the namespace.
+    #[serde(flatten)]
+    pub state: NamespaceState,
+    /// The suspension of the namespace, if it is suspended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suspension: Option<NamespaceSuspension>,
+}
+
+/// Represents a response to a list namespaces request.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespacesResponse {
+    /// The namespaces of the registry, in the order they were defined or
+    /// imported.
+    pub namespaces: Vec<Namespace>,
+}
+
+/// Represents a namespace API error.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum NamespaceError {
+    /// An error with a message occurred.
+    #[error("{message}")]
+    Message {
+        /// The HTTP status code.
+        status: u16,
+        /// The error message
+        message: String,
+    },
+}
+
+impl NamespaceError {
+    /// Returns the HTTP status code of the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::Message { status, .. } => *status,
+        }
+    }
+}
+
+impl Serialize for NamespaceError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Message { status, message } => {
+                ProblemDetails::from_status(*status, message.as_str()).serialize(serializer)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NamespaceError {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let details = ProblemDetails::deserialize(deserializer)?;
+        Ok(Self::Message {
+            status: details.status,
+            message: details.into_detail(),
+        })
+    }
+}
@@ -1,16 +1,18 @@
 //! Types relating to the package API.
 
+use super::problem::{ProblemCode, ProblemDetails};
 pub use super::ContentSource;
-use crate::Status;
 use indexmap::IndexMap;
-use serde::{de::Unexpected, Deserialize, Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use std::borrow::Cow;
 use std::str::FromStr;
 use thiserror::Error;
-use warg_crypto::hash::AnyHash;
+use warg_crypto::{hash::AnyHash, signing};
 use warg_protocol::{
-    registry::{LogId, PackageName, RecordId, RegistryIndex},
-    ProtoEnvelopeBody,
+    metadata::ReleaseNotes,
+    package::{InviteRedemption, PackageInvite, PackageSnapshot, Permission, RecordApproval},
+    registry::{LogId, PackageName, RecordId, RegistryIndex, RegistryLen},
+    ProtoEnvelopeBody, SerdeEnvelope, Version, VersionReq,
 };
 
 /// Represents the supported kinds of content upload endpoints.
@@ -52,6 +54,34 @@ pub struct PublishRecordRequest<'a> {
     /// A registry may not support specifying content sources directly.
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub content_sources: IndexMap<AnyHash, Vec<ContentSource>>,
+    /// Whether to stage the record rather than publish it.
+    ///
+    /// A staged record is not published once its content is present; it is
+    /// published when committed and rejected when abandoned or when it
+    /// expires. Requires the registry to support the `stagedPublishes`
+    /// capability.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stage: bool,
+    /// The time, in seconds since the Unix epoch, until which the content
+    /// of the record is embargoed.
+    ///
+    /// The record is published as usual, but the registry does not serve
+    /// its content or list its releases until the embargo passes. Requires
+    /// the registry to support the `embargoedReleases` capability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embargo_until: Option<u64>,
+}
+
+/// Represents the query parameters of a request to await a package record.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwaitRecordQuery {
+    /// The maximum time, in milliseconds, to wait for the record to finish
+    /// processing.
+    ///
+    /// The registry may wait for less time than requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
 }
 
 /// Represents a package record API entity in a registry.
@@ -71,6 +101,12 @@ impl PackageRecord {
         match &self.state {
             PackageRecordState::Sourcing {
                 missing_content, ..
+            }
+            | PackageRecordState::Staged {
+                missing_content, ..
+            }
+            | PackageRecordState::AwaitingApproval {
+                missing_content, ..
             } => itertools::Either::Left(missing_content.iter()),
             _ => itertools::Either::Right(std::iter::empty()),
         }
@@ -79,6 +115,8 @@ impl PackageRecord {
 
 /// Represents a package record in one of the following states:
 /// * `sourcing` - The record is sourcing content.
+/// * `staged` - The record is staged and awaits a commit.
+/// * `awaitingApproval` - The record awaits approval by another key.
 /// * `processing` - The record is being processed.
 /// * `rejected` - The record was rejected.
 /// * `published` - The record was published to the log.
@@ -92,9 +130,38 @@ pub enum PackageRecordState {
         /// The digests of the missing content.
         missing_content: IndexMap<AnyHash, MissingContent>,
     },
+    /// The package record is staged and awaits a commit.
+    #[serde(rename_all = "camelCase")]
+    Staged {
+        /// The digests of the missing content.
+        ///
+        /// A staged record cannot be committed until all of its content is
+        /// present.
+        #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+        missing_content: IndexMap<AnyHash, MissingContent>,
+        /// The time, in seconds since the Unix epoch, at which the record
+        /// is rejected if it has not been committed.
+        expires_at: u64,
+    },
+    /// The package record awaits approval by a key other than the key that
+    /// signed it.
+    #[serde(rename_all = "camelCase")]
+    AwaitingApproval {
+        /// The digests of the missing content.
+        ///
+        /// An approved record is not processed until all of its content is
+        /// present.
+        #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+        missing_content: IndexMap<AnyHash, MissingContent>,
+    },
     /// The package record is processing.
     #[serde(rename_all = "camelCase")]
-    Processing,
+    Processing {
+        /// A hint of how long, in milliseconds, a client should wait before
+        /// checking the state of the record again.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_ms: Option<u64>,
+    },
     /// The package record is rejected.
     #[serde(rename_all = "camelCase")]
     Rejected {
@@ -109,6 +176,288 @@ pub enum PackageRecordState {
     },
 }
 
+/// Represents the metadata of a package release.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseMetadata {
+    /// The version of the release.
+    pub version: Version,
+    /// Whether the release has been yanked.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub yanked: bool,
+    /// The normalized SPDX license expression of the released content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// The release notes attached to the release.
+    ///
+    /// If release notes were attached more than once, the latest are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_notes: Option<ReleaseNotes>,
+}
+
+/// Represents a response to a package metadata request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageMetadataResponse {
+    /// The metadata of the releases of the package, in package log order.
+    pub releases: Vec<ReleaseMetadata>,
+    /// The advisory for the package, if its name was flagged as suspicious.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub advisory: Option<PackageAdvisory>,
+}
+
+/// Represents the query parameters of a package snapshot request.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageSnapshotQuery {
+    /// The log length of the checkpoint to take the snapshot at.
+    ///
+    /// Defaults to the latest checkpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_length: Option<RegistryLen>,
+}
+
+/// Represents a response to a package snapshot request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageSnapshotResponse {
+    /// The snapshot of the package log, signed by the registry operator.
+    pub snapshot: SerdeEnvelope<PackageSnapshot>,
+    /// The fetch token of the head record of the snapshot.
+    ///
+    /// The token may be used to fetch the records of the package log that
+    /// follow the snapshot.
+    pub fetch_token: String,
+}
+
+/// Represents an advisory that a package name is suspiciously similar to the
+/// name of another package (e.g. a possible typosquat).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageAdvisory {
+    /// The package the package name is similar to.
+    pub similar_to: PackageName,
+    /// A description of the similarity.
+    pub reason: String,
+}
+
+/// Represents the severity of a vulnerability advisory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AdvisorySeverity {
+    /// The vulnerability has a low severity.
+    Low,
+    /// The vulnerability has a medium severity.
+    Medium,
+    /// The vulnerability has a high severity.
+    High,
+    /// The vulnerability has a critical severity.
+    Critical,
+}
+
+impl AdvisorySeverity {
+    /// Gets the string representation of the severity.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl std::fmt::Display for AdvisorySeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AdvisorySeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            _ => Err(format!(
+                "invalid advisory severity `{s}`: expected `low`, `medium`, `high`, or `critical`"
+            )),
+        }
+    }
+}
+
+/// Represents a vulnerability advisory for a range of versions of a package.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VulnerabilityAdvisory {
+    /// The identifier of the advisory (e.g. a CVE or GHSA identifier).
+    pub id: String,
+    /// The versions of the package affected by the vulnerability.
+    pub versions: VersionReq,
+    /// The severity of the vulnerability.
+    pub severity: AdvisorySeverity,
+    /// A summary of the vulnerability.
+    pub summary: String,
+    /// A URL with more information about the vulnerability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl VulnerabilityAdvisory {
+    /// Determines if the advisory applies to the given version.
+    pub fn affects(&self, version: &Version) -> bool {
+        self.versions.matches(version)
+    }
+}
+
+/// Represents a response to a package advisories request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageAdvisoriesResponse {
+    /// The vulnerability advisories of the package, in publication order.
+    pub advisories: Vec<VulnerabilityAdvisory>,
+}
+
+/// Represents the kind of a change to the permissions of a key of a
+/// package log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyEventKind {
+    /// The key initialized the package log with all permissions.
+    Init,
+    /// The key was granted permissions.
+    Grant,
+    /// Permissions of the key were revoked.
+    Revoke,
+}
+
+/// Represents a change to the permissions of a key of a package log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyEvent {
+    /// The kind of the change.
+    pub kind: KeyEventKind,
+    /// The ID of the key whose permissions changed.
+    pub key_id: signing::KeyID,
+    /// The key whose permissions changed; not present for revocations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<signing::PublicKey>,
+    /// The permissions that were granted or revoked.
+    pub permissions: Vec<Permission>,
+    /// The ID of the key that signed the record of the change.
+    pub signed_by: signing::KeyID,
+    /// The ID of the record of the change.
+    pub record_id: RecordId,
+    /// The registry log index of the record of the change.
+    pub registry_index: RegistryIndex,
+}
+
+/// Represents a response to a package key history request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageKeyHistoryResponse {
+    /// The changes to the permissions of the keys of the package log, in
+    /// package log order.
+    pub events: Vec<KeyEvent>,
+}
+
+/// Represents a request to create an invite of a package log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInviteRequest {
+    /// The invite, signed by a key of the package log that holds the
+    /// permissions of the invite.
+    pub invite: SerdeEnvelope<PackageInvite>,
+}
+
+/// Represents a request to redeem an invite of a package log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedeemInviteRequest {
+    /// The redemption, signed by the redeeming key.
+    pub redemption: SerdeEnvelope<InviteRedemption>,
+}
+
+/// Represents an invite of a package log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageInviteInfo {
+    /// The signed invite.
+    pub invite: SerdeEnvelope<PackageInvite>,
+    /// The redemption of the invite, if it was redeemed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redemption: Option<SerdeEnvelope<InviteRedemption>>,
+}
+
+/// Represents a response to a package invites request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageInvitesResponse {
+    /// The invites of the package log that have not expired or been
+    /// granted, in creation order.
+    pub invites: Vec<PackageInviteInfo>,
+}
+
+/// Represents a request to approve a package record awaiting approval.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveRecordRequest {
+    /// The approval, signed by the approving key.
+    pub approval: SerdeEnvelope<RecordApproval>,
+}
+
+/// Represents a filter of the states of listed package records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordStatusFilter {
+    /// Records that are sourcing content, staged, awaiting approval, or
+    /// processing.
+    Pending,
+    /// Records that were rejected.
+    Rejected,
+    /// Records that were published to the log.
+    Published,
+}
+
+impl RecordStatusFilter {
+    /// Determines if the given record state matches the filter.
+    pub fn matches(&self, state: &PackageRecordState) -> bool {
+        match state {
+            PackageRecordState::Rejected { .. } => *self == Self::Rejected,
+            PackageRecordState::Published { .. } => *self == Self::Published,
+            _ => *self == Self::Pending,
+        }
+    }
+}
+
+/// Represents the query parameters of a request to list package records.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRecordsQuery {
+    /// The state of the records to list.
+    ///
+    /// Defaults to records in any state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<RecordStatusFilter>,
+    /// The maximum number of records to list.
+    ///
+    /// The registry may return fewer records than requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u16>,
+}
+
+/// Represents a response to a package records request.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageRecordsResponse {
+    /// The most recently submitted records of the package log, in
+    /// submission order.
+    pub records: Vec<PackageRecord>,
+}
+
 /// Represents a package API error.
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -122,6 +471,15 @@ pub enum PackageError {
     /// The record is not currently sourcing content.
     #[error("the record is not currently sourcing content")]
     RecordNotSourcing,
+    /// The provided record is not a staged publish.
+    #[error("record `{0}` is not a staged publish")]
+    StagedPublishNotFound(RecordId),
+    /// The provided record is not awaiting approval.
+    #[error("record `{0}` is not awaiting approval")]
+    PendingApprovalNotFound(RecordId),
+    /// The provided invite was not found.
+    #[error("invite `{0}` was not found")]
+    InviteNotFound(String),
     /// The provided package's namespace was not found in the operator log.
     #[error("namespace `{0}` is not defined on the registry")]
     NamespaceNotDefined(String),
@@ -155,7 +513,12 @@ impl PackageError {
     pub fn status(&self) -> u16 {
         match self {
             Self::Unauthorized { .. } => 401,
-            Self::LogNotFound(_) | Self::RecordNotFound(_) | Self::NamespaceNotDefined(_) => 404,
+            Self::LogNotFound(_)
+            | Self::RecordNotFound(_)
+            | Self::StagedPublishNotFound(_)
+            | Self::PendingApprovalNotFound(_)
+            | Self::InviteNotFound(_)
+            | Self::NamespaceNotDefined(_) => 404,
             Self::NamespaceImported(_) | Self::ConflictPendingPublish(_) => 409,
             Self::RecordNotSourcing => 405,
             Self::Rejection(_) => 422,
@@ -165,114 +528,47 @@ impl PackageError {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-enum EntityType {
-    Log,
-    Record,
-    Namespace,
-    NamespaceImport,
-    Name,
-}
-
-#[derive(Serialize, Deserialize)]
-#[serde(untagged, rename_all = "camelCase")]
-enum RawError<'a, T>
-where
-    T: Clone + ToOwned,
-    <T as ToOwned>::Owned: Serialize + for<'b> Deserialize<'b>,
-{
-    Unauthorized {
-        status: Status<401>,
-        message: Cow<'a, str>,
-    },
-    NotFound {
-        status: Status<404>,
-        #[serde(rename = "type")]
-        ty: EntityType,
-        id: Cow<'a, T>,
-    },
-    Conflict {
-        status: Status<409>,
-        #[serde(rename = "type")]
-        ty: EntityType,
-        id: Cow<'a, T>,
-    },
-    RecordNotSourcing {
-        status: Status<405>,
-    },
-    Rejection {
-        status: Status<422>,
-        message: Cow<'a, str>,
-    },
-    NotSupported {
-        status: Status<501>,
-        message: Cow<'a, str>,
-    },
-    Message {
-        status: u16,
-        message: Cow<'a, str>,
-    },
-}
-
 impl Serialize for PackageError {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let problem = |code| ProblemDetails::new(code, self.to_string());
         match self {
-            Self::Unauthorized(message) => RawError::Unauthorized::<()> {
-                status: Status::<401>,
-                message: Cow::Borrowed(message),
+            Self::Unauthorized(message) => {
+                ProblemDetails::new(ProblemCode::Unauthorized, message.as_str())
             }
-            .serialize(serializer),
-            Self::LogNotFound(log_id) => RawError::NotFound {
-                status: Status::<404>,
-                ty: EntityType::Log,
-                id: Cow::Borrowed(log_id),
+            Self::LogNotFound(log_id) => problem(ProblemCode::LogNotFound).with_id(log_id),
+            Self::RecordNotFound(record_id) => {
+                problem(ProblemCode::RecordNotFound).with_id(record_id)
             }
-            .serialize(serializer),
-            Self::RecordNotFound(record_id) => RawError::NotFound {
-                status: Status::<404>,
-                ty: EntityType::Record,
-                id: Cow::Borrowed(record_id),
+            Self::StagedPublishNotFound(record_id) => {
+                problem(ProblemCode::StagedPublishNotFound).with_id(record_id)
             }
-            .serialize(serializer),
-            Self::NamespaceNotDefined(namespace) => RawError::NotFound {
-                status: Status::<404>,
-                ty: EntityType::Namespace,
-                id: Cow::Borrowed(namespace),
+            Self::PendingApprovalNotFound(record_id) => {
+                problem(ProblemCode::PendingApprovalNotFound).with_id(record_id)
             }
-            .serialize(serializer),
-            Self::NamespaceImported(namespace) => RawError::Conflict {
-                status: Status::<409>,
-                ty: EntityType::NamespaceImport,
-                id: Cow::Borrowed(namespace),
+            Self::InviteNotFound(invite_id) => {
+                problem(ProblemCode::InviteNotFound).with_id(invite_id)
             }
-            .serialize(serializer),
-            Self::ConflictPendingPublish(record_id) => RawError::Conflict {
-                status: Status::<409>,
-                ty: EntityType::Record,
-                id: Cow::Borrowed(record_id),
+            Self::NamespaceNotDefined(namespace) => {
+                problem(ProblemCode::NamespaceNotDefined).with_id(namespace)
             }
-            .serialize(serializer),
-            Self::RecordNotSourcing => RawError::RecordNotSourcing::<()> {
-                status: Status::<405>,
+            Self::NamespaceImported(namespace) => {
+                problem(ProblemCode::NamespaceImported).with_id(namespace)
             }
-            .serialize(serializer),
-            Self::Rejection(message) => RawError::Rejection::<()> {
-                status: Status::<422>,
-                message: Cow::Borrowed(message),
+            Self::ConflictPendingPublish(record_id) => {
+                problem(ProblemCode::ConflictPendingPublish).with_id(record_id)
             }
-            .serialize(serializer),
-            Self::NotSupported(message) => RawError::NotSupported::<()> {
-                status: Status::<501>,
-                message: Cow::Borrowed(message),
+            Self::RecordNotSourcing => problem(ProblemCode::RecordNotSourcing),
+            Self::Rejection(message) => {
+                ProblemDetails::new(ProblemCode::Rejected, message.as_str())
             }
-            .serialize(serializer),
-            Self::Message { status, message } => RawError::Message::<()> {
-                status: *status,
-                message: Cow::Borrowed(message),
+            Self::NotSupported(message) => {
+                ProblemDetails::new(ProblemCode::NotSupported, message.as_str())
+            }
+            Self::Message { status, message } => {
+                ProblemDetails::from_status(*status, message.as_str())
             }
-            .serialize(serializer),
         }
+        .serialize(serializer)
     }
 }
 
@@ -281,60 +577,37 @@ impl<'de> Deserialize<'de> for PackageError {
     where
         D: serde::Deserializer<'de>,
     {
-        match RawError::<String>::deserialize(deserializer)? {
-            RawError::Unauthorized { status: _, message } => {
-                Ok(Self::Unauthorized(message.into_owned()))
+        let details = ProblemDetails::deserialize(deserializer)?;
+        let record_id = |details: &ProblemDetails| -> Result<RecordId, D::Error> {
+            Ok(details.parse_id::<AnyHash, _>("a valid record id")?.into())
+        };
+        Ok(match details.code {
+            ProblemCode::Unauthorized => Self::Unauthorized(details.into_detail()),
+            ProblemCode::LogNotFound => {
+                Self::LogNotFound(details.parse_id::<AnyHash, _>("a valid log id")?.into())
             }
-            RawError::NotFound { status: _, ty, id } => match ty {
-                EntityType::Log => Ok(Self::LogNotFound(
-                    AnyHash::from_str(&id)
-                        .map_err(|_| {
-                            serde::de::Error::invalid_value(Unexpected::Str(&id), &"a valid log id")
-                        })?
-                        .into(),
-                )),
-                EntityType::Record => Ok(Self::RecordNotFound(
-                    AnyHash::from_str(&id)
-                        .map_err(|_| {
-                            serde::de::Error::invalid_value(
-                                Unexpected::Str(&id),
-                                &"a valid record id",
-                            )
-                        })?
-                        .into(),
-                )),
-                EntityType::Namespace => Ok(Self::NamespaceNotDefined(id.into_owned())),
-                _ => Err(serde::de::Error::invalid_value(
-                    Unexpected::Enum,
-                    &"a valid entity type",
-                )),
-            },
-            RawError::Conflict { status: _, ty, id } => match ty {
-                EntityType::NamespaceImport => Ok(Self::NamespaceImported(id.into_owned())),
-                EntityType::Record => Ok(Self::ConflictPendingPublish(
-                    AnyHash::from_str(&id)
-                        .map_err(|_| {
-                            serde::de::Error::invalid_value(
-                                Unexpected::Str(&id),
-                                &"a valid record id",
-                            )
-                        })?
-                        .into(),
-                )),
-                _ => Err(serde::de::Error::invalid_value(
-                    Unexpected::Enum,
-                    &"a valid entity type",
-                )),
-            },
-            RawError::RecordNotSourcing { status: _ } => Ok(Self::RecordNotSourcing),
-            RawError::Rejection { status: _, message } => Ok(Self::Rejection(message.into_owned())),
-            RawError::NotSupported { status: _, message } => {
-                Ok(Self::NotSupported(message.into_owned()))
+            ProblemCode::RecordNotFound => Self::RecordNotFound(record_id(&details)?),
+            ProblemCode::StagedPublishNotFound => Self::StagedPublishNotFound(record_id(&details)?),
+            ProblemCode::PendingApprovalNotFound => {
+                Self::PendingApprovalNotFound(record_id(&details)?)
             }
-            RawError::Message { status, message } => Ok(Self::Message {
-                status,
-                message: message.into_owned(),
-            }),
-        }
+            ProblemCode::InviteNotFound => Self::InviteNotFound(details.parse_id("an invite id")?),
+            ProblemCode::NamespaceNotDefined => {
+                Self::NamespaceNotDefined(details.parse_id("a namespace")?)
+            }
+            ProblemCode::NamespaceImported => {
+                Self::NamespaceImported(details.parse_id("a namespace")?)
+            }
+            ProblemCode::ConflictPendingPublish => {
+                Self::ConflictPendingPublish(record_id(&details)?)
+            }
+            ProblemCode::RecordNotSourcing => Self::RecordNotSourcing,
+            ProblemCode::Rejected => Self::Rejection(details.into_detail()),
+            ProblemCode::NotSupported => Self::NotSupported(details.into_detail()),
+            _ => Self::Message {
+                status: details.status,
+                message: details.into_detail(),
+            },
+        })
     }
 }
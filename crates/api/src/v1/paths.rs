@@ -1,7 +1,12 @@
 //! The paths of the Warg REST API.
 
 use warg_crypto::hash::AnyHash;
-use warg_protocol::registry::{LogId, RecordId};
+use warg_protocol::registry::{LogId, PackageName, RecordId};
+
+/// The path of the capabilities API.
+pub fn capabilities() -> &'static str {
+    "v1/capabilities"
+}
 
 /// The path of the "fetch logs" API.
 pub fn fetch_logs() -> &'static str {
@@ -23,6 +28,11 @@ pub fn ledger_sources() -> &'static str {
     "v1/ledger"
 }
 
+/// The path of the list namespaces API.
+pub fn namespaces() -> &'static str {
+    "v1/namespace"
+}
+
 /// The path of the "publish package record" API.
 pub fn publish_package_record(log_id: &LogId) -> String {
     format!("v1/package/{log_id}/record")
@@ -33,11 +43,71 @@ pub fn content_sources(digest: &AnyHash) -> String {
     format!("v1/content/{digest}")
 }
 
+/// The path to request the custom sections of content.
+pub fn content_sections(digest: &AnyHash) -> String {
+    format!("v1/content/{digest}/sections")
+}
+
+/// The path for listing the records of a package log.
+pub fn package_records(log_id: &LogId) -> String {
+    format!("v1/package/{log_id}/records")
+}
+
 /// The path for a package record.
 pub fn package_record(log_id: &LogId, record_id: &RecordId) -> String {
     format!("v1/package/{log_id}/record/{record_id}")
 }
 
+/// The path for awaiting the processing of a package record.
+pub fn package_record_await(log_id: &LogId, record_id: &RecordId) -> String {
+    format!("v1/package/{log_id}/record/{record_id}/await")
+}
+
+/// The path for committing a staged package record.
+pub fn package_record_commit(log_id: &LogId, record_id: &RecordId) -> String {
+    format!("v1/package/{log_id}/record/{record_id}/commit")
+}
+
+/// The path for abandoning a staged package record.
+pub fn package_record_abandon(log_id: &LogId, record_id: &RecordId) -> String {
+    format!("v1/package/{log_id}/record/{record_id}/abandon")
+}
+
+/// The path for approving a package record awaiting approval.
+pub fn package_record_approve(log_id: &LogId, record_id: &RecordId) -> String {
+    format!("v1/package/{log_id}/record/{record_id}/approve")
+}
+
+/// The path for the metadata of a package.
+pub fn package_metadata(log_id: &LogId) -> String {
+    format!("v1/package/{log_id}/metadata")
+}
+
+/// The path for the state snapshot of a package log.
+pub fn package_snapshot(log_id: &LogId) -> String {
+    format!("v1/package/{log_id}/snapshot")
+}
+
+/// The path for the key history of a package log.
+pub fn package_keys(log_id: &LogId) -> String {
+    format!("v1/package/{log_id}/keys")
+}
+
+/// The path for the invites of a package log.
+pub fn package_invites(log_id: &LogId) -> String {
+    format!("v1/package/{log_id}/invites")
+}
+
+/// The path for redeeming an invite of a package log.
+pub fn package_invite_redeem(log_id: &LogId, invite_id: &str) -> String {
+    format!("v1/package/{log_id}/invites/{invite_id}/redeem")
+}
+
+/// The path for the vulnerability advisories of a package.
+pub fn package_advisories(log_id: &LogId) -> String {
+    format!("v1/package/{log_id}/advisories")
+}
+
 /// The path for proving checkpoint consistency.
 pub fn prove_consistency() -> &'static str {
     "v1/proof/consistency"
@@ -48,7 +118,21 @@ pub fn prove_inclusion() -> &'static str {
     "v1/proof/inclusion"
 }
 
+/// The path for proving checkpoint inclusion with a compact multi-proof.
+pub fn prove_multi_inclusion() -> &'static str {
+    "v1/proof/multi"
+}
+
 /// The path for verifying a checkpoint.
 pub fn verify_checkpoint() -> &'static str {
     "v1/verify/checkpoint"
 }
+
+/// The path of the latest version badge for a package.
+pub fn package_badge(name: &PackageName) -> String {
+    format!(
+        "v1/badge/{namespace}/{name}.svg",
+        namespace = name.namespace(),
+        name = name.name()
+    )
+}
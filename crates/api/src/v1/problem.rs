@@ -0,0 +1,312 @@
+//! Types relating to the problem details of error responses.
+//!
+//! Every error response of the API has a [`ProblemDetails`] body of content
+//! type `application/problem+json`, as described by [RFC 9457], with a
+//! stable machine-readable [`ProblemCode`].
+//!
+//! A registry that enforces request quotas or sheds load responds with a
+//! `429 Too Many Requests` or `503 Service Unavailable` status and a problem
+//! details body with a retry hint.
+//!
+//! [RFC 9457]: https://www.rfc-editor.org/rfc/rfc9457
+
+use serde::{de::Unexpected, Deserialize, Serialize};
+use std::str::FromStr;
+
+/// The content type of a problem details response body.
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Represents the machine-readable code of a problem.
+///
+/// Codes are stable: a code is never repurposed once published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProblemCode {
+    /// The request was malformed.
+    BadRequest,
+    /// The request was not authorized.
+    Unauthorized,
+    /// The requested resource was not found.
+    NotFound,
+    /// The request conflicts with the state of the registry.
+    Conflict,
+    /// The request was well-formed but could not be processed.
+    UnprocessableContent,
+    /// The registry failed to process the request.
+    InternalError,
+    /// The requested functionality is not implemented by the registry.
+    NotImplemented,
+    /// An error without a more specific code.
+    Error,
+    /// The requested checkpoint was not found.
+    CheckpointNotFound,
+    /// The requested log was not found.
+    LogNotFound,
+    /// The requested log leaf was not found.
+    LeafNotFound,
+    /// The provided fetch token was not found.
+    FetchTokenNotFound,
+    /// The requested content was not found.
+    ContentNotFound,
+    /// The requested record was not found.
+    RecordNotFound,
+    /// The requested record is not a staged publish.
+    StagedPublishNotFound,
+    /// The requested record is not awaiting approval.
+    PendingApprovalNotFound,
+    /// The requested invite was not found.
+    InviteNotFound,
+    /// The namespace of the package is not defined by the registry.
+    NamespaceNotDefined,
+    /// The namespace of the package is imported from another registry.
+    NamespaceImported,
+    /// The record conflicts with a pending publish.
+    ConflictPendingPublish,
+    /// The record is not currently sourcing content.
+    RecordNotSourcing,
+    /// The operation was rejected by the registry's policy.
+    Rejected,
+    /// The operation is not supported by the registry.
+    NotSupported,
+    /// The inclusion of a package log could not be proven.
+    PackageLogNotIncluded,
+    /// The provided root of an inclusion proof was incorrect.
+    IncorrectProof,
+    /// The proofs could not be bundled.
+    BundleFailure,
+    /// The client exceeded its request quota.
+    QuotaExceeded,
+    /// The registry is shedding load.
+    Overloaded,
+    /// A code that is not known to this version of the API.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ProblemCode {
+    /// Gets the generic code of problems with the given HTTP status code.
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            400 => Self::BadRequest,
+            401 => Self::Unauthorized,
+            404 => Self::NotFound,
+            409 => Self::Conflict,
+            422 => Self::UnprocessableContent,
+            500 => Self::InternalError,
+            501 => Self::NotImplemented,
+            _ => Self::Error,
+        }
+    }
+
+    /// Gets the string form of the code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BadRequest => "bad-request",
+            Self::Unauthorized => "unauthorized",
+            Self::NotFound => "not-found",
+            Self::Conflict => "conflict",
+            Self::UnprocessableContent => "unprocessable-content",
+            Self::InternalError => "internal-error",
+            Self::NotImplemented => "not-implemented",
+            Self::Error => "error",
+            Self::CheckpointNotFound => "checkpoint-not-found",
+            Self::LogNotFound => "log-not-found",
+            Self::LeafNotFound => "leaf-not-found",
+            Self::FetchTokenNotFound => "fetch-token-not-found",
+            Self::ContentNotFound => "content-not-found",
+            Self::RecordNotFound => "record-not-found",
+            Self::StagedPublishNotFound => "staged-publish-not-found",
+            Self::PendingApprovalNotFound => "pending-approval-not-found",
+            Self::InviteNotFound => "invite-not-found",
+            Self::NamespaceNotDefined => "namespace-not-defined",
+            Self::NamespaceImported => "namespace-imported",
+            Self::ConflictPendingPublish => "conflict-pending-publish",
+            Self::RecordNotSourcing => "record-not-sourcing",
+            Self::Rejected => "rejected",
+            Self::NotSupported => "not-supported",
+            Self::PackageLogNotIncluded => "package-log-not-included",
+            Self::IncorrectProof => "incorrect-proof",
+            Self::BundleFailure => "bundle-failure",
+            Self::QuotaExceeded => "quota-exceeded",
+            Self::Overloaded => "overloaded",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Gets the HTTP status code of responses with the problem.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::BadRequest => 400,
+            Self::Unauthorized => 401,
+            Self::NotFound
+            | Self::CheckpointNotFound
+            | Self::LogNotFound
+            | Self::LeafNotFound
+            | Self::FetchTokenNotFound
+            | Self::ContentNotFound
+            | Self::RecordNotFound
+            | Self::StagedPublishNotFound
+            | Self::PendingApprovalNotFound
+            | Self::InviteNotFound
+            | Self::NamespaceNotDefined => 404,
+            Self::RecordNotSourcing => 405,
+            Self::Conflict | Self::NamespaceImported | Self::ConflictPendingPublish => 409,
+            Self::UnprocessableContent
+            | Self::Rejected
+            | Self::PackageLogNotIncluded
+            | Self::IncorrectProof
+            | Self::BundleFailure => 422,
+            Self::QuotaExceeded => 429,
+            Self::InternalError | Self::Error => 500,
+            Self::NotImplemented | Self::NotSupported => 501,
+            Self::Overloaded | Self::Unknown => 503,
+        }
+    }
+
+    /// Gets the URI that identifies the type of the problem.
+    pub fn type_uri(&self) -> String {
+        format!("https://warg.io/problems/{code}", code = self.as_str())
+    }
+
+    /// Gets a short summary of the problem.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::BadRequest => "Bad request",
+            Self::Unauthorized => "Unauthorized",
+            Self::NotFound => "Not found",
+            Self::Conflict => "Conflict",
+            Self::UnprocessableContent => "Unprocessable content",
+            Self::InternalError => "Internal error",
+            Self::NotImplemented => "Not implemented",
+            Self::Error => "Error",
+            Self::CheckpointNotFound => "Checkpoint not found",
+            Self::LogNotFound => "Log not found",
+            Self::LeafNotFound => "Log leaf not found",
+            Self::FetchTokenNotFound => "Fetch token not found",
+            Self::ContentNotFound => "Content not found",
+            Self::RecordNotFound => "Record not found",
+            Self::StagedPublishNotFound => "Staged publish not found",
+            Self::PendingApprovalNotFound => "Pending approval not found",
+            Self::InviteNotFound => "Invite not found",
+            Self::NamespaceNotDefined => "Namespace not defined",
+            Self::NamespaceImported => "Namespace imported",
+            Self::ConflictPendingPublish => "Conflicting pending publish",
+            Self::RecordNotSourcing => "Record not sourcing content",
+            Self::Rejected => "Rejected by policy",
+            Self::NotSupported => "Operation not supported",
+            Self::PackageLogNotIncluded => "Package log not included",
+            Self::IncorrectProof => "Incorrect proof",
+            Self::BundleFailure => "Proof bundling failed",
+            Self::QuotaExceeded => "Request quota exceeded",
+            Self::Overloaded => "Registry overloaded",
+            Self::Unknown => "Registry unavailable",
+        }
+    }
+}
+
+/// Represents the request quota of a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quota {
+    /// The number of requests allowed in each window.
+    pub limit: u64,
+    /// The length of the window, in milliseconds.
+    pub window_ms: u64,
+}
+
+/// Represents the problem details of an error response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemDetails {
+    /// The URI that identifies the type of the problem.
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    /// A short summary of the problem.
+    pub title: String,
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// An explanation of this occurrence of the problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// The machine-readable code of the problem.
+    pub code: ProblemCode,
+    /// The number of milliseconds the client should wait before retrying
+    /// the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    /// The quota that was exceeded, for a problem with code
+    /// [`ProblemCode::QuotaExceeded`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<Quota>,
+    /// The identifier of the entity the problem concerns, such as the log
+    /// of a [`ProblemCode::LogNotFound`] problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The root that was given, for a problem with code
+    /// [`ProblemCode::IncorrectProof`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// The root that was found, for a problem with code
+    /// [`ProblemCode::IncorrectProof`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub found: Option<String>,
+}
+
+impl ProblemDetails {
+    /// Creates the problem details of the given problem code.
+    pub fn new(code: ProblemCode, detail: impl Into<String>) -> Self {
+        Self {
+            type_uri: code.type_uri(),
+            title: code.title().to_string(),
+            status: code.status(),
+            detail: Some(detail.into()),
+            code,
+            retry_after_ms: None,
+            quota: None,
+            id: None,
+            root: None,
+            found: None,
+        }
+    }
+
+    /// Creates the problem details of an error with the given HTTP status
+    /// code and message.
+    ///
+    /// The problem has the generic code of the status code.
+    pub fn from_status(status: u16, detail: impl Into<String>) -> Self {
+        let mut details = Self::new(ProblemCode::from_status(status), detail);
+        details.status = status;
+        details
+    }
+
+    /// Sets the identifier of the entity the problem concerns.
+    pub fn with_id(mut self, id: impl ToString) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Gets the explanation of the problem, falling back to its title.
+    pub fn into_detail(self) -> String {
+        self.detail.unwrap_or(self.title)
+    }
+
+    /// Parses the identifier of the entity the problem concerns.
+    ///
+    /// This is used when deserializing API errors from their problem
+    /// details.
+    pub(crate) fn parse_id<T: FromStr, E: serde::de::Error>(&self, expected: &str) -> Result<T, E> {
+        parse_member("id", self.id.as_deref(), expected)
+    }
+}
+
+/// Parses an extension member of problem details.
+pub(crate) fn parse_member<T: FromStr, E: serde::de::Error>(
+    name: &'static str,
+    value: Option<&str>,
+    expected: &str,
+) -> Result<T, E> {
+    let value = value.ok_or_else(|| E::missing_field(name))?;
+    value
+        .parse()
+        .map_err(|_| E::invalid_value(Unexpected::Str(value), &expected))
+}
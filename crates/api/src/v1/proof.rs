@@ -1,9 +1,8 @@
 //! Types relating to the proof API.
 
-use crate::Status;
+use super::problem::{parse_member, ProblemCode, ProblemDetails};
 use serde::{Deserialize, Serialize, Serializer};
 use serde_with::{base64::Base64, serde_as};
-use std::borrow::Cow;
 use thiserror::Error;
 use warg_crypto::hash::AnyHash;
 use warg_protocol::registry::{LogId, RegistryIndex, RegistryLen};
@@ -51,6 +50,22 @@ pub struct InclusionResponse {
     pub map: Vec<u8>,
 }
 
+/// Represents a multi-proof inclusion response.
+///
+/// The request of a multi-proof is an [`InclusionRequest`].
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiProofResponse {
+    /// The bytes of the log inclusion proof bundle.
+    #[serde_as(as = "Base64")]
+    pub log: Vec<u8>,
+    /// The bytes of the map multi-proof, which includes each peer hash
+    /// shared by the map inclusion proofs once.
+    #[serde_as(as = "Base64")]
+    pub map: Vec<u8>,
+}
+
 /// Represents a proof API error.
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -98,91 +113,33 @@ impl ProofError {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-enum EntityType {
-    LogLength,
-    Leaf,
-}
-
-#[derive(Serialize, Deserialize)]
-#[serde(tag = "reason", rename_all = "camelCase")]
-enum BundleError<'a> {
-    PackageNotIncluded {
-        log_id: Cow<'a, LogId>,
-    },
-    IncorrectProof {
-        root: Cow<'a, AnyHash>,
-        found: Cow<'a, AnyHash>,
-    },
-    Failure {
-        message: Cow<'a, str>,
-    },
-}
-
-#[derive(Serialize, Deserialize)]
-#[serde(untagged, rename_all = "camelCase")]
-enum RawError<'a> {
-    NotFound {
-        status: Status<404>,
-        #[serde(rename = "type")]
-        ty: EntityType,
-        id: RegistryIndex,
-    },
-    BundleError {
-        status: Status<422>,
-        #[serde(flatten)]
-        error: BundleError<'a>,
-    },
-    Message {
-        status: u16,
-        message: Cow<'a, str>,
-    },
-}
-
 impl Serialize for ProofError {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let problem = |code| ProblemDetails::new(code, self.to_string());
         match self {
-            Self::CheckpointNotFound(log_length) => RawError::NotFound {
-                status: Status::<404>,
-                ty: EntityType::LogLength,
-                id: *log_length,
+            Self::CheckpointNotFound(log_length) => {
+                problem(ProblemCode::CheckpointNotFound).with_id(log_length)
             }
-            .serialize(serializer),
-            Self::LeafNotFound(leaf_index) => RawError::NotFound {
-                status: Status::<404>,
-                ty: EntityType::Leaf,
-                id: *leaf_index,
+            Self::LeafNotFound(leaf_index) => {
+                problem(ProblemCode::LeafNotFound).with_id(leaf_index)
             }
-            .serialize(serializer),
-            Self::PackageLogNotIncluded(log_id) => RawError::BundleError {
-                status: Status::<422>,
-                error: BundleError::PackageNotIncluded {
-                    log_id: Cow::Borrowed(log_id),
-                },
+            Self::PackageLogNotIncluded(log_id) => {
+                problem(ProblemCode::PackageLogNotIncluded).with_id(log_id)
             }
-            .serialize(serializer),
-            Self::IncorrectProof { root, found } => RawError::BundleError {
-                status: Status::<422>,
-                error: BundleError::IncorrectProof {
-                    root: Cow::Borrowed(root),
-                    found: Cow::Borrowed(found),
-                },
+            Self::IncorrectProof { root, found } => {
+                let mut details = problem(ProblemCode::IncorrectProof);
+                details.root = Some(root.to_string());
+                details.found = Some(found.to_string());
+                details
             }
-            .serialize(serializer),
-            Self::BundleFailure(message) => RawError::BundleError {
-                status: Status::<422>,
-                error: BundleError::Failure {
-                    message: Cow::Borrowed(message),
-                },
+            Self::BundleFailure(message) => {
+                ProblemDetails::new(ProblemCode::BundleFailure, message.as_str())
             }
-            .serialize(serializer),
-            Self::Message { status, message } => RawError::Message {
-                status: *status,
-                message: Cow::Borrowed(message),
+            Self::Message { status, message } => {
+                ProblemDetails::from_status(*status, message.as_str())
             }
-            .serialize(serializer),
         }
+        .serialize(serializer)
     }
 }
 
@@ -191,25 +148,26 @@ impl<'de> Deserialize<'de> for ProofError {
     where
         D: serde::Deserializer<'de>,
     {
-        match RawError::deserialize(deserializer)? {
-            RawError::NotFound { status: _, ty, id } => match ty {
-                EntityType::LogLength => Ok(Self::CheckpointNotFound(id)),
-                EntityType::Leaf => Ok(Self::LeafNotFound(id)),
+        let details = ProblemDetails::deserialize(deserializer)?;
+        Ok(match details.code {
+            ProblemCode::CheckpointNotFound => {
+                Self::CheckpointNotFound(details.parse_id("a valid log length")?)
+            }
+            ProblemCode::LeafNotFound => {
+                Self::LeafNotFound(details.parse_id("a valid leaf index")?)
+            }
+            ProblemCode::PackageLogNotIncluded => Self::PackageLogNotIncluded(
+                details.parse_id::<AnyHash, _>("a valid log id")?.into(),
+            ),
+            ProblemCode::IncorrectProof => Self::IncorrectProof {
+                root: parse_member("root", details.root.as_deref(), "a valid hash")?,
+                found: parse_member("found", details.found.as_deref(), "a valid hash")?,
             },
-            RawError::BundleError { status: _, error } => match error {
-                BundleError::PackageNotIncluded { log_id } => {
-                    Ok(Self::PackageLogNotIncluded(log_id.into_owned()))
-                }
-                BundleError::IncorrectProof { root, found } => Ok(Self::IncorrectProof {
-                    root: root.into_owned(),
-                    found: found.into_owned(),
-                }),
-                BundleError::Failure { message } => Ok(Self::BundleFailure(message.into_owned())),
+            ProblemCode::BundleFailure => Self::BundleFailure(details.into_detail()),
+            _ => Self::Message {
+                status: details.status,
+                message: details.into_detail(),
             },
-            RawError::Message { status, message } => Ok(Self::Message {
-                status,
-                message: message.into_owned(),
-            }),
-        }
+        })
     }
 }
@@ -0,0 +1,78 @@
+//! Types and paths relating to the static registry layout.
+//!
+//! A static registry is a read-only registry whose logs, checkpoint, proofs,
+//! and content are laid out as files that may be served by any static host.
+//! A registry advertises the layout with [`RegistryLayout::Static`] in its
+//! discovery document; paths are relative to the base URL of the registry.
+//!
+//! The proofs of a static registry are precomputed for its checkpoint only:
+//! the inclusion proof of each log proves the inclusion of its head, and a
+//! consistency proof is provided from each previous checkpoint.
+//!
+//! [`RegistryLayout::Static`]: crate::RegistryLayout::Static
+
+use super::fetch::PublishedRecord;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::registry::{LogId, PackageName, RegistryLen};
+
+/// The path of the checkpoint of the registry.
+///
+/// The checkpoint is a `SerdeEnvelope<TimestampedCheckpoint>`.
+pub const CHECKPOINT_PATH: &str = "checkpoint.json";
+
+/// The path of the names of the packages of the registry.
+pub const PACKAGE_NAMES_PATH: &str = "names.json";
+
+/// The path of the operator log.
+pub const OPERATOR_LOG_PATH: &str = "logs/operator.json";
+
+/// The path of a package log.
+pub fn package_log(log_id: &LogId) -> String {
+    format!("logs/{id}.json", id = file_name(log_id))
+}
+
+/// The path of the inclusion proof of the head of a log.
+///
+/// The proof is an [`InclusionResponse`](super::proof::InclusionResponse).
+pub fn inclusion_proof(log_id: &LogId) -> String {
+    format!("proofs/inclusion/{id}.json", id = file_name(log_id))
+}
+
+/// The directory of the consistency proofs.
+pub const CONSISTENCY_PROOFS_DIR: &str = "proofs/consistency";
+
+/// The path of the consistency proof from the checkpoint of the given log
+/// length to the checkpoint of the registry.
+///
+/// The proof is a [`ConsistencyResponse`](super::proof::ConsistencyResponse).
+pub fn consistency_proof(from: RegistryLen) -> String {
+    format!("{CONSISTENCY_PROOFS_DIR}/{from}.json")
+}
+
+/// The path of content.
+pub fn content(digest: &AnyHash) -> String {
+    format!("content/{digest}", digest = file_name(digest))
+}
+
+/// Gets the file name of a hash, which may not contain a `:`.
+fn file_name(hash: impl ToString) -> String {
+    hash.to_string().replace(':', "-")
+}
+
+/// Represents a log of a static registry.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticLog {
+    /// The records of the log included in the checkpoint, in order.
+    pub records: Vec<PublishedRecord>,
+}
+
+/// Represents the names of the packages of a static registry.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticPackageNames {
+    /// The package log ID hash mapping to a package name.
+    pub packages: IndexMap<LogId, PackageName>,
+}
@@ -0,0 +1,142 @@
+/*
+ * C bindings for the Warg registry client.
+ *
+ * Clients and downloads are opaque handles that must be freed with the
+ * corresponding `*_free` function. Every fallible function returns a
+ * `warg_error_code_t`; on failure, a message describing the error may be
+ * retrieved with `warg_last_error_message` on the same thread.
+ *
+ * Strings passed to the bindings must be NUL-terminated UTF-8. Strings
+ * returned by the bindings are owned by the caller unless documented
+ * otherwise and must be freed with `warg_string_free`.
+ *
+ * Functions of a client block the calling thread until the operation
+ * completes.
+ */
+
+#ifndef WARG_CLIENT_H
+#define WARG_CLIENT_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The result of a function of the bindings. */
+typedef enum warg_error_code {
+  /* The function succeeded. */
+  WARG_OK = 0,
+  /* An argument was null or invalid. */
+  WARG_ERROR_INVALID_ARGUMENT = 1,
+  /* The package, version, or content does not exist. */
+  WARG_ERROR_NOT_FOUND = 2,
+  /* The package log, checkpoint, or content failed verification. */
+  WARG_ERROR_VERIFICATION_FAILED = 3,
+  /* The package was rejected by a policy of the client. */
+  WARG_ERROR_POLICY_VIOLATION = 4,
+  /* The registry rejected a published record. */
+  WARG_ERROR_PUBLISH_REJECTED = 5,
+  /* The registry rejected the client's credentials. */
+  WARG_ERROR_UNAUTHORIZED = 6,
+  /* A request to the registry or a publish timed out. */
+  WARG_ERROR_TIMED_OUT = 7,
+  /* An I/O error occurred. */
+  WARG_ERROR_IO = 8,
+  /* The function panicked. */
+  WARG_ERROR_PANIC = 9,
+  /* Any other error. */
+  WARG_ERROR_OTHER = 10,
+} warg_error_code_t;
+
+/* A registry client. */
+typedef struct WargClient warg_client_t;
+
+/* A package release downloaded into client storage. */
+typedef struct WargDownload warg_download_t;
+
+/*
+ * Gets the message of the last error on the calling thread, or null if the
+ * last function called on the thread succeeded.
+ *
+ * The message is valid until the next function of the bindings is called on
+ * the thread.
+ */
+const char *warg_last_error_message(void);
+
+/* Frees a string returned by the bindings; `s` may be null. */
+void warg_string_free(char *s);
+
+/*
+ * Creates a client of a registry.
+ *
+ * `registry_url` may be null to use the home registry of the configuration;
+ * `config_path` may be null to use the default configuration file.
+ * Interactive prompts are always disabled.
+ */
+warg_error_code_t warg_client_new(const char *registry_url,
+                                  const char *config_path,
+                                  warg_client_t **out);
+
+/* Frees a client; `client` may be null. */
+void warg_client_free(warg_client_t *client);
+
+/*
+ * Downloads the latest version of a package satisfying a version requirement
+ * (e.g. `^1.0`) into client storage.
+ *
+ * `version_requirement` may be null to download the latest version. The
+ * package log and downloaded content are verified. Returns
+ * `WARG_ERROR_NOT_FOUND` if no version satisfies the requirement.
+ */
+warg_error_code_t warg_download(const warg_client_t *client,
+                                const char *package,
+                                const char *version_requirement,
+                                warg_download_t **out);
+
+/* Gets the version of a download; the string is owned by the download. */
+const char *warg_download_version(const warg_download_t *download);
+
+/* Gets the content digest of a download; the string is owned by the download. */
+const char *warg_download_digest(const warg_download_t *download);
+
+/* Gets the content path of a download; the string is owned by the download. */
+const char *warg_download_path(const warg_download_t *download);
+
+/* Frees a download; the content remains in client storage. */
+void warg_download_free(warg_download_t *download);
+
+/*
+ * Verifies the log of a package against the latest checkpoint of its
+ * registry.
+ *
+ * If `out_report` is not null, the verification report is written to it as
+ * JSON, even if verification fails. Returns `WARG_ERROR_VERIFICATION_FAILED`
+ * if any anomaly was found.
+ */
+warg_error_code_t warg_verify(const warg_client_t *client,
+                              const char *package,
+                              char **out_report);
+
+/*
+ * Publishes a release of a package from a content file and waits for the
+ * record to be published.
+ *
+ * If `init` is true, the package is initialized by the same record. The
+ * record is signed with `signing_key` (e.g. `ecdsa-p256:...`). If
+ * `out_record_id` is not null, the id of the published record is written to
+ * it.
+ */
+warg_error_code_t warg_publish(const warg_client_t *client,
+                               const char *package,
+                               const char *version,
+                               const char *content_path,
+                               const char *signing_key,
+                               bool init,
+                               char **out_record_id);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* WARG_CLIENT_H */
@@ -0,0 +1,641 @@
+//! C bindings for the Warg registry client.
+//!
+//! The bindings expose downloading, verifying, and publishing packages over
+//! a C ABI so that non-Rust toolchains can embed a verifying registry client
+//! without reimplementing the protocol. The C declarations are in
+//! `include/warg_client.h`.
+//!
+//! Clients and downloads are opaque handles that must be freed with the
+//! corresponding `*_free` function. Every fallible function returns a
+//! [`WargErrorCode`]; on failure, a message describing the error may be
+//! retrieved with [`warg_last_error_message`] on the same thread.
+//!
+//! Strings passed to the bindings must be NUL-terminated UTF-8. Strings
+//! returned by the bindings are owned by the caller unless documented
+//! otherwise and must be freed with [`warg_string_free`].
+
+#![deny(missing_docs)]
+
+use futures_util::stream;
+use semver::VersionReq;
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    fmt::Display,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::Path,
+    ptr,
+    time::Duration,
+};
+use tokio::runtime::Runtime;
+use warg_client::{
+    storage::{ContentStorage, PublishEntry, PublishInfo},
+    ClientError, Config, FileSystemClient,
+};
+use warg_crypto::signing::PrivateKey;
+use warg_protocol::{package::ContentType, registry::PackageName, Version};
+
+/// The interval at which the registry is polled while waiting for a
+/// published record.
+const PUBLISH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The result of a function of the bindings.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WargErrorCode {
+    /// The function succeeded.
+    Ok = 0,
+    /// An argument was null or invalid (e.g. not UTF-8 or not a valid
+    /// package name).
+    InvalidArgument = 1,
+    /// The package, version, or content does not exist.
+    NotFound = 2,
+    /// The package log, checkpoint, or content failed verification.
+    VerificationFailed = 3,
+    /// The package was rejected by a policy of the client.
+    PolicyViolation = 4,
+    /// The registry rejected a published record.
+    PublishRejected = 5,
+    /// The registry rejected the client's credentials.
+    Unauthorized = 6,
+    /// A request to the registry or a publish timed out.
+    TimedOut = 7,
+    /// An I/O error occurred.
+    Io = 8,
+    /// The function panicked.
+    Panic = 9,
+    /// Any other error.
+    Other = 10,
+}
+
+/// An error returned by a function of the bindings.
+struct Error {
+    code: WargErrorCode,
+    message: String,
+}
+
+impl Error {
+    fn new(code: WargErrorCode, message: impl Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    fn invalid_argument(name: &str, reason: impl Display) -> Self {
+        Self::new(
+            WargErrorCode::InvalidArgument,
+            format_args!("invalid argument `{name}`: {reason}"),
+        )
+    }
+}
+
+impl From<ClientError> for Error {
+    fn from(e: ClientError) -> Self {
+        let code = match &e {
+            ClientError::PackageDoesNotExist { .. }
+            | ClientError::PackageDoesNotExistWithHintHeader { .. }
+            | ClientError::PackageVersionDoesNotExist { .. }
+            | ClientError::PackageVersionRequirementDoesNotExist { .. }
+            | ClientError::ContentNotFound { .. } => WargErrorCode::NotFound,
+            ClientError::InvalidCheckpointSignature
+            | ClientError::InvalidPackageSnapshot { .. }
+            | ClientError::InvalidCheckpointKeyId { .. }
+            | ClientError::OperatorValidationFailed { .. }
+            | ClientError::PackageValidationFailed { .. }
+            | ClientError::UntrustedSigningKey { .. }
+            | ClientError::IncorrectContent { .. }
+            | ClientError::DependencyNotVerified { .. }
+            | ClientError::CheckpointLogLengthRewind { .. }
+            | ClientError::CheckpointChangedLogRootOrMapRoot { .. } => {
+                WargErrorCode::VerificationFailed
+            }
+            ClientError::PackagePolicyViolation { .. }
+            | ClientError::AdvisoryPolicyViolation { .. }
+            | ClientError::LicenseNotAllowed { .. } => WargErrorCode::PolicyViolation,
+            ClientError::PublishRejected { .. }
+            | ClientError::InvalidPublish { .. }
+            | ClientError::IncompatibleRelease { .. }
+            | ClientError::CannotInitializePackage { .. }
+            | ClientError::MustInitializePackage { .. } => WargErrorCode::PublishRejected,
+            ClientError::Unauthorized(_) => WargErrorCode::Unauthorized,
+            ClientError::DeadlineExceeded | ClientError::PublishTimedOut { .. } => {
+                WargErrorCode::TimedOut
+            }
+            ClientError::Api(e) if e.is_timeout() => WargErrorCode::TimedOut,
+            ClientError::IoError(_) => WargErrorCode::Io,
+            _ => WargErrorCode::Other,
+        };
+
+        Self::new(code, e)
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Runs the body of a function of the bindings, recording its error, if
+/// any, as the last error of the thread.
+fn call(f: impl FnOnce() -> Result<(), Error>) -> WargErrorCode {
+    let result = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(_) => Err(Error::new(WargErrorCode::Panic, "the client panicked")),
+    };
+
+    match result {
+        Ok(()) => {
+            set_last_error(None);
+            WargErrorCode::Ok
+        }
+        Err(e) => {
+            set_last_error(Some(e.message));
+            e.code
+        }
+    }
+}
+
+fn set_last_error(message: Option<String>) {
+    let message = message.map(|m| {
+        CString::new(m.replace('\0', ""))
+            .expect("message should not contain a NUL after removing them")
+    });
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Reads a required string argument.
+///
+/// # Safety
+///
+/// The pointer must be null or point to a NUL-terminated string that
+/// outlives the returned reference.
+unsafe fn str_arg<'a>(name: &str, s: *const c_char) -> Result<&'a str, Error> {
+    opt_str_arg(name, s)?.ok_or_else(|| Error::invalid_argument(name, "must not be null"))
+}
+
+/// Reads an optional string argument, which is `None` if null.
+///
+/// # Safety
+///
+/// The pointer must be null or point to a NUL-terminated string that
+/// outlives the returned reference.
+unsafe fn opt_str_arg<'a>(name: &str, s: *const c_char) -> Result<Option<&'a str>, Error> {
+    if s.is_null() {
+        return Ok(None);
+    }
+
+    CStr::from_ptr(s)
+        .to_str()
+        .map(Some)
+        .map_err(|e| Error::invalid_argument(name, e))
+}
+
+/// Reads an argument that is parsed from a string.
+///
+/// # Safety
+///
+/// See [`str_arg`].
+unsafe fn parse_arg<T>(name: &str, s: *const c_char) -> Result<T, Error>
+where
+    T: std::str::FromStr,
+    T::Err: Display,
+{
+    str_arg(name, s)?
+        .parse()
+        .map_err(|e| Error::invalid_argument(name, e))
+}
+
+/// Reads a required output argument.
+///
+/// # Safety
+///
+/// The pointer must be null or valid for writes.
+unsafe fn out_arg<'a, T>(name: &str, out: *mut T) -> Result<&'a mut T, Error> {
+    out.as_mut()
+        .ok_or_else(|| Error::invalid_argument(name, "must not be null"))
+}
+
+/// Reads a client handle.
+///
+/// # Safety
+///
+/// The pointer must be null or a client returned by [`warg_client_new`]
+/// that has not been freed.
+unsafe fn client_arg<'a>(client: *const WargClient) -> Result<&'a WargClient, Error> {
+    client
+        .as_ref()
+        .ok_or_else(|| Error::invalid_argument("client", "must not be null"))
+}
+
+fn to_c_string(name: &str, s: impl Into<Vec<u8>>) -> Result<CString, Error> {
+    CString::new(s).map_err(|e| {
+        Error::new(
+            WargErrorCode::Other,
+            format_args!("{name} contains a NUL character: {e}"),
+        )
+    })
+}
+
+/// Gets the message of the last error on the calling thread.
+///
+/// Returns null if the last function called on the thread succeeded. The
+/// message is owned by the bindings and is valid until the next function of
+/// the bindings is called on the thread.
+#[no_mangle]
+pub extern "C" fn warg_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Frees a string returned by the bindings.
+///
+/// # Safety
+///
+/// The string must be null or a string returned by the bindings that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn warg_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// A registry client.
+///
+/// Each client runs its operations to completion on its own runtime, so the
+/// functions of the bindings block the calling thread.
+pub struct WargClient {
+    runtime: Runtime,
+    client: FileSystemClient,
+}
+
+/// Creates a client of a registry.
+///
+/// The registry URL may be null to use the home registry of the
+/// configuration. The configuration is read from the given path or, if
+/// null, from the default configuration file. Interactive prompts are
+/// always disabled.
+///
+/// On success, the client is written to `out` and must be freed with
+/// [`warg_client_free`].
+///
+/// # Safety
+///
+/// The registry URL and configuration path must be null or NUL-terminated
+/// strings, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn warg_client_new(
+    registry_url: *const c_char,
+    config_path: *const c_char,
+    out: *mut *mut WargClient,
+) -> WargErrorCode {
+    call(|| {
+        let registry_url = opt_str_arg("registry_url", registry_url)?;
+        let config_path = opt_str_arg("config_path", config_path)?;
+        let out = out_arg("out", out)?;
+
+        let mut config = match config_path {
+            Some(path) => Config::from_file(Path::new(path)),
+            None => Config::from_default_file().map(Option::unwrap_or_default),
+        }
+        .map_err(|e| Error::new(WargErrorCode::InvalidArgument, format_args!("{e:#}")))?;
+        config.disable_interactive = true;
+
+        let runtime = Runtime::new().map_err(|e| Error::new(WargErrorCode::Io, e))?;
+        let client = runtime.block_on(FileSystemClient::new_with_config(
+            registry_url,
+            &config,
+            None,
+        ))?;
+
+        *out = Box::into_raw(Box::new(WargClient { runtime, client }));
+        Ok(())
+    })
+}
+
+/// Frees a client.
+///
+/// # Safety
+///
+/// The client must be null or a client returned by [`warg_client_new`]
+/// that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn warg_client_free(client: *mut WargClient) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// A package release downloaded into client storage.
+pub struct WargDownload {
+    version: CString,
+    digest: CString,
+    path: CString,
+}
+
+/// Downloads the latest version of a package satisfying a version
+/// requirement into client storage.
+///
+/// The version requirement may be null to download the latest version.
+/// The package log is verified before its content is downloaded, and the
+/// downloaded content is verified against its digest.
+///
+/// On success, the download is written to `out` and must be freed with
+/// [`warg_download_free`]. Returns [`WargErrorCode::NotFound`] if no version
+/// satisfies the requirement.
+///
+/// # Safety
+///
+/// The client must be a client returned by [`warg_client_new`], the package
+/// and version requirement must be null or NUL-terminated strings, and `out`
+/// must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn warg_download(
+    client: *const WargClient,
+    package: *const c_char,
+    version_requirement: *const c_char,
+    out: *mut *mut WargDownload,
+) -> WargErrorCode {
+    call(|| {
+        let client = client_arg(client)?;
+        let package: PackageName = parse_arg("package", package)?;
+        let requirement = match opt_str_arg("version_requirement", version_requirement)? {
+            Some(requirement) => requirement
+                .parse()
+                .map_err(|e| Error::invalid_argument("version_requirement", e))?,
+            None => VersionReq::STAR,
+        };
+        let out = out_arg("out", out)?;
+
+        let download = client
+            .runtime
+            .block_on(client.client.download(&package, &requirement))?
+            .ok_or_else(|| {
+                Error::new(
+                    WargErrorCode::NotFound,
+                    format_args!(
+                        "no version of package `{package}` satisfies requirement `{requirement}`"
+                    ),
+                )
+            })?;
+
+        let path = download.path.to_str().ok_or_else(|| {
+            Error::new(
+                WargErrorCode::Io,
+                format_args!(
+                    "download path `{path}` is not UTF-8",
+                    path = download.path.display()
+                ),
+            )
+        })?;
+
+        *out = Box::into_raw(Box::new(WargDownload {
+            version: to_c_string("version", download.version.to_string())?,
+            digest: to_c_string("digest", download.digest.to_string())?,
+            path: to_c_string("path", path)?,
+        }));
+        Ok(())
+    })
+}
+
+/// Gets the version of a download.
+///
+/// The string is owned by the download.
+///
+/// # Safety
+///
+/// The download must be a download returned by [`warg_download`] that has
+/// not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn warg_download_version(download: *const WargDownload) -> *const c_char {
+    download
+        .as_ref()
+        .map_or(ptr::null(), |d| d.version.as_ptr())
+}
+
+/// Gets the digest of the content of a download (e.g. `sha256:...`).
+///
+/// The string is owned by the download.
+///
+/// # Safety
+///
+/// The download must be a download returned by [`warg_download`] that has
+/// not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn warg_download_digest(download: *const WargDownload) -> *const c_char {
+    download.as_ref().map_or(ptr::null(), |d| d.digest.as_ptr())
+}
+
+/// Gets the path of the content of a download within client storage.
+///
+/// The string is owned by the download.
+///
+/// # Safety
+///
+/// The download must be a download returned by [`warg_download`] that has
+/// not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn warg_download_path(download: *const WargDownload) -> *const c_char {
+    download.as_ref().map_or(ptr::null(), |d| d.path.as_ptr())
+}
+
+/// Frees a download.
+///
+/// The downloaded content remains in client storage.
+///
+/// # Safety
+///
+/// The download must be null or a download returned by [`warg_download`]
+/// that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn warg_download_free(download: *mut WargDownload) {
+    if !download.is_null() {
+        drop(Box::from_raw(download));
+    }
+}
+
+/// Verifies the log of a package.
+///
+/// The operator and package logs are fetched from the registry and
+/// validated from the start; the inclusion of the package log in the latest
+/// checkpoint and the consistency of the checkpoint with the one in client
+/// storage are proven.
+///
+/// If `out_report` is not null, the verification report is written to it as
+/// JSON and must be freed with [`warg_string_free`]; the report is written
+/// even if verification fails. Returns [`WargErrorCode::VerificationFailed`]
+/// if any anomaly was found.
+///
+/// # Safety
+///
+/// The client must be a client returned by [`warg_client_new`], the package
+/// must be a NUL-terminated string, and `out_report` must be null or valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn warg_verify(
+    client: *const WargClient,
+    package: *const c_char,
+    out_report: *mut *mut c_char,
+) -> WargErrorCode {
+    call(|| {
+        let client = client_arg(client)?;
+        let package: PackageName = parse_arg("package", package)?;
+
+        let report = client
+            .runtime
+            .block_on(client.client.verification_report(&package))?;
+
+        if let Some(out_report) = out_report.as_mut() {
+            let json =
+                serde_json::to_string(&report).map_err(|e| Error::new(WargErrorCode::Other, e))?;
+            *out_report = to_c_string("report", json)?.into_raw();
+        }
+
+        if !report.is_verified() {
+            return Err(Error::new(
+                WargErrorCode::VerificationFailed,
+                format_args!(
+                    "package `{package}` failed verification with {count} anomalies",
+                    count = report.anomalies.len()
+                ),
+            ));
+        }
+
+        Ok(())
+    })
+}
+
+/// Publishes a release of a package and waits for the record to be
+/// published.
+///
+/// The content is read from the given file and uploaded as a component.
+/// If `init` is true, the package is initialized by the same record. The
+/// record is signed with the given key (e.g. `ecdsa-p256:...`).
+///
+/// If `out_record_id` is not null, the id of the published record is
+/// written to it and must be freed with [`warg_string_free`].
+///
+/// # Safety
+///
+/// The client must be a client returned by [`warg_client_new`], the
+/// package, version, content path, and signing key must be NUL-terminated
+/// strings, and `out_record_id` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn warg_publish(
+    client: *const WargClient,
+    package: *const c_char,
+    version: *const c_char,
+    content_path: *const c_char,
+    signing_key: *const c_char,
+    init: bool,
+    out_record_id: *mut *mut c_char,
+) -> WargErrorCode {
+    call(|| {
+        let client = client_arg(client)?;
+        let package: PackageName = parse_arg("package", package)?;
+        let version: Version = parse_arg("version", version)?;
+        let content_path = str_arg("content_path", content_path)?;
+        let signing_key = PrivateKey::decode(str_arg("signing_key", signing_key)?.to_string())
+            .map_err(|e| Error::invalid_argument("signing_key", e))?;
+
+        let content = std::fs::read(content_path).map_err(|e| {
+            Error::new(
+                WargErrorCode::Io,
+                format_args!("failed to read content `{content_path}`: {e}"),
+            )
+        })?;
+
+        let record_id = client.runtime.block_on(async {
+            let digest = client
+                .client
+                .content()
+                .store_content(
+                    Box::pin(stream::once(async move { Ok(content.into()) })),
+                    None,
+                )
+                .await
+                .map_err(ClientError::from)?;
+
+            let mut entries = Vec::with_capacity(2);
+            if init {
+                entries.push(PublishEntry::Init);
+            }
+            entries.push(PublishEntry::Release {
+                version,
+                content: digest,
+                content_type: ContentType::Component,
+            });
+
+            let record_id = client
+                .client
+                .publish_with_info(
+                    &signing_key,
+                    PublishInfo {
+                        name: package.clone(),
+                        head: None,
+                        entries,
+                    },
+                )
+                .await?;
+
+            client
+                .client
+                .wait_for_publish(&package, &record_id, PUBLISH_POLL_INTERVAL)
+                .await?;
+
+            Ok::<_, Error>(record_id)
+        })?;
+
+        if let Some(out_record_id) = out_record_id.as_mut() {
+            *out_record_id = to_c_string("record id", record_id.to_string())?.into_raw();
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> Option<String> {
+        let message = warg_last_error_message();
+        (!message.is_null()).then(|| {
+            unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+
+    #[test]
+    fn it_reports_invalid_arguments() {
+        let package = CString::new("test:foo").unwrap();
+        let mut download = ptr::null_mut();
+        let code =
+            unsafe { warg_download(ptr::null(), package.as_ptr(), ptr::null(), &mut download) };
+        assert_eq!(code, WargErrorCode::InvalidArgument);
+        assert!(download.is_null());
+        assert_eq!(
+            last_error().as_deref(),
+            Some("invalid argument `client`: must not be null")
+        );
+
+        let config_path = CString::new("/nonexistent/warg/config.json").unwrap();
+        let mut client = ptr::null_mut();
+        let code = unsafe { warg_client_new(ptr::null(), config_path.as_ptr(), &mut client) };
+        assert_eq!(code, WargErrorCode::InvalidArgument);
+        assert!(client.is_null());
+        assert!(last_error()
+            .unwrap()
+            .contains("failed to read configuration file"));
+
+        unsafe {
+            warg_client_free(ptr::null_mut());
+            warg_download_free(ptr::null_mut());
+            warg_string_free(ptr::null_mut());
+        }
+    }
+}
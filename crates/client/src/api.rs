@@ -1,47 +1,133 @@
 //! A module for Warg registry API clients.
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use bytes::Bytes;
-use futures_util::{future::ready, stream::once, Stream, StreamExt, TryStreamExt};
+use futures_util::{
+    future::ready,
+    stream::{once, BoxStream},
+    Stream, StreamExt, TryStreamExt,
+};
 use indexmap::IndexMap;
 use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    Body, IntoUrl, Method, RequestBuilder, Response, StatusCode,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::{
+        HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE,
+        IF_NONE_MATCH, LAST_MODIFIED, RANGE, RETRY_AFTER, USER_AGENT as USER_AGENT_HEADER,
+    },
+    Body, IntoUrl, Method, Response, StatusCode, Url,
 };
 use secrecy::{ExposeSecret, Secret};
-use serde::de::DeserializeOwned;
-use std::borrow::Cow;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    ops::Range,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use thiserror::Error;
+use tokio::sync::{OnceCell, OwnedSemaphorePermit};
 use warg_api::{
     v1::{
-        content::{ContentError, ContentSourcesResponse},
-        fetch::{
-            FetchError, FetchLogsRequest, FetchLogsResponse, FetchPackageNamesRequest,
-            FetchPackageNamesResponse,
-        },
-        ledger::{LedgerError, LedgerSourcesResponse},
-        monitor::{CheckpointVerificationResponse, MonitorError},
-        package::{ContentSource, PackageError, PackageRecord, PublishRecordRequest},
+        content::{ContentSectionsResponse, ContentSourcesResponse},
+        fetch::{FetchLogsStreamItem, FETCH_LOGS_STREAM_CONTENT_TYPE},
         paths,
-        proof::{
-            ConsistencyRequest, ConsistencyResponse, InclusionRequest, InclusionResponse,
-            ProofError,
-        },
+        problem::{ProblemCode, ProblemDetails, Quota, PROBLEM_CONTENT_TYPE},
+        proof::{ConsistencyResponse, InclusionResponse, MultiProofResponse},
+        static_registry::{self, StaticLog, StaticPackageNames},
         REGISTRY_HEADER_NAME, REGISTRY_HINT_HEADER_NAME,
     },
-    WellKnownConfig, WELL_KNOWN_PATH,
+    DiscoveryDocument, RegistryLayout, WellKnownConfig, DISCOVERY_PATH, WELL_KNOWN_PATH,
+};
+use warg_crypto::{
+    hash::{AnyHash, HashAlgorithm, HashError, Sha256},
+    signing::SignatureAlgorithm,
 };
-use warg_crypto::hash::{AnyHash, HashError, Sha256};
 use warg_protocol::{
-    registry::{Checkpoint, LogId, LogLeaf, MapLeaf, RecordId, TimestampedCheckpoint},
+    registry::{Checkpoint, LogId, LogLeaf, RecordId, RegistryLen, TimestampedCheckpoint},
+    verify::{self, VerifyError},
     SerdeEnvelope,
 };
-use warg_transparency::{
-    log::{ConsistencyProofError, InclusionProofError, LogProofBundle, ProofBundle},
-    map::MapProofBundle,
+use warg_transparency::log::{ConsistencyProofError, InclusionProofError};
+
+use crate::{
+    limits::{ConcurrencyLimits, Limiter},
+    middleware::Middleware,
+    registry_url::RegistryUrl,
+    storage::RegistryDomain,
+    transport::{HttpClient, RequestBuilder},
+};
+
+// The API types used by the client.
+//
+// These are the types of the negotiated API version; types of a newer API
+// version are converted to these by the client methods that request them.
+pub use warg_api::{
+    v1::{
+        capabilities::{AuthMode, CapabilitiesResponse, Compression, UploadProtocol},
+        content::ContentError,
+        fetch::{
+            FetchError, FetchLogsRequest, FetchLogsResponse, FetchPackageNamesRequest,
+            FetchPackageNamesResponse, PublishedRecord,
+        },
+        ledger::{LedgerError, LedgerSource, LedgerSourceContentType, LedgerSourcesResponse},
+        monitor::{CheckpointVerificationResponse, MonitorError},
+        namespace::{Namespace, NamespaceError, NamespaceState, NamespacesResponse},
+        package::{
+            AdvisorySeverity, ApproveRecordRequest, AwaitRecordQuery, ContentSource,
+            CreateInviteRequest, KeyEvent, KeyEventKind, ListRecordsQuery, MissingContent,
+            PackageAdvisoriesResponse, PackageAdvisory, PackageError, PackageInviteInfo,
+            PackageInvitesResponse, PackageKeyHistoryResponse, PackageMetadataResponse,
+            PackageRecord, PackageRecordState, PackageRecordsResponse, PackageSnapshotQuery,
+            PackageSnapshotResponse, PublishRecordRequest, RecordStatusFilter, RedeemInviteRequest,
+            ReleaseMetadata, UploadEndpoint, VulnerabilityAdvisory,
+        },
+        proof::{ConsistencyRequest, InclusionRequest, ProofError},
+    },
+    ApiVersion,
 };
 
-use crate::{registry_url::RegistryUrl, storage::RegistryDomain};
+/// The API versions supported by the client, in order of preference.
+const SUPPORTED_API_VERSIONS: &[ApiVersion] = &[ApiVersion::V1];
+
+/// Represents an API of a registry.
+///
+/// The version of each API is negotiated independently from the
+/// capabilities of the registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Api {
+    /// The content API.
+    Content,
+    /// The fetch API.
+    Fetch,
+    /// The ledger API.
+    Ledger,
+    /// The monitor API.
+    Monitor,
+    /// The namespace API.
+    Namespace,
+    /// The package API.
+    Package,
+    /// The proof API.
+    Proof,
+}
+
+impl Api {
+    /// Gets the name of the API as advertised in registry capabilities.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Content => "content",
+            Self::Fetch => "fetch",
+            Self::Ledger => "ledger",
+            Self::Monitor => "verify",
+            Self::Namespace => "namespace",
+            Self::Package => "package",
+            Self::Proof => "proof",
+        }
+    }
+}
+
 /// Represents an error that occurred while communicating with the registry.
 #[derive(Debug, Error)]
 pub enum ClientError {
@@ -63,9 +149,23 @@ pub enum ClientError {
     /// An error was returned from the ledger API.
     #[error(transparent)]
     Ledger(#[from] LedgerError),
+    /// An error was returned from the namespace API.
+    #[error(transparent)]
+    Namespace(#[from] NamespaceError),
     /// An error occurred while communicating with the registry.
     #[error("failed to send request to registry server: {0}")]
     Communication(#[from] reqwest::Error),
+    /// An error occurred while communicating with the registry over a Unix
+    /// domain socket or an in-process service.
+    #[error("failed to send request to registry server: {0:#}")]
+    LocalTransport(anyhow::Error),
+    /// The client is offline, so the request to the given URL was not sent.
+    #[error("cannot send request to `{0}` while the client is offline")]
+    Offline(Url),
+    /// The deadline of the operation sending the request passed before the
+    /// request was sent.
+    #[error("the deadline of the operation passed before the request was sent")]
+    DeadlineExceeded,
     /// An unexpected response was received from the server.
     #[error("{message} (status code: {status})")]
     UnexpectedResponse {
@@ -114,22 +214,272 @@ pub enum ClientError {
     /// Invalid well-known config.
     #[error("registry `{0}` returned an invalid well-known config")]
     InvalidWellKnownConfig(String),
+    /// Invalid discovery document.
+    #[error("registry `{0}` returned an invalid discovery document")]
+    InvalidDiscoveryDocument(String),
+    /// The operation is not supported by a static registry, which is
+    /// read-only.
+    #[error("registry is a static registry and does not support {0}")]
+    StaticRegistry(&'static str),
+    /// The registry does not support a version of an API known to the client.
+    #[error("registry does not support a version of the {0} API known to this client")]
+    UnsupportedApiVersion(&'static str),
+    /// The registry does not support the algorithms used by the client.
+    #[error("registry `{registry}` does not support the {kind} algorithm `{algorithm}`")]
+    UnsupportedAlgorithm {
+        /// The domain of the registry.
+        registry: String,
+        /// The kind of algorithm (e.g. `hash`).
+        kind: &'static str,
+        /// The algorithm used by the client.
+        algorithm: String,
+    },
+    /// The registry rejected the request as the client exceeded its
+    /// request quota.
+    #[error("the registry request quota was exceeded: {detail}")]
+    QuotaExceeded {
+        /// The explanation of the problem given by the registry.
+        detail: String,
+        /// The quota that was exceeded, if the registry provided it.
+        quota: Option<Quota>,
+        /// How long to wait before retrying the request, if the registry
+        /// provided a retry hint.
+        retry_after: Option<Duration>,
+    },
+    /// The registry is temporarily unavailable (e.g. it is shedding load).
+    #[error("the registry is temporarily unavailable: {detail}")]
+    Unavailable {
+        /// The machine-readable code of the problem.
+        code: ProblemCode,
+        /// The explanation of the problem given by the registry.
+        detail: String,
+        /// How long to wait before retrying the request, if the registry
+        /// provided a retry hint.
+        retry_after: Option<Duration>,
+    },
+    /// The registry served a checkpoint with a timestamp further in the
+    /// future than the allowed clock skew.
+    #[error("the registry served a checkpoint timestamped {ahead} second(s) in the future (allowed clock skew is {max_skew} second(s))", ahead = ahead.as_secs(), max_skew = max_skew.as_secs())]
+    CheckpointInFuture {
+        /// The timestamp of the checkpoint.
+        timestamp: u64,
+        /// How far the timestamp is ahead of the local time.
+        ahead: Duration,
+        /// The allowed clock skew.
+        max_skew: Duration,
+    },
+    /// The registry served a checkpoint older than the allowed staleness.
+    #[error("the registry served a checkpoint that is {age} second(s) old (allowed staleness is {max_staleness} second(s))", age = age.as_secs(), max_staleness = max_staleness.as_secs())]
+    StaleCheckpoint {
+        /// The timestamp of the checkpoint.
+        timestamp: u64,
+        /// The age of the checkpoint.
+        age: Duration,
+        /// The allowed staleness.
+        max_staleness: Duration,
+    },
     /// An other error occurred during the requested operation.
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+impl From<VerifyError> for ClientError {
+    fn from(e: VerifyError) -> Self {
+        match e {
+            VerifyError::UnexpectedBundle(message) => {
+                Self::Proof(ProofError::BundleFailure(message))
+            }
+            VerifyError::Hash(e) => Self::Hash(e),
+            VerifyError::InclusionProof(e) => Self::InclusionProof(e),
+            VerifyError::ConsistencyProof(e) => Self::ConsistencyProof(e),
+            VerifyError::IncorrectInclusionProof { root, found } => {
+                Self::Proof(ProofError::IncorrectProof { root, found })
+            }
+            VerifyError::IncorrectConsistencyProof { root, found } => {
+                Self::IncorrectConsistencyProof { root, found }
+            }
+            e => Self::Other(e.into()),
+        }
+    }
+}
+
+impl ClientError {
+    /// Determines if the error is a request to the registry that timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Communication(e) if e.is_timeout())
+    }
+
+    /// Determines if the request was not sent because the client is
+    /// offline.
+    pub fn is_offline(&self) -> bool {
+        matches!(self, Self::Offline(_))
+    }
+
+    /// Determines if the registry throttled the request, in which case it
+    /// may be retried after [`ClientError::retry_after`].
+    pub fn is_throttled(&self) -> bool {
+        matches!(self, Self::QuotaExceeded { .. } | Self::Unavailable { .. })
+    }
+
+    /// Gets how long the registry asked the client to wait before retrying
+    /// a throttled request.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::QuotaExceeded { retry_after, .. } | Self::Unavailable { retry_after, .. } => {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Represents how requests throttled by the registry are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of times a throttled request is retried.
+    pub max_retries: u32,
+    /// How long to wait before retrying a request the registry gave no
+    /// retry hint for.
+    pub initial_delay: Duration,
+    /// The maximum time to wait before retrying a request, regardless of
+    /// the registry's retry hint.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy that never retries throttled requests.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the maximum number of times a throttled request is retried.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets how long to wait before retrying a request the registry gave no
+    /// retry hint for.
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets the maximum time to wait before retrying a request.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Gets how long to wait before retrying the request that failed with
+    /// the given throttling error.
+    pub fn delay(&self, error: &ClientError) -> Duration {
+        error
+            .retry_after()
+            .unwrap_or(self.initial_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// Converts a throttled response into an error.
+///
+/// Returns the response if it was not throttled: a `429` or `503` response
+/// is only considered throttled if it has a problem details body or a
+/// `Retry-After` header.
+async fn throttled(response: Response) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+        return Ok(response);
+    }
+
+    let problem = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .is_some_and(|v| v == PROBLEM_CONTENT_TYPE);
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    if !problem && retry_after.is_none() {
+        return Ok(response);
+    }
+
+    let details = if problem {
+        response
+            .bytes()
+            .await
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<ProblemDetails>(&bytes).ok())
+    } else {
+        None
+    };
+
+    let code = match &details {
+        Some(details) => details.code,
+        None if status == StatusCode::TOO_MANY_REQUESTS => ProblemCode::QuotaExceeded,
+        None => ProblemCode::Unknown,
+    };
+    let detail = details
+        .as_ref()
+        .and_then(|d| d.detail.clone().or_else(|| Some(d.title.clone())))
+        .unwrap_or_else(|| {
+            status
+                .canonical_reason()
+                .unwrap_or("request throttled")
+                .to_lowercase()
+        });
+    let retry_after = details
+        .as_ref()
+        .and_then(|d| d.retry_after_ms)
+        .map(Duration::from_millis)
+        .or(retry_after);
+
+    Err(match code {
+        ProblemCode::QuotaExceeded => ClientError::QuotaExceeded {
+            detail,
+            quota: details.and_then(|d| d.quota),
+            retry_after,
+        },
+        code => ClientError::Unavailable {
+            code,
+            detail,
+            retry_after,
+        },
+    })
+}
+
 async fn deserialize<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
     let status = response.status();
     match response.headers().get("content-type") {
-        Some(content_type) if content_type == "application/json" => {
-            let bytes = response
-                .bytes()
-                .await
-                .map_err(|e| ClientError::UnexpectedResponse {
-                    status,
-                    message: format!("failed to read response: {e}"),
-                })?;
+        // Error responses have problem details bodies
+        Some(content_type)
+            if content_type == "application/json" || content_type == PROBLEM_CONTENT_TYPE =>
+        {
+            let bytes = response.bytes().await.map_err(|e| {
+                if e.is_timeout() {
+                    ClientError::Communication(e)
+                } else {
+                    ClientError::UnexpectedResponse {
+                        status,
+                        message: format!("failed to read response: {e}"),
+                    }
+                }
+            })?;
             serde_json::from_slice(&bytes).map_err(|e| {
                 tracing::debug!(
                     "Unexpected response body: {}",
@@ -161,10 +511,148 @@ async fn into_result<T: DeserializeOwned, E: DeserializeOwned + Into<ClientError
     if response.status().is_success() {
         deserialize::<T>(response).await
     } else {
+        let response = throttled(response).await?;
         Err(deserialize::<E>(response).await?.into())
     }
 }
 
+async fn package_record_result(response: Response) -> Result<PackageRecord, ClientError> {
+    // Fall back to a `Retry-After` header for the retry hint of a record
+    // that is still processing
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+
+    let mut record = into_result::<PackageRecord, PackageError>(response).await?;
+    if let PackageRecordState::Processing { retry_after_ms } = &mut record.state {
+        if retry_after_ms.is_none() {
+            *retry_after_ms = retry_after.map(|s| s.saturating_mul(1000));
+        }
+    }
+
+    Ok(record)
+}
+
+async fn fetch_logs_result(response: Response) -> Result<FetchLogsResponse, ClientError> {
+    let header = response.headers().get(REGISTRY_HINT_HEADER_NAME).cloned();
+    into_result::<_, FetchError>(response)
+        .await
+        .map_err(|err| match err {
+            ClientError::Fetch(FetchError::LogNotFound(log_id)) if header.is_some() => {
+                ClientError::LogNotFoundWithHint(log_id, header.unwrap())
+            }
+            _ => err,
+        })
+}
+
+/// Decodes a streamed fetch logs response into partial responses.
+struct FetchLogsStream {
+    body: BoxStream<'static, reqwest::Result<Bytes>>,
+    buffer: Vec<u8>,
+    pending: FetchLogsResponse,
+    done: bool,
+}
+
+impl FetchLogsStream {
+    fn new(response: Response) -> Self {
+        Self {
+            body: response.bytes_stream().boxed(),
+            buffer: Vec::new(),
+            pending: FetchLogsResponse::default(),
+            done: false,
+        }
+    }
+
+    fn into_stream(self) -> impl Stream<Item = Result<FetchLogsResponse, ClientError>> {
+        futures_util::stream::try_unfold(self, |mut stream| async move {
+            let response = stream.next_response().await?;
+            Ok(response.map(|response| (response, stream)))
+        })
+    }
+
+    /// Gets the next partial response, which has the records of a single
+    /// log.
+    async fn next_response(&mut self) -> Result<Option<FetchLogsResponse>, ClientError> {
+        while !self.done {
+            let item = self
+                .next_item()
+                .await?
+                .ok_or_else(|| ClientError::UnexpectedResponse {
+                    status: StatusCode::OK,
+                    message: "the streamed fetch logs response ended unexpectedly".into(),
+                })?;
+
+            // The pending response is complete once an item of another log is received
+            let complete = match &item {
+                FetchLogsStreamItem::Operator(_) => !self.pending.packages.is_empty(),
+                FetchLogsStreamItem::Package { log_id, .. } => {
+                    !self.pending.operator.is_empty()
+                        || self
+                            .pending
+                            .packages
+                            .last()
+                            .is_some_and(|(id, _)| id != log_id)
+                }
+                _ => false,
+            };
+            let response = complete.then(|| std::mem::take(&mut self.pending));
+
+            match item {
+                FetchLogsStreamItem::Operator(record) => self.pending.operator.push(record),
+                FetchLogsStreamItem::Package { log_id, record } => self
+                    .pending
+                    .packages
+                    .entry(log_id)
+                    .or_default()
+                    .push(record),
+                FetchLogsStreamItem::Warning(warning) => self.pending.warnings.push(warning),
+                FetchLogsStreamItem::End { more } => {
+                    self.done = true;
+                    let mut response = std::mem::take(&mut self.pending);
+                    response.more = more;
+                    return Ok(Some(response));
+                }
+                FetchLogsStreamItem::Error(e) => {
+                    self.done = true;
+                    return Err(e.into());
+                }
+            }
+
+            if response.is_some() {
+                return Ok(response);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Gets the next item of the response, reading more of the body as needed.
+    async fn next_item(&mut self) -> Result<Option<FetchLogsStreamItem>, ClientError> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line = self.buffer.drain(..=end).collect::<Vec<_>>();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+
+                return serde_json::from_slice(&line).map(Some).map_err(|e| {
+                    ClientError::UnexpectedResponse {
+                        status: StatusCode::OK,
+                        message: format!("failed to deserialize streamed fetch logs item: {e}"),
+                    }
+                });
+            }
+
+            match self.body.next().await {
+                Some(chunk) => self.buffer.extend_from_slice(&chunk?),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
 trait WithWargHeader {
     fn warg_header(self, registry_header: Option<&RegistryDomain>) -> Result<RequestBuilder>;
 }
@@ -184,7 +672,7 @@ trait WithAuth {
 }
 
 impl WithAuth for RequestBuilder {
-    fn auth(self, auth_token: &Option<Secret<String>>) -> reqwest::RequestBuilder {
+    fn auth(self, auth_token: &Option<Secret<String>>) -> RequestBuilder {
         if let Some(tok) = auth_token {
             self.bearer_auth(tok.expose_secret())
         } else {
@@ -193,25 +681,332 @@ impl WithAuth for RequestBuilder {
     }
 }
 
+/// The default `User-Agent` header of requests.
+const USER_AGENT: &str = concat!("warg-client/", env!("CARGO_PKG_VERSION"));
+
+/// The default duration for which the discovery of a registry is cached.
+const DEFAULT_DISCOVERY_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// The result of discovering a registry.
+#[derive(Debug)]
+struct Discovery {
+    api_url: RegistryUrl,
+    document: Option<DiscoveryDocument>,
+    /// Whether the result may be cached; a registry that failed to serve
+    /// its discovery document is discovered again on the next request.
+    cacheable: bool,
+}
+
+/// A checkpoint cached from a previous response, with the validators used
+/// to make conditional requests for the latest checkpoint.
+#[derive(Clone)]
+struct CachedCheckpoint {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+    checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+}
+
+/// Represents the IP address family used to connect to a registry.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IpFamily {
+    /// Connect over IPv4 or IPv6.
+    ///
+    /// Addresses are tried in the order returned by the system resolver;
+    /// if a connection is not established within 300ms, an address of the
+    /// other family is tried concurrently ("Happy Eyeballs").
+    #[default]
+    Any,
+    /// Try IPv4 addresses first, falling back to IPv6 addresses.
+    PreferIpv4,
+    /// Try IPv6 addresses first, falling back to IPv4 addresses.
+    PreferIpv6,
+    /// Connect over IPv4 only.
+    Ipv4Only,
+    /// Connect over IPv6 only.
+    Ipv6Only,
+}
+
+impl IpFamily {
+    /// Filters and orders resolved addresses according to the family.
+    fn apply(self, addrs: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+        let mut addrs: Vec<_> = addrs
+            .into_iter()
+            .filter(|addr| match self {
+                Self::Ipv4Only => addr.is_ipv4(),
+                Self::Ipv6Only => addr.is_ipv6(),
+                Self::Any | Self::PreferIpv4 | Self::PreferIpv6 => true,
+            })
+            .collect();
+
+        // The sort is stable, so the resolver's order is kept within a family
+        match self {
+            Self::PreferIpv4 => addrs.sort_by_key(|addr| addr.is_ipv6()),
+            Self::PreferIpv6 => addrs.sort_by_key(|addr| addr.is_ipv4()),
+            Self::Any | Self::Ipv4Only | Self::Ipv6Only => {}
+        }
+
+        addrs
+    }
+}
+
+/// The default clock skew allowed for the timestamps of checkpoints.
+const DEFAULT_MAX_CHECKPOINT_SKEW: Duration = Duration::from_secs(300);
+
+/// Represents the validation of the timestamps of checkpoints against the
+/// local clock.
+///
+/// By default, checkpoints may be timestamped at most 5 minutes in the
+/// future and may be of any age, as registries only produce checkpoints
+/// when their logs change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointClock {
+    /// The number of seconds a checkpoint's timestamp may be ahead of the
+    /// local clock; `None` allows any skew.
+    #[serde(default)]
+    pub max_skew_secs: Option<u64>,
+    /// The number of seconds a checkpoint's timestamp may be behind the
+    /// local clock; `None` allows checkpoints of any age.
+    #[serde(default)]
+    pub max_staleness_secs: Option<u64>,
+}
+
+impl Default for CheckpointClock {
+    fn default() -> Self {
+        Self {
+            max_skew_secs: Some(DEFAULT_MAX_CHECKPOINT_SKEW.as_secs()),
+            max_staleness_secs: None,
+        }
+    }
+}
+
+impl CheckpointClock {
+    /// Creates a clock validation that accepts checkpoints of any time.
+    pub fn disabled() -> Self {
+        Self {
+            max_skew_secs: None,
+            max_staleness_secs: None,
+        }
+    }
+
+    /// Sets how far a checkpoint's timestamp may be ahead of the local
+    /// clock.
+    pub fn with_max_skew(mut self, max_skew: Duration) -> Self {
+        self.max_skew_secs = Some(max_skew.as_secs());
+        self
+    }
+
+    /// Sets how far a checkpoint's timestamp may be behind the local clock.
+    pub fn with_max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness_secs = Some(max_staleness.as_secs());
+        self
+    }
+
+    /// Determines if the validation is the default.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Validates the timestamp of a checkpoint against the given local time.
+    pub fn validate(
+        &self,
+        checkpoint: &TimestampedCheckpoint,
+        now: SystemTime,
+    ) -> Result<(), ClientError> {
+        let timestamp = checkpoint.timestamp;
+        // A timestamp too large to be represented as a time is treated as
+        // being too far in the future
+        let offset = match UNIX_EPOCH.checked_add(Duration::from_secs(timestamp)) {
+            Some(time) => time.duration_since(now).map_err(|e| e.duration()),
+            None => Ok(Duration::from_secs(timestamp)
+                .saturating_sub(now.duration_since(UNIX_EPOCH).unwrap_or_default())),
+        };
+        match offset {
+            Ok(ahead) => match self.max_skew_secs.map(Duration::from_secs) {
+                Some(max_skew) if ahead > max_skew => Err(ClientError::CheckpointInFuture {
+                    timestamp,
+                    ahead,
+                    max_skew,
+                }),
+                _ => Ok(()),
+            },
+            Err(age) => match self.max_staleness_secs.map(Duration::from_secs) {
+                Some(max_staleness) if age > max_staleness => Err(ClientError::StaleCheckpoint {
+                    timestamp,
+                    age,
+                    max_staleness,
+                }),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+/// Represents the options for connecting to a registry.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectOptions {
+    /// The addresses to resolve host names to instead of using DNS (e.g. to
+    /// resolve `registry.example` to a fixed IP address for testing).
+    ///
+    /// Connections use the port of the registry URL.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub dns_overrides: IndexMap<String, Vec<IpAddr>>,
+    /// The local address to bind connections to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_address: Option<IpAddr>,
+    /// The name of the network interface to bind connections to.
+    ///
+    /// Only supported on Linux, Android and Fuchsia.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    /// The IP address family used to connect.
+    ///
+    /// The family does not apply to the addresses of DNS overrides.
+    #[serde(default)]
+    pub ip_family: IpFamily,
+}
+
+impl ConnectOptions {
+    /// Resolves the given host name to the given addresses instead of using
+    /// DNS.
+    pub fn with_dns_override(
+        mut self,
+        host: impl Into<String>,
+        addrs: impl IntoIterator<Item = IpAddr>,
+    ) -> Self {
+        self.dns_overrides
+            .insert(host.into(), addrs.into_iter().collect());
+        self
+    }
+
+    /// Binds connections to the given local address.
+    pub fn with_local_address(mut self, addr: IpAddr) -> Self {
+        self.local_address = Some(addr);
+        self
+    }
+
+    /// Binds connections to the network interface with the given name.
+    pub fn with_interface(mut self, interface: impl Into<String>) -> Self {
+        self.interface = Some(interface.into());
+        self
+    }
+
+    /// Sets the IP address family used to connect.
+    pub fn with_ip_family(mut self, family: IpFamily) -> Self {
+        self.ip_family = family;
+        self
+    }
+
+    /// Determines if the options are the defaults.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Applies the options to an HTTP client builder.
+    fn apply(&self, mut builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder> {
+        for (host, addrs) in &self.dns_overrides {
+            // The port of an override is ignored in favor of the URL's port
+            let addrs: Vec<_> = addrs.iter().map(|ip| SocketAddr::new(*ip, 0)).collect();
+            builder = builder.resolve_to_addrs(host, &addrs);
+        }
+
+        builder = builder.local_address(self.local_address);
+
+        if let Some(interface) = &self.interface {
+            #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+            {
+                builder = builder.interface(interface);
+            }
+            #[cfg(not(any(target_os = "android", target_os = "fuchsia", target_os = "linux")))]
+            return Err(anyhow!(
+                "binding to network interface `{interface}` is not supported on this platform"
+            ));
+        }
+
+        if self.ip_family != IpFamily::Any {
+            builder = builder.dns_resolver(Arc::new(FamilyResolver(self.ip_family)));
+        }
+
+        Ok(builder)
+    }
+}
+
+/// A DNS resolver that filters and orders the addresses of the system
+/// resolver by IP address family.
+struct FamilyResolver(IpFamily);
+
+impl Resolve for FamilyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let family = self.0;
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs = family.apply(tokio::net::lookup_host((host.as_str(), 0)).await?);
+            if addrs.is_empty() {
+                return Err(
+                    anyhow!("host `{host}` has no addresses of IP family `{family:?}`").into(),
+                );
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
 /// Represents a Warg API client for communicating with
 /// a Warg registry server.
 pub struct Client {
     url: RegistryUrl,
-    client: reqwest::Client,
+    client: HttpClient,
     warg_registry_header: Option<RegistryDomain>,
     auth_token: Option<Secret<String>>,
+    user_agent_suffix: Option<String>,
+    headers: HeaderMap,
+    connect: ConnectOptions,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    offline: bool,
+    checkpoint_clock: CheckpointClock,
+    middleware: Vec<Arc<dyn Middleware>>,
+    limiter: Limiter,
+    discovery_ttl: Duration,
+    discovery: tokio::sync::Mutex<Option<(Instant, Arc<Discovery>)>>,
+    capabilities: OnceCell<CapabilitiesResponse>,
+    checkpoints: Mutex<HashMap<Option<RegistryDomain>, CachedCheckpoint>>,
 }
 
 impl Client {
     /// Creates a new API client with the given URL.
+    ///
+    /// The registry is discovered from the `.well-known/warg.json` document
+    /// of the URL before the first request is sent; requests are sent to
+    /// the API URL of the document, if present. Discovery results are
+    /// cached by the client for five minutes by default; see
+    /// [`Client::with_discovery_ttl`].
     pub fn new(url: impl IntoUrl, auth_token: Option<Secret<String>>) -> Result<Self> {
         let url = RegistryUrl::new(url)?;
-        Ok(Self {
+        let mut client = Self {
             url,
-            client: reqwest::Client::new(),
+            client: HttpClient::new(reqwest::Client::new(), HeaderMap::new(), []),
             warg_registry_header: None,
             auth_token,
-        })
+            user_agent_suffix: None,
+            headers: HeaderMap::new(),
+            connect: ConnectOptions::default(),
+            timeout: None,
+            connect_timeout: None,
+            offline: false,
+            checkpoint_clock: CheckpointClock::default(),
+            middleware: Vec::new(),
+            limiter: Limiter::default(),
+            discovery_ttl: DEFAULT_DISCOVERY_TTL,
+            discovery: Default::default(),
+            capabilities: OnceCell::new(),
+            checkpoints: Default::default(),
+        };
+        client.client = client.build_http_client()?;
+        Ok(client)
     }
 
     /// Gets auth token
@@ -223,209 +1018,1274 @@ impl Client {
     pub fn url(&self) -> &RegistryUrl {
         &self.url
     }
-    /// Gets the `.well-known` configuration registry URL.
-    pub async fn well_known_config(&self) -> Result<Option<RegistryUrl>, ClientError> {
-        let url = self.url.join(WELL_KNOWN_PATH);
-        tracing::debug!(url, "getting `.well-known` config",);
 
-        let res = self.client.get(url).send().await?;
+    /// Appends the given suffix to the `User-Agent` header of requests.
+    ///
+    /// By default, the `User-Agent` header is `warg-client/<version>`.
+    pub fn with_user_agent_suffix(mut self, suffix: impl Into<String>) -> Result<Self> {
+        self.user_agent_suffix = Some(suffix.into());
+        self.client = self.build_http_client()?;
+        Ok(self)
+    }
 
-        if !res.status().is_success() {
-            tracing::debug!(
-                "the `.well-known` config request returned HTTP status `{status}`",
-                status = res.status()
-            );
-            return Ok(None);
+    /// Adds a header that is sent with every request.
+    ///
+    /// This may be used to attribute traffic to a tool or build.
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+        self.headers.insert(
+            HeaderName::try_from(name)
+                .with_context(|| format!("invalid request header name `{name}`"))?,
+            HeaderValue::try_from(value)
+                .with_context(|| format!("invalid value for request header `{name}`"))?,
+        );
+        self.client = self.build_http_client()?;
+        Ok(self)
+    }
+
+    /// Sets the options for connecting to the registry.
+    ///
+    /// The options apply to the discovery of the registry and to all
+    /// requests sent to it.
+    pub fn with_connect_options(mut self, options: ConnectOptions) -> Result<Self> {
+        self.connect = options;
+        self.client = self.build_http_client()?;
+        Ok(self)
+    }
+
+    /// Sets the timeout of requests, from connecting until the response
+    /// body has been read.
+    ///
+    /// Requests with their own timeout (e.g. fetching logs with
+    /// [`Client::fetch_logs_with_timeout`]) use that timeout instead.
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.timeout = Some(timeout);
+        self.client = self.build_http_client()?;
+        Ok(self)
+    }
+
+    /// Sets the timeout of connecting to the registry.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.connect_timeout = Some(timeout);
+        self.client = self.build_http_client()?;
+        Ok(self)
+    }
+
+    /// Sets the duration for which the discovery of the registry is cached.
+    ///
+    /// Failed discoveries are never cached.
+    pub fn with_discovery_ttl(mut self, ttl: Duration) -> Self {
+        self.discovery_ttl = ttl;
+        self
+    }
+
+    /// Sets whether the client is offline.
+    ///
+    /// An offline client sends no requests, including the requests that
+    /// discover the registry; every request fails with
+    /// [`ClientError::Offline`].
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self.client.set_offline(offline);
+        self
+    }
+
+    /// Determines if the client is offline.
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Sets the validation of the timestamps of the latest checkpoints of
+    /// the registry against the local clock.
+    pub fn with_checkpoint_clock(mut self, clock: CheckpointClock) -> Self {
+        self.checkpoint_clock = clock;
+        self
+    }
+
+    /// Adds a middleware that intercepts every request sent by the client.
+    ///
+    /// Middleware is run in the order it is added.
+    pub fn with_middleware(mut self, middleware: impl Middleware) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self.client.set_middleware(self.middleware.as_slice());
+        self
+    }
+
+    /// Sets the limits on the concurrency of the client.
+    ///
+    /// The limits are shared by every operation of the client.
+    pub fn with_concurrency_limits(mut self, limits: ConcurrencyLimits) -> Self {
+        self.limiter = Limiter::new(limits);
+        self.client.set_request_limit(self.limiter.requests.clone());
+        self
+    }
+
+    /// Gets the limits on the concurrency of the client.
+    pub fn concurrency_limits(&self) -> ConcurrencyLimits {
+        self.limiter.limits
+    }
+
+    fn build_http_client(&self) -> Result<HttpClient> {
+        let user_agent = match &self.user_agent_suffix {
+            Some(suffix) => format!("{USER_AGENT} {suffix}"),
+            None => USER_AGENT.to_string(),
+        };
+
+        let mut headers = self.headers.clone();
+        headers.insert(
+            USER_AGENT_HEADER,
+            HeaderValue::try_from(user_agent).context("invalid `User-Agent` header")?,
+        );
+
+        let mut builder = self.connect.apply(reqwest::Client::builder())?;
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
         }
 
-        if let Some(warg_url) = res
-            .json::<WellKnownConfig>()
-            .await
-            .map_err(|e| {
-                tracing::debug!("parsing `.well-known` config failed: {e}");
-                ClientError::InvalidWellKnownConfig(self.url.registry_domain().to_string())
-            })?
-            .warg_url
-        {
-            Ok(Some(RegistryUrl::new(warg_url)?))
-        } else {
-            tracing::debug!("the `.well-known` config did not have a `wargUrl` set");
-            Ok(None)
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
         }
+
+        let client = builder.build().context("failed to create HTTP client")?;
+        let mut client = HttpClient::new(client, headers, self.middleware.as_slice());
+        client.set_offline(self.offline);
+        client.set_request_limit(self.limiter.requests.clone());
+        Ok(client)
     }
 
-    /// Gets the latest checkpoint from the registry.
-    pub async fn latest_checkpoint(
-        &self,
-        registry_domain: Option<&RegistryDomain>,
+    /// Waits for a CPU-bound verification to be allowed to run.
+    ///
+    /// The verification counts against the limit of verification workers
+    /// until the returned permit is dropped.
+    pub(crate) async fn verification_permit(&self) -> Option<OwnedSemaphorePermit> {
+        self.limiter.verification.acquire().await
+    }
+
+    /// Gets the discovery document of the registry.
+    ///
+    /// Returns `Ok(None)` if the registry does not serve a discovery document.
+    pub async fn discovery(&self) -> Result<Option<DiscoveryDocument>, ClientError> {
+        Ok(self.discover().await?.document.clone())
+    }
+
+    /// Gets the base URL of the registry API.
+    ///
+    /// This is the API URL of the registry's discovery document or, if not
+    /// present, the URL of the API client.
+    pub async fn api_url(&self) -> Result<RegistryUrl, ClientError> {
+        Ok(self.discover().await?.api_url.clone())
+    }
+
+    /// Determines if the registry is a static registry.
+    ///
+    /// A static registry is a read-only set of files laid out as described
+    /// by [`static_registry`] on a static host; the client reads the files
+    /// in place of sending requests to the registry API.
+    pub async fn is_static(&self) -> Result<bool, ClientError> {
+        Ok(self
+            .discovery()
+            .await?
+            .is_some_and(|document| document.layout == RegistryLayout::Static))
+    }
+
+    /// Gets a file of a static registry.
+    ///
+    /// Returns `Ok(None)` if the file does not exist.
+    async fn get_static<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>, ClientError> {
+        let url = self.api_url().await?.join(path);
+        tracing::debug!(url, "getting static registry file");
+
+        let response = throttled(
+            self.client
+                .get(url)
+                .auth(self.auth_token())
+                .dispatch()
+                .await?,
+        )
+        .await?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !status.is_success() {
+            return Err(ClientError::UnexpectedResponse {
+                status,
+                message: format!("failed to get static registry file `{path}`"),
+            });
+        }
+
+        // Static hosts may not serve the files as JSON, so the content type
+        // of the response is not checked
+        let bytes = response.bytes().await?;
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| ClientError::UnexpectedResponse {
+                status,
+                message: format!("failed to deserialize static registry file `{path}`: {e}"),
+            })
+    }
+
+    async fn discover(&self) -> Result<Arc<Discovery>, ClientError> {
+        // The lock is held while fetching so that concurrent requests share
+        // a single discovery of the registry
+        let mut cached = self.discovery.lock().await;
+        if let Some((discovered, discovery)) = cached.as_ref() {
+            if discovered.elapsed() < self.discovery_ttl {
+                return Ok(discovery.clone());
+            }
+        }
+
+        let discovery = Arc::new(self.fetch_discovery().await?);
+        *cached = discovery
+            .cacheable
+            .then(|| (Instant::now(), discovery.clone()));
+        Ok(discovery)
+    }
+
+    async fn fetch_discovery(&self) -> Result<Discovery, ClientError> {
+        let url = self.url.join(DISCOVERY_PATH);
+        tracing::debug!(url, "getting discovery document");
+
+        // A throttled request is an error rather than a missing document, as
+        // the discovery is cached
+        let res = throttled(self.client.get(url).dispatch().await?).await?;
+        if !res.status().is_success() {
+            tracing::debug!(
+                "the discovery document request returned HTTP status `{status}`",
+                status = res.status()
+            );
+            // Only a missing document is cached; other statuses may be
+            // transient failures of the registry
+            return Ok(Discovery {
+                api_url: self.url.clone(),
+                document: None,
+                cacheable: res.status() == StatusCode::NOT_FOUND,
+            });
+        }
+
+        let registry = self.url.registry_domain().to_string();
+        let document = res.json::<DiscoveryDocument>().await.map_err(|e| {
+            tracing::debug!("parsing discovery document failed: {e}");
+            ClientError::InvalidDiscoveryDocument(registry.clone())
+        })?;
+
+        for (kind, supported, algorithm) in [
+            (
+                "hash",
+                &document.hash_algorithms,
+                HashAlgorithm::Sha256.to_string(),
+            ),
+            (
+                "signature",
+                &document.signature_algorithms,
+                SignatureAlgorithm::EcdsaP256.to_string(),
+            ),
+        ] {
+            if !supported.is_empty() && !supported.contains(&algorithm) {
+                return Err(ClientError::UnsupportedAlgorithm {
+                    registry,
+                    kind,
+                    algorithm,
+                });
+            }
+        }
+
+        let api_url = match &document.api_url {
+            Some(url) => RegistryUrl::new(url.as_str())
+                .map_err(|_| ClientError::InvalidDiscoveryDocument(registry))?,
+            None => self.url.clone(),
+        };
+
+        Ok(Discovery {
+            api_url,
+            document: Some(document),
+            cacheable: true,
+        })
+    }
+
+    /// Gets the capabilities of the registry.
+    ///
+    /// The capabilities are fetched once and cached for the lifetime of
+    /// the API client; a registry that does not serve its capabilities is
+    /// treated as having none.
+    pub async fn capabilities(&self) -> Result<&CapabilitiesResponse, ClientError> {
+        self.capabilities
+            .get_or_try_init(|| async {
+                // A static registry has no optional capabilities
+                if self.is_static().await? {
+                    return Ok(CapabilitiesResponse::default());
+                }
+
+                let url = self.api_url().await?.join(paths::capabilities());
+                tracing::debug!(url, "getting registry capabilities");
+
+                // A throttled request is an error rather than missing
+                // capabilities, as the capabilities are cached
+                let res = throttled(
+                    self.client
+                        .get(url)
+                        .auth(self.auth_token())
+                        .dispatch()
+                        .await?,
+                )
+                .await?;
+                if !res.status().is_success() {
+                    tracing::debug!(
+                        "the capabilities request returned HTTP status `{status}`",
+                        status = res.status()
+                    );
+                    return Ok(CapabilitiesResponse::default());
+                }
+
+                deserialize(res).await
+            })
+            .await
+    }
+
+    /// Negotiates the version of the given API with the registry.
+    ///
+    /// The most preferred version supported by both the client and the
+    /// registry is chosen; registries that do not advertise the versions of
+    /// an API are assumed to support version 1.
+    pub async fn api_version(&self, api: Api) -> Result<ApiVersion, ClientError> {
+        let Some(versions) = self.capabilities().await?.api_versions.get(api.name()) else {
+            return Ok(ApiVersion::V1);
+        };
+
+        SUPPORTED_API_VERSIONS
+            .iter()
+            .find(|v| versions.contains(v))
+            .copied()
+            .ok_or(ClientError::UnsupportedApiVersion(api.name()))
+    }
+
+    /// Gets the URL of a path of the given API for the negotiated version.
+    async fn endpoint_url(&self, api: Api, path: &str) -> Result<String, ClientError> {
+        match self.api_version(api).await? {
+            ApiVersion::V1 => Ok(self.api_url().await?.join(path)),
+            ApiVersion::Unknown => unreachable!("unknown API versions are never negotiated"),
+        }
+    }
+
+    /// Gets the `.well-known` configuration registry URL.
+    pub async fn well_known_config(&self) -> Result<Option<RegistryUrl>, ClientError> {
+        let url = self.url.join(WELL_KNOWN_PATH);
+        tracing::debug!(url, "getting `.well-known` config",);
+
+        let res = self.client.get(url).dispatch().await?;
+
+        if !res.status().is_success() {
+            tracing::debug!(
+                "the `.well-known` config request returned HTTP status `{status}`",
+                status = res.status()
+            );
+            return Ok(None);
+        }
+
+        if let Some(warg_url) = res
+            .json::<WellKnownConfig>()
+            .await
+            .map_err(|e| {
+                tracing::debug!("parsing `.well-known` config failed: {e}");
+                ClientError::InvalidWellKnownConfig(self.url.registry_domain().to_string())
+            })?
+            .warg_url
+        {
+            Ok(Some(RegistryUrl::new(warg_url)?))
+        } else {
+            tracing::debug!("the `.well-known` config did not have a `wargUrl` set");
+            Ok(None)
+        }
+    }
+
+    /// Gets the latest checkpoint from the registry.
+    ///
+    /// The last checkpoint fetched is cached; later requests are conditional
+    /// and return the cached checkpoint if the registry reports it as not
+    /// modified.
+    ///
+    /// The timestamp of the checkpoint is validated against the local clock
+    /// as configured with [`Client::with_checkpoint_clock`].
+    pub async fn latest_checkpoint(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
     ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, ClientError> {
-        let url = self.url.join(paths::fetch_checkpoint());
+        let checkpoint = self.fetch_latest_checkpoint(registry_domain).await?;
+        self.checkpoint_clock
+            .validate(checkpoint.as_ref(), SystemTime::now())?;
+        Ok(checkpoint)
+    }
+
+    async fn fetch_latest_checkpoint(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, ClientError> {
+        let is_static = self.is_static().await?;
+        let url = if is_static {
+            self.api_url().await?.join(static_registry::CHECKPOINT_PATH)
+        } else {
+            self.endpoint_url(Api::Fetch, paths::fetch_checkpoint())
+                .await?
+        };
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "getting latest checkpoint",
+        );
+
+        // Send a conditional request if the checkpoint was previously fetched
+        let cached = self
+            .checkpoints
+            .lock()
+            .unwrap()
+            .get(&registry_domain.cloned())
+            .cloned();
+        let mut request = self
+            .client
+            .get(url)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token());
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            // The modification times of static files have a resolution of
+            // a second, so a checkpoint exported within the same second as
+            // the cached checkpoint would be reported as not modified
+            if let Some(last_modified) = cached.last_modified.as_ref().filter(|_| !is_static) {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.dispatch().await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                tracing::debug!("latest checkpoint was not modified");
+                return Ok(cached.checkpoint);
+            }
+        }
+
+        let etag = response.headers().get(ETAG).cloned();
+        let last_modified = response.headers().get(LAST_MODIFIED).cloned();
+        let checkpoint: SerdeEnvelope<TimestampedCheckpoint> =
+            into_result::<_, FetchError>(response).await?;
+
+        if etag.is_some() || last_modified.is_some() {
+            self.checkpoints.lock().unwrap().insert(
+                registry_domain.cloned(),
+                CachedCheckpoint {
+                    etag,
+                    last_modified,
+                    checkpoint: checkpoint.clone(),
+                },
+            );
+        }
+
+        Ok(checkpoint)
+    }
+
+    /// Verify checkpoint of the registry.
+    pub async fn verify_checkpoint(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<CheckpointVerificationResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Monitor, paths::verify_checkpoint())
+            .await?;
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "verifying checkpoint",
+        );
+
+        let response = self
+            .client
+            .post(url)
+            .json(&request)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+        into_result::<_, MonitorError>(response).await
+    }
+
+    /// Fetches package log entries from the registry.
+    pub async fn fetch_logs(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchLogsRequest<'_>,
+    ) -> Result<FetchLogsResponse, ClientError> {
+        self.send_fetch_logs(registry_domain, request, None).await
+    }
+
+    /// Fetches logs from the registry, abandoning the request if it takes
+    /// longer than the given timeout.
+    ///
+    /// A request that times out fails with an error for which
+    /// [`ClientError::is_timeout`] is `true`.
+    pub async fn fetch_logs_with_timeout(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchLogsRequest<'_>,
+        timeout: Duration,
+    ) -> Result<FetchLogsResponse, ClientError> {
+        self.send_fetch_logs(registry_domain, request, Some(timeout))
+            .await
+    }
+
+    async fn send_fetch_logs(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchLogsRequest<'_>,
+        timeout: Option<Duration>,
+    ) -> Result<FetchLogsResponse, ClientError> {
+        if self.is_static().await? {
+            return self.fetch_static_logs(request).await;
+        }
+
+        let response = self
+            .fetch_logs_request(registry_domain, request, timeout, false)
+            .await?;
+        fetch_logs_result(response).await
+    }
+
+    /// Fetches logs from the registry as a streamed response.
+    ///
+    /// Each item of the returned stream is a partial response with the
+    /// records of a single log, so the records of a log may be validated
+    /// before the rest of the response is received; `more` is only set on
+    /// the last item. A registry that does not stream responses returns a
+    /// single item with the entire response.
+    pub async fn fetch_logs_stream(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchLogsRequest<'_>,
+    ) -> Result<BoxStream<'static, Result<FetchLogsResponse, ClientError>>, ClientError> {
+        self.send_fetch_logs_stream(registry_domain, request, None)
+            .await
+    }
+
+    /// Fetches logs from the registry as a streamed response, abandoning the
+    /// request if it takes longer than the given timeout.
+    ///
+    /// The timeout applies to the entire response, so the stream may end
+    /// with an error for which [`ClientError::is_timeout`] is `true`.
+    pub async fn fetch_logs_stream_with_timeout(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchLogsRequest<'_>,
+        timeout: Duration,
+    ) -> Result<BoxStream<'static, Result<FetchLogsResponse, ClientError>>, ClientError> {
+        self.send_fetch_logs_stream(registry_domain, request, Some(timeout))
+            .await
+    }
+
+    async fn send_fetch_logs_stream(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchLogsRequest<'_>,
+        timeout: Option<Duration>,
+    ) -> Result<BoxStream<'static, Result<FetchLogsResponse, ClientError>>, ClientError> {
+        if self.is_static().await? {
+            let response = self.fetch_static_logs(request).await;
+            return Ok(once(ready(response)).boxed());
+        }
+
+        let response = self
+            .fetch_logs_request(registry_domain, request, timeout, true)
+            .await?;
+
+        let streamed = response.status().is_success()
+            && response
+                .headers()
+                .get(CONTENT_TYPE)
+                .is_some_and(|ty| ty == FETCH_LOGS_STREAM_CONTENT_TYPE);
+        if !streamed {
+            return Ok(once(fetch_logs_result(response)).boxed());
+        }
+
+        Ok(FetchLogsStream::new(response).into_stream().boxed())
+    }
+
+    /// Fetches logs from the log files of a static registry.
+    ///
+    /// A static log file contains every record of the log, so the response
+    /// never has more records to fetch.
+    async fn fetch_static_logs(
+        &self,
+        request: FetchLogsRequest<'_>,
+    ) -> Result<FetchLogsResponse, ClientError> {
+        let operator = self
+            .fetch_static_log(
+                &LogId::operator_log::<Sha256>(),
+                static_registry::OPERATOR_LOG_PATH,
+                request.log_length,
+                request.operator.as_deref(),
+            )
+            .await?;
+
+        let mut packages = IndexMap::with_capacity(request.packages.len());
+        for (log_id, since) in request.packages.iter() {
+            let records = self
+                .fetch_static_log(
+                    log_id,
+                    &static_registry::package_log(log_id),
+                    request.log_length,
+                    since.as_deref(),
+                )
+                .await?;
+            packages.insert(log_id.clone(), records);
+        }
+
+        Ok(FetchLogsResponse {
+            more: false,
+            operator,
+            packages,
+            warnings: Vec::new(),
+        })
+    }
+
+    async fn fetch_static_log(
+        &self,
+        log_id: &LogId,
+        path: &str,
+        log_length: RegistryLen,
+        since: Option<&str>,
+    ) -> Result<Vec<PublishedRecord>, ClientError> {
+        let log: StaticLog = self
+            .get_static(path)
+            .await?
+            .ok_or_else(|| FetchError::LogNotFound(log_id.clone()))?;
+
+        let mut records = log
+            .records
+            .into_iter()
+            .filter(|record| record.envelope.registry_index < log_length);
+        if let Some(since) = since {
+            if !records.by_ref().any(|record| record.fetch_token == since) {
+                return Err(FetchError::FetchTokenNotFound(since.to_string()).into());
+            }
+        }
+
+        Ok(records.collect())
+    }
+
+    async fn fetch_logs_request(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchLogsRequest<'_>,
+        timeout: Option<Duration>,
+        stream: bool,
+    ) -> Result<Response, ClientError> {
+        let url = self.endpoint_url(Api::Fetch, paths::fetch_logs()).await?;
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            stream,
+            "fetching logs",
+        );
+        let mut builder = self.client.post(&url).json(&request);
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        if stream {
+            builder = builder.header(
+                ACCEPT,
+                format!("{FETCH_LOGS_STREAM_CONTENT_TYPE}, application/json"),
+            );
+        }
+
+        builder
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await
+    }
+
+    /// Fetches package names from the registry.
+    pub async fn fetch_package_names(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchPackageNamesRequest<'_>,
+    ) -> Result<FetchPackageNamesResponse, ClientError> {
+        if self.is_static().await? {
+            let names: StaticPackageNames = self
+                .get_static(static_registry::PACKAGE_NAMES_PATH)
+                .await?
+                .unwrap_or_default();
+            return Ok(FetchPackageNamesResponse {
+                packages: request
+                    .packages
+                    .iter()
+                    .map(|log_id| (log_id.clone(), names.packages.get(log_id).cloned()))
+                    .collect(),
+            });
+        }
+
+        let url = self
+            .endpoint_url(Api::Fetch, paths::fetch_package_names())
+            .await?;
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "fetching package names",
+        );
+        let response = self
+            .client
+            .post(url)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .json(&request)
+            .dispatch()
+            .await?;
+        into_result::<_, FetchError>(response).await
+    }
+
+    /// Lists the namespaces defined or imported by the registry.
+    pub async fn namespaces(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+    ) -> Result<NamespacesResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Namespace, paths::namespaces())
+            .await?;
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "listing namespaces",
+        );
+        into_result::<_, NamespaceError>(
+            self.client
+                .get(url)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .dispatch()
+                .await?,
+        )
+        .await
+    }
+
+    /// Gets ledger sources from the registry.
+    pub async fn ledger_sources(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+    ) -> Result<LedgerSourcesResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Ledger, paths::ledger_sources())
+            .await?;
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "getting ledger sources",
+        );
+        into_result::<_, LedgerError>(
+            self.client
+                .get(url)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .dispatch()
+                .await?,
+        )
+        .await
+    }
+
+    /// Gets the log leafs of a ledger source, in registry index order.
+    ///
+    /// The leafs start at the source's first registry index; a source may
+    /// return leafs beyond its last registry index.
+    pub async fn ledger_leafs(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        hash_algorithm: HashAlgorithm,
+        source: &LedgerSource,
+    ) -> Result<Vec<LogLeaf>, ClientError> {
+        if hash_algorithm != HashAlgorithm::Sha256 {
+            return Err(ClientError::UnsupportedAlgorithm {
+                registry: self.url.registry_domain().to_string(),
+                kind: "hash",
+                algorithm: hash_algorithm.to_string(),
+            });
+        }
+
+        let url = if source.url.contains("://") {
+            source.url.clone()
+        } else {
+            self.api_url().await?.join(&source.url)
+        };
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "getting ledger records",
+        );
+
+        let response = self
+            .client
+            .get(url)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+        if !response.status().is_success() {
+            let response = throttled(response).await?;
+            return Err(deserialize::<LedgerError>(response).await?.into());
+        }
+
+        // Each leaf is a packed log id and record id
+        const HASH_LEN: usize = 32;
+        let bytes = response.bytes().await?;
+        if bytes.len() % (HASH_LEN * 2) != 0 {
+            return Err(ClientError::Other(anyhow!(
+                "ledger source `{url}` returned a partial record",
+                url = source.url
+            )));
+        }
+
+        Ok(bytes
+            .chunks_exact(HASH_LEN * 2)
+            .map(|leaf| {
+                let (log_id, record_id) = leaf.split_at(HASH_LEN);
+                LogLeaf {
+                    log_id: AnyHash::new(HashAlgorithm::Sha256, log_id.to_vec()).into(),
+                    record_id: AnyHash::new(HashAlgorithm::Sha256, record_id.to_vec()).into(),
+                }
+            })
+            .collect())
+    }
+
+    /// Publish a new record to a package log.
+    pub async fn publish_package_record(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_id: &LogId,
+        request: PublishRecordRequest<'_>,
+    ) -> Result<PackageRecord, ClientError> {
+        if self.is_static().await? {
+            return Err(ClientError::StaticRegistry("publishing"));
+        }
+
+        let url = self
+            .endpoint_url(Api::Package, &paths::publish_package_record(log_id))
+            .await?;
+        tracing::debug!(
+            log_id = log_id.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "publishing to package",
+        );
+        let response = self
+            .client
+            .post(url)
+            .json(&request)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+        into_result::<_, PackageError>(response).await
+    }
+
+    /// Gets the metadata of a package from the registry.
+    pub async fn get_package_metadata(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_id: &LogId,
+    ) -> Result<PackageMetadataResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Package, &paths::package_metadata(log_id))
+            .await?;
+        tracing::debug!(
+            log_id = log_id.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "getting package metadata",
+        );
+        let response = self
+            .client
+            .get(url)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        into_result::<_, PackageError>(response).await
+    }
+
+    /// Gets the signed snapshot of a package log at the checkpoint with the
+    /// given log length.
+    pub async fn get_package_snapshot(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_id: &LogId,
+        log_length: RegistryLen,
+    ) -> Result<PackageSnapshotResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Package, &paths::package_snapshot(log_id))
+            .await?;
+        tracing::debug!(
+            log_id = log_id.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "getting package snapshot",
+        );
+        let response = self
+            .client
+            .get(url)
+            .query(&PackageSnapshotQuery {
+                log_length: Some(log_length),
+            })
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        into_result::<_, PackageError>(response).await
+    }
+
+    /// Gets the vulnerability advisories of a package from the registry.
+    pub async fn get_package_advisories(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_id: &LogId,
+    ) -> Result<PackageAdvisoriesResponse, ClientError> {
+        // A static registry does not publish advisories
+        if self.is_static().await? {
+            return Ok(PackageAdvisoriesResponse {
+                advisories: Vec::new(),
+            });
+        }
+
+        let url = self
+            .endpoint_url(Api::Package, &paths::package_advisories(log_id))
+            .await?;
+        tracing::debug!(
+            log_id = log_id.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "getting package advisories",
+        );
+        let response = self
+            .client
+            .get(url)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        into_result::<_, PackageError>(response).await
+    }
+
+    /// Gets the key history of a package log from the registry.
+    pub async fn get_package_key_history(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_id: &LogId,
+    ) -> Result<PackageKeyHistoryResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Package, &paths::package_keys(log_id))
+            .await?;
+        tracing::debug!(
+            log_id = log_id.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "getting package key history",
+        );
+        let response = self
+            .client
+            .get(url)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        into_result::<_, PackageError>(response).await
+    }
+
+    /// Gets the invites of a package log from the registry.
+    pub async fn get_package_invites(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_id: &LogId,
+    ) -> Result<PackageInvitesResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Package, &paths::package_invites(log_id))
+            .await?;
+        tracing::debug!(
+            log_id = log_id.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "getting package invites",
+        );
+        let response = self
+            .client
+            .get(url)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        into_result::<_, PackageError>(response).await
+    }
+
+    /// Creates an invite of a package log on the registry.
+    pub async fn create_package_invite(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_id: &LogId,
+        request: &CreateInviteRequest,
+    ) -> Result<PackageInviteInfo, ClientError> {
+        let url = self
+            .endpoint_url(Api::Package, &paths::package_invites(log_id))
+            .await?;
+        tracing::debug!(
+            log_id = log_id.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "creating package invite",
+        );
+        let response = self
+            .client
+            .post(url)
+            .json(request)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        into_result::<_, PackageError>(response).await
+    }
+
+    /// Redeems an invite of a package log on the registry.
+    pub async fn redeem_package_invite(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_id: &LogId,
+        invite_id: &str,
+        request: &RedeemInviteRequest,
+    ) -> Result<PackageInviteInfo, ClientError> {
+        let url = self
+            .endpoint_url(
+                Api::Package,
+                &paths::package_invite_redeem(log_id, invite_id),
+            )
+            .await?;
         tracing::debug!(
+            log_id = log_id.to_string(),
+            invite_id,
             url,
             registry_header = ?registry_domain,
-            "getting latest checkpoint",
+            "redeeming package invite",
         );
-        into_result::<_, FetchError>(
-            self.client
-                .get(url)
-                .warg_header(registry_domain)?
-                .auth(self.auth_token())
-                .send()
-                .await?,
-        )
-        .await
+        let response = self
+            .client
+            .post(url)
+            .json(request)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        into_result::<_, PackageError>(response).await
     }
 
-    /// Verify checkpoint of the registry.
-    pub async fn verify_checkpoint(
+    /// Gets a package record from the registry.
+    pub async fn get_package_record(
         &self,
         registry_domain: Option<&RegistryDomain>,
-        request: SerdeEnvelope<TimestampedCheckpoint>,
-    ) -> Result<CheckpointVerificationResponse, ClientError> {
-        let url = self.url.join(paths::verify_checkpoint());
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<PackageRecord, ClientError> {
+        let url = self
+            .endpoint_url(Api::Package, &paths::package_record(log_id, record_id))
+            .await?;
         tracing::debug!(
+            log_id = log_id.to_string(),
+            record_id = record_id.to_string(),
             url,
             registry_header = ?registry_domain,
-            "verifying checkpoint",
+            "getting package record",
         );
-
         let response = self
             .client
-            .post(url)
-            .json(&request)
+            .get(url)
             .warg_header(registry_domain)?
             .auth(self.auth_token())
-            .send()
+            .dispatch()
             .await?;
-        into_result::<_, MonitorError>(response).await
+
+        package_record_result(response).await
     }
 
-    /// Fetches package log entries from the registry.
-    pub async fn fetch_logs(
+    /// Lists the most recently submitted records of a package log in the
+    /// registry.
+    pub async fn list_package_records(
         &self,
         registry_domain: Option<&RegistryDomain>,
-        request: FetchLogsRequest<'_>,
-    ) -> Result<FetchLogsResponse, ClientError> {
-        let url = self.url.join(paths::fetch_logs());
+        log_id: &LogId,
+        query: &ListRecordsQuery,
+    ) -> Result<PackageRecordsResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Package, &paths::package_records(log_id))
+            .await?;
         tracing::debug!(
+            log_id = log_id.to_string(),
             url,
             registry_header = ?registry_domain,
-            "fetching logs",
+            "listing package records",
         );
         let response = self
             .client
-            .post(&url)
-            .json(&request)
+            .get(url)
+            .query(query)
             .warg_header(registry_domain)?
             .auth(self.auth_token())
-            .send()
+            .dispatch()
             .await?;
 
-        let header = response.headers().get(REGISTRY_HINT_HEADER_NAME).cloned();
-        into_result::<_, FetchError>(response)
-            .await
-            .map_err(|err| match err {
-                ClientError::Fetch(FetchError::LogNotFound(log_id)) if header.is_some() => {
-                    ClientError::LogNotFoundWithHint(log_id, header.unwrap())
-                }
-                _ => err,
-            })
+        into_result::<_, PackageError>(response).await
     }
 
-    /// Fetches package names from the registry.
-    pub async fn fetch_package_names(
+    /// Awaits the processing of a package record in the registry.
+    ///
+    /// The request returns once the record is no longer processing or after
+    /// at most the given timeout, in which case the returned record is still
+    /// processing.
+    ///
+    /// This requires the registry to support the `events` capability.
+    pub async fn await_package_record(
         &self,
         registry_domain: Option<&RegistryDomain>,
-        request: FetchPackageNamesRequest<'_>,
-    ) -> Result<FetchPackageNamesResponse, ClientError> {
-        let url = self.url.join(paths::fetch_package_names());
+        log_id: &LogId,
+        record_id: &RecordId,
+        timeout: Duration,
+    ) -> Result<PackageRecord, ClientError> {
+        let url = self
+            .endpoint_url(
+                Api::Package,
+                &paths::package_record_await(log_id, record_id),
+            )
+            .await?;
         tracing::debug!(
+            log_id = log_id.to_string(),
+            record_id = record_id.to_string(),
             url,
             registry_header = ?registry_domain,
-            "fetching package names",
+            "awaiting package record",
         );
         let response = self
             .client
-            .post(url)
+            .get(url)
+            .query(&AwaitRecordQuery {
+                timeout_ms: Some(timeout.as_millis().try_into().unwrap_or(u64::MAX)),
+            })
             .warg_header(registry_domain)?
             .auth(self.auth_token())
-            .json(&request)
-            .send()
+            .dispatch()
             .await?;
-        into_result::<_, FetchError>(response).await
+
+        package_record_result(response).await
     }
 
-    /// Gets ledger sources from the registry.
-    pub async fn ledger_sources(
+    /// Commits a staged package record in the registry, submitting it for
+    /// processing.
+    ///
+    /// This requires the registry to support the `stagedPublishes`
+    /// capability.
+    pub async fn commit_package_record(
         &self,
         registry_domain: Option<&RegistryDomain>,
-    ) -> Result<LedgerSourcesResponse, ClientError> {
-        let url = self.url.join(paths::ledger_sources());
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<PackageRecord, ClientError> {
+        let url = self
+            .endpoint_url(
+                Api::Package,
+                &paths::package_record_commit(log_id, record_id),
+            )
+            .await?;
         tracing::debug!(
+            log_id = log_id.to_string(),
+            record_id = record_id.to_string(),
             url,
             registry_header = ?registry_domain,
-            "getting ledger sources",
+            "committing package record",
         );
-        into_result::<_, LedgerError>(
-            self.client
-                .get(url)
-                .warg_header(registry_domain)?
-                .auth(self.auth_token())
-                .send()
-                .await?,
-        )
-        .await
+        let response = self
+            .client
+            .post(url)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        package_record_result(response).await
     }
 
-    /// Publish a new record to a package log.
-    pub async fn publish_package_record(
+    /// Abandons a staged package record in the registry, rejecting it.
+    ///
+    /// This requires the registry to support the `stagedPublishes`
+    /// capability.
+    pub async fn abandon_package_record(
         &self,
         registry_domain: Option<&RegistryDomain>,
         log_id: &LogId,
-        request: PublishRecordRequest<'_>,
+        record_id: &RecordId,
     ) -> Result<PackageRecord, ClientError> {
-        let url = self.url.join(&paths::publish_package_record(log_id));
+        let url = self
+            .endpoint_url(
+                Api::Package,
+                &paths::package_record_abandon(log_id, record_id),
+            )
+            .await?;
         tracing::debug!(
             log_id = log_id.to_string(),
+            record_id = record_id.to_string(),
             url,
             registry_header = ?registry_domain,
-            "publishing to package",
+            "abandoning package record",
         );
         let response = self
             .client
             .post(url)
-            .json(&request)
             .warg_header(registry_domain)?
             .auth(self.auth_token())
-            .send()
+            .dispatch()
             .await?;
+
         into_result::<_, PackageError>(response).await
     }
 
-    /// Gets a package record from the registry.
-    pub async fn get_package_record(
+    /// Approves a package record awaiting approval in the registry,
+    /// submitting it for processing.
+    pub async fn approve_package_record(
         &self,
         registry_domain: Option<&RegistryDomain>,
         log_id: &LogId,
         record_id: &RecordId,
+        request: &ApproveRecordRequest,
     ) -> Result<PackageRecord, ClientError> {
-        let url = self.url.join(&paths::package_record(log_id, record_id));
+        let url = self
+            .endpoint_url(
+                Api::Package,
+                &paths::package_record_approve(log_id, record_id),
+            )
+            .await?;
         tracing::debug!(
             log_id = log_id.to_string(),
             record_id = record_id.to_string(),
             url,
             registry_header = ?registry_domain,
-            "getting package record",
+            "approving package record",
         );
-        into_result::<_, PackageError>(
-            self.client
-                .get(url)
-                .warg_header(registry_domain)?
-                .auth(self.auth_token())
-                .send()
-                .await?,
-        )
-        .await
+        let response = self
+            .client
+            .post(url)
+            .json(request)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+
+        package_record_result(response).await
     }
 
     /// Gets a content sources from the registry.
@@ -434,7 +2294,28 @@ impl Client {
         registry_domain: Option<&RegistryDomain>,
         digest: &AnyHash,
     ) -> Result<ContentSourcesResponse, ClientError> {
-        let url = self.url.join(&paths::content_sources(digest));
+        if self.is_static().await? {
+            let url = self
+                .api_url()
+                .await?
+                .join(&static_registry::content(digest));
+            return Ok(ContentSourcesResponse {
+                content_sources: [(
+                    digest.clone(),
+                    vec![ContentSource::HttpGet {
+                        url,
+                        accept_ranges: false,
+                        size: None,
+                    }],
+                )]
+                .into_iter()
+                .collect(),
+            });
+        }
+
+        let url = self
+            .endpoint_url(Api::Content, &paths::content_sources(digest))
+            .await?;
         tracing::debug!(
             digest = digest.to_string(),
             url,
@@ -446,7 +2327,7 @@ impl Client {
                 .get(url)
                 .warg_header(registry_domain)?
                 .auth(self.auth_token())
-                .send()
+                .dispatch()
                 .await?,
         )
         .await
@@ -458,6 +2339,23 @@ impl Client {
         registry_domain: Option<&RegistryDomain>,
         digest: &AnyHash,
     ) -> Result<impl Stream<Item = Result<Bytes>>, ClientError> {
+        Ok(self
+            .download_content_with_size(registry_domain, digest)
+            .await?
+            .1)
+    }
+
+    /// Downloads the content associated with a given record, along with the
+    /// expected size of the content in bytes.
+    ///
+    /// The size is taken from the content source if the registry provides
+    /// it, or otherwise from the `Content-Length` of the download response;
+    /// either is known before any of the content is read from the stream.
+    pub async fn download_content_with_size(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        digest: &AnyHash,
+    ) -> Result<(Option<u64>, impl Stream<Item = Result<Bytes>>), ClientError> {
         let ContentSourcesResponse { content_sources } =
             self.content_sources(registry_domain, digest).await?;
 
@@ -465,12 +2363,14 @@ impl Client {
             .get(digest)
             .ok_or(ClientError::AllSourcesFailed(digest.clone()))?;
 
+        // The download counts against the limit until its stream is dropped
+        let permit = self.limiter.downloads.acquire().await;
         for source in sources {
-            let ContentSource::HttpGet { url, .. } = source;
+            let ContentSource::HttpGet { url, size, .. } = source;
 
             tracing::debug!("downloading content `{digest}` from `{url}`");
 
-            let response = self.client.get(url).send().await?;
+            let response = self.client.get(url).dispatch().await?;
             if !response.status().is_success() {
                 tracing::debug!(
                     "failed to download content `{digest}` from `{url}`: {status}",
@@ -479,21 +2379,132 @@ impl Client {
                 continue;
             }
 
-            return Ok(validate_stream(
-                digest,
-                response.bytes_stream().map_err(|e| anyhow!(e)),
+            let size = size.or_else(|| response.content_length());
+            return Ok((
+                size,
+                validate_stream(digest, response.bytes_stream().map_err(|e| anyhow!(e))).map(
+                    move |chunk| {
+                        let _permit = &permit;
+                        chunk
+                    },
+                ),
             ));
         }
 
         Err(ClientError::AllSourcesFailed(digest.clone()))
     }
 
+    /// Gets the locations of the custom sections of content from the
+    /// registry.
+    ///
+    /// Requires the registry to support the `contentSections` capability.
+    pub async fn content_sections(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        digest: &AnyHash,
+    ) -> Result<ContentSectionsResponse, ClientError> {
+        let url = self
+            .endpoint_url(Api::Content, &paths::content_sections(digest))
+            .await?;
+        tracing::debug!(
+            digest = digest.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "getting content sections for digest",
+        );
+        into_result::<_, ContentError>(
+            self.client
+                .get(url)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .dispatch()
+                .await?,
+        )
+        .await
+    }
+
+    /// Downloads the given byte range of content.
+    ///
+    /// Only content sources that accept range requests are used.
+    ///
+    /// The downloaded bytes cannot be validated against the content digest;
+    /// callers must only rely on them to the extent they trust the registry.
+    pub async fn download_content_range(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        digest: &AnyHash,
+        range: Range<u64>,
+    ) -> Result<Bytes, ClientError> {
+        if range.is_empty() {
+            return Ok(Bytes::new());
+        }
+
+        let ContentSourcesResponse { content_sources } =
+            self.content_sources(registry_domain, digest).await?;
+
+        let sources = content_sources
+            .get(digest)
+            .ok_or(ClientError::AllSourcesFailed(digest.clone()))?;
+
+        for source in sources {
+            let ContentSource::HttpGet {
+                url, accept_ranges, ..
+            } = source;
+            if !accept_ranges {
+                continue;
+            }
+
+            tracing::debug!(
+                "downloading bytes {start}..{end} of content `{digest}` from `{url}`",
+                start = range.start,
+                end = range.end
+            );
+
+            let response = self
+                .client
+                .get(url)
+                .header(
+                    RANGE,
+                    format!(
+                        "bytes={start}-{end}",
+                        start = range.start,
+                        end = range.end - 1
+                    ),
+                )
+                .dispatch()
+                .await?;
+            if response.status() != StatusCode::PARTIAL_CONTENT {
+                tracing::debug!(
+                    "failed to download a range of content `{digest}` from `{url}`: {status}",
+                    status = response.status()
+                );
+                continue;
+            }
+
+            let bytes = response.bytes().await?;
+            if bytes.len() as u64 != range.end - range.start {
+                tracing::debug!(
+                    "range of content `{digest}` from `{url}` has an unexpected length of {len} bytes",
+                    len = bytes.len()
+                );
+                continue;
+            }
+
+            return Ok(bytes);
+        }
+
+        Err(ClientError::AllSourcesFailed(digest.clone()))
+    }
+
     /// Set warg-registry header value
     pub fn set_warg_registry(&mut self, registry: Option<RegistryDomain>) {
         self.warg_registry_header = registry;
     }
 
     /// Proves the inclusion of the given package log heads in the registry.
+    ///
+    /// If the registry supports the `multiProofs` capability, the map
+    /// inclusion proofs are requested as a single multi-proof.
     pub async fn prove_inclusion(
         &self,
         registry_domain: Option<&RegistryDomain>,
@@ -501,24 +2512,86 @@ impl Client {
         checkpoint: &Checkpoint,
         leafs: &[LogLeaf],
     ) -> Result<(), ClientError> {
-        let url = self.url.join(paths::prove_inclusion());
+        if self.is_static().await? {
+            return self.prove_static_inclusion(checkpoint, leafs).await;
+        }
+
+        let multi = self.capabilities().await?.multi_proofs;
+        let path = if multi {
+            paths::prove_multi_inclusion()
+        } else {
+            paths::prove_inclusion()
+        };
+        let url = self.endpoint_url(Api::Proof, path).await?;
         tracing::debug!(
             url,
             registry_header = ?registry_domain,
+            multi,
             "proving checkpoint inclusion",
         );
-        let response = into_result::<InclusionResponse, ProofError>(
-            self.client
-                .post(url)
-                .json(&request)
-                .warg_header(registry_domain)?
-                .auth(self.auth_token())
-                .send()
-                .await?,
-        )
-        .await?;
+        let response = self
+            .client
+            .post(url)
+            .json(&request)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .dispatch()
+            .await?;
+        let (log, map) = if multi {
+            let response = into_result::<MultiProofResponse, ProofError>(response).await?;
+            (response.log, response.map)
+        } else {
+            let response = into_result::<InclusionResponse, ProofError>(response).await?;
+            (response.log, response.map)
+        };
+
+        // Verify the proofs on the blocking thread pool, as Merkle
+        // verification is CPU-bound
+        let checkpoint = checkpoint.clone();
+        let leafs = leafs.to_vec();
+        let _permit = self.verification_permit().await;
+        tokio::task::spawn_blocking(move || {
+            if multi {
+                verify::verify_multi_inclusion(&checkpoint, &leafs, &log, &map)?;
+            } else {
+                verify::verify_inclusion(&checkpoint, &leafs, &log, &map)?;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow!("failed to verify inclusion proof: {e}"))?
+    }
+
+    /// Proves the inclusion of log heads with the precomputed proofs of a
+    /// static registry.
+    ///
+    /// A static registry only has inclusion proofs of the head of each log
+    /// for its checkpoint.
+    async fn prove_static_inclusion(
+        &self,
+        checkpoint: &Checkpoint,
+        leafs: &[LogLeaf],
+    ) -> Result<(), ClientError> {
+        let mut proofs = Vec::with_capacity(leafs.len());
+        for leaf in leafs {
+            let response: InclusionResponse = self
+                .get_static(&static_registry::inclusion_proof(&leaf.log_id))
+                .await?
+                .ok_or_else(|| ProofError::PackageLogNotIncluded(leaf.log_id.clone()))?;
+            proofs.push((response, leaf.clone()));
+        }
+
+        let checkpoint = checkpoint.clone();
+        let _permit = self.verification_permit().await;
+        tokio::task::spawn_blocking(move || {
+            for (response, leaf) in proofs {
+                verify::verify_inclusion(&checkpoint, &[leaf], &response.log, &response.map)?;
+            }
 
-        Self::validate_inclusion_response(response, checkpoint, leafs)
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow!("failed to verify inclusion proof: {e}"))?
     }
 
     /// Proves consistency between two log roots.
@@ -529,53 +2602,36 @@ impl Client {
         from_log_root: Cow<'_, AnyHash>,
         to_log_root: Cow<'_, AnyHash>,
     ) -> Result<(), ClientError> {
-        let url = self.url.join(paths::prove_consistency());
-        let response = into_result::<ConsistencyResponse, ProofError>(
-            self.client
-                .post(url)
-                .json(&request)
-                .warg_header(registry_domain)?
-                .auth(self.auth_token())
-                .send()
-                .await?,
-        )
-        .await?;
-
-        let proof = ProofBundle::<Sha256, LogLeaf>::decode(&response.proof).unwrap();
-        let (log_data, consistencies, inclusions) = proof.unbundle();
-        if !inclusions.is_empty() {
-            return Err(ClientError::Proof(ProofError::BundleFailure(
-                "expected no inclusion proofs".into(),
-            )));
-        }
-
-        if consistencies.len() != 1 {
-            return Err(ClientError::Proof(ProofError::BundleFailure(
-                "expected exactly one consistency proof".into(),
-            )));
-        }
-
-        let (from, to) = consistencies
-            .first()
-            .unwrap()
-            .evaluate(&log_data)
-            .map(|(from, to)| (AnyHash::from(from), AnyHash::from(to)))?;
-
-        if from_log_root.as_ref() != &from {
-            return Err(ClientError::IncorrectConsistencyProof {
-                root: from_log_root.into_owned(),
-                found: from,
-            });
-        }
-
-        if to_log_root.as_ref() != &to {
-            return Err(ClientError::IncorrectConsistencyProof {
-                root: to_log_root.into_owned(),
-                found: to,
-            });
-        }
+        let response = if self.is_static().await? {
+            // A static registry only has consistency proofs to its checkpoint
+            self.get_static(&static_registry::consistency_proof(request.from))
+                .await?
+                .ok_or(ProofError::CheckpointNotFound(request.from))?
+        } else {
+            let url = self
+                .endpoint_url(Api::Proof, paths::prove_consistency())
+                .await?;
+            into_result::<ConsistencyResponse, ProofError>(
+                self.client
+                    .post(url)
+                    .json(&request)
+                    .warg_header(registry_domain)?
+                    .auth(self.auth_token())
+                    .dispatch()
+                    .await?,
+            )
+            .await?
+        };
 
-        Ok(())
+        let from_log_root = from_log_root.into_owned();
+        let to_log_root = to_log_root.into_owned();
+        let _permit = self.verification_permit().await;
+        tokio::task::spawn_blocking(move || {
+            verify::verify_consistency(&from_log_root, &to_log_root, &response.proof)?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| anyhow!("failed to verify consistency proof: {e}"))?
     }
 
     /// Uploads package content to the registry.
@@ -587,7 +2643,7 @@ impl Client {
         content: impl Into<Body>,
     ) -> Result<(), ClientError> {
         // Upload URLs may be relative to the registry URL.
-        let url = self.url.join(url);
+        let url = self.api_url().await?.join(url);
 
         let method = match method {
             "POST" => Method::POST,
@@ -611,14 +2667,16 @@ impl Client {
 
         tracing::debug!("uploading content to `{url}`");
 
+        let _permit = self.limiter.uploads.acquire().await;
         let response = self
             .client
             .request(method, url)
             .headers(headers)
             .body(content)
-            .send()
+            .dispatch()
             .await?;
         if !response.status().is_success() {
+            let response = throttled(response).await?;
             return Err(ClientError::Package(
                 deserialize::<PackageError>(response).await?,
             ));
@@ -626,47 +2684,6 @@ impl Client {
 
         Ok(())
     }
-
-    fn validate_inclusion_response(
-        response: InclusionResponse,
-        checkpoint: &Checkpoint,
-        leafs: &[LogLeaf],
-    ) -> Result<(), ClientError> {
-        let log_proof_bundle: LogProofBundle<Sha256, LogLeaf> =
-            LogProofBundle::decode(response.log.as_slice())?;
-        let (log_data, _, log_inclusions) = log_proof_bundle.unbundle();
-        for (leaf, proof) in leafs.iter().zip(log_inclusions.iter()) {
-            let found = proof.evaluate_value(&log_data, leaf)?;
-            let root = checkpoint.log_root.clone().try_into()?;
-            if found != root {
-                return Err(ClientError::Proof(ProofError::IncorrectProof {
-                    root: checkpoint.log_root.clone(),
-                    found: found.into(),
-                }));
-            }
-        }
-
-        let map_proof_bundle: MapProofBundle<Sha256, LogId, MapLeaf> =
-            MapProofBundle::decode(response.map.as_slice())?;
-        let map_inclusions = map_proof_bundle.unbundle();
-        for (leaf, proof) in leafs.iter().zip(map_inclusions.iter()) {
-            let found = proof.evaluate(
-                &leaf.log_id,
-                &MapLeaf {
-                    record_id: leaf.record_id.clone(),
-                },
-            );
-            let root = checkpoint.map_root.clone().try_into()?;
-            if found != root {
-                return Err(ClientError::Proof(ProofError::IncorrectProof {
-                    root: checkpoint.map_root.clone(),
-                    found: found.into(),
-                }));
-            }
-        }
-
-        Ok(())
-    }
 }
 
 fn validate_stream(
@@ -0,0 +1,207 @@
+//! A module for auditing the integrity of the local content cache and the
+//! records signed by the client.
+
+use super::{Client, ClientResult};
+use crate::storage::{ContentStorage, NamespaceMapStorage, RegistryStorage, SigningAuditEntry};
+use futures_util::StreamExt;
+use indexmap::IndexMap;
+use std::time::SystemTime;
+use warg_crypto::{
+    hash::{AnyHash, Digest, Hash, HashAlgorithm, Sha256},
+    signing::KeyID,
+};
+use warg_protocol::registry::{PackageName, RecordId};
+
+/// Content in the cache that does not match its digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptContent {
+    /// The digest the content is stored under.
+    pub digest: AnyHash,
+    /// The digest of the stored content; `None` if it could not be read.
+    pub actual: Option<AnyHash>,
+    /// Whether the content was downloaded again and now matches its digest.
+    pub repaired: bool,
+}
+
+/// The result of auditing the content cache.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ContentAudit {
+    /// The number of content blobs checked.
+    pub checked: usize,
+    /// The content found to be corrupt.
+    pub corrupted: Vec<CorruptContent>,
+}
+
+impl ContentAudit {
+    /// Determines if all checked content matched its digest.
+    pub fn is_intact(&self) -> bool {
+        self.corrupted.is_empty()
+    }
+}
+
+/// A query of the audit trail of the records signed by the client.
+///
+/// Entries match the query if they match all of its criteria; the default
+/// query matches every entry.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SigningAuditQuery {
+    /// Matches records of the given package.
+    pub package: Option<PackageName>,
+    /// Matches records signed by the given key.
+    pub key_id: Option<KeyID>,
+    /// Matches the record with the given id.
+    pub record_id: Option<RecordId>,
+    /// Matches records with a timestamp at or after the given time.
+    pub since: Option<SystemTime>,
+    /// Matches records with a timestamp before the given time.
+    pub until: Option<SystemTime>,
+}
+
+impl SigningAuditQuery {
+    /// Determines if the given entry matches the query.
+    pub fn matches(&self, entry: &SigningAuditEntry) -> bool {
+        self.package.as_ref().map_or(true, |p| p == &entry.package)
+            && self.key_id.as_ref().map_or(true, |k| k == &entry.key_id)
+            && self
+                .record_id
+                .as_ref()
+                .map_or(true, |r| r == &entry.record_id)
+            && self.since.map_or(true, |since| entry.timestamp >= since)
+            && self.until.map_or(true, |until| entry.timestamp < until)
+    }
+}
+
+impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Gets the entries of the audit trail of the records signed by the
+    /// client that match the given query, in the order they were submitted.
+    ///
+    /// An entry is recorded for every package record the client signs and
+    /// submits, whatever the outcome of the submission.
+    pub async fn signing_audit(
+        &self,
+        query: &SigningAuditQuery,
+    ) -> ClientResult<Vec<SigningAuditEntry>> {
+        Ok(self
+            .registry
+            .load_signing_audit()
+            .await?
+            .into_iter()
+            .filter(|entry| query.matches(entry))
+            .collect())
+    }
+
+    /// Records a signed record in the audit trail.
+    ///
+    /// The record has already been submitted, so a failure to record it is
+    /// logged rather than returned.
+    pub(crate) async fn audit_signing(&self, entry: SigningAuditEntry) {
+        if let Err(e) = self.registry.append_signing_audit(&entry).await {
+            tracing::warn!(
+                "failed to record signed record `{record_id}` in the audit trail: {e:#}",
+                record_id = entry.record_id
+            );
+        }
+    }
+
+    /// Audits the content cache by hashing every stored blob and comparing it
+    /// to the digest it is stored under.
+    ///
+    /// If `repair` is `true`, corrupt content is removed from the cache and
+    /// downloaded again from the registry of a package that references it.
+    /// Content not referenced by a package in client storage is removed
+    /// but not downloaded.
+    pub async fn audit_content_cache(&self, repair: bool) -> ClientResult<ContentAudit> {
+        let mut audit = ContentAudit::default();
+        let mut references = None;
+
+        for digest in self.content.list_content().await? {
+            audit.checked += 1;
+
+            let actual = self.hash_content(&digest).await;
+            if actual.as_ref() == Some(&digest) {
+                continue;
+            }
+
+            tracing::warn!(
+                "content `{digest}` in the cache is corrupt (found digest {actual})",
+                actual = actual
+                    .as_ref()
+                    .map_or_else(|| "<unreadable>".to_string(), ToString::to_string)
+            );
+
+            let mut repaired = false;
+            if repair {
+                self.content.remove_content(&digest).await?;
+
+                if references.is_none() {
+                    references = Some(self.content_references().await?);
+                }
+                if let Some(name) = references.as_ref().unwrap().get(&digest) {
+                    let registry_domain = self.get_warg_registry(name.namespace()).await?;
+                    match self
+                        .download_content(registry_domain.as_ref(), &digest)
+                        .await
+                    {
+                        Ok(_) => repaired = true,
+                        Err(e) => {
+                            tracing::warn!("failed to download content `{digest}` again: {e}")
+                        }
+                    }
+                }
+            }
+
+            audit.corrupted.push(CorruptContent {
+                digest,
+                actual,
+                repaired,
+            });
+        }
+
+        Ok(audit)
+    }
+
+    /// Hashes the stored content of the given digest.
+    ///
+    /// Returns `None` if the content could not be read.
+    pub(crate) async fn hash_content(&self, digest: &AnyHash) -> Option<AnyHash> {
+        if digest.algorithm() != HashAlgorithm::Sha256 {
+            return None;
+        }
+
+        let mut stream = self.content.load_content(digest).await.ok()??;
+        let mut hasher = Sha256::new();
+        while let Some(bytes) = stream.next().await {
+            hasher.update(&bytes.ok()?);
+        }
+
+        Some(Hash::<Sha256>::from(hasher.finalize()).into())
+    }
+
+    /// Maps the content digests of releases and attestations in client
+    /// storage to a package that references them.
+    async fn content_references(&self) -> ClientResult<IndexMap<AnyHash, PackageName>> {
+        let mut references = IndexMap::new();
+        for package in self
+            .registry
+            .load_all_packages()
+            .await?
+            .into_values()
+            .flatten()
+        {
+            for release in package.state.releases() {
+                let attestations = package.state.attestations(&release.version);
+                for digest in release
+                    .content()
+                    .into_iter()
+                    .chain(attestations.iter().map(|a| &a.content))
+                {
+                    references
+                        .entry(digest.clone())
+                        .or_insert_with(|| package.name.clone());
+                }
+            }
+        }
+
+        Ok(references)
+    }
+}
@@ -0,0 +1,249 @@
+//! Module for building clients.
+//!
+//! A [`ClientBuilder`] configures a [`Client`] incrementally; every setting
+//! other than the registry URL and the storages has a default.
+
+use crate::{
+    api::{self, RetryPolicy},
+    limits::ConcurrencyLimits,
+    storage::{ContentStorage, NamespaceMapStorage, RegistryStorage},
+    Client, ClientError, ClientResult, RegistryUrl, DEFAULT_SYNC_BATCH_SIZE,
+};
+use anyhow::{anyhow, Result};
+use indexmap::{IndexMap, IndexSet};
+use reqwest::IntoUrl;
+use secrecy::Secret;
+use std::time::Duration;
+
+/// A builder of a [`Client`].
+///
+/// Create a builder with [`Client::builder`].
+pub struct ClientBuilder<R, C, N> {
+    url: Option<Result<RegistryUrl>>,
+    registry: Option<R>,
+    content: Option<C>,
+    namespace_map: Option<N>,
+    auth_token: Option<Secret<String>>,
+    ignore_federation_hints: bool,
+    auto_accept_federation_hints: bool,
+    disable_interactive: bool,
+    keyring_backend: Option<String>,
+    keys: IndexSet<String>,
+    retry_policy: RetryPolicy,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent_suffix: Option<String>,
+    offline: bool,
+    concurrency_limits: ConcurrencyLimits,
+}
+
+impl<R, C, N> Default for ClientBuilder<R, C, N> {
+    fn default() -> Self {
+        Self {
+            url: None,
+            registry: None,
+            content: None,
+            namespace_map: None,
+            auth_token: None,
+            ignore_federation_hints: false,
+            auto_accept_federation_hints: false,
+            disable_interactive: false,
+            keyring_backend: None,
+            keys: IndexSet::new(),
+            retry_policy: RetryPolicy::default(),
+            timeout: None,
+            connect_timeout: None,
+            user_agent_suffix: None,
+            offline: false,
+            concurrency_limits: ConcurrencyLimits::default(),
+        }
+    }
+}
+
+impl<R, C, N> ClientBuilder<R, C, N>
+where
+    R: RegistryStorage,
+    C: ContentStorage,
+    N: NamespaceMapStorage,
+{
+    /// Sets the URL of the registry.
+    ///
+    /// The URL is required.
+    pub fn with_url(mut self, url: impl IntoUrl) -> Self {
+        self.url = Some(RegistryUrl::new(url));
+        self
+    }
+
+    /// Sets the registry storage of the client.
+    ///
+    /// The registry storage is required.
+    pub fn with_registry_storage(mut self, registry: R) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Sets the content storage of the client.
+    ///
+    /// The content storage is required.
+    pub fn with_content_storage(mut self, content: C) -> Self {
+        self.content = Some(content);
+        self
+    }
+
+    /// Sets the namespace map storage of the client.
+    ///
+    /// The namespace map storage is required.
+    pub fn with_namespace_map_storage(mut self, namespace_map: N) -> Self {
+        self.namespace_map = Some(namespace_map);
+        self
+    }
+
+    /// Sets the token that authenticates requests to the registry.
+    ///
+    /// By default, requests are not authenticated.
+    pub fn with_auth_token(mut self, token: Option<Secret<String>>) -> Self {
+        self.auth_token = token;
+        self
+    }
+
+    /// Sets whether federation hints from the registry are ignored.
+    pub fn with_ignore_federation_hints(mut self, ignore: bool) -> Self {
+        self.ignore_federation_hints = ignore;
+        self
+    }
+
+    /// Sets whether federation hints from the registry are accepted without
+    /// prompting.
+    pub fn with_auto_accept_federation_hints(mut self, accept: bool) -> Self {
+        self.auto_accept_federation_hints = accept;
+        self
+    }
+
+    /// Sets whether the client never prompts or prints interactively.
+    pub fn with_disable_interactive(mut self, disable: bool) -> Self {
+        self.disable_interactive = disable;
+        self
+    }
+
+    /// Sets the keyring backend used for signing keys.
+    ///
+    /// By default, the platform's default backend is used.
+    pub fn with_keyring_backend(mut self, backend: Option<String>) -> Self {
+        self.keyring_backend = backend;
+        self
+    }
+
+    /// Sets the names of the signing keys in the keyring.
+    pub fn with_keys(mut self, keys: IndexSet<String>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Sets how requests throttled by the registry are retried.
+    ///
+    /// By default, throttled requests are retried up to three times.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Sets the timeout of requests to the registry.
+    ///
+    /// By default, requests do not time out.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout of connecting to the registry.
+    ///
+    /// By default, connecting does not time out.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Appends the given suffix to the `User-Agent` header of requests.
+    ///
+    /// By default, the `User-Agent` header is `warg-client/<version>`.
+    pub fn with_user_agent_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.user_agent_suffix = Some(suffix.into());
+        self
+    }
+
+    /// Sets whether the client is offline.
+    ///
+    /// An offline client operates only on its storage; operations that
+    /// contact the registry fail with [`api::ClientError::Offline`].
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Sets the limits on the concurrency of the client.
+    ///
+    /// By default, the concurrency of the client is not limited.
+    pub fn with_concurrency_limits(mut self, limits: ConcurrencyLimits) -> Self {
+        self.concurrency_limits = limits;
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// Fails if the URL or any of the storages has not been set.
+    pub fn build(self) -> ClientResult<Client<R, C, N>> {
+        let url = self.url.ok_or_else(|| missing("registry URL"))??;
+        let registry = self.registry.ok_or_else(|| missing("registry storage"))?;
+        let content = self.content.ok_or_else(|| missing("content storage"))?;
+        let namespace_map = self
+            .namespace_map
+            .ok_or_else(|| missing("namespace map storage"))?;
+
+        let mut api = api::Client::new(url.into_url(), self.auth_token)?
+            .with_offline(self.offline)
+            .with_concurrency_limits(self.concurrency_limits);
+        if let Some(suffix) = self.user_agent_suffix {
+            api = api.with_user_agent_suffix(suffix)?;
+        }
+
+        if let Some(timeout) = self.timeout {
+            api = api.with_timeout(timeout)?;
+        }
+
+        if let Some(timeout) = self.connect_timeout {
+            api = api.with_connect_timeout(timeout)?;
+        }
+
+        Ok(Client {
+            registry,
+            content,
+            namespace_map,
+            api,
+            ignore_federation_hints: self.ignore_federation_hints,
+            auto_accept_federation_hints: self.auto_accept_federation_hints,
+            disable_interactive: self.disable_interactive,
+            keyring_backend: self.keyring_backend,
+            keys: self.keys,
+            package_policy: None,
+            license_allowlist: None,
+            advisory_policy: None,
+            provenance_policy: None,
+            signing_policy: None,
+            semver_check: None,
+            namespace_overrides: IndexMap::new(),
+            sync_batch_size: DEFAULT_SYNC_BATCH_SIZE,
+            fetch_page_size: None,
+            max_checkpoint_age: None,
+            sync_progress: None,
+            storage_reserve: 0,
+            package_snapshots: false,
+            streaming_fetch: false,
+            retry_policy: self.retry_policy,
+            namespace_registries: Default::default(),
+        })
+    }
+}
+
+fn missing(what: &str) -> ClientError {
+    ClientError::Other(anyhow!("the {what} of the client was not set"))
+}
@@ -1,8 +1,13 @@
 //! Module for client configuration.
 
-use crate::{ClientError, RegistryUrl};
+use crate::{
+    api::{CheckpointClock, ConnectOptions},
+    policy::{AdvisoryPolicy, SigningPolicy},
+    storage::StorageLayout,
+    ClientError, RegistryUrl,
+};
 use anyhow::{anyhow, Context, Result};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use normpath::PathExt;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -15,6 +20,9 @@ use std::{
 static CACHE_DIR: Lazy<Option<PathBuf>> = Lazy::new(dirs::cache_dir);
 static CONFIG_DIR: Lazy<Option<PathBuf>> = Lazy::new(dirs::config_dir);
 static CONFIG_FILE_NAME: &str = "warg-config.json";
+static WORKSPACE_REGISTRIES_DIR: &str = "registries";
+static WORKSPACE_CONTENT_DIR: &str = "content";
+static WORKSPACE_NAMESPACES_PATH: &str = "namespaces";
 
 fn find_warg_config(cwd: &Path) -> Option<PathBuf> {
     let mut current = Some(cwd);
@@ -70,6 +78,11 @@ pub struct StoragePaths {
     pub content_dir: PathBuf,
     /// The path to the namespace map storage directory.
     pub namespace_map_path: PathBuf,
+    /// The path to the content storage directory to read content from when
+    /// it is not in the content storage directory.
+    pub fallback_content_dir: Option<PathBuf>,
+    /// The layout of paths in the storage directories.
+    pub storage_layout: StorageLayout,
 }
 
 /// Represents the Warg client configuration.
@@ -130,6 +143,124 @@ pub struct Config {
     /// Use the specified backend for keyring access.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub keyring_backend: Option<String>,
+
+    /// The path to a policy component to check against each package
+    /// resolved by the client.
+    ///
+    /// This path is expected to be relative to the configuration file.
+    ///
+    /// Requires the `policy-component` feature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_policy: Option<PathBuf>,
+
+    /// The SPDX license identifiers that downloaded packages must be
+    /// licensed under.
+    ///
+    /// If not specified, packages are downloaded regardless of license.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_allowlist: Option<Vec<String>>,
+
+    /// The policy for the vulnerability advisories of downloaded packages.
+    ///
+    /// If not specified, packages are downloaded regardless of advisories.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub advisory_policy: Option<AdvisoryPolicy>,
+
+    /// The policy for the keys that sign the records of packages.
+    ///
+    /// If not specified, records may be signed by any key with permission
+    /// in the package log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_policy: Option<SigningPolicy>,
+
+    /// The suffix to append to the `User-Agent` header of requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent_suffix: Option<String>,
+
+    /// The headers to send with every request (e.g. a tool name or build ID).
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub request_headers: IndexMap<String, String>,
+
+    /// The options for connecting to registries (e.g. DNS overrides or the
+    /// IP address family to connect over).
+    #[serde(default, skip_serializing_if = "ConnectOptions::is_default")]
+    pub connect: ConnectOptions,
+
+    /// The validation of the timestamps of registry checkpoints against the
+    /// local clock.
+    #[serde(default, skip_serializing_if = "CheckpointClock::is_default")]
+    pub checkpoint_clock: CheckpointClock,
+
+    /// The maximum number of seconds since a package was last verified
+    /// against the latest checkpoint of its registry for it to be resolved
+    /// for a download without updating it first.
+    ///
+    /// If not specified, packages are resolved regardless of when they were
+    /// last verified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_checkpoint_age_secs: Option<u64>,
+
+    /// The maximum number of records to request per page when fetching logs.
+    ///
+    /// The page size is halved when a page request times out and grows back
+    /// to this maximum while pages are fetched quickly.
+    ///
+    /// If not specified, the registry's default page size is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_page_size: Option<u16>,
+
+    /// The maximum number of content downloads in progress at once.
+    ///
+    /// If not specified, downloads are not limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel_downloads: Option<usize>,
+
+    /// The maximum number of content uploads in progress at once.
+    ///
+    /// If not specified, uploads are not limited across operations, and each
+    /// publish uploads up to 4 contents at once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel_uploads: Option<usize>,
+
+    /// The maximum number of requests to registries awaiting a response at
+    /// once.
+    ///
+    /// If not specified, requests are not limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_in_flight_requests: Option<usize>,
+
+    /// The maximum number of CPU-bound verifications (e.g. of proofs and
+    /// package records) run at once.
+    ///
+    /// If not specified, verifications are limited only by the runtime's
+    /// blocking thread pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_workers: Option<usize>,
+
+    /// The layout of paths in the registries and content directories.
+    ///
+    /// The `hashed` layout keeps paths short and valid on Windows file
+    /// systems; changing the layout of existing storage fetches it again.
+    #[serde(default)]
+    pub storage_layout: StorageLayout,
+
+    /// The path to a project directory (e.g. `.warg`) to store registry
+    /// state and content in instead of the registries, content, and
+    /// namespace map paths.
+    ///
+    /// This path is expected to be relative to the configuration file.
+    ///
+    /// Storing verified registry state in a project directory allows it to
+    /// be checked into the project's repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_dir: Option<PathBuf>,
+
+    /// Whether content that is not in the workspace directory is read from
+    /// the content directory.
+    ///
+    /// Registry state is never read from outside the workspace directory.
+    #[serde(default)]
+    pub workspace_fallback: bool,
 }
 
 impl Config {
@@ -150,6 +281,8 @@ impl Config {
         if let Some(parent) = path.parent() {
             config.registries_dir = config.registries_dir.map(|p| parent.join(p));
             config.content_dir = config.content_dir.map(|p| parent.join(p));
+            config.package_policy = config.package_policy.map(|p| parent.join(p));
+            config.workspace_dir = config.workspace_dir.map(|p| parent.join(p));
         }
 
         Ok(config)
@@ -212,6 +345,31 @@ impl Config {
             auto_accept_federation_hints: self.auto_accept_federation_hints,
             disable_interactive: self.disable_interactive,
             keyring_backend: self.keyring_backend.clone(),
+            package_policy: self.package_policy.as_ref().map(|p| {
+                let p = normalize_path(parent.join(p).as_path());
+                assert!(p.is_absolute());
+                pathdiff::diff_paths(&p, &parent).unwrap()
+            }),
+            license_allowlist: self.license_allowlist.clone(),
+            advisory_policy: self.advisory_policy.clone(),
+            signing_policy: self.signing_policy.clone(),
+            user_agent_suffix: self.user_agent_suffix.clone(),
+            request_headers: self.request_headers.clone(),
+            connect: self.connect.clone(),
+            checkpoint_clock: self.checkpoint_clock,
+            max_checkpoint_age_secs: self.max_checkpoint_age_secs,
+            fetch_page_size: self.fetch_page_size,
+            max_parallel_downloads: self.max_parallel_downloads,
+            max_parallel_uploads: self.max_parallel_uploads,
+            max_in_flight_requests: self.max_in_flight_requests,
+            verification_workers: self.verification_workers,
+            storage_layout: self.storage_layout,
+            workspace_dir: self.workspace_dir.as_ref().map(|p| {
+                let p = normalize_path(parent.join(p).as_path());
+                assert!(p.is_absolute());
+                pathdiff::diff_paths(&p, &parent).unwrap()
+            }),
+            workspace_fallback: self.workspace_fallback,
         };
 
         serde_json::to_writer_pretty(
@@ -302,15 +460,33 @@ impl Config {
         &self,
         registry_url: RegistryUrl,
     ) -> Result<StoragePaths, ClientError> {
-        let label = registry_url.safe_label();
-        let registries_dir = self.registries_dir()?.join(label);
-        let content_dir = self.content_dir()?;
-        let namespace_map_path = self.namespace_map_path()?;
+        let label = self
+            .storage_layout
+            .registry_dir_name(&registry_url.safe_label());
+        let (registries_dir, content_dir, namespace_map_path, fallback_content_dir) =
+            match &self.workspace_dir {
+                Some(dir) => (
+                    dir.join(WORKSPACE_REGISTRIES_DIR).join(label),
+                    dir.join(WORKSPACE_CONTENT_DIR),
+                    dir.join(WORKSPACE_NAMESPACES_PATH),
+                    self.workspace_fallback
+                        .then(|| self.content_dir())
+                        .transpose()?,
+                ),
+                None => (
+                    self.registries_dir()?.join(label),
+                    self.content_dir()?,
+                    self.namespace_map_path()?,
+                    None,
+                ),
+            };
         Ok(StoragePaths {
             registry_url,
             registries_dir,
             content_dir,
             namespace_map_path,
+            fallback_content_dir,
+            storage_layout: self.storage_layout,
         })
     }
 }
@@ -0,0 +1,74 @@
+//! Module for bounding the latency of client operations.
+//!
+//! A deadline attached to an operation with [`within`] or [`timeout`]
+//! applies to everything the operation does: every request sent to a
+//! registry while the operation runs is given a timeout of the time left
+//! until the deadline, and the operation, including its storage
+//! operations, is cancelled once the deadline passes.
+//!
+//! Because request timeouts also bound the reading of response bodies,
+//! the deadline continues to bound content streams returned by an
+//! operation (e.g. [`Client::download_exact_as_stream`]).
+//!
+//! Deadlines nest: an operation run within another operation's deadline
+//! ends at the earlier of the two deadlines.
+//!
+//! [`Client::download_exact_as_stream`]: crate::Client::download_exact_as_stream
+use crate::{api, ClientError, ClientResult};
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Runs the given operation, failing with
+/// [`ClientError::DeadlineExceeded`] if it does not complete before the
+/// given deadline.
+pub async fn within<T>(
+    deadline: Instant,
+    operation: impl Future<Output = ClientResult<T>>,
+) -> ClientResult<T> {
+    let deadline = current().map_or(deadline, |current| current.min(deadline));
+    DEADLINE
+        .scope(deadline, async move {
+            match tokio::time::timeout_at(deadline.into(), operation).await {
+                Ok(Err(e)) if Instant::now() >= deadline && is_deadline_error(&e) => {
+                    Err(ClientError::DeadlineExceeded)
+                }
+                Ok(result) => result,
+                Err(_) => Err(ClientError::DeadlineExceeded),
+            }
+        })
+        .await
+}
+
+/// Runs the given operation, failing with
+/// [`ClientError::DeadlineExceeded`] if it does not complete within the
+/// given timeout.
+pub async fn timeout<T>(
+    timeout: Duration,
+    operation: impl Future<Output = ClientResult<T>>,
+) -> ClientResult<T> {
+    within(Instant::now() + timeout, operation).await
+}
+
+/// Gets the deadline of the operation being run, if any.
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Gets the time left until the deadline of the operation being run.
+///
+/// Returns `None` if the operation has no deadline and `Some(Duration::ZERO)`
+/// if the deadline has passed.
+pub(crate) fn remaining() -> Option<Duration> {
+    current().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// Determines if the error is the result of a request running out of time.
+fn is_deadline_error(e: &ClientError) -> bool {
+    matches!(e, ClientError::Api(e) if e.is_timeout() || matches!(e, api::ClientError::DeadlineExceeded))
+}
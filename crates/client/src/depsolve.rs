@@ -1,8 +1,9 @@
 use anyhow::{bail, Result};
 use async_recursion::async_recursion;
-use indexmap::IndexSet;
-use semver::VersionReq;
+use indexmap::{IndexMap, IndexSet};
+use semver::{Version, VersionReq};
 use std::fs;
+use warg_crypto::hash::AnyHash;
 use warg_protocol::package::{Release, ReleaseState};
 use warg_protocol::registry::PackageName;
 use wasm_encoder::{
@@ -11,14 +12,27 @@ use wasm_encoder::{
 use wasmparser::{Chunk, ComponentImportSectionReader, Parser, Payload};
 
 use super::Client;
-use crate::storage::{ContentStorage, NamespaceMapStorage, PackageInfo, RegistryStorage};
+use crate::policy::ResolvedPackage;
+use crate::storage::{
+    ContentStorage, NamespaceMapStorage, PackageInfo, RegistryDomain, RegistryStorage,
+};
 use crate::version_util::{DependencyImportParser, Import, ImportKind};
-/// Import Kinds found in components
 
 /// Creates list of dependenies for locking components
 pub struct LockListBuilder {
     /// List of deps to include in locked component
     pub lock_list: IndexSet<Import>,
+    /// The releases resolved for each package, in resolution order
+    resolved: IndexMap<PackageName, ResolvedRelease>,
+}
+
+/// A release resolved by a [`LockListBuilder`].
+pub(crate) struct ResolvedRelease {
+    /// The registry the package was resolved from; `None` for the home
+    /// registry.
+    pub(crate) registry: Option<RegistryDomain>,
+    pub(crate) version: Version,
+    pub(crate) digest: AnyHash,
 }
 
 impl Default for LockListBuilder {
@@ -26,6 +40,7 @@ impl Default for LockListBuilder {
     fn default() -> Self {
         Self {
             lock_list: IndexSet::new(),
+            resolved: IndexMap::new(),
         }
     }
 }
@@ -49,6 +64,7 @@ impl LockListBuilder {
         &mut self,
         client: &Client<R, C, N>,
         mut bytes: &[u8],
+        dependents: &mut Vec<PackageName>,
     ) -> Result<()>
     where
         R: RegistryStorage,
@@ -107,36 +123,33 @@ impl LockListBuilder {
                 ImportKind::Locked(_) | ImportKind::Unlocked => {
                     let id = PackageName::new(import.name.clone())?;
                     let registry_domain = client.get_warg_registry(id.namespace()).await?;
-                    if let Some(info) = client
+                    let info = match client
                         .registry()
                         .load_package(registry_domain.as_ref(), &id)
                         .await?
                     {
-                        let release = info.state.releases().last();
-                        if let Some(r) = release {
+                        Some(info) => Some(info),
+                        None => {
+                            client.download(&id, &VersionReq::STAR).await?;
+                            client
+                                .registry()
+                                .load_package(registry_domain.as_ref(), &id)
+                                .await?
+                        }
+                    };
+
+                    if let Some(info) = info {
+                        if let Some(r) = info.state.find_latest_release(&import.req) {
+                            self.check_policy(client, &info, r, dependents)?;
+                            self.resolve(&info, registry_domain, r);
                             if let Some(bytes) = self.release_bytes(r, client)? {
-                                self.parse_package(client, &bytes).await?;
+                                client.check_license(&id, &r.version, &bytes)?;
+                                dependents.push(id.clone());
+                                self.parse_package(client, &bytes, dependents).await?;
+                                dependents.pop();
                             }
                         }
                         self.lock_list.insert(import);
-                    } else {
-                        client.download(&id, &VersionReq::STAR).await?;
-                        if let Some(info) = client
-                            .registry()
-                            .load_package(
-                                client.get_warg_registry(id.namespace()).await?.as_ref(),
-                                &id,
-                            )
-                            .await?
-                        {
-                            let release = info.state.releases().last();
-                            if let Some(r) = release {
-                                if let Some(bytes) = self.release_bytes(r, client)? {
-                                    self.parse_package(client, &bytes).await?;
-                                }
-                            }
-                            self.lock_list.insert(import);
-                        }
                     }
                 }
                 ImportKind::Interface(_) => {}
@@ -145,6 +158,49 @@ impl LockListBuilder {
         Ok(())
     }
 
+    fn check_policy<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage>(
+        &self,
+        client: &Client<R, C, N>,
+        info: &PackageInfo,
+        release: &Release,
+        dependents: &[PackageName],
+    ) -> Result<()> {
+        if let Some(digest) = release.content() {
+            client.check_package_policy(ResolvedPackage {
+                name: &info.name,
+                version: &release.version,
+                digest,
+                dependents,
+            })?;
+            client.check_advisory_policy(
+                &info.name,
+                &release.version,
+                &info.advisories_for(&release.version),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Records the release resolved for a package.
+    ///
+    /// The first release resolved for a package is kept.
+    fn resolve(&mut self, info: &PackageInfo, registry: Option<RegistryDomain>, release: &Release) {
+        if let Some(digest) = release.content() {
+            self.resolved
+                .entry(info.name.clone())
+                .or_insert_with(|| ResolvedRelease {
+                    registry,
+                    version: release.version.clone(),
+                    digest: digest.clone(),
+                });
+        }
+    }
+
+    /// The releases resolved for each package, in resolution order.
+    pub(crate) fn resolved(&self) -> &IndexMap<PackageName, ResolvedRelease> {
+        &self.resolved
+    }
+
     fn release_bytes<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage>(
         &self,
         release: &Release,
@@ -174,12 +230,15 @@ impl LockListBuilder {
     {
         let release = info.state.releases().last();
         if let Some(r) = release {
+            let registry_domain = client.get_warg_registry(info.name.namespace()).await?;
+            self.resolve(info, registry_domain, r);
             let state = &r.state;
             if let ReleaseState::Released { content } = state {
                 let path = client.content().content_location(content);
                 if let Some(p) = path {
                     let bytes = fs::read(p)?;
-                    self.parse_package(client, &bytes).await?;
+                    self.parse_package(client, &bytes, &mut vec![info.name.clone()])
+                        .await?;
                 }
             }
         }
@@ -0,0 +1,354 @@
+//! A module for comparing the package state of two client storages and the
+//! contents of two releases of a package.
+
+use super::{Client, ClientError, ClientResult};
+use crate::{
+    policy::SemverCheck,
+    storage::{
+        ContentStorage, NamespaceMapStorage, PackageInfo, PublishEntry, RegistryDomain,
+        RegistryStorage,
+    },
+};
+use anyhow::Context;
+use semver::Version;
+use std::{collections::BTreeMap, fs};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    interface::{ContentInterfaces, InterfaceChange},
+    registry::PackageName,
+};
+use wasm_metadata::RegistryMetadata;
+
+/// A package version known to a client storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageVersion {
+    /// The registry the package is from.
+    pub registry: RegistryDomain,
+    /// The name of the package.
+    pub name: PackageName,
+    /// The version of the package.
+    pub version: Version,
+    /// The content digest of the release; `None` if the release is yanked.
+    pub digest: Option<AnyHash>,
+}
+
+/// A package version known to both storages with different contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionMismatch {
+    /// The registry the package is from.
+    pub registry: RegistryDomain,
+    /// The name of the package.
+    pub name: PackageName,
+    /// The version of the package.
+    pub version: Version,
+    /// The content digest in this client's storage; `None` if yanked.
+    pub digest: Option<AnyHash>,
+    /// The content digest in the other storage; `None` if yanked.
+    pub other_digest: Option<AnyHash>,
+}
+
+/// The differences between the package state of a client's storage and
+/// another storage.
+///
+/// Package versions are ordered by registry, package name, and version.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateDiff {
+    /// The package versions only in this client's storage.
+    pub only_in_self: Vec<PackageVersion>,
+    /// The package versions only in the other storage.
+    pub only_in_other: Vec<PackageVersion>,
+    /// The package versions in both storages with different content digests.
+    pub mismatched: Vec<VersionMismatch>,
+}
+
+impl StateDiff {
+    /// Determines if the storages have the same package versions.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_self.is_empty() && self.only_in_other.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// A change of a field of the registry metadata of a release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataChange {
+    /// The name of the changed field (e.g. `license`).
+    pub field: &'static str,
+    /// The value of the field in the older release; `None` if unset.
+    pub from: Option<String>,
+    /// The value of the field in the newer release; `None` if unset.
+    pub to: Option<String>,
+}
+
+/// The structural differences between the contents of two releases of a
+/// package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseDiff {
+    /// The name of the package.
+    pub name: PackageName,
+    /// The version the diff is from.
+    pub from_version: Version,
+    /// The version the diff is to.
+    pub to_version: Version,
+    /// The size, in bytes, of the content of the release the diff is from.
+    pub from_size: u64,
+    /// The size, in bytes, of the content of the release the diff is to.
+    pub to_size: u64,
+    /// The changes of the imported and exported interfaces.
+    pub interface_changes: Vec<InterfaceChange>,
+    /// The changes of the registry metadata.
+    pub metadata_changes: Vec<MetadataChange>,
+}
+
+impl ReleaseDiff {
+    /// Gets the change in content size, in bytes.
+    pub fn size_delta(&self) -> i64 {
+        self.to_size as i64 - self.from_size as i64
+    }
+
+    /// Determines if any interface change is breaking.
+    pub fn is_breaking(&self) -> bool {
+        self.interface_changes
+            .iter()
+            .any(InterfaceChange::is_breaking)
+    }
+}
+
+fn metadata_fields(bytes: &[u8]) -> ClientResult<BTreeMap<&'static str, String>> {
+    let mut fields = BTreeMap::new();
+    if !wasmparser::Parser::is_core_wasm(bytes) && !wasmparser::Parser::is_component(bytes) {
+        return Ok(fields);
+    }
+
+    let Some(metadata) = RegistryMetadata::from_wasm(bytes)
+        .context("failed to parse registry metadata of content")?
+    else {
+        return Ok(fields);
+    };
+
+    let mut insert = |field, value: Option<String>| {
+        if let Some(value) = value {
+            fields.insert(field, value);
+        }
+    };
+    insert("authors", metadata.get_authors().map(|a| a.join(", ")));
+    insert("description", metadata.get_description().cloned());
+    insert("license", metadata.get_license().cloned());
+    insert(
+        "customLicenses",
+        metadata.get_custom_licenses().map(|l| {
+            l.iter()
+                .map(|l| l.id.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }),
+    );
+    insert(
+        "links",
+        metadata.get_links().map(|l| {
+            l.iter()
+                .map(|l| l.value.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }),
+    );
+    insert(
+        "categories",
+        metadata.get_categories().map(|c| c.join(", ")),
+    );
+
+    Ok(fields)
+}
+
+type Versions = BTreeMap<(String, PackageName, Version), PackageVersion>;
+
+async fn load_versions(storage: &impl RegistryStorage) -> ClientResult<Versions> {
+    let mut versions = Versions::new();
+    for (registry, packages) in storage.load_all_packages().await? {
+        for package in packages {
+            for release in package.state.releases() {
+                versions.insert(
+                    (
+                        registry.to_string(),
+                        package.name.clone(),
+                        release.version.clone(),
+                    ),
+                    PackageVersion {
+                        registry: registry.clone(),
+                        name: package.name.clone(),
+                        version: release.version.clone(),
+                        digest: release.content().cloned(),
+                    },
+                );
+            }
+        }
+    }
+
+    Ok(versions)
+}
+
+impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Compares the package state of this client's storage with another
+    /// registry storage, such as the storage of a mirror's client or a copy
+    /// of a registries directory.
+    ///
+    /// Only the package logs already in each storage are compared; neither
+    /// storage is updated from its registry.
+    pub async fn diff_state(&self, other: &impl RegistryStorage) -> ClientResult<StateDiff> {
+        let mut mine = load_versions(&self.registry).await?;
+        let theirs = load_versions(other).await?;
+
+        let mut diff = StateDiff::default();
+        for (key, other_version) in theirs {
+            match mine.remove(&key) {
+                Some(version) if version.digest != other_version.digest => {
+                    diff.mismatched.push(VersionMismatch {
+                        registry: version.registry,
+                        name: version.name,
+                        version: version.version,
+                        digest: version.digest,
+                        other_digest: other_version.digest,
+                    });
+                }
+                Some(_) => {}
+                None => diff.only_in_other.push(other_version),
+            }
+        }
+        diff.only_in_self.extend(mine.into_values());
+
+        Ok(diff)
+    }
+}
+
+impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Compares the contents of two releases of a package.
+    ///
+    /// Both releases are downloaded into client storage, subject to the
+    /// client's policies, and the differences of their imported and exported
+    /// interfaces, content sizes, and registry metadata are reported.
+    pub async fn diff_releases(
+        &self,
+        package: &PackageName,
+        from_version: &Version,
+        to_version: &Version,
+    ) -> ClientResult<ReleaseDiff> {
+        let from = self.download_exact(package, from_version).await?;
+        let to = self.download_exact(package, to_version).await?;
+        let from = fs::read(&from.path).map_err(ClientError::IoError)?;
+        let to = fs::read(&to.path).map_err(ClientError::IoError)?;
+
+        let interfaces = |bytes: &[u8], version: &Version| {
+            ContentInterfaces::from_content(bytes).with_context(|| {
+                format!("failed to extract the interfaces of `{package}` version {version}")
+            })
+        };
+        let interface_changes =
+            interfaces(&from, from_version)?.diff(&interfaces(&to, to_version)?);
+
+        let mut from_fields = metadata_fields(&from)?;
+        let to_fields = metadata_fields(&to)?;
+        let mut metadata_changes = Vec::new();
+        for (field, to) in to_fields {
+            let from = from_fields.remove(field);
+            if from.as_ref() != Some(&to) {
+                metadata_changes.push(MetadataChange {
+                    field,
+                    from,
+                    to: Some(to),
+                });
+            }
+        }
+        metadata_changes.extend(from_fields.into_iter().map(|(field, from)| MetadataChange {
+            field,
+            from: Some(from),
+            to: None,
+        }));
+        metadata_changes.sort_by_key(|change| change.field);
+
+        Ok(ReleaseDiff {
+            name: package.clone(),
+            from_version: from_version.clone(),
+            to_version: to_version.clone(),
+            from_size: from.len() as u64,
+            to_size: to.len() as u64,
+            interface_changes,
+            metadata_changes,
+        })
+    }
+
+    /// Checks the interfaces of the releases to publish against the previous
+    /// release of the package.
+    ///
+    /// Releases whose content is not WebAssembly, is not in client storage
+    /// or that have no previous release are not checked.
+    pub(crate) async fn check_release_compatibility(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        package: &PackageInfo,
+        entries: &[PublishEntry],
+        check: SemverCheck,
+    ) -> ClientResult<()> {
+        for entry in entries {
+            let PublishEntry::Release {
+                version,
+                content,
+                content_type,
+            } = entry
+            else {
+                continue;
+            };
+            if !content_type.is_wasm() {
+                continue;
+            }
+            let Some(path) = self.content.content_location(content) else {
+                continue;
+            };
+            let Some(previous) = package
+                .state
+                .find_previous_release(version)
+                .filter(|release| release.content_type.is_wasm())
+            else {
+                continue;
+            };
+            let previous_digest = previous
+                .content()
+                .context("invalid state: not yanked but missing content")?;
+
+            let previous_path = self
+                .download_content(registry_domain, previous_digest)
+                .await?;
+            let interfaces = |path| -> ClientResult<ContentInterfaces> {
+                let bytes = fs::read(path).map_err(ClientError::IoError)?;
+                Ok(ContentInterfaces::from_content(&bytes)?)
+            };
+            let changes = interfaces(&previous_path)?.incompatible_changes(
+                &previous.version,
+                &interfaces(&path)?,
+                version,
+            );
+            if changes.is_empty() {
+                continue;
+            }
+
+            match check {
+                SemverCheck::Reject => {
+                    return Err(ClientError::IncompatibleRelease {
+                        name: package.name.clone(),
+                        version: version.clone(),
+                        changes,
+                    })
+                }
+                SemverCheck::Warn => {
+                    for change in changes {
+                        tracing::warn!(
+                            "version {version} of package `{name}` makes a breaking change from version {previous_version}: {change}",
+                            name = package.name,
+                            previous_version = previous.version
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
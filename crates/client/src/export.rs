@@ -0,0 +1,371 @@
+//! A module for exporting a registry's logs for offline analysis.
+//!
+//! Logs are exported from the archive of a [`Monitor`](crate::monitor::Monitor)
+//! as CSV files with a header row:
+//!
+//! * `checkpoints.csv`: one row per archived checkpoint.
+//! * `records.csv`: one row per operator or package record.
+//! * `entries.csv`: one row per entry of a record, including the keys
+//!   granted and revoked by the entry.
+
+use crate::{
+    monitor::{read_lines, ArchivedRecord, CHECKPOINTS_FILE, RECORDS_FILE},
+    ClientResult,
+};
+use anyhow::Context;
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use warg_crypto::hash::Sha256;
+use warg_protocol::{
+    operator, package,
+    registry::{LogId, RecordId, RegistryIndex, TimestampedCheckpoint},
+    PublishedProtoEnvelope, SerdeEnvelope,
+};
+
+const CHECKPOINTS_HEADER: &[&str] = &["log_length", "log_root", "map_root", "timestamp", "key_id"];
+const RECORDS_HEADER: &[&str] = &[
+    "log_id",
+    "log_kind",
+    "registry_index",
+    "record_id",
+    "prev",
+    "timestamp",
+    "key_id",
+    "version",
+    "entries",
+];
+const ENTRIES_HEADER: &[&str] = &[
+    "log_id",
+    "registry_index",
+    "record_id",
+    "entry_index",
+    "kind",
+    "key_id",
+    "key",
+    "permissions",
+    "version",
+    "content",
+    "namespace",
+    "registry",
+];
+
+/// The number of rows exported to each file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// The number of checkpoints exported.
+    pub checkpoints: usize,
+    /// The number of records exported.
+    pub records: usize,
+    /// The number of record entries exported.
+    pub entries: usize,
+}
+
+/// A row of `records.csv`.
+struct RecordRow {
+    log_kind: &'static str,
+    registry_index: RegistryIndex,
+    record_id: RecordId,
+    prev: Option<RecordId>,
+    timestamp: SystemTime,
+    key_id: String,
+    version: u32,
+    entries: Vec<EntryRow>,
+}
+
+/// A row of `entries.csv`; columns that do not apply to an entry are empty.
+#[derive(Default)]
+struct EntryRow {
+    kind: &'static str,
+    key_id: String,
+    key: String,
+    permissions: String,
+    version: String,
+    content: String,
+    namespace: String,
+    registry: String,
+}
+
+impl EntryRow {
+    fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            ..Default::default()
+        }
+    }
+}
+
+/// Exports the archive of a monitored registry as CSV files to the given
+/// output directory.
+///
+/// Existing files in the output directory are overwritten.
+pub fn export_archive(
+    archive_dir: impl AsRef<Path>,
+    output_dir: impl AsRef<Path>,
+) -> ClientResult<ExportSummary> {
+    let archive_dir = archive_dir.as_ref();
+    let output_dir = output_dir.as_ref();
+    fs::create_dir_all(output_dir).with_context(|| {
+        format!(
+            "failed to create output directory `{path}`",
+            path = output_dir.display()
+        )
+    })?;
+
+    let mut summary = ExportSummary::default();
+
+    let mut checkpoints =
+        CsvWriter::create(output_dir.join("checkpoints.csv"), CHECKPOINTS_HEADER)?;
+    for ts_checkpoint in
+        read_lines::<SerdeEnvelope<TimestampedCheckpoint>>(&archive_dir.join(CHECKPOINTS_FILE))?
+    {
+        let contents = ts_checkpoint.as_ref();
+        checkpoints.row(&[
+            &contents.checkpoint.log_length.to_string(),
+            &contents.checkpoint.log_root.to_string(),
+            &contents.checkpoint.map_root.to_string(),
+            &contents.timestamp.to_string(),
+            &ts_checkpoint.key_id().to_string(),
+        ])?;
+        summary.checkpoints += 1;
+    }
+    checkpoints.finish()?;
+
+    let operator_log_id = LogId::operator_log::<Sha256>();
+    let mut records = CsvWriter::create(output_dir.join("records.csv"), RECORDS_HEADER)?;
+    let mut entries = CsvWriter::create(output_dir.join("entries.csv"), ENTRIES_HEADER)?;
+    for record in read_lines::<ArchivedRecord>(&archive_dir.join(RECORDS_FILE))? {
+        let log_id = record.log_id.to_string();
+        let row = if record.log_id == operator_log_id {
+            let envelope: PublishedProtoEnvelope<operator::OperatorRecord> =
+                record.envelope.try_into()?;
+            let contents = envelope.envelope.as_ref();
+            RecordRow {
+                log_kind: "operator",
+                registry_index: envelope.registry_index,
+                record_id: RecordId::operator_record::<Sha256>(&envelope.envelope),
+                prev: contents.prev.clone(),
+                timestamp: contents.timestamp,
+                key_id: envelope.envelope.key_id().to_string(),
+                version: contents.version,
+                entries: contents.entries.iter().map(operator_entry_row).collect(),
+            }
+        } else {
+            let envelope: PublishedProtoEnvelope<package::PackageRecord> =
+                record.envelope.try_into()?;
+            let contents = envelope.envelope.as_ref();
+            RecordRow {
+                log_kind: "package",
+                registry_index: envelope.registry_index,
+                record_id: RecordId::package_record::<Sha256>(&envelope.envelope),
+                prev: contents.prev.clone(),
+                timestamp: contents.timestamp,
+                key_id: envelope.envelope.key_id().to_string(),
+                version: contents.version,
+                entries: contents.entries.iter().map(package_entry_row).collect(),
+            }
+        };
+
+        let registry_index = row.registry_index.to_string();
+        let record_id = row.record_id.to_string();
+        records.row(&[
+            &log_id,
+            row.log_kind,
+            &registry_index,
+            &record_id,
+            &row.prev.map(|prev| prev.to_string()).unwrap_or_default(),
+            &unix_timestamp(row.timestamp),
+            &row.key_id,
+            &row.version.to_string(),
+            &row.entries.len().to_string(),
+        ])?;
+        summary.records += 1;
+
+        for (index, entry) in row.entries.into_iter().enumerate() {
+            entries.row(&[
+                &log_id,
+                &registry_index,
+                &record_id,
+                &index.to_string(),
+                entry.kind,
+                &entry.key_id,
+                &entry.key,
+                &entry.permissions,
+                &entry.version,
+                &entry.content,
+                &entry.namespace,
+                &entry.registry,
+            ])?;
+            summary.entries += 1;
+        }
+    }
+    records.finish()?;
+    entries.finish()?;
+
+    Ok(summary)
+}
+
+fn operator_entry_row(entry: &operator::OperatorEntry) -> EntryRow {
+    use operator::OperatorEntry;
+
+    match entry {
+        OperatorEntry::Init { key, .. } => EntryRow {
+            key_id: key.fingerprint().to_string(),
+            key: key.to_string(),
+            ..EntryRow::new("init")
+        },
+        OperatorEntry::GrantFlat { key, permissions } => EntryRow {
+            key_id: key.fingerprint().to_string(),
+            key: key.to_string(),
+            permissions: join(permissions),
+            ..EntryRow::new("grant")
+        },
+        OperatorEntry::RevokeFlat {
+            key_id,
+            permissions,
+        } => EntryRow {
+            key_id: key_id.to_string(),
+            permissions: join(permissions),
+            ..EntryRow::new("revoke")
+        },
+        OperatorEntry::DefineNamespace { namespace } => EntryRow {
+            namespace: namespace.clone(),
+            ..EntryRow::new("defineNamespace")
+        },
+        OperatorEntry::ImportNamespace {
+            namespace,
+            registry,
+        } => EntryRow {
+            namespace: namespace.clone(),
+            registry: registry.clone(),
+            ..EntryRow::new("importNamespace")
+        },
+        _ => EntryRow::new("unknown"),
+    }
+}
+
+fn package_entry_row(entry: &package::PackageEntry) -> EntryRow {
+    use package::PackageEntry;
+
+    match entry {
+        PackageEntry::Init { key, .. } => EntryRow {
+            key_id: key.fingerprint().to_string(),
+            key: key.to_string(),
+            ..EntryRow::new("init")
+        },
+        PackageEntry::GrantFlat { key, permissions } => EntryRow {
+            key_id: key.fingerprint().to_string(),
+            key: key.to_string(),
+            permissions: join(permissions),
+            ..EntryRow::new("grant")
+        },
+        PackageEntry::RevokeFlat {
+            key_id,
+            permissions,
+        } => EntryRow {
+            key_id: key_id.to_string(),
+            permissions: join(permissions),
+            ..EntryRow::new("revoke")
+        },
+        PackageEntry::Release {
+            version, content, ..
+        } => EntryRow {
+            version: version.to_string(),
+            content: content.to_string(),
+            ..EntryRow::new("release")
+        },
+        PackageEntry::Yank { version } => EntryRow {
+            version: version.to_string(),
+            ..EntryRow::new("yank")
+        },
+        PackageEntry::Attest {
+            version, content, ..
+        } => EntryRow {
+            version: version.to_string(),
+            content: content.to_string(),
+            ..EntryRow::new("attest")
+        },
+        PackageEntry::Deprecate { .. } => EntryRow::new("deprecate"),
+        PackageEntry::Undeprecate => EntryRow::new("undeprecate"),
+        _ => EntryRow::new("unknown"),
+    }
+}
+
+fn join(permissions: &[impl ToString]) -> String {
+    permissions
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn unix_timestamp(timestamp: SystemTime) -> String {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs().to_string())
+        .unwrap_or_default()
+}
+
+/// Writes rows of a CSV file.
+struct CsvWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl CsvWriter {
+    fn create(path: PathBuf, header: &[&str]) -> ClientResult<Self> {
+        let file = File::create(&path)
+            .with_context(|| format!("failed to create `{path}`", path = path.display()))?;
+        let mut writer = Self {
+            path,
+            writer: BufWriter::new(file),
+        };
+        writer.row(header)?;
+        Ok(writer)
+    }
+
+    fn row(&mut self, fields: &[&str]) -> ClientResult<()> {
+        let line = fields
+            .iter()
+            .map(|field| escape(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(self.writer, "{line}")
+            .with_context(|| format!("failed to write `{path}`", path = self.path.display()))?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> ClientResult<()> {
+        self.writer
+            .flush()
+            .with_context(|| format!("failed to write `{path}`", path = self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// Escapes a CSV field, quoting it if it contains a separator, quote, or
+/// line break.
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{field}\"", field = field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_escapes_csv_fields() {
+        assert_eq!(escape("1.0.0"), "1.0.0");
+        assert_eq!(escape("a,b"), "\"a,b\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape("a\nb"), "\"a\nb\"");
+    }
+}
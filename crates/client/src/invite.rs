@@ -0,0 +1,201 @@
+//! A module for onboarding maintainers of packages through invites.
+//!
+//! A key of a package log creates an invite granting permissions for the
+//! package, which a new maintainer redeems with their own key. The key
+//! that created the invite then grants the permissions to the redeeming
+//! key, after verifying the signatures of both the invite and the
+//! redemption.
+
+use super::{Client, ClientError, ClientResult};
+use crate::{
+    api,
+    storage::{ContentStorage, NamespaceMapStorage, PublishEntry, PublishInfo, RegistryStorage},
+};
+use anyhow::{anyhow, bail, Result};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use warg_crypto::{hash::Sha256, signing, Encode, Signable};
+use warg_protocol::{
+    package::{InviteRedemption, PackageInvite, Permission},
+    registry::{LogId, PackageName, RecordId},
+    SerdeEnvelope,
+};
+
+/// The grant of the permissions of redeemed invites of a package.
+#[derive(Debug, Clone)]
+pub struct InviteGrant {
+    /// The identifier of the record granting the permissions.
+    pub record_id: RecordId,
+    /// The redeemed invites whose permissions were granted.
+    pub invites: Vec<api::PackageInviteInfo>,
+}
+
+impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Creates an invite for another key to be granted the given
+    /// permissions for a package.
+    ///
+    /// The signing key must be a key of the package log that holds the
+    /// permissions. The invite may be redeemed until it expires.
+    pub async fn create_invite(
+        &self,
+        signing_key: &signing::PrivateKey,
+        package: &PackageName,
+        permissions: &[Permission],
+        expires_in: Duration,
+    ) -> ClientResult<PackageInvite> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        let expires = (SystemTime::now() + expires_in)
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| anyhow!(e))?
+            .as_secs();
+        let invite = PackageInvite {
+            log_id: log_id.clone(),
+            id: format!("{id:032x}", id = rand::random::<u128>()),
+            permissions: permissions.to_vec(),
+            expires,
+        };
+
+        let request = api::CreateInviteRequest {
+            invite: SerdeEnvelope::signed_contents(signing_key, invite).map_err(|e| anyhow!(e))?,
+        };
+        let info = self
+            .api
+            .create_package_invite(registry_domain.as_ref(), &log_id, &request)
+            .await
+            .map_err(|e| self.translate_package_error(e, package, &log_id))?;
+        Ok(info.invite.into_contents())
+    }
+
+    /// Redeems an invite of a package with the signing key.
+    ///
+    /// The permissions of the invite are granted to the signing key once
+    /// the key that created the invite grants them.
+    pub async fn redeem_invite(
+        &self,
+        signing_key: &signing::PrivateKey,
+        package: &PackageName,
+        invite_id: &str,
+    ) -> ClientResult<PackageInvite> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        let redemption = InviteRedemption {
+            log_id: log_id.clone(),
+            invite_id: invite_id.to_string(),
+            key: signing_key.public_key(),
+        };
+
+        let request = api::RedeemInviteRequest {
+            redemption: SerdeEnvelope::signed_contents(signing_key, redemption)
+                .map_err(|e| anyhow!(e))?,
+        };
+        let info = self
+            .api
+            .redeem_package_invite(registry_domain.as_ref(), &log_id, invite_id, &request)
+            .await
+            .map_err(|e| self.translate_package_error(e, package, &log_id))?;
+        Ok(info.invite.into_contents())
+    }
+
+    /// Publishes a record granting the permissions of each redeemed invite
+    /// of a package that was created by the signing key.
+    ///
+    /// The signatures of each invite and its redemption are verified
+    /// before permissions are granted; invites that fail verification are
+    /// skipped.
+    ///
+    /// Returns `None` if no redeemed invites await a grant.
+    ///
+    /// Use `wait_for_publish` to wait for the record to transition to the
+    /// `published` state.
+    pub async fn grant_invites(
+        &self,
+        signing_key: &signing::PrivateKey,
+        package: &PackageName,
+    ) -> ClientResult<Option<InviteGrant>> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        let response = self
+            .api
+            .get_package_invites(registry_domain.as_ref(), &log_id)
+            .await
+            .map_err(|e| self.translate_package_error(e, package, &log_id))?;
+
+        let public_key = signing_key.public_key();
+        let mut entries = Vec::new();
+        let mut invites = Vec::new();
+        for info in response.invites {
+            let Some(redemption) = &info.redemption else {
+                continue;
+            };
+
+            if info.invite.key_id() != &public_key.fingerprint() {
+                continue;
+            }
+
+            if let Err(e) = verify_invite(&log_id, &public_key, &info.invite, redemption) {
+                tracing::warn!(
+                    "skipping invite `{id}` that failed verification: {e}",
+                    id = info.invite.as_ref().id
+                );
+                continue;
+            }
+
+            entries.push(PublishEntry::Grant {
+                key: redemption.as_ref().key.clone(),
+                permissions: info.invite.as_ref().permissions.clone(),
+            });
+            invites.push(info);
+        }
+
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        let record_id = self
+            .publish_with_info(
+                signing_key,
+                PublishInfo {
+                    name: package.clone(),
+                    head: None,
+                    entries,
+                },
+            )
+            .await?;
+
+        Ok(Some(InviteGrant { record_id, invites }))
+    }
+
+    fn translate_package_error(
+        &self,
+        e: api::ClientError,
+        package: &PackageName,
+        log_id: &LogId,
+    ) -> ClientError {
+        ClientError::translate_log_not_found(e, self.api.auth_token().is_some(), |id| {
+            if id == log_id {
+                Some(package.clone())
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Verifies that an invite of the given package log was signed by the given
+/// key and that its redemption was signed by the redeeming key.
+fn verify_invite(
+    log_id: &LogId,
+    key: &signing::PublicKey,
+    invite: &SerdeEnvelope<PackageInvite>,
+    redemption: &SerdeEnvelope<InviteRedemption>,
+) -> Result<()> {
+    if &invite.as_ref().log_id != log_id
+        || &redemption.as_ref().log_id != log_id
+        || redemption.as_ref().invite_id != invite.as_ref().id
+    {
+        bail!("the invite and its redemption are not for the same package log and invite");
+    }
+
+    PackageInvite::verify(key, &invite.as_ref().encode(), invite.signature())?;
+    InviteRedemption::verify_signed(redemption)
+}
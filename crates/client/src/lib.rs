@@ -1,40 +1,65 @@
 //! A client library for Warg component registries.
 
 #![deny(missing_docs)]
-use crate::storage::PackageInfo;
+use crate::storage::{OperatorInfo, PackageInfo};
+use api::{
+    ConsistencyRequest, ContentSource, FetchError, FetchLogsRequest, FetchLogsResponse,
+    InclusionRequest, MissingContent, PackageError, PackageRecord, PackageRecordState,
+    PublishRecordRequest, UploadEndpoint, UploadProtocol,
+};
 
 use anyhow::{anyhow, Context, Result};
 use bytes::Bytes;
-use futures_util::{Stream, StreamExt, TryStreamExt};
+use futures_util::{
+    future,
+    stream::{self, BoxStream},
+    Stream, StreamExt, TryStreamExt,
+};
 use indexmap::{IndexMap, IndexSet};
+use limits::ConcurrencyLimits;
+use operator::OperatorLogInfo;
+use paging::PageSize;
+use policy::{
+    AdvisoryPolicy, PackagePolicy, PolicyViolation, ResolvedPackage, SemverCheck, SigningPolicy,
+};
+use progress::{SyncProgress, SyncProgressHandler, SyncProgressTracker};
+use project::ProjectConfig;
+use provenance::ProvenancePolicy;
 use reqwest::{Body, IntoUrl};
 use secrecy::Secret;
 use semver::{Version, VersionReq};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fs;
 use std::str::FromStr;
-use std::{borrow::Cow, path::PathBuf, time::Duration};
+use std::sync::{Arc, Mutex};
+use std::{
+    borrow::Cow,
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use storage::{
     ContentStorage, FileSystemContentStorage, FileSystemNamespaceMapStorage,
-    FileSystemRegistryStorage, NamespaceMapStorage, PublishInfo, RegistryDomain, RegistryStorage,
+    FileSystemRegistryStorage, NamespaceMapStorage, PublishEntry, PublishInfo, RegistryDomain,
+    RegistryStorage, SigningAuditEntry, SigningOutcome, VerifiedProofs,
 };
 use thiserror::Error;
+use tokio::sync::mpsc;
 use tokio_util::io::ReaderStream;
-use warg_api::v1::{
-    fetch::{FetchError, FetchLogsRequest},
-    package::{
-        MissingContent, PackageError, PackageRecord, PackageRecordState, PublishRecordRequest,
-        UploadEndpoint,
-    },
-    proof::{ConsistencyRequest, InclusionRequest},
-};
 use warg_crypto::hash::Sha256;
 use warg_crypto::{hash::AnyHash, signing, Encode, Signable};
-use warg_protocol::package::ReleaseState;
+use warg_protocol::package::{
+    ContentType, Deprecation, PackageSnapshot, RecordApproval, Release, ReleaseChannel,
+    ReleaseState,
+};
 use warg_protocol::{
-    operator, package,
-    registry::{LogId, LogLeaf, PackageName, RecordId, RegistryLen, TimestampedCheckpoint},
-    PublishedProtoEnvelope,
+    metadata::{
+        license_allowed, locate_custom_sections, ContentMetadata, ReleaseNotes, DOCS_ARCHIVE_KIND,
+        RELEASE_NOTES_KIND,
+    },
+    package,
+    registry::{LogId, LogLeaf, PackageName, RecordId, RegistryLen},
+    verify, ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
 };
 use wasm_compose::graph::{CompositionGraph, EncodeOptions, ExportIndex, InstanceId};
 
@@ -42,25 +67,65 @@ use wasm_compose::graph::{CompositionGraph, EncodeOptions, ExportIndex, Instance
 pub mod keyring;
 
 pub mod api;
+pub mod audit;
+mod builder;
+pub use builder::ClientBuilder;
 mod config;
+pub mod deadline;
 /// Tools for locking and bundling components
 pub mod depsolve;
+pub mod diff;
+pub mod export;
+pub mod invite;
+pub mod limits;
 use depsolve::{Bundler, LockListBuilder};
 /// Tools for semver
 pub mod version_util;
 use version_util::{kindless_name, locked_package, versioned_package, Import, ImportKind};
 pub mod lock;
+pub mod lockfile;
+pub mod middleware;
+use lockfile::{LockedPackage, LockedRegistry, Lockfile};
+pub mod mirror;
+pub mod monitor;
+pub mod operator;
+mod paging;
+pub mod policy;
+pub mod progress;
+pub mod project;
+pub mod provenance;
 mod registry_url;
 pub mod storage;
+pub mod transport;
+pub mod vendor;
+pub mod verification;
 pub use self::config::*;
 pub use self::registry_url::RegistryUrl;
 
 const DEFAULT_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_WAIT_INTERVAL: Duration = Duration::from_secs(30);
+const AWAIT_RECORD_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_SYNC_BATCH_SIZE: usize = 100;
+const FETCH_PIPELINE_DEPTH: usize = 2;
+const MAX_CONCURRENT_REGISTRY_SYNCS: usize = 4;
+const MAX_CONCURRENT_ADVISORY_FETCHES: usize = 8;
+const MAX_CONCURRENT_UPLOADS: usize = 4;
 
 /// For Bytecode Alliance projects, the default registry is set to `bytecodealliance.org`.
 /// The `.well-known` config path may resolve to another domain where the registry is hosted.
 pub const DEFAULT_REGISTRY: &str = "bytecodealliance.org";
 
+/// Options for how a publish is submitted to the registry.
+#[derive(Default)]
+struct SubmitOptions {
+    /// Whether to stage the record to be committed later.
+    stage: bool,
+    /// The time until which the content of the record is embargoed.
+    embargo_until: Option<SystemTime>,
+    /// The external sources of the content of the record.
+    content_sources: IndexMap<AnyHash, Vec<ContentSource>>,
+}
+
 /// A client for a Warg registry.
 pub struct Client<R, C, N>
 where
@@ -77,11 +142,38 @@ where
     disable_interactive: bool,
     keyring_backend: Option<String>,
     keys: IndexSet<String>,
+    package_policy: Option<Arc<dyn PackagePolicy>>,
+    license_allowlist: Option<Vec<String>>,
+    advisory_policy: Option<AdvisoryPolicy>,
+    provenance_policy: Option<ProvenancePolicy>,
+    signing_policy: Option<Arc<SigningPolicy>>,
+    semver_check: Option<SemverCheck>,
+    namespace_overrides: IndexMap<String, RegistryDomain>,
+    sync_batch_size: usize,
+    fetch_page_size: Option<u16>,
+    max_checkpoint_age: Option<Duration>,
+    sync_progress: Option<SyncProgressHandler>,
+    storage_reserve: u64,
+    package_snapshots: bool,
+    streaming_fetch: bool,
+    retry_policy: api::RetryPolicy,
+    /// The resolved registry of each namespace; cleared whenever the operator
+    /// log or the namespace map changes.
+    namespace_registries: Mutex<HashMap<String, Option<RegistryDomain>>>,
 }
 
 impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Creates a builder of a client.
+    ///
+    /// The registry URL and the storages must be set before building the
+    /// client; every other setting has a default.
+    pub fn builder() -> ClientBuilder<R, C, N> {
+        ClientBuilder::default()
+    }
+
     /// Creates a new client for the given URL, registry storage, and
     /// content storage.
+    #[deprecated(note = "use `Client::builder` instead")]
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         url: impl IntoUrl,
@@ -95,20 +187,324 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         keyring_backend: Option<String>,
         keys: IndexSet<String>,
     ) -> ClientResult<Self> {
-        let api = api::Client::new(url, auth_token)?;
-        Ok(Self {
-            registry,
-            content,
-            namespace_map,
-            api,
-            ignore_federation_hints,
-            auto_accept_federation_hints,
-            disable_interactive,
-            keyring_backend,
-            keys,
+        Self::builder()
+            .with_url(url)
+            .with_registry_storage(registry)
+            .with_content_storage(content)
+            .with_namespace_map_storage(namespace_map)
+            .with_auth_token(auth_token)
+            .with_ignore_federation_hints(ignore_federation_hints)
+            .with_auto_accept_federation_hints(auto_accept_federation_hints)
+            .with_disable_interactive(disable_interactive)
+            .with_keyring_backend(keyring_backend)
+            .with_keys(keys)
+            .build()
+    }
+
+    /// Appends the given suffix to the `User-Agent` header of requests.
+    ///
+    /// By default, the `User-Agent` header is `warg-client/<version>`.
+    pub fn with_user_agent_suffix(mut self, suffix: impl Into<String>) -> ClientResult<Self> {
+        self.api = self.api.with_user_agent_suffix(suffix)?;
+        Ok(self)
+    }
+
+    /// Adds a header that is sent with every request to the registry.
+    ///
+    /// This may be used to attribute traffic to a tool or build.
+    pub fn with_request_header(mut self, name: &str, value: &str) -> ClientResult<Self> {
+        self.api = self.api.with_header(name, value)?;
+        Ok(self)
+    }
+
+    /// Sets the options for connecting to the registry.
+    pub fn with_connect_options(mut self, options: api::ConnectOptions) -> ClientResult<Self> {
+        self.api = self.api.with_connect_options(options)?;
+        Ok(self)
+    }
+
+    /// Sets the validation of the timestamps of registry checkpoints against
+    /// the local clock.
+    pub fn with_checkpoint_clock(mut self, clock: api::CheckpointClock) -> Self {
+        self.api = self.api.with_checkpoint_clock(clock);
+        self
+    }
+
+    /// Adds a middleware that intercepts every request sent to the registry.
+    ///
+    /// Middleware is run in the order it is added.
+    pub fn with_middleware(mut self, middleware: impl middleware::Middleware) -> Self {
+        self.api = self.api.with_middleware(middleware);
+        self
+    }
+
+    /// Sets the policy checked against each package resolved by the client.
+    ///
+    /// Packages are checked when downloaded and when resolved as a
+    /// dependency of a component being locked.
+    pub fn with_package_policy(mut self, policy: impl PackagePolicy + 'static) -> Self {
+        self.package_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Resolves packages of the given namespace from the given registry.
+    ///
+    /// Overrides take precedence over the client's namespace map, but not
+    /// over the namespaces of the registry's operator log.
+    pub fn with_namespace_override(
+        mut self,
+        namespace: impl Into<String>,
+        registry: RegistryDomain,
+    ) -> Self {
+        self.namespace_overrides.insert(namespace.into(), registry);
+        self
+    }
+
+    /// Sets the number of package logs synced to a checkpoint at a time.
+    ///
+    /// Each batch of package logs is stored as soon as it has been validated
+    /// and proven, so an interrupted sync only repeats the incomplete batch.
+    ///
+    /// The default is 100.
+    pub fn with_sync_batch_size(mut self, size: usize) -> Self {
+        self.sync_batch_size = size.max(1);
+        self
+    }
+
+    /// Sets the maximum number of records requested per page when fetching
+    /// logs.
+    ///
+    /// The page size is halved when a page request times out and grows back
+    /// to the maximum while pages are fetched quickly.
+    ///
+    /// By default, the registry's default page size is used.
+    pub fn with_fetch_page_size(mut self, size: u16) -> Self {
+        self.fetch_page_size = Some(size);
+        self
+    }
+
+    /// Sets a handler called with the progress of each sync of package logs.
+    ///
+    /// Registries are synced concurrently, so the handler may be called from
+    /// different tasks.
+    pub fn with_sync_progress(
+        mut self,
+        handler: impl Fn(&SyncProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.sync_progress = Some(Arc::new(handler));
+        self
+    }
+
+    /// Applies the namespace overrides of the given project configuration.
+    pub fn with_project_config(mut self, project: &ProjectConfig) -> ClientResult<Self> {
+        for (namespace, registry) in &project.namespaces {
+            self = self.with_namespace_override(namespace, RegistryDomain::from_str(registry)?);
+        }
+        Ok(self)
+    }
+
+    /// Checks a resolved package against the client's package policy.
+    fn check_package_policy(&self, package: ResolvedPackage) -> ClientResult<()> {
+        let Some(policy) = &self.package_policy else {
+            return Ok(());
+        };
+
+        let violations = policy.check(&package)?;
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        Err(ClientError::PackagePolicyViolation {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            violations,
+        })
+    }
+
+    /// Restricts downloads to packages licensed under the given SPDX
+    /// license identifiers.
+    ///
+    /// A package's license is read from the registry metadata of its
+    /// content; packages without a license are not allowed.
+    ///
+    /// When set, streamed downloads are stored in client storage so that
+    /// their license can be checked before streaming.
+    pub fn with_license_allowlist(
+        mut self,
+        licenses: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.license_allowlist = Some(licenses.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Checks the license of package content against the client's license
+    /// allowlist.
+    fn check_license(
+        &self,
+        name: &PackageName,
+        version: &Version,
+        content: &[u8],
+    ) -> ClientResult<()> {
+        let Some(allowlist) = &self.license_allowlist else {
+            return Ok(());
+        };
+
+        // Content with an invalid license is treated as unlicensed
+        let license = ContentMetadata::from_content(content)
+            .ok()
+            .and_then(|metadata| metadata.license);
+
+        match &license {
+            Some(license) if license_allowed(license, allowlist.iter().map(String::as_str)) => {
+                Ok(())
+            }
+            _ => Err(ClientError::LicenseNotAllowed {
+                name: name.clone(),
+                version: version.clone(),
+                license,
+            }),
+        }
+    }
+
+    /// Denies downloads of package versions affected by vulnerability
+    /// advisories according to the given policy.
+    ///
+    /// Advisories are only known for packages whose advisories have been
+    /// fetched from the registry.
+    pub fn with_advisory_policy(mut self, policy: AdvisoryPolicy) -> Self {
+        self.advisory_policy = Some(policy);
+        self
+    }
+
+    /// Verifies the provenance attestations of package releases against the
+    /// given policy when producing verification reports.
+    ///
+    /// See [`Client::verification_report`].
+    pub fn with_provenance_policy(mut self, policy: ProvenancePolicy) -> Self {
+        self.provenance_policy = Some(policy);
+        self
+    }
+
+    /// Requires the records of packages to be signed by the keys of the
+    /// given policy for their namespace.
+    ///
+    /// The policy is checked as package records are validated; records
+    /// already in client storage are not checked again. Packages restricted
+    /// by the policy are not started from package log snapshots.
+    pub fn with_signing_policy(mut self, policy: SigningPolicy) -> Self {
+        self.signing_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Checks the interfaces of each release to publish against the
+    /// previous release of the package before the record is signed.
+    ///
+    /// Breaking interface changes are only allowed when the leftmost
+    /// non-zero component of the version is bumped; the content of the
+    /// previous release is downloaded into client storage to compare it.
+    pub fn with_semver_check(mut self, check: SemverCheck) -> Self {
+        self.semver_check = Some(check);
+        self
+    }
+
+    /// Sets the space, in bytes, that must remain available in content
+    /// storage after content is downloaded.
+    ///
+    /// Downloads that would leave less space available fail with
+    /// [`ClientError::InsufficientStorage`] before any content is written.
+    /// Defaults to 0.
+    pub fn with_storage_reserve(mut self, bytes: u64) -> Self {
+        self.storage_reserve = bytes;
+        self
+    }
+
+    /// Sets whether packages that have not been fetched before are started
+    /// from a signed snapshot of their log, if the registry serves
+    /// snapshots.
+    ///
+    /// Only the records that follow the head of a snapshot are fetched and
+    /// validated; the state of the log up to the head is trusted on the
+    /// signature of the registry operator, and the head record is proven to
+    /// be included in the registry log. Defaults to `false`.
+    pub fn with_package_snapshots(mut self, enabled: bool) -> Self {
+        self.package_snapshots = enabled;
+        self
+    }
+
+    /// Requires packages to have been verified against the latest checkpoint
+    /// of their registry within the given age before they are resolved for
+    /// a download.
+    ///
+    /// Packages last verified before then are updated first, and the
+    /// download fails if they cannot be. By default, packages in client
+    /// storage are resolved regardless of when they were last verified.
+    pub fn with_max_checkpoint_age(mut self, age: Duration) -> Self {
+        self.max_checkpoint_age = Some(age);
+        self
+    }
+
+    /// Sets the limits on the concurrency of the client, replacing any limits
+    /// of its configuration.
+    ///
+    /// The limits are shared by every operation of the client.
+    pub fn with_concurrency_limits(mut self, limits: ConcurrencyLimits) -> Self {
+        self.api = self.api.with_concurrency_limits(limits);
+        self
+    }
+
+    /// Sets whether log records are fetched as streamed responses.
+    ///
+    /// The records of each log of a streamed response are validated as
+    /// they are received rather than once the entire page of records is
+    /// received. Registries that do not stream responses are fetched from as
+    /// usual. Defaults to `false`.
+    pub fn with_streaming_fetch(mut self, enabled: bool) -> Self {
+        self.streaming_fetch = enabled;
+        self
+    }
+
+    /// Checks the advisories affecting a package version against the
+    /// client's advisory policy.
+    fn check_advisory_policy(
+        &self,
+        name: &PackageName,
+        version: &Version,
+        advisories: &[api::VulnerabilityAdvisory],
+    ) -> ClientResult<()> {
+        let Some(policy) = &self.advisory_policy else {
+            return Ok(());
+        };
+
+        let denied = policy.denied(advisories);
+        if denied.is_empty() {
+            return Ok(());
+        }
+
+        Err(ClientError::AdvisoryPolicyViolation {
+            name: name.clone(),
+            version: version.clone(),
+            advisories: denied.into_iter().cloned().collect(),
         })
     }
 
+    /// Downloads content into client storage and checks its license against
+    /// the client's license allowlist.
+    async fn download_licensed_content(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        name: &PackageName,
+        version: &Version,
+        digest: &AnyHash,
+    ) -> ClientResult<PathBuf> {
+        let path = self.download_content(registry_domain, digest).await?;
+        if self.license_allowlist.is_some() {
+            let content = fs::read(&path).map_err(ClientError::IoError)?;
+            self.check_license(name, version, &content)?;
+        }
+
+        Ok(path)
+    }
+
     /// Gets the URL of the client.
     pub fn url(&self) -> &RegistryUrl {
         self.api.url()
@@ -130,10 +526,17 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
     }
 
     /// Get warg registry domain.
+    ///
+    /// Resolutions are cached by the client until the operator log or the
+    /// namespace map changes.
     pub async fn get_warg_registry(
         &self,
         namespace: &str,
     ) -> Result<Option<RegistryDomain>, ClientError> {
+        if let Some(domain) = self.namespace_registries.lock().unwrap().get(namespace) {
+            return Ok(domain.clone());
+        }
+
         let operator = self
             .registry()
             .load_operator(Some(&RegistryDomain::from_str(namespace)?))
@@ -141,20 +544,58 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         if let Some(op) = operator {
             match op.state.namespace_state(namespace) {
                 Some(warg_protocol::operator::NamespaceState::Imported { registry }) => {
-                    return Ok(Some(RegistryDomain::from_str(registry)?));
+                    return Ok(self.cache_namespace_registry(
+                        namespace,
+                        Some(RegistryDomain::from_str(registry)?),
+                    ));
                 }
                 Some(warg_protocol::operator::NamespaceState::Defined) => {
-                    return Ok(None);
+                    return Ok(self.cache_namespace_registry(namespace, None));
                 }
                 _ => (),
             }
         };
+        if let Some(domain) = self.namespace_overrides.get(namespace) {
+            return Ok(self.cache_namespace_registry(namespace, Some(domain.clone())));
+        }
         let nm_map = self.namespace_map.load_namespace_map().await?;
-        Ok(nm_map.and_then(|nm_map| {
-            nm_map
-                .get(namespace)
-                .map(|domain| RegistryDomain::from_str(domain).unwrap())
-        }))
+        if let Some(domain) = nm_map.as_ref().and_then(|nm_map| nm_map.get(namespace)) {
+            return Ok(
+                self.cache_namespace_registry(namespace, Some(RegistryDomain::from_str(domain)?))
+            );
+        }
+
+        // Fall back to the federation hints of the registry's discovery document
+        match self.api.discovery().await {
+            Ok(document) => {
+                let domain = document
+                    .and_then(|d| d.federation.into_iter().find(|h| h.namespace == namespace))
+                    .map(|h| RegistryDomain::from_str(&h.registry))
+                    .transpose()?;
+                Ok(self.cache_namespace_registry(namespace, domain))
+            }
+            Err(e) => {
+                // Failures are not cached so that discovery is attempted again
+                tracing::debug!("failed to discover registry: {e}");
+                Ok(None)
+            }
+        }
+    }
+
+    fn cache_namespace_registry(
+        &self,
+        namespace: &str,
+        domain: Option<RegistryDomain>,
+    ) -> Option<RegistryDomain> {
+        self.namespace_registries
+            .lock()
+            .unwrap()
+            .insert(namespace.to_string(), domain.clone());
+        domain
+    }
+
+    fn clear_namespace_registries(&self) {
+        self.namespace_registries.lock().unwrap().clear();
     }
 
     /// Stores namespace mapping in local storage
@@ -166,18 +607,21 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         self.namespace_map
             .store_namespace(namespace, registry_domain)
             .await?;
+        self.clear_namespace_registries();
         Ok(())
     }
 
     /// Resets the namespace map
     pub async fn reset_namespaces(&self) -> Result<()> {
         self.namespace_map.reset_namespaces().await?;
+        self.clear_namespace_registries();
         Ok(())
     }
 
     /// Reset client storage for the registry.
     pub async fn reset_registry(&self) -> ClientResult<()> {
         tracing::info!("resetting registry local state");
+        self.clear_namespace_registries();
         self.registry
             .reset(true)
             .await
@@ -193,10 +637,30 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
             .or(Err(ClientError::ClearContentCacheFailed))
     }
 
+    /// Pins the content of the given digest in the content cache.
+    ///
+    /// Pinned content, such as the releases of a lockfile, survives cache
+    /// eviction and [`Client::clear_content_cache`]. Content may be pinned
+    /// before it is downloaded.
+    pub async fn pin_content(&self, digest: &AnyHash) -> ClientResult<()> {
+        Ok(self.content.pin_content(digest).await?)
+    }
+
+    /// Unpins the content of the given digest in the content cache.
+    pub async fn unpin_content(&self, digest: &AnyHash) -> ClientResult<()> {
+        Ok(self.content.unpin_content(digest).await?)
+    }
+
     /// Locks component
     pub async fn lock_component(&self, info: &PackageInfo) -> ClientResult<Vec<u8>> {
         let mut builder = LockListBuilder::default();
-        builder.build_list(self, info).await?;
+        builder
+            .build_list(self, info)
+            .await
+            .map_err(|e| match e.downcast::<ClientError>() {
+                Ok(e) => e,
+                Err(e) => e.into(),
+            })?;
         let top = Import {
             name: format!("{}:{}", info.name.namespace(), info.name.name()),
             req: VersionReq::STAR,
@@ -287,6 +751,77 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         Ok(locked)
     }
 
+    /// Resolves the dependencies of a component into a lockfile.
+    ///
+    /// Dependencies are followed across registries through the client's
+    /// namespace mappings. The resolved packages are then fetched again and
+    /// verified against the latest checkpoint of their registry, with each
+    /// registry verified independently; the lockfile records the registry
+    /// and checkpoint of each locked release.
+    pub async fn lock_dependencies(&self, info: &PackageInfo) -> ClientResult<Lockfile> {
+        let mut builder = LockListBuilder::default();
+        builder
+            .build_list(self, info)
+            .await
+            .map_err(|e| match e.downcast::<ClientError>() {
+                Ok(e) => e,
+                Err(e) => e.into(),
+            })?;
+
+        let resolved = builder.resolved();
+        let packages = self.fetch_packages(resolved.keys()).await?;
+
+        let mut lockfile = Lockfile::default();
+        for (package, (name, release)) in packages.iter().zip(resolved) {
+            let registry = package
+                .registry
+                .clone()
+                .or_else(|| release.registry.clone())
+                .unwrap_or_else(|| self.url().registry_domain())
+                .to_string();
+            let not_verified = |reason: &str| ClientError::DependencyNotVerified {
+                name: name.clone(),
+                registry: registry.clone(),
+                reason: reason.to_string(),
+            };
+
+            let checkpoint = package
+                .checkpoint
+                .as_ref()
+                .ok_or_else(|| not_verified("the package log was not verified at a checkpoint"))?;
+            if package
+                .state
+                .release(&release.version)
+                .and_then(Release::content)
+                != Some(&release.digest)
+            {
+                return Err(not_verified(&format!(
+                    "release `{version}` is not in the verified package log",
+                    version = release.version
+                )));
+            }
+
+            let locked = lockfile
+                .registries
+                .entry(registry.clone())
+                .or_insert_with(|| checkpoint.into());
+            if locked != &LockedRegistry::from(checkpoint) {
+                return Err(not_verified(
+                    "the packages of the registry were verified at different checkpoints",
+                ));
+            }
+
+            lockfile.packages.push(LockedPackage {
+                name: name.clone(),
+                version: release.version.clone(),
+                registry,
+                digest: release.digest.clone(),
+            });
+        }
+
+        Ok(lockfile)
+    }
+
     /// Bundles component
     pub async fn bundle_component(&self, info: &PackageInfo) -> ClientResult<Vec<u8>> {
         let mut bundler = Bundler::new(self);
@@ -373,6 +908,169 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         &self,
         signing_key: &signing::PrivateKey,
         publish_info: PublishInfo,
+    ) -> ClientResult<RecordId> {
+        self.submit_with_info(signing_key, publish_info, SubmitOptions::default())
+            .await
+    }
+
+    /// Submits the provided publish information with its content embargoed
+    /// until the given time.
+    ///
+    /// The record is published as usual, but the registry does not serve
+    /// the content uploaded for it or list its releases until the embargo
+    /// passes.
+    ///
+    /// Returns the identifier of the record that was published.
+    ///
+    /// Use `wait_for_publish` to wait for the record to transition to the `published` state.
+    pub async fn publish_embargoed_with_info(
+        &self,
+        signing_key: &signing::PrivateKey,
+        publish_info: PublishInfo,
+        until: SystemTime,
+    ) -> ClientResult<RecordId> {
+        if !self.api.capabilities().await?.embargoed_releases {
+            return Err(ClientError::EmbargoNotSupported);
+        }
+
+        self.submit_with_info(
+            signing_key,
+            publish_info,
+            SubmitOptions {
+                embargo_until: Some(until),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Submits the provided publish information, declaring external sources
+    /// of its content.
+    ///
+    /// The registry fetches content with sources from the sources rather than
+    /// requiring it to be uploaded; content without sources is uploaded as
+    /// usual. The registry rejects sources it does not allow.
+    ///
+    /// Returns the identifier of the record that was published.
+    ///
+    /// Use `wait_for_publish` to wait for the record to transition to the `published` state.
+    pub async fn publish_with_content_sources(
+        &self,
+        signing_key: &signing::PrivateKey,
+        publish_info: PublishInfo,
+        content_sources: IndexMap<AnyHash, Vec<ContentSource>>,
+    ) -> ClientResult<RecordId> {
+        if !content_sources.is_empty() && !self.api.capabilities().await?.content_sources {
+            return Err(ClientError::ContentSourcesNotSupported);
+        }
+
+        self.submit_with_info(
+            signing_key,
+            publish_info,
+            SubmitOptions {
+                content_sources,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Stages the provided publish information in the registry.
+    ///
+    /// The record and its content are uploaded, but the record is not
+    /// published until it is committed with [`Client::commit_staged`]. The
+    /// registry rejects the record if it is abandoned with
+    /// [`Client::abandon_staged`] or is not committed before it expires.
+    ///
+    /// Returns the identifier of the record that was staged.
+    pub async fn stage_with_info(
+        &self,
+        signing_key: &signing::PrivateKey,
+        publish_info: PublishInfo,
+    ) -> ClientResult<RecordId> {
+        if !self.api.capabilities().await?.staged_publishes {
+            return Err(ClientError::StagedPublishNotSupported);
+        }
+
+        self.submit_with_info(
+            signing_key,
+            publish_info,
+            SubmitOptions {
+                stage: true,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Commits a staged package record, submitting it for publishing.
+    ///
+    /// Use `wait_for_publish` to wait for the record to transition to the `published` state.
+    pub async fn commit_staged(
+        &self,
+        package: &PackageName,
+        record_id: &RecordId,
+    ) -> ClientResult<()> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        self.api
+            .commit_package_record(registry_domain.as_ref(), &log_id, record_id)
+            .await
+            .map_err(|e| self.package_record_error(e, package, &log_id, record_id))?;
+        Ok(())
+    }
+
+    /// Approves a package record awaiting approval with the signing key,
+    /// submitting it for publishing.
+    ///
+    /// The signing key must differ from the key that signed the record and
+    /// be authorized by the registry to approve records of the package.
+    ///
+    /// Use `wait_for_publish` to wait for the record to transition to the `published` state.
+    pub async fn approve_record(
+        &self,
+        signing_key: &signing::PrivateKey,
+        package: &PackageName,
+        record_id: &RecordId,
+    ) -> ClientResult<()> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        let approval = RecordApproval {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            key: signing_key.public_key(),
+        };
+        let request = api::ApproveRecordRequest {
+            approval: SerdeEnvelope::signed_contents(signing_key, approval)
+                .map_err(|e| anyhow!(e))?,
+        };
+        self.api
+            .approve_package_record(registry_domain.as_ref(), &log_id, record_id, &request)
+            .await
+            .map_err(|e| self.package_record_error(e, package, &log_id, record_id))?;
+        Ok(())
+    }
+
+    /// Abandons a staged package record, causing the registry to reject it.
+    pub async fn abandon_staged(
+        &self,
+        package: &PackageName,
+        record_id: &RecordId,
+    ) -> ClientResult<()> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        self.api
+            .abandon_package_record(registry_domain.as_ref(), &log_id, record_id)
+            .await
+            .map_err(|e| self.package_record_error(e, package, &log_id, record_id))?;
+        Ok(())
+    }
+
+    async fn submit_with_info(
+        &self,
+        signing_key: &signing::PrivateKey,
+        publish_info: PublishInfo,
+        options: SubmitOptions,
     ) -> ClientResult<RecordId> {
         if publish_info.entries.is_empty() {
             return Err(ClientError::NothingToPublish {
@@ -461,9 +1159,37 @@ Attempt to create `{package_name}` and publish the release y/N\n",
             let registry_domain = self.get_warg_registry(package.name.namespace()).await?;
 
             let log_id = LogId::package_log::<Sha256>(&package.name);
-            let record = info.finalize(signing_key)?;
+            // Check the record against the local package state before signing
+            // it, as the registry would reject an invalid record anyway; a
+            // record following a head that is not yet known locally (i.e. a
+            // pending publish) cannot be checked
+            let known_head = info.head.as_ref() == package.state.head().as_ref().map(|h| &h.digest);
+            if let Some(check) = self.semver_check {
+                self.check_release_compatibility(
+                    registry_domain.as_ref(),
+                    &package,
+                    &info.entries,
+                    check,
+                )
+                .await?;
+            }
+            let prev = info.head.clone();
+            let entries = info.entries.clone();
+            let record = info.into_record(&signing_key.public_key());
+            let timestamp = record.timestamp;
+            if known_head {
+                package
+                    .state
+                    .check(&signing_key.public_key().fingerprint(), &record)
+                    .map_err(|inner| ClientError::InvalidPublish {
+                        name: package.name.clone(),
+                        inner,
+                    })?;
+            }
+            let record = ProtoEnvelope::signed_contents(signing_key, record)
+                .context("failed to sign package record")?;
             let record_id = RecordId::package_record::<Sha256>(&record);
-            let record = match self
+            let result = self
                 .api
                 .publish_package_record(
                     registry_domain.as_ref(),
@@ -471,15 +1197,46 @@ Attempt to create `{package_name}` and publish the release y/N\n",
                     PublishRecordRequest {
                         package_name: Cow::Borrowed(&package.name),
                         record: Cow::Owned(record.into()),
-                        content_sources: Default::default(),
+                        content_sources: options.content_sources.clone(),
+                        stage: options.stage,
+                        embargo_until: options.embargo_until.map(|until| {
+                            until
+                                .duration_since(UNIX_EPOCH)
+                                .map(|d| d.as_secs())
+                                .unwrap_or_default()
+                        }),
                     },
                 )
-                .await
-            {
-                Ok(record) => Ok(record),
-                Err(api::ClientError::Package(PackageError::Rejection(reason))) => {
-                    Err(ClientError::PublishRejected {
-                        name: package.name.clone(),
+                .await;
+
+            self.audit_signing(SigningAuditEntry {
+                record_id: record_id.clone(),
+                package: package.name.clone(),
+                registry: registry_domain.clone(),
+                prev,
+                entries,
+                key_id: signing_key.public_key().fingerprint(),
+                timestamp,
+                outcome: match &result {
+                    Ok(_) if options.stage => SigningOutcome::Staged,
+                    Ok(_) => SigningOutcome::Submitted,
+                    Err(api::ClientError::Package(PackageError::Rejection(reason))) => {
+                        SigningOutcome::Rejected {
+                            reason: reason.clone(),
+                        }
+                    }
+                    Err(e) => SigningOutcome::Failed {
+                        error: e.to_string(),
+                    },
+                },
+            })
+            .await;
+
+            let record = match result {
+                Ok(record) => Ok(record),
+                Err(api::ClientError::Package(PackageError::Rejection(reason))) => {
+                    Err(ClientError::PublishRejected {
+                        name: package.name.clone(),
                         reason,
                         record_id,
                     })
@@ -533,51 +1290,102 @@ Attempt to create `{package_name}` and publish the release y/N\n",
             break (package, record);
         };
 
-        // TODO: parallelize this
-        for (digest, MissingContent { upload }) in record.missing_content() {
-            // Upload the missing content, if the registry supports it
-            let Some(UploadEndpoint::Http {
-                method,
-                url,
-                headers,
-            }) = upload.first()
-            else {
-                continue;
-            };
+        // Upload the missing content, if the registry supports it
+        let capabilities = self.api.capabilities().await?;
+        let (name, record_id) = (&package.name, &record.record_id);
+        let uploads = record
+            .missing_content()
+            .filter_map(|(digest, MissingContent { upload })| {
+                let endpoint = upload
+                    .iter()
+                    .find(|UploadEndpoint::Http { method, .. }| {
+                        // Prefer an endpoint with a protocol negotiated with the registry
+                        let protocol = UploadProtocol::from_method(method);
+                        protocol != UploadProtocol::Unknown
+                            && (capabilities.upload_protocols.is_empty()
+                                || capabilities.upload_protocols.contains(&protocol))
+                    })
+                    .or_else(|| upload.first())?;
+                Some(async move {
+                    let res = self
+                        .upload_missing_content(name, record_id, digest, endpoint)
+                        .await;
+                    (digest.clone(), res)
+                })
+            })
+            .collect::<Vec<_>>();
 
-            self.api
-                .upload_content(
-                    method,
-                    url,
-                    headers,
-                    Body::wrap_stream(self.content.load_content(digest).await?.ok_or_else(
-                        || ClientError::ContentNotFound {
-                            digest: digest.clone(),
-                        },
-                    )?),
-                )
-                .await
-                .map_err(|e| match e {
-                    api::ClientError::Package(PackageError::Rejection(reason)) => {
-                        ClientError::PublishRejected {
-                            name: package.name.clone(),
-                            record_id: record.record_id.clone(),
-                            reason,
-                        }
-                    }
-                    api::ClientError::Package(PackageError::Unauthorized(reason)) => {
-                        ClientError::Unauthorized(reason)
-                    }
-                    _ => e.into(),
-                })?;
+        let parallel = self
+            .api
+            .concurrency_limits()
+            .max_parallel_uploads
+            .unwrap_or(MAX_CONCURRENT_UPLOADS)
+            .max(1);
+        let mut failures = futures_util::stream::iter(uploads)
+            .buffer_unordered(parallel)
+            .filter_map(|(digest, res)| async move { res.err().map(|e| (digest, e)) })
+            .collect::<Vec<_>>()
+            .await;
+
+        match failures.len() {
+            0 => {}
+            1 => return Err(failures.remove(0).1),
+            _ => {
+                return Err(ClientError::ContentUploadsFailed {
+                    name: package.name.clone(),
+                    record_id: record.record_id.clone(),
+                    failures,
+                })
+            }
         }
 
         Ok(record.record_id)
     }
 
+    /// Uploads the missing content of a package record to an upload
+    /// endpoint given by the registry.
+    async fn upload_missing_content(
+        &self,
+        name: &PackageName,
+        record_id: &RecordId,
+        digest: &AnyHash,
+        UploadEndpoint::Http {
+            method,
+            url,
+            headers,
+        }: &UploadEndpoint,
+    ) -> ClientResult<()> {
+        let content = self.content.load_content(digest).await?.ok_or_else(|| {
+            ClientError::ContentNotFound {
+                digest: digest.clone(),
+            }
+        })?;
+
+        self.api
+            .upload_content(method, url, headers, Body::wrap_stream(content))
+            .await
+            .map_err(|e| match e {
+                api::ClientError::Package(PackageError::Rejection(reason)) => {
+                    ClientError::PublishRejected {
+                        name: name.clone(),
+                        record_id: record_id.clone(),
+                        reason,
+                    }
+                }
+                api::ClientError::Package(PackageError::Unauthorized(reason)) => {
+                    ClientError::Unauthorized(reason)
+                }
+                _ => e.into(),
+            })
+    }
+
     /// Waits for a package record to transition to the `published` state.
     ///
-    /// The `interval` is the amount of time to wait between checks.
+    /// The record is awaited with [`Client::await_record`]; if the registry
+    /// does not support awaiting records, the `interval` is the initial
+    /// amount of time to wait between checks of the record. It doubles after
+    /// each check, up to a maximum of 30 seconds, and a retry hint provided
+    /// by the registry takes precedence over it.
     ///
     /// Returns an error if the package record was rejected.
     pub async fn wait_for_publish(
@@ -586,44 +1394,202 @@ Attempt to create `{package_name}` and publish the release y/N\n",
         record_id: &RecordId,
         interval: Duration,
     ) -> ClientResult<()> {
+        self.wait_for_publish_until(package, record_id, interval, None)
+            .await
+    }
+
+    /// Waits for a package record to transition to the `published` state,
+    /// for at most the given `timeout`.
+    ///
+    /// See [`Client::wait_for_publish`] for how the record is awaited.
+    ///
+    /// Returns [`ClientError::PublishTimedOut`] if the package record is
+    /// still processing after the timeout has elapsed.
+    pub async fn wait_for_publish_with_timeout(
+        &self,
+        package: &PackageName,
+        record_id: &RecordId,
+        interval: Duration,
+        timeout: Duration,
+    ) -> ClientResult<()> {
+        self.wait_for_publish_until(package, record_id, interval, Some(timeout))
+            .await
+    }
+
+    async fn wait_for_publish_until(
+        &self,
+        package: &PackageName,
+        record_id: &RecordId,
+        interval: Duration,
+        timeout: Option<Duration>,
+    ) -> ClientResult<()> {
+        let record = self.await_record_with_interval(package, record_id, interval);
+        let record = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, record).await.map_err(|_| {
+                ClientError::PublishTimedOut {
+                    name: package.clone(),
+                    record_id: record_id.clone(),
+                    timeout,
+                }
+            })??,
+            None => record.await?,
+        };
+
+        match record.state {
+            PackageRecordState::Sourcing { .. } => Err(ClientError::PackageMissingContent),
+            PackageRecordState::Staged { .. } => Err(ClientError::PublishStaged {
+                name: package.clone(),
+                record_id: record_id.clone(),
+            }),
+            PackageRecordState::AwaitingApproval { .. } => {
+                Err(ClientError::PublishAwaitingApproval {
+                    name: package.clone(),
+                    record_id: record_id.clone(),
+                })
+            }
+            PackageRecordState::Published { .. } => {
+                self.fetch_package(package).await?;
+                Ok(())
+            }
+            PackageRecordState::Rejected { reason } => Err(ClientError::PublishRejected {
+                name: package.clone(),
+                record_id: record_id.clone(),
+                reason,
+            }),
+            PackageRecordState::Processing { .. } => {
+                unreachable!("awaited records are no longer processing")
+            }
+        }
+    }
+
+    /// Awaits the processing of a package record.
+    ///
+    /// If the registry supports the `events` capability, the record is
+    /// awaited with long-polling requests to the registry; otherwise, the
+    /// state of the record is polled.
+    ///
+    /// Returns the record once it is no longer processing.
+    pub async fn await_record(
+        &self,
+        package: &PackageName,
+        record_id: &RecordId,
+    ) -> ClientResult<PackageRecord> {
+        self.await_record_with_interval(package, record_id, DEFAULT_WAIT_INTERVAL)
+            .await
+    }
+
+    async fn await_record_with_interval(
+        &self,
+        package: &PackageName,
+        record_id: &RecordId,
+        interval: Duration,
+    ) -> ClientResult<PackageRecord> {
         let registry_domain = self.get_warg_registry(package.namespace()).await?;
         let log_id = LogId::package_log::<Sha256>(package);
-        let mut current = self
-            .get_package_record(registry_domain.as_ref(), package, &log_id, record_id)
-            .await?;
+        let events = self.api.capabilities().await?.events;
+        let mut interval = interval.min(MAX_WAIT_INTERVAL);
 
         loop {
-            match current.state {
-                PackageRecordState::Sourcing { .. } => {
-                    return Err(ClientError::PackageMissingContent);
-                }
-                PackageRecordState::Published { .. } => {
-                    self.fetch_package(package).await?;
-                    return Ok(());
-                }
-                PackageRecordState::Rejected { reason } => {
-                    return Err(ClientError::PublishRejected {
-                        name: package.clone(),
-                        record_id: record_id.clone(),
-                        reason,
-                    });
-                }
-                PackageRecordState::Processing => {
-                    tokio::time::sleep(interval).await;
-                    current = self
-                        .get_package_record(registry_domain.as_ref(), package, &log_id, record_id)
-                        .await?;
+            let result = if events {
+                self.api
+                    .await_package_record(
+                        registry_domain.as_ref(),
+                        &log_id,
+                        record_id,
+                        AWAIT_RECORD_TIMEOUT,
+                    )
+                    .await
+                    .map_err(|e| self.package_record_error(e, package, &log_id, record_id))
+            } else {
+                self.get_package_record(registry_domain.as_ref(), package, &log_id, record_id)
+                    .await
+            };
+
+            let record = match result {
+                Ok(record) => record,
+                // A throttled check is retried once the registry allows it
+                Err(ClientError::Api(e)) if e.is_throttled() => {
+                    let delay = e
+                        .retry_after()
+                        .unwrap_or(interval)
+                        .min(self.retry_policy.max_delay);
+                    tracing::debug!(
+                        "checking record `{record_id}` was throttled; retrying in {delay:?}"
+                    );
+                    tokio::time::sleep(delay).await;
+                    continue;
                 }
+                Err(e) => return Err(e),
+            };
+
+            let retry_after_ms = match &record.state {
+                PackageRecordState::Processing { retry_after_ms } => *retry_after_ms,
+                _ => return Ok(record),
+            };
+
+            if !events {
+                let delay = retry_after_ms
+                    .map(|ms| Duration::from_millis(ms).min(MAX_WAIT_INTERVAL))
+                    .unwrap_or(interval);
+                tokio::time::sleep(delay).await;
+                interval = (interval * 2).min(MAX_WAIT_INTERVAL);
             }
         }
     }
 
     /// Updates all package logs in client registry storage to the latest registry checkpoint.
+    ///
+    /// The vulnerability advisories of the packages are also updated.
     pub async fn update(&self) -> ClientResult<()> {
         tracing::info!("updating downloaded package logs");
 
         for mut packages in self.registry.load_all_packages().await?.into_values() {
             self.update_checkpoints(&mut packages).await?;
+            self.update_advisories(&mut packages).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the vulnerability advisories of the given packages and
+    /// stores them with the packages.
+    ///
+    /// Advisories are informational, so a package whose advisories cannot
+    /// be fetched (e.g. from a registry that does not publish advisories)
+    /// keeps its previously fetched advisories.
+    async fn update_advisories(
+        &self,
+        packages: impl IntoIterator<Item = &mut PackageInfo>,
+    ) -> ClientResult<()> {
+        let fetches = packages
+            .into_iter()
+            .map(|package| self.update_package_advisories(package))
+            .collect::<Vec<_>>();
+        futures_util::stream::iter(fetches)
+            .buffer_unordered(MAX_CONCURRENT_ADVISORY_FETCHES)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(())
+    }
+
+    async fn update_package_advisories(&self, package: &mut PackageInfo) -> ClientResult<()> {
+        let registry_domain = self.get_warg_registry(package.name.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(&package.name);
+        match self
+            .api
+            .get_package_advisories(registry_domain.as_ref(), &log_id)
+            .await
+        {
+            Ok(response) => {
+                package.advisories = response.advisories;
+                self.registry
+                    .store_package(registry_domain.as_ref(), package)
+                    .await?;
+            }
+            Err(e) => tracing::warn!(
+                "failed to fetch advisories for package `{name}`: {e}",
+                name = package.name
+            ),
         }
 
         Ok(())
@@ -659,24 +1625,88 @@ Attempt to create `{package_name}` and publish the release y/N\n",
         );
 
         match info.state.find_latest_release(requirement) {
-            Some(release) => {
-                let digest = release
-                    .content()
-                    .context("invalid state: not yanked but missing content")?
-                    .clone();
-                let path = self
-                    .download_content(registry_domain.as_ref(), &digest)
-                    .await?;
-                Ok(Some(PackageDownload {
-                    version: release.version.clone(),
-                    digest,
-                    path,
-                }))
-            }
+            Some(release) => self
+                .download_release(registry_domain.as_ref(), &info, release)
+                .await
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Downloads the latest version of a package in the given release
+    /// channel into client storage that satisfies the given version
+    /// requirement.
+    ///
+    /// Unlike [`Client::download`], pre-releases in the channel are selected
+    /// when the version they are a pre-release of satisfies the requirement;
+    /// for example, `^1.0` in the `nightly` channel selects
+    /// `1.2.0-nightly.3`.
+    ///
+    /// If the requested package log is not present in client storage, it
+    /// will be fetched from the registry first.
+    ///
+    /// An error is returned if the package does not exist.
+    ///
+    /// If a version in the channel satisfying the requirement does not
+    /// exist, `None` is returned.
+    pub async fn download_from_channel(
+        &self,
+        package: &PackageName,
+        requirement: &VersionReq,
+        channel: &ReleaseChannel,
+    ) -> Result<Option<PackageDownload>, ClientError> {
+        let info = self.package(package).await?;
+
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+
+        tracing::debug!(
+            package = package.as_ref(),
+            version_requirement = requirement.to_string(),
+            channel = channel.to_string(),
+            registry_header = ?registry_domain,
+            "downloading",
+        );
+
+        match info.state.find_latest_in_channel(requirement, channel) {
+            Some(release) => self
+                .download_release(registry_domain.as_ref(), &info, release)
+                .await
+                .map(Some),
             None => Ok(None),
         }
     }
 
+    async fn download_release(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        info: &PackageInfo,
+        release: &Release,
+    ) -> Result<PackageDownload, ClientError> {
+        let package = &info.name;
+        let digest = release
+            .content()
+            .context("invalid state: not yanked but missing content")?
+            .clone();
+        self.check_package_policy(ResolvedPackage {
+            name: package,
+            version: &release.version,
+            digest: &digest,
+            dependents: &[],
+        })?;
+        let advisories = info.advisories_for(&release.version);
+        self.check_advisory_policy(package, &release.version, &advisories)?;
+        let path = self
+            .download_licensed_content(registry_domain, package, &release.version, &digest)
+            .await?;
+        Ok(PackageDownload {
+            version: release.version.clone(),
+            digest,
+            path,
+            deprecation: info.state.deprecation().cloned(),
+            advisories,
+        })
+    }
+
     /// Downloads the latest version of a package.
     ///
     /// If the requested package log is not present in client storage, it
@@ -708,13 +1738,32 @@ Attempt to create `{package_name}` and publish the release y/N\n",
                     .content()
                     .context("invalid state: not yanked but missing content")?
                     .clone();
+                self.check_package_policy(ResolvedPackage {
+                    name: package,
+                    version: &release.version,
+                    digest: &digest,
+                    dependents: &[],
+                })?;
+                let advisories = info.advisories_for(&release.version);
+                self.check_advisory_policy(package, &release.version, &advisories)?;
+                if self.license_allowlist.is_some() {
+                    self.download_licensed_content(
+                        registry_domain.as_ref(),
+                        package,
+                        &release.version,
+                        &digest,
+                    )
+                    .await?;
+                }
                 let stream = self
                     .download_content_stream(registry_domain.as_ref(), &digest)
                     .await?;
                 Ok(Some((
                     PackageDownloadInfo {
                         version: release.version.clone(),
+                        advisories,
                         digest,
+                        deprecation: info.state.deprecation().cloned(),
                     },
                     stream,
                 )))
@@ -763,12 +1812,23 @@ Attempt to create `{package_name}` and publish the release y/N\n",
                 name: package.clone(),
             })?;
 
+        self.check_package_policy(ResolvedPackage {
+            name: package,
+            version,
+            digest,
+            dependents: &[],
+        })?;
+        let advisories = info.advisories_for(version);
+        self.check_advisory_policy(package, version, &advisories)?;
+
         Ok(PackageDownload {
             version: version.clone(),
             digest: digest.clone(),
             path: self
-                .download_content(registry_domain.as_ref(), digest)
+                .download_licensed_content(registry_domain.as_ref(), package, version, digest)
                 .await?,
+            deprecation: info.state.deprecation().cloned(),
+            advisories,
         })
     }
 
@@ -809,186 +1869,616 @@ Attempt to create `{package_name}` and publish the release y/N\n",
                 name: package.clone(),
             })?;
 
+        self.check_package_policy(ResolvedPackage {
+            name: package,
+            version,
+            digest,
+            dependents: &[],
+        })?;
+        let advisories = info.advisories_for(version);
+        self.check_advisory_policy(package, version, &advisories)?;
+
+        if self.license_allowlist.is_some() {
+            self.download_licensed_content(registry_domain.as_ref(), package, version, digest)
+                .await?;
+        }
+
         Ok((
             PackageDownloadInfo {
                 version: version.clone(),
                 digest: digest.clone(),
+                deprecation: info.state.deprecation().cloned(),
+                advisories,
             },
             self.download_content_stream(registry_domain.as_ref(), digest)
                 .await?,
         ))
     }
 
-    async fn update_packages_and_return_federated_packages<'a>(
+    /// Downloads the attestations attached to the specified version of a
+    /// package into client storage.
+    ///
+    /// If the requested package log is not present in client storage, it
+    /// will be fetched from the registry first.
+    ///
+    /// Attestations are listed in the validated package log, so each
+    /// attestation was signed by a key with permission to release the
+    /// package; its content is verified against the digest in the log.
+    ///
+    /// An error is returned if the package or version does not exist.
+    pub async fn download_attestations(
         &self,
-        registry_domain: Option<&RegistryDomain>,
-        packages: impl IntoIterator<Item = &'a mut PackageInfo>,
-    ) -> Result<IndexMap<Option<RegistryDomain>, Vec<&'a mut PackageInfo>>, ClientError> {
-        let ts_checkpoint = self.api.latest_checkpoint(registry_domain).await?;
-        let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+        package: &PackageName,
+        version: &Version,
+    ) -> Result<Vec<AttestationDownload>, ClientError> {
+        let info = self.package(package).await?;
+
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
 
         tracing::debug!(
-            log_length = checkpoint.log_length,
+            package = package.as_ref(),
+            version = version.to_string(),
             registry_header = ?registry_domain,
-            "updating to checkpoint",
+            "downloading attestations",
         );
 
-        // operator log info
-        let mut operator = self
-            .registry
-            .load_operator(registry_domain)
-            .await?
-            .unwrap_or_default();
-
-        // map package names to package logs that need to be updated
-        let mut packages = packages
-            .into_iter()
-            .filter_map(|p| match &p.checkpoint {
-                // Don't bother updating if the package is already at the specified checkpoint
-                // If `registry` field is not set, then update.
-                Some(c) if p.registry.is_some() && c == checkpoint => None,
-                _ => Some((LogId::package_log::<Sha256>(&p.name), p)),
-            })
-            .inspect(|(_, p)| tracing::info!("package `{name}` will be updated", name = p.name))
-            .collect::<IndexMap<_, _>>();
+        if info.state.release(version).is_none() {
+            return Err(ClientError::PackageVersionDoesNotExist {
+                version: version.clone(),
+                name: package.clone(),
+            });
+        }
 
-        // if operator log and all packages are up to date at the latest checkpoint, then return
-        if operator.checkpoint.is_some_and(|c| &c == checkpoint) && packages.is_empty() {
-            return Ok(IndexMap::default());
+        let mut downloads = Vec::new();
+        for attestation in info.state.attestations(version) {
+            downloads.push(AttestationDownload {
+                kind: attestation.kind.clone(),
+                digest: attestation.content.clone(),
+                by: attestation.by.clone(),
+                path: self
+                    .download_content(registry_domain.as_ref(), &attestation.content)
+                    .await?,
+            });
         }
 
-        // federated packages in other registries
-        let mut federated_packages: IndexMap<Option<RegistryDomain>, Vec<&mut PackageInfo>> =
-            IndexMap::with_capacity(packages.len());
+        Ok(downloads)
+    }
 
-        // loop and fetch logs
-        let has_auth_token = self.api.auth_token().is_some();
-        loop {
-            let response = match self
-                .api
-                .fetch_logs(
-                    registry_domain,
-                    FetchLogsRequest {
-                        log_length: checkpoint.log_length,
-                        operator: operator
-                            .head_fetch_token
-                            .as_ref()
-                            .map(|t| Cow::Borrowed(t.as_str())),
-                        limit: None,
-                        // last known fetch token for each package log ID
-                        packages: Cow::Owned(
-                            packages
-                                .iter()
-                                .map(|(id, p)| (id.clone(), p.head_fetch_token.clone()))
-                                .collect::<IndexMap<_, _>>(),
-                        ),
-                    },
-                )
-                .await
-                .inspect(|res| {
-                    for warning in res.warnings.iter() {
-                        tracing::warn!("Fetch warning from registry: {}", warning.message);
-                    }
-                }) {
-                Ok(res) => Ok(res),
-                Err(err) => match &err {
-                    api::ClientError::Fetch(FetchError::LogNotFound(log_id))
-                    | api::ClientError::Package(PackageError::LogNotFound(log_id)) => {
-                        if let Some(name) = packages.get(log_id).map(|p| p.name.clone()) {
-                            Err(ClientError::PackageDoesNotExist {
-                                name,
-                                has_auth_token,
-                            })
-                        } else {
-                            Err(ClientError::Api(err))
-                        }
-                    }
+    /// Downloads the artifacts of the specified version of a package into
+    /// client storage, limited to the given content types.
+    ///
+    /// The artifacts of a release are its content, of the type declared in
+    /// its release entry, and the attestations attached to it, including
+    /// documentation archives; artifacts of other types are not downloaded.
+    ///
+    /// If the requested package log is not present in client storage, it
+    /// will be fetched from the registry first.
+    ///
+    /// An error is returned if the package or version does not exist.
+    pub async fn download_artifacts(
+        &self,
+        package: &PackageName,
+        version: &Version,
+        content_types: &[ContentType],
+    ) -> Result<Vec<ArtifactDownload>, ClientError> {
+        let info = self.package(package).await?;
 
-                    api::ClientError::LogNotFoundWithHint(log_id, hint)
-                        if self.disable_interactive =>
-                    {
-                        let name = packages.get(log_id).unwrap().name.clone();
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
 
-                        match hint.to_str().ok().map(|s| s.split_once('=')) {
-                            Some(Some((namespace, registry))) if packages.contains_key(log_id) => {
-                                Err(ClientError::PackageDoesNotExistWithHintHeader {
-                                    name,
-                                    has_auth_token,
-                                    hint_namespace: namespace.to_string(),
-                                    hint_registry: registry.to_string(),
-                                })
-                            }
-                            _ => Err(ClientError::PackageDoesNotExist {
-                                name,
-                                has_auth_token,
-                            }),
-                        }
+        tracing::debug!(
+            package = package.as_ref(),
+            version = version.to_string(),
+            registry_header = ?registry_domain,
+            "downloading artifacts",
+        );
+
+        let release =
+            info.state
+                .release(version)
+                .ok_or_else(|| ClientError::PackageVersionDoesNotExist {
+                    version: version.clone(),
+                    name: package.clone(),
+                })?;
+
+        let mut downloads = Vec::new();
+        if content_types.contains(&release.content_type) {
+            let digest =
+                release
+                    .content()
+                    .ok_or_else(|| ClientError::PackageVersionDoesNotExist {
+                        version: version.clone(),
+                        name: package.clone(),
+                    })?;
+
+            self.check_package_policy(ResolvedPackage {
+                name: package,
+                version,
+                digest,
+                dependents: &[],
+            })?;
+            self.check_advisory_policy(package, version, &info.advisories_for(version))?;
+
+            // Only WebAssembly content carries license metadata
+            let path = if release.content_type.is_wasm() {
+                self.download_licensed_content(registry_domain.as_ref(), package, version, digest)
+                    .await?
+            } else {
+                self.download_content(registry_domain.as_ref(), digest)
+                    .await?
+            };
+
+            downloads.push(ArtifactDownload {
+                content_type: release.content_type,
+                digest: digest.clone(),
+                path,
+            });
+        }
+
+        for attestation in info.state.attestations(version) {
+            let content_type = attestation.content_type();
+            if !content_types.contains(&content_type) {
+                continue;
+            }
+
+            downloads.push(ArtifactDownload {
+                content_type,
+                digest: attestation.content.clone(),
+                path: self
+                    .download_content(registry_domain.as_ref(), &attestation.content)
+                    .await?,
+            });
+        }
+
+        Ok(downloads)
+    }
+
+    /// Fetches the custom sections with the given names from the content of
+    /// the specified version of a package.
+    ///
+    /// Only custom sections at the top level of the content are fetched, in
+    /// content order; a name may match more than one section.
+    ///
+    /// If the content is in client storage, the sections are read from it.
+    /// Otherwise, if the registry supports the `contentSections`
+    /// capability, only the requested sections are downloaded; such
+    /// partially downloaded content cannot be validated against its digest.
+    /// Failing both, the content is downloaded into client storage first.
+    ///
+    /// If the requested package log is not present in client storage, it
+    /// will be fetched from the registry first.
+    ///
+    /// An error is returned if the package or version does not exist.
+    pub async fn fetch_custom_sections(
+        &self,
+        package: &PackageName,
+        version: &Version,
+        names: &[&str],
+    ) -> Result<Vec<CustomSection>, ClientError> {
+        let info = self.package(package).await?;
+
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+
+        tracing::debug!(
+            package = package.as_ref(),
+            version = version.to_string(),
+            registry_header = ?registry_domain,
+            "fetching custom sections",
+        );
+
+        let digest = info
+            .state
+            .release(version)
+            .and_then(|release| release.content())
+            .ok_or_else(|| ClientError::PackageVersionDoesNotExist {
+                version: version.clone(),
+                name: package.clone(),
+            })?;
+
+        if self.content.content_location(digest).is_none()
+            && self.api.capabilities().await?.content_sections
+        {
+            let response = self
+                .api
+                .content_sections(registry_domain.as_ref(), digest)
+                .await?;
+
+            let mut sections = Vec::new();
+            for section in response.sections {
+                if !names.contains(&section.name.as_str()) {
+                    continue;
+                }
+
+                let data = self
+                    .api
+                    .download_content_range(
+                        registry_domain.as_ref(),
+                        digest,
+                        section.offset..section.offset + section.size,
+                    )
+                    .await?;
+                sections.push(CustomSection {
+                    name: section.name,
+                    data: data.to_vec(),
+                });
+            }
+
+            return Ok(sections);
+        }
+
+        let path = self
+            .download_content(registry_domain.as_ref(), digest)
+            .await?;
+        let bytes = tokio::fs::read(&path).await.map_err(ClientError::IoError)?;
+
+        Ok(locate_custom_sections(&bytes)?
+            .into_iter()
+            .filter(|section| names.contains(&section.name.as_str()))
+            .map(|section| CustomSection {
+                data: bytes[section.range].to_vec(),
+                name: section.name,
+            })
+            .collect())
+    }
+
+    /// Attaches release notes to the specified version of a package.
+    ///
+    /// The release notes are published as an attestation of kind
+    /// [`RELEASE_NOTES_KIND`]; the registry surfaces the latest release notes
+    /// of each release in its package metadata.
+    ///
+    /// Returns the identifier of the record that was published.
+    pub async fn publish_release_notes(
+        &self,
+        signing_key: &signing::PrivateKey,
+        package: &PackageName,
+        version: &Version,
+        notes: &ReleaseNotes,
+    ) -> ClientResult<RecordId> {
+        let bytes = notes.to_content()?;
+        let content = self
+            .content
+            .store_content(
+                Box::pin(futures_util::stream::once(async { Ok(Bytes::from(bytes)) })),
+                None,
+            )
+            .await?;
+
+        self.publish_with_info(
+            signing_key,
+            PublishInfo {
+                name: package.clone(),
+                head: None,
+                entries: vec![PublishEntry::Attest {
+                    version: version.clone(),
+                    kind: RELEASE_NOTES_KIND.to_string(),
+                    content,
+                }],
+            },
+        )
+        .await
+    }
+
+    /// Attaches a documentation archive to the specified version of a
+    /// package.
+    ///
+    /// The archive is a gzip-compressed tar archive with an `index.html` at
+    /// its root; it is published as an attestation of kind
+    /// [`DOCS_ARCHIVE_KIND`] and served by the registry at
+    /// `/docs/{package}/{version}/`.
+    ///
+    /// Returns the identifier of the record that was published.
+    pub async fn publish_docs(
+        &self,
+        signing_key: &signing::PrivateKey,
+        package: &PackageName,
+        version: &Version,
+        archive: Bytes,
+    ) -> ClientResult<RecordId> {
+        let content = self
+            .content
+            .store_content(
+                Box::pin(futures_util::stream::once(async { Ok(archive) })),
+                None,
+            )
+            .await?;
+
+        self.publish_with_info(
+            signing_key,
+            PublishInfo {
+                name: package.clone(),
+                head: None,
+                entries: vec![PublishEntry::Attest {
+                    version: version.clone(),
+                    kind: DOCS_ARCHIVE_KIND.to_string(),
+                    content,
+                }],
+            },
+        )
+        .await
+    }
+
+    /// Gets the metadata of a package from its registry, including the
+    /// release notes attached to each release.
+    ///
+    /// Unlike the package log, the metadata is not verified by the client.
+    pub async fn package_metadata(
+        &self,
+        package: &PackageName,
+    ) -> ClientResult<api::PackageMetadataResponse> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        self.api
+            .get_package_metadata(registry_domain.as_ref(), &log_id)
+            .await
+            .map_err(|e| {
+                ClientError::translate_log_not_found(e, self.api.auth_token().is_some(), |id| {
+                    if id == &log_id {
+                        Some(package.clone())
+                    } else {
+                        None
                     }
+                })
+            })
+    }
 
-                    #[cfg(feature = "cli-interactive")]
-                    api::ClientError::LogNotFoundWithHint(log_id, hint) => {
-                        match hint.to_str().ok().map(|s| s.split_once('=')) {
-                            Some(Some((namespace, registry)))
-                                if !self.ignore_federation_hints
-                                    && packages.contains_key(log_id) =>
-                            {
-                                use dialoguer::{theme::ColorfulTheme, Confirm};
-
-                                let package_name = &packages.get(log_id).unwrap().name;
-
-                                if self.auto_accept_federation_hints
-                                    || Confirm::with_theme(&ColorfulTheme::default())
-                                        .with_prompt(format!(
-"Package `{package_name}` is not in `{current_registry}` registry.
-Registry recommends using `{registry}` registry for packages in `{namespace}` namespace.
-Accept recommendation y/N\n",
-current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().safe_label()),
-))
-                                        .default(true)
-                                        .interact()
-                                        .unwrap()
-                                {
-                                    let federated_registry_domain =
-                                        Some(RegistryDomain::from_str(registry)?);
-                                    self.store_namespace(
-                                        namespace.to_string(),
-                                        federated_registry_domain.clone().unwrap(),
-                                    )
-                                    .await?;
-
-                                    // filter packages with namespace in other registry
-                                    packages = packages
-                                        .into_iter()
-                                        .filter_map(|(log_id, package_info)| {
-                                            if package_info.name.namespace() == namespace {
-                                                if let Some(package_set) = federated_packages
-                                                    .get_mut(&federated_registry_domain)
-                                                {
-                                                    package_set.push(package_info);
-                                                } else {
-                                                    federated_packages.insert(
-                                                        federated_registry_domain.clone(),
-                                                        vec![package_info],
-                                                    );
-                                                }
-
-                                                None
-                                            } else {
-                                                Some((log_id, package_info))
-                                            }
-                                        })
-                                        .collect();
+    /// Gets the history of the keys of a package from its registry: each
+    /// grant and revocation of permissions, with a reference to the record
+    /// that made it.
+    ///
+    /// Unlike the package log, the key history is not verified by the
+    /// client.
+    pub async fn package_key_history(
+        &self,
+        package: &PackageName,
+    ) -> ClientResult<api::PackageKeyHistoryResponse> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        self.api
+            .get_package_key_history(registry_domain.as_ref(), &log_id)
+            .await
+            .map_err(|e| {
+                ClientError::translate_log_not_found(e, self.api.auth_token().is_some(), |id| {
+                    if id == &log_id {
+                        Some(package.clone())
+                    } else {
+                        None
+                    }
+                })
+            })
+    }
 
-                                    // continue fetching logs from this registry
-                                    continue;
-                                } else {
-                                    Err(ClientError::PackageDoesNotExist {
-                                        name: package_name.clone(),
-                                        has_auth_token,
-                                    })
-                                }
-                            }
-                            _ => {
+    /// Lists the most recently submitted records of a package with their
+    /// states, including the reasons rejected records were rejected.
+    ///
+    /// Records submitted by any client are listed, in submission order;
+    /// the registry limits how many records are returned.
+    pub async fn list_records(
+        &self,
+        package: &PackageName,
+        status_filter: Option<api::RecordStatusFilter>,
+    ) -> ClientResult<Vec<api::PackageRecord>> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let log_id = LogId::package_log::<Sha256>(package);
+        let response = self
+            .api
+            .list_package_records(
+                registry_domain.as_ref(),
+                &log_id,
+                &api::ListRecordsQuery {
+                    status: status_filter,
+                    limit: None,
+                },
+            )
+            .await
+            .map_err(|e| {
+                ClientError::translate_log_not_found(e, self.api.auth_token().is_some(), |id| {
+                    if id == &log_id {
+                        Some(package.clone())
+                    } else {
+                        None
+                    }
+                })
+            })?;
+        Ok(response.records)
+    }
+
+    /// Starts the given packages that were not fetched before from signed
+    /// snapshots of their logs at the checkpoint with the given log length,
+    /// if enabled and served by the registry.
+    ///
+    /// Returns the snapshots that were applied; the caller must verify them
+    /// against the operator log.
+    async fn apply_package_snapshots(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_length: RegistryLen,
+        packages: &mut IndexMap<LogId, &mut PackageInfo>,
+    ) -> Result<Vec<SerdeEnvelope<PackageSnapshot>>, ClientError> {
+        let log_ids = packages
+            .iter()
+            .filter(|(_, p)| {
+                p.head_registry_index.is_none()
+                    && self
+                        .signing_policy
+                        .as_ref()
+                        .and_then(|policy| policy.namespace_keys(p.name.namespace()))
+                        .is_none()
+            })
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        if !self.package_snapshots
+            || log_ids.is_empty()
+            || !self.api.capabilities().await?.package_snapshots
+        {
+            return Ok(Vec::new());
+        }
+
+        let responses = futures_util::future::join_all(log_ids.iter().map(|id| {
+            self.api
+                .get_package_snapshot(registry_domain, id, log_length)
+        }))
+        .await;
+
+        let mut snapshots = Vec::with_capacity(log_ids.len());
+        for (log_id, response) in log_ids.iter().zip(responses) {
+            let response = match response {
+                Ok(response) => response,
+                // packages that are not found are reported when fetching logs
+                Err(api::ClientError::Package(PackageError::LogNotFound(_)))
+                | Err(api::ClientError::LogNotFoundWithHint(..)) => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            let package = packages.get_mut(log_id).unwrap();
+            let snapshot = response.snapshot.as_ref();
+            if &snapshot.log_id != log_id {
+                return Err(ClientError::InvalidPackageSnapshot {
+                    name: package.name.clone(),
+                    reason: "the snapshot is of another package log".to_string(),
+                });
+            }
+
+            package.state = snapshot
+                .state()
+                .map_err(|e| ClientError::InvalidPackageSnapshot {
+                    name: package.name.clone(),
+                    reason: e.to_string(),
+                })?;
+            package.head_registry_index = Some(snapshot.registry_index);
+            package.head_fetch_token = Some(response.fetch_token);
+            tracing::debug!(
+                "package `{name}` was started from a snapshot at registry index {index}",
+                name = package.name,
+                index = snapshot.registry_index
+            );
+            snapshots.push(response.snapshot);
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Updates the given packages to the latest checkpoint of a registry in
+    /// batches, storing each batch once it has been validated and proven.
+    ///
+    /// Packages already stored at the latest checkpoint are skipped, so a
+    /// sync that was interrupted resumes from the first incomplete batch.
+    async fn update_packages_and_return_federated_packages<'a>(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        packages: impl IntoIterator<Item = &'a mut PackageInfo>,
+    ) -> Result<IndexMap<Option<RegistryDomain>, Vec<&'a mut PackageInfo>>, ClientError> {
+        let mut packages = packages.into_iter().peekable();
+        let mut federated_packages: IndexMap<Option<RegistryDomain>, Vec<&mut PackageInfo>> =
+            IndexMap::new();
+
+        loop {
+            let batch = packages
+                .by_ref()
+                .take(self.sync_batch_size)
+                .collect::<Vec<_>>();
+
+            for (registry_domain, packages) in
+                self.update_package_batch(registry_domain, batch).await?
+            {
+                federated_packages
+                    .entry(registry_domain)
+                    .or_default()
+                    .extend(packages);
+            }
+
+            if packages.peek().is_none() {
+                return Ok(federated_packages);
+            }
+        }
+    }
+
+    async fn update_package_batch<'a>(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        packages: Vec<&'a mut PackageInfo>,
+    ) -> Result<IndexMap<Option<RegistryDomain>, Vec<&'a mut PackageInfo>>, ClientError> {
+        let ts_checkpoint = self.api.latest_checkpoint(registry_domain).await?;
+        let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+
+        tracing::debug!(
+            log_length = checkpoint.log_length,
+            registry_header = ?registry_domain,
+            "updating to checkpoint",
+        );
+
+        // operator log info
+        let mut operator = self
+            .registry
+            .load_operator(registry_domain)
+            .await?
+            .unwrap_or_default();
+        let operator_head = operator.state.head().clone();
+
+        // map package names to package logs that need to be updated
+        let verified_at = SystemTime::now();
+        let mut current = Vec::new();
+        let mut packages = packages
+            .into_iter()
+            .filter_map(|p| {
+                p.verified_at = Some(verified_at);
+                match &p.checkpoint {
+                    // Don't bother updating if the package is already at the specified checkpoint
+                    // If `registry` field is not set, then update.
+                    Some(c) if p.registry.is_some() && c == checkpoint => {
+                        current.push(p);
+                        None
+                    }
+                    _ => Some((LogId::package_log::<Sha256>(&p.name), p)),
+                }
+            })
+            .inspect(|(_, p)| tracing::info!("package `{name}` will be updated", name = p.name))
+            .collect::<IndexMap<_, _>>();
+
+        // packages already at the checkpoint are verified against it
+        for package in current {
+            self.registry
+                .store_package(registry_domain, package)
+                .await?;
+        }
+
+        // if operator log and all packages are up to date at the latest checkpoint, then return
+        if operator.checkpoint.is_some_and(|c| &c == checkpoint) && packages.is_empty() {
+            return Ok(IndexMap::default());
+        }
+
+        // start packages that were not fetched before from snapshots; the
+        // snapshots are verified once the operator log has been validated
+        let snapshots = self
+            .apply_package_snapshots(registry_domain, checkpoint.log_length, &mut packages)
+            .await?;
+
+        // federated packages in other registries
+        let mut federated_packages: IndexMap<Option<RegistryDomain>, Vec<&mut PackageInfo>> =
+            IndexMap::with_capacity(packages.len());
+
+        // Fetch pages of log records while the previous page is validated;
+        // fetching restarts if the set of packages changes
+        let has_auth_token = self.api.auth_token().is_some();
+        loop {
+            let (sender, mut receiver) = mpsc::channel(FETCH_PIPELINE_DEPTH);
+            let fetch = self.fetch_log_pages(
+                registry_domain,
+                checkpoint.log_length,
+                operator.head_fetch_token.clone(),
+                packages
+                    .iter()
+                    .map(|(id, p)| (id.clone(), p.head_fetch_token.clone()))
+                    .collect(),
+                sender,
+            );
+
+            let validate = async {
+                while let Some(page) = receiver.recv().await {
+                    let response = match page {
+                        Ok(res) => Ok(res),
+                        Err(err) => match &err {
+                            api::ClientError::Fetch(FetchError::LogNotFound(log_id))
+                            | api::ClientError::Package(PackageError::LogNotFound(log_id)) => {
                                 if let Some(name) = packages.get(log_id).map(|p| p.name.clone()) {
                                     Err(ClientError::PackageDoesNotExist {
                                         name,
@@ -998,78 +2488,214 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
                                     Err(ClientError::Api(err))
                                 }
                             }
+
+                            api::ClientError::LogNotFoundWithHint(log_id, hint)
+                                if self.disable_interactive =>
+                            {
+                                let name = packages.get(log_id).unwrap().name.clone();
+
+                                match hint.to_str().ok().map(|s| s.split_once('=')) {
+                                    Some(Some((namespace, registry)))
+                                        if packages.contains_key(log_id) =>
+                                    {
+                                        Err(ClientError::PackageDoesNotExistWithHintHeader {
+                                            name,
+                                            has_auth_token,
+                                            hint_namespace: namespace.to_string(),
+                                            hint_registry: registry.to_string(),
+                                        })
+                                    }
+                                    _ => Err(ClientError::PackageDoesNotExist {
+                                        name,
+                                        has_auth_token,
+                                    }),
+                                }
+                            }
+
+                            #[cfg(feature = "cli-interactive")]
+                            api::ClientError::LogNotFoundWithHint(log_id, hint) => {
+                                match hint.to_str().ok().map(|s| s.split_once('=')) {
+                                    Some(Some((namespace, registry)))
+                                        if !self.ignore_federation_hints
+                                            && packages.contains_key(log_id) =>
+                                    {
+                                        use dialoguer::{theme::ColorfulTheme, Confirm};
+
+                                        let package_name = &packages.get(log_id).unwrap().name;
+
+                                        if self.auto_accept_federation_hints
+                                            || Confirm::with_theme(&ColorfulTheme::default())
+                                                .with_prompt(format!(
+        "Package `{package_name}` is not in `{current_registry}` registry.
+        Registry recommends using `{registry}` registry for packages in `{namespace}` namespace.
+        Accept recommendation y/N\n",
+        current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().safe_label()),
+        ))
+                                                .default(true)
+                                                .interact()
+                                                .unwrap()
+                                        {
+                                            let federated_registry_domain =
+                                                Some(RegistryDomain::from_str(registry)?);
+                                            self.store_namespace(
+                                                namespace.to_string(),
+                                                federated_registry_domain.clone().unwrap(),
+                                            )
+                                            .await?;
+
+                                            // filter packages with namespace in other registry
+                                            packages = std::mem::take(&mut packages)
+                                                .into_iter()
+                                                .filter_map(|(log_id, package_info)| {
+                                                    if package_info.name.namespace() == namespace {
+                                                        if let Some(package_set) =
+                                                            federated_packages
+                                                                .get_mut(&federated_registry_domain)
+                                                        {
+                                                            package_set.push(package_info);
+                                                        } else {
+                                                            federated_packages.insert(
+                                                                federated_registry_domain.clone(),
+                                                                vec![package_info],
+                                                            );
+                                                        }
+
+                                                        None
+                                                    } else {
+                                                        Some((log_id, package_info))
+                                                    }
+                                                })
+                                                .collect();
+
+                                            // restart fetching logs from this registry
+                                            return Ok(true);
+                                        } else {
+                                            Err(ClientError::PackageDoesNotExist {
+                                                name: package_name.clone(),
+                                                has_auth_token,
+                                            })
+                                        }
+                                    }
+                                    _ => {
+                                        if let Some(name) =
+                                            packages.get(log_id).map(|p| p.name.clone())
+                                        {
+                                            Err(ClientError::PackageDoesNotExist {
+                                                name,
+                                                has_auth_token,
+                                            })
+                                        } else {
+                                            Err(ClientError::Api(err))
+                                        }
+                                    }
+                                }
+                            }
+                            _ => Err(ClientError::Api(err)),
+                        },
+                    }?;
+
+                    for record in response.operator {
+                        let proto_envelope: PublishedProtoEnvelope<
+                            warg_protocol::operator::OperatorRecord,
+                        > = record.envelope.try_into()?;
+
+                        // skip over records that has already seen
+                        if operator.head_registry_index.is_none()
+                            || proto_envelope.registry_index > operator.head_registry_index.unwrap()
+                        {
+                            operator.state = std::mem::take(&mut operator.state)
+                                .validate(&proto_envelope.envelope)
+                                .map_err(|inner| ClientError::OperatorValidationFailed { inner })?;
+                            operator.head_registry_index = Some(proto_envelope.registry_index);
+                            operator.head_fetch_token = Some(record.fetch_token);
                         }
                     }
-                    _ => Err(ClientError::Api(err)),
-                },
-            }?;
 
-            for record in response.operator {
-                let proto_envelope: PublishedProtoEnvelope<operator::OperatorRecord> =
-                    record.envelope.try_into()?;
-
-                // skip over records that has already seen
-                if operator.head_registry_index.is_none()
-                    || proto_envelope.registry_index > operator.head_registry_index.unwrap()
-                {
-                    operator.state = operator
-                        .state
-                        .validate(&proto_envelope.envelope)
-                        .map_err(|inner| ClientError::OperatorValidationFailed { inner })?;
-                    operator.head_registry_index = Some(proto_envelope.registry_index);
-                    operator.head_fetch_token = Some(record.fetch_token);
-                }
-            }
+                    // Validate the records of each package log on the blocking thread
+                    // pool, as signature verification is CPU-bound
+                    let mut validations = Vec::with_capacity(response.packages.len());
+                    for (log_id, records) in response.packages {
+                        let package = packages.get_mut(&log_id).ok_or_else(|| {
+                            anyhow!("received records for unknown package log `{log_id}`")
+                        })?;
 
-            for (log_id, records) in response.packages {
-                let package = packages.get_mut(&log_id).ok_or_else(|| {
-                    anyhow!("received records for unknown package log `{log_id}`")
-                })?;
+                        let mut envelopes = Vec::with_capacity(records.len());
+                        for record in records {
+                            let proto_envelope: PublishedProtoEnvelope<package::PackageRecord> =
+                                record.envelope.try_into()?;
 
-                for record in records {
-                    let proto_envelope: PublishedProtoEnvelope<package::PackageRecord> =
-                        record.envelope.try_into()?;
+                            // skip over records that has already seen
+                            if package.head_registry_index.is_none()
+                                || proto_envelope.registry_index
+                                    > package.head_registry_index.unwrap()
+                            {
+                                package.head_registry_index = Some(proto_envelope.registry_index);
+                                package.head_fetch_token = Some(record.fetch_token);
+                                envelopes.push(proto_envelope.envelope);
+                            }
+                        }
 
-                    // skip over records that has already seen
-                    if package.head_registry_index.is_none()
-                        || proto_envelope.registry_index > package.head_registry_index.unwrap()
-                    {
                         let state = std::mem::take(&mut package.state);
-                        package.state =
-                            state.validate(&proto_envelope.envelope).map_err(|inner| {
-                                ClientError::PackageValidationFailed {
-                                    name: package.name.clone(),
-                                    inner,
-                                }
-                            })?;
-                        package.head_registry_index = Some(proto_envelope.registry_index);
-                        package.head_fetch_token = Some(record.fetch_token);
+                        let name = package.name.clone();
+                        let signing_policy = self.signing_policy.clone();
+                        let permit = self.api.verification_permit().await;
+                        validations.push((
+                            log_id,
+                            tokio::task::spawn_blocking(move || {
+                                let _permit = permit;
+                                envelopes.iter().try_fold(state, |state, envelope| {
+                                    if let Some(policy) = &signing_policy {
+                                        if !policy.allows(&name, envelope.key_id()) {
+                                            return Err(ClientError::UntrustedSigningKey {
+                                                name: name.clone(),
+                                                key_id: envelope.key_id().clone(),
+                                            });
+                                        }
+                                    }
+
+                                    state.validate(envelope).map_err(|inner| {
+                                        ClientError::PackageValidationFailed {
+                                            name: name.clone(),
+                                            inner,
+                                        }
+                                    })
+                                })
+                            }),
+                        ));
                     }
-                }
 
-                // At this point, the package log should not be empty
-                if package.state.head().is_none() {
-                    return Err(ClientError::PackageLogEmpty {
-                        name: package.name.clone(),
-                    });
+                    for (log_id, validation) in validations {
+                        let package = packages.get_mut(&log_id).unwrap();
+                        package.state = validation
+                            .await
+                            .map_err(|e| anyhow!("failed to validate package records: {e}"))??;
+
+                        // At this point, the package log should not be empty
+                        if package.state.head().is_none() {
+                            return Err(ClientError::PackageLogEmpty {
+                                name: package.name.clone(),
+                            });
+                        }
+                    }
                 }
-            }
 
-            if !response.more {
+                Ok::<_, ClientError>(false)
+            };
+
+            let (_, restart) = tokio::join!(fetch, validate);
+            if !restart? {
                 break;
             }
         }
 
         // verify checkpoint signature
-        TimestampedCheckpoint::verify(
-            operator
-                .state
-                .public_key(ts_checkpoint.to_owned().to_owned().key_id())
-                .ok_or(ClientError::InvalidCheckpointKeyId {
+        verify::verify_checkpoint(
+            &ts_checkpoint,
+            operator.state.public_key(ts_checkpoint.key_id()).ok_or(
+                ClientError::InvalidCheckpointKeyId {
                     key_id: ts_checkpoint.key_id().clone(),
-                })?,
-            &ts_checkpoint.as_ref().encode(),
-            ts_checkpoint.signature(),
+                },
+            )?,
         )
         .or(Err(ClientError::InvalidCheckpointSignature))?;
 
@@ -1077,6 +2703,45 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
         let mut leaf_indices = Vec::with_capacity(packages.len() + 1 /* for operator */);
         let mut leafs = Vec::with_capacity(leaf_indices.len());
 
+        // verify the snapshots and prove the inclusion of their heads
+        for snapshot in &snapshots {
+            let Some(package) = packages.get(&snapshot.as_ref().log_id) else {
+                continue;
+            };
+            let invalid = |reason: &str| ClientError::InvalidPackageSnapshot {
+                name: package.name.clone(),
+                reason: reason.to_string(),
+            };
+
+            let key = operator
+                .state
+                .public_key(snapshot.key_id())
+                .ok_or_else(|| invalid("the snapshot is not signed by an operator key"))?;
+            PackageSnapshot::verify(key, &snapshot.as_ref().encode(), snapshot.signature())
+                .map_err(|_| invalid("the snapshot signature is invalid"))?;
+
+            let PackageSnapshot {
+                log_id,
+                log_length,
+                registry_index,
+                record_id,
+                ..
+            } = snapshot.as_ref();
+            if *log_length > checkpoint.log_length || registry_index >= log_length {
+                return Err(invalid(
+                    "the snapshot is not of a checkpoint of the registry",
+                ));
+            }
+
+            if package.head_registry_index != Some(*registry_index) {
+                leaf_indices.push(*registry_index);
+                leafs.push(LogLeaf {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                });
+            }
+        }
+
         // operator record inclusion
         if let Some(index) = operator.head_registry_index {
             leaf_indices.push(index);
@@ -1100,68 +2765,106 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
                 return Err(ClientError::PackageLogEmpty {
                     name: package.name.clone(),
                 });
-            }
-        }
-
-        if !leafs.is_empty() {
-            self.api
-                .prove_inclusion(
-                    registry_domain,
-                    InclusionRequest {
-                        log_length: checkpoint.log_length,
-                        leafs: leaf_indices,
-                    },
-                    checkpoint,
-                    &leafs,
-                )
-                .await?;
+            }
         }
 
-        if let Some(from) = self.registry.load_checkpoint(registry_domain).await? {
-            let from_log_length = from.as_ref().checkpoint.log_length;
-            let to_log_length = ts_checkpoint.as_ref().checkpoint.log_length;
+        // Only prove the inclusion of log heads that were not already proven
+        // for this checkpoint
+        let mut proofs = self
+            .registry
+            .load_verified_proofs(registry_domain)
+            .await?
+            .filter(|proofs| &proofs.checkpoint == checkpoint)
+            .unwrap_or_else(|| VerifiedProofs::new(checkpoint.clone()));
+        let (leaf_indices, leafs): (Vec<_>, Vec<_>) = leaf_indices
+            .into_iter()
+            .zip(leafs)
+            .filter(|(index, leaf)| !proofs.is_included(*index, leaf))
+            .unzip();
+
+        // Prove inclusion of the log heads and consistency with the last
+        // stored checkpoint concurrently
+        let from = self.registry.load_checkpoint(registry_domain).await?;
+        let inclusion = async {
+            if !leafs.is_empty() {
+                self.api
+                    .prove_inclusion(
+                        registry_domain,
+                        InclusionRequest {
+                            log_length: checkpoint.log_length,
+                            leafs: leaf_indices.clone(),
+                        },
+                        checkpoint,
+                        &leafs,
+                    )
+                    .await?;
+            }
 
-            match from_log_length.cmp(&to_log_length) {
-                Ordering::Greater => {
-                    return Err(ClientError::CheckpointLogLengthRewind {
-                        from: from_log_length,
-                        to: to_log_length,
-                    });
-                }
-                Ordering::Less => {
-                    self.api
-                        .prove_log_consistency(
-                            registry_domain,
-                            ConsistencyRequest {
-                                from: from_log_length,
-                                to: to_log_length,
-                            },
-                            Cow::Borrowed(&from.as_ref().checkpoint.log_root),
-                            Cow::Borrowed(&ts_checkpoint.as_ref().checkpoint.log_root),
-                        )
-                        .await?
-                }
-                Ordering::Equal => {
-                    if from.as_ref().checkpoint.log_root
-                        != ts_checkpoint.as_ref().checkpoint.log_root
-                        || from.as_ref().checkpoint.map_root
-                            != ts_checkpoint.as_ref().checkpoint.map_root
-                    {
-                        return Err(ClientError::CheckpointChangedLogRootOrMapRoot {
-                            log_length: from_log_length,
+            Ok(())
+        };
+        let consistency = async {
+            if let Some(from) = &from {
+                let from_log_length = from.as_ref().checkpoint.log_length;
+                let to_log_length = ts_checkpoint.as_ref().checkpoint.log_length;
+
+                match from_log_length.cmp(&to_log_length) {
+                    Ordering::Greater => {
+                        return Err(ClientError::CheckpointLogLengthRewind {
+                            from: from_log_length,
+                            to: to_log_length,
                         });
                     }
+                    Ordering::Less => {
+                        self.api
+                            .prove_log_consistency(
+                                registry_domain,
+                                ConsistencyRequest {
+                                    from: from_log_length,
+                                    to: to_log_length,
+                                },
+                                Cow::Borrowed(&from.as_ref().checkpoint.log_root),
+                                Cow::Borrowed(&ts_checkpoint.as_ref().checkpoint.log_root),
+                            )
+                            .await?
+                    }
+                    Ordering::Equal => {
+                        if from.as_ref().checkpoint.log_root
+                            != ts_checkpoint.as_ref().checkpoint.log_root
+                            || from.as_ref().checkpoint.map_root
+                                != ts_checkpoint.as_ref().checkpoint.map_root
+                        {
+                            return Err(ClientError::CheckpointChangedLogRootOrMapRoot {
+                                log_length: from_log_length,
+                            });
+                        }
+                    }
                 }
             }
+
+            Ok(())
+        };
+        tokio::try_join!(inclusion, consistency)?;
+
+        if !leafs.is_empty() {
+            proofs
+                .inclusions
+                .extend(leaf_indices.into_iter().zip(leafs));
+            self.registry
+                .store_verified_proofs(registry_domain, &proofs)
+                .await?;
         }
 
         operator.registry = registry_domain
             .cloned()
             .or_else(|| Some(self.url().registry_domain()));
         operator.checkpoint = Some(checkpoint.clone()); // updated to this checkpoint
+        let operator_updated = operator.state.head() != &operator_head;
         self.registry
             .store_operator(registry_domain, operator)
             .await?;
+        if operator_updated {
+            self.clear_namespace_registries();
+        }
 
         for package in packages.values_mut() {
             package.registry = registry_domain
@@ -1181,6 +2884,138 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
         Ok(federated_packages)
     }
 
+    /// Fetches pages of log records up to the given log length.
+    ///
+    /// Each page is sent as soon as it is received and the next page is
+    /// requested from the fetch tokens of the previous page, so fetching
+    /// stops at the first error, the last page, or when the receiver is
+    /// dropped.
+    async fn fetch_log_pages(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_length: RegistryLen,
+        mut operator: Option<String>,
+        mut packages: IndexMap<LogId, Option<String>>,
+        pages: mpsc::Sender<Result<FetchLogsResponse, api::ClientError>>,
+    ) {
+        let mut page_size = self.fetch_page_size.map(PageSize::new);
+        let mut throttled_retries = 0;
+        loop {
+            let request = FetchLogsRequest {
+                log_length,
+                operator: operator.as_deref().map(Cow::Borrowed),
+                limit: page_size.map(|size| size.limit()),
+                // last known fetch token for each package log ID
+                packages: Cow::Borrowed(&packages),
+            };
+
+            let start = Instant::now();
+            let timeout = page_size.map(|_| paging::PAGE_TIMEOUT);
+            let mut responses = self.fetch_log_page(registry_domain, request, timeout).await;
+
+            let mut more = false;
+            let mut retry = false;
+            let mut delay = None;
+            while let Some(response) = responses.next().await {
+                let next = match &response {
+                    Ok(response) => {
+                        for warning in response.warnings.iter() {
+                            tracing::warn!("Fetch warning from registry: {}", warning.message);
+                        }
+
+                        if let Some(record) = response.operator.last() {
+                            operator = Some(record.fetch_token.clone());
+                        }
+
+                        for (log_id, records) in &response.packages {
+                            if let (Some(token), Some(record)) =
+                                (packages.get_mut(log_id), records.last())
+                            {
+                                *token = Some(record.fetch_token.clone());
+                            }
+                        }
+
+                        more = response.more;
+                        throttled_retries = 0;
+                        true
+                    }
+                    Err(e)
+                        if e.is_timeout() && page_size.as_mut().is_some_and(PageSize::shrink) =>
+                    {
+                        tracing::debug!(
+                            limit = page_size.map(|size| size.limit()),
+                            "fetching logs timed out; retrying with a smaller page",
+                        );
+                        retry = true;
+                        break;
+                    }
+                    Err(e)
+                        if e.is_throttled()
+                            && throttled_retries < self.retry_policy.max_retries =>
+                    {
+                        let wait = self.retry_policy.delay(e);
+                        tracing::debug!("fetching logs was throttled; retrying in {wait:?}");
+                        throttled_retries += 1;
+                        delay = Some(wait);
+                        retry = true;
+                        break;
+                    }
+                    Err(_) => false,
+                };
+
+                if pages.send(response).await.is_err() || !next {
+                    return;
+                }
+            }
+
+            if retry {
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+                continue;
+            }
+
+            if let Some(size) = &mut page_size {
+                size.fetched(start.elapsed(), more);
+            }
+
+            if !more {
+                return;
+            }
+        }
+    }
+
+    /// Fetches a page of log records, as a streamed response if streaming
+    /// fetches are enabled.
+    async fn fetch_log_page(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: FetchLogsRequest<'_>,
+        timeout: Option<Duration>,
+    ) -> BoxStream<'static, Result<FetchLogsResponse, api::ClientError>> {
+        if !self.streaming_fetch {
+            let response = match timeout {
+                Some(timeout) => {
+                    self.api
+                        .fetch_logs_with_timeout(registry_domain, request, timeout)
+                        .await
+                }
+                None => self.api.fetch_logs(registry_domain, request).await,
+            };
+            return stream::once(future::ready(response)).boxed();
+        }
+
+        let responses = match timeout {
+            Some(timeout) => {
+                self.api
+                    .fetch_logs_stream_with_timeout(registry_domain, request, timeout)
+                    .await
+            }
+            None => self.api.fetch_logs_stream(registry_domain, request).await,
+        };
+        responses.unwrap_or_else(|e| stream::once(future::ready(Err(e))).boxed())
+    }
+
     /// Update checkpoint for list of packages
     async fn update_checkpoints<'a>(
         &self,
@@ -1198,16 +3033,25 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
             }
         }
 
-        while let Some((registry_domain, packages)) = federated_packages.pop() {
-            for (registry_domain, packages) in self
-                .update_packages_and_return_federated_packages(registry_domain.as_ref(), packages)
-                .await?
+        // Sync the registries concurrently; packages federated to other
+        // registries are synced once the current registries are done
+        let progress = SyncProgressTracker::new(self.sync_progress.as_ref());
+        while !federated_packages.is_empty() {
+            let updates = std::mem::take(&mut federated_packages)
                 .into_iter()
-            {
-                if let Some(package_set) = federated_packages.get_mut(&registry_domain) {
-                    package_set.extend(packages);
-                } else {
-                    federated_packages.insert(registry_domain, packages);
+                .map(|(registry_domain, packages)| {
+                    self.sync_registry(registry_domain, packages, &progress)
+                })
+                .collect::<Vec<_>>();
+            let mut updates =
+                futures_util::stream::iter(updates).buffer_unordered(MAX_CONCURRENT_REGISTRY_SYNCS);
+
+            while let Some(federated) = updates.try_next().await? {
+                for (registry_domain, packages) in federated {
+                    federated_packages
+                        .entry(registry_domain)
+                        .or_default()
+                        .extend(packages);
                 }
             }
         }
@@ -1215,6 +3059,22 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
         Ok(())
     }
 
+    /// Syncs packages from a registry, reporting the progress of the sync.
+    async fn sync_registry<'a>(
+        &self,
+        registry_domain: Option<RegistryDomain>,
+        packages: Vec<&'a mut PackageInfo>,
+        progress: &SyncProgressTracker<'_>,
+    ) -> Result<IndexMap<Option<RegistryDomain>, Vec<&'a mut PackageInfo>>, ClientError> {
+        let count = packages.len();
+        progress.start(count);
+        let federated = self
+            .update_packages_and_return_federated_packages(registry_domain.as_ref(), packages)
+            .await?;
+        progress.finish(count, federated.values().map(Vec::len).sum());
+        Ok(federated)
+    }
+
     /// Fetches package logs without checking local storage first.
     pub async fn fetch_packages(
         &self,
@@ -1244,6 +3104,13 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
             .load_package(registry_domain.as_ref(), name)
             .await?
         {
+            Some(mut info) if self.is_outdated(&info) => {
+                tracing::info!(
+                    "log for package `{name}` was not verified recently enough; updating"
+                );
+                self.update_checkpoints([&mut info]).await?;
+                Ok(info)
+            }
             Some(mut info) => {
                 tracing::info!("log for package `{name}` already exists in storage");
                 if info.registry.is_none() {
@@ -1256,11 +3123,54 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
             None => {
                 let mut info = PackageInfo::new(name.clone());
                 self.update_checkpoints([&mut info]).await?;
+                self.update_advisories([&mut info]).await?;
                 Ok(info)
             }
         }
     }
 
+    /// Determines if a package in client storage was last verified before
+    /// the maximum checkpoint age.
+    fn is_outdated(&self, info: &PackageInfo) -> bool {
+        let Some(max_age) = self.max_checkpoint_age else {
+            return false;
+        };
+
+        info.verified_at.map_or(true, |verified_at| {
+            verified_at.elapsed().is_ok_and(|elapsed| elapsed > max_age)
+        })
+    }
+
+    /// Lists the namespaces defined or imported by the registry.
+    ///
+    /// Packages can only be published to namespaces defined by the registry.
+    pub async fn namespaces(&self) -> ClientResult<Vec<api::Namespace>> {
+        Ok(self.api.namespaces(None).await?.namespaces)
+    }
+
+    /// Gets the validated state of the registry's operator log.
+    ///
+    /// The operator log is first updated to the latest checkpoint of the
+    /// registry.
+    pub async fn operator_info(&self) -> ClientResult<OperatorLogInfo> {
+        // Operator log state stored by older clients does not include the
+        // checkpoint key history, so the log must be fetched again
+        if let Some(operator) = self.registry.load_operator(None).await? {
+            if operator.state.head().is_some() && operator.state.checkpoint_key_history().is_empty()
+            {
+                self.registry
+                    .store_operator(None, OperatorInfo::default())
+                    .await?;
+            }
+        }
+
+        self.update_packages_and_return_federated_packages(None, Vec::new())
+            .await?;
+
+        let operator = self.registry.load_operator(None).await?.unwrap_or_default();
+        Ok(OperatorLogInfo::new(&operator))
+    }
+
     async fn get_package_record(
         &self,
         registry_domain: Option<&RegistryDomain>,
@@ -1268,29 +3178,47 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
         log_id: &LogId,
         record_id: &RecordId,
     ) -> ClientResult<PackageRecord> {
-        let record = self
-            .api
+        self.api
             .get_package_record(registry_domain, log_id, record_id)
             .await
-            .map_err(|e| match e {
-                api::ClientError::Package(PackageError::Rejection(reason)) => {
-                    ClientError::PublishRejected {
-                        name: package.clone(),
-                        reason,
-                        record_id: record_id.clone(),
-                    }
+            .map_err(|e| self.package_record_error(e, package, log_id, record_id))
+    }
+
+    fn package_record_error(
+        &self,
+        e: api::ClientError,
+        package: &PackageName,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> ClientError {
+        match e {
+            api::ClientError::Package(PackageError::Rejection(reason)) => {
+                ClientError::PublishRejected {
+                    name: package.clone(),
+                    reason,
+                    record_id: record_id.clone(),
                 }
-                e => {
-                    ClientError::translate_log_not_found(e, self.api.auth_token().is_some(), |id| {
-                        if id == log_id {
-                            Some(package.clone())
-                        } else {
-                            None
-                        }
-                    })
+            }
+            api::ClientError::Package(PackageError::StagedPublishNotFound(_)) => {
+                ClientError::StagedPublishNotFound {
+                    name: package.clone(),
+                    record_id: record_id.clone(),
                 }
-            })?;
-        Ok(record)
+            }
+            api::ClientError::Package(PackageError::PendingApprovalNotFound(_)) => {
+                ClientError::PendingApprovalNotFound {
+                    name: package.clone(),
+                    record_id: record_id.clone(),
+                }
+            }
+            e => ClientError::translate_log_not_found(e, self.api.auth_token().is_some(), |id| {
+                if id == log_id {
+                    Some(package.clone())
+                } else {
+                    None
+                }
+            }),
+        }
     }
 
     /// Downloads the content for the specified digest into client storage.
@@ -1308,11 +3236,13 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
                 Ok(path)
             }
             None => {
+                let (size, stream) = self
+                    .api
+                    .download_content_with_size(registry_domain, digest)
+                    .await?;
+                self.check_available_space(digest, size)?;
                 self.content
-                    .store_content(
-                        Box::pin(self.api.download_content(registry_domain, digest).await?),
-                        Some(digest),
-                    )
+                    .store_content(Box::pin(stream), Some(digest))
                     .await?;
 
                 self.content
@@ -1324,6 +3254,31 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
         }
     }
 
+    /// Checks that content storage has space for content of the given
+    /// expected size.
+    ///
+    /// Content of unknown size, or storage with unknown available space,
+    /// passes the check.
+    fn check_available_space(&self, digest: &AnyHash, size: Option<u64>) -> ClientResult<()> {
+        let Some(size) = size else {
+            return Ok(());
+        };
+        let Some(available) = self.content.available_space()? else {
+            return Ok(());
+        };
+
+        let required = size.saturating_add(self.storage_reserve);
+        if required > available {
+            return Err(ClientError::InsufficientStorage {
+                digest: digest.clone(),
+                required,
+                available,
+            });
+        }
+
+        Ok(())
+    }
+
     /// Downloads the content for the specified digest as a stream.
     ///
     /// If the content already exists in client storage, it is read from the client storage.
@@ -1353,6 +3308,53 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
 pub type FileSystemClient =
     Client<FileSystemRegistryStorage, FileSystemContentStorage, FileSystemNamespaceMapStorage>;
 
+/// A Warg registry client with type-erased storage.
+///
+/// Clients with different storage implementations may be held in the same
+/// collection once converted with [`Client::into_dyn`].
+pub type DynClient =
+    Client<Arc<dyn RegistryStorage>, Arc<dyn ContentStorage>, Arc<dyn NamespaceMapStorage>>;
+
+impl<R, C, N> Client<R, C, N>
+where
+    R: RegistryStorage + 'static,
+    C: ContentStorage + 'static,
+    N: NamespaceMapStorage + 'static,
+{
+    /// Converts the client into a [`DynClient`].
+    ///
+    /// The configuration of the client is kept.
+    pub fn into_dyn(self) -> DynClient {
+        Client {
+            registry: Arc::new(self.registry),
+            content: Arc::new(self.content),
+            namespace_map: Arc::new(self.namespace_map),
+            api: self.api,
+            ignore_federation_hints: self.ignore_federation_hints,
+            auto_accept_federation_hints: self.auto_accept_federation_hints,
+            disable_interactive: self.disable_interactive,
+            keyring_backend: self.keyring_backend,
+            keys: self.keys,
+            package_policy: self.package_policy,
+            license_allowlist: self.license_allowlist,
+            advisory_policy: self.advisory_policy,
+            provenance_policy: self.provenance_policy,
+            signing_policy: self.signing_policy,
+            semver_check: self.semver_check,
+            namespace_overrides: self.namespace_overrides,
+            sync_batch_size: self.sync_batch_size,
+            fetch_page_size: self.fetch_page_size,
+            max_checkpoint_age: self.max_checkpoint_age,
+            sync_progress: self.sync_progress,
+            storage_reserve: self.storage_reserve,
+            package_snapshots: self.package_snapshots,
+            streaming_fetch: self.streaming_fetch,
+            retry_policy: self.retry_policy,
+            namespace_registries: self.namespace_registries,
+        }
+    }
+}
+
 /// A result of an attempt to lock client storage.
 pub enum StorageLockResult<T> {
     /// The storage lock was acquired.
@@ -1372,10 +3374,12 @@ impl FileSystemClient {
                 .unwrap_or(DEFAULT_REGISTRY),
         )?;
 
-        let url = if let Some(warg_url) =
-            api::Client::new(checking_url_for_well_known.to_string(), None)?
-                .well_known_config()
-                .await?
+        let url = if let Some(warg_url) = request_metadata_from_config(
+            api::Client::new(checking_url_for_well_known.to_string(), None)?,
+            config,
+        )?
+        .well_known_config()
+        .await?
         {
             if !disable_interactive && warg_url != checking_url_for_well_known {
                 println!(
@@ -1416,6 +3420,8 @@ impl FileSystemClient {
             registries_dir,
             content_dir,
             namespace_map_path,
+            fallback_content_dir,
+            storage_layout,
         } = Self::storage_paths(registry, config, disable_interactive).await?;
 
         let (keyring_backend, keys) = if cfg!(feature = "keyring") {
@@ -1430,27 +3436,43 @@ impl FileSystemClient {
         }
 
         let (packages, content, namespace_map) = match (
-            FileSystemRegistryStorage::try_lock(registries_dir.clone())?,
-            FileSystemContentStorage::try_lock(content_dir.clone())?,
+            FileSystemRegistryStorage::try_lock_with_layout(
+                registries_dir.clone(),
+                storage_layout,
+            )?,
+            FileSystemContentStorage::try_lock_with_layout(content_dir.clone(), storage_layout)?,
             FileSystemNamespaceMapStorage::new(namespace_map_path.clone()),
         ) {
-            (Some(packages), Some(content), namespace_map) => (packages, content, namespace_map),
+            (Some(packages), Some(content), namespace_map) => (
+                packages,
+                with_fallback_content(content, fallback_content_dir),
+                namespace_map,
+            ),
             (None, _, _) => return Ok(StorageLockResult::NotAcquired(registries_dir)),
             (_, None, _) => return Ok(StorageLockResult::NotAcquired(content_dir)),
         };
 
-        Ok(StorageLockResult::Acquired(Self::new(
-            url.into_url(),
-            packages,
-            content,
-            namespace_map,
-            auth_token,
-            config.ignore_federation_hints,
-            config.auto_accept_federation_hints,
-            disable_interactive,
-            keyring_backend,
-            keys,
-        )?))
+        let mut client = Self::builder()
+            .with_url(url.into_url())
+            .with_registry_storage(packages)
+            .with_content_storage(content)
+            .with_namespace_map_storage(namespace_map)
+            .with_auth_token(auth_token)
+            .with_ignore_federation_hints(config.ignore_federation_hints)
+            .with_auto_accept_federation_hints(config.auto_accept_federation_hints)
+            .with_disable_interactive(disable_interactive)
+            .with_keyring_backend(keyring_backend)
+            .with_keys(keys)
+            .build()?;
+        client.api = request_metadata_from_config(client.api, config)?;
+        client.package_policy = package_policy_from_config(config)?;
+        client.license_allowlist = config.license_allowlist.clone();
+        client.advisory_policy = config.advisory_policy.clone();
+        client.fetch_page_size = config.fetch_page_size;
+        client.max_checkpoint_age = config.max_checkpoint_age_secs.map(Duration::from_secs);
+        client.signing_policy = config.signing_policy.clone().map(Arc::new);
+
+        Ok(StorageLockResult::Acquired(client))
     }
 
     /// Attempts to create a client for the given registry URL.
@@ -1490,6 +3512,8 @@ impl FileSystemClient {
             registries_dir,
             content_dir,
             namespace_map_path,
+            fallback_content_dir,
+            storage_layout,
         } = Self::storage_paths(registry, config, disable_interactive).await?;
 
         let (keyring_backend, keys) = if cfg!(feature = "keyring") {
@@ -1503,18 +3527,33 @@ impl FileSystemClient {
             auth_token = crate::keyring::Keyring::from_config(config)?.get_auth_token(&url)?
         }
 
-        Self::new(
-            url.into_url(),
-            FileSystemRegistryStorage::lock(registries_dir)?,
-            FileSystemContentStorage::lock(content_dir)?,
-            FileSystemNamespaceMapStorage::new(namespace_map_path),
-            auth_token,
-            config.ignore_federation_hints,
-            config.auto_accept_federation_hints,
-            disable_interactive,
-            keyring_backend,
-            keys,
-        )
+        let mut client = Self::builder()
+            .with_url(url.into_url())
+            .with_registry_storage(FileSystemRegistryStorage::lock_with_layout(
+                registries_dir,
+                storage_layout,
+            )?)
+            .with_content_storage(with_fallback_content(
+                FileSystemContentStorage::lock_with_layout(content_dir, storage_layout)?,
+                fallback_content_dir,
+            ))
+            .with_namespace_map_storage(FileSystemNamespaceMapStorage::new(namespace_map_path))
+            .with_auth_token(auth_token)
+            .with_ignore_federation_hints(config.ignore_federation_hints)
+            .with_auto_accept_federation_hints(config.auto_accept_federation_hints)
+            .with_disable_interactive(disable_interactive)
+            .with_keyring_backend(keyring_backend)
+            .with_keys(keys)
+            .build()?;
+        client.api = request_metadata_from_config(client.api, config)?;
+        client.package_policy = package_policy_from_config(config)?;
+        client.license_allowlist = config.license_allowlist.clone();
+        client.advisory_policy = config.advisory_policy.clone();
+        client.fetch_page_size = config.fetch_page_size;
+        client.max_checkpoint_age = config.max_checkpoint_age_secs.map(Duration::from_secs);
+        client.signing_policy = config.signing_policy.clone().map(Arc::new);
+
+        Ok(client)
     }
 
     /// Creates a client for the given registry URL.
@@ -1531,6 +3570,60 @@ impl FileSystemClient {
     }
 }
 
+/// Sets the fallback content directory of the given content storage, if any.
+fn with_fallback_content(
+    content: FileSystemContentStorage,
+    fallback_dir: Option<PathBuf>,
+) -> FileSystemContentStorage {
+    match fallback_dir {
+        Some(dir) => content.with_fallback(dir),
+        None => content,
+    }
+}
+
+/// Applies the request metadata specified in the client configuration.
+fn request_metadata_from_config(mut api: api::Client, config: &Config) -> Result<api::Client> {
+    if let Some(suffix) = &config.user_agent_suffix {
+        api = api.with_user_agent_suffix(suffix)?;
+    }
+
+    for (name, value) in &config.request_headers {
+        api = api.with_header(name, value)?;
+    }
+
+    if !config.connect.is_default() {
+        api = api.with_connect_options(config.connect.clone())?;
+    }
+
+    api = api
+        .with_checkpoint_clock(config.checkpoint_clock)
+        .with_concurrency_limits(ConcurrencyLimits::from_config(config));
+
+    Ok(api)
+}
+
+/// Loads the package policy specified in the client configuration.
+fn package_policy_from_config(config: &Config) -> ClientResult<Option<Arc<dyn PackagePolicy>>> {
+    let Some(path) = &config.package_policy else {
+        return Ok(None);
+    };
+
+    #[cfg(feature = "policy-component")]
+    {
+        Ok(Some(Arc::new(policy::ComponentPackagePolicy::from_file(
+            path,
+        )?)))
+    }
+
+    #[cfg(not(feature = "policy-component"))]
+    {
+        Err(ClientError::Other(anyhow!(
+            "package policy `{path}` requires the `policy-component` feature",
+            path = path.display()
+        )))
+    }
+}
+
 /// Represents information about a downloaded package.
 #[derive(Debug, Clone)]
 pub struct PackageDownload {
@@ -1540,6 +3633,50 @@ pub struct PackageDownload {
     pub digest: AnyHash,
     /// The path to the downloaded package contents.
     pub path: PathBuf,
+    /// The deprecation of the package, if it is deprecated.
+    ///
+    /// Releases of a deprecated package are still downloaded; callers
+    /// should warn about the deprecation.
+    pub deprecation: Option<Deprecation>,
+    /// The vulnerability advisories affecting the downloaded version.
+    ///
+    /// Advisories are fetched when the package is first downloaded and on
+    /// each update of client storage.
+    pub advisories: Vec<api::VulnerabilityAdvisory>,
+}
+
+/// Represents a downloaded attestation of a package release.
+#[derive(Debug, Clone)]
+pub struct AttestationDownload {
+    /// The kind of the attestation (e.g. `https://slsa.dev/provenance/v1`).
+    pub kind: String,
+    /// The digest of the attestation contents.
+    pub digest: AnyHash,
+    /// The key that attached the attestation.
+    pub by: signing::KeyID,
+    /// The path to the downloaded attestation contents.
+    pub path: PathBuf,
+}
+
+/// Represents a downloaded artifact of a package release.
+#[derive(Debug, Clone)]
+pub struct ArtifactDownload {
+    /// The declared type of the artifact.
+    pub content_type: ContentType,
+    /// The digest of the artifact contents.
+    pub digest: AnyHash,
+    /// The path to the downloaded artifact contents.
+    pub path: PathBuf,
+}
+
+/// Represents a custom section fetched from the content of a package
+/// release.
+#[derive(Debug, Clone)]
+pub struct CustomSection {
+    /// The name of the custom section.
+    pub name: String,
+    /// The data of the custom section.
+    pub data: Vec<u8>,
 }
 
 /// Represents information about a downloaded package.
@@ -1548,6 +3685,10 @@ pub struct PackageDownloadInfo {
     pub version: Version,
     /// The digest of the package contents.
     pub digest: AnyHash,
+    /// The deprecation of the package, if it is deprecated.
+    pub deprecation: Option<Deprecation>,
+    /// The vulnerability advisories affecting the downloaded version.
+    pub advisories: Vec<api::VulnerabilityAdvisory>,
 }
 
 /// Represents an error returned by Warg registry clients.
@@ -1573,6 +3714,15 @@ pub enum ClientError {
     #[error("invalid checkpoint signature")]
     InvalidCheckpointSignature,
 
+    /// A package log snapshot failed verification.
+    #[error("invalid snapshot of package `{name}`: {reason}")]
+    InvalidPackageSnapshot {
+        /// The name of the package.
+        name: PackageName,
+        /// The reason the snapshot is invalid.
+        reason: String,
+    },
+
     /// Checkpoint signature failed verification
     #[error("invalid checkpoint key ID `{key_id}`")]
     InvalidCheckpointKeyId {
@@ -1588,7 +3738,7 @@ pub enum ClientError {
     #[error("operator failed validation: {inner}")]
     OperatorValidationFailed {
         /// The validation error.
-        inner: operator::ValidationError,
+        inner: warg_protocol::operator::ValidationError,
     },
 
     /// The package already exists and cannot be initialized.
@@ -1660,6 +3810,52 @@ pub enum ClientError {
         name: PackageName,
     },
 
+    /// The package violates the client's package policy.
+    #[error("version `{version}` of package `{name}` violates policy: {}", violations.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    PackagePolicyViolation {
+        /// The package that violates the policy.
+        name: PackageName,
+        /// The version of the package that violates the policy.
+        version: Version,
+        /// The violations of the policy.
+        violations: Vec<PolicyViolation>,
+    },
+
+    /// The package version is affected by vulnerability advisories denied
+    /// by the client's advisory policy.
+    #[error("version `{version}` of package `{name}` is affected by denied advisories: {}", advisories.iter().map(|a| format!("`{id}` ({severity})", id = a.id, severity = a.severity)).collect::<Vec<_>>().join(", "))]
+    AdvisoryPolicyViolation {
+        /// The package that violates the policy.
+        name: PackageName,
+        /// The version of the package that violates the policy.
+        version: Version,
+        /// The advisories denied by the policy.
+        advisories: Vec<api::VulnerabilityAdvisory>,
+    },
+
+    /// A resolved dependency could not be verified against its registry.
+    #[error("dependency `{name}` from registry `{registry}` could not be verified: {reason}")]
+    DependencyNotVerified {
+        /// The dependency that could not be verified.
+        name: PackageName,
+        /// The registry the dependency was resolved from.
+        registry: String,
+        /// The reason the dependency could not be verified.
+        reason: String,
+    },
+
+    /// The package is not licensed under a license in the client's
+    /// license allowlist.
+    #[error("version `{version}` of package `{name}` has license `{}` which is not in the license allowlist", license.as_deref().unwrap_or("none"))]
+    LicenseNotAllowed {
+        /// The package that is not allowed.
+        name: PackageName,
+        /// The version of the package that is not allowed.
+        version: Version,
+        /// The license of the package, if specified.
+        license: Option<String>,
+    },
+
     /// The package failed validation.
     #[error("package `{name}` failed validation: {inner}")]
     PackageValidationFailed {
@@ -1669,6 +3865,37 @@ pub enum ClientError {
         inner: package::ValidationError,
     },
 
+    /// A record of a package is signed by a key that the client's signing
+    /// policy does not trust for the package's namespace.
+    #[error("a record of package `{name}` is signed by key `{key_id}`, which is not trusted for its namespace")]
+    UntrustedSigningKey {
+        /// The package of the record.
+        name: PackageName,
+        /// The key that signed the record.
+        key_id: signing::KeyID,
+    },
+
+    /// The record to publish is not valid for the package log.
+    #[error("the record to publish for package `{name}` is invalid: {inner}")]
+    InvalidPublish {
+        /// The package being published.
+        name: PackageName,
+        /// The validation error.
+        inner: package::ValidationError,
+    },
+
+    /// A release to publish makes breaking interface changes from the
+    /// previous release without a major version bump.
+    #[error("version {version} of package `{name}` makes breaking interface changes without a major version bump: {changes}", changes = changes.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    IncompatibleRelease {
+        /// The package being published.
+        name: PackageName,
+        /// The version being released.
+        version: Version,
+        /// The breaking interface changes from the previous release.
+        changes: Vec<warg_protocol::interface::InterfaceChange>,
+    },
+
     /// Content was not found during a publish operation.
     #[error("content with digest `{digest}` was not found in client storage")]
     ContentNotFound {
@@ -1685,6 +3912,17 @@ pub enum ClientError {
         expected: AnyHash,
     },
 
+    /// Content storage does not have enough space for the content.
+    #[error("not enough space to store content with digest `{digest}`: {required} bytes are required but {available} bytes are available")]
+    InsufficientStorage {
+        /// The digest of the content.
+        digest: AnyHash,
+        /// The space required to store the content, in bytes.
+        required: u64,
+        /// The space available in content storage, in bytes.
+        available: u64,
+    },
+
     /// The package log is empty and cannot be validated.
     #[error("package log is empty and cannot be validated")]
     PackageLogEmpty {
@@ -1703,6 +3941,32 @@ pub enum ClientError {
         reason: String,
     },
 
+    /// More than one content upload of a publish operation failed.
+    ///
+    /// A publish whose only failed upload is returned as the error of that
+    /// upload.
+    #[error("{count} content uploads of package `{name}` failed: {summary}", count = failures.len(), summary = failures.iter().map(|(digest, e)| format!("`{digest}`: {e}")).collect::<Vec<_>>().join("; "))]
+    ContentUploadsFailed {
+        /// The package being published.
+        name: PackageName,
+        /// The record identifier of the record whose content was uploaded.
+        record_id: RecordId,
+        /// The digest of each content that failed to upload, with its
+        /// error.
+        failures: Vec<(AnyHash, ClientError)>,
+    },
+
+    /// A publish operation did not complete within the wait timeout.
+    #[error("the publishing of package `{name}` did not complete within {timeout:?}")]
+    PublishTimedOut {
+        /// The package being published.
+        name: PackageName,
+        /// The record identifier for the record still processing.
+        record_id: RecordId,
+        /// The timeout that elapsed.
+        timeout: Duration,
+    },
+
     /// A publish operation was rejected due to conflicting pending publish.
     #[error("the publishing of package `{name}` was rejected due to conflicting pending publish of record `{pending_record_id}`")]
     ConflictPendingPublish {
@@ -1718,6 +3982,58 @@ pub enum ClientError {
     #[error("the package is still missing content after all content was uploaded")]
     PackageMissingContent,
 
+    /// A publish operation is staged and awaits a commit.
+    #[error(
+        "the publishing of package `{name}` is staged; commit record `{record_id}` to publish it"
+    )]
+    PublishStaged {
+        /// The package being published.
+        name: PackageName,
+        /// The record identifier for the staged record.
+        record_id: RecordId,
+    },
+
+    /// A publish operation awaits approval by another key.
+    #[error(
+        "the publishing of package `{name}` awaits approval; record `{record_id}` must be approved by another key to publish it"
+    )]
+    PublishAwaitingApproval {
+        /// The package being published.
+        name: PackageName,
+        /// The record identifier for the record awaiting approval.
+        record_id: RecordId,
+    },
+
+    /// The registry does not support embargoed releases.
+    #[error("the registry does not support embargoed releases")]
+    EmbargoNotSupported,
+
+    /// The registry does not support declaring external content sources.
+    #[error("the registry does not support declaring content sources")]
+    ContentSourcesNotSupported,
+
+    /// The registry does not support staged publishes.
+    #[error("the registry does not support staged publishes")]
+    StagedPublishNotSupported,
+
+    /// The record is not a staged publish.
+    #[error("record `{record_id}` of package `{name}` is not a staged publish")]
+    StagedPublishNotFound {
+        /// The package of the record.
+        name: PackageName,
+        /// The record identifier that is not staged.
+        record_id: RecordId,
+    },
+
+    /// The record is not awaiting approval.
+    #[error("record `{record_id}` of package `{name}` is not awaiting approval")]
+    PendingApprovalNotFound {
+        /// The package of the record.
+        name: PackageName,
+        /// The record identifier that is not awaiting approval.
+        record_id: RecordId,
+    },
+
     /// The registry provided a latest checkpoint with a log length less than a previously provided
     /// checkpoint log length.
     #[error("registry rewinded checkpoints; latest checkpoint log length `{to}` is less than previously received checkpoint log length `{from}`")]
@@ -1744,6 +4060,12 @@ pub enum ClientError {
     #[error(transparent)]
     Api(#[from] api::ClientError),
 
+    /// The operation did not complete before its deadline.
+    ///
+    /// See the [`deadline`] module.
+    #[error("the operation did not complete before its deadline")]
+    DeadlineExceeded,
+
     /// An error occurred while performing a client operation.
     #[error("{0:?}")]
     Other(#[from] anyhow::Error),
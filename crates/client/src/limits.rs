@@ -0,0 +1,97 @@
+//! Module for limiting the concurrency of client operations.
+//!
+//! Embedders that share a runtime with other workloads may bound how much
+//! of it a client uses with [`ConcurrencyLimits`]. Each limit applies to
+//! every operation of the client it is set on, so concurrent operations
+//! (e.g. downloads run in parallel by the embedder) share the same limits.
+
+use crate::Config;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Limits on the concurrency of a client.
+///
+/// A limit of `None` leaves the concurrency unbounded; a limit of zero is
+/// treated as one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimits {
+    /// The maximum number of content downloads in progress at once.
+    ///
+    /// A download is in progress until its content has been read.
+    pub max_parallel_downloads: Option<usize>,
+    /// The maximum number of content uploads in progress at once.
+    ///
+    /// The missing content of a publish is uploaded concurrently up to this
+    /// limit.
+    pub max_parallel_uploads: Option<usize>,
+    /// The maximum number of requests to registries awaiting a response at
+    /// once.
+    ///
+    /// A request stops counting against the limit once the response headers
+    /// are received.
+    pub max_in_flight_requests: Option<usize>,
+    /// The maximum number of CPU-bound verifications (e.g. of proofs and
+    /// package records) run at once on the blocking thread pool.
+    pub verification_workers: Option<usize>,
+}
+
+impl ConcurrencyLimits {
+    /// Gets the concurrency limits of a client configuration.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_parallel_downloads: config.max_parallel_downloads,
+            max_parallel_uploads: config.max_parallel_uploads,
+            max_in_flight_requests: config.max_in_flight_requests,
+            verification_workers: config.verification_workers,
+        }
+    }
+}
+
+/// A limit on the number of operations of a kind run at once.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Limit(Option<Arc<Semaphore>>);
+
+impl Limit {
+    fn new(max: Option<usize>) -> Self {
+        Self(max.map(|max| Arc::new(Semaphore::new(max.max(1)))))
+    }
+
+    /// Waits for an operation to be allowed to run.
+    ///
+    /// The operation counts against the limit until the returned permit is
+    /// dropped; unlimited operations have no permit.
+    pub(crate) async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        match &self.0 {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("limit semaphore is never closed"),
+            ),
+            None => None,
+        }
+    }
+}
+
+/// The limits of a client, shared by all of its operations.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Limiter {
+    pub(crate) limits: ConcurrencyLimits,
+    pub(crate) downloads: Limit,
+    pub(crate) uploads: Limit,
+    pub(crate) requests: Limit,
+    pub(crate) verification: Limit,
+}
+
+impl Limiter {
+    pub(crate) fn new(limits: ConcurrencyLimits) -> Self {
+        Self {
+            limits,
+            downloads: Limit::new(limits.max_parallel_downloads),
+            uploads: Limit::new(limits.max_parallel_uploads),
+            requests: Limit::new(limits.max_in_flight_requests),
+            verification: Limit::new(limits.verification_workers),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! A module for dependency lockfiles.
+//!
+//! A lockfile records the exact release of each package a component
+//! depends on, along with the registry the package was resolved from and
+//! the checkpoint of that registry the release was verified at. Packages
+//! of a lockfile may be resolved from different registries through the
+//! client's namespace mappings.
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::registry::{Checkpoint, PackageName, RegistryLen};
+
+/// The default file name of a lockfile.
+pub const LOCKFILE_NAME: &str = "warg-lock.json";
+
+/// The current version of the lockfile format.
+pub const LOCKFILE_VERSION: u32 = 1;
+
+/// Represents a dependency lockfile.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Lockfile {
+    /// The version of the lockfile format.
+    pub version: u32,
+    /// The checkpoint each registry was verified at, keyed by registry
+    /// domain.
+    #[serde(default)]
+    pub registries: IndexMap<String, LockedRegistry>,
+    /// The locked packages, in resolution order.
+    #[serde(default)]
+    pub packages: Vec<LockedPackage>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            registries: Default::default(),
+            packages: Default::default(),
+        }
+    }
+}
+
+/// The checkpoint a registry of a lockfile was verified at.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LockedRegistry {
+    /// The log length of the checkpoint.
+    pub log_length: RegistryLen,
+    /// The log root of the checkpoint.
+    pub log_root: AnyHash,
+    /// The map root of the checkpoint.
+    pub map_root: AnyHash,
+}
+
+impl From<&Checkpoint> for LockedRegistry {
+    fn from(checkpoint: &Checkpoint) -> Self {
+        Self {
+            log_length: checkpoint.log_length,
+            log_root: checkpoint.log_root.clone(),
+            map_root: checkpoint.map_root.clone(),
+        }
+    }
+}
+
+/// A package release locked by a lockfile.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LockedPackage {
+    /// The name of the package.
+    pub name: PackageName,
+    /// The locked version of the package.
+    pub version: Version,
+    /// The domain of the registry the package was resolved from.
+    pub registry: String,
+    /// The content digest of the locked release.
+    pub digest: AnyHash,
+}
+
+impl Lockfile {
+    /// Reads and validates a lockfile from the given file path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read lockfile `{path}`", path = path.display()))?;
+
+        let lockfile: Self = serde_json::from_str(&contents).with_context(|| {
+            format!("failed to deserialize file `{path}`", path = path.display())
+        })?;
+
+        lockfile
+            .validate()
+            .with_context(|| format!("invalid lockfile `{path}`", path = path.display()))?;
+
+        Ok(lockfile)
+    }
+
+    /// Validates and writes the lockfile to the given file path.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        self.validate()?;
+
+        let mut contents = serde_json::to_string_pretty(self)
+            .with_context(|| format!("failed to serialize file `{path}`", path = path.display()))?;
+        contents.push('\n');
+
+        fs::write(path, contents)
+            .with_context(|| format!("failed to write file `{path}`", path = path.display()))
+    }
+
+    /// Validates the lockfile.
+    ///
+    /// Each package must be locked once and must name a registry with a
+    /// recorded checkpoint.
+    pub fn validate(&self) -> Result<()> {
+        if self.version != LOCKFILE_VERSION {
+            bail!(
+                "unsupported lockfile version {version}",
+                version = self.version
+            );
+        }
+
+        for (i, package) in self.packages.iter().enumerate() {
+            if self.packages[..i].iter().any(|p| p.name == package.name) {
+                bail!(
+                    "package `{name}` is locked more than once",
+                    name = package.name
+                );
+            }
+
+            if !self.registries.contains_key(&package.registry) {
+                bail!(
+                    "package `{name}` is locked to registry `{registry}`, which has no checkpoint",
+                    name = package.name,
+                    registry = package.registry
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the locked release of the given package.
+    pub fn package(&self, name: &PackageName) -> Option<&LockedPackage> {
+        self.packages.iter().find(|p| &p.name == name)
+    }
+
+    /// Gets the locked packages resolved from the given registry.
+    pub fn registry_packages<'a>(
+        &'a self,
+        registry: &'a str,
+    ) -> impl Iterator<Item = &'a LockedPackage> + 'a {
+        self.packages.iter().filter(move |p| p.registry == registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lockfile() -> Lockfile {
+        let digest: AnyHash =
+            "sha256:7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069"
+                .parse()
+                .unwrap();
+        let checkpoint = LockedRegistry {
+            log_length: 3,
+            log_root: digest.clone(),
+            map_root: digest.clone(),
+        };
+        Lockfile {
+            registries: [
+                ("registry.example.com".to_string(), checkpoint.clone()),
+                ("wasi.dev".to_string(), checkpoint),
+            ]
+            .into(),
+            packages: vec![
+                LockedPackage {
+                    name: PackageName::new("test:app").unwrap(),
+                    version: "1.0.0".parse().unwrap(),
+                    registry: "registry.example.com".to_string(),
+                    digest: digest.clone(),
+                },
+                LockedPackage {
+                    name: PackageName::new("wasi:http").unwrap(),
+                    version: "0.2.0".parse().unwrap(),
+                    registry: "wasi.dev".to_string(),
+                    digest,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_round_trips_lockfile() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(LOCKFILE_NAME);
+
+        let lockfile = lockfile();
+        lockfile.write_to_file(&path)?;
+
+        assert_eq!(Lockfile::from_file(&path)?, lockfile);
+        assert_eq!(
+            lockfile
+                .package(&PackageName::new("wasi:http")?)
+                .map(|p| p.registry.as_str()),
+            Some("wasi.dev")
+        );
+        assert_eq!(lockfile.registry_packages("wasi.dev").count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_invalid_lockfile() {
+        let mut unknown_registry = lockfile();
+        unknown_registry.registries.shift_remove("wasi.dev");
+        assert!(unknown_registry.validate().is_err());
+
+        let mut duplicate = lockfile();
+        duplicate.packages.push(duplicate.packages[0].clone());
+        assert!(duplicate.validate().is_err());
+
+        let unsupported = Lockfile {
+            version: 0,
+            ..lockfile()
+        };
+        assert!(unsupported.validate().is_err());
+    }
+}
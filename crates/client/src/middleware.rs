@@ -0,0 +1,55 @@
+//! Module for the middleware of API clients.
+//!
+//! Middleware intercepts every request sent by an [`api::Client`], including
+//! the requests that discover the registry, so embedders can sign requests,
+//! record metrics, serve responses from a cache or inject faults without
+//! forking the client.
+//!
+//! Middleware is run in the order it was added to the client: the first
+//! middleware sees a request first and its response last. A middleware
+//! passes a request on by calling [`Next::run`], and may instead respond
+//! without sending the request at all.
+//!
+//! Requests seen by middleware have the URL the client resolved, which may
+//! use one of the local transport schemes of the [`transport`] module.
+//!
+//! [`api::Client`]: crate::api::Client
+//! [`transport`]: crate::transport
+use crate::{api::ClientError, transport};
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use std::sync::Arc;
+
+/// A middleware of an API client.
+#[async_trait]
+pub trait Middleware: Send + Sync + 'static {
+    /// Handles a request, passing it on to the rest of the chain with
+    /// `next`.
+    async fn handle(&self, request: Request, next: Next<'_>) -> Result<Response, ClientError>;
+}
+
+/// The rest of the middleware chain of a request.
+///
+/// The chain ends with the transport that sends the request.
+pub struct Next<'a> {
+    client: &'a reqwest::Client,
+    middleware: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    /// Creates the middleware chain of a request sent with the given client.
+    pub(crate) fn new(client: &'a reqwest::Client, middleware: &'a [Arc<dyn Middleware>]) -> Self {
+        Self { client, middleware }
+    }
+
+    /// Runs the rest of the chain for the given request.
+    pub async fn run(mut self, request: Request) -> Result<Response, ClientError> {
+        match self.middleware.split_first() {
+            Some((middleware, rest)) => {
+                self.middleware = rest;
+                middleware.handle(request, self).await
+            }
+            None => transport::send(self.client, request).await,
+        }
+    }
+}
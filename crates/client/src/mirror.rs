@@ -0,0 +1,347 @@
+//! A module for verifying a registry mirror against its upstream registry.
+
+use super::{
+    api::{self, ConsistencyRequest, FetchError, FetchLogsRequest},
+    Client, ClientError, ClientResult,
+};
+use crate::storage::{ContentStorage, NamespaceMapStorage, RegistryStorage};
+use indexmap::{IndexMap, IndexSet};
+use reqwest::IntoUrl;
+use std::{borrow::Cow, cmp::Ordering};
+use warg_crypto::{hash::Sha256, signing::Signature};
+use warg_protocol::{
+    operator, package,
+    registry::{Checkpoint, LogId, PackageName, RecordId, RegistryIndex, RegistryLen},
+    PublishedProtoEnvelope,
+};
+
+/// A divergence found between a mirror and its upstream registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MirrorDivergence {
+    /// The mirror's checkpoint is ahead of the upstream's latest checkpoint.
+    CheckpointAhead {
+        /// The log length of the mirror's checkpoint.
+        mirror: RegistryLen,
+        /// The log length of the upstream's checkpoint.
+        upstream: RegistryLen,
+    },
+    /// The mirror's checkpoint is not a consistent prefix of the upstream's log.
+    InconsistentCheckpoint {
+        /// The log length of the mirror's checkpoint.
+        log_length: RegistryLen,
+        /// The reason the checkpoint is inconsistent.
+        reason: String,
+    },
+    /// The mirror and upstream serve a different number of records for a log.
+    RecordCountMismatch {
+        /// The log that diverged.
+        log_id: LogId,
+        /// The package name of the log; `None` for the operator log.
+        name: Option<PackageName>,
+        /// The number of records served by the mirror.
+        mirror: usize,
+        /// The number of records served by the upstream.
+        upstream: usize,
+    },
+    /// The mirror and upstream serve a different record at the same position in a log.
+    RecordMismatch {
+        /// The log that diverged.
+        log_id: LogId,
+        /// The package name of the log; `None` for the operator log.
+        name: Option<PackageName>,
+        /// The position of the record in the log.
+        index: usize,
+    },
+    /// A registry reported a package log as not found after serving some of
+    /// its records.
+    LogNotFound {
+        /// The log that diverged.
+        log_id: LogId,
+        /// The package name of the log.
+        name: PackageName,
+        /// Whether the upstream, rather than the mirror, did not find the log.
+        upstream: bool,
+        /// The number of records served before the log was not found.
+        records: usize,
+    },
+}
+
+/// The result of verifying a mirror against its upstream registry.
+#[derive(Debug, Clone)]
+pub struct MirrorReport {
+    /// The latest checkpoint of the mirror.
+    pub mirror_checkpoint: Checkpoint,
+    /// The latest checkpoint of the upstream.
+    pub upstream_checkpoint: Checkpoint,
+    /// The divergences found between the mirror and the upstream.
+    pub divergences: Vec<MirrorDivergence>,
+}
+
+impl MirrorReport {
+    /// Determines if the mirror is consistent with the upstream.
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// The identifying parts of a published record.
+#[derive(PartialEq, Eq)]
+struct RecordSummary {
+    registry_index: RegistryIndex,
+    record_id: RecordId,
+    signature: Signature,
+}
+
+impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Verifies that this client's registry is a faithful mirror of the given
+    /// upstream registry.
+    ///
+    /// The mirror's latest checkpoint must be a consistent prefix of the
+    /// upstream's log, and the operator log and every package log in client
+    /// storage must have identical records on both registries as of the
+    /// mirror's checkpoint.
+    ///
+    /// Divergences are reported rather than returned as errors; an error is
+    /// only returned if either registry could not be queried.
+    pub async fn verify_mirror(&self, upstream_url: impl IntoUrl) -> ClientResult<MirrorReport> {
+        let upstream = api::Client::new(upstream_url, None)?;
+
+        let mirror_checkpoint = self
+            .api
+            .latest_checkpoint(None)
+            .await?
+            .into_contents()
+            .checkpoint;
+        let upstream_checkpoint = upstream
+            .latest_checkpoint(None)
+            .await?
+            .into_contents()
+            .checkpoint;
+
+        tracing::info!(
+            mirror = mirror_checkpoint.log_length,
+            upstream = upstream_checkpoint.log_length,
+            "verifying mirror against upstream `{url}`",
+            url = upstream.url()
+        );
+
+        let mut report = MirrorReport {
+            mirror_checkpoint,
+            upstream_checkpoint,
+            divergences: Vec::new(),
+        };
+
+        let log_length = report.mirror_checkpoint.log_length;
+        match log_length.cmp(&report.upstream_checkpoint.log_length) {
+            Ordering::Greater => {
+                report.divergences.push(MirrorDivergence::CheckpointAhead {
+                    mirror: log_length,
+                    upstream: report.upstream_checkpoint.log_length,
+                });
+                return Ok(report);
+            }
+            Ordering::Equal => {
+                if report.mirror_checkpoint != report.upstream_checkpoint {
+                    report
+                        .divergences
+                        .push(MirrorDivergence::InconsistentCheckpoint {
+                            log_length,
+                            reason: "the log root or map root differs from the upstream".into(),
+                        });
+                    return Ok(report);
+                }
+            }
+            Ordering::Less => {
+                if let Err(e) = upstream
+                    .prove_log_consistency(
+                        None,
+                        ConsistencyRequest {
+                            from: log_length,
+                            to: report.upstream_checkpoint.log_length,
+                        },
+                        Cow::Borrowed(&report.mirror_checkpoint.log_root),
+                        Cow::Borrowed(&report.upstream_checkpoint.log_root),
+                    )
+                    .await
+                {
+                    report
+                        .divergences
+                        .push(MirrorDivergence::InconsistentCheckpoint {
+                            log_length,
+                            reason: e.to_string(),
+                        });
+                    return Ok(report);
+                }
+            }
+        }
+
+        // Compare the operator log and the package logs known to client storage
+        let names = self
+            .registry
+            .load_all_packages()
+            .await?
+            .into_values()
+            .flatten()
+            .map(|p| (LogId::package_log::<Sha256>(&p.name), p.name))
+            .collect::<IndexMap<_, _>>();
+
+        let mirror = fetch_records(&self.api, log_length, names.keys()).await?;
+        let upstream = match fetch_records(&upstream, log_length, names.keys()).await {
+            Ok(records) => records,
+            Err(e) => {
+                report
+                    .divergences
+                    .push(MirrorDivergence::InconsistentCheckpoint {
+                        log_length,
+                        reason: format!("failed to fetch logs from the upstream: {e}"),
+                    });
+                return Ok(report);
+            }
+        };
+
+        compare_log(
+            &mut report.divergences,
+            LogId::operator_log::<Sha256>(),
+            None,
+            &mirror.operator,
+            &upstream.operator,
+        );
+
+        for (log_id, name) in names {
+            for (records, upstream) in [(&mirror, false), (&upstream, true)] {
+                if records.not_found.contains(&log_id) {
+                    report.divergences.push(MirrorDivergence::LogNotFound {
+                        log_id: log_id.clone(),
+                        name: name.clone(),
+                        upstream,
+                        records: records.package(&log_id).len(),
+                    });
+                }
+            }
+
+            compare_log(
+                &mut report.divergences,
+                log_id.clone(),
+                Some(name),
+                mirror.package(&log_id),
+                upstream.package(&log_id),
+            );
+        }
+
+        Ok(report)
+    }
+}
+
+/// The records of a registry's logs as of a log length.
+#[derive(Default)]
+struct LogRecords {
+    operator: Vec<RecordSummary>,
+    packages: IndexMap<LogId, Vec<RecordSummary>>,
+    /// The package logs the registry reported as not found after serving
+    /// some of their records.
+    not_found: IndexSet<LogId>,
+}
+
+impl LogRecords {
+    fn package(&self, log_id: &LogId) -> &[RecordSummary] {
+        self.packages.get(log_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+/// Fetches all operator and package records as of the given log length.
+async fn fetch_records(
+    client: &api::Client,
+    log_length: RegistryLen,
+    log_ids: impl IntoIterator<Item = &LogId>,
+) -> Result<LogRecords, ClientError> {
+    let mut operator_token: Option<String> = None;
+    let mut package_tokens: IndexMap<LogId, Option<String>> =
+        log_ids.into_iter().map(|id| (id.clone(), None)).collect();
+
+    let mut records = LogRecords::default();
+    loop {
+        let response = match client
+            .fetch_logs(
+                None,
+                FetchLogsRequest {
+                    log_length,
+                    operator: operator_token.as_deref().map(Cow::Borrowed),
+                    limit: None,
+                    packages: Cow::Borrowed(&package_tokens),
+                },
+            )
+            .await
+        {
+            Ok(response) => response,
+            // A log that is unknown to the registry has no further records;
+            // stop requesting it, noting if it was known before
+            Err(api::ClientError::Fetch(FetchError::LogNotFound(log_id)))
+            | Err(api::ClientError::LogNotFoundWithHint(log_id, _))
+                if package_tokens.shift_remove(&log_id).is_some() =>
+            {
+                if records.packages.contains_key(&log_id) {
+                    records.not_found.insert(log_id);
+                }
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        for record in response.operator {
+            let envelope: PublishedProtoEnvelope<operator::OperatorRecord> =
+                record.envelope.try_into()?;
+            records.operator.push(RecordSummary {
+                registry_index: envelope.registry_index,
+                record_id: RecordId::operator_record::<Sha256>(&envelope.envelope),
+                signature: envelope.envelope.signature().clone(),
+            });
+            operator_token = Some(record.fetch_token);
+        }
+
+        for (log_id, package_records) in response.packages {
+            for record in package_records {
+                let envelope: PublishedProtoEnvelope<package::PackageRecord> =
+                    record.envelope.try_into()?;
+                records
+                    .packages
+                    .entry(log_id.clone())
+                    .or_default()
+                    .push(RecordSummary {
+                        registry_index: envelope.registry_index,
+                        record_id: RecordId::package_record::<Sha256>(&envelope.envelope),
+                        signature: envelope.envelope.signature().clone(),
+                    });
+                package_tokens.insert(log_id.clone(), Some(record.fetch_token));
+            }
+        }
+
+        if !response.more {
+            break;
+        }
+    }
+
+    Ok(records)
+}
+
+fn compare_log(
+    divergences: &mut Vec<MirrorDivergence>,
+    log_id: LogId,
+    name: Option<PackageName>,
+    mirror: &[RecordSummary],
+    upstream: &[RecordSummary],
+) {
+    if let Some(index) = mirror.iter().zip(upstream).position(|(m, u)| m != u) {
+        divergences.push(MirrorDivergence::RecordMismatch {
+            log_id,
+            name,
+            index,
+        });
+    } else if mirror.len() != upstream.len() {
+        divergences.push(MirrorDivergence::RecordCountMismatch {
+            log_id,
+            name,
+            mirror: mirror.len(),
+            upstream: upstream.len(),
+        });
+    }
+}
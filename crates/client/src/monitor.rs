@@ -0,0 +1,794 @@
+//! A module for monitoring registries for misbehavior.
+//!
+//! A [`Monitor`] tails a registry's log: each new checkpoint must extend the
+//! previous one, every record in the registry's ledger must be served and
+//! valid, and the checkpoint must be signed by a key of the operator log.
+//!
+//! Verified checkpoints, ledger entries, and records are appended to an
+//! archive directory; misbehavior is reported to the monitor's alert sinks.
+
+use crate::{
+    api::{self, ConsistencyRequest, FetchError, FetchLogsRequest},
+    ClientError, ClientResult, RegistryUrl,
+};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use reqwest::IntoUrl;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt,
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use warg_crypto::{
+    hash::{AnyHash, Sha256},
+    signing::KeyID,
+};
+use warg_protocol::{
+    operator, package,
+    registry::{
+        Checkpoint, LogId, LogLeaf, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
+    },
+    verify, PublishedProtoEnvelope, PublishedProtoEnvelopeBody, SerdeEnvelope,
+};
+use warg_transparency::log::{LogBuilder, StackLog};
+
+const STATE_FILE: &str = "state.json";
+pub(crate) const CHECKPOINTS_FILE: &str = "checkpoints.jsonl";
+const LEDGER_FILE: &str = "ledger.jsonl";
+pub(crate) const RECORDS_FILE: &str = "records.jsonl";
+const ALERTS_FILE: &str = "alerts.jsonl";
+
+/// Misbehavior of a registry detected by a monitor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum MonitorAlert {
+    /// The registry's latest checkpoint is shorter than a previous one.
+    CheckpointRewind {
+        /// The log length of the previous checkpoint.
+        from: RegistryLen,
+        /// The log length of the latest checkpoint.
+        to: RegistryLen,
+    },
+    /// The registry published two different checkpoints of the same length.
+    CheckpointFork {
+        /// The log length of the checkpoints.
+        log_length: RegistryLen,
+    },
+    /// The registry's latest checkpoint does not extend the previous one.
+    InconsistentCheckpoint {
+        /// The log length of the previous checkpoint.
+        from: RegistryLen,
+        /// The log length of the latest checkpoint.
+        to: RegistryLen,
+        /// The reason the checkpoint is inconsistent.
+        reason: String,
+    },
+    /// The registry's latest checkpoint is not signed by a checkpoint key of
+    /// the operator log.
+    InvalidCheckpointSignature {
+        /// The log length of the checkpoint.
+        log_length: RegistryLen,
+        /// The id of the key that signed the checkpoint.
+        key_id: KeyID,
+    },
+    /// The registry served a record that fails validation.
+    InvalidRecord {
+        /// The log of the record.
+        log_id: LogId,
+        /// The registry index of the record.
+        registry_index: RegistryIndex,
+        /// The reason the record is invalid.
+        reason: String,
+    },
+    /// The registry served a record that differs from its ledger entry.
+    LedgerMismatch {
+        /// The log of the record.
+        log_id: LogId,
+        /// The registry index of the record.
+        registry_index: RegistryIndex,
+    },
+    /// The registry did not serve a record in its ledger.
+    RecordWithheld {
+        /// The log of the record.
+        log_id: LogId,
+        /// The registry index of the record.
+        registry_index: RegistryIndex,
+    },
+}
+
+impl fmt::Display for MonitorAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CheckpointRewind { from, to } => write!(
+                f,
+                "checkpoint was rewound from log length {from} to log length {to}"
+            ),
+            Self::CheckpointFork { log_length } => write!(
+                f,
+                "a different checkpoint was published for log length {log_length}"
+            ),
+            Self::InconsistentCheckpoint { from, to, reason } => write!(
+                f,
+                "checkpoint of log length {to} is inconsistent with log length {from}: {reason}"
+            ),
+            Self::InvalidCheckpointSignature { log_length, key_id } => write!(
+                f,
+                "checkpoint of log length {log_length} has an invalid signature from key `{key_id}`"
+            ),
+            Self::InvalidRecord {
+                log_id,
+                registry_index,
+                reason,
+            } => write!(
+                f,
+                "record at registry index {registry_index} of log `{log_id}` is invalid: {reason}"
+            ),
+            Self::LedgerMismatch {
+                log_id,
+                registry_index,
+            } => write!(
+                f,
+                "record at registry index {registry_index} of log `{log_id}` differs from the ledger"
+            ),
+            Self::RecordWithheld {
+                log_id,
+                registry_index,
+            } => write!(
+                f,
+                "record at registry index {registry_index} of log `{log_id}` was not served"
+            ),
+        }
+    }
+}
+
+/// A destination for the alerts raised by a monitor.
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    /// Sends an alert raised for the given registry.
+    async fn send(&self, registry: &RegistryUrl, alert: &MonitorAlert) -> anyhow::Result<()>;
+}
+
+/// An alert sink that logs alerts as errors.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogAlertSink;
+
+#[async_trait]
+impl AlertSink for LogAlertSink {
+    async fn send(&self, registry: &RegistryUrl, alert: &MonitorAlert) -> anyhow::Result<()> {
+        tracing::error!("registry `{registry}` misbehaved: {alert}");
+        Ok(())
+    }
+}
+
+/// An alert sink that posts alerts as JSON to a webhook.
+///
+/// The body of each request is an object with the `registry` URL and the
+/// `alert`.
+#[derive(Debug, Clone)]
+pub struct WebhookAlertSink {
+    client: reqwest::Client,
+    url: reqwest::Url,
+}
+
+impl WebhookAlertSink {
+    /// Creates a new webhook alert sink for the given URL.
+    pub fn new(url: impl IntoUrl) -> ClientResult<Self> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url: url.into_url().context("invalid webhook URL")?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookBody<'a> {
+    registry: String,
+    alert: &'a MonitorAlert,
+}
+
+#[async_trait]
+impl AlertSink for WebhookAlertSink {
+    async fn send(&self, registry: &RegistryUrl, alert: &MonitorAlert) -> anyhow::Result<()> {
+        self.client
+            .post(self.url.clone())
+            .json(&WebhookBody {
+                registry: registry.to_string(),
+                alert,
+            })
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// The position of the monitor in a log.
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogTail<S> {
+    state: S,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fetch_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    registry_index: Option<RegistryIndex>,
+}
+
+impl<S> LogTail<S> {
+    fn is_new(&self, registry_index: RegistryIndex) -> bool {
+        self.registry_index
+            .map_or(true, |index| registry_index > index)
+    }
+}
+
+/// The state of a monitor, persisted in its archive directory.
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorState {
+    /// The last verified checkpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checkpoint: Option<SerdeEnvelope<TimestampedCheckpoint>>,
+    #[serde(default)]
+    operator: LogTail<operator::LogState>,
+    #[serde(default)]
+    packages: IndexMap<LogId, LogTail<package::LogState>>,
+    /// The last checkpoint alerts were raised for; it is not verified again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rejected: Option<Checkpoint>,
+}
+
+/// A ledger entry of the archive.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LedgerEntry {
+    registry_index: RegistryIndex,
+    #[serde(flatten)]
+    leaf: LogLeaf,
+}
+
+/// A record of the archive.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchivedRecord {
+    pub(crate) log_id: LogId,
+    #[serde(flatten)]
+    pub(crate) envelope: PublishedProtoEnvelopeBody,
+}
+
+/// An alert of the archive.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchivedAlert<'a> {
+    checkpoint: &'a Checkpoint,
+    #[serde(flatten)]
+    alert: &'a MonitorAlert,
+}
+
+/// The verified extension of a registry's log to a new checkpoint.
+struct Advance {
+    state: MonitorState,
+    log: StackLog<Sha256, LogLeaf>,
+    ledger: Vec<LedgerEntry>,
+    records: Vec<ArchivedRecord>,
+}
+
+/// Continuously verifies that a registry's log is append-only and archives
+/// its checkpoints and records.
+pub struct Monitor {
+    api: api::Client,
+    archive: PathBuf,
+    state: MonitorState,
+    log: StackLog<Sha256, LogLeaf>,
+    sinks: Vec<Arc<dyn AlertSink>>,
+}
+
+impl Monitor {
+    /// Creates a monitor for the registry at the given URL that archives to
+    /// the given directory.
+    ///
+    /// If the directory contains the archive of a previous monitor of the
+    /// registry, monitoring resumes from its last verified checkpoint.
+    pub fn new(url: impl IntoUrl, archive_dir: impl Into<PathBuf>) -> ClientResult<Self> {
+        let api = api::Client::new(url, None)?;
+        let archive = archive_dir.into();
+        fs::create_dir_all(&archive).with_context(|| {
+            format!(
+                "failed to create archive directory `{path}`",
+                path = archive.display()
+            )
+        })?;
+
+        let state_path = archive.join(STATE_FILE);
+        let state: MonitorState = if state_path.is_file() {
+            let contents = fs::read_to_string(&state_path).with_context(|| {
+                format!(
+                    "failed to read monitor state `{path}`",
+                    path = state_path.display()
+                )
+            })?;
+            serde_json::from_str(&contents).with_context(|| {
+                format!(
+                    "failed to deserialize monitor state `{path}`",
+                    path = state_path.display()
+                )
+            })?
+        } else {
+            MonitorState::default()
+        };
+
+        // Rebuild the log from the archived ledger; entries archived after the
+        // last verified checkpoint were not committed and are ignored
+        let log_length = state
+            .checkpoint
+            .as_ref()
+            .map_or(0, |c| c.as_ref().checkpoint.log_length);
+        let mut log = StackLog::default();
+        for entry in read_lines::<LedgerEntry>(&archive.join(LEDGER_FILE))?
+            .into_iter()
+            .take(log_length)
+        {
+            log.push(&entry.leaf);
+        }
+
+        if log.length() != log_length {
+            return Err(ClientError::Other(anyhow!(
+                "archive `{path}` is missing ledger entries for log length {log_length}",
+                path = archive.display()
+            )));
+        }
+
+        Ok(Self {
+            api,
+            archive,
+            state,
+            log,
+            sinks: Vec::new(),
+        })
+    }
+
+    /// Adds a sink to send alerts to.
+    pub fn with_alert_sink(mut self, sink: impl AlertSink + 'static) -> Self {
+        self.sinks.push(Arc::new(sink));
+        self
+    }
+
+    /// Gets the URL of the monitored registry.
+    pub fn url(&self) -> &RegistryUrl {
+        self.api.url()
+    }
+
+    /// Gets the archive directory of the monitor.
+    pub fn archive_dir(&self) -> &Path {
+        &self.archive
+    }
+
+    /// Gets the last checkpoint verified by the monitor.
+    pub fn checkpoint(&self) -> Option<&Checkpoint> {
+        self.state
+            .checkpoint
+            .as_ref()
+            .map(|c| &c.as_ref().checkpoint)
+    }
+
+    /// Polls the registry for a new checkpoint and verifies it.
+    ///
+    /// Returns the alerts raised for the registry's latest checkpoint, which
+    /// have also been sent to the monitor's alert sinks. A checkpoint with
+    /// alerts is not archived and the monitor stays at its last verified
+    /// checkpoint.
+    ///
+    /// An error is returned if the registry could not be queried or the
+    /// archive could not be written.
+    pub async fn poll(&mut self) -> ClientResult<Vec<MonitorAlert>> {
+        let ts_checkpoint = self.api.latest_checkpoint(None).await?;
+        let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+        if self.checkpoint() == Some(checkpoint) || self.state.rejected.as_ref() == Some(checkpoint)
+        {
+            return Ok(Vec::new());
+        }
+
+        tracing::debug!(
+            log_length = checkpoint.log_length,
+            "verifying checkpoint of registry `{url}`",
+            url = self.url()
+        );
+
+        let mut alerts = Vec::new();
+        match self.advance(&ts_checkpoint, &mut alerts).await? {
+            Some(advance) if alerts.is_empty() => {
+                self.commit(ts_checkpoint, advance)?;
+            }
+            _ => {
+                self.reject(checkpoint, &alerts).await?;
+            }
+        }
+
+        Ok(alerts)
+    }
+
+    /// Verifies the given checkpoint against the monitor state.
+    ///
+    /// Returns `None` if verification could not continue after an alert.
+    async fn advance(
+        &self,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+        alerts: &mut Vec<MonitorAlert>,
+    ) -> ClientResult<Option<Advance>> {
+        let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+        let from = self.checkpoint().map_or(0, |c| c.log_length);
+        let to = checkpoint.log_length;
+
+        if let Some(previous) = self.checkpoint() {
+            match from.cmp(&to) {
+                Ordering::Greater => {
+                    alerts.push(MonitorAlert::CheckpointRewind { from, to });
+                    return Ok(None);
+                }
+                Ordering::Equal => {
+                    alerts.push(MonitorAlert::CheckpointFork { log_length: to });
+                    return Ok(None);
+                }
+                Ordering::Less => {
+                    match self
+                        .api
+                        .prove_log_consistency(
+                            None,
+                            ConsistencyRequest { from, to },
+                            Cow::Borrowed(&previous.log_root),
+                            Cow::Borrowed(&checkpoint.log_root),
+                        )
+                        .await
+                    {
+                        Ok(()) => {}
+                        Err(
+                            e @ (api::ClientError::Communication(_)
+                            | api::ClientError::LocalTransport(_)
+                            | api::ClientError::UnexpectedResponse { .. }),
+                        ) => return Err(e.into()),
+                        Err(e) => {
+                            alerts.push(MonitorAlert::InconsistentCheckpoint {
+                                from,
+                                to,
+                                reason: e.to_string(),
+                            });
+                            return Ok(None);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut state = self.state.clone();
+        state.rejected = None;
+
+        // Extend the log with the new ledger entries, which must produce the
+        // checkpoint's log root
+        let mut log = self.log.clone();
+        let mut ledger = Vec::new();
+        let sources = self.api.ledger_sources(None).await?;
+        for source in &sources.sources {
+            if source.last_registry_index < from || source.first_registry_index >= to {
+                continue;
+            }
+
+            let leafs = self
+                .api
+                .ledger_leafs(None, sources.hash_algorithm, source)
+                .await?;
+            for (registry_index, leaf) in (source.first_registry_index..).zip(leafs) {
+                if registry_index == from + ledger.len() && registry_index < to {
+                    log.push(&leaf);
+                    ledger.push(LedgerEntry {
+                        registry_index,
+                        leaf,
+                    });
+                }
+            }
+        }
+
+        let log_root: AnyHash = log.checkpoint().root().into();
+        if log.length() != to || log_root != checkpoint.log_root {
+            alerts.push(MonitorAlert::InconsistentCheckpoint {
+                from,
+                to,
+                reason: "the registry's ledger does not produce the checkpoint's log root".into(),
+            });
+            return Ok(None);
+        }
+
+        // Every record of the new ledger entries must be served
+        let operator_log_id = LogId::operator_log::<Sha256>();
+        let mut unserved: IndexMap<RegistryIndex, &LogLeaf> = ledger
+            .iter()
+            .map(|entry| (entry.registry_index, &entry.leaf))
+            .collect();
+        for entry in &ledger {
+            if entry.leaf.log_id != operator_log_id {
+                state.packages.entry(entry.leaf.log_id.clone()).or_default();
+            }
+        }
+
+        let mut records = Vec::new();
+        loop {
+            let packages = state
+                .packages
+                .iter()
+                .map(|(log_id, tail)| (log_id.clone(), tail.fetch_token.clone()))
+                .collect::<IndexMap<_, _>>();
+            let response = match self
+                .api
+                .fetch_logs(
+                    None,
+                    FetchLogsRequest {
+                        log_length: to,
+                        operator: state.operator.fetch_token.as_deref().map(Cow::Borrowed),
+                        limit: None,
+                        packages: Cow::Owned(packages),
+                    },
+                )
+                .await
+            {
+                Ok(response) => response,
+                // The records of a log that is not found are reported as withheld
+                Err(api::ClientError::Fetch(FetchError::LogNotFound(log_id)))
+                | Err(api::ClientError::LogNotFoundWithHint(log_id, _))
+                    if state.packages.shift_remove(&log_id).is_some() =>
+                {
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            for record in response.operator {
+                let body = record.envelope.clone();
+                let envelope: PublishedProtoEnvelope<operator::OperatorRecord> =
+                    record.envelope.try_into()?;
+                let registry_index = envelope.registry_index;
+                if !state.operator.is_new(registry_index) {
+                    continue;
+                }
+
+                let leaf = LogLeaf {
+                    log_id: operator_log_id.clone(),
+                    record_id: RecordId::operator_record::<Sha256>(&envelope.envelope),
+                };
+                if unserved.shift_remove(&registry_index) != Some(&leaf) {
+                    alerts.push(MonitorAlert::LedgerMismatch {
+                        log_id: leaf.log_id,
+                        registry_index,
+                    });
+                    return Ok(None);
+                }
+
+                state.operator.state =
+                    match std::mem::take(&mut state.operator.state).validate(&envelope.envelope) {
+                        Ok(validated) => validated,
+                        Err(e) => {
+                            alerts.push(MonitorAlert::InvalidRecord {
+                                log_id: leaf.log_id,
+                                registry_index,
+                                reason: e.to_string(),
+                            });
+                            return Ok(None);
+                        }
+                    };
+                state.operator.fetch_token = Some(record.fetch_token);
+                state.operator.registry_index = Some(registry_index);
+                records.push(ArchivedRecord {
+                    log_id: leaf.log_id,
+                    envelope: body,
+                });
+            }
+
+            for (log_id, package_records) in response.packages {
+                let tail = state.packages.get_mut(&log_id).ok_or_else(|| {
+                    anyhow!("received records for unknown package log `{log_id}`")
+                })?;
+
+                for record in package_records {
+                    let body = record.envelope.clone();
+                    let envelope: PublishedProtoEnvelope<package::PackageRecord> =
+                        record.envelope.try_into()?;
+                    let registry_index = envelope.registry_index;
+                    if !tail.is_new(registry_index) {
+                        continue;
+                    }
+
+                    let leaf = LogLeaf {
+                        log_id: log_id.clone(),
+                        record_id: RecordId::package_record::<Sha256>(&envelope.envelope),
+                    };
+                    if unserved.shift_remove(&registry_index) != Some(&leaf) {
+                        alerts.push(MonitorAlert::LedgerMismatch {
+                            log_id,
+                            registry_index,
+                        });
+                        return Ok(None);
+                    }
+
+                    tail.state = match std::mem::take(&mut tail.state).validate(&envelope.envelope)
+                    {
+                        Ok(validated) => validated,
+                        Err(e) => {
+                            alerts.push(MonitorAlert::InvalidRecord {
+                                log_id,
+                                registry_index,
+                                reason: e.to_string(),
+                            });
+                            return Ok(None);
+                        }
+                    };
+                    tail.fetch_token = Some(record.fetch_token);
+                    tail.registry_index = Some(registry_index);
+                    records.push(ArchivedRecord {
+                        log_id: log_id.clone(),
+                        envelope: body,
+                    });
+                }
+            }
+
+            if !response.more {
+                break;
+            }
+        }
+
+        alerts.extend(unserved.into_iter().map(|(registry_index, leaf)| {
+            MonitorAlert::RecordWithheld {
+                log_id: leaf.log_id.clone(),
+                registry_index,
+            }
+        }));
+
+        let key_id = ts_checkpoint.key_id();
+        let signed = state
+            .operator
+            .state
+            .public_key(key_id)
+            .is_some_and(|key| verify::verify_checkpoint(ts_checkpoint, key).is_ok());
+        if !signed {
+            alerts.push(MonitorAlert::InvalidCheckpointSignature {
+                log_length: to,
+                key_id: key_id.clone(),
+            });
+        }
+
+        // Drop logs that were not found and have no outstanding records
+        state
+            .packages
+            .retain(|_, tail| tail.registry_index.is_some());
+
+        Ok(Some(Advance {
+            state,
+            log,
+            ledger,
+            records,
+        }))
+    }
+
+    /// Archives a verified checkpoint and moves the monitor to it.
+    fn commit(
+        &mut self,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+        advance: Advance,
+    ) -> ClientResult<()> {
+        let Advance {
+            mut state,
+            log,
+            ledger,
+            records,
+        } = advance;
+
+        append_lines(&self.archive.join(LEDGER_FILE), &ledger)?;
+        append_lines(&self.archive.join(RECORDS_FILE), &records)?;
+        append_lines(
+            &self.archive.join(CHECKPOINTS_FILE),
+            std::slice::from_ref(&ts_checkpoint),
+        )?;
+
+        tracing::info!(
+            log_length = ts_checkpoint.as_ref().checkpoint.log_length,
+            records = records.len(),
+            "verified checkpoint of registry `{url}`",
+            url = self.url()
+        );
+
+        state.checkpoint = Some(ts_checkpoint);
+        self.write_state(&state)?;
+        self.state = state;
+        self.log = log;
+        Ok(())
+    }
+
+    /// Archives and sends the alerts raised for a checkpoint.
+    async fn reject(
+        &mut self,
+        checkpoint: &Checkpoint,
+        alerts: &[MonitorAlert],
+    ) -> ClientResult<()> {
+        let archived = alerts
+            .iter()
+            .map(|alert| ArchivedAlert { checkpoint, alert })
+            .collect::<Vec<_>>();
+        append_lines(&self.archive.join(ALERTS_FILE), &archived)?;
+
+        let mut state = self.state.clone();
+        state.rejected = Some(checkpoint.clone());
+        self.write_state(&state)?;
+        self.state = state;
+
+        for alert in alerts {
+            for sink in &self.sinks {
+                if let Err(e) = sink.send(self.api.url(), alert).await {
+                    tracing::warn!(
+                        "failed to send alert for registry `{url}`: {e:#}",
+                        url = self.api.url()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_state(&self, state: &MonitorState) -> ClientResult<()> {
+        let path = self.archive.join(STATE_FILE);
+        let contents = serde_json::to_vec(state).context("failed to serialize monitor state")?;
+        fs::write(&path, contents)
+            .with_context(|| {
+                format!(
+                    "failed to write monitor state `{path}`",
+                    path = path.display()
+                )
+            })
+            .map_err(Into::into)
+    }
+}
+
+pub(crate) fn read_lines<T: DeserializeOwned>(path: &Path) -> ClientResult<Vec<T>> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let file = fs::File::open(path)
+        .with_context(|| format!("failed to open `{path}`", path = path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .map(|line| {
+            let line =
+                line.with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+            serde_json::from_str(&line)
+                .with_context(|| format!("failed to deserialize `{path}`", path = path.display()))
+                .map_err(Into::into)
+        })
+        .collect()
+}
+
+fn append_lines<T: Serialize>(path: &Path, items: &[T]) -> ClientResult<()> {
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    let mut contents = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut contents, item)
+            .with_context(|| format!("failed to serialize `{path}`", path = path.display()))?;
+        contents.push(b'\n');
+    }
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&contents))
+        .with_context(|| format!("failed to write `{path}`", path = path.display()))
+        .map_err(Into::into)
+}
@@ -0,0 +1,102 @@
+//! A module for browsing the validated state of a registry's operator log.
+
+use crate::storage::OperatorInfo;
+use warg_crypto::signing::{KeyID, PublicKey};
+use warg_protocol::{
+    operator::{self, CheckpointKeyChange, Permission},
+    registry::Checkpoint,
+};
+
+/// Represents the validated state of a registry's operator log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperatorLogInfo {
+    /// The checkpoint the operator log was validated at.
+    pub checkpoint: Option<Checkpoint>,
+    /// The namespaces defined by or imported into the registry, in the
+    /// order they were added to the operator log.
+    pub namespaces: Vec<Namespace>,
+    /// The keys currently authorized by the operator log.
+    pub keys: Vec<AuthorizedKey>,
+    /// The changes to the keys permitted to sign checkpoints, oldest first.
+    pub checkpoint_key_history: Vec<CheckpointKeyChange>,
+}
+
+impl OperatorLogInfo {
+    pub(crate) fn new(info: &OperatorInfo) -> Self {
+        let state = &info.state;
+        Self {
+            checkpoint: info.checkpoint.clone(),
+            namespaces: state
+                .namespaces()
+                .map(|(name, ns)| Namespace {
+                    name: name.to_string(),
+                    kind: match ns {
+                        operator::NamespaceState::Defined => NamespaceKind::Defined,
+                        operator::NamespaceState::Imported { registry } => {
+                            NamespaceKind::Imported {
+                                registry: registry.clone(),
+                            }
+                        }
+                    },
+                })
+                .collect(),
+            keys: state
+                .keys()
+                .filter_map(|key| {
+                    let key_id = key.fingerprint();
+                    let permissions = state
+                        .permissions(&key_id)
+                        .filter(|permissions| !permissions.is_empty())?
+                        .iter()
+                        .copied()
+                        .collect();
+                    Some(AuthorizedKey {
+                        key_id,
+                        key: key.clone(),
+                        permissions,
+                    })
+                })
+                .collect(),
+            checkpoint_key_history: state.checkpoint_key_history().to_vec(),
+        }
+    }
+
+    /// Gets the namespace with the given name.
+    ///
+    /// Returns `None` if the namespace is not known to the operator log.
+    pub fn namespace(&self, name: &str) -> Option<&Namespace> {
+        self.namespaces.iter().find(|ns| ns.name == name)
+    }
+}
+
+/// Represents a namespace known to a registry's operator log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Namespace {
+    /// The name of the namespace.
+    pub name: String,
+    /// How the namespace was added to the operator log.
+    pub kind: NamespaceKind,
+}
+
+/// Represents how a namespace was added to a registry's operator log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamespaceKind {
+    /// The namespace is defined by the registry for its own packages.
+    Defined,
+    /// The namespace is imported from another registry.
+    Imported {
+        /// The registry the namespace is imported from.
+        registry: String,
+    },
+}
+
+/// Represents a key authorized by a registry's operator log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizedKey {
+    /// The id of the key.
+    pub key_id: KeyID,
+    /// The public key.
+    pub key: PublicKey,
+    /// The permissions granted to the key.
+    pub permissions: Vec<Permission>,
+}
@@ -0,0 +1,86 @@
+//! Adaptive page sizes for fetching logs.
+
+use std::time::Duration;
+
+/// The time after which a page request is abandoned and retried with a
+/// smaller page.
+pub(crate) const PAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Pages requested faster than this grow the page size.
+const FAST_PAGE: Duration = Duration::from_secs(1);
+
+/// Tunes the number of records requested per page of a log fetch.
+///
+/// The page size starts at the configured maximum, is halved when a page
+/// request times out, and is doubled (up to the maximum) when a full page
+/// is fetched quickly.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PageSize {
+    current: u16,
+    max: u16,
+}
+
+impl PageSize {
+    /// Creates a page size tuned between one and the given maximum.
+    pub(crate) fn new(max: u16) -> Self {
+        let max = max.max(1);
+        Self { current: max, max }
+    }
+
+    /// Gets the number of records to request for the next page.
+    pub(crate) fn limit(&self) -> u16 {
+        self.current
+    }
+
+    /// Shrinks the page size after a page request timed out.
+    ///
+    /// Returns `false` if the page size cannot shrink any further.
+    pub(crate) fn shrink(&mut self) -> bool {
+        if self.current == 1 {
+            return false;
+        }
+
+        self.current /= 2;
+        true
+    }
+
+    /// Updates the page size after a page was fetched.
+    ///
+    /// Only full pages (i.e. pages with more records to fetch) grow the page
+    /// size, as a partial page says nothing about the cost of a larger one.
+    pub(crate) fn fetched(&mut self, elapsed: Duration, full: bool) {
+        if full && elapsed < FAST_PAGE {
+            self.current = self.current.saturating_mul(2).min(self.max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_tunes_page_size() {
+        let mut size = PageSize::new(100);
+        assert_eq!(size.limit(), 100);
+
+        assert!(size.shrink());
+        assert!(size.shrink());
+        assert_eq!(size.limit(), 25);
+
+        size.fetched(Duration::from_secs(5), true);
+        assert_eq!(size.limit(), 25);
+        size.fetched(Duration::from_millis(10), false);
+        assert_eq!(size.limit(), 25);
+        size.fetched(Duration::from_millis(10), true);
+        assert_eq!(size.limit(), 50);
+        size.fetched(Duration::from_millis(10), true);
+        size.fetched(Duration::from_millis(10), true);
+        assert_eq!(size.limit(), 100);
+
+        let mut size = PageSize::new(2);
+        assert!(size.shrink());
+        assert!(!size.shrink());
+        assert_eq!(size.limit(), 1);
+    }
+}
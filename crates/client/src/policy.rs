@@ -0,0 +1,222 @@
+//! A module for client-side package policies.
+//!
+//! Package policies are checked against each package resolved by a client
+//! operation, such as a download or the dependencies found while locking a
+//! component, before the package's content is used.
+
+use crate::api::{AdvisorySeverity, VulnerabilityAdvisory};
+use indexmap::IndexMap;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use warg_crypto::{hash::AnyHash, signing::KeyID};
+use warg_protocol::registry::PackageName;
+
+#[cfg(feature = "policy-component")]
+mod component;
+#[cfg(feature = "policy-component")]
+pub use component::*;
+
+/// A package resolved by a client operation.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedPackage<'a> {
+    /// The name of the package.
+    pub name: &'a PackageName,
+    /// The resolved version of the package.
+    pub version: &'a Version,
+    /// The content digest of the resolved version.
+    pub digest: &'a AnyHash,
+    /// The packages that transitively depend on this package, starting with
+    /// the root package of the operation.
+    ///
+    /// This is empty for a package that was requested directly.
+    pub dependents: &'a [PackageName],
+}
+
+impl ResolvedPackage<'_> {
+    /// Gets the dependency depth of the package.
+    ///
+    /// A package that was requested directly has a depth of zero.
+    pub fn depth(&self) -> usize {
+        self.dependents.len()
+    }
+}
+
+/// A violation of a package policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyViolation {
+    /// The rule that was violated (e.g. `banned-namespace`).
+    pub rule: String,
+    /// A message describing the violation.
+    pub message: String,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{rule}: {message}",
+            rule = self.rule,
+            message = self.message
+        )
+    }
+}
+
+/// The action taken before publishing a release that makes breaking
+/// interface changes without a major version bump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SemverCheck {
+    /// The publish fails with [`ClientError::IncompatibleRelease`](crate::ClientError::IncompatibleRelease).
+    Reject,
+    /// The breaking changes are logged as warnings and the release is
+    /// published.
+    Warn,
+}
+
+/// A trait implemented by client-side package policies.
+pub trait PackagePolicy: Send + Sync {
+    /// Checks a resolved package against the policy.
+    ///
+    /// Returns the violations of the policy; an empty list means the
+    /// package is allowed.
+    fn check(&self, package: &ResolvedPackage) -> anyhow::Result<Vec<PolicyViolation>>;
+}
+
+/// A policy for the vulnerability advisories of resolved packages.
+///
+/// A package version is denied if it is affected by an advisory of at least
+/// the policy's severity that has not been waived.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvisoryPolicy {
+    /// The minimum severity of the advisories that deny a package version.
+    pub deny_severity: AdvisorySeverity,
+    /// The identifiers of the advisories that are waived.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waived: Vec<String>,
+}
+
+impl AdvisoryPolicy {
+    /// Creates an advisory policy that denies package versions affected by
+    /// an advisory of at least the given severity.
+    pub fn new(deny_severity: AdvisorySeverity) -> Self {
+        Self {
+            deny_severity,
+            waived: Vec::new(),
+        }
+    }
+
+    /// Waives the advisories with the given identifiers.
+    pub fn with_waived(mut self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.waived.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Gets the advisories that deny a package version under the policy.
+    pub fn denied<'a>(
+        &self,
+        advisories: &'a [VulnerabilityAdvisory],
+    ) -> Vec<&'a VulnerabilityAdvisory> {
+        advisories
+            .iter()
+            .filter(|advisory| {
+                advisory.severity >= self.deny_severity && !self.waived.contains(&advisory.id)
+            })
+            .collect()
+    }
+}
+
+/// A policy for the keys that sign the records of packages.
+///
+/// The records of packages in a namespace of the policy must be signed by
+/// one of the namespace's keys; packages of other namespaces may be signed
+/// by any key with permission in the package log.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningPolicy {
+    /// The IDs of the keys that may sign records, keyed by namespace.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub namespaces: IndexMap<String, Vec<KeyID>>,
+}
+
+impl SigningPolicy {
+    /// Creates a signing policy without namespaces.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the records of packages in the given namespace to be signed
+    /// by one of the given keys.
+    pub fn with_namespace_keys(
+        mut self,
+        namespace: impl Into<String>,
+        keys: impl IntoIterator<Item = KeyID>,
+    ) -> Self {
+        self.namespaces
+            .entry(namespace.into())
+            .or_default()
+            .extend(keys);
+        self
+    }
+
+    /// Gets the keys that may sign the records of packages in the given
+    /// namespace.
+    ///
+    /// Returns `None` if the namespace is not restricted by the policy.
+    pub fn namespace_keys(&self, namespace: &str) -> Option<&[KeyID]> {
+        self.namespaces.get(namespace).map(Vec::as_slice)
+    }
+
+    /// Determines if a record of the given package may be signed by the
+    /// given key.
+    pub fn allows(&self, name: &PackageName, key_id: &KeyID) -> bool {
+        self.namespace_keys(name.namespace())
+            .map_or(true, |keys| keys.contains(key_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advisory(id: &str, severity: AdvisorySeverity) -> VulnerabilityAdvisory {
+        VulnerabilityAdvisory {
+            id: id.to_string(),
+            versions: "*".parse().unwrap(),
+            severity,
+            summary: "a vulnerability".to_string(),
+            url: None,
+        }
+    }
+
+    #[test]
+    fn it_denies_unwaived_advisories_at_severity() {
+        let advisories = [
+            advisory("WARG-1", AdvisorySeverity::Low),
+            advisory("WARG-2", AdvisorySeverity::High),
+            advisory("WARG-3", AdvisorySeverity::Critical),
+        ];
+        let policy = AdvisoryPolicy::new(AdvisorySeverity::High).with_waived(["WARG-3"]);
+
+        let denied = policy.denied(&advisories);
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0].id, "WARG-2");
+        assert!(AdvisoryPolicy::new(AdvisorySeverity::Critical)
+            .with_waived(["WARG-3"])
+            .denied(&advisories)
+            .is_empty());
+    }
+
+    #[test]
+    fn it_restricts_signing_keys_by_namespace() {
+        let corp = KeyID::from("sha256:corp".to_string());
+        let other = KeyID::from("sha256:other".to_string());
+        let policy = SigningPolicy::new().with_namespace_keys("corp", [corp.clone()]);
+
+        let name = PackageName::new("corp:app").unwrap();
+        assert!(policy.allows(&name, &corp));
+        assert!(!policy.allows(&name, &other));
+        assert!(policy.allows(&PackageName::new("test:app").unwrap(), &other));
+    }
+}
@@ -0,0 +1,183 @@
+use super::{PackagePolicy, PolicyViolation, ResolvedPackage};
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::path::Path;
+use wasmtime::{
+    component::{Component, Linker},
+    Config, Engine, Store,
+};
+
+const DEFAULT_FUEL: u64 = 100_000_000;
+
+mod bindings {
+    wasmtime::component::bindgen!({
+        path: "wit/policy.wit",
+        world: "package-policy",
+    });
+}
+
+/// A package policy implemented by a WebAssembly component.
+///
+/// The component must implement the `warg:package-policy/package-policy`
+/// world found in the `wit` directory of this crate and must not have any
+/// imports.
+///
+/// The resolved package is passed to the component's `check` export as a
+/// JSON document of the form:
+///
+/// ```json
+/// {
+///   "packageName": "example:package",
+///   "namespace": "example",
+///   "version": "1.0.0",
+///   "digest": "sha256:...",
+///   "depth": 1,
+///   "dependents": ["example:app"]
+/// }
+/// ```
+///
+/// Each check runs in a new instance of the component and is limited by an
+/// amount of fuel.
+pub struct ComponentPackagePolicy {
+    engine: Engine,
+    component: Component,
+    linker: Linker<()>,
+    fuel: u64,
+}
+
+impl ComponentPackagePolicy {
+    /// Creates a new component package policy from the given component bytes.
+    pub fn new(bytes: impl AsRef<[u8]>) -> Result<Self> {
+        let mut config = Config::new();
+        config.wasm_component_model(true).consume_fuel(true);
+
+        let engine = Engine::new(&config)?;
+        let component =
+            Component::new(&engine, bytes).context("failed to compile package policy component")?;
+        let linker = Linker::new(&engine);
+
+        Ok(Self {
+            engine,
+            component,
+            linker,
+            fuel: DEFAULT_FUEL,
+        })
+    }
+
+    /// Creates a new component package policy from the component at the given path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| {
+            format!(
+                "failed to read package policy component `{path}`",
+                path = path.display()
+            )
+        })?;
+        Self::new(bytes)
+    }
+
+    /// Sets the amount of fuel available to each check.
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+}
+
+impl PackagePolicy for ComponentPackagePolicy {
+    fn check(&self, package: &ResolvedPackage) -> Result<Vec<PolicyViolation>> {
+        let input = json!({
+            "packageName": package.name.as_ref(),
+            "namespace": package.name.namespace(),
+            "version": package.version.to_string(),
+            "digest": package.digest.to_string(),
+            "depth": package.depth(),
+            "dependents": package.dependents.iter().map(|n| n.as_ref()).collect::<Vec<_>>(),
+        })
+        .to_string();
+
+        let mut store = Store::new(&self.engine, ());
+        store.set_fuel(self.fuel)?;
+
+        let (policy, _) =
+            bindings::PackagePolicy::instantiate(&mut store, &self.component, &self.linker)?;
+        let violations = policy
+            .call_check(&mut store, &input)
+            .context("package policy component failed to check package")?;
+
+        Ok(violations
+            .into_iter()
+            .map(|v| PolicyViolation {
+                rule: v.rule,
+                message: v.message,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warg_protocol::registry::PackageName;
+    use wit_component::{ComponentEncoder, StringEncoding};
+    use wit_parser::{Resolve, UnresolvedPackage};
+
+    fn encode(wat: &str) -> Vec<u8> {
+        let mut resolve = Resolve::new();
+        let pkg = resolve
+            .push(
+                UnresolvedPackage::parse(
+                    Path::new("policy.wit"),
+                    include_str!("../../wit/policy.wit"),
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        let world = resolve.select_world(pkg, Some("package-policy")).unwrap();
+
+        let mut module = wat::parse_str(wat).unwrap();
+        wit_component::embed_component_metadata(&mut module, &resolve, world, StringEncoding::UTF8)
+            .unwrap();
+
+        ComponentEncoder::default()
+            .module(&module)
+            .unwrap()
+            .validate(true)
+            .encode()
+            .unwrap()
+    }
+
+    #[test]
+    fn it_returns_component_violations() -> Result<()> {
+        // A component that reports a single violation for every package
+        let policy = ComponentPackagePolicy::new(encode(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 16) "\20\00\00\00\01\00\00\00")
+                (data (i32.const 32) "\40\00\00\00\0e\00\00\00\60\00\00\00\06\00\00\00")
+                (data (i32.const 64) "banned-package")
+                (data (i32.const 96) "banned")
+                (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32) i32.const 1024)
+                (func (export "check") (param i32 i32) (result i32) i32.const 16)
+            )"#,
+        ))?;
+
+        let name: PackageName = "test:package".parse()?;
+        let violations = policy.check(&ResolvedPackage {
+            name: &name,
+            version: &"1.0.0".parse()?,
+            digest: &"sha256:0000000000000000000000000000000000000000000000000000000000000000"
+                .parse()?,
+            dependents: &[],
+        })?;
+
+        assert_eq!(
+            violations,
+            [PolicyViolation {
+                rule: "banned-package".to_string(),
+                message: "banned".to_string(),
+            }]
+        );
+
+        Ok(())
+    }
+}
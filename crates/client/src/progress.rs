@@ -0,0 +1,71 @@
+//! A module for reporting the progress of syncing package logs.
+
+use std::sync::{Arc, Mutex};
+
+/// The progress of syncing package logs to the latest checkpoints of their
+/// registries.
+///
+/// Packages in a federated namespace are synced from another registry, so the
+/// number of registries and packages may grow while a sync is in progress.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// The number of registries being synced.
+    pub registries: usize,
+    /// The number of registries that have been synced.
+    pub registries_synced: usize,
+    /// The number of packages being synced.
+    pub packages: usize,
+    /// The number of packages that have been synced.
+    pub packages_synced: usize,
+}
+
+/// A handler called with the progress of a sync after each change.
+pub(crate) type SyncProgressHandler = Arc<dyn Fn(&SyncProgress) + Send + Sync>;
+
+/// Tracks the progress of a sync shared by concurrent registry updates.
+pub(crate) struct SyncProgressTracker<'a> {
+    progress: Mutex<SyncProgress>,
+    handler: Option<&'a SyncProgressHandler>,
+}
+
+impl<'a> SyncProgressTracker<'a> {
+    pub(crate) fn new(handler: Option<&'a SyncProgressHandler>) -> Self {
+        Self {
+            progress: Mutex::new(SyncProgress::default()),
+            handler,
+        }
+    }
+
+    /// Records that a registry with the given number of packages will be synced.
+    pub(crate) fn start(&self, packages: usize) {
+        self.update(|progress| {
+            progress.registries += 1;
+            progress.packages += packages;
+        });
+    }
+
+    /// Records that a registry was synced.
+    ///
+    /// The packages that were not synced are federated to other registries
+    /// and are started again with their registry.
+    pub(crate) fn finish(&self, packages: usize, federated: usize) {
+        self.update(|progress| {
+            progress.registries_synced += 1;
+            progress.packages_synced += packages - federated;
+            progress.packages -= federated;
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut SyncProgress)) {
+        let progress = {
+            let mut progress = self.progress.lock().unwrap();
+            f(&mut progress);
+            *progress
+        };
+
+        tracing::debug!(?progress, "sync progress");
+        if let Some(handler) = self.handler {
+            handler(&progress);
+        }
+    }
+}
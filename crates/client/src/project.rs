@@ -0,0 +1,206 @@
+//! A module for project-local client configuration.
+//!
+//! A project configuration is a `.warg.json` file checked into a project's
+//! directory. It is discovered by searching the current directory and its
+//! parents, and overrides the user's client configuration for commands run
+//! within the project.
+
+use crate::RegistryUrl;
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use warg_protocol::registry::PackageName;
+
+/// The file name of a project configuration.
+pub const PROJECT_CONFIG_FILE_NAME: &str = ".warg.json";
+
+/// Represents a project-local client configuration (`.warg.json`).
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// The URL of the registry used by the project.
+    ///
+    /// If `None`, the home registry of the client configuration is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+
+    /// The registry domain to resolve packages of a namespace from, keyed by
+    /// namespace.
+    ///
+    /// Overrides take precedence over the client's namespace map, but not
+    /// over the namespaces of the registry's operator log.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub namespaces: IndexMap<String, String>,
+
+    /// The keyring entry of the signing key used by the project.
+    ///
+    /// This is the registry URL the key was stored for (e.g. with
+    /// `warg key new`), or `default` for the default signing key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Reads and validates a project configuration from the given file path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let config = fs::read_to_string(path).with_context(|| {
+            format!(
+                "failed to read project configuration file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let config: Self = serde_json::from_str(&config).with_context(|| {
+            format!("failed to deserialize file `{path}`", path = path.display())
+        })?;
+
+        config.validate().with_context(|| {
+            format!(
+                "invalid project configuration `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        Ok(config)
+    }
+
+    /// Validates and writes the project configuration to the given file path.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        self.validate()?;
+
+        let mut contents = serde_json::to_string_pretty(self)
+            .with_context(|| format!("failed to serialize file `{path}`", path = path.display()))?;
+        contents.push('\n');
+
+        fs::write(path, contents)
+            .with_context(|| format!("failed to write file `{path}`", path = path.display()))
+    }
+
+    /// Finds the project configuration file for the given directory.
+    ///
+    /// The directory and then each of its parents are searched for a
+    /// `.warg.json` file; the nearest one is returned.
+    pub fn find(dir: impl AsRef<Path>) -> Option<PathBuf> {
+        dir.as_ref()
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Loads the project configuration for the given directory.
+    ///
+    /// Returns `Ok(None)` if the directory is not within a project.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Option<Self>> {
+        Self::find(dir).map(Self::from_file).transpose()
+    }
+
+    /// Loads the project configuration for the current directory.
+    ///
+    /// Returns `Ok(None)` if the current directory is not within a project.
+    pub fn from_current_dir() -> Result<Option<Self>> {
+        Self::from_dir(std::env::current_dir().context("failed to get current directory")?)
+    }
+
+    /// Validates the project configuration.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(registry) = &self.registry {
+            RegistryUrl::new(registry)
+                .with_context(|| format!("invalid registry URL `{registry}`"))?;
+        }
+
+        for (namespace, registry) in &self.namespaces {
+            if !PackageName::is_valid_namespace(namespace) {
+                bail!("invalid namespace `{namespace}`");
+            }
+
+            if registry.is_empty() {
+                bail!("namespace `{namespace}` has an empty registry");
+            }
+        }
+
+        if self.signing_key.as_deref() == Some("") {
+            bail!("signing key reference cannot be empty");
+        }
+
+        Ok(())
+    }
+
+    /// Gets the registry that packages of the given namespace are resolved
+    /// from, if it is overridden by the project.
+    pub fn namespace_registry(&self, namespace: &str) -> Option<&str> {
+        self.namespaces.get(namespace).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_project_config() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(PROJECT_CONFIG_FILE_NAME);
+
+        let config = ProjectConfig {
+            registry: Some("https://registry.example.com".to_string()),
+            namespaces: [("wasi".to_string(), "wasi.dev".to_string())].into(),
+            signing_key: Some("default".to_string()),
+        };
+        config.write_to_file(&path)?;
+
+        assert_eq!(ProjectConfig::from_file(&path)?, config);
+        assert_eq!(config.namespace_registry("wasi"), Some("wasi.dev"));
+        assert_eq!(config.namespace_registry("other"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn it_discovers_project_config_upward() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let nested = dir.path().join("a/b/c");
+        fs::create_dir_all(&nested)?;
+
+        assert_eq!(ProjectConfig::from_dir(&nested)?, None);
+
+        let config = ProjectConfig {
+            registry: Some("https://registry.example.com".to_string()),
+            ..Default::default()
+        };
+        config.write_to_file(dir.path().join("a").join(PROJECT_CONFIG_FILE_NAME))?;
+
+        assert_eq!(
+            ProjectConfig::find(&nested),
+            Some(dir.path().join("a").join(PROJECT_CONFIG_FILE_NAME))
+        );
+        assert_eq!(ProjectConfig::from_dir(&nested)?, Some(config));
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_invalid_project_config() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(PROJECT_CONFIG_FILE_NAME);
+
+        fs::write(&path, r#"{ "registy": "https://registry.example.com" }"#)?;
+        assert!(ProjectConfig::from_file(&path).is_err());
+
+        fs::write(&path, r#"{ "namespaces": { "Not Valid": "example.com" } }"#)?;
+        assert!(ProjectConfig::from_file(&path).is_err());
+
+        fs::write(&path, r#"{ "signingKey": "" }"#)?;
+        assert!(ProjectConfig::from_file(&path).is_err());
+
+        let config = ProjectConfig {
+            registry: Some("http://registry.example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(config.write_to_file(&path).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,469 @@
+//! A module for verifying the provenance of package releases.
+//!
+//! Provenance is attached to a release as an attestation of kind
+//! [`PROVENANCE_KIND`] whose content is a [Sigstore bundle] of an in-toto
+//! statement for the release content.
+//!
+//! The bundle is signed "keylessly" with the key of a short-lived
+//! certificate issued for the identity of a CI workload, such as a GitHub
+//! Actions workflow. A provenance is verified if the certificate chains to
+//! a trusted root, the certificate was valid when the attestation was
+//! recorded in the package log, the certificate identity matches one of a
+//! policy's patterns, and the statement covers the release content.
+//!
+//! The package log stands in for the Sigstore transparency log: entries of
+//! the bundle's transparency log are not checked.
+//!
+//! [Sigstore bundle]: https://docs.sigstore.dev/about/bundle/
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use serde::Deserialize;
+use sha2::{Digest, Sha256, Sha384};
+use std::time::SystemTime;
+use warg_crypto::hash::AnyHash;
+use x509_cert::{
+    der::{
+        asn1::{ObjectIdentifier, Utf8StringRef},
+        Decode, Encode,
+    },
+    ext::pkix::{name::GeneralName, SubjectAltName},
+    spki::SubjectPublicKeyInfoOwned,
+    Certificate,
+};
+
+/// The attestation kind of SLSA provenance.
+pub const PROVENANCE_KIND: &str = "https://slsa.dev/provenance/v1";
+
+/// The OIDC issuer of GitHub Actions workload identities.
+pub const GITHUB_ACTIONS_ISSUER: &str = "https://token.actions.githubusercontent.com";
+
+/// The Fulcio certificate extension of the OIDC issuer.
+const OIDC_ISSUER_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.57264.1.8");
+/// The deprecated Fulcio certificate extension of the OIDC issuer, whose
+/// value is not DER encoded.
+const OIDC_ISSUER_V1_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.57264.1.1");
+
+const EC_PUBLIC_KEY_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const SECP256R1_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const SECP384R1_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+const ECDSA_WITH_SHA256_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+const ECDSA_WITH_SHA384_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.3");
+
+/// The maximum number of intermediate certificates in a chain.
+const MAX_CHAIN_DEPTH: usize = 4;
+
+/// A policy for the provenance of package releases.
+///
+/// Identity patterns match the subject alternative name URI of a
+/// certificate, which for GitHub Actions is the URI of the workflow and the
+/// ref it ran for (e.g.
+/// `https://github.com/example/repo/.github/workflows/release.yml@refs/tags/v1.0.0`).
+/// A `*` in a pattern matches any sequence of characters.
+#[derive(Debug, Clone)]
+pub struct ProvenancePolicy {
+    issuer: String,
+    identities: Vec<String>,
+    roots: Vec<Certificate>,
+}
+
+impl ProvenancePolicy {
+    /// Creates a provenance policy that trusts GitHub Actions certificates
+    /// with an identity matching the given pattern.
+    ///
+    /// At least one trusted root must be added before provenance can be
+    /// verified.
+    pub fn new(identity: impl Into<String>) -> Self {
+        Self {
+            issuer: GITHUB_ACTIONS_ISSUER.to_string(),
+            identities: vec![identity.into()],
+            roots: Vec::new(),
+        }
+    }
+
+    /// Sets the OIDC issuer that certificate identities must be issued by.
+    ///
+    /// Defaults to [`GITHUB_ACTIONS_ISSUER`].
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = issuer.into();
+        self
+    }
+
+    /// Adds another pattern of trusted certificate identities.
+    pub fn with_identity(mut self, identity: impl Into<String>) -> Self {
+        self.identities.push(identity.into());
+        self
+    }
+
+    /// Adds trusted root certificates from PEM-encoded certificates (e.g.
+    /// the roots of the Sigstore public good instance).
+    pub fn with_trusted_roots_pem(mut self, pem: &[u8]) -> Result<Self> {
+        let roots = Certificate::load_pem_chain(pem).context("invalid trusted root certificate")?;
+        if roots.is_empty() {
+            bail!("no trusted root certificates were found");
+        }
+
+        self.roots.extend(roots);
+        Ok(self)
+    }
+
+    /// Adds a trusted root certificate from a DER-encoded certificate.
+    pub fn with_trusted_root_der(mut self, der: &[u8]) -> Result<Self> {
+        self.roots
+            .push(Certificate::from_der(der).context("invalid trusted root certificate")?);
+        Ok(self)
+    }
+
+    /// Verifies a provenance bundle for release content recorded in the
+    /// package log at the given time.
+    ///
+    /// Returns the identity of the certificate that signed the bundle.
+    pub fn verify(&self, bundle: &[u8], content: &AnyHash, recorded: SystemTime) -> Result<String> {
+        let bundle: Bundle =
+            serde_json::from_slice(bundle).context("the provenance is not a Sigstore bundle")?;
+        let certificates = bundle.verification_material.certificates()?;
+        let (leaf, intermediates) = certificates
+            .split_first()
+            .context("the bundle has no signing certificate")?;
+
+        self.verify_chain(leaf, intermediates, recorded)?;
+
+        let identity = identity(leaf)?;
+        let issuer = issuer(leaf)?;
+        if issuer != self.issuer {
+            bail!("the certificate was issued for an identity of `{issuer}`");
+        }
+        if !self
+            .identities
+            .iter()
+            .any(|pattern| wildcard_match(pattern, &identity))
+        {
+            bail!("certificate identity `{identity}` does not match the policy");
+        }
+
+        let envelope = &bundle.dsse_envelope;
+        let payload = STANDARD
+            .decode(&envelope.payload)
+            .context("the bundle payload is not base64")?;
+        let message = pae(&envelope.payload_type, &payload);
+        let key = &leaf.tbs_certificate.subject_public_key_info;
+        if !envelope.signatures.iter().any(|s| {
+            STANDARD
+                .decode(&s.sig)
+                .is_ok_and(|sig| verify_signature(key, None, &message, &sig).is_ok())
+        }) {
+            bail!("the bundle is not signed by the certificate");
+        }
+
+        let statement: Statement =
+            serde_json::from_slice(&payload).context("the bundle payload is not a statement")?;
+        if !statement
+            .predicate_type
+            .starts_with("https://slsa.dev/provenance/")
+        {
+            bail!(
+                "the statement has predicate type `{ty}`",
+                ty = statement.predicate_type
+            );
+        }
+        let content = content.to_string();
+        if !statement.subject.iter().any(|subject| {
+            subject
+                .digest
+                .iter()
+                .any(|(algorithm, digest)| format!("{algorithm}:{digest}") == content)
+        }) {
+            bail!("the statement does not cover content `{content}`");
+        }
+
+        Ok(identity)
+    }
+
+    /// Verifies that a certificate chains to a trusted root and that each
+    /// certificate in the chain was valid at the given time.
+    fn verify_chain(
+        &self,
+        leaf: &Certificate,
+        intermediates: &[Certificate],
+        time: SystemTime,
+    ) -> Result<()> {
+        if self.roots.is_empty() {
+            bail!("the provenance policy has no trusted roots");
+        }
+
+        let mut current = leaf;
+        for _ in 0..=MAX_CHAIN_DEPTH {
+            check_validity(current, time)?;
+
+            let tbs = current.tbs_certificate.to_der()?;
+            let signature = current.signature.raw_bytes();
+            let algorithm = &current.signature_algorithm.oid;
+            let signed_by = |issuer: &&Certificate| {
+                issuer.tbs_certificate.subject == current.tbs_certificate.issuer
+                    && verify_signature(
+                        &issuer.tbs_certificate.subject_public_key_info,
+                        Some(algorithm),
+                        &tbs,
+                        signature,
+                    )
+                    .is_ok()
+            };
+
+            if self.roots.iter().any(|root| signed_by(&root)) {
+                return Ok(());
+            }
+
+            current = intermediates.iter().find(signed_by).with_context(|| {
+                format!(
+                    "certificate `{subject}` is not issued by a trusted root",
+                    subject = current.tbs_certificate.subject
+                )
+            })?;
+        }
+
+        bail!("the certificate chain is too long")
+    }
+}
+
+/// A Sigstore bundle with a DSSE envelope.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Bundle {
+    verification_material: VerificationMaterial,
+    dsse_envelope: DsseEnvelope,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationMaterial {
+    /// The signing certificate of a v0.3 bundle.
+    certificate: Option<RawCertificate>,
+    /// The certificate chain, starting with the signing certificate, of
+    /// earlier bundles.
+    x509_certificate_chain: Option<CertificateChain>,
+}
+
+impl VerificationMaterial {
+    fn certificates(&self) -> Result<Vec<Certificate>> {
+        let raw = match (&self.certificate, &self.x509_certificate_chain) {
+            (Some(certificate), _) => std::slice::from_ref(certificate),
+            (None, Some(chain)) => chain.certificates.as_slice(),
+            (None, None) => bail!("the bundle has no certificate"),
+        };
+
+        raw.iter()
+            .map(|c| {
+                let der = STANDARD
+                    .decode(&c.raw_bytes)
+                    .context("the bundle certificate is not base64")?;
+                Certificate::from_der(&der).context("the bundle certificate is invalid")
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCertificate {
+    raw_bytes: String,
+}
+
+#[derive(Deserialize)]
+struct CertificateChain {
+    certificates: Vec<RawCertificate>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DsseEnvelope {
+    payload: String,
+    payload_type: String,
+    signatures: Vec<DsseSignature>,
+}
+
+#[derive(Deserialize)]
+struct DsseSignature {
+    sig: String,
+}
+
+/// An in-toto statement.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Statement {
+    subject: Vec<StatementSubject>,
+    predicate_type: String,
+}
+
+#[derive(Deserialize)]
+struct StatementSubject {
+    digest: indexmap::IndexMap<String, String>,
+}
+
+/// Computes the DSSE pre-authentication encoding of a payload.
+fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut message = format!(
+        "DSSEv1 {type_len} {payload_type} {payload_len} ",
+        type_len = payload_type.len(),
+        payload_len = payload.len()
+    )
+    .into_bytes();
+    message.extend_from_slice(payload);
+    message
+}
+
+/// Verifies an ECDSA signature of a message.
+///
+/// If no signature algorithm is given, the digest conventional for the
+/// key's curve is used.
+fn verify_signature(
+    key: &SubjectPublicKeyInfoOwned,
+    algorithm: Option<&ObjectIdentifier>,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    if key.algorithm.oid != EC_PUBLIC_KEY_OID {
+        bail!(
+            "unsupported public key algorithm `{oid}`",
+            oid = key.algorithm.oid
+        );
+    }
+    let curve = key
+        .algorithm
+        .parameters
+        .as_ref()
+        .context("the public key has no curve")?
+        .decode_as::<ObjectIdentifier>()
+        .map_err(|e| anyhow!("invalid public key curve: {e}"))?;
+
+    let prehash = match algorithm {
+        Some(oid) if *oid == ECDSA_WITH_SHA256_OID => Sha256::digest(message).to_vec(),
+        Some(oid) if *oid == ECDSA_WITH_SHA384_OID => Sha384::digest(message).to_vec(),
+        Some(oid) => bail!("unsupported signature algorithm `{oid}`"),
+        None if curve == SECP384R1_OID => Sha384::digest(message).to_vec(),
+        None => Sha256::digest(message).to_vec(),
+    };
+
+    let point = key.subject_public_key.raw_bytes();
+    if curve == SECP256R1_OID {
+        let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(point)?;
+        key.verify_prehash(&prehash, &p256::ecdsa::Signature::from_der(signature)?)?;
+    } else if curve == SECP384R1_OID {
+        let key = p384::ecdsa::VerifyingKey::from_sec1_bytes(point)?;
+        key.verify_prehash(&prehash, &p384::ecdsa::Signature::from_der(signature)?)?;
+    } else {
+        bail!("unsupported elliptic curve `{curve}`");
+    }
+
+    Ok(())
+}
+
+fn check_validity(certificate: &Certificate, time: SystemTime) -> Result<()> {
+    let validity = &certificate.tbs_certificate.validity;
+    if time < validity.not_before.to_system_time() || time > validity.not_after.to_system_time() {
+        bail!(
+            "certificate `{subject}` was not valid when the attestation was recorded",
+            subject = certificate.tbs_certificate.subject
+        );
+    }
+    Ok(())
+}
+
+/// Gets the identity of a certificate from its subject alternative name.
+fn identity(certificate: &Certificate) -> Result<String> {
+    let (_, names) = certificate
+        .tbs_certificate
+        .get::<SubjectAltName>()?
+        .context("the certificate has no subject alternative name")?;
+
+    names
+        .0
+        .into_iter()
+        .find_map(|name| match name {
+            GeneralName::UniformResourceIdentifier(uri) => Some(uri.to_string()),
+            GeneralName::Rfc822Name(email) => Some(email.to_string()),
+            _ => None,
+        })
+        .context("the certificate has no URI or email identity")
+}
+
+/// Gets the OIDC issuer of a certificate's identity.
+fn issuer(certificate: &Certificate) -> Result<String> {
+    let extensions = certificate.tbs_certificate.extensions.as_deref();
+    let extension = |oid| extensions?.iter().find(|e| e.extn_id == oid);
+
+    if let Some(extension) = extension(OIDC_ISSUER_OID) {
+        return Ok(Utf8StringRef::from_der(extension.extn_value.as_bytes())
+            .context("invalid OIDC issuer extension")?
+            .to_string());
+    }
+
+    if let Some(extension) = extension(OIDC_ISSUER_V1_OID) {
+        return String::from_utf8(extension.extn_value.as_bytes().to_vec())
+            .context("invalid OIDC issuer extension");
+    }
+
+    bail!("the certificate has no OIDC issuer")
+}
+
+/// Matches a string against a pattern where `*` matches any sequence of
+/// characters.
+fn wildcard_match(pattern: &str, s: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = s.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_identity_patterns() {
+        let identity =
+            "https://github.com/example/repo/.github/workflows/release.yml@refs/tags/v1.0.0";
+        assert!(wildcard_match(identity, identity));
+        assert!(wildcard_match(
+            "https://github.com/example/repo/.github/workflows/release.yml@refs/tags/*",
+            identity
+        ));
+        assert!(wildcard_match(
+            "https://github.com/example/*/.github/workflows/*.yml@*",
+            identity
+        ));
+        assert!(!wildcard_match(
+            "https://github.com/example/repo/.github/workflows/ci.yml@*",
+            identity
+        ));
+        assert!(!wildcard_match(
+            "https://github.com/example/repo/.github/workflows/release.yml",
+            identity
+        ));
+        assert!(wildcard_match("a*a", "aa"));
+        assert!(!wildcard_match("a*a", "a"));
+    }
+
+    #[test]
+    fn it_encodes_pae() {
+        assert_eq!(
+            pae("application/vnd.in-toto+json", b"{}"),
+            b"DSSEv1 28 application/vnd.in-toto+json 2 {}"
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::storage::RegistryDomain;
+use crate::{
+    storage::RegistryDomain,
+    transport::{LOCAL_SCHEME, UNIX_SCHEME},
+};
 use anyhow::{anyhow, bail, Context, Result};
 use reqwest::IntoUrl;
 use url::{Host, Url};
@@ -45,6 +48,12 @@ impl RegistryUrl {
                     }
                 }
             }
+            // Local transports never leave the machine
+            UNIX_SCHEME | LOCAL_SCHEME => {
+                if url.host_str().map_or(true, str::is_empty) {
+                    bail!("expected a host for URL `{url}`");
+                }
+            }
             _ => bail!("expected a HTTPS scheme for URL `{url}`"),
         }
 
@@ -61,6 +70,14 @@ impl RegistryUrl {
     /// appropriate to use with external systems that can't accept arbitrary
     /// URLs such as file system paths.
     pub fn safe_label(&self) -> String {
+        // Local transports are labeled by their socket path or service name
+        if let kind @ ("unix" | "local") = self.0.scheme().trim_start_matches("http+") {
+            return format!(
+                "{kind}_{host}",
+                host = self.0.host_str().unwrap().replace('%', ".")
+            );
+        }
+
         // Host
         let mut label = match self.0.host().unwrap() {
             Host::Domain(domain) => domain.to_string(),
@@ -154,6 +171,11 @@ mod tests {
             ("http://[::1]", "http://[::1]/"),
             ("http://localhost:8080", "http://localhost:8080/"),
             ("https://unchanged/", "https://unchanged/"),
+            (
+                "http+unix://%2Ftmp%2Fwarg.sock",
+                "http+unix://%2Ftmp%2Fwarg.sock/",
+            ),
+            ("http+local://embedded", "http+local://embedded/"),
         ] {
             assert_eq!(
                 must_parse(input).to_string(),
@@ -192,6 +214,8 @@ mod tests {
             ("https://[abcd::1234]:5678", "ipv6_abcd..1234-5678"),
             ("syms/splat*dot.lowdash_", "syms_splat.2Adot.2Elowdash.5F"),
             ("☃︎/☃︎", "xn--n3h_.E2.98.83.EF.B8.8E"), // punycode host + percent-encoded path
+            ("http+unix://%2Ftmp%2Fwarg.sock", "unix_.2Ftmp.2Fwarg.sock"),
+            ("http+local://embedded", "local_embedded"),
         ] {
             let url = must_parse(input);
             assert_eq!(url.safe_label(), expected);
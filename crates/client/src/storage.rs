@@ -1,10 +1,11 @@
 //! A module for client storage implementations.
 
+use crate::api::VulnerabilityAdvisory;
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures_util::Stream;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use reqwest::header::HeaderValue;
 use serde::{Deserialize, Serialize};
 use std::{fmt, path::PathBuf, pin::Pin, str::FromStr, time::SystemTime};
@@ -14,12 +15,13 @@ use warg_crypto::{
 };
 use warg_protocol::{
     operator,
-    package::{self, PackageRecord, Permission, PACKAGE_RECORD_VERSION},
-    registry::{Checkpoint, PackageName, RecordId, RegistryIndex, TimestampedCheckpoint},
-    ProtoEnvelope, SerdeEnvelope, Version,
+    package::{self, ContentType, PackageRecord, Permission, PACKAGE_RECORD_VERSION},
+    registry::{Checkpoint, LogLeaf, PackageName, RecordId, RegistryIndex, TimestampedCheckpoint},
+    SerdeEnvelope, Version,
 };
 
 mod fs;
+mod shared;
 pub use fs::*;
 
 /// Registry domain used for warg header values
@@ -130,6 +132,37 @@ pub trait RegistryStorage: Send + Sync {
     ///
     /// If the info is `None`, the any existing publish information is deleted.
     async fn store_publish(&self, info: Option<&PublishInfo>) -> Result<()>;
+
+    /// Loads the proofs verified against the most recent checkpoint.
+    ///
+    /// Returns `Ok(None)` if no proofs are present.
+    async fn load_verified_proofs(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+    ) -> Result<Option<VerifiedProofs>>;
+
+    /// Stores the proofs verified against the most recent checkpoint.
+    async fn store_verified_proofs(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+        proofs: &VerifiedProofs,
+    ) -> Result<()>;
+
+    /// Loads the audit trail of the records signed by the client, in the
+    /// order they were submitted.
+    ///
+    /// The default implementation does not keep an audit trail.
+    async fn load_signing_audit(&self) -> Result<Vec<SigningAuditEntry>> {
+        Ok(Vec::new())
+    }
+
+    /// Appends an entry to the audit trail of the records signed by the
+    /// client.
+    ///
+    /// The default implementation does not keep an audit trail.
+    async fn append_signing_audit(&self, _entry: &SigningAuditEntry) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// Trait for content storage implementations.
@@ -139,6 +172,8 @@ pub trait RegistryStorage: Send + Sync {
 #[async_trait]
 pub trait ContentStorage: Send + Sync {
     /// Clear content local data
+    ///
+    /// Pinned content is kept.
     async fn clear(&self) -> Result<()>;
 
     /// Gets the location of the content associated with the given digest if it
@@ -167,6 +202,105 @@ pub trait ContentStorage: Send + Sync {
         stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
         expected_digest: Option<&AnyHash>,
     ) -> Result<AnyHash>;
+
+    /// Lists the digests of all content in storage.
+    async fn list_content(&self) -> Result<Vec<AnyHash>>;
+
+    /// Gets the space, in bytes, available for storing new content.
+    ///
+    /// Returns `Ok(None)` if the available space cannot be determined.
+    fn available_space(&self) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Removes the content associated with the given digest.
+    ///
+    /// Removing content that is not in storage is not an error.
+    async fn remove_content(&self, digest: &AnyHash) -> Result<()>;
+
+    /// Pins the content associated with the given digest.
+    ///
+    /// Pinned content is kept when storage is cleared and should not be
+    /// removed by eviction policies. Content may be pinned before it is
+    /// stored.
+    async fn pin_content(&self, digest: &AnyHash) -> Result<()>;
+
+    /// Unpins the content associated with the given digest.
+    ///
+    /// Unpinning content that is not pinned is not an error.
+    async fn unpin_content(&self, digest: &AnyHash) -> Result<()>;
+
+    /// Lists the digests of all pinned content.
+    async fn pinned_content(&self) -> Result<IndexSet<AnyHash>>;
+
+    /// Maps the content associated with the given digest into memory as a
+    /// read-only view.
+    ///
+    /// If the content is not found, `Ok(None)` is returned.
+    ///
+    /// The default implementation maps the file at the content's location.
+    #[cfg(feature = "mmap")]
+    async fn map_content(&self, digest: &AnyHash) -> Result<Option<MappedContent>> {
+        match self.content_location(digest) {
+            Some(path) => MappedContent::open(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A memory-mapped, read-only view of content in storage.
+///
+/// Content files are never modified once stored, so the view remains valid
+/// for its lifetime; on Windows, content cannot be removed from storage
+/// while it is mapped.
+#[cfg(feature = "mmap")]
+pub struct MappedContent(memmap2::Mmap);
+
+#[cfg(feature = "mmap")]
+impl MappedContent {
+    /// Maps the content file at the given path.
+    pub fn open(path: &std::path::Path) -> Result<Self> {
+        use anyhow::Context;
+
+        let file = std::fs::File::open(path).with_context(|| {
+            format!(
+                "failed to open content file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        // SAFETY: content files are written to a temporary file that is
+        // renamed into place, so a stored file is never modified
+        let map = unsafe { memmap2::Mmap::map(&file) }.with_context(|| {
+            format!("failed to map content file `{path}`", path = path.display())
+        })?;
+        Ok(Self(map))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl std::ops::Deref for MappedContent {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl AsRef<[u8]> for MappedContent {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl fmt::Debug for MappedContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedContent")
+            .field("len", &self.0.len())
+            .finish()
+    }
 }
 
 /// Trait for namespace map storage implementations.
@@ -187,6 +321,38 @@ pub trait NamespaceMapStorage: Send + Sync {
     ) -> Result<()>;
 }
 
+/// Represents the inclusion proofs verified by the client against a
+/// registry checkpoint.
+///
+/// Log leafs that have been proven are not proven again while syncing to the
+/// same checkpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedProofs {
+    /// The checkpoint the proofs were verified against.
+    pub checkpoint: Checkpoint,
+    /// The log leafs proven to be included in the checkpoint's log, keyed by
+    /// registry index.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub inclusions: IndexMap<RegistryIndex, LogLeaf>,
+}
+
+impl VerifiedProofs {
+    /// Creates an empty set of proofs for the given checkpoint.
+    pub fn new(checkpoint: Checkpoint) -> Self {
+        Self {
+            checkpoint,
+            inclusions: IndexMap::new(),
+        }
+    }
+
+    /// Determines if the given log leaf was proven to be included at the
+    /// given registry index.
+    pub fn is_included(&self, index: RegistryIndex, leaf: &LogLeaf) -> bool {
+        self.inclusions.get(&index) == Some(leaf)
+    }
+}
+
 /// Represents information about a registry operator.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -229,6 +395,13 @@ pub struct PackageInfo {
     /// The fetch token for the most recent record
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub head_fetch_token: Option<String>,
+    /// The vulnerability advisories of the package, as of the last update.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advisories: Vec<VulnerabilityAdvisory>,
+    /// The time the package was last verified against the latest
+    /// checkpoint of the registry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<SystemTime>,
 }
 
 impl PackageInfo {
@@ -241,8 +414,20 @@ impl PackageInfo {
             state: package::LogState::default(),
             head_registry_index: None,
             head_fetch_token: None,
+            advisories: Vec::new(),
+            verified_at: None,
         }
     }
+
+    /// Gets the vulnerability advisories that affect the given version of
+    /// the package.
+    pub fn advisories_for(&self, version: &Version) -> Vec<VulnerabilityAdvisory> {
+        self.advisories
+            .iter()
+            .filter(|advisory| advisory.affects(version))
+            .cloned()
+            .collect()
+    }
 }
 
 /// Represents a record entry being published.
@@ -252,17 +437,41 @@ pub enum PublishEntry {
     /// The package is being initialized.
     Init,
     /// A new release entry is being published.
+    #[serde(rename_all = "camelCase")]
     Release {
         /// The version of the release.
         version: Version,
         /// The content digest of the release.
         content: AnyHash,
+        /// The declared type of the release content.
+        #[serde(default)]
+        content_type: ContentType,
     },
     /// A release is being yanked.
     Yank {
         /// The version of the release being yanked.
         version: Version,
     },
+    /// An attestation is being attached to a release.
+    Attest {
+        /// The version of the release being attested.
+        version: Version,
+        /// The kind of the attestation (e.g. `https://slsa.dev/provenance/v1`).
+        kind: String,
+        /// The content digest of the attestation.
+        content: AnyHash,
+    },
+    /// The package is being deprecated.
+    #[serde(rename_all = "camelCase")]
+    Deprecate {
+        /// The reason the package is deprecated.
+        message: String,
+        /// The name of the package that replaces the deprecated package.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        replacement: Option<PackageName>,
+    },
+    /// The deprecation of the package is being removed.
+    Undeprecate,
     /// A key is being granted permission(s).
     Grant {
         /// The public key being granted to.
@@ -279,6 +488,50 @@ pub enum PublishEntry {
     },
 }
 
+/// Represents a package record signed and submitted by the client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningAuditEntry {
+    /// The id of the signed record.
+    pub record_id: RecordId,
+    /// The package of the record.
+    pub package: PackageName,
+    /// The registry domain the record was submitted to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<RegistryDomain>,
+    /// The id of the record the signed record follows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev: Option<RecordId>,
+    /// The entries of the record.
+    pub entries: Vec<PublishEntry>,
+    /// The key that signed the record.
+    pub key_id: KeyID,
+    /// The timestamp of the record.
+    pub timestamp: SystemTime,
+    /// The outcome of submitting the record.
+    pub outcome: SigningOutcome,
+}
+
+/// Represents the outcome of submitting a signed package record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SigningOutcome {
+    /// The record was accepted by the registry for publishing.
+    Submitted,
+    /// The record was staged by the registry.
+    Staged,
+    /// The record was rejected by the registry.
+    Rejected {
+        /// The reason the record was rejected.
+        reason: String,
+    },
+    /// The record could not be submitted.
+    Failed {
+        /// The error that prevented the record from being submitted.
+        error: String,
+    },
+}
+
 /// Represents information about a package publish.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -300,25 +553,49 @@ impl PublishInfo {
         self.entries.iter().any(|e| matches!(e, PublishEntry::Init))
     }
 
-    pub(crate) fn finalize(
-        self,
-        signing_key: &signing::PrivateKey,
-    ) -> Result<ProtoEnvelope<PackageRecord>> {
+    /// Converts the publish information into an unsigned package record to
+    /// be signed with the given key.
+    pub(crate) fn into_record(self, key: &signing::PublicKey) -> PackageRecord {
         let mut entries = Vec::with_capacity(self.entries.len());
         for entry in self.entries {
             match entry {
                 PublishEntry::Init => {
                     entries.push(package::PackageEntry::Init {
                         hash_algorithm: HashAlgorithm::Sha256,
-                        key: signing_key.public_key(),
+                        key: key.clone(),
                     });
                 }
-                PublishEntry::Release { version, content } => {
-                    entries.push(package::PackageEntry::Release { version, content });
+                PublishEntry::Release {
+                    version,
+                    content,
+                    content_type,
+                } => {
+                    entries.push(package::PackageEntry::Release {
+                        version,
+                        content,
+                        content_type,
+                    });
                 }
                 PublishEntry::Yank { version } => {
                     entries.push(package::PackageEntry::Yank { version })
                 }
+                PublishEntry::Attest {
+                    version,
+                    kind,
+                    content,
+                } => entries.push(package::PackageEntry::Attest {
+                    version,
+                    kind,
+                    content,
+                }),
+                PublishEntry::Deprecate {
+                    message,
+                    replacement,
+                } => entries.push(package::PackageEntry::Deprecate {
+                    message,
+                    replacement,
+                }),
+                PublishEntry::Undeprecate => entries.push(package::PackageEntry::Undeprecate),
                 PublishEntry::Grant { key, permissions } => {
                     entries.push(package::PackageEntry::GrantFlat { key, permissions })
                 }
@@ -332,7 +609,7 @@ impl PublishInfo {
             }
         }
 
-        let record = package::PackageRecord {
+        package::PackageRecord {
             prev: self.head,
             version: PACKAGE_RECORD_VERSION,
             // TODO: this seems wrong to record the current time client-side
@@ -340,8 +617,6 @@ impl PublishInfo {
             // Should incrementing timestamps even be a requirement?
             timestamp: SystemTime::now(),
             entries,
-        };
-
-        Ok(ProtoEnvelope::signed_contents(signing_key, record)?)
+        }
     }
 }
@@ -2,18 +2,19 @@
 
 use super::{
     ContentStorage, NamespaceMapStorage, OperatorInfo, PackageInfo, PublishInfo, RegistryDomain,
-    RegistryStorage,
+    RegistryStorage, SigningAuditEntry, VerifiedProofs,
 };
 use crate::lock::FileLock;
 use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures_util::{Stream, StreamExt, TryStreamExt};
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
 use std::{
     ffi::OsStr,
-    fs,
+    fmt, fs,
+    io::Write,
     path::{Path, PathBuf},
     pin::Pin,
     str::FromStr,
@@ -31,13 +32,187 @@ use warg_protocol::{
 const TEMP_DIRECTORY: &str = "temp";
 const PENDING_PUBLISH_FILE: &str = "pending-publish.json";
 const LOCK_FILE_NAME: &str = ".lock";
+const TEMP_FILE_PREFIX: &str = ".tmp-";
+const MAX_REGISTRY_PREFIX_LEN: usize = 32;
+const REGISTRY_HASH_LEN: usize = 8;
 const PACKAGE_LOGS_DIR: &str = "package-logs";
+const VERIFIED_PROOFS_FILE: &str = "verified-proofs.json";
+const PINS_FILE: &str = "pins.json";
+const SIGNING_AUDIT_FILE: &str = "signing-audit.json";
+
+/// The layout of paths in file system storage.
+///
+/// Changing the layout of existing storage does not migrate its files; the
+/// storage is fetched again from the registry.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageLayout {
+    /// Registry directories are named after their registry.
+    #[default]
+    Legacy,
+    /// Registry directories are named with a sanitized and truncated prefix
+    /// of their registry followed by a hash of it, and package logs are
+    /// fanned out by the first byte of their log ID.
+    ///
+    /// Every path component is of bounded length and valid on Windows file
+    /// systems, and on Windows, absolute paths use the extended-length
+    /// prefix so that storage is not limited to `MAX_PATH`.
+    Hashed,
+}
+
+impl StorageLayout {
+    /// Gets the name of the storage directory of the given registry, which
+    /// is a registry domain or the label of a registry URL.
+    pub fn registry_dir_name(&self, registry: &str) -> String {
+        match self {
+            Self::Legacy => registry.to_string(),
+            Self::Hashed => {
+                let prefix = sanitize_component(registry)
+                    .chars()
+                    .take(MAX_REGISTRY_PREFIX_LEN)
+                    .collect::<String>();
+                let hash = Sha256::digest(registry.as_bytes())
+                    .iter()
+                    .take(REGISTRY_HASH_LEN)
+                    .map(|b| format!("{b:02x}"))
+                    .collect::<String>();
+                format!("{prefix}-{hash}")
+            }
+        }
+    }
+
+    /// Gets the path of the log of the given package relative to the
+    /// package logs directory.
+    fn package_log_path(&self, name: &PackageName) -> PathBuf {
+        let id = LogId::package_log::<Sha256>(name).to_string();
+        match (self, id.split_once(':')) {
+            (Self::Hashed, Some((algorithm, hex))) if hex.len() > 2 => {
+                [algorithm, &hex[..2], &hex[2..]].iter().collect()
+            }
+            _ => PathBuf::from(id.replace(':', "/")),
+        }
+    }
+
+    /// Prepares a storage directory path for use with the layout.
+    fn storage_path(&self, path: PathBuf) -> PathBuf {
+        match self {
+            Self::Legacy => path,
+            Self::Hashed => long_path(path),
+        }
+    }
+}
+
+impl fmt::Display for StorageLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Legacy => write!(f, "legacy"),
+            Self::Hashed => write!(f, "hashed"),
+        }
+    }
+}
+
+impl FromStr for StorageLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "legacy" => Ok(Self::Legacy),
+            "hashed" => Ok(Self::Hashed),
+            _ => bail!("invalid storage layout `{s}`; expected `legacy` or `hashed`"),
+        }
+    }
+}
+
+/// The file names that are reserved on Windows, regardless of extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Sanitizes a path component so that it is valid on all supported file
+/// systems.
+///
+/// Characters that are not allowed in Windows file names are replaced,
+/// trailing dots and spaces are removed, and reserved names are prefixed.
+fn sanitize_component(name: &str) -> String {
+    let mut component = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+
+    while component.ends_with(['.', ' ']) {
+        component.pop();
+    }
+
+    let stem = component.split('.').next().unwrap_or_default();
+    if component.is_empty() || RESERVED_NAMES.iter().any(|r| stem.eq_ignore_ascii_case(r)) {
+        component.insert(0, '_');
+    }
+
+    component
+}
+
+/// Converts an absolute path to an extended-length path on Windows.
+#[cfg(windows)]
+fn long_path(path: PathBuf) -> PathBuf {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    // Verbatim paths are not normalized, so only paths without relative
+    // components can be converted
+    if !path.is_absolute()
+        || path
+            .components()
+            .any(|c| matches!(c, Component::CurDir | Component::ParentDir))
+    {
+        return path;
+    }
+
+    let mut components = path.components();
+    let mut long = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(_) => {
+                let mut long = OsString::from(r"\\?\");
+                long.push(prefix.as_os_str());
+                long
+            }
+            Prefix::UNC(server, share) => {
+                let mut long = OsString::from(r"\\?\UNC\");
+                long.push(server);
+                long.push(r"\");
+                long.push(share);
+                long
+            }
+            _ => return path,
+        },
+        _ => return path,
+    };
+
+    for component in components {
+        if let Component::Normal(name) = component {
+            long.push(r"\");
+            long.push(name);
+        }
+    }
+
+    PathBuf::from(long)
+}
+
+#[cfg(not(windows))]
+fn long_path(path: PathBuf) -> PathBuf {
+    path
+}
 
 /// Represents a package storage using the local file system.
 pub struct FileSystemRegistryStorage {
     _lock: FileLock,
     base_dir: PathBuf,
     registries_dir: PathBuf,
+    layout: StorageLayout,
 }
 
 impl FileSystemRegistryStorage {
@@ -47,17 +222,22 @@ impl FileSystemRegistryStorage {
     ///
     /// If the lock cannot be acquired, `Ok(None)` is returned.
     pub fn try_lock(base_dir: impl Into<PathBuf>) -> Result<Option<Self>> {
-        let base_dir = base_dir.into();
-        let registries_dir = &mut base_dir
-            .parent()
-            .context("base_dir cannot be empty")?
-            .to_path_buf();
+        Self::try_lock_with_layout(base_dir, StorageLayout::Legacy)
+    }
+
+    /// Attempts to lock the package storage with the given layout.
+    ///
+    /// The base directory will be created if it does not exist; its name
+    /// is expected to be given by [`StorageLayout::registry_dir_name`].
+    ///
+    /// If the lock cannot be acquired, `Ok(None)` is returned.
+    pub fn try_lock_with_layout(
+        base_dir: impl Into<PathBuf>,
+        layout: StorageLayout,
+    ) -> Result<Option<Self>> {
+        let base_dir = layout.storage_path(base_dir.into());
         match FileLock::try_open_rw(base_dir.join(LOCK_FILE_NAME))? {
-            Some(lock) => Ok(Some(Self {
-                _lock: lock,
-                base_dir,
-                registries_dir: registries_dir.to_path_buf(),
-            })),
+            Some(lock) => Self::new(lock, base_dir, layout).map(Some),
             None => Ok(None),
         }
     }
@@ -69,27 +249,88 @@ impl FileSystemRegistryStorage {
     /// If the lock cannot be immediately acquired, this function
     /// will block.
     pub fn lock(base_dir: impl Into<PathBuf>) -> Result<Self> {
-        let base_dir = base_dir.into();
+        Self::lock_with_layout(base_dir, StorageLayout::Legacy)
+    }
+
+    /// Locks a new package storage with the given layout at the given base
+    /// directory.
+    ///
+    /// The base directory will be created if it does not exist; its name
+    /// is expected to be given by [`StorageLayout::registry_dir_name`].
+    ///
+    /// If the lock cannot be immediately acquired, this function
+    /// will block.
+    pub fn lock_with_layout(base_dir: impl Into<PathBuf>, layout: StorageLayout) -> Result<Self> {
+        let base_dir = layout.storage_path(base_dir.into());
         let lock = FileLock::open_rw(base_dir.join(LOCK_FILE_NAME))?;
-        let registries_dir = &mut base_dir
+        Self::new(lock, base_dir, layout)
+    }
+
+    fn new(lock: FileLock, base_dir: PathBuf, layout: StorageLayout) -> Result<Self> {
+        let registries_dir = base_dir
             .parent()
             .context("base_dir cannot be empty")?
             .to_path_buf();
-        Ok(Self {
+        let storage = Self {
             _lock: lock,
             base_dir,
-            registries_dir: registries_dir.to_path_buf(),
-        })
+            registries_dir,
+            layout,
+        };
+        storage.recover()?;
+        Ok(storage)
     }
 
-    fn operator_path(&self, namespace_registry: Option<&RegistryDomain>) -> PathBuf {
-        if let Some(nm) = namespace_registry {
-            return self
+    /// Removes temporary files left by interrupted writes from the storage.
+    ///
+    /// Writes are atomic, but a crash may leave temporary files behind.
+    /// State files are not read here; a torn state file written by an
+    /// earlier version of the client is treated as absent when loaded, so
+    /// the state is fetched again. The directories of other registries are
+    /// only recovered when they are not locked by another client.
+    fn recover(&self) -> Result<()> {
+        let Ok(entries) = fs::read_dir(&self.registries_dir) else {
+            return Ok(());
+        };
+
+        for entry in entries {
+            let dir = entry?.path();
+            if !dir.is_dir() {
+                continue;
+            }
+
+            let _lock = if dir == self.base_dir {
+                None
+            } else {
+                match FileLock::try_open_rw(dir.join(LOCK_FILE_NAME))? {
+                    Some(lock) => Some(lock),
+                    None => continue,
+                }
+            };
+
+            let removed = remove_temp_files(&dir)?;
+            if removed > 0 {
+                tracing::warn!(
+                    "removed {removed} temporary file(s) from registry storage `{path}`",
+                    path = dir.display()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn registry_dir(&self, namespace_registry: Option<&RegistryDomain>) -> PathBuf {
+        match namespace_registry {
+            Some(nm) => self
                 .registries_dir
-                .join(nm.to_string())
-                .join("operator.log");
+                .join(self.layout.registry_dir_name(nm.as_str())),
+            None => self.base_dir.clone(),
         }
-        self.base_dir.join("operator.log")
+    }
+
+    fn operator_path(&self, namespace_registry: Option<&RegistryDomain>) -> PathBuf {
+        self.registry_dir(namespace_registry).join("operator.log")
     }
 
     fn package_path(
@@ -97,27 +338,23 @@ impl FileSystemRegistryStorage {
         namespace_registry: Option<&RegistryDomain>,
         name: &PackageName,
     ) -> PathBuf {
-        if let Some(nm) = namespace_registry {
-            return self
-                .registries_dir
-                .join(nm.to_string())
-                .join(PACKAGE_LOGS_DIR)
-                .join(
-                    LogId::package_log::<Sha256>(name)
-                        .to_string()
-                        .replace(':', "/"),
-                );
-        }
-        self.base_dir.join(PACKAGE_LOGS_DIR).join(
-            LogId::package_log::<Sha256>(name)
-                .to_string()
-                .replace(':', "/"),
-        )
+        self.registry_dir(namespace_registry)
+            .join(PACKAGE_LOGS_DIR)
+            .join(self.layout.package_log_path(name))
     }
 
     fn pending_publish_path(&self) -> PathBuf {
         self.base_dir.join(PENDING_PUBLISH_FILE)
     }
+
+    fn signing_audit_path(&self) -> PathBuf {
+        self.base_dir.join(SIGNING_AUDIT_FILE)
+    }
+
+    fn verified_proofs_path(&self, namespace_registry: Option<&RegistryDomain>) -> PathBuf {
+        self.registry_dir(namespace_registry)
+            .join(VERIFIED_PROOFS_FILE)
+    }
 }
 
 #[async_trait]
@@ -134,10 +371,7 @@ impl RegistryStorage for FileSystemRegistryStorage {
         &self,
         namespace_registry: Option<&RegistryDomain>,
     ) -> Result<Option<SerdeEnvelope<TimestampedCheckpoint>>> {
-        if let Some(nm) = namespace_registry {
-            return load(&self.registries_dir.join(nm.to_string()).join("checkpoint")).await;
-        }
-        load(&self.base_dir.join("checkpoint")).await
+        load(&self.registry_dir(namespace_registry).join("checkpoint")).await
     }
 
     async fn store_checkpoint(
@@ -145,14 +379,11 @@ impl RegistryStorage for FileSystemRegistryStorage {
         namespace_registry: Option<&RegistryDomain>,
         ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
     ) -> Result<()> {
-        if let Some(nm) = namespace_registry {
-            return store(
-                &self.registries_dir.join(nm.to_string()).join("checkpoint"),
-                ts_checkpoint,
-            )
-            .await;
-        }
-        store(&self.base_dir.join("checkpoint"), ts_checkpoint).await
+        store(
+            &self.registry_dir(namespace_registry).join("checkpoint"),
+            ts_checkpoint,
+        )
+        .await
     }
 
     async fn load_all_packages(&self) -> Result<IndexMap<RegistryDomain, Vec<PackageInfo>>> {
@@ -186,7 +417,17 @@ impl RegistryStorage for FileSystemRegistryStorage {
                     })?;
                     packages.push(info);
                 }
-                all_packages.insert(RegistryDomain::from_str(name)?, packages);
+                // Hashed directory names cannot be mapped back to their
+                // registry, so the registry recorded by the packages is used
+                let registry = match self.layout {
+                    StorageLayout::Legacy => None,
+                    StorageLayout::Hashed => packages.iter().find_map(|p| p.registry.clone()),
+                };
+                let registry = match registry {
+                    Some(registry) => registry,
+                    None => RegistryDomain::from_str(name)?,
+                };
+                all_packages.insert(registry, packages);
             };
         }
         Ok(all_packages)
@@ -236,6 +477,33 @@ impl RegistryStorage for FileSystemRegistryStorage {
             None => delete(&path).await,
         }
     }
+
+    async fn load_verified_proofs(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+    ) -> Result<Option<VerifiedProofs>> {
+        load(&self.verified_proofs_path(namespace_registry)).await
+    }
+
+    async fn store_verified_proofs(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+        proofs: &VerifiedProofs,
+    ) -> Result<()> {
+        store(&self.verified_proofs_path(namespace_registry), proofs).await
+    }
+
+    async fn load_signing_audit(&self) -> Result<Vec<SigningAuditEntry>> {
+        Ok(load(&self.signing_audit_path()).await?.unwrap_or_default())
+    }
+
+    async fn append_signing_audit(&self, entry: &SigningAuditEntry) -> Result<()> {
+        // The storage lock is held, so the trail cannot be appended to
+        // concurrently
+        let mut entries = self.load_signing_audit().await?;
+        entries.push(entry.clone());
+        store(&self.signing_audit_path(), entries).await
+    }
 }
 
 /// Represents a content storage using the local file system.
@@ -243,6 +511,7 @@ pub struct FileSystemContentStorage {
     _lock: FileLock,
     base_dir: PathBuf,
     temp_dir: PathBuf,
+    fallback_dir: Option<PathBuf>,
 }
 
 impl FileSystemContentStorage {
@@ -252,14 +521,21 @@ impl FileSystemContentStorage {
     ///
     /// If the lock cannot be acquired, `Ok(None)` is returned.
     pub fn try_lock(base_dir: impl Into<PathBuf>) -> Result<Option<Self>> {
-        let base_dir = base_dir.into();
-        let temp_dir = base_dir.join(TEMP_DIRECTORY);
+        Self::try_lock_with_layout(base_dir, StorageLayout::Legacy)
+    }
+
+    /// Attempts to lock the content storage with the given layout.
+    ///
+    /// The base directory will be created if it does not exist.
+    ///
+    /// If the lock cannot be acquired, `Ok(None)` is returned.
+    pub fn try_lock_with_layout(
+        base_dir: impl Into<PathBuf>,
+        layout: StorageLayout,
+    ) -> Result<Option<Self>> {
+        let base_dir = layout.storage_path(base_dir.into());
         match FileLock::try_open_rw(base_dir.join(LOCK_FILE_NAME))? {
-            Some(lock) => Ok(Some(Self {
-                _lock: lock,
-                base_dir,
-                temp_dir,
-            })),
+            Some(lock) => Self::new(lock, base_dir).map(Some),
             None => Ok(None),
         }
     }
@@ -271,14 +547,59 @@ impl FileSystemContentStorage {
     /// If the lock cannot be immediately acquired, this function
     /// will block.
     pub fn lock(base_dir: impl Into<PathBuf>) -> Result<Self> {
-        let base_dir = base_dir.into();
-        let temp_dir = base_dir.join(TEMP_DIRECTORY);
+        Self::lock_with_layout(base_dir, StorageLayout::Legacy)
+    }
+
+    /// Locks a new content storage with the given layout at the given base
+    /// directory.
+    ///
+    /// The base directory will be created if it does not exist.
+    ///
+    /// If the lock cannot be immediately acquired, this function
+    /// will block.
+    pub fn lock_with_layout(base_dir: impl Into<PathBuf>, layout: StorageLayout) -> Result<Self> {
+        let base_dir = layout.storage_path(base_dir.into());
         let lock = FileLock::open_rw(base_dir.join(LOCK_FILE_NAME))?;
-        Ok(Self {
+        Self::new(lock, base_dir)
+    }
+
+    fn new(lock: FileLock, base_dir: PathBuf) -> Result<Self> {
+        let temp_dir = base_dir.join(TEMP_DIRECTORY);
+        let storage = Self {
             _lock: lock,
             base_dir,
             temp_dir,
-        })
+            fallback_dir: None,
+        };
+        storage.recover()?;
+        Ok(storage)
+    }
+
+    /// Sets a content directory to read content from when it is not in the
+    /// storage's base directory.
+    ///
+    /// The fallback directory is not locked and is never written to; content
+    /// read from it is not copied into the base directory.
+    pub fn with_fallback(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.fallback_dir = Some(dir.into());
+        self
+    }
+
+    /// Removes the temporary files of downloads interrupted by a crash.
+    ///
+    /// Content is only moved out of the temporary directory once it is
+    /// completely written and synced, so anything left in it is partial.
+    fn recover(&self) -> Result<()> {
+        if self.temp_dir.is_dir() {
+            fs::remove_dir_all(&self.temp_dir).with_context(|| {
+                format!(
+                    "failed to remove directory `{path}`",
+                    path = self.temp_dir.display()
+                )
+            })?;
+        }
+
+        Ok(())
     }
 
     fn temp_file(&self) -> Result<NamedTempFile> {
@@ -300,16 +621,39 @@ impl FileSystemContentStorage {
     fn content_path(&self, digest: &AnyHash) -> PathBuf {
         self.base_dir.join(digest.to_string().replace(':', "/"))
     }
+
+    fn pins_path(&self) -> PathBuf {
+        self.base_dir.join(PINS_FILE)
+    }
 }
 
 #[async_trait]
 impl ContentStorage for FileSystemContentStorage {
     async fn clear(&self) -> Result<()> {
-        remove(&self.base_dir).await
+        let pins = self.pinned_content().await?;
+        if pins.is_empty() {
+            return remove(&self.base_dir).await;
+        }
+
+        for digest in self.list_content().await? {
+            if !pins.contains(&digest) {
+                self.remove_content(&digest).await?;
+            }
+        }
+
+        Ok(())
     }
 
     fn content_location(&self, digest: &AnyHash) -> Option<PathBuf> {
         let path = self.content_path(digest);
+        if path.is_file() {
+            return Some(path);
+        }
+
+        let path = self
+            .fallback_dir
+            .as_ref()?
+            .join(digest.to_string().replace(':', "/"));
         if path.is_file() {
             Some(path)
         } else {
@@ -321,10 +665,9 @@ impl ContentStorage for FileSystemContentStorage {
         &self,
         digest: &AnyHash,
     ) -> Result<Option<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>>> {
-        let path = self.content_path(digest);
-        if !path.is_file() {
+        let Some(path) = self.content_location(digest) else {
             return Ok(None);
-        }
+        };
 
         Ok(Some(Box::pin(
             ReaderStream::new(BufReader::new(
@@ -367,8 +710,11 @@ impl ContentStorage for FileSystemContentStorage {
             .shutdown()
             .await
             .with_context(|| format!("failed to write `{path}`", path = path.display()))?;
-
-        drop(writer);
+        writer
+            .into_inner()
+            .sync_all()
+            .await
+            .with_context(|| format!("failed to sync `{path}`", path = path.display()))?;
 
         let content_path = self.content_path(&hash);
         if !content_path.is_file() {
@@ -387,10 +733,93 @@ impl ContentStorage for FileSystemContentStorage {
                     path = content_path.display()
                 )
             })?;
+            if let Some(parent) = content_path.parent() {
+                sync_dir(parent)?;
+            }
         }
 
         Ok(hash)
     }
+
+    async fn list_content(&self) -> Result<Vec<AnyHash>> {
+        let mut digests = Vec::new();
+        if !self.base_dir.is_dir() {
+            return Ok(digests);
+        }
+
+        // Content is stored at `<algorithm>/<hex digest>` in the base directory
+        for entry in WalkDir::new(&self.base_dir)
+            .min_depth(2)
+            .max_depth(2)
+            .into_iter()
+            .filter_entry(|entry| entry.path() != self.temp_dir)
+        {
+            let entry = entry.with_context(|| {
+                format!(
+                    "failed to read content directory `{path}`",
+                    path = self.base_dir.display()
+                )
+            })?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let algorithm = entry
+                .path()
+                .parent()
+                .and_then(Path::file_name)
+                .and_then(OsStr::to_str);
+            let digest = entry.file_name().to_str();
+            if let (Some(algorithm), Some(digest)) = (algorithm, digest) {
+                if let Ok(digest) = format!("{algorithm}:{digest}").parse() {
+                    digests.push(digest);
+                }
+            }
+        }
+
+        Ok(digests)
+    }
+
+    async fn remove_content(&self, digest: &AnyHash) -> Result<()> {
+        delete(&self.content_path(digest)).await
+    }
+
+    async fn pin_content(&self, digest: &AnyHash) -> Result<()> {
+        let mut pins = self.pinned_content().await?;
+        if pins.insert(digest.clone()) {
+            store(&self.pins_path(), pins).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn unpin_content(&self, digest: &AnyHash) -> Result<()> {
+        let mut pins = self.pinned_content().await?;
+        if pins.shift_remove(digest) {
+            store(&self.pins_path(), pins).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn pinned_content(&self) -> Result<IndexSet<AnyHash>> {
+        Ok(load(&self.pins_path()).await?.unwrap_or_default())
+    }
+
+    fn available_space(&self) -> Result<Option<u64>> {
+        // The base directory is removed when storage is cleared, so use the
+        // nearest directory that exists
+        let Some(dir) = self.base_dir.ancestors().find(|dir| dir.is_dir()) else {
+            return Ok(None);
+        };
+
+        sys::available_space(dir).with_context(|| {
+            format!(
+                "failed to get the available space of `{path}`",
+                path = self.base_dir.display()
+            )
+        })
+    }
 }
 
 /// Represents a namespace_domain map storage using the local file system.
@@ -425,9 +854,7 @@ impl NamespaceMapStorage for FileSystemNamespaceMapStorage {
     ) -> Result<()> {
         let mut mapping = self.load_namespace_map().await?.unwrap_or_default();
         mapping.insert(namespace, registry_domain.to_string());
-        let json = serde_json::to_string(&mapping)?;
-        fs::write(&self.path, json)?;
-        Ok(())
+        store(&self.path, mapping).await
     }
 }
 
@@ -452,24 +879,22 @@ async fn load<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<Option<T>> {
         .await
         .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
 
-    serde_json::from_str(&contents).with_context(|| {
-        format!(
-            "failed to deserialize contents of `{path}`",
-            path = path.display()
-        )
-    })
-}
-
-async fn store(path: &Path, value: impl Serialize) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).with_context(|| {
-            format!(
-                "failed to create parent directory for `{path}`",
+    // A file that cannot be deserialized (e.g. one torn by a crash while
+    // written by an earlier version of the client) is treated as absent so
+    // that its state is fetched and stored again
+    match serde_json::from_str(&contents) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) => {
+            tracing::warn!(
+                "ignoring invalid storage file `{path}`: {e}",
                 path = path.display()
-            )
-        })?;
+            );
+            Ok(None)
+        }
     }
+}
 
+async fn store(path: &Path, value: impl Serialize) -> Result<()> {
     let contents = serde_json::to_vec_pretty(&value).with_context(|| {
         format!(
             "failed to serialize contents of `{path}`",
@@ -477,9 +902,87 @@ async fn store(path: &Path, value: impl Serialize) -> Result<()> {
         )
     })?;
 
-    tokio::fs::write(path, contents)
-        .await
-        .with_context(|| format!("failed to write `{path}`", path = path.display()))
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || write_atomic(&path, &contents)).await?
+}
+
+/// Writes the given contents to a file atomically.
+///
+/// The contents are written to a temporary file in the same directory and
+/// synced before the temporary file replaces the file, so a crash leaves
+/// either the previous or the new contents in place.
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let parent = path
+        .parent()
+        .with_context(|| format!("`{path}` has no parent directory", path = path.display()))?;
+    fs::create_dir_all(parent).with_context(|| {
+        format!(
+            "failed to create parent directory for `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    let mut file = tempfile::Builder::new()
+        .prefix(TEMP_FILE_PREFIX)
+        .tempfile_in(parent)
+        .with_context(|| {
+            format!(
+                "failed to create temporary file in `{path}`",
+                path = parent.display()
+            )
+        })?;
+    file.write_all(contents)
+        .and_then(|_| file.as_file().sync_all())
+        .with_context(|| format!("failed to write `{path}`", path = file.path().display()))?;
+    file.persist(path)
+        .with_context(|| format!("failed to write `{path}`", path = path.display()))?;
+
+    sync_dir(parent)
+}
+
+/// Syncs a directory so that the renames of its entries are durable.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<()> {
+    fs::File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .with_context(|| format!("failed to sync directory `{path}`", path = dir.display()))
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Removes the temporary files left in the given directory by interrupted
+/// writes of [`write_atomic`].
+///
+/// Only file names are inspected; no file is read.
+///
+/// Returns the number of files removed.
+fn remove_temp_files(dir: &Path) -> Result<usize> {
+    let mut removed = 0;
+    for entry in WalkDir::new(dir) {
+        let entry = entry.with_context(|| {
+            format!(
+                "failed to read storage directory `{path}`",
+                path = dir.display()
+            )
+        })?;
+        let path = entry.path();
+        let temporary = entry.file_type().is_file()
+            && entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with(TEMP_FILE_PREFIX));
+        if temporary {
+            fs::remove_file(path).with_context(|| {
+                format!("failed to remove file `{path}`", path = path.display())
+            })?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
 }
 
 async fn delete(path: &Path) -> Result<()> {
@@ -491,3 +994,166 @@ async fn delete(path: &Path) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::CString;
+    use std::io::{Error, ErrorKind, Result};
+    use std::mem;
+    use std::os::unix::prelude::*;
+    use std::path::Path;
+
+    pub(super) fn available_space(path: &Path) -> Result<Option<u64>> {
+        let path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+
+        unsafe {
+            let mut buf: libc::statvfs = mem::zeroed();
+            if libc::statvfs(path.as_ptr(), &mut buf) != 0 {
+                return Err(Error::last_os_error());
+            }
+
+            // The field types differ between targets
+            #[allow(clippy::unnecessary_cast)]
+            Ok(Some(
+                (buf.f_bavail as u64).saturating_mul(buf.f_frsize as u64),
+            ))
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::io::{Error, Result};
+    use std::iter;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use std::ptr;
+
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    pub(super) fn available_space(path: &Path) -> Result<Option<u64>> {
+        let path = path
+            .as_os_str()
+            .encode_wide()
+            .chain(iter::once(0))
+            .collect::<Vec<_>>();
+
+        let mut available = 0u64;
+        let ret = unsafe {
+            GetDiskFreeSpaceExW(
+                path.as_ptr(),
+                &mut available,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        if ret == 0 {
+            Err(Error::last_os_error())
+        } else {
+            Ok(Some(available))
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use std::io::Result;
+    use std::path::Path;
+
+    pub(super) fn available_space(_path: &Path) -> Result<Option<u64>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_sanitizes_path_components() {
+        assert_eq!(sanitize_component("localhost:8090"), "localhost_8090");
+        assert_eq!(sanitize_component("con"), "_con");
+        assert_eq!(sanitize_component("Aux.json"), "_Aux.json");
+        assert_eq!(sanitize_component("com1. "), "_com1");
+        assert_eq!(sanitize_component("..."), "_");
+        assert_eq!(sanitize_component("a<b>c|d?e*f\\g\u{7}"), "a_b_c_d_e_f_g_");
+        assert_eq!(sanitize_component("console"), "console");
+    }
+
+    #[test]
+    fn it_hashes_registry_dir_names() {
+        let long = "a".repeat(300);
+        let names = ["con", "registry.example.com", long.as_str()]
+            .map(|registry| StorageLayout::Hashed.registry_dir_name(registry));
+        for name in &names {
+            assert!(name.len() <= MAX_REGISTRY_PREFIX_LEN + 1 + REGISTRY_HASH_LEN * 2);
+            assert_eq!(sanitize_component(name), *name);
+        }
+        assert!(names[0].starts_with("_con-"));
+        assert_ne!(
+            StorageLayout::Hashed.registry_dir_name(&"a".repeat(301)),
+            names[2]
+        );
+        assert_eq!(
+            StorageLayout::Legacy.registry_dir_name("registry.example.com"),
+            "registry.example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_keeps_pinned_content() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let storage = FileSystemContentStorage::lock(dir.path())?;
+        let mut digests = Vec::new();
+        for content in [&b"pinned"[..], b"unpinned"] {
+            digests.push(
+                storage
+                    .store_content(
+                        Box::pin(futures_util::stream::once(async move {
+                            Ok(Bytes::from_static(content))
+                        })),
+                        None,
+                    )
+                    .await?,
+            );
+        }
+
+        storage.pin_content(&digests[0]).await?;
+        storage.pin_content(&digests[0]).await?;
+        assert_eq!(storage.pinned_content().await?.len(), 1);
+
+        storage.clear().await?;
+        assert_eq!(storage.list_content().await?, [digests[0].clone()]);
+
+        storage.unpin_content(&digests[0]).await?;
+        storage.unpin_content(&digests[1]).await?;
+        assert!(storage.pinned_content().await?.is_empty());
+
+        storage.clear().await?;
+        assert!(storage.list_content().await?.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[tokio::test]
+    async fn it_maps_content() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let storage = FileSystemContentStorage::lock(dir.path())?;
+        let digest = storage
+            .store_content(
+                Box::pin(futures_util::stream::once(async {
+                    Ok(Bytes::from_static(b"mapped content"))
+                })),
+                None,
+            )
+            .await?;
+
+        let mapped = storage.map_content(&digest).await?.unwrap();
+        assert_eq!(&*mapped, b"mapped content");
+
+        let missing = warg_crypto::hash::HashAlgorithm::Sha256.digest(b"missing");
+        assert!(storage.map_content(&missing).await?.is_none());
+        Ok(())
+    }
+}
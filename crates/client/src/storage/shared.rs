@@ -0,0 +1,189 @@
+//! Storage implementations for shared storage.
+//!
+//! Storage behind an [`Arc`] forwards to the shared storage, so clients may
+//! share storage or hold storage as trait objects (e.g. an
+//! `Arc<dyn RegistryStorage>`).
+
+#[cfg(feature = "mmap")]
+use super::MappedContent;
+use super::{
+    ContentStorage, NamespaceMapStorage, OperatorInfo, PackageInfo, PublishInfo, RegistryDomain,
+    RegistryStorage, SigningAuditEntry, VerifiedProofs,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::Stream;
+use indexmap::{IndexMap, IndexSet};
+use std::{path::PathBuf, pin::Pin, sync::Arc};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    registry::{PackageName, TimestampedCheckpoint},
+    SerdeEnvelope,
+};
+
+#[async_trait]
+impl<T: RegistryStorage + ?Sized> RegistryStorage for Arc<T> {
+    async fn reset(&self, all_registries: bool) -> Result<()> {
+        (**self).reset(all_registries).await
+    }
+
+    async fn load_checkpoint(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+    ) -> Result<Option<SerdeEnvelope<TimestampedCheckpoint>>> {
+        (**self).load_checkpoint(namespace_registry).await
+    }
+
+    async fn store_checkpoint(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<()> {
+        (**self)
+            .store_checkpoint(namespace_registry, ts_checkpoint)
+            .await
+    }
+
+    async fn load_operator(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+    ) -> Result<Option<OperatorInfo>> {
+        (**self).load_operator(namespace_registry).await
+    }
+
+    async fn store_operator(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+        operator: OperatorInfo,
+    ) -> Result<()> {
+        (**self).store_operator(namespace_registry, operator).await
+    }
+
+    async fn load_all_packages(&self) -> Result<IndexMap<RegistryDomain, Vec<PackageInfo>>> {
+        (**self).load_all_packages().await
+    }
+
+    async fn load_package(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+        package: &PackageName,
+    ) -> Result<Option<PackageInfo>> {
+        (**self).load_package(namespace_registry, package).await
+    }
+
+    async fn store_package(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+        info: &PackageInfo,
+    ) -> Result<()> {
+        (**self).store_package(namespace_registry, info).await
+    }
+
+    async fn load_publish(&self) -> Result<Option<PublishInfo>> {
+        (**self).load_publish().await
+    }
+
+    async fn store_publish(&self, info: Option<&PublishInfo>) -> Result<()> {
+        (**self).store_publish(info).await
+    }
+
+    async fn load_verified_proofs(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+    ) -> Result<Option<VerifiedProofs>> {
+        (**self).load_verified_proofs(namespace_registry).await
+    }
+
+    async fn store_verified_proofs(
+        &self,
+        namespace_registry: Option<&RegistryDomain>,
+        proofs: &VerifiedProofs,
+    ) -> Result<()> {
+        (**self)
+            .store_verified_proofs(namespace_registry, proofs)
+            .await
+    }
+
+    async fn load_signing_audit(&self) -> Result<Vec<SigningAuditEntry>> {
+        (**self).load_signing_audit().await
+    }
+
+    async fn append_signing_audit(&self, entry: &SigningAuditEntry) -> Result<()> {
+        (**self).append_signing_audit(entry).await
+    }
+}
+
+#[async_trait]
+impl<T: ContentStorage + ?Sized> ContentStorage for Arc<T> {
+    async fn clear(&self) -> Result<()> {
+        (**self).clear().await
+    }
+
+    fn content_location(&self, digest: &AnyHash) -> Option<PathBuf> {
+        (**self).content_location(digest)
+    }
+
+    async fn load_content(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Option<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>>> {
+        (**self).load_content(digest).await
+    }
+
+    async fn store_content(
+        &self,
+        stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+        expected_digest: Option<&AnyHash>,
+    ) -> Result<AnyHash> {
+        (**self).store_content(stream, expected_digest).await
+    }
+
+    async fn list_content(&self) -> Result<Vec<AnyHash>> {
+        (**self).list_content().await
+    }
+
+    fn available_space(&self) -> Result<Option<u64>> {
+        (**self).available_space()
+    }
+
+    async fn remove_content(&self, digest: &AnyHash) -> Result<()> {
+        (**self).remove_content(digest).await
+    }
+
+    async fn pin_content(&self, digest: &AnyHash) -> Result<()> {
+        (**self).pin_content(digest).await
+    }
+
+    async fn unpin_content(&self, digest: &AnyHash) -> Result<()> {
+        (**self).unpin_content(digest).await
+    }
+
+    async fn pinned_content(&self) -> Result<IndexSet<AnyHash>> {
+        (**self).pinned_content().await
+    }
+
+    #[cfg(feature = "mmap")]
+    async fn map_content(&self, digest: &AnyHash) -> Result<Option<MappedContent>> {
+        (**self).map_content(digest).await
+    }
+}
+
+#[async_trait]
+impl<T: NamespaceMapStorage + ?Sized> NamespaceMapStorage for Arc<T> {
+    async fn load_namespace_map(&self) -> Result<Option<IndexMap<String, String>>> {
+        (**self).load_namespace_map().await
+    }
+
+    async fn reset_namespaces(&self) -> Result<()> {
+        (**self).reset_namespaces().await
+    }
+
+    async fn store_namespace(
+        &self,
+        namespace: String,
+        registry_domain: RegistryDomain,
+    ) -> Result<()> {
+        (**self).store_namespace(namespace, registry_domain).await
+    }
+}
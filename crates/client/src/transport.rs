@@ -0,0 +1,417 @@
+//! Module for the transports used to communicate with registries.
+//!
+//! Besides HTTP and HTTPS, a registry may be reached over a Unix domain
+//! socket or, when the registry server is embedded in the same process,
+//! through an in-process service. The transport of a request is selected by
+//! the scheme of its URL:
+//!
+//! * `http+unix://<socket path>/` sends requests over the Unix domain socket
+//!   at the percent-encoded socket path (e.g. `http+unix://%2Ftmp%2Fwarg.sock/`).
+//! * `http+local://<name>/` sends requests to the in-process service
+//!   registered with [`register_local_service`] under the given name.
+//!
+//! Requests sent over these transports are HTTP/1.1 requests with a `Host`
+//! header of `localhost`.
+use crate::{
+    api::ClientError,
+    deadline,
+    limits::Limit,
+    middleware::{Middleware, Next},
+};
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use futures_util::future::{poll_fn, BoxFuture};
+use http_body::Body as HttpBody;
+use http_body_util::BodyExt;
+use once_cell::sync::Lazy;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, HOST},
+    Body, IntoUrl, Method, Request, Response,
+};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tower_service::Service;
+use tracing::instrument::WithSubscriber;
+use url::Url;
+
+/// The URL scheme of registries reached over a Unix domain socket.
+pub const UNIX_SCHEME: &str = "http+unix";
+
+/// The URL scheme of registries reached through an in-process service.
+pub const LOCAL_SCHEME: &str = "http+local";
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+type LocalHandler = Arc<
+    dyn Fn(http::Request<Body>) -> BoxFuture<'static, Result<http::Response<Body>, BoxError>>
+        + Send
+        + Sync,
+>;
+
+/// The in-process services, keyed by name.
+static LOCAL_SERVICES: Lazy<Mutex<HashMap<String, LocalHandler>>> = Lazy::new(Default::default);
+
+/// Registers an in-process service that serves requests to registry URLs
+/// of the form `http+local://<name>/`.
+///
+/// The service is typically the router of an embedded registry server.
+/// Registering a service with the name of an existing service replaces it.
+pub fn register_local_service<S, B>(name: impl Into<String>, service: S)
+where
+    S: Service<http::Request<Body>, Response = http::Response<B>> + Clone + Send + Sync + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let handler: LocalHandler = Arc::new(move |request| {
+        let mut service = service.clone();
+        Box::pin(async move {
+            poll_fn(|cx| service.poll_ready(cx))
+                .await
+                .map_err(Into::into)?;
+            let response = service.call(request).await.map_err(Into::into)?;
+            Ok(response.map(into_body))
+        })
+    });
+
+    LOCAL_SERVICES.lock().unwrap().insert(name.into(), handler);
+}
+
+/// Unregisters the in-process service with the given name.
+///
+/// Returns `true` if a service was registered with the name.
+pub fn unregister_local_service(name: &str) -> bool {
+    LOCAL_SERVICES.lock().unwrap().remove(name).is_some()
+}
+
+/// Creates the URL of a registry reached over the Unix domain socket at the
+/// given path.
+pub fn unix_socket_url(path: impl AsRef<Path>) -> Result<Url> {
+    let path = path.as_ref();
+    let path = path
+        .to_str()
+        .ok_or_else(|| anyhow!("socket path `{path}` is not UTF-8", path = path.display()))?;
+    Url::parse(&format!(
+        "{UNIX_SCHEME}://{host}/",
+        host = utf8_percent_encode(path, NON_ALPHANUMERIC)
+    ))
+    .context("failed to create Unix domain socket URL")
+}
+
+/// Gets the path of the Unix domain socket of a `http+unix` URL.
+fn unix_socket_path(url: &Url) -> Result<PathBuf> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("URL `{url}` has no socket path"))?;
+    Ok(PathBuf::from(
+        percent_decode_str(host)
+            .decode_utf8()
+            .with_context(|| format!("socket path of URL `{url}` is not UTF-8"))?
+            .into_owned(),
+    ))
+}
+
+/// An HTTP client that sends the given default headers with every request.
+///
+/// Unlike the default headers of a [`reqwest::Client`], the headers are set
+/// on each request so they are also sent over local transports.
+///
+/// Requests are sent through the client's middleware. An offline client
+/// fails every request without sending it.
+pub(crate) struct HttpClient {
+    client: reqwest::Client,
+    headers: HeaderMap,
+    middleware: Arc<[Arc<dyn Middleware>]>,
+    offline: bool,
+    requests: Limit,
+}
+
+impl HttpClient {
+    /// Creates a new HTTP client with the given default headers and
+    /// middleware.
+    pub fn new(
+        client: reqwest::Client,
+        headers: HeaderMap,
+        middleware: impl Into<Arc<[Arc<dyn Middleware>]>>,
+    ) -> Self {
+        Self {
+            client,
+            headers,
+            middleware: middleware.into(),
+            offline: false,
+            requests: Limit::default(),
+        }
+    }
+
+    /// Sets the middleware of the client.
+    pub fn set_middleware(&mut self, middleware: impl Into<Arc<[Arc<dyn Middleware>]>>) {
+        self.middleware = middleware.into();
+    }
+
+    /// Sets whether the client is offline.
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// Sets the limit on the requests of the client awaiting a response.
+    pub fn set_request_limit(&mut self, limit: Limit) {
+        self.requests = limit;
+    }
+
+    /// Starts a `GET` request to the given URL.
+    pub fn get(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    /// Starts a `POST` request to the given URL.
+    pub fn post(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Starts a request with the given method to the given URL.
+    pub fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        RequestBuilder {
+            builder: self
+                .client
+                .request(method, url)
+                .headers(self.headers.clone()),
+            middleware: self.middleware.clone(),
+            offline: self.offline,
+            requests: self.requests.clone(),
+        }
+    }
+}
+
+/// A builder of a request sent through the middleware of an [`HttpClient`].
+pub(crate) struct RequestBuilder {
+    builder: reqwest::RequestBuilder,
+    middleware: Arc<[Arc<dyn Middleware>]>,
+    offline: bool,
+    requests: Limit,
+}
+
+impl RequestBuilder {
+    /// Adds a header to the request.
+    pub fn header<K, V>(self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        self.map(|builder| builder.header(key, value))
+    }
+
+    /// Adds the given headers to the request.
+    pub fn headers(self, headers: HeaderMap) -> Self {
+        self.map(|builder| builder.headers(headers))
+    }
+
+    /// Enables bearer authentication of the request.
+    pub fn bearer_auth<T: Display>(self, token: T) -> Self {
+        self.map(|builder| builder.bearer_auth(token))
+    }
+
+    /// Sets the body of the request.
+    pub fn body(self, body: impl Into<Body>) -> Self {
+        self.map(|builder| builder.body(body))
+    }
+
+    /// Sets the body of the request to the JSON serialization of the value.
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        self.map(|builder| builder.json(json))
+    }
+
+    /// Appends the serialization of the value to the query of the request.
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        self.map(|builder| builder.query(query))
+    }
+
+    /// Sets the timeout of the request.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.map(|builder| builder.timeout(timeout))
+    }
+
+    fn map(self, f: impl FnOnce(reqwest::RequestBuilder) -> reqwest::RequestBuilder) -> Self {
+        Self {
+            builder: f(self.builder),
+            middleware: self.middleware,
+            offline: self.offline,
+            requests: self.requests,
+        }
+    }
+
+    /// Sends the request through the middleware of the client.
+    pub fn dispatch(self) -> BoxFuture<'static, Result<Response, ClientError>> {
+        Box::pin(async move {
+            let (client, request) = self.builder.build_split();
+            let mut request = request?;
+            if self.offline {
+                return Err(ClientError::Offline(request.url().clone()));
+            }
+
+            // Wait for the request to be allowed before setting its timeout,
+            // so the timeout is the time left to a deadline once it is sent
+            let _permit = self.requests.acquire().await;
+
+            // Requests sent by an operation with a deadline time out at the
+            // deadline
+            if let Some(remaining) = deadline::remaining() {
+                if remaining.is_zero() {
+                    return Err(ClientError::DeadlineExceeded);
+                }
+
+                let timeout = request.timeout_mut();
+                *timeout = Some(timeout.map_or(remaining, |t| t.min(remaining)));
+            }
+
+            Next::new(&client, &self.middleware).run(request).await
+        })
+    }
+}
+
+/// Sends a request over the transport selected by the scheme of its URL.
+pub(crate) async fn send(
+    client: &reqwest::Client,
+    request: Request,
+) -> Result<Response, ClientError> {
+    let url = request.url().clone();
+    let local = match url.scheme() {
+        UNIX_SCHEME => None,
+        LOCAL_SCHEME => Some(
+            url.host_str()
+                .and_then(|name| LOCAL_SERVICES.lock().unwrap().get(name).cloned())
+                .ok_or_else(|| {
+                    ClientError::LocalTransport(anyhow!(
+                        "no in-process service is registered for URL `{url}`"
+                    ))
+                })?,
+        ),
+        _ => return Ok(client.execute(request).await?),
+    };
+
+    // The socket path or service name of the URL is not a valid URI
+    // authority, so requests are sent with just the path and query
+    let mut request = request;
+    let mut builder = http::Request::builder()
+        .method(request.method().clone())
+        .uri(&url[url::Position::BeforePath..url::Position::AfterQuery])
+        .version(request.version());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = std::mem::take(request.headers_mut());
+        headers.insert(HOST, HeaderValue::from_static("localhost"));
+    }
+    let request = builder
+        .body(
+            request
+                .body_mut()
+                .take()
+                .unwrap_or_else(|| Body::from(Bytes::new())),
+        )
+        .map_err(|e| ClientError::LocalTransport(anyhow!("invalid request: {e}")))?;
+
+    let response = match local {
+        Some(handler) => handler(request)
+            .await
+            .map_err(|e| ClientError::LocalTransport(anyhow!(e)))?,
+        None => send_unix(&url, request)
+            .await
+            .map_err(ClientError::LocalTransport)?,
+    };
+
+    Ok(Response::from(response))
+}
+
+/// Sends a request over the Unix domain socket of the given URL.
+#[cfg(unix)]
+async fn send_unix(url: &Url, request: http::Request<Body>) -> Result<http::Response<Body>> {
+    let path = unix_socket_path(url)?;
+    let stream = tokio::net::UnixStream::connect(&path)
+        .await
+        .with_context(|| {
+            format!(
+                "failed to connect to Unix domain socket `{path}`",
+                path = path.display()
+            )
+        })?;
+
+    let (mut sender, connection) =
+        hyper::client::conn::http1::handshake(hyper_util::rt::TokioIo::new(stream)).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::debug!("Unix domain socket connection failed: {e}");
+        }
+    });
+
+    Ok(sender.send_request(request).await?.map(into_body))
+}
+
+/// Sends a request over the Unix domain socket of the given URL.
+#[cfg(not(unix))]
+async fn send_unix(url: &Url, _request: http::Request<Body>) -> Result<http::Response<Body>> {
+    let path = unix_socket_path(url)?;
+    Err(anyhow!(
+        "cannot connect to Unix domain socket `{path}`: Unix domain sockets are not supported on this platform",
+        path = path.display()
+    ))
+}
+
+/// Converts the body of a response into a `reqwest` body.
+///
+/// The body is read by a separate task as `reqwest` requires response bodies
+/// to be `Sync`; the task uses the subscriber of the caller.
+fn into_body<B>(body: B) -> Body
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<Bytes, BoxError>>(1);
+    tokio::spawn(
+        async move {
+            let mut body = Box::pin(body.map_err(Into::<BoxError>::into));
+            while let Some(frame) = body.frame().await {
+                let data = match frame {
+                    Ok(frame) => match frame.into_data() {
+                        Ok(data) => Ok(data),
+                        // Trailers are not forwarded
+                        Err(_) => continue,
+                    },
+                    Err(e) => Err(e),
+                };
+
+                let failed = data.is_err();
+                if tx.send(data).await.is_err() || failed {
+                    break;
+                }
+            }
+        }
+        // The body of an in-process response may hold spans of the caller's
+        // subscriber
+        .with_current_subscriber(),
+    );
+
+    Body::wrap_stream(futures_util::stream::poll_fn(move |cx| rx.poll_recv(cx)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_socket_paths() {
+        let url = unix_socket_url("/tmp/warg registry.sock").unwrap();
+        assert_eq!(url.scheme(), UNIX_SCHEME);
+        assert_eq!(
+            unix_socket_path(&url.join("v1/fetch/logs").unwrap()).unwrap(),
+            Path::new("/tmp/warg registry.sock")
+        );
+    }
+}
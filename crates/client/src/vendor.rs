@@ -0,0 +1,288 @@
+//! A module for vendoring the locked dependencies of a project.
+//!
+//! A vendor directory holds the content of each release of a lockfile
+//! along with a manifest (`vendor.json`) mapping the name and version of
+//! each release to its file and digest. A vendor directory checked into a
+//! project allows it to be built without access to a registry.
+
+use super::{Client, ClientResult};
+use crate::{
+    lockfile::Lockfile,
+    storage::{ContentStorage, NamespaceMapStorage, RegistryDomain, RegistryStorage},
+};
+use anyhow::{bail, Context, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::registry::PackageName;
+
+/// The file name of a vendor manifest.
+pub const VENDOR_MANIFEST_NAME: &str = "vendor.json";
+
+/// The current version of the vendor manifest format.
+pub const VENDOR_MANIFEST_VERSION: u32 = 1;
+
+/// Represents the manifest of a vendor directory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct VendorManifest {
+    /// The version of the vendor manifest format.
+    pub version: u32,
+    /// The vendored packages, in the order of the lockfile.
+    #[serde(default)]
+    pub packages: Vec<VendoredPackage>,
+}
+
+impl Default for VendorManifest {
+    fn default() -> Self {
+        Self {
+            version: VENDOR_MANIFEST_VERSION,
+            packages: Default::default(),
+        }
+    }
+}
+
+/// A package release vendored into a vendor directory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct VendoredPackage {
+    /// The name of the package.
+    pub name: PackageName,
+    /// The vendored version of the package.
+    pub version: Version,
+    /// The domain of the registry the package was resolved from.
+    pub registry: String,
+    /// The content digest of the vendored release.
+    pub digest: AnyHash,
+    /// The path of the release's content relative to the vendor directory,
+    /// with `/` separated components.
+    pub path: String,
+}
+
+impl VendoredPackage {
+    /// Gets the path of the vendored content of a release relative to the
+    /// vendor directory.
+    fn content_path(name: &PackageName, version: &Version) -> String {
+        format!(
+            "{namespace}/{name}-{version}.wasm",
+            namespace = name.namespace(),
+            name = name.name()
+        )
+    }
+}
+
+impl VendorManifest {
+    /// Reads and validates the manifest of the given vendor directory.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(VENDOR_MANIFEST_NAME);
+        let contents = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "failed to read vendor manifest `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let manifest: Self = serde_json::from_str(&contents).with_context(|| {
+            format!("failed to deserialize file `{path}`", path = path.display())
+        })?;
+
+        manifest
+            .validate()
+            .with_context(|| format!("invalid vendor manifest `{path}`", path = path.display()))?;
+
+        Ok(manifest)
+    }
+
+    /// Validates and writes the manifest to the given vendor directory.
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let path = dir.as_ref().join(VENDOR_MANIFEST_NAME);
+        self.validate()?;
+
+        let mut contents = serde_json::to_string_pretty(self)
+            .with_context(|| format!("failed to serialize file `{path}`", path = path.display()))?;
+        contents.push('\n');
+
+        fs::write(&path, contents)
+            .with_context(|| format!("failed to write file `{path}`", path = path.display()))
+    }
+
+    /// Validates the manifest.
+    ///
+    /// Each package must be vendored once, at a path within the vendor
+    /// directory.
+    pub fn validate(&self) -> Result<()> {
+        if self.version != VENDOR_MANIFEST_VERSION {
+            bail!(
+                "unsupported vendor manifest version {version}",
+                version = self.version
+            );
+        }
+
+        for (i, package) in self.packages.iter().enumerate() {
+            if self.packages[..i].iter().any(|p| p.name == package.name) {
+                bail!(
+                    "package `{name}` is vendored more than once",
+                    name = package.name
+                );
+            }
+
+            if package.path.starts_with('/')
+                || package
+                    .path
+                    .split(['/', '\\'])
+                    .any(|c| c.is_empty() || c == "." || c == ".." || c.contains(':'))
+            {
+                bail!(
+                    "package `{name}` is vendored at invalid path `{path}`",
+                    name = package.name,
+                    path = package.path
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the vendored release of the given package.
+    pub fn package(&self, name: &PackageName) -> Option<&VendoredPackage> {
+        self.packages.iter().find(|p| &p.name == name)
+    }
+
+    /// Verifies that the content of each vendored release in the given
+    /// vendor directory matches its digest.
+    pub fn verify(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        for package in &self.packages {
+            let path = dir.join(&package.path);
+            let contents = fs::read(&path)
+                .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+            let digest = package.digest.algorithm().digest(&contents);
+            if digest != package.digest {
+                bail!(
+                    "vendored content of package `{name}` has digest `{digest}` but a digest of `{expected}` was expected",
+                    name = package.name,
+                    expected = package.digest
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Copies the content of each release of a lockfile into the given
+    /// vendor directory and writes its manifest.
+    ///
+    /// Content not in client storage is downloaded from the registry the
+    /// release was locked to and verified against its locked digest.
+    ///
+    /// Files of a previous manifest of the directory that are no longer
+    /// vendored are removed.
+    pub async fn vendor(
+        &self,
+        lockfile: &Lockfile,
+        dir: impl AsRef<Path>,
+    ) -> ClientResult<VendorManifest> {
+        let dir = dir.as_ref();
+        lockfile.validate()?;
+
+        let previous = if dir.join(VENDOR_MANIFEST_NAME).is_file() {
+            Some(VendorManifest::from_dir(dir)?)
+        } else {
+            None
+        };
+
+        let home = self.url().registry_domain();
+        let mut manifest = VendorManifest::default();
+        for package in &lockfile.packages {
+            let registry_domain = RegistryDomain::new(package.registry.clone());
+            let source = self
+                .download_content(
+                    (registry_domain != home).then_some(&registry_domain),
+                    &package.digest,
+                )
+                .await?;
+
+            let vendored = VendoredPackage {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                registry: package.registry.clone(),
+                digest: package.digest.clone(),
+                path: VendoredPackage::content_path(&package.name, &package.version),
+            };
+            let path = dir.join(&vendored.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!(
+                        "failed to create directory `{path}`",
+                        path = parent.display()
+                    )
+                })?;
+            }
+            fs::copy(&source, &path).with_context(|| {
+                format!(
+                    "failed to copy `{source}` to `{path}`",
+                    source = source.display(),
+                    path = path.display()
+                )
+            })?;
+
+            manifest.packages.push(vendored);
+        }
+
+        for stale in previous.iter().flat_map(|p| &p.packages) {
+            if !manifest.packages.iter().any(|p| p.path == stale.path) {
+                let path = dir.join(&stale.path);
+                if path.is_file() {
+                    fs::remove_file(&path).with_context(|| {
+                        format!("failed to remove file `{path}`", path = path.display())
+                    })?;
+                }
+            }
+        }
+
+        manifest.write_to_dir(dir)?;
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warg_crypto::hash::HashAlgorithm;
+
+    #[test]
+    fn it_verifies_vendored_content() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let name = PackageName::new("test:app")?;
+        let version: Version = "1.0.0".parse()?;
+        let path = VendoredPackage::content_path(&name, &version);
+        assert_eq!(path, "test/app-1.0.0.wasm");
+
+        fs::create_dir_all(dir.path().join("test"))?;
+        fs::write(dir.path().join(&path), b"content")?;
+        let manifest = VendorManifest {
+            packages: vec![VendoredPackage {
+                name,
+                version,
+                registry: "registry.example.com".to_string(),
+                digest: HashAlgorithm::Sha256.digest(b"content"),
+                path,
+            }],
+            ..Default::default()
+        };
+        manifest.write_to_dir(dir.path())?;
+        assert_eq!(VendorManifest::from_dir(dir.path())?, manifest);
+        manifest.verify(dir.path())?;
+
+        fs::write(dir.path().join("test/app-1.0.0.wasm"), b"tampered")?;
+        assert!(manifest.verify(dir.path()).is_err());
+
+        let mut escaping = manifest.clone();
+        escaping.packages[0].path = "../app.wasm".to_string();
+        assert!(escaping.validate().is_err());
+        Ok(())
+    }
+}
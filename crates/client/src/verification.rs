@@ -0,0 +1,833 @@
+//! A module for producing verification reports of package logs.
+//!
+//! A verification report documents the evidence a client used to trust a
+//! package log: the key that signed each record, the checkpoints that cover
+//! the records, and the results of the signature and proof checks.
+//!
+//! When the client has a [`ProvenancePolicy`], the report also documents
+//! the verification of each release's provenance.
+//!
+//! A closure report documents the verification of every package of a
+//! lockfile, and is signed so it may be attached to release builds.
+
+use super::{
+    api::{self, ConsistencyRequest, FetchError, FetchLogsRequest, InclusionRequest},
+    Client, ClientError, ClientResult,
+};
+use crate::{
+    lockfile::{LockedPackage, LockedRegistry, Lockfile},
+    provenance::{ProvenancePolicy, PROVENANCE_KIND},
+    storage::{ContentStorage, NamespaceMapStorage, RegistryDomain, RegistryStorage},
+};
+use anyhow::anyhow;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use indexmap::IndexMap;
+use semver::Version;
+use serde::Serialize;
+use std::{borrow::Cow, cmp::Ordering, fs, time::SystemTime};
+use warg_crypto::{
+    hash::{AnyHash, Sha256},
+    prefix::{self, VisitPrefixEncode},
+    signing, ByteVisitor, Signable, VisitBytes,
+};
+use warg_protocol::{
+    operator, package,
+    registry::{
+        Checkpoint, LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen,
+        TimestampedCheckpoint,
+    },
+    verify, PublishedProtoEnvelope, SerdeEnvelope,
+};
+
+/// The maximum number of packages of a lockfile verified concurrently.
+const MAX_CONCURRENT_CLOSURE_VERIFICATIONS: usize = 8;
+
+/// The outcome of a verification check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "status", content = "reason")]
+pub enum CheckOutcome {
+    /// The check succeeded.
+    Verified,
+    /// The check failed for the given reason.
+    Failed(String),
+    /// The check was not performed for the given reason.
+    Skipped(String),
+}
+
+impl CheckOutcome {
+    /// Determines if the check succeeded.
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Self::Verified)
+    }
+}
+
+/// An anomaly found while verifying a package log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum VerificationAnomaly {
+    /// A record failed validation against the preceding records of its log.
+    #[serde(rename_all = "camelCase")]
+    InvalidRecord {
+        /// The id of the invalid record.
+        record_id: RecordId,
+        /// The reason the record is invalid.
+        reason: String,
+    },
+    /// The signature of a checkpoint could not be verified.
+    #[serde(rename_all = "camelCase")]
+    InvalidCheckpointSignature {
+        /// The log length of the checkpoint.
+        log_length: RegistryLen,
+        /// The reason the signature could not be verified.
+        reason: String,
+    },
+    /// The head of the package log could not be proven to be included in the
+    /// latest checkpoint.
+    #[serde(rename_all = "camelCase")]
+    InclusionProofFailed {
+        /// The reason the proof failed.
+        reason: String,
+    },
+    /// The checkpoint in client storage is not consistent with the latest
+    /// checkpoint.
+    #[serde(rename_all = "camelCase")]
+    InconsistentCheckpoint {
+        /// The log length of the checkpoint in client storage.
+        from: RegistryLen,
+        /// The log length of the latest checkpoint.
+        to: RegistryLen,
+        /// The reason the checkpoints are inconsistent.
+        reason: String,
+    },
+    /// The package log in client storage has a head that is not part of the
+    /// log served by the registry.
+    #[serde(rename_all = "camelCase")]
+    StoredHeadMismatch {
+        /// The head record id of the package log in client storage.
+        record_id: RecordId,
+    },
+    /// The provenance of a release could not be verified against the
+    /// client's provenance policy.
+    #[serde(rename_all = "camelCase")]
+    ProvenanceNotVerified {
+        /// The version of the release.
+        version: Version,
+        /// The reason the provenance could not be verified.
+        reason: String,
+    },
+}
+
+/// The verification of a checkpoint.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointVerification {
+    /// The checkpoint.
+    pub checkpoint: Checkpoint,
+    /// The timestamp of the checkpoint.
+    pub timestamp: u64,
+    /// The operator key that signed the checkpoint.
+    pub key_id: signing::KeyID,
+    /// The outcome of verifying the checkpoint's signature.
+    pub signature: CheckOutcome,
+    /// Whether or not the checkpoint is the latest checkpoint of the registry.
+    ///
+    /// Otherwise, the checkpoint was loaded from client storage.
+    pub latest: bool,
+}
+
+/// The verification of a package record.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordVerification {
+    /// The id of the record.
+    pub record_id: RecordId,
+    /// The index of the record in the registry log.
+    pub registry_index: RegistryIndex,
+    /// The key that signed the record.
+    pub key_id: signing::KeyID,
+    /// The timestamp of the record.
+    pub timestamp: SystemTime,
+    /// The outcome of validating the record, including its signature.
+    pub validation: CheckOutcome,
+    /// The log lengths of the report's checkpoints that cover the record.
+    pub covered_by: Vec<RegistryLen>,
+}
+
+/// The verification of the provenance of a release.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenanceVerification {
+    /// The version of the release.
+    pub version: Version,
+    /// The id of the record that attached the verified provenance
+    /// attestation, if any.
+    pub record_id: Option<RecordId>,
+    /// The certificate identity that signed the verified provenance (e.g.
+    /// the URI of a GitHub Actions workflow), if any.
+    pub identity: Option<String>,
+    /// The outcome of verifying the provenance.
+    pub outcome: CheckOutcome,
+}
+
+/// A report of the verification of a package log.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    /// The name of the package.
+    pub package: PackageName,
+    /// The id of the package log.
+    pub log_id: LogId,
+    /// The registry the package log was fetched from; `None` for the
+    /// client's default registry.
+    pub registry: Option<RegistryDomain>,
+    /// The checkpoints used for verification, starting with the latest.
+    pub checkpoints: Vec<CheckpointVerification>,
+    /// The records of the package log, in log order.
+    pub records: Vec<RecordVerification>,
+    /// The outcome of proving inclusion of the package log head in the
+    /// latest checkpoint.
+    ///
+    /// As each record contains the id of the previous record, this proof
+    /// also covers the records before the head.
+    pub inclusion: CheckOutcome,
+    /// The outcome of proving the checkpoint in client storage is
+    /// consistent with the latest checkpoint.
+    pub consistency: CheckOutcome,
+    /// The provenance verification of each release, in log order.
+    ///
+    /// This is empty if the client has no provenance policy.
+    pub provenance: Vec<ProvenanceVerification>,
+    /// The anomalies found during verification.
+    pub anomalies: Vec<VerificationAnomaly>,
+}
+
+impl VerificationReport {
+    /// Determines if the package log was verified without any anomalies.
+    pub fn is_verified(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+
+    /// Gets the ids of the records signed by each key, in log order.
+    pub fn signers(&self) -> IndexMap<&signing::KeyID, Vec<&RecordId>> {
+        let mut signers: IndexMap<_, Vec<_>> = IndexMap::new();
+        for record in &self.records {
+            signers
+                .entry(&record.key_id)
+                .or_default()
+                .push(&record.record_id);
+        }
+        signers
+    }
+}
+
+/// The verification of the checkpoint a registry of a lockfile was locked
+/// at.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockedRegistryVerification {
+    /// The domain of the registry, as named by the lockfile.
+    pub registry: String,
+    /// The checkpoint the registry was locked at.
+    pub locked: LockedRegistry,
+    /// The latest checkpoint of the registry.
+    pub latest: Checkpoint,
+    /// The outcome of proving the locked checkpoint is consistent with the
+    /// latest checkpoint.
+    pub consistency: CheckOutcome,
+}
+
+/// The verification of a package of a lockfile.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockedPackageVerification {
+    /// The locked package.
+    #[serde(flatten)]
+    pub locked: LockedPackage,
+    /// The verification report of the package log; `None` if the package
+    /// log does not exist.
+    pub log: Option<VerificationReport>,
+    /// The outcome of checking the locked release against the validated
+    /// package log.
+    pub release: CheckOutcome,
+    /// The outcome of checking the locked content against its digest.
+    pub content: CheckOutcome,
+}
+
+impl LockedPackageVerification {
+    /// Determines if the locked package was verified.
+    pub fn is_verified(&self) -> bool {
+        self.log
+            .as_ref()
+            .is_some_and(VerificationReport::is_verified)
+            && self.release.is_verified()
+            && self.content.is_verified()
+    }
+}
+
+/// A report of the verification of the dependency closure of a lockfile.
+///
+/// The report is signed by [`Client::verify_closure`]; the signature covers
+/// the compact JSON serialization of the report.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosureReport {
+    /// The time the closure was verified.
+    pub timestamp: SystemTime,
+    /// The verification of each registry of the lockfile.
+    pub registries: Vec<LockedRegistryVerification>,
+    /// The verification of each package of the lockfile, in lockfile
+    /// order.
+    pub packages: Vec<LockedPackageVerification>,
+}
+
+impl ClosureReport {
+    /// Determines if every registry and package of the lockfile was
+    /// verified.
+    pub fn is_verified(&self) -> bool {
+        self.registries.iter().all(|r| r.consistency.is_verified())
+            && self
+                .packages
+                .iter()
+                .all(LockedPackageVerification::is_verified)
+    }
+}
+
+impl Signable for ClosureReport {
+    const PREFIX: &'static [u8] = b"WARG-CLOSURE-REPORT-SIGNATURE-V0";
+}
+
+impl VisitPrefixEncode for ClosureReport {
+    fn visit_pe<BV: ?Sized + ByteVisitor>(&self, visitor: &mut prefix::PrefixEncodeVisitor<BV>) {
+        visitor.visit_str_raw("WARG-CLOSURE-REPORT-V0");
+        visitor.visit_str(
+            &serde_json::to_string(self).expect("closure report should serialize to JSON"),
+        );
+    }
+}
+
+// Manual impls of VisitBytes for VisitPrefixEncode to avoid conflict with blanket impls
+impl VisitBytes for ClosureReport {
+    fn visit<BV: ?Sized + ByteVisitor>(&self, visitor: &mut BV) {
+        self.visit_bv(visitor);
+    }
+}
+
+impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Produces a verification report for the given package.
+    ///
+    /// The operator log and package log are fetched from the registry
+    /// responsible for the package and validated from the start; client
+    /// storage is not updated.
+    ///
+    /// If the client has a provenance policy, the provenance attestations
+    /// of each release are downloaded into content storage and verified
+    /// against the policy; a release without verified provenance is an
+    /// anomaly.
+    ///
+    /// Failed checks are reported as anomalies rather than returned as
+    /// errors; an error is only returned if the registry could not be
+    /// queried.
+    pub async fn verification_report(
+        &self,
+        package: &PackageName,
+    ) -> ClientResult<VerificationReport> {
+        let registry_domain = self.get_warg_registry(package.namespace()).await?;
+        let (report, _) = self
+            .verify_package_log(registry_domain.as_ref(), package)
+            .await?;
+        Ok(report)
+    }
+
+    /// Verifies the dependency closure of a lockfile and signs the report
+    /// of the verification with the given key.
+    ///
+    /// Every locked package is verified in parallel from the registry it
+    /// was locked from: its package log is validated and proven as with
+    /// [`Client::verification_report`], the locked release is checked
+    /// against the validated log, and the locked content is downloaded
+    /// into content storage and hashed again. The checkpoint each registry
+    /// was locked at is proven consistent with its latest checkpoint.
+    ///
+    /// The signed report is suitable for attaching to release builds.
+    /// Failed checks are reported rather than returned as errors; an error
+    /// is only returned if the lockfile is invalid or a registry could not
+    /// be queried.
+    pub async fn verify_closure(
+        &self,
+        lockfile: &Lockfile,
+        signing_key: &signing::PrivateKey,
+    ) -> ClientResult<SerdeEnvelope<ClosureReport>> {
+        lockfile.validate()?;
+
+        // Lockfiles name the default registry by its domain
+        let home = self.url().registry_domain();
+        let registry_domain = |registry: &str| {
+            (registry != home.as_str()).then(|| RegistryDomain::new(registry.to_string()))
+        };
+
+        let registries = stream::iter(&lockfile.registries)
+            .map(|(registry, locked)| {
+                self.verify_locked_registry(registry_domain(registry), registry, locked)
+            })
+            .buffered(MAX_CONCURRENT_CLOSURE_VERIFICATIONS)
+            .try_collect()
+            .await?;
+
+        let packages = stream::iter(&lockfile.packages)
+            .map(|locked| self.verify_locked_package(registry_domain(&locked.registry), locked))
+            .buffered(MAX_CONCURRENT_CLOSURE_VERIFICATIONS)
+            .try_collect()
+            .await?;
+
+        let report = ClosureReport {
+            timestamp: SystemTime::now(),
+            registries,
+            packages,
+        };
+
+        Ok(SerdeEnvelope::signed_contents(signing_key, report).map_err(|e| anyhow!(e))?)
+    }
+
+    /// Verifies a package log from the given registry.
+    ///
+    /// Returns the verification report and the validated state of the
+    /// package log, if every record is valid.
+    async fn verify_package_log(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        package: &PackageName,
+    ) -> ClientResult<(VerificationReport, Option<package::LogState>)> {
+        let log_id = LogId::package_log::<Sha256>(package);
+
+        let latest = self.api.latest_checkpoint(registry_domain).await?;
+        let log_length = latest.as_ref().checkpoint.log_length;
+        let stored = self
+            .registry
+            .load_checkpoint(registry_domain)
+            .await?
+            .filter(|c| c.as_ref() != latest.as_ref());
+
+        let (operator_records, package_records) =
+            fetch_records(&self.api, registry_domain, log_length, &log_id)
+                .await
+                .map_err(|e| match e {
+                    ClientError::Api(api::ClientError::Fetch(FetchError::LogNotFound(_)))
+                    | ClientError::Api(api::ClientError::LogNotFoundWithHint(..)) => {
+                        ClientError::PackageDoesNotExist {
+                            name: package.clone(),
+                            has_auth_token: self.api.auth_token().is_some(),
+                        }
+                    }
+                    e => e,
+                })?;
+
+        let mut report = VerificationReport {
+            package: package.clone(),
+            log_id: log_id.clone(),
+            registry: registry_domain.cloned(),
+            checkpoints: Vec::new(),
+            records: Vec::new(),
+            inclusion: CheckOutcome::Skipped("the package log was not validated".into()),
+            consistency: CheckOutcome::Skipped("no checkpoint is in client storage".into()),
+            provenance: Vec::new(),
+            anomalies: Vec::new(),
+        };
+
+        // The operator log provides the keys that sign checkpoints
+        let mut operator = operator::LogState::default();
+        let mut operator_head = None;
+        for envelope in &operator_records {
+            match operator.validate(&envelope.envelope) {
+                Ok(state) => {
+                    operator = state;
+                    operator_head = Some(envelope.registry_index);
+                }
+                Err(e) => {
+                    report.anomalies.push(VerificationAnomaly::InvalidRecord {
+                        record_id: RecordId::operator_record::<Sha256>(&envelope.envelope),
+                        reason: format!("invalid operator record: {e}"),
+                    });
+                    operator = operator::LogState::default();
+                    break;
+                }
+            }
+        }
+
+        for (checkpoint, latest) in [(Some(&latest), true), (stored.as_ref(), false)] {
+            let Some(checkpoint) = checkpoint else {
+                continue;
+            };
+            let verification = verify_checkpoint(&operator, checkpoint, latest);
+            if let CheckOutcome::Failed(reason) = &verification.signature {
+                report
+                    .anomalies
+                    .push(VerificationAnomaly::InvalidCheckpointSignature {
+                        log_length: verification.checkpoint.log_length,
+                        reason: reason.clone(),
+                    });
+            }
+            report.checkpoints.push(verification);
+        }
+
+        let mut state = Some(package::LogState::default());
+        let mut package_head = None;
+        for envelope in &package_records {
+            let record_id = RecordId::package_record::<Sha256>(&envelope.envelope);
+            let validation = match state.take().map(|s| s.validate(&envelope.envelope)) {
+                Some(Ok(s)) => {
+                    state = Some(s);
+                    package_head = Some((envelope.registry_index, record_id.clone()));
+                    CheckOutcome::Verified
+                }
+                Some(Err(e)) => {
+                    report.anomalies.push(VerificationAnomaly::InvalidRecord {
+                        record_id: record_id.clone(),
+                        reason: e.to_string(),
+                    });
+                    CheckOutcome::Failed(e.to_string())
+                }
+                None => CheckOutcome::Skipped("a previous record is invalid".into()),
+            };
+
+            report.records.push(RecordVerification {
+                record_id,
+                registry_index: envelope.registry_index,
+                key_id: envelope.envelope.key_id().clone(),
+                timestamp: envelope.envelope.as_ref().timestamp,
+                validation,
+                covered_by: report
+                    .checkpoints
+                    .iter()
+                    .map(|c| c.checkpoint.log_length)
+                    .filter(|len| envelope.registry_index < *len)
+                    .collect(),
+            });
+        }
+
+        if let (Some(policy), Some(state)) = (&self.provenance_policy, &state) {
+            for release in state.releases() {
+                let Some(content) = release.content() else {
+                    continue;
+                };
+                let verification = self
+                    .verify_provenance(registry_domain, policy, state, &release.version, content)
+                    .await?;
+                if let CheckOutcome::Failed(reason) = &verification.outcome {
+                    report
+                        .anomalies
+                        .push(VerificationAnomaly::ProvenanceNotVerified {
+                            version: release.version.clone(),
+                            reason: reason.clone(),
+                        });
+                }
+                report.provenance.push(verification);
+            }
+        }
+
+        if let Some(stored) = self.registry.load_package(registry_domain, package).await? {
+            if let Some(head) = stored.state.head() {
+                if !report.records.iter().any(|r| r.record_id == head.digest) {
+                    report
+                        .anomalies
+                        .push(VerificationAnomaly::StoredHeadMismatch {
+                            record_id: head.digest.clone(),
+                        });
+                }
+            }
+        }
+
+        // Prove inclusion of the operator and package log heads
+        let package_head = state.as_ref().and(package_head);
+        if let (Some((package_index, package_record)), Some(operator_index)) =
+            (package_head, operator_head)
+        {
+            let checkpoint = &latest.as_ref().checkpoint;
+            let leafs = [
+                LogLeaf {
+                    log_id: LogId::operator_log::<Sha256>(),
+                    record_id: operator.head().as_ref().unwrap().digest.clone(),
+                },
+                LogLeaf {
+                    log_id: log_id.clone(),
+                    record_id: package_record,
+                },
+            ];
+
+            report.inclusion = match self
+                .api
+                .prove_inclusion(
+                    registry_domain,
+                    InclusionRequest {
+                        log_length,
+                        leafs: vec![operator_index, package_index],
+                    },
+                    checkpoint,
+                    &leafs,
+                )
+                .await
+            {
+                Ok(()) => CheckOutcome::Verified,
+                Err(e) => {
+                    report
+                        .anomalies
+                        .push(VerificationAnomaly::InclusionProofFailed {
+                            reason: e.to_string(),
+                        });
+                    CheckOutcome::Failed(e.to_string())
+                }
+            };
+        }
+
+        if let Some(stored) = &stored {
+            let from = &stored.as_ref().checkpoint;
+            let to = &latest.as_ref().checkpoint;
+            let result = match from.log_length.cmp(&to.log_length) {
+                Ordering::Greater => {
+                    Err("the stored checkpoint is ahead of the latest".to_string())
+                }
+                // The registry may sign the same checkpoint again with a new timestamp
+                Ordering::Equal if from == to => Ok(()),
+                Ordering::Equal => Err("the log root or map root has changed".to_string()),
+                Ordering::Less => self
+                    .api
+                    .prove_log_consistency(
+                        registry_domain,
+                        ConsistencyRequest {
+                            from: from.log_length,
+                            to: to.log_length,
+                        },
+                        Cow::Borrowed(&from.log_root),
+                        Cow::Borrowed(&to.log_root),
+                    )
+                    .await
+                    .map_err(|e| e.to_string()),
+            };
+
+            report.consistency = match result {
+                Ok(()) => CheckOutcome::Verified,
+                Err(reason) => {
+                    report
+                        .anomalies
+                        .push(VerificationAnomaly::InconsistentCheckpoint {
+                            from: from.log_length,
+                            to: to.log_length,
+                            reason: reason.clone(),
+                        });
+                    CheckOutcome::Failed(reason)
+                }
+            };
+        }
+
+        Ok((report, state))
+    }
+
+    /// Proves the checkpoint a registry of a lockfile was locked at is
+    /// consistent with the latest checkpoint of the registry.
+    async fn verify_locked_registry(
+        &self,
+        registry_domain: Option<RegistryDomain>,
+        registry: &str,
+        locked: &LockedRegistry,
+    ) -> ClientResult<LockedRegistryVerification> {
+        let registry_domain = registry_domain.as_ref();
+        let latest = self.api.latest_checkpoint(registry_domain).await?;
+        let to = &latest.as_ref().checkpoint;
+        let result = match locked.log_length.cmp(&to.log_length) {
+            Ordering::Greater => Err("the locked checkpoint is ahead of the latest".to_string()),
+            Ordering::Equal if locked == &LockedRegistry::from(to) => Ok(()),
+            Ordering::Equal => Err("the log root or map root has changed".to_string()),
+            Ordering::Less => self
+                .api
+                .prove_log_consistency(
+                    registry_domain,
+                    ConsistencyRequest {
+                        from: locked.log_length,
+                        to: to.log_length,
+                    },
+                    Cow::Borrowed(&locked.log_root),
+                    Cow::Borrowed(&to.log_root),
+                )
+                .await
+                .map_err(|e| e.to_string()),
+        };
+
+        Ok(LockedRegistryVerification {
+            registry: registry.to_string(),
+            locked: locked.clone(),
+            latest: to.clone(),
+            consistency: match result {
+                Ok(()) => CheckOutcome::Verified,
+                Err(reason) => CheckOutcome::Failed(reason),
+            },
+        })
+    }
+
+    /// Verifies a package of a lockfile against its package log and
+    /// content.
+    async fn verify_locked_package(
+        &self,
+        registry_domain: Option<RegistryDomain>,
+        locked: &LockedPackage,
+    ) -> ClientResult<LockedPackageVerification> {
+        let registry_domain = registry_domain.as_ref();
+        let (log, state) = match self.verify_package_log(registry_domain, &locked.name).await {
+            Ok((report, state)) => (Some(report), state),
+            Err(ClientError::PackageDoesNotExist { .. }) => (None, None),
+            Err(e) => return Err(e),
+        };
+
+        let release = match (&log, &state) {
+            (None, _) => CheckOutcome::Failed("the package log does not exist".into()),
+            (Some(_), None) => CheckOutcome::Failed("the package log is invalid".into()),
+            (Some(_), Some(state)) => match state
+                .release(&locked.version)
+                .map(|release| release.content())
+            {
+                Some(Some(digest)) if digest == &locked.digest => CheckOutcome::Verified,
+                Some(Some(digest)) => {
+                    CheckOutcome::Failed(format!("the release has content digest `{digest}`"))
+                }
+                Some(None) => CheckOutcome::Failed("the release has been yanked".into()),
+                None => CheckOutcome::Failed(format!(
+                    "release `{version}` is not in the package log",
+                    version = locked.version
+                )),
+            },
+        };
+
+        let content = match self.download_content(registry_domain, &locked.digest).await {
+            Ok(_) => match self.hash_content(&locked.digest).await {
+                Some(actual) if actual == locked.digest => CheckOutcome::Verified,
+                Some(actual) => CheckOutcome::Failed(format!("the content has digest `{actual}`")),
+                None => CheckOutcome::Skipped("the content could not be hashed".into()),
+            },
+            Err(e) => CheckOutcome::Failed(e.to_string()),
+        };
+
+        Ok(LockedPackageVerification {
+            locked: locked.clone(),
+            log,
+            release,
+            content,
+        })
+    }
+}
+
+impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
+    /// Verifies the provenance attestations of a release until one is
+    /// verified.
+    async fn verify_provenance(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        policy: &ProvenancePolicy,
+        state: &package::LogState,
+        version: &Version,
+        content: &AnyHash,
+    ) -> ClientResult<ProvenanceVerification> {
+        let mut verification = ProvenanceVerification {
+            version: version.clone(),
+            record_id: None,
+            identity: None,
+            outcome: CheckOutcome::Failed("the release has no provenance attestation".into()),
+        };
+
+        for attestation in state
+            .attestations(version)
+            .iter()
+            .filter(|a| a.kind == PROVENANCE_KIND)
+        {
+            let path = self
+                .download_content(registry_domain, &attestation.content)
+                .await?;
+            let bundle = fs::read(&path).map_err(ClientError::IoError)?;
+            match policy.verify(&bundle, content, attestation.timestamp) {
+                Ok(identity) => {
+                    verification.record_id = Some(attestation.record_id.clone());
+                    verification.identity = Some(identity);
+                    verification.outcome = CheckOutcome::Verified;
+                    break;
+                }
+                Err(e) => verification.outcome = CheckOutcome::Failed(format!("{e:#}")),
+            }
+        }
+
+        Ok(verification)
+    }
+}
+
+fn verify_checkpoint(
+    operator: &operator::LogState,
+    checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    latest: bool,
+) -> CheckpointVerification {
+    let key_id = checkpoint.key_id().clone();
+    let signature = match operator.public_key(&key_id) {
+        Some(key) => match verify::verify_checkpoint(checkpoint, key) {
+            Ok(()) => CheckOutcome::Verified,
+            Err(e) => CheckOutcome::Failed(e.to_string()),
+        },
+        None => CheckOutcome::Failed(format!(
+            "key id `{key_id}` is not known to the operator log"
+        )),
+    };
+
+    CheckpointVerification {
+        checkpoint: checkpoint.as_ref().checkpoint.clone(),
+        timestamp: checkpoint.as_ref().timestamp,
+        key_id,
+        signature,
+        latest,
+    }
+}
+
+type Records<T> = Vec<PublishedProtoEnvelope<T>>;
+
+/// Fetches all operator records and the records of a package log as of the
+/// given log length.
+async fn fetch_records(
+    client: &api::Client,
+    registry_domain: Option<&RegistryDomain>,
+    log_length: RegistryLen,
+    log_id: &LogId,
+) -> Result<
+    (
+        Records<operator::OperatorRecord>,
+        Records<package::PackageRecord>,
+    ),
+    ClientError,
+> {
+    let mut operator_token: Option<String> = None;
+    let mut package_tokens = IndexMap::from([(log_id.clone(), None)]);
+
+    let mut operator = Vec::new();
+    let mut packages = Vec::new();
+    loop {
+        let response = client
+            .fetch_logs(
+                registry_domain,
+                FetchLogsRequest {
+                    log_length,
+                    operator: operator_token.as_deref().map(Cow::Borrowed),
+                    limit: None,
+                    packages: Cow::Borrowed(&package_tokens),
+                },
+            )
+            .await?;
+
+        for record in response.operator {
+            operator.push(record.envelope.try_into()?);
+            operator_token = Some(record.fetch_token);
+        }
+
+        for record in response.packages.into_values().flatten() {
+            packages.push(record.envelope.try_into()?);
+            package_tokens.insert(log_id.clone(), Some(record.fetch_token));
+        }
+
+        if !response.more {
+            break;
+        }
+    }
+
+    Ok((operator, packages))
+}
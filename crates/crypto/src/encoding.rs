@@ -32,12 +32,25 @@ where
 pub trait Signable: Encode {
     const PREFIX: &'static [u8];
 
+    /// Gets the exact bytes that are signed for this value.
+    ///
+    /// This is the signature prefix of the type, followed by `:` and the
+    /// encoding of the value.
+    fn signable_bytes(&self) -> Vec<u8> {
+        Self::prefixed(&self.encode())
+    }
+
+    /// Prefixes the given encoded content with the signature prefix of the
+    /// type.
+    fn prefixed(content: &[u8]) -> Vec<u8> {
+        [Self::PREFIX, b":", content].concat()
+    }
+
     fn sign(
         &self,
         private_key: &signing::PrivateKey,
     ) -> Result<signing::Signature, SignatureError> {
-        let prefixed_content = [Self::PREFIX, b":", self.encode().as_slice()].concat();
-        private_key.sign(&prefixed_content)
+        private_key.sign(&self.signable_bytes())
     }
 
     fn verify(
@@ -45,7 +58,6 @@ pub trait Signable: Encode {
         msg: &[u8],
         signature: &signing::Signature,
     ) -> Result<(), SignatureError> {
-        let prefixed_content = [Self::PREFIX, b":", msg].concat();
-        public_key.verify(&prefixed_content, signature)
+        public_key.verify(&Self::prefixed(msg), signature)
     }
 }
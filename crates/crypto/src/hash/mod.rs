@@ -8,7 +8,7 @@ mod dynamic;
 mod r#static;
 
 pub use digest::{Digest, Output};
-pub use dynamic::{AnyHash, AnyHashError};
+pub use dynamic::{AnyHash, AnyHashError, Hasher};
 pub use r#static::Hash;
 pub use sha2::Sha256;
 
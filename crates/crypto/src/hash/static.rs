@@ -121,7 +121,7 @@ impl<D: SupportedDigest> TryFrom<Vec<u8>> for Hash<D> {
 
     fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
         let hash = Hash {
-            digest: GenericArray::from_exact_iter(value.into_iter()).ok_or(IncorrectLengthError)?,
+            digest: GenericArray::from_exact_iter(value).ok_or(IncorrectLengthError)?,
         };
         Ok(hash)
     }
@@ -0,0 +1,655 @@
+//! Module for extracting the interfaces of package content.
+//!
+//! The imported and exported interfaces of a component, or the interfaces
+//! defined by a binary-encoded WIT package, are decoded into
+//! [`ContentInterfaces`], which records a rendered signature of each
+//! function and type so that two releases can be compared.
+use anyhow::{Context, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+use wit_component::DecodedWasm;
+use wit_parser::{
+    Function, Handle, Resolve, Results, Type, TypeDefKind, TypeId, WorldItem, WorldKey,
+};
+
+/// Represents the signatures of the functions and types of an interface.
+///
+/// Signatures are rendered in a WIT-like syntax, so that two signatures are
+/// equal when the function or type is structurally unchanged.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceSignature {
+    /// The definitions of the types of the interface, keyed by type name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub types: BTreeMap<String, String>,
+    /// The signatures of the functions of the interface, keyed by function
+    /// name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub functions: BTreeMap<String, String>,
+}
+
+/// Represents the interfaces imported and exported by package content.
+///
+/// Functions and types imported or exported directly by a component are
+/// represented as an interface named after the item.
+///
+/// The interfaces defined by a WIT package are represented as exports.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentInterfaces {
+    /// The imported interfaces, keyed by interface name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub imports: BTreeMap<String, InterfaceSignature>,
+    /// The exported interfaces, keyed by interface name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub exports: BTreeMap<String, InterfaceSignature>,
+}
+
+impl ContentInterfaces {
+    /// Extracts the interfaces of the given content.
+    ///
+    /// Content that is not a WebAssembly component, such as a core module,
+    /// yields no interfaces.
+    ///
+    /// A component that cannot be decoded is an error.
+    pub fn from_content(bytes: &[u8]) -> Result<Self> {
+        if !wasmparser::Parser::is_component(bytes) {
+            return Ok(Self::default());
+        }
+
+        let mut interfaces = Self::default();
+        match wit_component::decode(bytes).context("failed to decode component interfaces")? {
+            DecodedWasm::Component(resolve, world) => {
+                let world = &resolve.worlds[world];
+                for (key, item) in &world.imports {
+                    interfaces
+                        .imports
+                        .insert(item_name(&resolve, key, item), signature(&resolve, item));
+                }
+                for (key, item) in &world.exports {
+                    interfaces
+                        .exports
+                        .insert(item_name(&resolve, key, item), signature(&resolve, item));
+                }
+            }
+            DecodedWasm::WitPackage(resolve, package) => {
+                for id in resolve.packages[package].interfaces.values() {
+                    let item = WorldItem::Interface(*id);
+                    let name = resolve
+                        .id_of(*id)
+                        .context("invalid WIT package: interface is missing a name")?;
+                    interfaces.exports.insert(name, signature(&resolve, &item));
+                }
+            }
+        }
+
+        Ok(interfaces)
+    }
+
+    /// Gets the breaking changes of newer content that are incompatible with
+    /// the version bump between the two releases.
+    ///
+    /// No changes are incompatible if the version bump allows breaking
+    /// changes; see [`allows_breaking_changes`].
+    pub fn incompatible_changes(
+        &self,
+        version: &Version,
+        newer: &Self,
+        newer_version: &Version,
+    ) -> Vec<InterfaceChange> {
+        if allows_breaking_changes(version, newer_version) {
+            return Vec::new();
+        }
+
+        self.diff(newer)
+            .into_iter()
+            .filter(InterfaceChange::is_breaking)
+            .collect()
+    }
+
+    /// Determines if the content has no interfaces.
+    pub fn is_empty(&self) -> bool {
+        self.imports.is_empty() && self.exports.is_empty()
+    }
+
+    /// Compares these interfaces with the interfaces of newer content.
+    ///
+    /// Changes are ordered by direction, interface name, and item name.
+    pub fn diff(&self, newer: &Self) -> Vec<InterfaceChange> {
+        let mut changes = Vec::new();
+        diff_interfaces(
+            InterfaceDirection::Import,
+            &self.imports,
+            &newer.imports,
+            &mut changes,
+        );
+        diff_interfaces(
+            InterfaceDirection::Export,
+            &self.exports,
+            &newer.exports,
+            &mut changes,
+        );
+        changes
+    }
+}
+
+/// Determines if a release may make breaking changes to the interfaces of
+/// the release of the previous version.
+///
+/// Breaking changes are allowed when the leftmost non-zero component of the
+/// version changes, so `1.2.0` to `2.0.0` and `0.1.3` to `0.2.0` may break
+/// interfaces but `1.2.0` to `1.3.0` may not.
+pub fn allows_breaking_changes(previous: &Version, version: &Version) -> bool {
+    if previous.major != version.major {
+        return true;
+    }
+
+    if version.major > 0 {
+        return false;
+    }
+
+    if previous.minor != version.minor {
+        return true;
+    }
+
+    version.minor == 0 && previous.patch != version.patch
+}
+
+/// Represents whether an interface is imported or exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InterfaceDirection {
+    /// The interface is imported.
+    Import,
+    /// The interface is exported.
+    Export,
+}
+
+impl fmt::Display for InterfaceDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Import => write!(f, "import"),
+            Self::Export => write!(f, "export"),
+        }
+    }
+}
+
+/// Represents a change of an item of an interface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum InterfaceChangeKind {
+    /// The interface was added.
+    Added,
+    /// The interface was removed.
+    Removed,
+    /// A function was added to the interface.
+    FunctionAdded {
+        /// The name of the function.
+        name: String,
+    },
+    /// A function was removed from the interface.
+    FunctionRemoved {
+        /// The name of the function.
+        name: String,
+    },
+    /// The signature of a function of the interface changed.
+    FunctionChanged {
+        /// The name of the function.
+        name: String,
+        /// The previous signature of the function.
+        from: String,
+        /// The new signature of the function.
+        to: String,
+    },
+    /// A type was added to the interface.
+    TypeAdded {
+        /// The name of the type.
+        name: String,
+    },
+    /// A type was removed from the interface.
+    TypeRemoved {
+        /// The name of the type.
+        name: String,
+    },
+    /// The definition of a type of the interface changed.
+    TypeChanged {
+        /// The name of the type.
+        name: String,
+        /// The previous definition of the type.
+        from: String,
+        /// The new definition of the type.
+        to: String,
+    },
+}
+
+/// Represents a change of an imported or exported interface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceChange {
+    /// Whether the changed interface is imported or exported.
+    pub direction: InterfaceDirection,
+    /// The name of the changed interface.
+    pub interface: String,
+    /// The change of the interface.
+    #[serde(flatten)]
+    pub kind: InterfaceChangeKind,
+}
+
+impl InterfaceChange {
+    /// Determines if the change is breaking for users of the content.
+    ///
+    /// Removing an exported item or adding an imported item is breaking, as
+    /// is changing the signature of any item; adding an exported item or
+    /// removing an imported item is not.
+    pub fn is_breaking(&self) -> bool {
+        match (&self.kind, self.direction) {
+            (
+                InterfaceChangeKind::FunctionChanged { .. }
+                | InterfaceChangeKind::TypeChanged { .. },
+                _,
+            ) => true,
+            (
+                InterfaceChangeKind::Removed
+                | InterfaceChangeKind::FunctionRemoved { .. }
+                | InterfaceChangeKind::TypeRemoved { .. },
+                direction,
+            ) => direction == InterfaceDirection::Export,
+            (
+                InterfaceChangeKind::Added
+                | InterfaceChangeKind::FunctionAdded { .. }
+                | InterfaceChangeKind::TypeAdded { .. },
+                direction,
+            ) => direction == InterfaceDirection::Import,
+        }
+    }
+}
+
+impl fmt::Display for InterfaceChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            direction,
+            interface,
+            kind,
+        } = self;
+        match kind {
+            InterfaceChangeKind::Added => write!(f, "{direction} `{interface}` was added"),
+            InterfaceChangeKind::Removed => write!(f, "{direction} `{interface}` was removed"),
+            InterfaceChangeKind::FunctionAdded { name } => {
+                write!(
+                    f,
+                    "function `{name}` was added to {direction} `{interface}`"
+                )
+            }
+            InterfaceChangeKind::FunctionRemoved { name } => {
+                write!(
+                    f,
+                    "function `{name}` was removed from {direction} `{interface}`"
+                )
+            }
+            InterfaceChangeKind::FunctionChanged { name, from, to } => write!(
+                f,
+                "function `{name}` of {direction} `{interface}` changed from `{from}` to `{to}`"
+            ),
+            InterfaceChangeKind::TypeAdded { name } => {
+                write!(f, "type `{name}` was added to {direction} `{interface}`")
+            }
+            InterfaceChangeKind::TypeRemoved { name } => {
+                write!(
+                    f,
+                    "type `{name}` was removed from {direction} `{interface}`"
+                )
+            }
+            InterfaceChangeKind::TypeChanged { name, from, to } => write!(
+                f,
+                "type `{name}` of {direction} `{interface}` changed from `{from}` to `{to}`"
+            ),
+        }
+    }
+}
+
+fn diff_interfaces(
+    direction: InterfaceDirection,
+    old: &BTreeMap<String, InterfaceSignature>,
+    new: &BTreeMap<String, InterfaceSignature>,
+    changes: &mut Vec<InterfaceChange>,
+) {
+    let mut names = old.keys().chain(new.keys()).collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        let mut push = |kind| {
+            changes.push(InterfaceChange {
+                direction,
+                interface: name.clone(),
+                kind,
+            })
+        };
+
+        let (old, new) = match (old.get(name), new.get(name)) {
+            (Some(old), Some(new)) => (old, new),
+            (Some(_), None) => {
+                push(InterfaceChangeKind::Removed);
+                continue;
+            }
+            (None, Some(_)) => {
+                push(InterfaceChangeKind::Added);
+                continue;
+            }
+            (None, None) => unreachable!(),
+        };
+
+        diff_items(
+            &old.types,
+            &new.types,
+            |name| InterfaceChangeKind::TypeAdded { name },
+            |name| InterfaceChangeKind::TypeRemoved { name },
+            |name, from, to| InterfaceChangeKind::TypeChanged { name, from, to },
+            &mut push,
+        );
+        diff_items(
+            &old.functions,
+            &new.functions,
+            |name| InterfaceChangeKind::FunctionAdded { name },
+            |name| InterfaceChangeKind::FunctionRemoved { name },
+            |name, from, to| InterfaceChangeKind::FunctionChanged { name, from, to },
+            &mut push,
+        );
+    }
+}
+
+fn diff_items(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+    added: impl Fn(String) -> InterfaceChangeKind,
+    removed: impl Fn(String) -> InterfaceChangeKind,
+    changed: impl Fn(String, String, String) -> InterfaceChangeKind,
+    push: &mut impl FnMut(InterfaceChangeKind),
+) {
+    for (name, from) in old {
+        match new.get(name) {
+            Some(to) if to != from => push(changed(name.clone(), from.clone(), to.clone())),
+            Some(_) => {}
+            None => push(removed(name.clone())),
+        }
+    }
+
+    for name in new.keys().filter(|name| !old.contains_key(*name)) {
+        push(added(name.clone()));
+    }
+}
+
+fn item_name(resolve: &Resolve, key: &WorldKey, item: &WorldItem) -> String {
+    match item {
+        // Unlike `Resolve::name_world_key`, include the version of the interface's package
+        WorldItem::Interface(id) => resolve
+            .id_of(*id)
+            .unwrap_or_else(|| resolve.name_world_key(key)),
+        _ => resolve.name_world_key(key),
+    }
+}
+
+fn signature(resolve: &Resolve, item: &WorldItem) -> InterfaceSignature {
+    let mut signature = InterfaceSignature::default();
+    match item {
+        WorldItem::Interface(id) => {
+            let interface = &resolve.interfaces[*id];
+            for (name, ty) in &interface.types {
+                signature
+                    .types
+                    .insert(name.clone(), render_type_def(resolve, *ty));
+            }
+            for (name, function) in &interface.functions {
+                signature
+                    .functions
+                    .insert(name.clone(), render_function(resolve, function));
+            }
+        }
+        WorldItem::Function(function) => {
+            signature
+                .functions
+                .insert(function.name.clone(), render_function(resolve, function));
+        }
+        WorldItem::Type(ty) => {
+            let name = resolve.types[*ty].name.clone().unwrap_or_default();
+            signature.types.insert(name, render_type_def(resolve, *ty));
+        }
+    }
+
+    signature
+}
+
+fn render_function(resolve: &Resolve, function: &Function) -> String {
+    let params = function
+        .params
+        .iter()
+        .map(|(name, ty)| format!("{name}: {ty}", ty = render_type(resolve, ty)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match &function.results {
+        Results::Named(results) if results.is_empty() => format!("func({params})"),
+        Results::Named(results) => format!(
+            "func({params}) -> ({results})",
+            results = results
+                .iter()
+                .map(|(name, ty)| format!("{name}: {ty}", ty = render_type(resolve, ty)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Results::Anon(ty) => format!("func({params}) -> {ty}", ty = render_type(resolve, ty)),
+    }
+}
+
+fn render_type(resolve: &Resolve, ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::S8 => "s8".to_string(),
+        Type::S16 => "s16".to_string(),
+        Type::S32 => "s32".to_string(),
+        Type::S64 => "s64".to_string(),
+        Type::Float32 => "float32".to_string(),
+        Type::Float64 => "float64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "string".to_string(),
+        Type::Id(id) => match &resolve.types[*id].name {
+            Some(name) => name.clone(),
+            None => render_type_def(resolve, *id),
+        },
+    }
+}
+
+fn render_optional_type(resolve: &Resolve, ty: Option<&Type>) -> String {
+    ty.map(|ty| render_type(resolve, ty))
+        .unwrap_or_else(|| "_".to_string())
+}
+
+fn render_type_def(resolve: &Resolve, id: TypeId) -> String {
+    let render_names =
+        |names: &mut dyn Iterator<Item = String>| names.collect::<Vec<_>>().join(", ");
+
+    match &resolve.types[id].kind {
+        TypeDefKind::Record(record) => format!(
+            "record {{ {fields} }}",
+            fields = render_names(&mut record.fields.iter().map(|field| format!(
+                "{name}: {ty}",
+                name = field.name,
+                ty = render_type(resolve, &field.ty)
+            )))
+        ),
+        TypeDefKind::Resource => "resource".to_string(),
+        TypeDefKind::Handle(Handle::Own(id)) => {
+            format!("own<{ty}>", ty = render_type(resolve, &Type::Id(*id)))
+        }
+        TypeDefKind::Handle(Handle::Borrow(id)) => {
+            format!("borrow<{ty}>", ty = render_type(resolve, &Type::Id(*id)))
+        }
+        TypeDefKind::Flags(flags) => format!(
+            "flags {{ {flags} }}",
+            flags = render_names(&mut flags.flags.iter().map(|flag| flag.name.clone()))
+        ),
+        TypeDefKind::Tuple(tuple) => format!(
+            "tuple<{types}>",
+            types = render_names(&mut tuple.types.iter().map(|ty| render_type(resolve, ty)))
+        ),
+        TypeDefKind::Variant(variant) => format!(
+            "variant {{ {cases} }}",
+            cases = render_names(&mut variant.cases.iter().map(|case| match &case.ty {
+                Some(ty) => format!(
+                    "{name}({ty})",
+                    name = case.name,
+                    ty = render_type(resolve, ty)
+                ),
+                None => case.name.clone(),
+            }))
+        ),
+        TypeDefKind::Enum(e) => format!(
+            "enum {{ {cases} }}",
+            cases = render_names(&mut e.cases.iter().map(|case| case.name.clone()))
+        ),
+        TypeDefKind::Option(ty) => format!("option<{ty}>", ty = render_type(resolve, ty)),
+        TypeDefKind::Result(result) => format!(
+            "result<{ok}, {err}>",
+            ok = render_optional_type(resolve, result.ok.as_ref()),
+            err = render_optional_type(resolve, result.err.as_ref())
+        ),
+        TypeDefKind::List(ty) => format!("list<{ty}>", ty = render_type(resolve, ty)),
+        TypeDefKind::Future(ty) => format!(
+            "future<{ty}>",
+            ty = render_optional_type(resolve, ty.as_ref())
+        ),
+        TypeDefKind::Stream(stream) => format!(
+            "stream<{element}, {end}>",
+            element = render_optional_type(resolve, stream.element.as_ref()),
+            end = render_optional_type(resolve, stream.end.as_ref())
+        ),
+        TypeDefKind::Type(ty) => render_type(resolve, ty),
+        TypeDefKind::Unknown => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use wit_parser::UnresolvedPackage;
+
+    fn encode_wit(wit: &str) -> Vec<u8> {
+        let mut resolve = Resolve::new();
+        let package = resolve
+            .push(UnresolvedPackage::parse(Path::new("foo.wit"), wit).unwrap())
+            .unwrap();
+        wit_component::encode(Some(true), &resolve, package).unwrap()
+    }
+
+    #[test]
+    fn it_extracts_interfaces() {
+        let interfaces = ContentInterfaces::from_content(&encode_wit(
+            "package test:pkg@1.0.0;
+            interface greet {
+                record person { name: string, age: option<u32> }
+                greet: func(who: person) -> result<string>;
+            }",
+        ))
+        .unwrap();
+
+        let greet = &interfaces.exports["test:pkg/greet@1.0.0"];
+        assert_eq!(
+            greet.types["person"],
+            "record { name: string, age: option<u32> }"
+        );
+        assert_eq!(
+            greet.functions["greet"],
+            "func(who: person) -> result<string, _>"
+        );
+        assert!(interfaces.imports.is_empty());
+
+        assert!(ContentInterfaces::from_content(b"not wasm")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn it_diffs_interfaces() {
+        let old = ContentInterfaces::from_content(&encode_wit(
+            "package test:pkg@1.0.0;
+            interface a {
+                f: func(x: u32);
+                g: func();
+            }
+            interface b {
+                h: func();
+            }",
+        ))
+        .unwrap();
+        let new = ContentInterfaces::from_content(&encode_wit(
+            "package test:pkg@1.0.0;
+            interface a {
+                f: func(x: u64);
+                i: func();
+                type t = u8;
+            }
+            interface c {
+                h: func();
+            }",
+        ))
+        .unwrap();
+
+        let changes = old.diff(&new);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| (change.to_string(), change.is_breaking()))
+                .collect::<Vec<_>>(),
+            [
+                ("type `t` was added to export `test:pkg/a@1.0.0`", false),
+                (
+                    "function `f` of export `test:pkg/a@1.0.0` changed from `func(x: u32)` to `func(x: u64)`",
+                    true
+                ),
+                ("function `g` was removed from export `test:pkg/a@1.0.0`", true),
+                ("function `i` was added to export `test:pkg/a@1.0.0`", false),
+                ("export `test:pkg/b@1.0.0` was removed", true),
+                ("export `test:pkg/c@1.0.0` was added", false),
+            ]
+            .map(|(change, breaking)| (change.to_string(), breaking))
+        );
+
+        assert!(new.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn it_allows_breaking_changes_for_incompatible_versions() {
+        let allows = |previous: &str, version: &str| {
+            allows_breaking_changes(&previous.parse().unwrap(), &version.parse().unwrap())
+        };
+
+        assert!(allows("1.2.0", "2.0.0"));
+        assert!(!allows("1.2.0", "1.3.0"));
+        assert!(!allows("1.2.0", "1.2.1"));
+        assert!(allows("0.1.3", "0.2.0"));
+        assert!(!allows("0.1.3", "0.1.4"));
+        assert!(allows("0.0.1", "0.0.2"));
+    }
+
+    #[test]
+    fn it_classifies_import_changes() {
+        let change = |kind| InterfaceChange {
+            direction: InterfaceDirection::Import,
+            interface: "test:pkg/a".to_string(),
+            kind,
+        };
+
+        assert!(change(InterfaceChangeKind::Added).is_breaking());
+        assert!(!change(InterfaceChangeKind::Removed).is_breaking());
+        assert!(!change(InterfaceChangeKind::FunctionRemoved {
+            name: "f".to_string()
+        })
+        .is_breaking());
+    }
+}
@@ -2,11 +2,17 @@ use indexmap::IndexSet;
 use serde::{de::DeserializeOwned, Serialize};
 use warg_crypto::{hash::AnyHash, Decode};
 
+#[cfg(feature = "content")]
+pub mod interface;
+pub mod metadata;
 pub mod operator;
 pub mod package;
 mod proto_envelope;
 pub mod registry;
 mod serde_envelope;
+pub mod test_vectors;
+#[cfg(feature = "verify")]
+pub mod verify;
 
 pub use proto_envelope::{
     ProtoEnvelope, ProtoEnvelopeBody, PublishedProtoEnvelope, PublishedProtoEnvelopeBody,
@@ -36,7 +42,7 @@ pub trait Validator:
     fn validate(self, record: &ProtoEnvelope<Self::Record>) -> Result<Self, Self::Error>;
 }
 
-/// Helpers for converting to and from protobuf
+// Helpers for converting to and from protobuf
 
 fn prost_to_pbjson_timestamp(timestamp: prost_types::Timestamp) -> pbjson_types::Timestamp {
     pbjson_types::Timestamp {
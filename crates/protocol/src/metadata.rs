@@ -0,0 +1,334 @@
+//! Module for extracting metadata from package content.
+//!
+//! Metadata is read from the `registry-metadata` custom section of a
+//! WebAssembly module or component, as written by tools such as
+//! `wasm-tools metadata add`.
+//!
+//! Release notes are attached to a release as an attestation of kind
+//! [`RELEASE_NOTES_KIND`] with a JSON [`ReleaseNotes`] document as content.
+//!
+//! Documentation is attached to a release as an attestation of kind
+//! [`DOCS_ARCHIVE_KIND`] with a gzip-compressed tar archive as content.
+//!
+//! Extracting metadata, parsing release notes and evaluating licenses
+//! require the `content` feature.
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+#[cfg(feature = "content")]
+use {
+    anyhow::bail,
+    spdx::{Expression, ParseMode},
+    thiserror::Error,
+    wasm_metadata::RegistryMetadata,
+};
+
+/// Represents an error normalizing a license expression.
+#[cfg(feature = "content")]
+#[derive(Debug, Error)]
+#[error("invalid SPDX license expression `{expression}`: {reason}")]
+pub struct InvalidLicenseError {
+    expression: String,
+    reason: String,
+}
+
+/// Normalizes an SPDX license expression.
+///
+/// Imprecise license identifiers (e.g. `apache2`), lower-cased operators and
+/// `/` separators are rewritten to their canonical SPDX forms, so that the
+/// normalized expression of `mit/apache2` is `MIT OR Apache-2.0`.
+#[cfg(feature = "content")]
+pub fn normalize_license(expression: &str) -> Result<String, InvalidLicenseError> {
+    let invalid = |reason: String| InvalidLicenseError {
+        expression: expression.to_string(),
+        reason,
+    };
+
+    let canonical = Expression::canonicalize(expression.trim())
+        .map_err(|e| invalid(e.reason.to_string()))?
+        .unwrap_or_else(|| expression.trim().to_string());
+
+    Expression::parse_mode(&canonical, ParseMode::STRICT)
+        .map_err(|e| invalid(e.reason.to_string()))?;
+
+    Ok(canonical)
+}
+
+/// Represents metadata extracted from package content.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentMetadata {
+    /// The normalized SPDX license expression of the content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+#[cfg(feature = "content")]
+impl ContentMetadata {
+    /// Extracts the metadata from the given content.
+    ///
+    /// Content that is not WebAssembly, or that has no registry metadata,
+    /// yields empty metadata.
+    ///
+    /// A license that is not a valid SPDX expression is an error.
+    pub fn from_content(bytes: &[u8]) -> Result<Self> {
+        if !wasmparser::Parser::is_core_wasm(bytes) && !wasmparser::Parser::is_component(bytes) {
+            return Ok(Self::default());
+        }
+
+        let Some(metadata) = RegistryMetadata::from_wasm(bytes)
+            .context("failed to parse registry metadata of content")?
+        else {
+            return Ok(Self::default());
+        };
+
+        Ok(Self {
+            license: metadata
+                .get_license()
+                .map(|license| normalize_license(license))
+                .transpose()?,
+        })
+    }
+}
+
+impl ContentMetadata {
+    /// Determines if the metadata is empty.
+    pub fn is_empty(&self) -> bool {
+        self.license.is_none()
+    }
+}
+
+/// Represents the location of a custom section in WebAssembly content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomSectionLocation {
+    /// The name of the custom section.
+    pub name: String,
+    /// The byte range of the data of the custom section within the content.
+    pub range: Range<usize>,
+}
+
+/// Locates the custom sections of the given content.
+///
+/// Only custom sections at the top level of the content are located; the
+/// custom sections of nested modules and components are not. Content that
+/// is not WebAssembly has no custom sections.
+pub fn locate_custom_sections(bytes: &[u8]) -> Result<Vec<CustomSectionLocation>> {
+    if !wasmparser::Parser::is_core_wasm(bytes) && !wasmparser::Parser::is_component(bytes) {
+        return Ok(Vec::new());
+    }
+
+    let mut parser = wasmparser::Parser::new(0);
+    let mut offset = 0;
+    let mut sections = Vec::new();
+    loop {
+        let (payload, consumed) = match parser
+            .parse(&bytes[offset..], true)
+            .context("failed to parse content")?
+        {
+            wasmparser::Chunk::Parsed { consumed, payload } => (payload, consumed),
+            // This state isn't possible with `eof = true`
+            wasmparser::Chunk::NeedMoreData(_) => unreachable!(),
+        };
+        offset += consumed;
+
+        match payload {
+            wasmparser::Payload::CustomSection(reader) => sections.push(CustomSectionLocation {
+                name: reader.name().to_string(),
+                range: reader.data_offset()..reader.data_offset() + reader.data().len(),
+            }),
+            wasmparser::Payload::CodeSectionStart { size, .. } => {
+                parser.skip_section();
+                offset += size as usize;
+            }
+            wasmparser::Payload::ModuleSection { range, .. }
+            | wasmparser::Payload::ComponentSection { range, .. } => offset = range.end,
+            wasmparser::Payload::End(_) => break,
+            _ => {}
+        }
+    }
+
+    Ok(sections)
+}
+
+/// The attestation kind of release notes attached to a release.
+pub const RELEASE_NOTES_KIND: &str = "https://warg.io/release-notes/v1";
+
+/// The attestation kind of a documentation archive attached to a release.
+///
+/// The content of the attestation is declared as a
+/// [`ContentType::DocsArchive`](crate::package::ContentType::DocsArchive).
+pub const DOCS_ARCHIVE_KIND: &str = "https://warg.io/docs-archive/v1";
+
+/// The maximum size, in bytes, of a release notes document.
+pub const MAX_RELEASE_NOTES_SIZE: usize = 64 * 1024;
+
+/// Represents a link in release notes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseLink {
+    /// The title of the link (e.g. `Changelog`).
+    pub title: String,
+    /// The URL of the link.
+    pub url: String,
+}
+
+/// Represents the release notes of a release.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseNotes {
+    /// The notes of the release, as Markdown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Links related to the release.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<ReleaseLink>,
+}
+
+#[cfg(feature = "content")]
+impl ReleaseNotes {
+    /// Parses release notes from attestation content.
+    ///
+    /// Content larger than [`MAX_RELEASE_NOTES_SIZE`] is an error.
+    pub fn from_content(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() > MAX_RELEASE_NOTES_SIZE {
+            bail!(
+                "release notes of {len} bytes exceed the maximum size of {MAX_RELEASE_NOTES_SIZE} bytes",
+                len = bytes.len()
+            );
+        }
+
+        serde_json::from_slice(bytes).context("failed to parse release notes")
+    }
+
+    /// Serializes the release notes as attestation content.
+    ///
+    /// Release notes larger than [`MAX_RELEASE_NOTES_SIZE`] are an error.
+    pub fn to_content(&self) -> Result<Vec<u8>> {
+        let bytes = serde_json::to_vec(self).context("failed to serialize release notes")?;
+        if bytes.len() > MAX_RELEASE_NOTES_SIZE {
+            bail!(
+                "release notes of {len} bytes exceed the maximum size of {MAX_RELEASE_NOTES_SIZE} bytes",
+                len = bytes.len()
+            );
+        }
+
+        Ok(bytes)
+    }
+}
+
+/// Determines if a license expression is satisfied by the given set of
+/// allowed SPDX license identifiers.
+///
+/// For example, `MIT OR GPL-3.0-only` is allowed by `["MIT"]`, but
+/// `MIT AND GPL-3.0-only` is not.
+///
+/// The allowed identifiers are compared case insensitively; expressions that
+/// are not valid SPDX are never allowed.
+#[cfg(feature = "content")]
+pub fn license_allowed<'a>(
+    expression: &str,
+    allowed: impl IntoIterator<Item = &'a str> + Clone,
+) -> bool {
+    let Ok(expression) = Expression::parse_mode(expression, ParseMode::LAX) else {
+        return false;
+    };
+
+    expression.evaluate(|req| {
+        let name = req.license.to_string();
+        allowed
+            .clone()
+            .into_iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&name))
+    })
+}
+
+#[cfg(all(test, feature = "content"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_normalizes_licenses() {
+        assert_eq!(normalize_license("MIT").unwrap(), "MIT");
+        assert_eq!(
+            normalize_license("mit/apache2").unwrap(),
+            "MIT OR Apache-2.0"
+        );
+        assert_eq!(
+            normalize_license("Apache-2.0 with LLVM-exception").unwrap(),
+            "Apache-2.0 WITH LLVM-exception"
+        );
+        assert!(normalize_license("not a license").is_err());
+        assert!(normalize_license("").is_err());
+    }
+
+    #[test]
+    fn it_extracts_licenses() {
+        let mut metadata = RegistryMetadata::default();
+        metadata.set_license(Some("mit/apache2".to_string()));
+        let bytes = metadata
+            .add_to_wasm(&wasm_encoder::Component::new().finish())
+            .unwrap();
+
+        assert_eq!(
+            ContentMetadata::from_content(&bytes).unwrap().license,
+            Some("MIT OR Apache-2.0".to_string())
+        );
+        assert!(ContentMetadata::from_content(b"not wasm")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn it_locates_custom_sections() {
+        let mut metadata = RegistryMetadata::default();
+        metadata.set_license(Some("MIT".to_string()));
+        let mut module = wasm_encoder::Module::new();
+        module.section(&wasm_encoder::CustomSection {
+            name: "nested".into(),
+            data: b"ignored".as_slice().into(),
+        });
+        let mut component = wasm_encoder::Component::new();
+        component.section(&wasm_encoder::ModuleSection(&module));
+        let bytes = metadata.add_to_wasm(&component.finish()).unwrap();
+
+        let sections = locate_custom_sections(&bytes).unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].name, "registry-metadata");
+        assert_eq!(
+            RegistryMetadata::from_bytes(&bytes[sections[0].range.clone()], 0)
+                .unwrap()
+                .get_license()
+                .map(String::as_str),
+            Some("MIT")
+        );
+        assert!(locate_custom_sections(b"not wasm").unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_parses_release_notes() {
+        let notes = ReleaseNotes {
+            notes: Some("Fixed a bug.".to_string()),
+            links: vec![ReleaseLink {
+                title: "Changelog".to_string(),
+                url: "https://example.com/changelog".to_string(),
+            }],
+        };
+        let bytes = notes.to_content().unwrap();
+        assert_eq!(ReleaseNotes::from_content(&bytes).unwrap(), notes);
+
+        assert!(ReleaseNotes::from_content(b"not json").is_err());
+        assert!(ReleaseNotes::from_content(&vec![b' '; MAX_RELEASE_NOTES_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn it_checks_allowed_licenses() {
+        assert!(license_allowed("MIT", ["MIT"]));
+        assert!(license_allowed("MIT OR GPL-3.0-only", ["mit"]));
+        assert!(!license_allowed("MIT AND GPL-3.0-only", ["MIT"]));
+        assert!(license_allowed(
+            "Apache-2.0 WITH LLVM-exception",
+            ["Apache-2.0"]
+        ));
+        assert!(!license_allowed("not a license", ["MIT"]));
+    }
+}
@@ -9,8 +9,8 @@ use crate::{pbjson_to_prost_timestamp, prost_to_pbjson_timestamp, registry::Reco
 mod model;
 mod state;
 
-pub use model::{OperatorEntry, OperatorRecord};
-pub use state::{LogState, NamespaceState, ValidationError};
+pub use model::{OperatorEntry, OperatorRecord, Permission};
+pub use state::{CheckpointKeyChange, LogState, NamespaceState, ValidationError};
 
 /// The currently supported operator protocol version.
 pub const OPERATOR_RECORD_VERSION: u32 = 0;
@@ -103,6 +103,22 @@ pub struct Head {
     pub timestamp: SystemTime,
 }
 
+/// A change to the permission of a key to sign checkpoints.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointKeyChange {
+    /// The id of the key.
+    pub key_id: signing::KeyID,
+    /// Whether the permission was granted to (`true`) or revoked from
+    /// (`false`) the key.
+    pub granted: bool,
+    /// The id of the record that made the change.
+    pub record_id: RecordId,
+    /// The timestamp of the record that made the change.
+    #[serde(with = "crate::timestamp")]
+    pub timestamp: SystemTime,
+}
+
 /// Calculated state for an operator log.
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default, rename_all = "camelCase")]
@@ -123,6 +139,9 @@ pub struct LogState {
     /// The namespaces known to the state. The key is the namespace.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     namespaces: IndexMap<String, NamespaceDefinition>,
+    /// The changes to the keys permitted to sign checkpoints, oldest first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    checkpoint_key_history: Vec<CheckpointKeyChange>,
 }
 
 impl LogState {
@@ -165,6 +184,32 @@ impl LogState {
         self.namespaces.get(namespace).map(|def| &def.state)
     }
 
+    /// Gets the keys known to the state.
+    pub fn keys(&self) -> impl Iterator<Item = &signing::PublicKey> {
+        self.keys.values()
+    }
+
+    /// Gets the permissions of the given key id.
+    ///
+    /// Returns `None` if the key id has never been granted a permission.
+    pub fn permissions(&self, key_id: &signing::KeyID) -> Option<&IndexSet<model::Permission>> {
+        self.permissions.get(key_id)
+    }
+
+    /// Gets the namespaces known to the state, in the order they were
+    /// defined or imported.
+    pub fn namespaces(&self) -> impl Iterator<Item = (&str, &NamespaceState)> {
+        self.namespaces
+            .iter()
+            .map(|(namespace, def)| (namespace.as_str(), &def.state))
+    }
+
+    /// Gets the changes to the keys permitted to sign checkpoints, oldest
+    /// first.
+    pub fn checkpoint_key_history(&self) -> &[CheckpointKeyChange] {
+        &self.checkpoint_key_history
+    }
+
     /// Checks the key has permission to sign checkpoints.
     pub fn key_has_permission_to_sign_checkpoints(&self, key_id: &signing::KeyID) -> bool {
         self.check_key_permissions(key_id, &[model::Permission::Commit])
@@ -192,6 +237,7 @@ impl LogState {
         self.validate_record_timestamp(record)?;
 
         // Validate entries
+        let checkpoint_keys = self.checkpoint_keys();
         self.validate_record_entries(envelope.key_id(), &record.entries)?;
 
         // At this point the digest algorithm must be set via an init entry
@@ -209,15 +255,44 @@ impl LogState {
         // Validate the envelope signature
         model::OperatorRecord::verify(key, envelope.content_bytes(), envelope.signature())?;
 
+        let record_id = RecordId::operator_record::<Sha256>(envelope);
+
+        // Record any changes to the keys permitted to sign checkpoints
+        let change = |key_id: &signing::KeyID, granted| CheckpointKeyChange {
+            key_id: key_id.clone(),
+            granted,
+            record_id: record_id.clone(),
+            timestamp: record.timestamp,
+        };
+        let current = self.checkpoint_keys();
+        let changes = checkpoint_keys
+            .difference(&current)
+            .map(|key_id| change(key_id, false))
+            .chain(
+                current
+                    .difference(&checkpoint_keys)
+                    .map(|key_id| change(key_id, true)),
+            )
+            .collect::<Vec<_>>();
+        self.checkpoint_key_history.extend(changes);
+
         // Update the state head
         self.head = Some(Head {
-            digest: RecordId::operator_record::<Sha256>(envelope),
+            digest: record_id,
             timestamp: record.timestamp,
         });
 
         Ok(())
     }
 
+    fn checkpoint_keys(&self) -> IndexSet<signing::KeyID> {
+        self.permissions
+            .iter()
+            .filter(|(_, permissions)| permissions.contains(&model::Permission::Commit))
+            .map(|(key_id, _)| key_id.clone())
+            .collect()
+    }
+
     fn validate_record_hash(&self, record: &model::OperatorRecord) -> Result<(), ValidationError> {
         match (&self.head, &record.prev) {
             (None, Some(_)) => Err(ValidationError::PreviousHashOnFirstRecord),
@@ -488,12 +563,96 @@ mod tests {
                         model::Permission::ImportNamespace
                     ]),
                 )]),
+                checkpoint_key_history: vec![CheckpointKeyChange {
+                    key_id: alice_id.clone(),
+                    granted: true,
+                    record_id: RecordId::operator_record::<Sha256>(&envelope),
+                    timestamp,
+                }],
                 keys: IndexMap::from([(alice_id, alice_pub)]),
                 namespaces: IndexMap::new(),
             }
         );
     }
 
+    #[test]
+    fn test_checkpoint_key_history() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let alice_id = alice_pub.fingerprint();
+        let (bob_pub, _) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+
+        let timestamp = SystemTime::now();
+        let init = ProtoEnvelope::signed_contents(
+            &alice_priv,
+            model::OperatorRecord {
+                prev: None,
+                version: 0,
+                timestamp,
+                entries: vec![model::OperatorEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                }],
+            },
+        )
+        .expect("failed to sign envelope");
+        let state = LogState::default().validate(&init).unwrap();
+
+        // Granting a permission other than commit does not change the history
+        let grant = ProtoEnvelope::signed_contents(
+            &alice_priv,
+            model::OperatorRecord {
+                prev: Some(RecordId::operator_record::<Sha256>(&init)),
+                version: 0,
+                timestamp,
+                entries: vec![
+                    model::OperatorEntry::GrantFlat {
+                        key: bob_pub.clone(),
+                        permissions: vec![model::Permission::DefineNamespace],
+                    },
+                    model::OperatorEntry::GrantFlat {
+                        key: bob_pub,
+                        permissions: vec![model::Permission::Commit],
+                    },
+                ],
+            },
+        )
+        .expect("failed to sign envelope");
+        let state = state.validate(&grant).unwrap();
+
+        let revoke = ProtoEnvelope::signed_contents(
+            &alice_priv,
+            model::OperatorRecord {
+                prev: Some(RecordId::operator_record::<Sha256>(&grant)),
+                version: 0,
+                timestamp,
+                entries: vec![model::OperatorEntry::RevokeFlat {
+                    key_id: alice_id.clone(),
+                    permissions: vec![model::Permission::Commit],
+                }],
+            },
+        )
+        .expect("failed to sign envelope");
+        let state = state.validate(&revoke).unwrap();
+
+        let change = |key_id: &signing::KeyID, granted, envelope| CheckpointKeyChange {
+            key_id: key_id.clone(),
+            granted,
+            record_id: RecordId::operator_record::<Sha256>(envelope),
+            timestamp,
+        };
+        assert_eq!(
+            state.checkpoint_key_history(),
+            [
+                change(&alice_id, true, &init),
+                change(&bob_id, true, &grant),
+                change(&alice_id, false, &revoke),
+            ]
+        );
+        assert!(!state.key_has_permission_to_sign_checkpoints(&alice_id));
+        assert!(state.key_has_permission_to_sign_checkpoints(&bob_id));
+    }
+
     #[test]
     fn test_rollback() {
         let (alice_pub, alice_priv) = generate_p256_pair();
@@ -530,6 +689,12 @@ mod tests {
                     model::Permission::ImportNamespace,
                 ]),
             )]),
+            checkpoint_key_history: vec![CheckpointKeyChange {
+                key_id: alice_id.clone(),
+                granted: true,
+                record_id: RecordId::operator_record::<Sha256>(&envelope),
+                timestamp,
+            }],
             keys: IndexMap::from([(alice_id, alice_pub)]),
             namespaces: IndexMap::new(),
         };
@@ -612,6 +777,12 @@ mod tests {
                     model::Permission::ImportNamespace,
                 ]),
             )]),
+            checkpoint_key_history: vec![CheckpointKeyChange {
+                key_id: alice_id.clone(),
+                granted: true,
+                record_id: RecordId::operator_record::<Sha256>(&envelope),
+                timestamp,
+            }],
             keys: IndexMap::from([(alice_id, alice_pub)]),
             namespaces: IndexMap::from([
                 (
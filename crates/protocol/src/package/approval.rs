@@ -0,0 +1,64 @@
+use crate::{
+    registry::{LogId, RecordId},
+    SerdeEnvelope,
+};
+use serde::{Deserialize, Serialize};
+use warg_crypto::{
+    prefix::{self, VisitPrefixEncode},
+    signing, ByteVisitor, Encode, Signable, VisitBytes,
+};
+
+/// The approval of a package record by a key other than the key that
+/// signed the record.
+///
+/// Registries may require records of some namespaces to be approved
+/// before they are validated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordApproval {
+    /// The identifier of the package log.
+    pub log_id: LogId,
+    /// The identifier of the approved record.
+    pub record_id: RecordId,
+    /// The key approving the record.
+    pub key: signing::PublicKey,
+}
+
+impl RecordApproval {
+    /// Verifies that the given approval is signed by its approving key.
+    pub fn verify_signed(approval: &SerdeEnvelope<Self>) -> anyhow::Result<()> {
+        let key = &approval.as_ref().key;
+        if &key.fingerprint() != approval.key_id() {
+            anyhow::bail!(
+                "the approval is signed by key `{key_id}` rather than the approving key",
+                key_id = approval.key_id()
+            );
+        }
+
+        Ok(Self::verify(
+            key,
+            &approval.as_ref().encode(),
+            approval.signature(),
+        )?)
+    }
+}
+
+impl Signable for RecordApproval {
+    const PREFIX: &'static [u8] = b"WARG-RECORD-APPROVAL-SIGNATURE-V0";
+}
+
+impl VisitPrefixEncode for RecordApproval {
+    fn visit_pe<BV: ?Sized + ByteVisitor>(&self, visitor: &mut prefix::PrefixEncodeVisitor<BV>) {
+        visitor.visit_str_raw("WARG-RECORD-APPROVAL-V0");
+        visitor.visit_str(&self.log_id.to_string());
+        visitor.visit_str(&self.record_id.to_string());
+        visitor.visit_str(&self.key.to_string());
+    }
+}
+
+// Manual impls of VisitBytes for VisitPrefixEncode to avoid conflict with blanket impls
+impl VisitBytes for RecordApproval {
+    fn visit<BV: ?Sized + ByteVisitor>(&self, visitor: &mut BV) {
+        self.visit_bv(visitor);
+    }
+}
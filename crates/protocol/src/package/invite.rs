@@ -0,0 +1,155 @@
+use super::Permission;
+use crate::{registry::LogId, SerdeEnvelope};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use warg_crypto::{
+    prefix::{self, VisitPrefixEncode},
+    signing, ByteVisitor, Encode, Signable, VisitBytes,
+};
+
+/// An invitation for another key to be granted permissions for a package.
+///
+/// An invite is signed by a key of the package log that holds the
+/// permissions being granted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageInvite {
+    /// The identifier of the package log.
+    pub log_id: LogId,
+    /// The identifier of the invite, unique to the package log.
+    pub id: String,
+    /// The permissions granted to the key that redeems the invite.
+    pub permissions: Vec<Permission>,
+    /// The time the invite expires, in seconds since the Unix epoch.
+    pub expires: u64,
+}
+
+impl PackageInvite {
+    /// Determines if the invite has expired as of the given time.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        now.duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() >= self.expires)
+            .unwrap_or(false)
+    }
+}
+
+impl Signable for PackageInvite {
+    const PREFIX: &'static [u8] = b"WARG-PACKAGE-INVITE-SIGNATURE-V0";
+}
+
+impl VisitPrefixEncode for PackageInvite {
+    fn visit_pe<BV: ?Sized + ByteVisitor>(&self, visitor: &mut prefix::PrefixEncodeVisitor<BV>) {
+        visitor.visit_str_raw("WARG-PACKAGE-INVITE-V0");
+        visitor.visit_str(&self.log_id.to_string());
+        visitor.visit_str(&self.id);
+        visitor.visit_unsigned(self.permissions.len() as u64);
+        for permission in &self.permissions {
+            visitor.visit_str(&permission.to_string());
+        }
+        visitor.visit_unsigned(self.expires);
+    }
+}
+
+// Manual impls of VisitBytes for VisitPrefixEncode to avoid conflict with blanket impls
+impl VisitBytes for PackageInvite {
+    fn visit<BV: ?Sized + ByteVisitor>(&self, visitor: &mut BV) {
+        self.visit_bv(visitor);
+    }
+}
+
+/// The redemption of a [`PackageInvite`] by a key.
+///
+/// A redemption is signed by the redeeming key, proving possession of the
+/// key to be granted the permissions of the invite.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteRedemption {
+    /// The identifier of the package log.
+    pub log_id: LogId,
+    /// The identifier of the redeemed invite.
+    pub invite_id: String,
+    /// The key redeeming the invite.
+    pub key: signing::PublicKey,
+}
+
+impl InviteRedemption {
+    /// Verifies that the given redemption is signed by its redeeming key.
+    pub fn verify_signed(redemption: &SerdeEnvelope<Self>) -> anyhow::Result<()> {
+        let key = &redemption.as_ref().key;
+        if &key.fingerprint() != redemption.key_id() {
+            anyhow::bail!(
+                "the redemption is signed by key `{key_id}` rather than the redeeming key",
+                key_id = redemption.key_id()
+            );
+        }
+
+        Ok(Self::verify(
+            key,
+            &redemption.as_ref().encode(),
+            redemption.signature(),
+        )?)
+    }
+}
+
+impl Signable for InviteRedemption {
+    const PREFIX: &'static [u8] = b"WARG-INVITE-REDEMPTION-SIGNATURE-V0";
+}
+
+impl VisitPrefixEncode for InviteRedemption {
+    fn visit_pe<BV: ?Sized + ByteVisitor>(&self, visitor: &mut prefix::PrefixEncodeVisitor<BV>) {
+        visitor.visit_str_raw("WARG-INVITE-REDEMPTION-V0");
+        visitor.visit_str(&self.log_id.to_string());
+        visitor.visit_str(&self.invite_id);
+        visitor.visit_str(&self.key.to_string());
+    }
+}
+
+// Manual impls of VisitBytes for VisitPrefixEncode to avoid conflict with blanket impls
+impl VisitBytes for InviteRedemption {
+    fn visit<BV: ?Sized + ByteVisitor>(&self, visitor: &mut BV) {
+        self.visit_bv(visitor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::PackageName;
+    use warg_crypto::{hash::Sha256, signing::generate_p256_pair};
+
+    #[test]
+    fn test_signed_invite() {
+        let (owner_pub, owner_priv) = generate_p256_pair();
+        let (invitee_pub, invitee_priv) = generate_p256_pair();
+        let log_id = LogId::package_log::<Sha256>(&PackageName::new("test:invite").unwrap());
+
+        let invite = PackageInvite {
+            log_id: log_id.clone(),
+            id: "invite".to_string(),
+            permissions: vec![Permission::Release],
+            expires: 100,
+        };
+        assert!(invite.is_expired(SystemTime::now()));
+        assert!(!invite.is_expired(UNIX_EPOCH));
+
+        let signed = SerdeEnvelope::signed_contents(&owner_priv, invite.clone()).unwrap();
+        PackageInvite::verify(&owner_pub, &signed.as_ref().encode(), signed.signature()).unwrap();
+        let tampered = PackageInvite {
+            permissions: vec![Permission::Release, Permission::Yank],
+            ..invite
+        };
+        assert!(PackageInvite::verify(&owner_pub, &tampered.encode(), signed.signature()).is_err());
+
+        let redemption = InviteRedemption {
+            log_id,
+            invite_id: "invite".to_string(),
+            key: invitee_pub,
+        };
+        let signed = SerdeEnvelope::signed_contents(&invitee_priv, redemption.clone()).unwrap();
+        InviteRedemption::verify_signed(&signed).unwrap();
+
+        // A redemption must be signed by the redeeming key
+        let signed = SerdeEnvelope::signed_contents(&owner_priv, redemption).unwrap();
+        assert!(InviteRedemption::verify_signed(&signed).is_err());
+    }
+}
@@ -6,11 +6,22 @@ use warg_protobuf::protocol as protobuf;
 
 use crate::{pbjson_to_prost_timestamp, prost_to_pbjson_timestamp, registry::RecordId};
 
+mod approval;
+mod invite;
 mod model;
+#[cfg(feature = "snapshot")]
+mod snapshot;
 mod state;
 
-pub use model::{PackageEntry, PackageRecord, Permission};
-pub use state::{LogState, Release, ReleaseState, ValidationError};
+pub use approval::RecordApproval;
+pub use invite::{InviteRedemption, PackageInvite};
+pub use model::{ContentType, PackageEntry, PackageRecord, Permission};
+#[cfg(feature = "snapshot")]
+pub use snapshot::PackageSnapshot;
+pub use state::{
+    Attestation, Deprecation, LogState, Release, ReleaseChannel, ReleaseChannelParseError,
+    ReleaseState, ValidationError,
+};
 
 /// The currently supported package protocol version.
 pub const PACKAGE_RECORD_VERSION: u32 = 0;
@@ -89,10 +100,26 @@ impl TryFrom<protobuf::PackageEntry> for model::PackageEntry {
                     .parse()
                     .map_err(|error| Error::new(error) as Error)?,
                 content: release.content_hash.parse()?,
+                // Records that predate content types only released components
+                content_type: if release.content_type.is_empty() {
+                    model::ContentType::Component
+                } else {
+                    release.content_type.parse().map_err(Error::msg)?
+                },
             },
             Contents::Yank(yank) => model::PackageEntry::Yank {
                 version: yank.version.parse()?,
             },
+            Contents::Attest(attest) => model::PackageEntry::Attest {
+                version: attest.version.parse()?,
+                kind: attest.kind,
+                content: attest.content_hash.parse()?,
+            },
+            Contents::Deprecate(deprecate) => model::PackageEntry::Deprecate {
+                message: deprecate.message,
+                replacement: deprecate.replacement.map(|name| name.parse()).transpose()?,
+            },
+            Contents::Undeprecate(_) => model::PackageEntry::Undeprecate,
         };
         Ok(output)
     }
@@ -172,15 +199,42 @@ impl<'a> From<&'a model::PackageEntry> for protobuf::PackageEntry {
                 key_id: key_id.to_string(),
                 permissions: permissions.iter().map(Into::into).collect(),
             }),
-            model::PackageEntry::Release { version, content } => {
-                Contents::Release(protobuf::PackageRelease {
-                    version: version.to_string(),
-                    content_hash: content.to_string(),
-                })
-            }
+            model::PackageEntry::Release {
+                version,
+                content,
+                content_type,
+            } => Contents::Release(protobuf::PackageRelease {
+                version: version.to_string(),
+                content_hash: content.to_string(),
+                // Components are encoded without a content type so that their
+                // encoding is unchanged from records that predate content types
+                content_type: match content_type {
+                    model::ContentType::Component => String::new(),
+                    ty => ty.to_string(),
+                },
+            }),
             model::PackageEntry::Yank { version } => Contents::Yank(protobuf::PackageYank {
                 version: version.to_string(),
             }),
+            model::PackageEntry::Attest {
+                version,
+                kind,
+                content,
+            } => Contents::Attest(protobuf::PackageAttest {
+                version: version.to_string(),
+                kind: kind.clone(),
+                content_hash: content.to_string(),
+            }),
+            model::PackageEntry::Deprecate {
+                message,
+                replacement,
+            } => Contents::Deprecate(protobuf::PackageDeprecate {
+                message: message.clone(),
+                replacement: replacement.as_ref().map(ToString::to_string),
+            }),
+            model::PackageEntry::Undeprecate => {
+                Contents::Undeprecate(protobuf::PackageUndeprecate {})
+            }
         };
         let contents = Some(contents);
         protobuf::PackageEntry { contents }
@@ -235,7 +289,23 @@ mod tests {
                 model::PackageEntry::Release {
                     version: Version::new(1, 0, 0),
                     content: HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]),
+                    content_type: model::ContentType::Component,
+                },
+                model::PackageEntry::Release {
+                    version: Version::new(1, 1, 0),
+                    content: HashAlgorithm::Sha256.digest(&[8, 9, 10, 11]),
+                    content_type: model::ContentType::DocsArchive,
+                },
+                model::PackageEntry::Attest {
+                    version: Version::new(1, 0, 0),
+                    kind: "https://slsa.dev/provenance/v1".to_string(),
+                    content: HashAlgorithm::Sha256.digest(&[4, 5, 6, 7]),
+                },
+                model::PackageEntry::Deprecate {
+                    message: "use `test:replacement` instead".to_string(),
+                    replacement: Some("test:replacement".parse().unwrap()),
                 },
+                model::PackageEntry::Undeprecate,
             ],
         };
 
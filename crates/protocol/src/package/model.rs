@@ -1,4 +1,5 @@
-use crate::registry::RecordId;
+use crate::metadata::DOCS_ARCHIVE_KIND;
+use crate::registry::{PackageName, RecordId};
 use core::fmt;
 use indexmap::IndexSet;
 use semver::Version;
@@ -66,6 +67,69 @@ impl FromStr for Permission {
     }
 }
 
+/// The declared artifact type of content in a package log.
+///
+/// Clients may use the type to download only the kinds of artifacts they
+/// need; registries validate content against its declared type.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContentType {
+    /// A WebAssembly component (or core module).
+    #[default]
+    Component,
+    /// A binary-encoded WIT package.
+    WitPackage,
+    /// A detached attestation attached to a release.
+    Attestation,
+    /// A gzip-compressed tar archive of documentation.
+    ///
+    /// A documentation archive may be released or attached to a release as
+    /// an attestation of kind [`DOCS_ARCHIVE_KIND`](crate::metadata::DOCS_ARCHIVE_KIND).
+    DocsArchive,
+}
+
+impl ContentType {
+    /// Gets an array of all content types.
+    pub const fn all() -> [ContentType; 4] {
+        [
+            ContentType::Component,
+            ContentType::WitPackage,
+            ContentType::Attestation,
+            ContentType::DocsArchive,
+        ]
+    }
+
+    /// Determines if content of the type is WebAssembly.
+    pub fn is_wasm(&self) -> bool {
+        matches!(self, Self::Component | Self::WitPackage)
+    }
+}
+
+impl fmt::Display for ContentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentType::Component => write!(f, "component"),
+            ContentType::WitPackage => write!(f, "wit-package"),
+            ContentType::Attestation => write!(f, "attestation"),
+            ContentType::DocsArchive => write!(f, "docs-archive"),
+        }
+    }
+}
+
+impl FromStr for ContentType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "component" => Ok(ContentType::Component),
+            "wit-package" => Ok(ContentType::WitPackage),
+            "attestation" => Ok(ContentType::Attestation),
+            "docs-archive" => Ok(ContentType::DocsArchive),
+            _ => Err(format!("invalid content type {s:?}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum PackageEntry {
@@ -91,10 +155,32 @@ pub enum PackageEntry {
     },
     /// Release a version of a package.
     /// The version must not have been released yet.
-    Release { version: Version, content: AnyHash },
+    /// The content may not be declared as an attestation.
+    Release {
+        version: Version,
+        content: AnyHash,
+        content_type: ContentType,
+    },
     /// Yank a version of a package.
     /// The version must have been released and not yanked.
     Yank { version: Version },
+    /// Attach a detached attestation (e.g. SLSA provenance or an SBOM
+    /// signature) to a version of a package.
+    /// The version must have been released and not yanked.
+    Attest {
+        version: Version,
+        kind: String,
+        content: AnyHash,
+    },
+    /// Deprecate the package, optionally pointing to a replacement package.
+    /// Releases of a deprecated package remain available.
+    Deprecate {
+        message: String,
+        replacement: Option<PackageName>,
+    },
+    /// Remove the deprecation of the package.
+    /// The package must be deprecated.
+    Undeprecate,
 }
 
 impl PackageEntry {
@@ -102,8 +188,10 @@ impl PackageEntry {
     pub fn required_permission(&self) -> Option<Permission> {
         match self {
             Self::Init { .. } | Self::GrantFlat { .. } | Self::RevokeFlat { .. } => None,
-            Self::Release { .. } => Some(Permission::Release),
-            Self::Yank { .. } => Some(Permission::Yank),
+            Self::Release { .. } | Self::Attest { .. } => Some(Permission::Release),
+            Self::Yank { .. } | Self::Deprecate { .. } | Self::Undeprecate => {
+                Some(Permission::Yank)
+            }
         }
     }
 
@@ -112,7 +200,21 @@ impl PackageEntry {
     /// Returns `None` if the entry does not have content.
     pub fn content(&self) -> Option<&AnyHash> {
         match self {
-            Self::Release { content, .. } => Some(content),
+            Self::Release { content, .. } | Self::Attest { content, .. } => Some(content),
+            _ => None,
+        }
+    }
+
+    /// Gets the declared type of the content associated with the entry.
+    ///
+    /// Returns `None` if the entry does not have content.
+    pub fn content_type(&self) -> Option<ContentType> {
+        match self {
+            Self::Release { content_type, .. } => Some(*content_type),
+            Self::Attest { kind, .. } if kind == DOCS_ARCHIVE_KIND => {
+                Some(ContentType::DocsArchive)
+            }
+            Self::Attest { .. } => Some(ContentType::Attestation),
             _ => None,
         }
     }
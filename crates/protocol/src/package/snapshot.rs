@@ -0,0 +1,155 @@
+use super::LogState;
+use crate::registry::{LogId, RecordId, RegistryIndex, RegistryLen};
+use serde::{Deserialize, Serialize};
+use warg_crypto::{
+    prefix::{self, VisitPrefixEncode},
+    ByteVisitor, Signable, VisitBytes,
+};
+
+/// A snapshot of the validated state of a package log.
+///
+/// A snapshot is signed by the registry operator so that a client may start
+/// validating a package log from the snapshot's head record rather than
+/// from the first record of the log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageSnapshot {
+    /// The identifier of the package log.
+    pub log_id: LogId,
+    /// The length of the registry log at the checkpoint the snapshot was
+    /// taken at.
+    pub log_length: RegistryLen,
+    /// The registry index of the head record of the snapshot.
+    pub registry_index: RegistryIndex,
+    /// The identifier of the head record of the snapshot.
+    pub record_id: RecordId,
+    /// The JSON encoding of the package log state after validating the
+    /// head record.
+    pub state: String,
+}
+
+impl PackageSnapshot {
+    /// Creates a snapshot of the given package log state.
+    ///
+    /// Returns `None` if the state has no head record.
+    pub fn new(
+        log_id: LogId,
+        log_length: RegistryLen,
+        registry_index: RegistryIndex,
+        state: &LogState,
+    ) -> Result<Option<Self>, serde_json::Error> {
+        let Some(head) = state.head() else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            log_id,
+            log_length,
+            registry_index,
+            record_id: head.digest.clone(),
+            state: serde_json::to_string(state)?,
+        }))
+    }
+
+    /// Decodes the package log state of the snapshot.
+    ///
+    /// It is an error if the head of the state is not the snapshot's head
+    /// record.
+    pub fn state(&self) -> anyhow::Result<LogState> {
+        let state: LogState = serde_json::from_str(&self.state)?;
+        match state.head() {
+            Some(head) if head.digest == self.record_id => Ok(state),
+            _ => anyhow::bail!(
+                "the state of the snapshot does not have head record `{record_id}`",
+                record_id = self.record_id
+            ),
+        }
+    }
+}
+
+impl Signable for PackageSnapshot {
+    const PREFIX: &'static [u8] = b"WARG-PACKAGE-SNAPSHOT-SIGNATURE-V0";
+}
+
+impl VisitPrefixEncode for PackageSnapshot {
+    fn visit_pe<BV: ?Sized + ByteVisitor>(&self, visitor: &mut prefix::PrefixEncodeVisitor<BV>) {
+        visitor.visit_str_raw("WARG-PACKAGE-SNAPSHOT-V0");
+        visitor.visit_str(&self.log_id.to_string());
+        visitor.visit_unsigned(self.log_length as u64);
+        visitor.visit_unsigned(self.registry_index as u64);
+        visitor.visit_str(&self.record_id.to_string());
+        visitor.visit_str(&self.state);
+    }
+}
+
+// Manual impls of VisitBytes for VisitPrefixEncode to avoid conflict with blanket impls
+impl VisitBytes for PackageSnapshot {
+    fn visit<BV: ?Sized + ByteVisitor>(&self, visitor: &mut BV) {
+        self.visit_bv(visitor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        package::{model, PACKAGE_RECORD_VERSION},
+        registry::PackageName,
+        ProtoEnvelope, SerdeEnvelope,
+    };
+    use std::time::SystemTime;
+    use warg_crypto::{
+        hash::{AnyHash, HashAlgorithm, Sha256},
+        signing::generate_p256_pair,
+        Encode,
+    };
+
+    #[test]
+    fn test_signed_snapshot() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: alice_pub.clone(),
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+
+        let log_id = LogId::package_log::<Sha256>(&PackageName::new("test:snapshot").unwrap());
+        let snapshot = PackageSnapshot::new(log_id, 2, 1, &state).unwrap().unwrap();
+        assert_eq!(
+            snapshot.record_id,
+            RecordId::package_record::<Sha256>(&envelope)
+        );
+        assert_eq!(snapshot.state().unwrap(), state);
+        assert!(
+            PackageSnapshot::new(snapshot.log_id.clone(), 2, 1, &LogState::default())
+                .unwrap()
+                .is_none()
+        );
+
+        let signed = SerdeEnvelope::signed_contents(&alice_priv, snapshot.clone()).unwrap();
+        PackageSnapshot::verify(&alice_pub, &signed.as_ref().encode(), signed.signature()).unwrap();
+
+        let tampered = PackageSnapshot {
+            registry_index: 0,
+            ..snapshot.clone()
+        };
+        assert!(
+            PackageSnapshot::verify(&alice_pub, &tampered.encode(), signed.signature()).is_err()
+        );
+
+        let mismatched = PackageSnapshot {
+            record_id: "sha256:7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069"
+                .parse::<AnyHash>()
+                .unwrap()
+                .into(),
+            ..snapshot
+        };
+        assert!(mismatched.state().is_err());
+    }
+}
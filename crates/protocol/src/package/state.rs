@@ -1,13 +1,14 @@
 use super::{model, PACKAGE_RECORD_VERSION};
-use crate::registry::RecordId;
+use crate::metadata::DOCS_ARCHIVE_KIND;
+use crate::registry::{PackageName, RecordId};
 use crate::ProtoEnvelope;
 use indexmap::{map::Entry, IndexMap, IndexSet};
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
-use std::time::SystemTime;
+use std::{fmt, str::FromStr, time::SystemTime};
 use thiserror::Error;
 use warg_crypto::hash::{AnyHash, HashAlgorithm, Sha256};
-use warg_crypto::{signing, Signable};
+use warg_crypto::{signing, Encode, Signable};
 
 #[derive(Error, Debug)]
 pub enum ValidationError {
@@ -38,12 +39,32 @@ pub enum ValidationError {
     #[error("an entry attempted to release version {version} which is already released")]
     ReleaseOfReleased { version: Version },
 
+    #[error(
+        "an entry attempted to release version {version} with content declared as {content_type}"
+    )]
+    InvalidReleaseContentType {
+        version: Version,
+        content_type: model::ContentType,
+    },
+
     #[error("an entry attempted to yank version {version} which had not yet been released")]
     YankOfUnreleased { version: Version },
 
     #[error("an entry attempted to yank version {version} which is already yanked")]
     YankOfYanked { version: Version },
 
+    #[error("an entry attempted to attest version {version} which had not yet been released")]
+    AttestationOfUnreleased { version: Version },
+
+    #[error("an entry attempted to attest version {version} which is yanked")]
+    AttestationOfYanked { version: Version },
+
+    #[error("an entry attempted to attest version {version} with an empty attestation kind")]
+    EmptyAttestationKind { version: Version },
+
+    #[error("an entry attempted to undeprecate the package which is not deprecated")]
+    UndeprecateOfNotDeprecated,
+
     #[error("unable to verify signature")]
     SignatureError(#[from] signing::SignatureError),
 
@@ -88,6 +109,53 @@ pub enum ReleaseState {
     },
 }
 
+/// Represents a detached attestation attached to a release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attestation {
+    /// The id of the record that attached the attestation.
+    pub record_id: RecordId,
+    /// The kind of the attestation (e.g. `https://slsa.dev/provenance/v1`).
+    pub kind: String,
+    /// The content digest of the attestation.
+    pub content: AnyHash,
+    /// The key id that attached the attestation.
+    pub by: signing::KeyID,
+    /// The timestamp of the attestation.
+    #[serde(with = "crate::timestamp")]
+    pub timestamp: SystemTime,
+}
+
+impl Attestation {
+    /// Gets the declared type of the attestation content.
+    pub fn content_type(&self) -> model::ContentType {
+        if self.kind == DOCS_ARCHIVE_KIND {
+            model::ContentType::DocsArchive
+        } else {
+            model::ContentType::Attestation
+        }
+    }
+}
+
+/// Represents the deprecation of a package.
+///
+/// Unlike a yank, a deprecation does not affect the availability of
+/// releases; clients warn when resolving releases of a deprecated package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deprecation {
+    /// The reason the package is deprecated.
+    pub message: String,
+    /// The name of the package that replaces the deprecated package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<PackageName>,
+    /// The key id that deprecated the package.
+    pub by: signing::KeyID,
+    /// The timestamp of the deprecation.
+    #[serde(with = "crate::timestamp")]
+    pub timestamp: SystemTime,
+}
+
 /// Represents information about a release.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -101,6 +169,9 @@ pub struct Release {
     /// The timestamp of the release.
     #[serde(with = "crate::timestamp")]
     pub timestamp: SystemTime,
+    /// The declared type of the release content.
+    #[serde(default)]
+    pub content_type: model::ContentType,
     /// The current state of the release.
     pub state: ReleaseState,
 }
@@ -122,6 +193,85 @@ impl Release {
     }
 }
 
+/// A release channel used to select between the releases of a package.
+///
+/// Channels are derived from version metadata: the channel of a pre-release
+/// is the first identifier of its pre-release (e.g. `1.0.0-nightly.3` is in
+/// the `nightly` channel).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub enum ReleaseChannel {
+    /// Only releases without a pre-release.
+    #[default]
+    Stable,
+    /// All releases, including pre-releases of any channel.
+    PreRelease,
+    /// Only pre-releases of the named channel.
+    Named(String),
+}
+
+impl ReleaseChannel {
+    /// Determines if the given version is in the channel.
+    pub fn contains(&self, version: &Version) -> bool {
+        match self {
+            Self::Stable => version.pre.is_empty(),
+            Self::PreRelease => true,
+            Self::Named(name) => version.pre.split('.').next() == Some(name.as_str()),
+        }
+    }
+
+    /// Determines if the given version is in the channel and satisfies the
+    /// given version requirement.
+    ///
+    /// Unlike [`VersionReq::matches`], a pre-release satisfies the
+    /// requirement if the version it is a pre-release of does.
+    pub fn matches(&self, req: &VersionReq, version: &Version) -> bool {
+        if !self.contains(version) {
+            return false;
+        }
+
+        if req.matches(version) {
+            return true;
+        }
+
+        !version.pre.is_empty()
+            && req.matches(&Version::new(version.major, version.minor, version.patch))
+    }
+}
+
+impl fmt::Display for ReleaseChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stable => write!(f, "stable"),
+            Self::PreRelease => write!(f, "pre-release"),
+            Self::Named(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl FromStr for ReleaseChannel {
+    type Err = ReleaseChannelParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stable" => Ok(Self::Stable),
+            "pre-release" => Ok(Self::PreRelease),
+            name if !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') =>
+            {
+                Ok(Self::Named(name.to_string()))
+            }
+            name => Err(ReleaseChannelParseError(name.to_string())),
+        }
+    }
+}
+
+/// An error returned when parsing an invalid release channel.
+#[derive(Error, Debug)]
+#[error(
+    "invalid release channel `{0}`: expected `stable`, `pre-release`, or a pre-release identifier"
+)]
+pub struct ReleaseChannelParseError(String);
+
 /// Information about the current head of the package log.
 ///
 /// A head is the last validated record digest and timestamp.
@@ -152,6 +302,12 @@ pub struct LogState {
     /// The releases in the package log.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     releases: IndexMap<Version, Release>,
+    /// The attestations attached to each release.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    attestations: IndexMap<Version, Vec<Attestation>>,
+    /// The deprecation of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deprecation: Option<Deprecation>,
     /// The keys known to the state.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     keys: IndexMap<signing::KeyID, signing::PublicKey>,
@@ -185,6 +341,21 @@ impl LogState {
         Ok(self)
     }
 
+    /// Checks an unsigned package record against the state.
+    ///
+    /// The record is checked as if it were signed by the given key and
+    /// validated next, so that an invalid record can be detected before it is
+    /// signed. Unlike `validate`, the state is not modified.
+    pub fn check(
+        &self,
+        signer_key_id: &signing::KeyID,
+        record: &model::PackageRecord,
+    ) -> Result<(), ValidationError> {
+        let record_id = RecordId::package_record_content::<Sha256>(&record.encode());
+        self.clone()
+            .validate_unsigned_record(&record_id, signer_key_id, record)
+    }
+
     /// Gets the releases known to the state.
     ///
     /// The releases are returned in package log order.
@@ -211,6 +382,74 @@ impl LogState {
             .max_by(|a, b| a.version.cmp(&b.version))
     }
 
+    /// Finds the latest release with a version lower than the given version.
+    ///
+    /// Releases that have been yanked are not considered.
+    pub fn find_previous_release(&self, version: &Version) -> Option<&Release> {
+        self.releases
+            .values()
+            .filter(|release| !release.yanked() && release.version < *version)
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+
+    /// Finds the latest release in the given channel matching the given
+    /// version requirement.
+    ///
+    /// See [`ReleaseChannel::matches`] for how pre-releases are matched.
+    ///
+    /// Releases that have been yanked are not considered.
+    pub fn find_latest_in_channel(
+        &self,
+        req: &VersionReq,
+        channel: &ReleaseChannel,
+    ) -> Option<&Release> {
+        self.releases
+            .values()
+            .filter(|release| !release.yanked() && channel.matches(req, &release.version))
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+
+    /// Gets the attestations attached to the release with the given version.
+    ///
+    /// The attestations are returned in package log order; attestations of
+    /// a release remain after it is yanked.
+    pub fn attestations(&self, version: &Version) -> &[Attestation] {
+        self.attestations
+            .get(version)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Gets the content digest of the documentation archive of the release
+    /// with the given version.
+    ///
+    /// This is the release content if it was released as a documentation
+    /// archive; otherwise, it is the latest documentation archive attached to
+    /// the release.
+    ///
+    /// Returns `None` if the release does not exist, is yanked or has no
+    /// documentation.
+    pub fn docs(&self, version: &Version) -> Option<&AnyHash> {
+        let release = self.release(version)?;
+        let content = release.content()?;
+        if release.content_type == model::ContentType::DocsArchive {
+            return Some(content);
+        }
+
+        self.attestations(version)
+            .iter()
+            .rev()
+            .find(|attestation| attestation.kind == DOCS_ARCHIVE_KIND)
+            .map(|attestation| &attestation.content)
+    }
+
+    /// Gets the deprecation of the package.
+    ///
+    /// Returns `None` if the package is not deprecated.
+    pub fn deprecation(&self) -> Option<&Deprecation> {
+        self.deprecation.as_ref()
+    }
+
     /// Gets the public key of the given key id.
     ///
     /// Returns `None` if the key id is not recognized.
@@ -237,6 +476,27 @@ impl LogState {
         let record = envelope.as_ref();
         let record_id = RecordId::package_record::<Sha256>(envelope);
 
+        self.validate_unsigned_record(&record_id, envelope.key_id(), record)?;
+
+        // Validate the envelope signature
+        let key = &self.keys[envelope.key_id()];
+        model::PackageRecord::verify(key, envelope.content_bytes(), envelope.signature())?;
+
+        // Update the state head
+        self.head = Some(Head {
+            digest: record_id,
+            timestamp: record.timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn validate_unsigned_record(
+        &mut self,
+        record_id: &RecordId,
+        signer_key_id: &signing::KeyID,
+        record: &model::PackageRecord,
+    ) -> Result<(), ValidationError> {
         // Validate previous hash
         self.validate_record_hash(record)?;
 
@@ -247,33 +507,19 @@ impl LogState {
         self.validate_record_timestamp(record)?;
 
         // Validate entries
-        self.validate_record_entries(
-            &record_id,
-            envelope.key_id(),
-            record.timestamp,
-            &record.entries,
-        )?;
+        self.validate_record_entries(record_id, signer_key_id, record.timestamp, &record.entries)?;
 
         // At this point the digest algorithm must be set via an init entry
         let _algorithm = self
             .algorithm
             .ok_or(ValidationError::InitialRecordDoesNotInit)?;
 
-        // Validate the envelope key id
-        let key = self.keys.get(envelope.key_id()).ok_or_else(|| {
-            ValidationError::KeyIDNotRecognized {
-                key_id: envelope.key_id().clone(),
-            }
-        })?;
-
-        // Validate the envelope signature
-        model::PackageRecord::verify(key, envelope.content_bytes(), envelope.signature())?;
-
-        // Update the state head
-        self.head = Some(Head {
-            digest: record_id,
-            timestamp: record.timestamp,
-        });
+        // Validate the signer key id
+        if !self.keys.contains_key(signer_key_id) {
+            return Err(ValidationError::KeyIDNotRecognized {
+                key_id: signer_key_id.clone(),
+            });
+        }
 
         Ok(())
     }
@@ -362,16 +608,49 @@ impl LogState {
                     key_id,
                     permissions,
                 } => self.validate_revoke_entry(signer_key_id, key_id, permissions)?,
-                model::PackageEntry::Release { version, content } => self.validate_release_entry(
+                model::PackageEntry::Release {
+                    version,
+                    content,
+                    content_type,
+                } => self.validate_release_entry(
                     record_id,
                     signer_key_id,
                     timestamp,
                     version,
                     content,
+                    *content_type,
                 )?,
                 model::PackageEntry::Yank { version } => {
                     self.validate_yank_entry(signer_key_id, timestamp, version)?
                 }
+                model::PackageEntry::Attest {
+                    version,
+                    kind,
+                    content,
+                } => self.validate_attest_entry(
+                    record_id,
+                    signer_key_id,
+                    timestamp,
+                    version,
+                    kind,
+                    content,
+                )?,
+                model::PackageEntry::Deprecate {
+                    message,
+                    replacement,
+                } => {
+                    self.deprecation = Some(Deprecation {
+                        message: message.clone(),
+                        replacement: replacement.clone(),
+                        by: signer_key_id.clone(),
+                        timestamp,
+                    });
+                }
+                model::PackageEntry::Undeprecate => {
+                    if self.deprecation.take().is_none() {
+                        return Err(ValidationError::UndeprecateOfNotDeprecated);
+                    }
+                }
             }
         }
 
@@ -396,6 +675,8 @@ impl LogState {
 
         assert!(self.permissions.is_empty());
         assert!(self.releases.is_empty());
+        assert!(self.attestations.is_empty());
+        assert!(self.deprecation.is_none());
         assert!(self.keys.is_empty());
 
         self.algorithm = Some(algorithm);
@@ -459,7 +740,16 @@ impl LogState {
         timestamp: SystemTime,
         version: &Version,
         content: &AnyHash,
+        content_type: model::ContentType,
     ) -> Result<(), ValidationError> {
+        // Attestations are attached to a release rather than released
+        if content_type == model::ContentType::Attestation {
+            return Err(ValidationError::InvalidReleaseContentType {
+                version: version.clone(),
+                content_type,
+            });
+        }
+
         match self.releases.entry(version.clone()) {
             Entry::Occupied(e) => {
                 return Err(ValidationError::ReleaseOfReleased {
@@ -473,6 +763,7 @@ impl LogState {
                     version,
                     by: signer_key_id.clone(),
                     timestamp,
+                    content_type,
                     state: ReleaseState::Released {
                         content: content.clone(),
                     },
@@ -508,6 +799,46 @@ impl LogState {
         }
     }
 
+    fn validate_attest_entry(
+        &mut self,
+        record_id: &RecordId,
+        signer_key_id: &signing::KeyID,
+        timestamp: SystemTime,
+        version: &Version,
+        kind: &str,
+        content: &AnyHash,
+    ) -> Result<(), ValidationError> {
+        if kind.is_empty() {
+            return Err(ValidationError::EmptyAttestationKind {
+                version: version.clone(),
+            });
+        }
+
+        match self.releases.get(version) {
+            Some(e) => match e.state {
+                ReleaseState::Yanked { .. } => Err(ValidationError::AttestationOfYanked {
+                    version: version.clone(),
+                }),
+                ReleaseState::Released { .. } => {
+                    self.attestations
+                        .entry(version.clone())
+                        .or_default()
+                        .push(Attestation {
+                            record_id: record_id.clone(),
+                            kind: kind.to_string(),
+                            content: content.clone(),
+                            by: signer_key_id.clone(),
+                            timestamp,
+                        });
+                    Ok(())
+                }
+            },
+            None => Err(ValidationError::AttestationOfUnreleased {
+                version: version.clone(),
+            }),
+        }
+    }
+
     fn check_key_permissions(
         &self,
         key_id: &signing::KeyID,
@@ -580,6 +911,8 @@ mod tests {
                     IndexSet::from([model::Permission::Release, model::Permission::Yank]),
                 )]),
                 releases: IndexMap::default(),
+                attestations: IndexMap::default(),
+                deprecation: None,
                 keys: IndexMap::from([(alice_id, alice_pub)]),
             }
         );
@@ -625,6 +958,7 @@ mod tests {
             entries: vec![model::PackageEntry::Release {
                 version: Version::new(1, 1, 0),
                 content: content.clone(),
+                content_type: model::ContentType::Component,
             }],
         };
 
@@ -640,6 +974,7 @@ mod tests {
                 version: Version::new(1, 1, 0),
                 by: bob_id.clone(),
                 timestamp: timestamp1,
+                content_type: model::ContentType::Component,
                 state: ReleaseState::Released {
                     content: content.clone()
                 }
@@ -655,6 +990,7 @@ mod tests {
                 version: Version::new(1, 1, 0),
                 by: bob_id.clone(),
                 timestamp: timestamp1,
+                content_type: model::ContentType::Component,
                 state: ReleaseState::Released { content }
             }]
         );
@@ -687,6 +1023,7 @@ mod tests {
                 version: Version::new(1, 1, 0),
                 by: bob_id.clone(),
                 timestamp: timestamp1,
+                content_type: model::ContentType::Component,
                 state: ReleaseState::Yanked {
                     by: alice_id.clone(),
                     timestamp: timestamp2
@@ -716,12 +1053,15 @@ mod tests {
                         version: Version::new(1, 1, 0),
                         by: bob_id.clone(),
                         timestamp: timestamp1,
+                        content_type: model::ContentType::Component,
                         state: ReleaseState::Yanked {
                             by: alice_id.clone(),
                             timestamp: timestamp2
                         }
                     }
                 )]),
+                attestations: IndexMap::default(),
+                deprecation: None,
                 keys: IndexMap::from([(alice_id, alice_pub), (bob_id, bob_pub),]),
             }
         );
@@ -756,6 +1096,8 @@ mod tests {
             }),
             algorithm: Some(HashAlgorithm::Sha256),
             releases: IndexMap::new(),
+            attestations: IndexMap::new(),
+            deprecation: None,
             permissions: IndexMap::from([(
                 alice_id.clone(),
                 IndexSet::from([model::Permission::Release, model::Permission::Yank]),
@@ -792,4 +1134,128 @@ mod tests {
             _ => panic!("expected a different error"),
         }
     }
+
+    #[test]
+    fn test_check_unsigned_record() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, _) = generate_p256_pair();
+        let alice_id = alice_pub.fingerprint();
+
+        let timestamp = SystemTime::now();
+        let content = HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]);
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp,
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub.clone(),
+                },
+                model::PackageEntry::Release {
+                    version: Version::new(1, 0, 0),
+                    content: content.clone(),
+                    content_type: model::ContentType::Component,
+                },
+            ],
+        };
+
+        let state = LogState::default();
+        state.check(&alice_id, &record).unwrap();
+        assert!(matches!(
+            state.check(&bob_pub.fingerprint(), &record),
+            Err(ValidationError::KeyIDNotRecognized { .. })
+        ));
+
+        // Checking a record does not modify the state
+        assert_eq!(state, LogState::default());
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = state.validate(&envelope).unwrap();
+
+        let next = |entries| model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: timestamp + Duration::from_secs(1),
+            entries,
+        };
+
+        assert!(matches!(
+            state.check(
+                &alice_id,
+                &next(vec![model::PackageEntry::Release {
+                    version: Version::new(1, 0, 0),
+                    content: content.clone(),
+                    content_type: model::ContentType::Component,
+                }])
+            ),
+            Err(ValidationError::ReleaseOfReleased { .. })
+        ));
+        assert!(matches!(
+            state.check(
+                &alice_id,
+                &next(vec![model::PackageEntry::Release {
+                    version: Version::new(2, 0, 0),
+                    content: content.clone(),
+                    content_type: model::ContentType::Attestation,
+                }])
+            ),
+            Err(ValidationError::InvalidReleaseContentType { .. })
+        ));
+        assert!(matches!(
+            state.check(
+                &alice_id,
+                &next(vec![model::PackageEntry::Yank {
+                    version: Version::new(2, 0, 0),
+                }])
+            ),
+            Err(ValidationError::YankOfUnreleased { .. })
+        ));
+        assert!(matches!(
+            state.check(
+                &bob_pub.fingerprint(),
+                &next(vec![model::PackageEntry::Yank {
+                    version: Version::new(1, 0, 0),
+                }])
+            ),
+            Err(ValidationError::UnauthorizedAction { .. })
+        ));
+        state
+            .check(
+                &alice_id,
+                &next(vec![model::PackageEntry::Yank {
+                    version: Version::new(1, 0, 0),
+                }]),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_release_channels() {
+        let req: VersionReq = "^1.0".parse().unwrap();
+        let stable = Version::parse("1.1.0").unwrap();
+        let nightly = Version::parse("1.2.0-nightly.3").unwrap();
+        let beta = Version::parse("1.2.0-beta.1").unwrap();
+        let next_major = Version::parse("2.0.0-nightly.1").unwrap();
+
+        let channel: ReleaseChannel = "stable".parse().unwrap();
+        assert!(channel.matches(&req, &stable));
+        assert!(!channel.matches(&req, &nightly));
+
+        let channel: ReleaseChannel = "pre-release".parse().unwrap();
+        assert!(channel.matches(&req, &stable));
+        assert!(channel.matches(&req, &nightly));
+        assert!(channel.matches(&req, &beta));
+        assert!(!channel.matches(&req, &next_major));
+
+        let channel: ReleaseChannel = "nightly".parse().unwrap();
+        assert_eq!(channel, ReleaseChannel::Named("nightly".to_string()));
+        assert!(!channel.matches(&req, &stable));
+        assert!(channel.matches(&req, &nightly));
+        assert!(!channel.matches(&req, &beta));
+        assert!(channel.matches(&VersionReq::STAR, &next_major));
+
+        assert!("".parse::<ReleaseChannel>().is_err());
+        assert!("night.ly".parse::<ReleaseChannel>().is_err());
+    }
 }
@@ -277,14 +277,28 @@ impl RecordId {
     }
 
     pub fn operator_record<D: SupportedDigest>(record: &ProtoEnvelope<OperatorRecord>) -> Self {
+        Self::operator_record_content::<D>(record.content_bytes())
+    }
+
+    /// Computes the identifier of an operator record from its encoded content.
+    ///
+    /// The identifier does not depend on the signature of the record.
+    pub(crate) fn operator_record_content<D: SupportedDigest>(content: &[u8]) -> Self {
         let prefix: &[u8] = b"WARG-OPERATOR-LOG-RECORD-V0:".as_slice();
-        let hash: Hash<D> = Hash::of((prefix, record.content_bytes()));
+        let hash: Hash<D> = Hash::of((prefix, content));
         Self(hash.into())
     }
 
     pub fn package_record<D: SupportedDigest>(record: &ProtoEnvelope<PackageRecord>) -> Self {
+        Self::package_record_content::<D>(record.content_bytes())
+    }
+
+    /// Computes the identifier of a package record from its encoded content.
+    ///
+    /// The identifier does not depend on the signature of the record.
+    pub(crate) fn package_record_content<D: SupportedDigest>(content: &[u8]) -> Self {
         let prefix: &[u8] = b"WARG-PACKAGE-LOG-RECORD-V0:".as_slice();
-        let hash: Hash<D> = Hash::of((prefix, record.content_bytes()));
+        let hash: Hash<D> = Hash::of((prefix, content));
         Self(hash.into())
     }
 }
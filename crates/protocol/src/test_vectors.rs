@@ -0,0 +1,253 @@
+//! Module for canonicalization test vectors.
+//!
+//! A test vector describes the exact bytes that are encoded, hashed and
+//! signed for a package record, operator record or checkpoint, so that
+//! alternative implementations of the protocol can check that they are
+//! byte-for-byte compatible with this one.
+//!
+//! Test vectors are generated deterministically with [`generate`]; the
+//! golden test vectors are found in `tests/test-vectors.json` of this crate.
+use crate::{
+    operator::{self, OPERATOR_RECORD_VERSION},
+    package::{self, PACKAGE_RECORD_VERSION},
+    registry::{Checkpoint, RecordId, TimestampedCheckpoint},
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{base64::Base64, serde_as};
+use std::{str::FromStr, time::Duration, time::SystemTime};
+use warg_crypto::{
+    hash::{AnyHash, Hash, HashAlgorithm, Sha256},
+    signing, Encode, Signable,
+};
+use warg_protobuf::protocol as protobuf;
+
+/// The private key that signs test vectors.
+///
+/// This key was generated for test purposes only.
+pub const TEST_VECTOR_KEY: &str = "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=";
+
+/// The public key that is granted permissions in test vectors.
+const GRANTED_KEY: &str = "ecdsa-p256:A5qc6uBi070EBb4GihGzpx6Cm5+oZnv4dWpBhhuZVagu";
+
+/// Represents the input of a test vector.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "input", rename_all = "camelCase")]
+#[allow(clippy::large_enum_variant)]
+pub enum TestVectorInput {
+    /// A package record in its protobuf JSON representation.
+    PackageRecord(protobuf::PackageRecord),
+    /// An operator record in its protobuf JSON representation.
+    OperatorRecord(protobuf::OperatorRecord),
+    /// A timestamped checkpoint.
+    Checkpoint(TimestampedCheckpoint),
+}
+
+/// Represents a canonicalization test vector.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestVector {
+    /// The name of the test vector.
+    pub name: String,
+    /// The input of the test vector.
+    #[serde(flatten)]
+    pub input: TestVectorInput,
+    /// The canonical encoding of the input.
+    ///
+    /// For records, these are the content bytes of the record envelope.
+    #[serde_as(as = "Base64")]
+    pub encoded: Vec<u8>,
+    /// The exact bytes that are signed for the input.
+    #[serde_as(as = "Base64")]
+    pub signable: Vec<u8>,
+    /// The identifier of the input.
+    ///
+    /// For records, this is the record identifier; for checkpoints, this is
+    /// the hash of the checkpoint without its timestamp.
+    pub id: AnyHash,
+    /// The signature of the signable bytes by [`TEST_VECTOR_KEY`].
+    ///
+    /// Signatures are deterministic (RFC 6979).
+    pub signature: signing::Signature,
+}
+
+impl TestVector {
+    fn new<T: Signable>(
+        name: &str,
+        input: TestVectorInput,
+        value: &T,
+        id: AnyHash,
+        key: &signing::PrivateKey,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            input,
+            encoded: value.encode(),
+            signable: value.signable_bytes(),
+            id,
+            signature: value
+                .sign(key)
+                .expect("test vector should be signed with the test key"),
+        }
+    }
+
+    fn package_record(
+        name: &str,
+        record: &package::PackageRecord,
+        key: &signing::PrivateKey,
+    ) -> Self {
+        let id = RecordId::package_record_content::<Sha256>(&record.encode());
+        Self::new(
+            name,
+            TestVectorInput::PackageRecord(record.into()),
+            record,
+            id.into(),
+            key,
+        )
+    }
+
+    fn operator_record(
+        name: &str,
+        record: &operator::OperatorRecord,
+        key: &signing::PrivateKey,
+    ) -> Self {
+        let id = RecordId::operator_record_content::<Sha256>(&record.encode());
+        Self::new(
+            name,
+            TestVectorInput::OperatorRecord(record.into()),
+            record,
+            id.into(),
+            key,
+        )
+    }
+
+    fn checkpoint(
+        name: &str,
+        checkpoint: TimestampedCheckpoint,
+        key: &signing::PrivateKey,
+    ) -> Self {
+        let id = Hash::<Sha256>::of(&checkpoint.checkpoint).into();
+        Self::new(
+            name,
+            TestVectorInput::Checkpoint(checkpoint.clone()),
+            &checkpoint,
+            id,
+            key,
+        )
+    }
+}
+
+/// Generates the canonicalization test vectors.
+///
+/// The inputs and outputs of the test vectors are deterministic, so the
+/// generated test vectors only change when the canonical encodings change.
+pub fn generate() -> Vec<TestVector> {
+    let key = signing::PrivateKey::decode(TEST_VECTOR_KEY.to_string())
+        .expect("test vector key should be valid");
+    let granted = signing::PublicKey::from_str(GRANTED_KEY).expect("granted key should be valid");
+    let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let content = HashAlgorithm::Sha256.digest(b"test vector content");
+
+    let package_init = package::PackageRecord {
+        prev: None,
+        version: PACKAGE_RECORD_VERSION,
+        timestamp,
+        entries: vec![package::PackageEntry::Init {
+            hash_algorithm: HashAlgorithm::Sha256,
+            key: key.public_key(),
+        }],
+    };
+    let package_release = package::PackageRecord {
+        prev: Some(RecordId::package_record_content::<Sha256>(
+            &package_init.encode(),
+        )),
+        version: PACKAGE_RECORD_VERSION,
+        timestamp: timestamp + Duration::from_nanos(1),
+        entries: vec![
+            package::PackageEntry::GrantFlat {
+                key: granted.clone(),
+                permissions: vec![package::Permission::Release, package::Permission::Yank],
+            },
+            package::PackageEntry::Release {
+                version: "1.0.0".parse().unwrap(),
+                content: content.clone(),
+                content_type: package::ContentType::Component,
+            },
+            package::PackageEntry::Attest {
+                version: "1.0.0".parse().unwrap(),
+                kind: "test".to_string(),
+                content: content.clone(),
+            },
+        ],
+    };
+    let package_yank = package::PackageRecord {
+        prev: Some(RecordId::package_record_content::<Sha256>(
+            &package_release.encode(),
+        )),
+        version: PACKAGE_RECORD_VERSION,
+        timestamp: timestamp + Duration::from_secs(1),
+        entries: vec![
+            package::PackageEntry::Yank {
+                version: "1.0.0".parse().unwrap(),
+            },
+            package::PackageEntry::RevokeFlat {
+                key_id: granted.fingerprint(),
+                permissions: vec![package::Permission::Yank],
+            },
+        ],
+    };
+
+    let operator_init = operator::OperatorRecord {
+        prev: None,
+        version: OPERATOR_RECORD_VERSION,
+        timestamp,
+        entries: vec![
+            operator::OperatorEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: key.public_key(),
+            },
+            operator::OperatorEntry::GrantFlat {
+                key: granted.clone(),
+                permissions: vec![operator::Permission::Commit],
+            },
+        ],
+    };
+    let operator_namespaces = operator::OperatorRecord {
+        prev: Some(RecordId::operator_record_content::<Sha256>(
+            &operator_init.encode(),
+        )),
+        version: OPERATOR_RECORD_VERSION,
+        timestamp: timestamp + Duration::from_secs(1),
+        entries: vec![
+            operator::OperatorEntry::DefineNamespace {
+                namespace: "test".to_string(),
+            },
+            operator::OperatorEntry::ImportNamespace {
+                namespace: "imported".to_string(),
+                registry: "registry.example.com".to_string(),
+            },
+            operator::OperatorEntry::RevokeFlat {
+                key_id: granted.fingerprint(),
+                permissions: vec![operator::Permission::Commit],
+            },
+        ],
+    };
+
+    let checkpoint = TimestampedCheckpoint {
+        checkpoint: Checkpoint {
+            log_root: HashAlgorithm::Sha256.digest(b"log root"),
+            log_length: 3,
+            map_root: HashAlgorithm::Sha256.digest(b"map root"),
+        },
+        timestamp: 1_700_000_001,
+    };
+
+    vec![
+        TestVector::package_record("package-init", &package_init, &key),
+        TestVector::package_record("package-release", &package_release, &key),
+        TestVector::package_record("package-yank", &package_yank, &key),
+        TestVector::operator_record("operator-init", &operator_init, &key),
+        TestVector::operator_record("operator-namespaces", &operator_namespaces, &key),
+        TestVector::checkpoint("checkpoint", checkpoint, &key),
+    ]
+}
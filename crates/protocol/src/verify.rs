@@ -0,0 +1,317 @@
+//! Verification of checkpoints and the proofs of their logs.
+//!
+//! The functions of this module only verify data already obtained from a
+//! registry; they never perform I/O. Environments that cannot run the
+//! registry client (e.g. a Wasm sandbox verifying its own dependencies) may
+//! use them to verify checkpoints and proofs obtained by other means.
+
+use crate::{
+    registry::{Checkpoint, LogId, LogLeaf, MapLeaf, TimestampedCheckpoint},
+    SerdeEnvelope,
+};
+use thiserror::Error;
+use warg_crypto::{
+    hash::{AnyHash, HashError, Sha256},
+    signing::{KeyID, PublicKey, SignatureError},
+    Encode, Signable,
+};
+use warg_transparency::{
+    log::{ConsistencyProofError, InclusionProofError, LogProofBundle},
+    map::{MapMultiProof, MapProofBundle, Proof as MapProof},
+};
+
+/// An error that occurred while verifying a checkpoint or proof.
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    /// The checkpoint was not signed by the given key.
+    #[error("checkpoint was signed by key `{found}` but the key `{expected}` was given")]
+    CheckpointKeyMismatch {
+        /// The key id of the given key.
+        expected: KeyID,
+        /// The key id the checkpoint was signed by.
+        found: KeyID,
+    },
+    /// The signature of the checkpoint is invalid.
+    #[error("checkpoint signature is invalid: {0}")]
+    InvalidCheckpointSignature(#[from] SignatureError),
+    /// A proof could not be decoded.
+    #[error("failed to decode proof: {0:#}")]
+    InvalidProof(anyhow::Error),
+    /// A proof bundle did not contain the expected proofs.
+    #[error("unexpected proof bundle: {0}")]
+    UnexpectedBundle(String),
+    /// A root of the checkpoint is not a supported hash.
+    #[error("checkpoint has an invalid root: {0}")]
+    Hash(#[from] HashError),
+    /// A log inclusion proof could not be evaluated.
+    #[error("failed to evaluate inclusion proof: {0}")]
+    InclusionProof(#[from] InclusionProofError),
+    /// A log consistency proof could not be evaluated.
+    #[error("failed to evaluate consistency proof: {0}")]
+    ConsistencyProof(#[from] ConsistencyProofError),
+    /// An inclusion proof evaluated to a root other than the checkpoint's.
+    #[error("failed to prove inclusion: found root `{found}` but was given root `{root}`")]
+    IncorrectInclusionProof {
+        /// The root of the checkpoint.
+        root: AnyHash,
+        /// The root the proof evaluated to.
+        found: AnyHash,
+    },
+    /// A consistency proof evaluated to a root other than the given one.
+    #[error("failed to prove consistency: found root `{found}` but was given root `{root}`")]
+    IncorrectConsistencyProof {
+        /// The given root.
+        root: AnyHash,
+        /// The root the proof evaluated to.
+        found: AnyHash,
+    },
+}
+
+/// Verifies the signature of a checkpoint with the key that signed it.
+///
+/// The key is typically found in the operator log state by the key id of
+/// the checkpoint.
+pub fn verify_checkpoint(
+    checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    key: &PublicKey,
+) -> Result<(), VerifyError> {
+    let expected = key.fingerprint();
+    if &expected != checkpoint.key_id() {
+        return Err(VerifyError::CheckpointKeyMismatch {
+            expected,
+            found: checkpoint.key_id().clone(),
+        });
+    }
+
+    TimestampedCheckpoint::verify(key, &checkpoint.as_ref().encode(), checkpoint.signature())?;
+    Ok(())
+}
+
+/// Verifies the inclusion of log leafs in a checkpoint.
+///
+/// `log` is the encoded log proof bundle and `map` the encoded map proof
+/// bundle of an inclusion proof response, with one proof per leaf in the
+/// order of `leafs`.
+pub fn verify_inclusion(
+    checkpoint: &Checkpoint,
+    leafs: &[LogLeaf],
+    log: &[u8],
+    map: &[u8],
+) -> Result<(), VerifyError> {
+    let map = MapProofBundle::decode(map).map_err(VerifyError::InvalidProof)?;
+    verify_inclusion_proofs(checkpoint, leafs, log, map.unbundle())
+}
+
+/// Verifies the inclusion of log leafs in a checkpoint with a map
+/// multi-proof.
+///
+/// This is [`verify_inclusion`] for responses whose map proofs are encoded
+/// as a single multi-proof.
+pub fn verify_multi_inclusion(
+    checkpoint: &Checkpoint,
+    leafs: &[LogLeaf],
+    log: &[u8],
+    map: &[u8],
+) -> Result<(), VerifyError> {
+    let map = MapMultiProof::decode(map).map_err(VerifyError::InvalidProof)?;
+    verify_inclusion_proofs(checkpoint, leafs, log, map.into_proofs())
+}
+
+fn verify_inclusion_proofs(
+    checkpoint: &Checkpoint,
+    leafs: &[LogLeaf],
+    log: &[u8],
+    map: Vec<MapProof<Sha256, LogId, MapLeaf>>,
+) -> Result<(), VerifyError> {
+    let bundle: LogProofBundle<Sha256, LogLeaf> =
+        LogProofBundle::decode(log).map_err(VerifyError::InvalidProof)?;
+    let (log_data, _, log_inclusions) = bundle.unbundle();
+    if log_inclusions.len() != leafs.len() || map.len() != leafs.len() {
+        return Err(VerifyError::UnexpectedBundle(format!(
+            "expected {expected} log and map proofs but found {log} log and {map} map proofs",
+            expected = leafs.len(),
+            log = log_inclusions.len(),
+            map = map.len(),
+        )));
+    }
+
+    let log_root = checkpoint.log_root.clone().try_into()?;
+    for (leaf, proof) in leafs.iter().zip(log_inclusions.iter()) {
+        let found = proof.evaluate_value(&log_data, leaf)?;
+        if found != log_root {
+            return Err(VerifyError::IncorrectInclusionProof {
+                root: checkpoint.log_root.clone(),
+                found: found.into(),
+            });
+        }
+    }
+
+    let map_root = checkpoint.map_root.clone().try_into()?;
+    for (leaf, proof) in leafs.iter().zip(map.iter()) {
+        let found = proof.evaluate(
+            &leaf.log_id,
+            &MapLeaf {
+                record_id: leaf.record_id.clone(),
+            },
+        );
+        if found != map_root {
+            return Err(VerifyError::IncorrectInclusionProof {
+                root: checkpoint.map_root.clone(),
+                found: found.into(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies that a log with the root `from` is a prefix of the log with the
+/// root `to`.
+///
+/// `proof` is the encoded log proof bundle of a consistency proof response.
+pub fn verify_consistency(from: &AnyHash, to: &AnyHash, proof: &[u8]) -> Result<(), VerifyError> {
+    let bundle: LogProofBundle<Sha256, LogLeaf> =
+        LogProofBundle::decode(proof).map_err(VerifyError::InvalidProof)?;
+    let (log_data, consistencies, inclusions) = bundle.unbundle();
+    if !inclusions.is_empty() {
+        return Err(VerifyError::UnexpectedBundle(
+            "expected no inclusion proofs".into(),
+        ));
+    }
+
+    let [consistency] = consistencies.as_slice() else {
+        return Err(VerifyError::UnexpectedBundle(
+            "expected exactly one consistency proof".into(),
+        ));
+    };
+
+    let (found_from, found_to) = consistency.evaluate(&log_data)?;
+    for (root, found) in [(from, found_from), (to, found_to)] {
+        let found = AnyHash::from(found);
+        if root != &found {
+            return Err(VerifyError::IncorrectConsistencyProof {
+                root: root.clone(),
+                found,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::RecordId;
+    use warg_crypto::{hash::HashAlgorithm, signing::generate_p256_pair};
+    use warg_transparency::{
+        log::{LogBuilder, LogData, Node, VecLog},
+        map::Map,
+    };
+
+    fn leaf(n: u8) -> LogLeaf {
+        LogLeaf {
+            log_id: LogId::from(HashAlgorithm::Sha256.digest(&[n])),
+            record_id: RecordId::from(HashAlgorithm::Sha256.digest(&[n, n])),
+        }
+    }
+
+    fn registry(leafs: &[LogLeaf]) -> (VecLog<Sha256, LogLeaf>, Map<Sha256, LogId, MapLeaf>) {
+        let mut log = VecLog::default();
+        let mut map = Map::default();
+        for leaf in leafs {
+            log.push(leaf);
+            map = map.insert(
+                leaf.log_id.clone(),
+                MapLeaf {
+                    record_id: leaf.record_id.clone(),
+                },
+            );
+        }
+        (log, map)
+    }
+
+    fn checkpoint(log: &VecLog<Sha256, LogLeaf>, map: &Map<Sha256, LogId, MapLeaf>) -> Checkpoint {
+        let log_checkpoint = log.checkpoint();
+        Checkpoint {
+            log_root: log_checkpoint.root().into(),
+            log_length: log_checkpoint.length() as _,
+            map_root: map.root().clone().into(),
+        }
+    }
+
+    #[test]
+    fn verifies_checkpoint_signatures() {
+        let (public_key, private_key) = generate_p256_pair();
+        let (other_key, _) = generate_p256_pair();
+        let (log, map) = registry(&[leaf(0)]);
+        let checkpoint = TimestampedCheckpoint {
+            checkpoint: checkpoint(&log, &map),
+            timestamp: 0,
+        };
+        let envelope = SerdeEnvelope::signed_contents(&private_key, checkpoint).unwrap();
+
+        verify_checkpoint(&envelope, &public_key).unwrap();
+        assert!(matches!(
+            verify_checkpoint(&envelope, &other_key),
+            Err(VerifyError::CheckpointKeyMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verifies_inclusion() {
+        let leafs = [leaf(0), leaf(1), leaf(2)];
+        let (log, map) = registry(&leafs);
+        let checkpoint = checkpoint(&log, &map);
+        let length = leafs.len();
+
+        let proofs = (0..length)
+            .map(|i| log.prove_inclusion(Node(i * 2), length))
+            .collect();
+        let log_bundle = LogProofBundle::bundle(vec![], proofs, &log)
+            .unwrap()
+            .encode();
+        let map_proofs = || {
+            leafs
+                .iter()
+                .map(|leaf| map.prove(leaf.log_id.clone()).unwrap())
+                .collect()
+        };
+        let map_bundle = MapProofBundle::bundle(map_proofs()).encode();
+        let multi_proof = MapMultiProof::new(map_proofs()).encode();
+
+        verify_inclusion(&checkpoint, &leafs, &log_bundle, &map_bundle).unwrap();
+        verify_multi_inclusion(&checkpoint, &leafs, &log_bundle, &multi_proof).unwrap();
+
+        // A leaf that is not in the registry is not proven
+        let mut wrong = leafs.clone();
+        wrong[1] = leaf(3);
+        assert!(verify_inclusion(&checkpoint, &wrong, &log_bundle, &map_bundle).is_err());
+
+        // Every leaf must have a proof
+        assert!(matches!(
+            verify_inclusion(&checkpoint, &[leafs[0].clone()], &log_bundle, &map_bundle),
+            Err(VerifyError::UnexpectedBundle(_))
+        ));
+    }
+
+    #[test]
+    fn verifies_consistency() {
+        let leafs = [leaf(0), leaf(1), leaf(2), leaf(3), leaf(4)];
+        let (old, _) = registry(&leafs[..2]);
+        let (new, _) = registry(&leafs);
+        let from = AnyHash::from(old.checkpoint().root());
+        let to = AnyHash::from(new.checkpoint().root());
+
+        let proof = new.prove_consistency(2, leafs.len());
+        let bundle = LogProofBundle::bundle(vec![proof], vec![], &new)
+            .unwrap()
+            .encode();
+
+        verify_consistency(&from, &to, &bundle).unwrap();
+        assert!(matches!(
+            verify_consistency(&to, &to, &bundle),
+            Err(VerifyError::IncorrectConsistencyProof { .. })
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use pretty_assertions::assert_eq;
+use std::fs;
+use warg_crypto::{
+    hash::{AnyHash, Hash, Sha256},
+    signing, Decode, Signable,
+};
+use warg_protocol::{
+    operator, package,
+    registry::{RecordId, TimestampedCheckpoint},
+    test_vectors::{self, TestVector, TestVectorInput, TEST_VECTOR_KEY},
+    ProtoEnvelope,
+};
+
+const TEST_VECTORS_PATH: &str = "./tests/test-vectors.json";
+
+#[test]
+fn test_golden_vectors() {
+    let vectors = test_vectors::generate();
+
+    if std::env::var_os("BLESS").is_some() {
+        // Update the test baseline
+        fs::write(
+            TEST_VECTORS_PATH,
+            serde_json::to_string_pretty(&vectors).unwrap(),
+        )
+        .unwrap();
+    } else {
+        let golden: Vec<TestVector> =
+            serde_json::from_str(&fs::read_to_string(TEST_VECTORS_PATH).unwrap()).unwrap();
+        assert_eq!(golden, vectors);
+    }
+}
+
+#[test]
+fn test_vectors_match_envelopes() {
+    let key = signing::PrivateKey::decode(TEST_VECTOR_KEY.to_string()).unwrap();
+    let public_key = key.public_key();
+
+    for vector in test_vectors::generate() {
+        match &vector.input {
+            TestVectorInput::PackageRecord(input) => {
+                let record = package::PackageRecord::try_from(input.clone()).unwrap();
+                let envelope = ProtoEnvelope::signed_contents(&key, record).unwrap();
+                assert_eq!(envelope.content_bytes(), vector.encoded, "{}", vector.name);
+                assert_eq!(
+                    RecordId::package_record::<Sha256>(&envelope),
+                    vector.id.clone().into(),
+                    "{}",
+                    vector.name
+                );
+                assert_eq!(
+                    package::PackageRecord::decode(&vector.encoded).unwrap(),
+                    *envelope.as_ref()
+                );
+                package::PackageRecord::verify(&public_key, &vector.encoded, &vector.signature)
+                    .unwrap();
+            }
+            TestVectorInput::OperatorRecord(input) => {
+                let record = operator::OperatorRecord::try_from(input.clone()).unwrap();
+                let envelope = ProtoEnvelope::signed_contents(&key, record).unwrap();
+                assert_eq!(envelope.content_bytes(), vector.encoded, "{}", vector.name);
+                assert_eq!(
+                    RecordId::operator_record::<Sha256>(&envelope),
+                    vector.id.clone().into(),
+                    "{}",
+                    vector.name
+                );
+                operator::OperatorRecord::verify(&public_key, &vector.encoded, &vector.signature)
+                    .unwrap();
+            }
+            TestVectorInput::Checkpoint(checkpoint) => {
+                assert_eq!(
+                    warg_crypto::Encode::encode(checkpoint),
+                    vector.encoded,
+                    "{}",
+                    vector.name
+                );
+                assert_eq!(
+                    AnyHash::from(Hash::<Sha256>::of(&checkpoint.checkpoint)),
+                    vector.id
+                );
+                TimestampedCheckpoint::verify(&public_key, &vector.encoded, &vector.signature)
+                    .unwrap();
+            }
+        }
+
+        assert!(vector.signable.ends_with(&vector.encoded));
+    }
+}
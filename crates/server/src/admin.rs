@@ -0,0 +1,345 @@
+//! A client and command line interface for the administration API of the
+//! server.
+//!
+//! The `warg-admin` binary uses this module so operators can manage a
+//! running registry without scripting raw HTTP requests against the admin
+//! API.
+
+use crate::{api::admin::GarbageCollection, services::StaticExport};
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use reqwest::{Method, RequestBuilder};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use std::{fs, path::PathBuf};
+use url::Url;
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    registry::{LogId, PackageName, TimestampedCheckpoint},
+    SerdeEnvelope,
+};
+
+/// A client of the administration API of a server.
+pub struct AdminClient {
+    url: Url,
+    token: SecretString,
+    client: reqwest::Client,
+}
+
+impl AdminClient {
+    /// Creates a client of the admin API of the server at the given URL,
+    /// authenticating requests with the given admin token.
+    pub fn new(url: Url, token: SecretString) -> Result<Self> {
+        let url = url
+            .join("admin/")
+            .with_context(|| format!("invalid server URL `{url}`"))?;
+        Ok(Self {
+            url,
+            token,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Creates a checkpoint without waiting for the server's checkpoint
+    /// interval.
+    ///
+    /// Returns the latest checkpoint once it has been stored.
+    pub async fn create_checkpoint(&self) -> Result<SerdeEnvelope<TimestampedCheckpoint>> {
+        self.send(self.request(Method::POST, "checkpoint")?).await
+    }
+
+    /// Removes the rejected and pending records whose retention window has
+    /// passed.
+    ///
+    /// The server must be configured with a record retention.
+    pub async fn collect_garbage(&self) -> Result<GarbageCollection> {
+        self.send(self.request(Method::POST, "gc")?).await
+    }
+
+    /// Exports the latest checkpoint of the registry as a static registry.
+    ///
+    /// The server must be configured with a static export directory.
+    pub async fn export_static(&self) -> Result<StaticExport> {
+        self.send(self.request(Method::POST, "export")?).await
+    }
+
+    /// Lists the packages flagged for moderation.
+    pub async fn list_package_flags(&self) -> Result<Value> {
+        self.send(self.request(Method::GET, "flags")?).await
+    }
+
+    /// Clears the moderation flag of a package log.
+    pub async fn clear_package_flag(&self, log_id: &LogId) -> Result<()> {
+        self.send_empty(self.request(Method::DELETE, &format!("flags/{log_id}"))?)
+            .await
+    }
+
+    /// Lists the vulnerability advisories of the registry's packages.
+    pub async fn list_advisories(&self) -> Result<Value> {
+        self.send(self.request(Method::GET, "advisories")?).await
+    }
+
+    /// Imports vulnerability advisories from one or more OSV records.
+    ///
+    /// Returns the imported advisories.
+    pub async fn import_osv_advisories(&self, records: &Value) -> Result<Value> {
+        self.send(self.request(Method::POST, "advisories/osv")?.json(records))
+            .await
+    }
+
+    /// Removes a vulnerability advisory of a package.
+    pub async fn remove_advisory(&self, package: &PackageName, id: &str) -> Result<()> {
+        self.send_empty(self.request(Method::DELETE, &format!("advisories/{package}/{id}"))?)
+            .await
+    }
+
+    /// Lists the suspended namespaces.
+    pub async fn list_namespace_suspensions(&self) -> Result<Value> {
+        self.send(self.request(Method::GET, "suspensions")?).await
+    }
+
+    /// Suspends a namespace for the given reason.
+    ///
+    /// Packages of a suspended namespace cannot be published and are hidden
+    /// from listings, but their logs are preserved.
+    pub async fn suspend_namespace(&self, namespace: &str, reason: &str) -> Result<Value> {
+        self.send(
+            self.request(Method::PUT, &format!("suspensions/{namespace}"))?
+                .json(&json!({ "reason": reason })),
+        )
+        .await
+    }
+
+    /// Restores a suspended namespace.
+    pub async fn restore_namespace(&self, namespace: &str) -> Result<()> {
+        self.send_empty(self.request(Method::DELETE, &format!("suspensions/{namespace}"))?)
+            .await
+    }
+
+    /// Checks the federation health of each imported namespace.
+    pub async fn federation_health(&self) -> Result<Value> {
+        self.send(self.request(Method::GET, "federation")?).await
+    }
+
+    fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        let url = self
+            .url
+            .join(path)
+            .with_context(|| format!("invalid admin API path `{path}`"))?;
+        Ok(self
+            .client
+            .request(method, url)
+            .bearer_auth(self.token.expose_secret()))
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let response = check(request.send().await?).await?;
+        response
+            .json()
+            .await
+            .context("failed to deserialize admin API response")
+    }
+
+    async fn send_empty(&self, request: RequestBuilder) -> Result<()> {
+        check(request.send().await?).await?;
+        Ok(())
+    }
+}
+
+/// The body of an admin API error response.
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    match response.json::<ErrorBody>().await {
+        Ok(body) => bail!(
+            "admin API request failed ({status}): {message}",
+            message = body.message
+        ),
+        Err(_) => bail!("admin API request failed ({status})"),
+    }
+}
+
+/// The options of the admin command line interface.
+#[derive(Args, Debug)]
+pub struct AdminOptions {
+    /// The URL of the server.
+    #[arg(long, env = "WARG_ADMIN_URL", default_value = "http://127.0.0.1:8090")]
+    pub url: Url,
+
+    /// The token that authenticates requests to the admin API.
+    #[arg(long, env = "WARG_ADMIN_TOKEN")]
+    pub token: Option<SecretString>,
+
+    /// The path to the token that authenticates requests to the admin API.
+    #[arg(long, env = "WARG_ADMIN_TOKEN_FILE", conflicts_with = "token")]
+    pub token_file: Option<PathBuf>,
+}
+
+impl AdminOptions {
+    /// Creates a client of the admin API from the options.
+    pub fn into_client(self) -> Result<AdminClient> {
+        let token = crate::args::get_opt_secret("token", self.token_file, self.token)?;
+        AdminClient::new(self.url, token.expose_secret().trim().to_string().into())
+    }
+}
+
+/// An operation of the admin API.
+#[derive(Subcommand, Debug)]
+pub enum AdminCommand {
+    /// Create a checkpoint without waiting for the checkpoint interval.
+    Checkpoint,
+    /// Remove rejected and pending records whose retention window has passed.
+    Gc,
+    /// Export the latest checkpoint as a static registry.
+    Export,
+    /// Manage the packages flagged for moderation.
+    #[clap(subcommand)]
+    Flags(FlagsCommand),
+    /// Manage vulnerability advisories.
+    #[clap(subcommand)]
+    Advisories(AdvisoriesCommand),
+    /// Suspend and restore namespaces.
+    #[clap(subcommand)]
+    Namespaces(NamespacesCommand),
+    /// Check the federation health of each imported namespace.
+    Federation,
+}
+
+/// An operation on flagged packages.
+#[derive(Subcommand, Debug)]
+pub enum FlagsCommand {
+    /// List the packages flagged for moderation.
+    List,
+    /// Clear the flag of a package log.
+    Clear {
+        /// The id of the package log.
+        log_id: AnyHash,
+    },
+}
+
+/// An operation on vulnerability advisories.
+#[derive(Subcommand, Debug)]
+pub enum AdvisoriesCommand {
+    /// List the advisories of the registry's packages.
+    List,
+    /// Import advisories from a file of one or more OSV records.
+    Import {
+        /// The path to the OSV records.
+        path: PathBuf,
+    },
+    /// Remove an advisory of a package.
+    Remove {
+        /// The name of the package.
+        package: PackageName,
+        /// The identifier of the advisory.
+        id: String,
+    },
+}
+
+/// An operation on namespaces.
+#[derive(Subcommand, Debug)]
+pub enum NamespacesCommand {
+    /// List the suspended namespaces.
+    Suspended,
+    /// Suspend a namespace, rejecting publishes and hiding its packages.
+    Suspend {
+        /// The namespace to suspend.
+        namespace: String,
+        /// The reason the namespace is suspended.
+        #[arg(long)]
+        reason: String,
+    },
+    /// Restore a suspended namespace.
+    Restore {
+        /// The namespace to restore.
+        namespace: String,
+    },
+}
+
+impl AdminCommand {
+    /// Executes the command against the admin API.
+    pub async fn exec(self, client: &AdminClient) -> Result<()> {
+        match self {
+            Self::Checkpoint => {
+                let checkpoint = client.create_checkpoint().await?;
+                let checkpoint = &checkpoint.as_ref().checkpoint;
+                println!(
+                    "created checkpoint of log length {len} with log root `{log_root}` and map root `{map_root}`",
+                    len = checkpoint.log_length,
+                    log_root = checkpoint.log_root,
+                    map_root = checkpoint.map_root
+                );
+            }
+            Self::Gc => {
+                let collected = client.collect_garbage().await?;
+                println!(
+                    "removed {rejected} rejected and {pending} pending records",
+                    rejected = collected.rejected,
+                    pending = collected.pending
+                );
+            }
+            Self::Export => {
+                let export = client.export_static().await?;
+                println!(
+                    "exported checkpoint of log length {len} with {logs} logs and {content} content files",
+                    len = export.log_length,
+                    logs = export.logs,
+                    content = export.content
+                );
+            }
+            Self::Flags(FlagsCommand::List) => print_json(&client.list_package_flags().await?)?,
+            Self::Flags(FlagsCommand::Clear { log_id }) => {
+                let log_id = LogId::from(log_id);
+                client.clear_package_flag(&log_id).await?;
+                println!("cleared flag of package log `{log_id}`");
+            }
+            Self::Advisories(AdvisoriesCommand::List) => {
+                print_json(&client.list_advisories().await?)?
+            }
+            Self::Advisories(AdvisoriesCommand::Import { path }) => {
+                let contents = fs::read_to_string(&path).with_context(|| {
+                    format!("failed to read OSV records `{path}`", path = path.display())
+                })?;
+                let records = serde_json::from_str(&contents).with_context(|| {
+                    format!(
+                        "failed to parse OSV records `{path}`",
+                        path = path.display()
+                    )
+                })?;
+                print_json(&client.import_osv_advisories(&records).await?)?
+            }
+            Self::Advisories(AdvisoriesCommand::Remove { package, id }) => {
+                client.remove_advisory(&package, &id).await?;
+                println!("removed advisory `{id}` of package `{package}`");
+            }
+            Self::Namespaces(NamespacesCommand::Suspended) => {
+                print_json(&client.list_namespace_suspensions().await?)?
+            }
+            Self::Namespaces(NamespacesCommand::Suspend { namespace, reason }) => {
+                client.suspend_namespace(&namespace, &reason).await?;
+                println!("suspended namespace `{namespace}`");
+            }
+            Self::Namespaces(NamespacesCommand::Restore { namespace }) => {
+                client.restore_namespace(&namespace).await?;
+                println!("restored namespace `{namespace}`");
+            }
+            Self::Federation => print_json(&client.federation_health().await?)?,
+        }
+
+        Ok(())
+    }
+}
+
+fn print_json(value: &Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
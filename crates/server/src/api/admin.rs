@@ -0,0 +1,636 @@
+//! The administration API of the server.
+//!
+//! Requests must have an `Authorization: Bearer <token>` header with the
+//! admin token the server was configured with.
+
+use crate::{
+    api::v1::Json,
+    datastore::{Advisory, DataStoreError, NamespaceSuspension, PackageFlag},
+    policy::record::NameListPolicy,
+    policy::record::NameLists,
+    policy::record::{Team, TeamPolicy, Teams},
+    services::{
+        from_osv, CoreService, FederationConfig, NamespaceHealth, OsvRecord, RecordRetention,
+        RetentionConfig, StaticExport, StaticExporter,
+    },
+};
+use axum::{
+    debug_handler,
+    extract::{Path, Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Router,
+};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use warg_api::v1::package::VulnerabilityAdvisory;
+use warg_crypto::hash::Sha256;
+use warg_protocol::{
+    operator::NamespaceState,
+    registry::{LogId, PackageName, TimestampedCheckpoint},
+    SerdeEnvelope,
+};
+
+/// The configuration of the administration API.
+#[derive(Clone)]
+pub struct Config {
+    token: Arc<SecretString>,
+    core: CoreService,
+    name_lists: Option<NameListPolicy>,
+    teams: Option<TeamPolicy>,
+    federation: Option<Arc<FederationConfig>>,
+    retention: Option<RetentionConfig>,
+    exporter: Option<Arc<StaticExporter>>,
+    client: reqwest::Client,
+}
+
+impl Config {
+    /// Creates an administration API configuration that authenticates
+    /// requests with the given token.
+    pub fn new(token: SecretString, core: CoreService) -> Self {
+        Self {
+            token: Arc::new(token),
+            core,
+            name_lists: None,
+            teams: None,
+            federation: None,
+            retention: None,
+            exporter: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Sets the name list policy managed by the API.
+    pub fn with_name_lists(mut self, policy: NameListPolicy) -> Self {
+        self.name_lists = Some(policy);
+        self
+    }
+
+    /// Sets the team policy managed by the API.
+    pub fn with_teams(mut self, policy: TeamPolicy) -> Self {
+        self.teams = Some(policy);
+        self
+    }
+
+    /// Sets the federation configuration whose health is reported by the
+    /// API.
+    pub fn with_federation(mut self, config: FederationConfig) -> Self {
+        self.federation = Some(Arc::new(config));
+        self
+    }
+
+    /// Sets the record retention applied when garbage is collected through
+    /// the API.
+    pub fn with_retention(mut self, config: RetentionConfig) -> Self {
+        self.retention = Some(config);
+        self
+    }
+
+    /// Sets the exporter of the registry as a static registry used by the
+    /// API.
+    pub fn with_static_exporter(mut self, exporter: Arc<StaticExporter>) -> Self {
+        self.exporter = Some(exporter);
+        self
+    }
+
+    pub(crate) fn into_router(self) -> Router {
+        Router::new()
+            .route("/names", get(get_name_lists).put(set_name_lists))
+            .route("/teams", get(get_teams))
+            .route("/teams/:namespace/:team", put(set_team).delete(remove_team))
+            .route("/flags", get(list_package_flags))
+            .route("/flags/:log_id", delete(clear_package_flag))
+            .route("/advisories", get(list_advisories).put(publish_advisory))
+            .route("/advisories/osv", post(import_osv_advisories))
+            .route("/advisories/:package/:id", delete(remove_advisory))
+            .route("/suspensions", get(list_namespace_suspensions))
+            .route(
+                "/suspensions/:namespace",
+                put(suspend_namespace).delete(restore_namespace),
+            )
+            .route("/federation", get(federation_health))
+            .route("/checkpoint", post(create_checkpoint))
+            .route("/gc", post(collect_garbage))
+            .route("/export", post(export_static_registry))
+            .route_layer(middleware::from_fn_with_state(self.clone(), authenticate))
+            .with_state(self)
+    }
+
+    fn name_lists(&self) -> Result<&NameListPolicy, AdminError> {
+        self.name_lists.as_ref().ok_or_else(|| {
+            AdminError::new(
+                StatusCode::NOT_FOUND,
+                "the server has no name list policy configured",
+            )
+        })
+    }
+
+    fn teams(&self) -> Result<&TeamPolicy, AdminError> {
+        self.teams.as_ref().ok_or_else(|| {
+            AdminError::new(
+                StatusCode::NOT_FOUND,
+                "the server has no team policy configured",
+            )
+        })
+    }
+
+    fn federation(&self) -> Result<&FederationConfig, AdminError> {
+        self.federation.as_deref().ok_or_else(|| {
+            AdminError::new(
+                StatusCode::NOT_FOUND,
+                "the server has no federation configured",
+            )
+        })
+    }
+
+    fn exporter(&self) -> Result<&StaticExporter, AdminError> {
+        self.exporter.as_deref().ok_or_else(|| {
+            AdminError::new(
+                StatusCode::NOT_FOUND,
+                "the server has no static export directory configured",
+            )
+        })
+    }
+
+    fn retention(&self) -> Result<&RetentionConfig, AdminError> {
+        self.retention.as_ref().ok_or_else(|| {
+            AdminError::new(
+                StatusCode::NOT_FOUND,
+                "the server has no record retention configured",
+            )
+        })
+    }
+}
+
+/// The records removed by a garbage collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GarbageCollection {
+    /// The number of rejected records removed.
+    pub rejected: usize,
+    /// The number of pending records removed.
+    pub pending: usize,
+}
+
+#[derive(Serialize)]
+struct AdminError {
+    status: u16,
+    message: String,
+}
+
+impl AdminError {
+    fn new(status: StatusCode, message: impl ToString) -> Self {
+        Self {
+            status: status.as_u16(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<DataStoreError> for AdminError {
+    fn from(e: DataStoreError) -> Self {
+        tracing::error!("unexpected data store error: {e}");
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "an error occurred while processing the request",
+        )
+    }
+}
+
+impl IntoResponse for AdminError {
+    fn into_response(self) -> Response {
+        (StatusCode::from_u16(self.status).unwrap(), Json(self)).into_response()
+    }
+}
+
+async fn authenticate(State(config): State<Config>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| {
+            constant_time_eq(token.as_bytes(), config.token.expose_secret().as_bytes())
+        });
+
+    if !authorized {
+        return AdminError::new(StatusCode::UNAUTHORIZED, "a valid admin token is required")
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Compares two byte strings in time independent of their contents.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[debug_handler]
+async fn get_name_lists(State(config): State<Config>) -> Result<Json<NameLists>, AdminError> {
+    Ok(Json(config.name_lists()?.lists()))
+}
+
+#[debug_handler]
+async fn set_name_lists(
+    State(config): State<Config>,
+    Json(lists): Json<NameLists>,
+) -> Result<Json<NameLists>, AdminError> {
+    let policy = config.name_lists()?;
+    policy
+        .set_lists(lists)
+        .map_err(|e| AdminError::new(StatusCode::BAD_REQUEST, e))?;
+
+    tracing::info!("name lists were updated through the admin API");
+    Ok(Json(policy.lists()))
+}
+
+#[debug_handler]
+async fn get_teams(State(config): State<Config>) -> Result<Json<Teams>, AdminError> {
+    Ok(Json(config.teams()?.teams()))
+}
+
+#[debug_handler]
+async fn set_team(
+    State(config): State<Config>,
+    Path((namespace, name)): Path<(String, String)>,
+    Json(team): Json<Team>,
+) -> Result<Json<Team>, AdminError> {
+    config
+        .teams()?
+        .set_team(&namespace, &name, team.clone())
+        .map_err(|e| AdminError::new(StatusCode::BAD_REQUEST, e))?;
+
+    tracing::info!("team `{name}` of namespace `{namespace}` was updated through the admin API");
+    Ok(Json(team))
+}
+
+#[debug_handler]
+async fn remove_team(
+    State(config): State<Config>,
+    Path((namespace, name)): Path<(String, String)>,
+) -> Result<StatusCode, AdminError> {
+    if !config.teams()?.remove_team(&namespace, &name) {
+        return Err(AdminError::new(
+            StatusCode::NOT_FOUND,
+            format!("namespace `{namespace}` has no team `{name}`"),
+        ));
+    }
+
+    tracing::info!("team `{name}` of namespace `{namespace}` was removed through the admin API");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A package flagged for moderation.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageFlagResponse {
+    log_id: LogId,
+    name: PackageName,
+    similar_to: PackageName,
+    reason: String,
+    /// The time the package was flagged, in seconds since the Unix epoch.
+    flagged_at: u64,
+}
+
+/// A vulnerability advisory of a package.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AdvisoryBody {
+    package: PackageName,
+    #[serde(flatten)]
+    advisory: VulnerabilityAdvisory,
+    /// The time the advisory was published, in seconds since the Unix epoch.
+    #[serde(default, skip_deserializing)]
+    published_at: u64,
+}
+
+impl From<Advisory> for AdvisoryBody {
+    fn from(advisory: Advisory) -> Self {
+        Self {
+            package: advisory.package,
+            advisory: advisory.advisory,
+            published_at: unix_seconds(advisory.published_at),
+        }
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl From<PackageFlag> for PackageFlagResponse {
+    fn from(flag: PackageFlag) -> Self {
+        Self {
+            log_id: flag.log_id,
+            name: flag.name,
+            similar_to: flag.similar_to,
+            reason: flag.reason,
+            flagged_at: unix_seconds(flag.flagged_at),
+        }
+    }
+}
+
+#[debug_handler]
+async fn list_package_flags(
+    State(config): State<Config>,
+) -> Result<Json<Vec<PackageFlagResponse>>, AdminError> {
+    let flags = config.core.store().list_package_flags().await?;
+    Ok(Json(flags.into_iter().map(Into::into).collect()))
+}
+
+#[debug_handler]
+async fn clear_package_flag(
+    State(config): State<Config>,
+    Path(log_id): Path<LogId>,
+) -> Result<StatusCode, AdminError> {
+    if !config.core.store().remove_package_flag(&log_id).await? {
+        return Err(AdminError::new(
+            StatusCode::NOT_FOUND,
+            format!("package log `{log_id}` is not flagged"),
+        ));
+    }
+
+    tracing::info!("flag of package log `{log_id}` was cleared through the admin API");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[debug_handler]
+async fn list_advisories(
+    State(config): State<Config>,
+) -> Result<Json<Vec<AdvisoryBody>>, AdminError> {
+    let advisories = config.core.store().list_advisories(None).await?;
+    Ok(Json(advisories.into_iter().map(Into::into).collect()))
+}
+
+#[debug_handler]
+async fn publish_advisory(
+    State(config): State<Config>,
+    Json(body): Json<AdvisoryBody>,
+) -> Result<Json<AdvisoryBody>, AdminError> {
+    if body.advisory.id.trim().is_empty() {
+        return Err(AdminError::new(
+            StatusCode::BAD_REQUEST,
+            "the advisory identifier cannot be empty",
+        ));
+    }
+
+    let advisory = Advisory {
+        package: body.package,
+        advisory: body.advisory,
+        published_at: SystemTime::now(),
+    };
+    config.core.store().store_advisory(&advisory).await?;
+
+    tracing::info!(
+        "advisory `{id}` for package `{package}` was published through the admin API",
+        id = advisory.advisory.id,
+        package = advisory.package
+    );
+    Ok(Json(advisory.into()))
+}
+
+/// A single OSV record or a list of OSV records.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OsvRecords {
+    One(Box<OsvRecord>),
+    Many(Vec<OsvRecord>),
+}
+
+/// Imports the advisories of the registry's packages from OSV records.
+///
+/// Records are validated before any advisory is stored, so an invalid
+/// record imports nothing.
+#[debug_handler]
+async fn import_osv_advisories(
+    State(config): State<Config>,
+    Json(records): Json<OsvRecords>,
+) -> Result<Json<Vec<AdvisoryBody>>, AdminError> {
+    let records = match records {
+        OsvRecords::One(record) => vec![*record],
+        OsvRecords::Many(records) => records,
+    };
+
+    let mut advisories = Vec::new();
+    for record in &records {
+        advisories.extend(from_osv(record).map_err(|e| {
+            AdminError::new(
+                StatusCode::BAD_REQUEST,
+                format!("invalid OSV record `{id}`: {e:#}", id = record.id),
+            )
+        })?);
+    }
+
+    let store = config.core.store();
+    for advisory in &advisories {
+        store.store_advisory(advisory).await?;
+    }
+
+    tracing::info!(
+        "{count} advisories were imported from {records} OSV records through the admin API",
+        count = advisories.len(),
+        records = records.len()
+    );
+    Ok(Json(advisories.into_iter().map(Into::into).collect()))
+}
+
+#[debug_handler]
+async fn remove_advisory(
+    State(config): State<Config>,
+    Path((package, id)): Path<(PackageName, String)>,
+) -> Result<StatusCode, AdminError> {
+    if !config.core.store().remove_advisory(&package, &id).await? {
+        return Err(AdminError::new(
+            StatusCode::NOT_FOUND,
+            format!("package `{package}` has no advisory `{id}`"),
+        ));
+    }
+
+    tracing::info!("advisory `{id}` for package `{package}` was removed through the admin API");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A suspended namespace.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NamespaceSuspensionResponse {
+    namespace: String,
+    reason: String,
+    /// The time the namespace was suspended, in seconds since the Unix epoch.
+    suspended_at: u64,
+}
+
+impl From<NamespaceSuspension> for NamespaceSuspensionResponse {
+    fn from(suspension: NamespaceSuspension) -> Self {
+        Self {
+            namespace: suspension.namespace,
+            reason: suspension.reason,
+            suspended_at: unix_seconds(suspension.suspended_at),
+        }
+    }
+}
+
+/// The body of a request to suspend a namespace.
+#[derive(Deserialize)]
+struct SuspendNamespaceRequest {
+    reason: String,
+}
+
+#[debug_handler]
+async fn list_namespace_suspensions(
+    State(config): State<Config>,
+) -> Result<Json<Vec<NamespaceSuspensionResponse>>, AdminError> {
+    let suspensions = config.core.store().list_namespace_suspensions().await?;
+    Ok(Json(suspensions.into_iter().map(Into::into).collect()))
+}
+
+/// Suspends a namespace, rejecting publishes of its packages and hiding
+/// them from listings while preserving their logs.
+#[debug_handler]
+async fn suspend_namespace(
+    State(config): State<Config>,
+    Path(namespace): Path<String>,
+    Json(body): Json<SuspendNamespaceRequest>,
+) -> Result<Json<NamespaceSuspensionResponse>, AdminError> {
+    if body.reason.trim().is_empty() {
+        return Err(AdminError::new(
+            StatusCode::BAD_REQUEST,
+            "the suspension reason cannot be empty",
+        ));
+    }
+
+    let store = config.core.store();
+    let namespaces = store
+        .get_namespaces(&LogId::operator_log::<Sha256>())
+        .await?;
+    if !namespaces.iter().any(|(name, _)| *name == namespace) {
+        return Err(AdminError::new(
+            StatusCode::NOT_FOUND,
+            format!("namespace `{namespace}` is not defined on the registry"),
+        ));
+    }
+
+    let suspension = NamespaceSuspension {
+        namespace,
+        reason: body.reason,
+        suspended_at: SystemTime::now(),
+    };
+    store.store_namespace_suspension(&suspension).await?;
+
+    tracing::info!(
+        "namespace `{namespace}` was suspended through the admin API",
+        namespace = suspension.namespace
+    );
+    Ok(Json(suspension.into()))
+}
+
+/// Restores a suspended namespace.
+#[debug_handler]
+async fn restore_namespace(
+    State(config): State<Config>,
+    Path(namespace): Path<String>,
+) -> Result<StatusCode, AdminError> {
+    if !config
+        .core
+        .store()
+        .remove_namespace_suspension(&namespace)
+        .await?
+    {
+        return Err(AdminError::new(
+            StatusCode::NOT_FOUND,
+            format!("namespace `{namespace}` is not suspended"),
+        ));
+    }
+
+    tracing::info!("namespace `{namespace}` was restored through the admin API");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Checks the federation health of each imported namespace.
+#[debug_handler]
+async fn federation_health(
+    State(config): State<Config>,
+) -> Result<Json<Vec<NamespaceHealth>>, AdminError> {
+    let federation = config.federation()?;
+    let namespaces = config
+        .core
+        .store()
+        .get_namespaces(&LogId::operator_log::<Sha256>())
+        .await?;
+
+    let checks = namespaces
+        .iter()
+        .filter_map(|(namespace, state)| match state {
+            NamespaceState::Imported { registry } => {
+                Some(federation.check_health(&config.client, namespace, registry))
+            }
+            NamespaceState::Defined => None,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(futures::future::join_all(checks).await))
+}
+
+/// Creates a checkpoint without waiting for the checkpoint interval.
+#[debug_handler]
+async fn create_checkpoint(
+    State(config): State<Config>,
+) -> Result<Json<SerdeEnvelope<TimestampedCheckpoint>>, AdminError> {
+    let checkpoint = config.core.create_checkpoint().await;
+    tracing::info!(
+        "checkpoint of log length {len} was created through the admin API",
+        len = checkpoint.log_length
+    );
+    Ok(Json(config.core.store().get_latest_checkpoint().await?))
+}
+
+/// Removes the rejected and pending records whose retention window has
+/// passed.
+#[debug_handler]
+async fn collect_garbage(
+    State(config): State<Config>,
+) -> Result<Json<GarbageCollection>, AdminError> {
+    let retention = RecordRetention::new(config.retention()?.clone(), config.core.clone());
+    let expired = retention.expire().await?;
+
+    tracing::info!(
+        "{rejected} rejected and {pending} pending records were removed through the admin API",
+        rejected = expired.rejected,
+        pending = expired.pending
+    );
+    Ok(Json(GarbageCollection {
+        rejected: expired.rejected,
+        pending: expired.pending,
+    }))
+}
+
+/// Exports the latest checkpoint of the registry as a static registry.
+#[debug_handler]
+async fn export_static_registry(
+    State(config): State<Config>,
+) -> Result<Json<StaticExport>, AdminError> {
+    let exporter = config.exporter()?;
+    let export = exporter.export().await.map_err(|e| {
+        tracing::error!("static registry export failed: {e:#}");
+        AdminError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to export the registry: {e:#}"),
+        )
+    })?;
+
+    tracing::info!(
+        "checkpoint of log length {len} was exported to `{dir}` through the admin API",
+        len = export.log_length,
+        dir = exporter.output_dir().display()
+    );
+    Ok(Json(export))
+}
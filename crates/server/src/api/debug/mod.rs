@@ -147,7 +147,9 @@ async fn get_package_info(
                             permissions: permissions.clone(),
                             ..Default::default()
                         },
-                        Release { version, content } => EntryInfo {
+                        Release {
+                            version, content, ..
+                        } => EntryInfo {
                             kind: "release",
                             version: Some(version.clone()),
                             content: Some(content.clone()),
@@ -158,6 +160,22 @@ async fn get_package_info(
                             version: Some(version.clone()),
                             ..Default::default()
                         },
+                        Attest {
+                            version, content, ..
+                        } => EntryInfo {
+                            kind: "attest",
+                            version: Some(version.clone()),
+                            content: Some(content.clone()),
+                            ..Default::default()
+                        },
+                        Deprecate { .. } => EntryInfo {
+                            kind: "deprecate",
+                            ..Default::default()
+                        },
+                        Undeprecate => EntryInfo {
+                            kind: "undeprecate",
+                            ..Default::default()
+                        },
                         _ => EntryInfo {
                             kind: "UNKNOWN",
                             ..Default::default()
@@ -0,0 +1,303 @@
+//! Serves the documentation archives of package releases.
+//!
+//! Documentation archives are gzip-compressed tar archives published as the
+//! content of a release or attached to a release as an attestation of kind
+//! [`DOCS_ARCHIVE_KIND`](warg_protocol::metadata::DOCS_ARCHIVE_KIND). An
+//! archive is extracted when its documentation is first requested and its
+//! files are served at `/docs/{package}/{version}/`.
+use crate::{
+    datastore::DataStoreError,
+    services::{CoreService, CoreServiceError},
+};
+use anyhow::{bail, Context, Result};
+use axum::{
+    debug_handler,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+    Router,
+};
+use flate2::read::GzDecoder;
+use std::path::{Component, PathBuf};
+use tempfile::TempDir;
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{registry::PackageName, Version};
+
+/// The maximum total size, in bytes, of the files in a documentation archive.
+pub const MAX_DOCS_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The file served for the root of the documentation.
+const INDEX_FILE: &str = "index.html";
+
+/// Validates a documentation archive.
+///
+/// An archive may only contain regular files and directories with relative
+/// paths, must contain an `index.html` at its root and must not exceed
+/// [`MAX_DOCS_SIZE`] when extracted.
+pub fn validate_archive(bytes: &[u8]) -> Result<()> {
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    let mut size = 0u64;
+    let mut index = false;
+    for entry in archive
+        .entries()
+        .context("archive is not a gzip-compressed tar archive")?
+    {
+        let entry = entry.context("failed to read archive entry")?;
+        let path = entry.path().context("archive entry has an invalid path")?;
+        if !path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            bail!(
+                "archive entry `{path}` is not a relative path",
+                path = path.display()
+            );
+        }
+
+        let entry_type = entry.header().entry_type();
+        if !entry_type.is_file() && !entry_type.is_dir() {
+            bail!(
+                "archive entry `{path}` is not a file or directory",
+                path = path.display()
+            );
+        }
+
+        index |= entry_type.is_file()
+            && path
+                .components()
+                .eq([Component::Normal(INDEX_FILE.as_ref())]);
+        size = size.saturating_add(entry.size());
+        if size > MAX_DOCS_SIZE {
+            bail!("archive exceeds the maximum documentation size of {MAX_DOCS_SIZE} bytes");
+        }
+    }
+
+    if !index {
+        bail!("archive does not contain `{INDEX_FILE}` at its root");
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+    files_dir: PathBuf,
+    docs_dir: PathBuf,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService, files_dir: PathBuf, docs_dir: PathBuf) -> Self {
+        Self {
+            core_service,
+            files_dir,
+            docs_dir,
+        }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/:package_name/:version", get(redirect_to_index))
+            .route("/:package_name/:version/", get(get_index))
+            .route("/:package_name/:version/*path", get(get_file))
+            .with_state(self)
+    }
+
+    fn file_name(digest: &AnyHash) -> String {
+        digest.to_string().replace(':', "-")
+    }
+
+    /// Gets the directory of the extracted documentation of the given
+    /// release.
+    ///
+    /// The documentation archive is extracted if it has not been already.
+    async fn docs_path(&self, name: &PackageName, version: &Version) -> Result<PathBuf, DocsError> {
+        let state = match self.core_service.package_state(name).await {
+            Ok(state) => state,
+            Err(CoreServiceError::DataStore(DataStoreError::LogNotFound(_))) => {
+                return Err(DocsError::not_found(format!(
+                    "package `{name}` was not found"
+                )))
+            }
+            Err(e) => return Err(DocsError::internal_error(e)),
+        };
+
+        let digest = state.docs(version).ok_or_else(|| {
+            DocsError::not_found(format!(
+                "version {version} of package `{name}` has no documentation"
+            ))
+        })?;
+
+        if self
+            .core_service
+            .is_embargoed(digest)
+            .await
+            .map_err(DocsError::internal_error)?
+        {
+            return Err(DocsError::not_found(format!(
+                "version {version} of package `{name}` has no documentation"
+            )));
+        }
+
+        let path = self.docs_dir.join(Self::file_name(digest));
+        if !path.is_dir() {
+            self.extract(digest, &path)
+                .await
+                .map_err(DocsError::internal_error)?;
+        }
+
+        Ok(path)
+    }
+
+    /// Extracts the documentation archive with the given digest.
+    ///
+    /// The archive is extracted to a temporary directory that is then
+    /// renamed, so a partially extracted archive is never served.
+    async fn extract(&self, digest: &AnyHash, path: &std::path::Path) -> Result<()> {
+        let bytes = tokio::fs::read(self.files_dir.join(Self::file_name(digest)))
+            .await
+            .with_context(|| format!("failed to read documentation archive `{digest}`"))?;
+        let dir = TempDir::new_in(&self.docs_dir)?;
+        let dir = tokio::task::spawn_blocking(move || -> Result<TempDir> {
+            validate_archive(&bytes)?;
+            tar::Archive::new(GzDecoder::new(bytes.as_slice())).unpack(dir.path())?;
+            Ok(dir)
+        })
+        .await??;
+
+        // Another request may have extracted the archive concurrently
+        if let Err(e) = std::fs::rename(dir.path(), path) {
+            if !path.is_dir() {
+                return Err(e).with_context(|| {
+                    format!("failed to extract documentation archive `{digest}`")
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn serve(
+        &self,
+        name: &PackageName,
+        version: &Version,
+        file: &str,
+    ) -> Result<Response, DocsError> {
+        let relative = std::path::Path::new(file);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(DocsError::not_found(format!("file `{file}` was not found")));
+        }
+
+        let mut path = self.docs_path(name, version).await?.join(relative);
+        if path.is_dir() {
+            // Directories are only served with a trailing slash so that
+            // relative links resolve within them
+            if !file.is_empty() && !file.ends_with('/') {
+                let last = file.rsplit('/').next().unwrap_or_default();
+                return Ok(Redirect::permanent(&format!("{last}/")).into_response());
+            }
+            path.push(INDEX_FILE);
+        }
+
+        let contents = match tokio::fs::read(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(DocsError::not_found(format!("file `{file}` was not found")))
+            }
+            Err(e) => return Err(DocsError::internal_error(e)),
+        };
+
+        let mime = mime_guess::from_path(&path).first_or_octet_stream();
+        Ok(([(header::CONTENT_TYPE, mime.to_string())], contents).into_response())
+    }
+}
+
+#[debug_handler]
+async fn redirect_to_index(Path((_, version)): Path<(PackageName, Version)>) -> Redirect {
+    Redirect::permanent(&format!("{version}/"))
+}
+
+#[debug_handler]
+async fn get_index(
+    State(config): State<Config>,
+    Path((name, version)): Path<(PackageName, Version)>,
+) -> Result<Response, DocsError> {
+    config.serve(&name, &version, "").await
+}
+
+#[debug_handler]
+async fn get_file(
+    State(config): State<Config>,
+    Path((name, version, file)): Path<(PackageName, Version, String)>,
+) -> Result<Response, DocsError> {
+    config.serve(&name, &version, &file).await
+}
+
+struct DocsError {
+    status: StatusCode,
+    message: String,
+}
+
+impl DocsError {
+    fn not_found(message: impl ToString) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.to_string(),
+        }
+    }
+
+    fn internal_error(e: impl std::fmt::Display) -> Self {
+        tracing::error!("unexpected error: {e}");
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: "an error occurred while processing the request".into(),
+        }
+    }
+}
+
+impl IntoResponse for DocsError {
+    fn into_response(self) -> Response {
+        (self.status, self.message).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *contents).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn it_validates_archives() {
+        validate_archive(&archive(&[
+            ("index.html", b"<h1>docs</h1>"),
+            ("api/index.html", b"<h1>api</h1>"),
+        ]))
+        .unwrap();
+
+        let e = validate_archive(&archive(&[("api/index.html", b"")])).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "archive does not contain `index.html` at its root"
+        );
+
+        assert!(validate_archive(b"not an archive").is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! An optional GraphQL endpoint for querying registry data.
+//!
+//! The schema is read-only and intended for registry frontends that need to
+//! list and aggregate packages, which is awkward to do with the
+//! record-oriented REST API.
+use crate::{
+    datastore::DataStoreError,
+    services::{CoreService, CoreServiceError},
+};
+use async_graphql::{
+    connection::{Connection, Edge},
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Result, Schema, SimpleObject,
+};
+use async_graphql_axum::GraphQL;
+use axum::{routing::post_service, Router};
+use std::{path::PathBuf, time::SystemTime};
+use warg_crypto::hash::{AnyHash, Sha256};
+use warg_protocol::{
+    package,
+    registry::{LogId, PackageName},
+};
+use wasmparser::{Parser, Payload};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+type RegistrySchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+    files_dir: PathBuf,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService, files_dir: PathBuf) -> Self {
+        Self {
+            core_service,
+            files_dir,
+        }
+    }
+
+    pub fn into_router(self) -> Router {
+        let schema = RegistrySchema::build(Query, EmptyMutation, EmptySubscription)
+            .data(self)
+            .finish();
+
+        Router::new().route("/", post_service(GraphQL::new(schema)))
+    }
+
+    fn content_path(&self, digest: &AnyHash) -> PathBuf {
+        self.files_dir.join(digest.to_string().replace(':', "-"))
+    }
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Lists the packages in the registry, ordered by name.
+    ///
+    /// Packages of suspended namespaces are not listed.
+    ///
+    /// The cursor of each edge is the package name.
+    async fn packages(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<String, Package>> {
+        let config = ctx.data::<Config>()?;
+        let first = first
+            .map(|n| (n.max(0) as usize).min(MAX_PAGE_SIZE))
+            .unwrap_or(DEFAULT_PAGE_SIZE);
+        let after = after.map(PackageName::new).transpose()?;
+
+        // Fetch one more than requested to determine if there is a next page
+        let mut names = config
+            .core_service
+            .listed_package_names(after.as_ref(), (first + 1) as u16)
+            .await?;
+        let has_next_page = names.len() > first;
+        names.truncate(first);
+
+        let mut connection = Connection::new(after.is_some(), has_next_page);
+        for name in names {
+            if let Some(package) = Package::load(config, name.clone()).await? {
+                connection.edges.push(Edge::new(name.to_string(), package));
+            }
+        }
+
+        Ok(connection)
+    }
+
+    /// Gets a package by name.
+    ///
+    /// Returns `null` if the package has no published records.
+    async fn package(&self, ctx: &Context<'_>, name: String) -> Result<Option<Package>> {
+        let config = ctx.data::<Config>()?;
+        Package::load(config, PackageName::new(name)?).await
+    }
+
+    /// Gets the latest checkpoint of the registry.
+    async fn checkpoint(&self, ctx: &Context<'_>) -> Result<Checkpoint> {
+        let config = ctx.data::<Config>()?;
+        let checkpoint = config
+            .core_service
+            .store()
+            .get_latest_checkpoint()
+            .await?
+            .into_contents();
+
+        Ok(Checkpoint {
+            log_length: checkpoint.checkpoint.log_length as u64,
+            log_root: checkpoint.checkpoint.log_root.to_string(),
+            map_root: checkpoint.checkpoint.map_root.to_string(),
+            timestamp: checkpoint.timestamp,
+        })
+    }
+}
+
+/// A package in the registry.
+#[derive(SimpleObject)]
+pub struct Package {
+    /// The name of the package.
+    name: String,
+    /// The namespace of the package.
+    namespace: String,
+    /// The log identifier of the package.
+    log_id: String,
+    /// The latest non-yanked version of the package.
+    latest_version: Option<String>,
+    /// The releases of the package, in package log order.
+    releases: Vec<Release>,
+}
+
+impl Package {
+    async fn load(config: &Config, name: PackageName) -> Result<Option<Self>> {
+        let state = match config.core_service.package_state(&name).await {
+            Ok(state) => state,
+            Err(CoreServiceError::DataStore(DataStoreError::LogNotFound(_))) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let releases = config.core_service.listed_releases(&state).await?;
+        Ok(Some(Self::new(name, &releases)))
+    }
+
+    fn new(name: PackageName, listed: &[&package::Release]) -> Self {
+        let releases: Vec<Release> = listed
+            .iter()
+            .map(|release| Release {
+                version: release.version.to_string(),
+                published_at: timestamp(release.timestamp),
+                yanked: release.yanked(),
+                digest: release.content().map(ToString::to_string),
+                content: release.content().cloned(),
+            })
+            .collect();
+
+        let latest_version = listed
+            .iter()
+            .filter(|release| !release.yanked())
+            .max_by(|a, b| a.version.cmp(&b.version))
+            .map(|release| release.version.to_string());
+
+        Self {
+            namespace: name.namespace().to_string(),
+            log_id: LogId::package_log::<Sha256>(&name).to_string(),
+            name: name.to_string(),
+            latest_version,
+            releases,
+        }
+    }
+}
+
+/// A release of a package.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Release {
+    /// The version of the release.
+    version: String,
+    /// The time the release was published, in seconds since the Unix epoch.
+    published_at: u64,
+    /// Whether or not the release has been yanked.
+    yanked: bool,
+    /// The content digest of the release; `null` if yanked.
+    digest: Option<String>,
+    #[graphql(skip)]
+    content: Option<AnyHash>,
+}
+
+#[ComplexObject]
+impl Release {
+    /// The normalized SPDX license expression of the release's content.
+    ///
+    /// Returns `null` if the release was yanked or its content does not
+    /// specify a valid license.
+    async fn license(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let config = ctx.data::<Config>()?;
+        let Some(content) = &self.content else {
+            return Ok(None);
+        };
+
+        Ok(config
+            .core_service
+            .store()
+            .get_content_metadata(content)
+            .await?
+            .and_then(|metadata| metadata.license))
+    }
+
+    /// The dependencies imported by the release's component.
+    ///
+    /// Returns an empty list if the release was yanked or its content is
+    /// not a component.
+    async fn dependencies(&self, ctx: &Context<'_>) -> Result<Vec<Dependency>> {
+        let config = ctx.data::<Config>()?;
+        let Some(content) = &self.content else {
+            return Ok(Vec::new());
+        };
+
+        let bytes = match tokio::fs::read(config.content_path(content)).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(component_dependencies(&bytes))
+    }
+}
+
+/// A dependency of a release.
+#[derive(SimpleObject)]
+pub struct Dependency {
+    /// The name of the package depended upon.
+    name: String,
+    /// The version or version requirement of the dependency, if specified.
+    version: Option<String>,
+}
+
+/// A registry checkpoint.
+#[derive(SimpleObject)]
+pub struct Checkpoint {
+    /// The length of the registry log.
+    log_length: u64,
+    /// The root of the registry log.
+    log_root: String,
+    /// The root of the registry map.
+    map_root: String,
+    /// The time of the checkpoint, in seconds since the Unix epoch.
+    timestamp: u64,
+}
+
+fn timestamp(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Collects the package dependencies from the top-level imports of a component.
+///
+/// Invalid WebAssembly yields whatever dependencies were found before the error.
+fn component_dependencies(bytes: &[u8]) -> Vec<Dependency> {
+    let mut dependencies = Vec::<Dependency>::new();
+    let mut depth = 0usize;
+    for payload in Parser::new(0).parse_all(bytes) {
+        let Ok(payload) = payload else {
+            break;
+        };
+
+        match payload {
+            Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => depth += 1,
+            Payload::End(_) => depth = depth.saturating_sub(1),
+            Payload::ComponentImportSection(reader) if depth == 0 => {
+                for import in reader.into_iter().flatten() {
+                    if let Some(dependency) = parse_dependency(import.name.0) {
+                        if !dependencies.iter().any(|d| d.name == dependency.name) {
+                            dependencies.push(dependency);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    dependencies
+}
+
+fn parse_dependency(import: &str) -> Option<Dependency> {
+    // Strip the `unlocked-dep=<...>` and `locked-dep=<...>` forms
+    let import = ["unlocked-dep=<", "locked-dep=<"]
+        .iter()
+        .find_map(|prefix| import.strip_prefix(prefix))
+        .map(|s| s.strip_suffix('>').unwrap_or(s))
+        .unwrap_or(import);
+
+    let (name, version) = match import.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (import, None),
+    };
+
+    // Strip any interface path from the package name
+    let name = name.split_once('/').map(|(name, _)| name).unwrap_or(name);
+    let name = PackageName::new(name).ok()?;
+
+    Some(Dependency {
+        name: name.to_string(),
+        version: version
+            .map(|v| v.trim_start_matches('{').trim_end_matches('}'))
+            .filter(|v| !v.is_empty())
+            .map(ToString::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_dependency_imports() {
+        let dependency = parse_dependency("wasi:http/types@0.2.0").unwrap();
+        assert_eq!(dependency.name, "wasi:http");
+        assert_eq!(dependency.version.as_deref(), Some("0.2.0"));
+
+        let dependency = parse_dependency("unlocked-dep=<test:add@{>=1.0.0}>").unwrap();
+        assert_eq!(dependency.name, "test:add");
+        assert_eq!(dependency.version.as_deref(), Some(">=1.0.0"));
+
+        let dependency = parse_dependency("locked-dep=<test:add>").unwrap();
+        assert_eq!(dependency.name, "test:add");
+        assert_eq!(dependency.version, None);
+
+        assert!(parse_dependency("not-a-package").is_none());
+    }
+}
@@ -1,9 +1,20 @@
 use crate::{
-    policy::{content::ContentPolicy, record::RecordPolicy},
-    services::CoreService,
+    policy::{
+        approval::ApprovalPolicy, content::ContentPolicy, record::RecordPolicy,
+        semver::SemverPolicy, sources::ContentSourcePolicy, PolicyMode,
+    },
+    services::{CoreService, TyposquatConfig},
 };
-use axum::{body::Body, http::Request, Router};
-use std::{path::PathBuf, sync::Arc};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
@@ -13,25 +24,108 @@ use tower_http::{
 };
 use tracing::{Level, Span};
 use url::Url;
+use warg_api::{DiscoveryDocument, DISCOVERY_PATH};
+use warg_crypto::{
+    hash::{AnyHash, HashAlgorithm},
+    signing::SignatureAlgorithm,
+};
 
+pub mod admin;
+pub mod docs;
+pub mod throttle;
 pub mod v1;
 
 #[cfg(feature = "debug")]
 pub mod debug;
 
+#[cfg(feature = "graphql")]
+pub mod graphql;
+
+#[cfg(feature = "ui")]
+pub mod ui;
+
+/// Serves the discovery document of the registry.
+async fn discovery() -> Json<DiscoveryDocument> {
+    Json(DiscoveryDocument {
+        hash_algorithms: vec![HashAlgorithm::Sha256.to_string()],
+        signature_algorithms: vec![SignatureAlgorithm::EcdsaP256.to_string()],
+        ..Default::default()
+    })
+}
+
+/// Rejects requests for content files that are under an embargo.
+async fn embargo_filter(
+    State(core): State<CoreService>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    // Content files are named `<algorithm>-<hex digest>`
+    let digest = request
+        .uri()
+        .path()
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.split_once('-'))
+        .and_then(|(algorithm, digest)| format!("{algorithm}:{digest}").parse::<AnyHash>().ok());
+
+    if let Some(digest) = digest {
+        match core.is_embargoed(&digest).await {
+            Ok(false) => {}
+            Ok(true) => return StatusCode::NOT_FOUND.into_response(),
+            Err(e) => {
+                tracing::error!("failed to check embargo of content `{digest}`: {e}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+    }
+
+    next.run(request).await
+}
+
 /// Creates the router for the API.
+#[allow(clippy::too_many_arguments)]
 pub fn create_router(
     content_base_url: Url,
     core: CoreService,
     temp_dir: PathBuf,
     files_dir: PathBuf,
+    docs_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    policy_mode: PolicyMode,
+    staged_publish_ttl: Duration,
+    typosquat: Option<TyposquatConfig>,
+    approvals: Option<ApprovalPolicy>,
+    semver: Option<SemverPolicy>,
+    content_sources: Option<ContentSourcePolicy>,
+    admin: Option<admin::Config>,
+    throttle: Option<throttle::ThrottleConfig>,
 ) -> Router {
     let router = Router::new();
+    let router = match admin {
+        Some(admin) => router.nest("/admin", admin.into_router()),
+        None => router,
+    };
     #[cfg(feature = "debug")]
     let router = router.nest("/debug", debug::Config::new(core.clone()).into_router());
-    router
+    #[cfg(feature = "graphql")]
+    let router = router.nest(
+        "/graphql",
+        graphql::Config::new(core.clone(), files_dir.clone()).into_router(),
+    );
+    #[cfg(feature = "ui")]
+    let router = router.nest(
+        "/ui",
+        ui::Config::new(core.clone(), files_dir.clone()).into_router(),
+    );
+    let router = router.nest(
+        "/docs",
+        docs::Config::new(core.clone(), files_dir.clone(), docs_dir).into_router(),
+    );
+    let content = ServiceBuilder::new()
+        .layer(middleware::from_fn_with_state(core.clone(), embargo_filter))
+        .service(ServeDir::new(files_dir.clone()));
+    let router = router
         .nest(
             "/v1",
             v1::create_router(
@@ -41,31 +135,42 @@ pub fn create_router(
                 files_dir.clone(),
                 content_policy,
                 record_policy,
+                policy_mode,
+                staged_publish_ttl,
+                typosquat,
+                approvals,
+                semver,
+                content_sources,
             ),
         )
-        .nest_service("/content", ServeDir::new(files_dir))
-        .layer(
-            ServiceBuilder::new()
-                .layer(
-                    TraceLayer::new_for_http()
-                        .make_span_with(DefaultMakeSpan::new().include_headers(true))
-                        .on_request(|request: &Request<Body>, _span: &Span| {
-                            tracing::info!("starting {} {}", request.method(), request.uri().path())
-                        })
-                        .on_response(
-                            DefaultOnResponse::new()
-                                .level(Level::INFO)
-                                .latency_unit(LatencyUnit::Micros),
-                        ),
-                )
-                .layer(
-                    CorsLayer::new()
-                        .allow_origin(Any)
-                        .allow_methods([axum::http::Method::GET, axum::http::Method::POST])
-                        .allow_headers([
-                            axum::http::header::CONTENT_TYPE,
-                            axum::http::header::ACCEPT,
-                        ]),
-                ),
-        )
+        .nest_service("/content", content)
+        .route(&format!("/{DISCOVERY_PATH}"), get(discovery));
+    let router = match throttle {
+        Some(config) => router.layer(middleware::from_fn_with_state(
+            throttle::Throttle::new(config),
+            throttle::throttle,
+        )),
+        None => router,
+    };
+    router.layer(
+        ServiceBuilder::new()
+            .layer(
+                TraceLayer::new_for_http()
+                    .make_span_with(DefaultMakeSpan::new().include_headers(true))
+                    .on_request(|request: &Request<Body>, _span: &Span| {
+                        tracing::info!("starting {} {}", request.method(), request.uri().path())
+                    })
+                    .on_response(
+                        DefaultOnResponse::new()
+                            .level(Level::INFO)
+                            .latency_unit(LatencyUnit::Micros),
+                    ),
+            )
+            .layer(
+                CorsLayer::new()
+                    .allow_origin(Any)
+                    .allow_methods([axum::http::Method::GET, axum::http::Method::POST])
+                    .allow_headers([axum::http::header::CONTENT_TYPE, axum::http::header::ACCEPT]),
+            ),
+    )
 }
@@ -0,0 +1,227 @@
+//! Throttling of requests to the registry.
+//!
+//! Requests beyond a client's quota are rejected with `429 Too Many
+//! Requests`, and requests beyond the server's concurrency limit are shed
+//! with `503 Service Unavailable`. Both carry a problem details body with a
+//! machine-readable code and a retry hint.
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+use warg_api::v1::problem::{ProblemCode, ProblemDetails, Quota, PROBLEM_CONTENT_TYPE};
+
+/// The default amount of time clients are asked to wait before retrying a
+/// request that was shed.
+const DEFAULT_OVERLOAD_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// The configuration of request throttling.
+#[derive(Debug, Clone)]
+pub struct ThrottleConfig {
+    max_concurrent_requests: Option<usize>,
+    quota: Option<(u64, Duration)>,
+    overload_retry_after: Duration,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThrottleConfig {
+    /// Creates a throttling configuration that does not throttle requests.
+    pub fn new() -> Self {
+        Self {
+            max_concurrent_requests: None,
+            quota: None,
+            overload_retry_after: DEFAULT_OVERLOAD_RETRY_AFTER,
+        }
+    }
+
+    /// Sets the maximum number of requests served concurrently; further
+    /// requests are shed until a request completes.
+    pub fn with_max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    /// Sets the number of requests each client may make in each window of
+    /// the given length.
+    ///
+    /// Clients are identified by their IP address.
+    pub fn with_quota(mut self, limit: u64, window: Duration) -> Self {
+        self.quota = Some((limit, window));
+        self
+    }
+
+    /// Sets how long clients are asked to wait before retrying a request
+    /// that was shed.
+    ///
+    /// Defaults to 1 second.
+    pub fn with_overload_retry_after(mut self, retry_after: Duration) -> Self {
+        self.overload_retry_after = retry_after;
+        self
+    }
+}
+
+/// The request count of a client in the current quota window.
+struct Window {
+    start: Instant,
+    count: u64,
+}
+
+/// The state of request throttling.
+#[derive(Clone)]
+pub(crate) struct Throttle {
+    config: ThrottleConfig,
+    permits: Option<Arc<Semaphore>>,
+    windows: Arc<Mutex<HashMap<IpAddr, Window>>>,
+}
+
+impl Throttle {
+    /// Creates the throttling state of the given configuration.
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            permits: config
+                .max_concurrent_requests
+                .map(|max| Arc::new(Semaphore::new(max))),
+            config,
+            windows: Default::default(),
+        }
+    }
+
+    /// Counts a request of the given client against its quota.
+    ///
+    /// Returns the time until the quota is replenished if it is exhausted.
+    fn check_quota(&self, client: IpAddr, limit: u64, window: Duration) -> Option<Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+
+        // Forget clients whose windows have passed so the map does not grow
+        // without bound
+        if windows.len() >= 1024 {
+            windows.retain(|_, w| now.duration_since(w.start) < window);
+        }
+
+        let current = windows.entry(client).or_insert(Window {
+            start: now,
+            count: 0,
+        });
+        let elapsed = now.duration_since(current.start);
+        if elapsed >= window {
+            current.start = now;
+            current.count = 0;
+        }
+
+        if current.count >= limit {
+            return Some(window - now.duration_since(current.start));
+        }
+
+        current.count += 1;
+        None
+    }
+}
+
+/// Creates a problem details response for a throttled request.
+fn problem(mut details: ProblemDetails, retry_after: Duration) -> Response {
+    let status = StatusCode::from_u16(details.status).unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+    details.retry_after_ms = Some(retry_after.as_millis() as u64);
+
+    let mut response = (status, Json(details)).into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+    );
+    // `Retry-After` is in whole seconds, so round up
+    headers.insert(
+        header::RETRY_AFTER,
+        HeaderValue::from(retry_after.as_millis().div_ceil(1000) as u64),
+    );
+    response
+}
+
+/// Throttles requests according to the server's throttling configuration.
+pub(crate) async fn throttle(
+    State(throttle): State<Throttle>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if let Some((limit, window)) = throttle.config.quota {
+        let client = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        if let Some(retry_after) = throttle.check_quota(client, limit, window) {
+            tracing::debug!("client `{client}` exceeded its request quota");
+            let mut details = ProblemDetails::new(
+                ProblemCode::QuotaExceeded,
+                format!(
+                    "the quota of {limit} requests every {window} seconds was exceeded",
+                    window = window.as_secs_f64()
+                ),
+            );
+            details.quota = Some(Quota {
+                limit,
+                window_ms: window.as_millis() as u64,
+            });
+            return problem(details, retry_after);
+        }
+    }
+
+    let _permit = match &throttle.permits {
+        Some(permits) => match permits.clone().try_acquire_owned() {
+            Ok(permit) => Some(permit),
+            Err(_) => {
+                tracing::warn!("shedding request as the server is at its concurrency limit");
+                return problem(
+                    ProblemDetails::new(
+                        ProblemCode::Overloaded,
+                        "the registry is serving too many requests; try again later",
+                    ),
+                    throttle.config.overload_retry_after,
+                );
+            }
+        },
+        None => None,
+    };
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_enforces_quotas_per_client() {
+        let throttle = Throttle::new(ThrottleConfig::new());
+        let window = Duration::from_secs(60);
+        let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        assert!(throttle.check_quota(client, 2, window).is_none());
+        assert!(throttle.check_quota(client, 2, window).is_none());
+        let retry_after = throttle.check_quota(client, 2, window).unwrap();
+        assert!(retry_after <= window);
+        assert!(throttle.check_quota(other, 2, window).is_none());
+
+        // A new window replenishes the quota
+        assert!(throttle
+            .check_quota(client, 2, Duration::from_secs(0))
+            .is_none());
+    }
+}
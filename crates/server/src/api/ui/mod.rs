@@ -0,0 +1,306 @@
+//! A minimal, server-rendered web UI for browsing the registry.
+//!
+//! The UI is intended for small deployments that want a browsable registry
+//! without running a separate frontend.
+use crate::{
+    datastore::DataStoreError,
+    services::{CoreService, CoreServiceError},
+};
+use axum::{
+    debug_handler,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect},
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use std::{fmt::Write, path::PathBuf, time::SystemTime};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{package::LogState, registry::PackageName, Version};
+
+const PAGE_SIZE: u16 = 100;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#222}\
+a{color:#0b5cad;text-decoration:none}a:hover{text-decoration:underline}\
+table{border-collapse:collapse;width:100%}th,td{text-align:left;padding:.4em;border-bottom:1px solid #ddd}\
+pre{background:#f6f8fa;padding:1em;overflow:auto}.yanked{color:#999;text-decoration:line-through}\
+nav a{margin-right:1em}";
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+    files_dir: PathBuf,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService, files_dir: PathBuf) -> Self {
+        Self {
+            core_service,
+            files_dir,
+        }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/", get(|| async { Redirect::permanent("/ui/packages") }))
+            .route("/packages", get(list_packages))
+            .route("/package/:package_name", get(get_package))
+            .route("/package/:package_name/:version/wit", get(get_wit))
+            .route("/checkpoint", get(get_checkpoint))
+            .with_state(self)
+    }
+
+    fn content_path(&self, digest: &AnyHash) -> PathBuf {
+        self.files_dir.join(digest.to_string().replace(':', "-"))
+    }
+
+    async fn package_state(&self, name: &PackageName) -> Result<LogState, UiError> {
+        match self.core_service.package_state(name).await {
+            Ok(state) => Ok(state),
+            Err(CoreServiceError::DataStore(DataStoreError::LogNotFound(_))) => Err(
+                UiError::not_found(format!("package `{name}` was not found")),
+            ),
+            Err(e) => Err(UiError::internal_error(e)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ListPackagesQuery {
+    after: Option<PackageName>,
+}
+
+#[debug_handler]
+async fn list_packages(
+    State(config): State<Config>,
+    Query(query): Query<ListPackagesQuery>,
+) -> Result<Html<String>, UiError> {
+    let names = config
+        .core_service
+        .listed_package_names(query.after.as_ref(), PAGE_SIZE)
+        .await
+        .map_err(UiError::internal_error)?;
+
+    let mut body = String::from("<h1>Packages</h1>");
+    if names.is_empty() {
+        body.push_str("<p>No packages have been published.</p>");
+    } else {
+        body.push_str("<ul>");
+        for name in &names {
+            let _ = write!(
+                body,
+                r#"<li><a href="/ui/package/{name}">{name}</a></li>"#,
+                name = escape(name.as_ref())
+            );
+        }
+        body.push_str("</ul>");
+    }
+
+    if names.len() == PAGE_SIZE as usize {
+        let _ = write!(
+            body,
+            r#"<p><a href="/ui/packages?after={after}">Next page</a></p>"#,
+            after = escape(names.last().unwrap().as_ref())
+        );
+    }
+
+    Ok(page("Packages", &body))
+}
+
+#[debug_handler]
+async fn get_package(
+    State(config): State<Config>,
+    Path(package_name): Path<PackageName>,
+) -> Result<Html<String>, UiError> {
+    let state = config.package_state(&package_name).await?;
+    let releases = config
+        .core_service
+        .listed_releases(&state)
+        .await
+        .map_err(UiError::internal_error)?;
+    let name = escape(package_name.as_ref());
+
+    let mut body = format!("<h1>{name}</h1>");
+    if let Some(latest) = releases
+        .iter()
+        .filter(|r| !r.yanked())
+        .max_by(|a, b| a.version.cmp(&b.version))
+    {
+        let _ = write!(body, "<p>Latest version: <b>{}</b></p>", latest.version);
+    }
+
+    body.push_str("<h2>Versions</h2><table><tr><th>Version</th><th>Published</th><th>License</th><th>Content</th><th></th></tr>");
+    for release in releases.into_iter().rev() {
+        let published = timestamp(release.timestamp);
+        match release.content() {
+            Some(content) => {
+                let license = config
+                    .core_service
+                    .store()
+                    .get_content_metadata(content)
+                    .await
+                    .map_err(UiError::internal_error)?
+                    .and_then(|m| m.license);
+                let _ = write!(
+                    body,
+                    r#"<tr><td>{version}</td><td>{published}</td><td>{license}</td><td><code>{content}</code></td><td><a href="/ui/package/{name}/{version}/wit">WIT</a></td></tr>"#,
+                    version = release.version,
+                    license = license.as_deref().map(escape).unwrap_or_default(),
+                );
+            }
+            None => {
+                let _ = write!(
+                    body,
+                    r#"<tr class="yanked"><td>{version}</td><td>{published}</td><td></td><td>yanked</td><td></td></tr>"#,
+                    version = release.version,
+                );
+            }
+        }
+    }
+    body.push_str("</table>");
+
+    Ok(page(&name, &body))
+}
+
+#[debug_handler]
+async fn get_wit(
+    State(config): State<Config>,
+    Path((package_name, version)): Path<(PackageName, Version)>,
+) -> Result<Html<String>, UiError> {
+    let state = config.package_state(&package_name).await?;
+    let name = escape(package_name.as_ref());
+    let content = state
+        .release(&version)
+        .and_then(|r| r.content())
+        .ok_or_else(|| {
+            UiError::not_found(format!(
+                "version `{version}` of package `{package_name}` was not found"
+            ))
+        })?;
+
+    // Embargoed releases are not found until the embargo passes
+    if config
+        .core_service
+        .is_embargoed(content)
+        .await
+        .map_err(UiError::internal_error)?
+    {
+        return Err(UiError::not_found(format!(
+            "version `{version}` of package `{package_name}` was not found"
+        )));
+    }
+
+    let bytes = tokio::fs::read(config.content_path(content))
+        .await
+        .map_err(UiError::internal_error)?;
+
+    let wit = match wit_component::decode(&bytes) {
+        Ok(decoded) => wit_component::WitPrinter::default()
+            .print(decoded.resolve(), decoded.package())
+            .map_err(UiError::internal_error)?,
+        Err(e) => format!("// content is not a component or WIT package: {e}"),
+    };
+
+    let body = format!(
+        r#"<h1><a href="/ui/package/{name}">{name}</a>@{version}</h1><pre>{wit}</pre>"#,
+        wit = escape(&wit)
+    );
+    Ok(page(&format!("{name}@{version}"), &body))
+}
+
+#[debug_handler]
+async fn get_checkpoint(State(config): State<Config>) -> Result<Html<String>, UiError> {
+    let checkpoint = config
+        .core_service
+        .store()
+        .get_latest_checkpoint()
+        .await
+        .map_err(UiError::internal_error)?;
+    let key_id = escape(&checkpoint.key_id().to_string());
+    let checkpoint = checkpoint.into_contents();
+
+    let body = format!(
+        "<h1>Checkpoint</h1><table>\
+         <tr><th>Log length</th><td>{log_length}</td></tr>\
+         <tr><th>Log root</th><td><code>{log_root}</code></td></tr>\
+         <tr><th>Map root</th><td><code>{map_root}</code></td></tr>\
+         <tr><th>Timestamp</th><td>{timestamp}</td></tr>\
+         <tr><th>Signed by</th><td><code>{key_id}</code></td></tr>\
+         </table>",
+        log_length = checkpoint.checkpoint.log_length,
+        log_root = checkpoint.checkpoint.log_root,
+        map_root = checkpoint.checkpoint.map_root,
+        timestamp = checkpoint.timestamp,
+    );
+    Ok(page("Checkpoint", &body))
+}
+
+fn page(title: &str, body: &str) -> Html<String> {
+    Html(format!(
+        r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>{title} - Warg Registry</title><style>{STYLE}</style></head><body><nav><a href="/ui/packages">Packages</a><a href="/ui/checkpoint">Checkpoint</a></nav>{body}</body></html>"#
+    ))
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn timestamp(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+struct UiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl UiError {
+    fn not_found(message: impl ToString) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.to_string(),
+        }
+    }
+
+    fn internal_error(e: impl std::fmt::Display) -> Self {
+        tracing::error!("unexpected error: {e}");
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: "an error occurred while processing the request".into(),
+        }
+    }
+}
+
+impl IntoResponse for UiError {
+    fn into_response(self) -> axum::response::Response {
+        let body = format!("<h1>Error</h1><p>{}</p>", escape(&self.message));
+        (self.status, page("Error", &body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_escapes_html() {
+        assert_eq!(
+            escape(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use super::{problem_response, Json, RegistryHeader};
+use crate::services::{to_osv, CoreService, OsvRecord};
+use axum::{
+    debug_handler,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use warg_api::v1::package::PackageError;
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService) -> Self {
+        Self { core_service }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/osv", get(get_osv_advisories))
+            .with_state(self)
+    }
+}
+
+struct AdvisoryApiError;
+
+impl IntoResponse for AdvisoryApiError {
+    fn into_response(self) -> Response {
+        let error = PackageError::Message {
+            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            message: "an error occurred while processing the request".into(),
+        };
+        problem_response(error.status(), error)
+    }
+}
+
+/// Exports the vulnerability advisories of every package in the OSV format,
+/// for consumption by vulnerability scanners.
+#[debug_handler]
+async fn get_osv_advisories(
+    State(config): State<Config>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<Vec<OsvRecord>>, AdvisoryApiError> {
+    let advisories = config
+        .core_service
+        .store()
+        .list_advisories(None)
+        .await
+        .map_err(|e| {
+            tracing::error!("failed to list advisories: {e}");
+            AdvisoryApiError
+        })?;
+
+    Ok(Json(to_osv(&advisories).map_err(|e| {
+        tracing::error!("failed to export advisories: {e:#}");
+        AdvisoryApiError
+    })?))
+}
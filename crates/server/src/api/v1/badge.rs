@@ -0,0 +1,125 @@
+use super::{Path, RegistryHeader};
+use crate::{
+    datastore::DataStoreError,
+    services::{CoreService, CoreServiceError},
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use warg_protocol::registry::PackageName;
+
+const LABEL: &str = "warg";
+const COLOR_FOUND: &str = "#007ec6";
+const COLOR_MISSING: &str = "#9f9f9f";
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService) -> Self {
+        Self { core_service }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/:namespace/:file", get(get_badge))
+            .with_state(self)
+    }
+}
+
+/// Renders a badge with the latest non-yanked version of a package.
+///
+/// A grey badge is returned with a `404` status when the package does not
+/// exist or has no available releases, so that embedded badges still render.
+#[debug_handler]
+async fn get_badge(
+    State(config): State<Config>,
+    Path((namespace, file)): Path<(String, String)>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Response {
+    let Some(name) = file
+        .strip_suffix(".svg")
+        .and_then(|name| PackageName::new(format!("{namespace}:{name}")).ok())
+    else {
+        return badge(StatusCode::NOT_FOUND, "not found", COLOR_MISSING);
+    };
+
+    let state = match config.core_service.package_state(&name).await {
+        Ok(state) => state,
+        Err(CoreServiceError::DataStore(DataStoreError::LogNotFound(_))) => {
+            return badge(StatusCode::NOT_FOUND, "not found", COLOR_MISSING);
+        }
+        Err(e) => {
+            tracing::error!("failed to get state of package `{name}`: {e}");
+            return badge(StatusCode::INTERNAL_SERVER_ERROR, "error", COLOR_MISSING);
+        }
+    };
+
+    let releases = match config.core_service.listed_releases(&state).await {
+        Ok(releases) => releases,
+        Err(e) => {
+            tracing::error!("failed to get releases of package `{name}`: {e}");
+            return badge(StatusCode::INTERNAL_SERVER_ERROR, "error", COLOR_MISSING);
+        }
+    };
+
+    match releases
+        .into_iter()
+        .filter(|r| !r.yanked())
+        .max_by(|a, b| a.version.cmp(&b.version))
+    {
+        Some(release) => badge(
+            StatusCode::OK,
+            &format!("v{version}", version = release.version),
+            COLOR_FOUND,
+        ),
+        None => badge(StatusCode::NOT_FOUND, "no releases", COLOR_MISSING),
+    }
+}
+
+fn badge(status: StatusCode, message: &str, color: &str) -> Response {
+    (
+        status,
+        [
+            (header::CONTENT_TYPE, "image/svg+xml"),
+            (header::CACHE_CONTROL, "max-age=300"),
+        ],
+        render_svg(LABEL, message, color),
+    )
+        .into_response()
+}
+
+/// Renders a flat, shields-style badge.
+///
+/// Text widths are approximated as the font is not known to the server.
+fn render_svg(label: &str, message: &str, color: &str) -> String {
+    fn width(text: &str) -> usize {
+        text.chars().count() * 7 + 10
+    }
+
+    let label = escape(label);
+    let message = escape(message);
+    let label_width = width(&label);
+    let message_width = width(&message);
+    let total_width = label_width + message_width;
+    let label_x = label_width / 2;
+    let message_x = label_width + message_width / 2;
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="20" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{total_width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{total_width}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="14">{label}</text><text x="{message_x}" y="14">{message}</text></g></svg>"##
+    )
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
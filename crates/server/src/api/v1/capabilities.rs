@@ -0,0 +1,43 @@
+use super::Json;
+use axum::{debug_handler, extract::State, routing::get, Router};
+use warg_api::{
+    v1::capabilities::{CapabilitiesResponse, UploadProtocol},
+    ApiVersion,
+};
+
+/// The APIs served by this registry.
+const APIS: &[&str] = &[
+    "badge",
+    "content",
+    "fetch",
+    "ledger",
+    "namespace",
+    "package",
+    "proof",
+    "verify",
+];
+
+pub fn into_router(content_sources: bool) -> Router {
+    Router::new()
+        .route("/", get(get_capabilities))
+        .with_state(content_sources)
+}
+
+#[debug_handler]
+async fn get_capabilities(State(content_sources): State<bool>) -> Json<CapabilitiesResponse> {
+    Json(CapabilitiesResponse {
+        api_versions: APIS
+            .iter()
+            .map(|api| (api.to_string(), vec![ApiVersion::V1]))
+            .collect(),
+        upload_protocols: vec![UploadProtocol::HttpPost],
+        events: true,
+        staged_publishes: true,
+        embargoed_releases: true,
+        package_snapshots: true,
+        multi_proofs: true,
+        content_sections: true,
+        content_sources,
+        ..Default::default()
+    })
+}
@@ -1,22 +1,28 @@
-use super::{Json, Path, RegistryHeader};
+use super::{problem_response, Json, Path, RegistryHeader};
+use crate::services::CoreService;
 use axum::{
     debug_handler, extract::State, http::StatusCode, response::IntoResponse, routing::get, Router,
 };
 use indexmap::IndexMap;
 use std::path::PathBuf;
 use url::Url;
-use warg_api::v1::content::{ContentError, ContentSource, ContentSourcesResponse};
+use warg_api::v1::content::{
+    ContentError, ContentSection, ContentSectionsResponse, ContentSource, ContentSourcesResponse,
+};
 use warg_crypto::hash::AnyHash;
+use warg_protocol::metadata::locate_custom_sections;
 
 #[derive(Clone)]
 pub struct Config {
+    core_service: CoreService,
     content_base_url: Url,
     files_dir: PathBuf,
 }
 
 impl Config {
-    pub fn new(content_base_url: Url, files_dir: PathBuf) -> Self {
+    pub fn new(core_service: CoreService, content_base_url: Url, files_dir: PathBuf) -> Self {
         Self {
+            core_service,
             content_base_url,
             files_dir,
         }
@@ -25,6 +31,7 @@ impl Config {
     pub fn into_router(self) -> Router {
         Router::new()
             .route("/:digest", get(get_content))
+            .route("/:digest/sections", get(get_content_sections))
             .with_state(self)
     }
 
@@ -32,6 +39,10 @@ impl Config {
         self.content_path(digest).is_file()
     }
 
+    fn content_size(&self, digest: &AnyHash) -> Option<u64> {
+        self.content_path(digest).metadata().ok().map(|m| m.len())
+    }
+
     fn content_file_name(&self, digest: &AnyHash) -> String {
         digest.to_string().replace(':', "-")
     }
@@ -54,30 +65,86 @@ struct ContentApiError(ContentError);
 
 impl IntoResponse for ContentApiError {
     fn into_response(self) -> axum::response::Response {
-        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+        problem_response(self.0.status(), self.0)
+    }
+}
+
+impl ContentApiError {
+    fn internal_error(e: impl std::fmt::Display) -> Self {
+        tracing::error!("unexpected error: {e}");
+        Self(ContentError::Message {
+            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            message: "an error occurred while processing the request".into(),
+        })
     }
 }
 
+/// Checks that the given content is present and may be served.
+async fn check_content(config: &Config, digest: &AnyHash) -> Result<(), ContentApiError> {
+    // Embargoed content is reported as not found until the embargo passes
+    let embargoed = config
+        .core_service
+        .is_embargoed(digest)
+        .await
+        .map_err(ContentApiError::internal_error)?;
+    if embargoed || !config.content_present(digest) {
+        return Err(ContentApiError(ContentError::ContentDigestNotFound(
+            digest.clone(),
+        )));
+    }
+
+    Ok(())
+}
+
 #[debug_handler]
 async fn get_content(
     State(config): State<Config>,
     Path(digest): Path<AnyHash>,
     RegistryHeader(_registry_header): RegistryHeader,
 ) -> Result<Json<ContentSourcesResponse>, ContentApiError> {
-    if !config.content_present(&digest) {
-        return Err(ContentApiError(ContentError::ContentDigestNotFound(digest)));
-    }
+    check_content(&config, &digest).await?;
 
     let mut content_sources = IndexMap::with_capacity(1);
     let url = config.content_url(&digest);
+    let size = config.content_size(&digest);
     content_sources.insert(
         digest,
         vec![ContentSource::HttpGet {
             url,
-            accept_ranges: false,
-            size: None,
+            accept_ranges: true,
+            size,
         }],
     );
 
     Ok(Json(ContentSourcesResponse { content_sources }))
 }
+
+#[debug_handler]
+async fn get_content_sections(
+    State(config): State<Config>,
+    Path(digest): Path<AnyHash>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<ContentSectionsResponse>, ContentApiError> {
+    check_content(&config, &digest).await?;
+
+    let bytes = tokio::fs::read(config.content_path(&digest))
+        .await
+        .map_err(ContentApiError::internal_error)?;
+    let sections = locate_custom_sections(&bytes).map_err(|e| {
+        ContentApiError(ContentError::Message {
+            status: StatusCode::UNPROCESSABLE_ENTITY.as_u16(),
+            message: format!("content `{digest}` is not valid WebAssembly: {e:#}"),
+        })
+    })?;
+
+    Ok(Json(ContentSectionsResponse {
+        sections: sections
+            .into_iter()
+            .map(|section| ContentSection {
+                name: section.name,
+                offset: section.range.start as u64,
+                size: section.range.len() as u64,
+            })
+            .collect(),
+    }))
+}
@@ -1,21 +1,28 @@
-use super::{Json, RegistryHeader};
+use super::{problem_response, Json, RegistryHeader};
 use crate::datastore::DataStoreError;
 use crate::services::CoreService;
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
 use axum::{
+    body::Body,
     debug_handler,
     extract::State,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{get, post},
     Router,
 };
+use bytes::Bytes;
+use futures::{future, StreamExt};
 use indexmap::IndexMap;
+use std::{
+    convert::Infallible,
+    time::{Duration, SystemTime},
+};
 use warg_api::v1::fetch::{
-    FetchError, FetchLogsRequest, FetchLogsResponse, FetchPackageNamesRequest,
-    FetchPackageNamesResponse, PublishedRecord,
+    FetchError, FetchLogsRequest, FetchLogsResponse, FetchLogsStreamItem, FetchPackageNamesRequest,
+    FetchPackageNamesResponse, PublishedRecord, FETCH_LOGS_STREAM_CONTENT_TYPE,
 };
 use warg_crypto::hash::{AnyHash, Sha256};
-use warg_protocol::registry::{LogId, RecordId, TimestampedCheckpoint};
+use warg_protocol::registry::{LogId, RecordId, RegistryLen, TimestampedCheckpoint};
 use warg_protocol::SerdeEnvelope;
 
 const DEFAULT_RECORDS_LIMIT: u16 = 100;
@@ -77,7 +84,7 @@ impl From<DataStoreError> for FetchApiError {
 
 impl IntoResponse for FetchApiError {
     fn into_response(self) -> axum::response::Response {
-        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+        problem_response(self.0.status(), self.0)
     }
 }
 
@@ -85,8 +92,9 @@ impl IntoResponse for FetchApiError {
 async fn fetch_logs(
     State(config): State<Config>,
     RegistryHeader(_registry_header): RegistryHeader,
+    headers: HeaderMap,
     Json(body): Json<FetchLogsRequest<'static>>,
-) -> Result<Json<FetchLogsResponse>, FetchApiError> {
+) -> Result<Response, FetchApiError> {
     let limit = body.limit.unwrap_or(DEFAULT_RECORDS_LIMIT);
     if limit == 0 || limit > MAX_RECORDS_LIMIT {
         return Err(FetchApiError::bad_request(format!(
@@ -94,23 +102,77 @@ async fn fetch_logs(
         )));
     }
 
-    let operator_fetch_token: Option<RecordId> = match body.operator {
-        Some(s) => Some(
-            s.parse::<AnyHash>()
-                .map_err(|_| FetchApiError(FetchError::FetchTokenNotFound(s.into_owned())))?
-                .into(),
-        ),
-        None => None,
-    };
-    let operator: Vec<PublishedRecord> = config
-        .core_service
-        .store()
-        .get_operator_records(
-            &LogId::operator_log::<Sha256>(),
+    let operator_fetch_token = body
+        .operator
+        .map(|s| parse_fetch_token(s.into_owned()))
+        .transpose()?;
+    let packages = body
+        .packages
+        .into_owned()
+        .into_iter()
+        .map(|(id, fetch_token)| Ok((id, fetch_token.map(parse_fetch_token).transpose()?)))
+        .collect::<Result<Vec<_>, FetchApiError>>()?;
+
+    let operator = operator_records(
+        &config.core_service,
+        body.log_length,
+        operator_fetch_token.as_ref(),
+        limit,
+    )
+    .await?;
+
+    if accepts_stream(&headers) {
+        let stream = LogStream {
+            core_service: config.core_service,
+            log_length: body.log_length,
+            limit,
+            packages: packages.into_iter(),
+            more: operator.len() == limit as usize,
+        };
+        return Ok(stream_logs(operator, stream));
+    }
+
+    let mut more = operator.len() == limit as usize;
+
+    let mut map = IndexMap::new();
+    for (id, since) in packages {
+        let records = package_records(
+            &config.core_service,
+            &id,
             body.log_length,
-            operator_fetch_token.as_ref(),
+            since.as_ref(),
             limit,
         )
+        .await?;
+        more |= records.len() == limit as usize;
+        map.insert(id, records);
+    }
+
+    Ok(Json(FetchLogsResponse {
+        more,
+        operator,
+        packages: map,
+        warnings: Vec::default(),
+    })
+    .into_response())
+}
+
+fn parse_fetch_token(token: String) -> Result<RecordId, FetchApiError> {
+    match token.parse::<AnyHash>() {
+        Ok(hash) => Ok(hash.into()),
+        Err(_) => Err(FetchApiError(FetchError::FetchTokenNotFound(token))),
+    }
+}
+
+async fn operator_records(
+    core_service: &CoreService,
+    log_length: RegistryLen,
+    since: Option<&RecordId>,
+    limit: u16,
+) -> Result<Vec<PublishedRecord>, DataStoreError> {
+    Ok(core_service
+        .store()
+        .get_operator_records(&LogId::operator_log::<Sha256>(), log_length, since, limit)
         .await?
         .into_iter()
         .map(|envelope| {
@@ -121,57 +183,166 @@ async fn fetch_logs(
                 fetch_token,
             }
         })
-        .collect();
+        .collect())
+}
 
-    let mut more = operator.len() == limit as usize;
+async fn package_records(
+    core_service: &CoreService,
+    log_id: &LogId,
+    log_length: RegistryLen,
+    since: Option<&RecordId>,
+    limit: u16,
+) -> Result<Vec<PublishedRecord>, DataStoreError> {
+    Ok(core_service
+        .store()
+        .get_package_records(log_id, log_length, since, limit)
+        .await?
+        .into_iter()
+        .map(|envelope| {
+            // use the record ID as the fetch token
+            let fetch_token = RecordId::package_record::<Sha256>(&envelope.envelope).to_string();
+            PublishedRecord {
+                envelope: envelope.into(),
+                fetch_token,
+            }
+        })
+        .collect())
+}
 
-    let mut map = IndexMap::new();
-    let packages = body.packages.into_owned();
-    for (id, fetch_token) in packages {
-        let since: Option<RecordId> = match fetch_token {
-            Some(s) => Some(
-                s.parse::<AnyHash>()
-                    .map_err(|_| FetchApiError(FetchError::FetchTokenNotFound(s)))?
-                    .into(),
-            ),
-            None => None,
+/// Determines if the `Accept` header of a request accepts a streamed fetch
+/// logs response.
+fn accepts_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|ty| ty.split(';').next())
+        .any(|ty| {
+            ty.trim()
+                .eq_ignore_ascii_case(FETCH_LOGS_STREAM_CONTENT_TYPE)
+        })
+}
+
+/// The package logs remaining to be streamed in a fetch logs response.
+struct LogStream {
+    core_service: CoreService,
+    log_length: RegistryLen,
+    limit: u16,
+    packages: std::vec::IntoIter<(LogId, Option<RecordId>)>,
+    more: bool,
+}
+
+/// Streams a fetch logs response as newline-delimited JSON items.
+fn stream_logs(operator: Vec<PublishedRecord>, stream: LogStream) -> Response {
+    let operator = encode_items(operator.into_iter().map(FetchLogsStreamItem::Operator));
+
+    // The records of each package log are fetched once the records of the
+    // previous log are streamed, so only one log's records are buffered
+    let packages = futures::stream::unfold(Some(stream), |stream| async move {
+        let mut stream = stream?;
+        let Some((log_id, since)) = stream.packages.next() else {
+            let end = encode_items([FetchLogsStreamItem::End { more: stream.more }]);
+            return Some((end, None));
         };
-        let records: Vec<PublishedRecord> = config
-            .core_service
-            .store()
-            .get_package_records(&id, body.log_length, since.as_ref(), limit)
-            .await?
-            .into_iter()
-            .map(|envelope| {
-                // use the record ID as the fetch token
-                let fetch_token =
-                    RecordId::package_record::<Sha256>(&envelope.envelope).to_string();
-                PublishedRecord {
-                    envelope: envelope.into(),
-                    fetch_token,
-                }
-            })
-            .collect();
-        more |= records.len() == limit as usize;
-        map.insert(id, records);
-    }
 
-    Ok(Json(FetchLogsResponse {
-        more,
-        operator,
-        packages: map,
-        warnings: Vec::default(),
-    }))
+        match package_records(
+            &stream.core_service,
+            &log_id,
+            stream.log_length,
+            since.as_ref(),
+            stream.limit,
+        )
+        .await
+        {
+            Ok(records) => {
+                stream.more |= records.len() == stream.limit as usize;
+                let items = records
+                    .into_iter()
+                    .map(|record| FetchLogsStreamItem::Package {
+                        log_id: log_id.clone(),
+                        record,
+                    });
+                Some((encode_items(items), Some(stream)))
+            }
+            Err(e) => {
+                let error = FetchLogsStreamItem::Error(FetchApiError::from(e).0);
+                Some((encode_items([error]), None))
+            }
+        }
+    });
+
+    let body = futures::stream::once(future::ready(operator))
+        .chain(packages)
+        .filter(|chunk| future::ready(!chunk.is_empty()))
+        .map(Ok::<_, Infallible>);
+
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(FETCH_LOGS_STREAM_CONTENT_TYPE),
+        )],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+fn encode_items(items: impl IntoIterator<Item = FetchLogsStreamItem>) -> Bytes {
+    let mut bytes = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut bytes, &item).unwrap();
+        bytes.push(b'\n');
+    }
+    bytes.into()
 }
 
 #[debug_handler]
 async fn fetch_checkpoint(
     State(config): State<Config>,
     RegistryHeader(_registry_header): RegistryHeader,
-) -> Result<Json<SerdeEnvelope<TimestampedCheckpoint>>, FetchApiError> {
-    Ok(Json(
-        config.core_service.store().get_latest_checkpoint().await?,
-    ))
+    headers: HeaderMap,
+) -> Result<Response, FetchApiError> {
+    let checkpoint: SerdeEnvelope<TimestampedCheckpoint> =
+        config.core_service.store().get_latest_checkpoint().await?;
+
+    // A checkpoint is identified by its log length and timestamp
+    let etag = format!(
+        "\"{len}-{timestamp}\"",
+        len = checkpoint.as_ref().checkpoint.log_length,
+        timestamp = checkpoint.as_ref().timestamp
+    );
+    let last_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(checkpoint.as_ref().timestamp);
+
+    let not_modified = match headers.get(header::IF_NONE_MATCH) {
+        Some(value) => value
+            .to_str()
+            .map(|v| {
+                v.split(',')
+                    .map(|t| t.trim().trim_start_matches("W/"))
+                    .any(|t| t == etag || t == "*")
+            })
+            .unwrap_or(false),
+        None => headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| httpdate::parse_http_date(v).ok())
+            .is_some_and(|since| last_modified <= since),
+    };
+
+    let headers = [
+        (header::ETAG, HeaderValue::try_from(etag).unwrap()),
+        (
+            header::LAST_MODIFIED,
+            HeaderValue::try_from(httpdate::fmt_http_date(last_modified)).unwrap(),
+        ),
+        (header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+    ];
+
+    if not_modified {
+        Ok((StatusCode::NOT_MODIFIED, headers).into_response())
+    } else {
+        Ok((headers, Json(checkpoint)).into_response())
+    }
 }
 
 #[debug_handler]
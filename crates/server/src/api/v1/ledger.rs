@@ -1,4 +1,4 @@
-use super::{Json, Path, RegistryHeader};
+use super::{problem_response, Json, Path, RegistryHeader};
 use crate::datastore::DataStoreError;
 use crate::services::CoreService;
 use axum::http::StatusCode;
@@ -46,7 +46,7 @@ impl From<DataStoreError> for LedgerApiError {
 
 impl IntoResponse for LedgerApiError {
     fn into_response(self) -> axum::response::Response {
-        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+        problem_response(self.0.status(), self.0)
     }
 }
 
@@ -1,27 +1,37 @@
 use crate::{
-    policy::{content::ContentPolicy, record::RecordPolicy},
-    services::CoreService,
+    policy::{
+        approval::ApprovalPolicy, content::ContentPolicy, record::RecordPolicy,
+        semver::SemverPolicy, sources::ContentSourcePolicy, PolicyMode,
+    },
+    services::{CoreService, TyposquatConfig},
 };
 use anyhow::Result;
 use axum::{
     async_trait,
     extract::{
-        rejection::{JsonRejection, PathRejection},
+        rejection::{JsonRejection, PathRejection, QueryRejection},
         FromRequest, FromRequestParts,
     },
-    http::{request::Parts, StatusCode},
-    response::IntoResponse,
+    http::{header, request::Parts, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Router,
 };
-use serde::{Serialize, Serializer};
-use std::{path::PathBuf, str::FromStr, sync::Arc};
+use serde::Serialize;
+use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 use url::Url;
-use warg_api::v1::REGISTRY_HEADER_NAME;
+use warg_api::v1::{
+    problem::{ProblemDetails, PROBLEM_CONTENT_TYPE},
+    REGISTRY_HEADER_NAME,
+};
 
+pub mod advisory;
+pub mod badge;
+pub mod capabilities;
 pub mod content;
 pub mod fetch;
 pub mod ledger;
 pub mod monitor;
+pub mod namespace;
 pub mod package;
 pub mod proof;
 
@@ -41,17 +51,24 @@ where
     }
 }
 
-fn serialize_status<S>(status: &StatusCode, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    serializer.serialize_u16(status.as_u16())
+/// Creates an error response with the given status code and an API error
+/// serialized as its problem details body.
+pub(crate) fn problem_response(status: u16, error: impl Serialize) -> Response {
+    let mut response = (
+        StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        axum::Json(error),
+    )
+        .into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+    );
+    response
 }
 
 /// Represents a generic error from the API.
-#[derive(Serialize, Debug)]
+#[derive(Debug)]
 pub struct Error {
-    #[serde(serialize_with = "serialize_status")]
     status: StatusCode,
     message: String,
 }
@@ -67,7 +84,8 @@ impl From<JsonRejection> for Error {
 
 impl IntoResponse for Error {
     fn into_response(self) -> axum::response::Response {
-        (self.status, axum::Json(self)).into_response()
+        let status = self.status.as_u16();
+        problem_response(status, ProblemDetails::from_status(status, self.message))
     }
 }
 
@@ -87,6 +105,22 @@ impl From<PathRejection> for Error {
     }
 }
 
+/// An extractor that wraps the query extractor of Axum.
+///
+/// This extractor returns an API error on rejection.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(Error))]
+pub struct Query<T>(T);
+
+impl From<QueryRejection> for Error {
+    fn from(rejection: QueryRejection) -> Self {
+        Self {
+            status: rejection.status(),
+            message: rejection.body_text(),
+        }
+    }
+}
+
 pub async fn not_found() -> impl IntoResponse {
     Error {
         status: StatusCode::NOT_FOUND,
@@ -103,14 +137,14 @@ impl<S> FromRequestParts<S> for RegistryHeader
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, &'static str);
+    type Rejection = Error;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         if parts.headers.contains_key(REGISTRY_HEADER_NAME) {
-            Err((
-                StatusCode::NOT_IMPLEMENTED,
-                "`Warg-Registry` header is not supported",
-            ))
+            Err(Error {
+                status: StatusCode::NOT_IMPLEMENTED,
+                message: "`Warg-Registry` header is not supported".to_string(),
+            })
         } else {
             Ok(RegistryHeader(None))
         }
@@ -124,6 +158,7 @@ impl FromStr for RegistryHeader {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_router(
     content_base_url: Url,
     core: CoreService,
@@ -131,7 +166,14 @@ pub fn create_router(
     files_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    policy_mode: PolicyMode,
+    staged_publish_ttl: Duration,
+    typosquat: Option<TyposquatConfig>,
+    approvals: Option<ApprovalPolicy>,
+    semver: Option<SemverPolicy>,
+    content_sources: Option<ContentSourcePolicy>,
 ) -> Router {
+    let external_content = content_sources.is_some();
     let proof_config = proof::Config::new(core.clone());
     let package_config = package::Config::new(
         core.clone(),
@@ -139,16 +181,29 @@ pub fn create_router(
         temp_dir,
         content_policy,
         record_policy,
+        policy_mode,
+        staged_publish_ttl,
+        typosquat,
+        approvals,
+        semver,
+        content_sources,
     );
     let fetch_config = fetch::Config::new(core.clone());
-    let content_config = content::Config::new(content_base_url, files_dir);
+    let content_config = content::Config::new(core.clone(), content_base_url, files_dir);
     let monitor_config = monitor::Config::new(core.clone());
-    let ledger_config = ledger::Config::new(core);
+    let ledger_config = ledger::Config::new(core.clone());
+    let namespace_config = namespace::Config::new(core.clone());
+    let advisory_config = advisory::Config::new(core.clone());
+    let badge_config = badge::Config::new(core);
 
     Router::new()
+        .nest("/advisories", advisory_config.into_router())
+        .nest("/badge", badge_config.into_router())
+        .nest("/capabilities", capabilities::into_router(external_content))
         .nest("/content", content_config.into_router())
         .nest("/fetch", fetch_config.into_router())
         .nest("/ledger", ledger_config.into_router())
+        .nest("/namespace", namespace_config.into_router())
         .nest("/package", package_config.into_router())
         .nest("/proof", proof_config.into_router())
         .nest("/verify", monitor_config.into_router())
@@ -1,4 +1,4 @@
-use super::{Json, RegistryHeader};
+use super::{problem_response, Json, RegistryHeader};
 use crate::datastore::DataStoreError;
 use crate::services::CoreService;
 use axum::http::StatusCode;
@@ -40,7 +40,7 @@ impl From<DataStoreError> for MonitorApiError {
 
 impl IntoResponse for MonitorApiError {
     fn into_response(self) -> axum::response::Response {
-        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+        problem_response(self.0.status(), self.0)
     }
 }
 
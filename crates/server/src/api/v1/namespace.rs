@@ -0,0 +1,86 @@
+use super::{problem_response, Json, RegistryHeader};
+use crate::datastore::DataStoreError;
+use crate::services::CoreService;
+use axum::http::StatusCode;
+use axum::{debug_handler, extract::State, response::IntoResponse, routing::get, Router};
+use std::{collections::HashMap, time::UNIX_EPOCH};
+use warg_api::v1::namespace::{
+    Namespace, NamespaceError, NamespaceState, NamespaceSuspension, NamespacesResponse,
+};
+use warg_crypto::hash::Sha256;
+use warg_protocol::{operator, registry::LogId};
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService) -> Self {
+        Self { core_service }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/", get(list_namespaces))
+            .with_state(self)
+    }
+}
+
+struct NamespaceApiError(NamespaceError);
+
+impl From<DataStoreError> for NamespaceApiError {
+    fn from(e: DataStoreError) -> Self {
+        tracing::error!("unexpected data store error: {e}");
+
+        Self(NamespaceError::Message {
+            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            message: "an error occurred while processing the request".into(),
+        })
+    }
+}
+
+impl IntoResponse for NamespaceApiError {
+    fn into_response(self) -> axum::response::Response {
+        problem_response(self.0.status(), self.0)
+    }
+}
+
+#[debug_handler]
+async fn list_namespaces(
+    State(config): State<Config>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<NamespacesResponse>, NamespaceApiError> {
+    let store = config.core_service.store();
+    let mut suspensions = store
+        .list_namespace_suspensions()
+        .await?
+        .into_iter()
+        .map(|s| (s.namespace.clone(), s))
+        .collect::<HashMap<_, _>>();
+
+    let namespaces = store
+        .get_namespaces(&LogId::operator_log::<Sha256>())
+        .await?
+        .into_iter()
+        .map(|(name, state)| Namespace {
+            suspension: suspensions.remove(&name).map(|s| NamespaceSuspension {
+                reason: s.reason,
+                suspended_at: s
+                    .suspended_at
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+            }),
+            name,
+            state: match state {
+                operator::NamespaceState::Defined => NamespaceState::Defined,
+                operator::NamespaceState::Imported { registry } => {
+                    NamespaceState::Imported { registry }
+                }
+            },
+        })
+        .collect();
+
+    Ok(Json(NamespacesResponse { namespaces }))
+}
@@ -1,38 +1,72 @@
-use super::{Json, Path, RegistryHeader};
+use super::{problem_response, Json, Path, Query, RegistryHeader};
 use crate::{
-    datastore::{DataStoreError, RecordStatus},
+    api::docs,
+    datastore::{
+        ApprovalStatus, DataStoreError, PackageFlag, PolicyDecision, PolicyKind, RecordStatus,
+    },
     policy::{
-        content::{ContentPolicy, ContentPolicyError},
+        approval::ApprovalPolicy,
+        content::{ContentPolicy, ContentPolicyError, ContentPolicyResult},
         record::{RecordPolicy, RecordPolicyError},
+        semver::{SemverAction, SemverPolicy},
+        sources::ContentSourcePolicy,
+        PolicyMode,
     },
-    services::CoreService,
+    services::{CoreService, CoreServiceError, TyposquatConfig},
 };
 use axum::{
-    body::{Body, BodyDataStream},
+    body::Body,
     debug_handler,
     extract::State,
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Router,
 };
-use futures::StreamExt;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
 use indexmap::IndexMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tempfile::NamedTempFile;
 use tokio::io::AsyncWriteExt;
+use url::Url;
 use warg_api::v1::package::{
-    MissingContent, PackageError, PackageRecord, PackageRecordState, PublishRecordRequest,
-    UploadEndpoint,
+    ApproveRecordRequest, AwaitRecordQuery, ContentSource, CreateInviteRequest, KeyEvent,
+    KeyEventKind, ListRecordsQuery, MissingContent, PackageAdvisoriesResponse, PackageAdvisory,
+    PackageError, PackageInviteInfo, PackageInvitesResponse, PackageKeyHistoryResponse,
+    PackageMetadataResponse, PackageRecord, PackageRecordState, PackageRecordsResponse,
+    PackageSnapshotQuery, PackageSnapshotResponse, PublishRecordRequest, RedeemInviteRequest,
+    ReleaseMetadata, UploadEndpoint,
+};
+use warg_crypto::{
+    hash::{AnyHash, Hasher, Sha256},
+    Encode, Signable,
 };
-use warg_crypto::hash::{AnyHash, Sha256};
 use warg_protocol::{
-    package,
-    registry::{LogId, RecordId},
-    ProtoEnvelope, Record as _,
+    interface::ContentInterfaces,
+    metadata::{ContentMetadata, ReleaseNotes, RELEASE_NOTES_KIND},
+    package::{self, ContentType, InviteRedemption, PackageInvite, RecordApproval},
+    registry::{LogId, PackageName, RecordId},
+    ProtoEnvelope, Record as _, Version,
 };
 
+/// The maximum time a request to await a record may wait.
+const MAX_AWAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The maximum length of the identifier of a package invite.
+const MAX_INVITE_ID_LEN: usize = 128;
+
+/// The default number of records listed by a request to list package records.
+const DEFAULT_RECORDS_LIMIT: u16 = 20;
+
+/// The maximum number of records listed by a request to list package records.
+const MAX_RECORDS_LIMIT: u16 = 100;
+
+/// The number of package names read at a time when checking for typosquats.
+const TYPOSQUAT_PAGE_SIZE: u16 = 1000;
+
 #[derive(Clone)]
 pub struct Config {
     core_service: CoreService,
@@ -40,15 +74,29 @@ pub struct Config {
     temp_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    policy_mode: PolicyMode,
+    staged_publish_ttl: Duration,
+    typosquat: Option<TyposquatConfig>,
+    approvals: Option<ApprovalPolicy>,
+    semver: Option<SemverPolicy>,
+    content_sources: Option<ContentSourcePolicy>,
+    http: reqwest::Client,
 }
 
 impl Config {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         core_service: CoreService,
         files_dir: PathBuf,
         temp_dir: PathBuf,
         content_policy: Option<Arc<dyn ContentPolicy>>,
         record_policy: Option<Arc<dyn RecordPolicy>>,
+        policy_mode: PolicyMode,
+        staged_publish_ttl: Duration,
+        typosquat: Option<TyposquatConfig>,
+        approvals: Option<ApprovalPolicy>,
+        semver: Option<SemverPolicy>,
+        content_sources: Option<ContentSourcePolicy>,
     ) -> Self {
         Self {
             core_service,
@@ -56,13 +104,142 @@ impl Config {
             temp_dir,
             content_policy,
             record_policy,
+            policy_mode,
+            staged_publish_ttl,
+            typosquat,
+            approvals,
+            semver,
+            content_sources,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn record_state(
+        &self,
+        log_id: LogId,
+        record_id: RecordId,
+    ) -> Result<(HeaderMap, PackageRecord), PackageApiError> {
+        let staged = self.staged_expiry(&log_id, &record_id).await?;
+        let awaiting_approval = self.awaiting_approval(&log_id, &record_id).await?;
+        let record = self
+            .core_service
+            .store()
+            .get_package_record(&log_id, &record_id)
+            .await?;
+
+        let mut headers = HeaderMap::new();
+        let state = match record.status {
+            RecordStatus::MissingContent(missing) if staged.is_some() => {
+                let missing_content = self.build_missing_content(&log_id, &record_id, &missing);
+                staged_state(missing_content, staged.unwrap())
+            }
+            RecordStatus::Pending if staged.is_some() => {
+                staged_state(IndexMap::new(), staged.unwrap())
+            }
+            RecordStatus::MissingContent(missing) if awaiting_approval => {
+                let missing_content = self.build_missing_content(&log_id, &record_id, &missing);
+                PackageRecordState::AwaitingApproval { missing_content }
+            }
+            RecordStatus::Pending if awaiting_approval => PackageRecordState::AwaitingApproval {
+                missing_content: IndexMap::new(),
+            },
+            RecordStatus::MissingContent(missing) => {
+                let missing_content = self.build_missing_content(&log_id, &record_id, &missing);
+                PackageRecordState::Sourcing { missing_content }
+            }
+            // Validated is considered still processing until included in a checkpoint
+            RecordStatus::Pending | RecordStatus::Validated => {
+                // The `Retry-After` header is in whole seconds, so round up
+                let interval = self.core_service.checkpoint_interval();
+                let seconds = interval.as_secs() + u64::from(interval.subsec_nanos() > 0);
+                headers.insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+                self.processing_state()
+            }
+            RecordStatus::Rejected(reason) => PackageRecordState::Rejected { reason },
+            RecordStatus::Published => PackageRecordState::Published {
+                registry_index: record.registry_index.unwrap(),
+            },
+        };
+
+        Ok((headers, PackageRecord { record_id, state }))
+    }
+
+    /// Gets the time at which a staged record expires.
+    ///
+    /// Staged records are expired as they are accessed: a record found to be
+    /// past its expiry is rejected and `None` is returned. The record
+    /// retention job also rejects staged records that are never accessed.
+    async fn staged_expiry(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<SystemTime>, PackageApiError> {
+        let store = self.core_service.store();
+        let Some(expires_at) = store.get_staged_publish(log_id, record_id).await? else {
+            return Ok(None);
+        };
+
+        if expires_at > SystemTime::now() {
+            return Ok(Some(expires_at));
+        }
+
+        // Only the request that removes the staging rejects the record
+        if store.remove_staged_publish(log_id, record_id).await? {
+            tracing::info!("staged record `{record_id}` from `{log_id}` expired");
+            store
+                .reject_package_record(log_id, record_id, "the staged publish expired")
+                .await?;
+        }
+
+        Ok(None)
+    }
+
+    /// Determines if a record requires approval and has not been approved.
+    async fn awaiting_approval(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<bool, PackageApiError> {
+        Ok(matches!(
+            self.core_service
+                .store()
+                .get_record_approval(log_id, record_id)
+                .await?,
+            Some(ApprovalStatus::Awaiting)
+        ))
+    }
+
+    /// Gets the state of a record that is processing.
+    ///
+    /// Records are published with the next checkpoint, so clients are hinted
+    /// to check again after the checkpoint interval.
+    fn processing_state(&self) -> PackageRecordState {
+        PackageRecordState::Processing {
+            retry_after_ms: Some(
+                self.core_service
+                    .checkpoint_interval()
+                    .as_millis()
+                    .try_into()
+                    .unwrap_or(u64::MAX),
+            ),
         }
     }
 
     pub fn into_router(self) -> Router {
         Router::new()
+            .route("/:log_id/metadata", get(get_metadata))
+            .route("/:log_id/advisories", get(get_advisories))
+            .route("/:log_id/keys", get(get_key_history))
+            .route("/:log_id/invites", get(list_invites).post(create_invite))
+            .route("/:log_id/invites/:invite_id/redeem", post(redeem_invite))
+            .route("/:log_id/snapshot", get(get_snapshot))
+            .route("/:log_id/records", get(list_records))
             .route("/:log_id/record", post(publish_record))
             .route("/:log_id/record/:record_id", get(get_record))
+            .route("/:log_id/record/:record_id/await", get(await_record))
+            .route("/:log_id/record/:record_id/commit", post(commit_record))
+            .route("/:log_id/record/:record_id/abandon", post(abandon_record))
+            .route("/:log_id/record/:record_id/approve", post(approve_record))
             .route(
                 "/:log_id/record/:record_id/content/:digest",
                 post(upload_content),
@@ -74,6 +251,27 @@ impl Config {
         self.content_path(digest).is_file()
     }
 
+    /// Checks content that is already present against the given content
+    /// policy.
+    async fn check_present_content(
+        &self,
+        policy: &dyn ContentPolicy,
+        name: &PackageName,
+        digest: &AnyHash,
+        content_type: ContentType,
+    ) -> Result<ContentPolicyResult<()>, PackageApiError> {
+        let bytes = tokio::fs::read(self.content_path(digest))
+            .await
+            .map_err(PackageApiError::internal_error)?;
+
+        Ok(policy
+            .new_stream_policy(name, digest, content_type)
+            .and_then(|mut policy| {
+                policy.check(&bytes)?;
+                policy.finalize()
+            }))
+    }
+
     fn content_file_name(&self, digest: &AnyHash) -> String {
         digest.to_string().replace(':', "-")
     }
@@ -82,6 +280,455 @@ impl Config {
         self.files_dir.join(self.content_file_name(digest))
     }
 
+    /// Logs and stores a policy decision for a package record.
+    async fn record_policy_decision(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        kind: PolicyKind,
+        digest: Option<&AnyHash>,
+        rejection: Option<String>,
+    ) -> Result<(), DataStoreError> {
+        let enforced = self.policy_mode.is_enforcing();
+        match (&rejection, enforced) {
+            (Some(reason), true) => tracing::info!(
+                "{kind} policy rejected record `{record_id}` from `{log_id}`: {reason}",
+                kind = kind.as_str()
+            ),
+            (Some(reason), false) => tracing::warn!(
+                "{kind} policy would have rejected record `{record_id}` from `{log_id}` (dry run): {reason}",
+                kind = kind.as_str()
+            ),
+            (None, _) => tracing::debug!(
+                "{kind} policy allowed record `{record_id}` from `{log_id}`",
+                kind = kind.as_str()
+            ),
+        }
+
+        self.core_service
+            .store()
+            .store_policy_decision(&PolicyDecision {
+                log_id: log_id.clone(),
+                record_id: record_id.clone(),
+                kind,
+                digest: digest.cloned(),
+                enforced,
+                rejection,
+            })
+            .await
+    }
+
+    /// Checks the interfaces of released content against the previous
+    /// release of the package under the server's semver policy.
+    ///
+    /// Returns the reason the release was rejected, if any; breaking changes
+    /// are only logged if the policy's action is to warn. Content whose
+    /// interfaces cannot be extracted is not checked.
+    async fn check_semver(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        name: &PackageName,
+        version: &Version,
+        digest: &AnyHash,
+    ) -> Result<Option<String>, PackageApiError> {
+        let Some(policy) = self.semver.as_ref().filter(|p| p.applies_to(name)) else {
+            return Ok(None);
+        };
+
+        let state = match self.core_service.package_log_state(log_id).await {
+            Ok(state) => state,
+            Err(CoreServiceError::DataStore(DataStoreError::LogNotFound(_))) => return Ok(None),
+            Err(CoreServiceError::DataStore(e)) => return Err(e.into()),
+            Err(e) => return Err(PackageApiError::internal_error(e)),
+        };
+        let Some((previous_version, previous_digest)) = state
+            .find_previous_release(version)
+            .filter(|release| release.content_type.is_wasm())
+            .and_then(|release| Some((&release.version, release.content()?)))
+        else {
+            return Ok(None);
+        };
+
+        let (Some(previous), Some(current)) = (
+            self.content_interfaces(previous_digest).await,
+            self.content_interfaces(digest).await,
+        ) else {
+            return Ok(None);
+        };
+
+        let Some(reason) = policy.check(previous_version, &previous, version, &current) else {
+            return Ok(None);
+        };
+
+        match policy.action {
+            SemverAction::Reject => {
+                self.record_policy_decision(
+                    log_id,
+                    record_id,
+                    PolicyKind::Content,
+                    Some(digest),
+                    Some(reason.clone()),
+                )
+                .await?;
+
+                Ok(Some(reason).filter(|_| self.policy_mode.is_enforcing()))
+            }
+            SemverAction::Warn => {
+                tracing::warn!("record `{record_id}` of package `{name}`: {reason}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Extracts the interfaces of stored content.
+    ///
+    /// Content that cannot be read or decoded yields no interfaces.
+    async fn content_interfaces(&self, digest: &AnyHash) -> Option<ContentInterfaces> {
+        let bytes = match tokio::fs::read(self.content_path(digest)).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("failed to read content `{digest}`: {e}");
+                return None;
+            }
+        };
+
+        match ContentInterfaces::from_content(&bytes) {
+            Ok(interfaces) => Some(interfaces),
+            Err(e) => {
+                tracing::warn!("failed to extract interfaces of content `{digest}`: {e:#}");
+                None
+            }
+        }
+    }
+
+    /// Extracts and stores the metadata of uploaded content.
+    ///
+    /// Content with invalid metadata is still accepted, but no metadata
+    /// is stored for it.
+    async fn store_content_metadata(&self, digest: &AnyHash) -> Result<(), PackageApiError> {
+        let bytes = tokio::fs::read(self.content_path(digest))
+            .await
+            .map_err(PackageApiError::internal_error)?;
+
+        let metadata = match ContentMetadata::from_content(&bytes) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                tracing::warn!("failed to extract metadata from content `{digest}`: {e:#}");
+                return Ok(());
+            }
+        };
+
+        if metadata.is_empty() {
+            return Ok(());
+        }
+
+        Ok(self
+            .core_service
+            .store()
+            .store_content_metadata(digest, &metadata)
+            .await?)
+    }
+
+    /// Reads the latest release notes attached to the given release.
+    ///
+    /// Release notes that are embargoed or that cannot be read are omitted.
+    async fn release_notes(
+        &self,
+        state: &package::LogState,
+        release: &package::Release,
+    ) -> Result<Option<ReleaseNotes>, PackageApiError> {
+        let Some(attestation) = state
+            .attestations(&release.version)
+            .iter()
+            .rev()
+            .find(|a| a.kind == RELEASE_NOTES_KIND)
+        else {
+            return Ok(None);
+        };
+
+        let digest = &attestation.content;
+        if self.core_service.is_embargoed(digest).await? {
+            return Ok(None);
+        }
+
+        let bytes = match tokio::fs::read(self.content_path(digest)).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("failed to read release notes `{digest}`: {e}");
+                return Ok(None);
+            }
+        };
+
+        match ReleaseNotes::from_content(&bytes) {
+            Ok(notes) => Ok(Some(notes)),
+            Err(e) => {
+                tracing::warn!("invalid release notes `{digest}`: {e:#}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Flags a new package for moderation if its name is suspiciously
+    /// similar to the name of an existing package.
+    ///
+    /// The check is advisory: the record is published regardless.
+    async fn flag_similar_name(
+        &self,
+        typosquat: &TyposquatConfig,
+        log_id: &LogId,
+        name: &PackageName,
+    ) -> Result<(), DataStoreError> {
+        let store = self.core_service.store();
+        let mut after = None;
+        let similarity = loop {
+            let names = store
+                .list_package_names(after.as_ref(), TYPOSQUAT_PAGE_SIZE)
+                .await?;
+            if let Some(similarity) = typosquat.find_similar(name, &names) {
+                break Some(similarity);
+            }
+            if names.len() < usize::from(TYPOSQUAT_PAGE_SIZE) {
+                break None;
+            }
+            after = names.into_iter().last();
+        };
+
+        let Some(similarity) = similarity else {
+            return Ok(());
+        };
+
+        tracing::warn!(
+            "package `{name}` was flagged for moderation: {reason}",
+            reason = similarity.reason
+        );
+        store
+            .store_package_flag(&PackageFlag {
+                log_id: log_id.clone(),
+                name: name.clone(),
+                similar_to: similarity.similar_to,
+                reason: similarity.reason,
+                flagged_at: SystemTime::now(),
+            })
+            .await
+    }
+
+    /// Receives content of a record, verifying it, checking it against the
+    /// server's policies, and storing it.
+    ///
+    /// Returns whether the content was the last content the record needed.
+    async fn receive_content<E: std::fmt::Display>(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+        declared_len: Option<u64>,
+        stream: impl Stream<Item = Result<Bytes, E>> + Unpin,
+    ) -> Result<bool, PackageApiError> {
+        // Content policies check the content against the type declared for it
+        // in the record
+        let record = self
+            .core_service
+            .store()
+            .get_package_record(log_id, record_id)
+            .await?;
+        let content_type = record
+            .envelope
+            .as_ref()
+            .entries
+            .iter()
+            .find(|entry| entry.content() == Some(digest))
+            .and_then(package::PackageEntry::content_type)
+            .unwrap_or_default();
+        let released = record
+            .envelope
+            .as_ref()
+            .entries
+            .iter()
+            .find_map(|entry| match entry {
+                package::PackageEntry::Release {
+                    version,
+                    content,
+                    content_type,
+                } if content == digest && content_type.is_wasm() => Some(version.clone()),
+                _ => None,
+            });
+        let content_policy = self.content_policy.as_deref();
+        let name = match content_policy {
+            Some(_) => Some(
+                self.core_service
+                    .store()
+                    .get_package_names(std::slice::from_ref(log_id))
+                    .await?
+                    .swap_remove(log_id)
+                    .flatten()
+                    .ok_or_else(|| PackageApiError(PackageError::LogNotFound(log_id.clone())))?,
+            ),
+            None => None,
+        };
+        let release_notes = record.envelope.as_ref().entries.iter().any(|entry| {
+            matches!(entry, package::PackageEntry::Attest { kind, content, .. } if kind == RELEASE_NOTES_KIND && content == digest)
+        });
+
+        let tmp_path = NamedTempFile::new_in(&self.temp_dir)
+            .map_err(PackageApiError::internal_error)?
+            .into_temp_path();
+
+        tracing::debug!(
+            "receiving content for record `{record_id}` from `{log_id}` to `{path}`",
+            path = tmp_path.display()
+        );
+
+        let rejection = process_content(
+            &tmp_path,
+            digest,
+            declared_len,
+            stream,
+            content_policy
+                .zip(name.as_ref())
+                .map(|(policy, name)| (policy, name, content_type)),
+            self.policy_mode,
+        )
+        .await?;
+
+        if content_policy.is_some() {
+            self.record_policy_decision(
+                log_id,
+                record_id,
+                PolicyKind::Content,
+                Some(digest),
+                rejection.as_ref().map(ToString::to_string),
+            )
+            .await?;
+        }
+
+        // If the content was rejected, transition the record itself to rejected
+        if let Some(e) = rejection.filter(|_| self.policy_mode.is_enforcing()) {
+            let ContentPolicyError::Rejection(reason) = &e;
+            self.core_service
+                .store()
+                .reject_package_record(
+                    log_id,
+                    record_id,
+                    &format!("content with digest `{digest}` was rejected by policy: {reason}"),
+                )
+                .await?;
+
+            return Err(e.into());
+        }
+
+        // Release notes are served by the registry, so they must be valid
+        if release_notes {
+            let bytes = tokio::fs::read(&tmp_path)
+                .await
+                .map_err(PackageApiError::internal_error)?;
+            if let Err(e) = ReleaseNotes::from_content(&bytes) {
+                return Err(PackageApiError::bad_request(format!(
+                    "invalid release notes with digest `{digest}`: {e:#}"
+                )));
+            }
+        }
+
+        // Likewise, documentation archives are extracted and served
+        if content_type == ContentType::DocsArchive {
+            let bytes = tokio::fs::read(&tmp_path)
+                .await
+                .map_err(PackageApiError::internal_error)?;
+            let res = tokio::task::spawn_blocking(move || docs::validate_archive(&bytes))
+                .await
+                .map_err(PackageApiError::internal_error)?;
+            if let Err(e) = res {
+                return Err(PackageApiError::bad_request(format!(
+                    "invalid documentation archive with digest `{digest}`: {e:#}"
+                )));
+            }
+        }
+
+        tmp_path
+            .persist(self.content_path(digest))
+            .map_err(PackageApiError::internal_error)?;
+
+        self.store_content_metadata(digest).await?;
+
+        // Released content is checked against the previous release once present
+        if let Some(version) = &released {
+            let name = self
+                .core_service
+                .store()
+                .get_package_names(std::slice::from_ref(log_id))
+                .await?
+                .swap_remove(log_id)
+                .flatten()
+                .ok_or_else(|| PackageApiError(PackageError::LogNotFound(log_id.clone())))?;
+            if let Some(reason) = self
+                .check_semver(log_id, record_id, &name, version, digest)
+                .await?
+            {
+                self.core_service
+                    .store()
+                    .reject_package_record(log_id, record_id, &reason)
+                    .await?;
+
+                return Err(PackageApiError(PackageError::Rejection(reason)));
+            }
+        }
+
+        Ok(self
+            .core_service
+            .store()
+            .set_content_present(log_id, record_id, digest)
+            .await?)
+    }
+
+    /// Fetches missing content of a record from the given external sources.
+    ///
+    /// The sources are tried in order until content is received from one of
+    /// them.
+    async fn fetch_content(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+        sources: &[ContentSource],
+    ) -> Result<(), PackageApiError> {
+        let mut error = None;
+        for ContentSource::HttpGet { url, size, .. } in sources {
+            tracing::debug!(
+                "fetching content `{digest}` for record `{record_id}` from `{log_id}` from `{url}`"
+            );
+
+            let response = match self
+                .http
+                .get(url.as_str())
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::info!("failed to fetch content `{digest}` from `{url}`: {e}");
+                    error = Some(format!("failed to fetch content from `{url}`: {e}"));
+                    continue;
+                }
+            };
+
+            let declared_len = size.or(response.content_length());
+            self.receive_content(
+                log_id,
+                record_id,
+                digest,
+                declared_len,
+                response.bytes_stream(),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        Err(PackageApiError::bad_request(error.unwrap_or_else(|| {
+            format!("no content sources were provided for content digest `{digest}`")
+        })))
+    }
+
     fn build_missing_content<'a>(
         &self,
         log_id: &LogId,
@@ -117,6 +764,13 @@ impl PackageApiError {
         })
     }
 
+    fn conflict(message: impl ToString) -> Self {
+        Self(PackageError::Message {
+            status: StatusCode::CONFLICT.as_u16(),
+            message: message.to_string(),
+        })
+    }
+
     fn internal_error(e: impl std::fmt::Display) -> Self {
         tracing::error!("unexpected error: {e}");
         Self(PackageError::Message {
@@ -125,6 +779,13 @@ impl PackageApiError {
         })
     }
 
+    fn forbidden(message: impl ToString) -> Self {
+        Self(PackageError::Message {
+            status: StatusCode::FORBIDDEN.as_u16(),
+            message: message.to_string(),
+        })
+    }
+
     fn unsupported(message: impl ToString) -> Self {
         Self(PackageError::Message {
             status: StatusCode::NOT_IMPLEMENTED.as_u16(),
@@ -141,6 +802,10 @@ impl From<DataStoreError> for PackageApiError {
             }
             DataStoreError::LogNotFound(id) => PackageError::LogNotFound(id),
             DataStoreError::RecordNotFound(id) => PackageError::RecordNotFound(id),
+            DataStoreError::CheckpointNotFound(log_length) => PackageError::Message {
+                status: StatusCode::NOT_FOUND.as_u16(),
+                message: format!("checkpoint at log length {log_length} was not found"),
+            },
             DataStoreError::UnknownKey(_) | DataStoreError::SignatureVerificationFailed(_) => {
                 PackageError::Unauthorized(e.to_string())
             }
@@ -177,205 +842,1036 @@ impl From<RecordPolicyError> for PackageApiError {
 
 impl IntoResponse for PackageApiError {
     fn into_response(self) -> axum::response::Response {
-        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+        problem_response(self.0.status(), self.0)
     }
 }
 
 #[debug_handler]
-async fn publish_record(
+async fn get_metadata(
     State(config): State<Config>,
     Path(log_id): Path<LogId>,
     RegistryHeader(_registry_header): RegistryHeader,
-    Json(body): Json<PublishRecordRequest<'static>>,
-) -> Result<impl IntoResponse, PackageApiError> {
-    let expected_log_id = LogId::package_log::<Sha256>(&body.package_name);
-    if expected_log_id != log_id {
-        return Err(PackageApiError::bad_request(format!(
-            "package log identifier `{expected_log_id}` derived from `{name}` does not match provided log identifier `{log_id}`",
-            name = body.package_name
-        )));
-    }
+) -> Result<Json<PackageMetadataResponse>, PackageApiError> {
+    let state = match config.core_service.package_log_state(&log_id).await {
+        Ok(state) => state,
+        Err(CoreServiceError::DataStore(e)) => return Err(e.into()),
+        Err(e) => return Err(PackageApiError::internal_error(e)),
+    };
 
-    let record: ProtoEnvelope<package::PackageRecord> = body
-        .record
-        .into_owned()
-        .try_into()
-        .map_err(PackageApiError::bad_request)?;
-
-    // Specifying content sources is not allowed in this implementation
-    if !body.content_sources.is_empty() {
-        return Err(PackageApiError::unsupported(
-            "specifying content sources is not supported",
-        ));
-    }
-
-    // Verify the package name is unique in a case insensitive way and
-    // the namespace is defined in the operator log and not imported
-    // from another registry.
-    config
-        .core_service
-        .store()
-        .verify_can_publish_package(&LogId::operator_log::<Sha256>(), &body.package_name)
-        .await?;
+    let mut releases = Vec::new();
+    for release in config.core_service.listed_releases(&state).await? {
+        let license = match release.content() {
+            Some(digest) => config
+                .core_service
+                .store()
+                .get_content_metadata(digest)
+                .await?
+                .and_then(|metadata| metadata.license),
+            None => None,
+        };
 
-    // Preemptively perform the policy check on the record before storing it
-    // This is performed here so that we never store an unauthorized record
-    if let Some(policy) = &config.record_policy {
-        policy.check(&body.package_name, &record)?;
+        releases.push(ReleaseMetadata {
+            version: release.version.clone(),
+            yanked: release.yanked(),
+            license,
+            release_notes: config.release_notes(&state, release).await?,
+        });
     }
 
-    // Verify the signature on the record itself before storing it
-    config
+    let advisory = config
         .core_service
         .store()
-        .verify_package_record_signature(&log_id, &record)
-        .await?;
-
-    let record_id = RecordId::package_record::<Sha256>(&record);
-    let mut missing = record.as_ref().contents();
-    missing.retain(|d| !config.content_present(d));
+        .get_package_flag(&log_id)
+        .await?
+        .map(|flag| PackageAdvisory {
+            similar_to: flag.similar_to,
+            reason: flag.reason,
+        });
 
-    config
-        .core_service
-        .store()
-        .store_package_record(&log_id, &body.package_name, &record_id, &record, &missing)
-        .await?;
+    Ok(Json(PackageMetadataResponse { releases, advisory }))
+}
 
-    // If there's no missing content, submit the record for processing now
-    if missing.is_empty() {
-        config
-            .core_service
-            .submit_package_record(log_id, record_id.clone())
-            .await;
+#[debug_handler]
+async fn get_advisories(
+    State(config): State<Config>,
+    Path(log_id): Path<LogId>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<PackageAdvisoriesResponse>, PackageApiError> {
+    let store = config.core_service.store();
+    let Some(name) = store
+        .get_package_names(std::slice::from_ref(&log_id))
+        .await?
+        .swap_remove(&log_id)
+        .flatten()
+    else {
+        return Err(PackageApiError(PackageError::LogNotFound(log_id)));
+    };
 
-        return Ok((
-            StatusCode::ACCEPTED,
-            Json(PackageRecord {
-                record_id,
-                state: PackageRecordState::Processing,
-            }),
-        ));
-    }
+    let advisories = store
+        .list_advisories(Some(&name))
+        .await?
+        .into_iter()
+        .map(|advisory| advisory.advisory)
+        .collect();
 
-    let missing_content = config.build_missing_content(&log_id, &record_id, missing);
-    Ok((
-        StatusCode::ACCEPTED,
-        Json(PackageRecord {
-            record_id,
-            state: PackageRecordState::Sourcing { missing_content },
-        }),
-    ))
+    Ok(Json(PackageAdvisoriesResponse { advisories }))
 }
 
+/// Gets the changes to the permissions of the keys of a package log, with
+/// references to the records that made them.
 #[debug_handler]
-async fn get_record(
+async fn get_key_history(
     State(config): State<Config>,
-    Path((log_id, record_id)): Path<(LogId, RecordId)>,
+    Path(log_id): Path<LogId>,
     RegistryHeader(_registry_header): RegistryHeader,
-) -> Result<Json<PackageRecord>, PackageApiError> {
-    let record = config
-        .core_service
-        .store()
-        .get_package_record(&log_id, &record_id)
-        .await?;
+) -> Result<Json<PackageKeyHistoryResponse>, PackageApiError> {
+    let records = match config.core_service.package_key_records(&log_id).await {
+        Ok(records) => records,
+        Err(CoreServiceError::DataStore(e)) => return Err(e.into()),
+        Err(e) => return Err(PackageApiError::internal_error(e)),
+    };
 
-    match record.status {
-        RecordStatus::MissingContent(missing) => {
-            let missing_content = config.build_missing_content(&log_id, &record_id, &missing);
-            Ok(Json(PackageRecord {
-                record_id,
-                state: PackageRecordState::Sourcing { missing_content },
-            }))
-        }
-        // Validated is considered still processing until included in a checkpoint
-        RecordStatus::Pending | RecordStatus::Validated => Ok(Json(PackageRecord {
-            record_id,
-            state: PackageRecordState::Processing,
-        })),
-        RecordStatus::Rejected(reason) => Ok(Json(PackageRecord {
-            record_id,
-            state: PackageRecordState::Rejected { reason },
-        })),
-        RecordStatus::Published => {
-            let registry_index = record.registry_index.unwrap();
+    let mut events = Vec::new();
+    for record in records {
+        let record_id = RecordId::package_record::<Sha256>(&record.envelope);
+        for entry in &record.envelope.as_ref().entries {
+            let (kind, key_id, key, permissions) = match entry {
+                package::PackageEntry::Init { key, .. } => (
+                    KeyEventKind::Init,
+                    key.fingerprint(),
+                    Some(key.clone()),
+                    package::Permission::all().to_vec(),
+                ),
+                package::PackageEntry::GrantFlat { key, permissions } => (
+                    KeyEventKind::Grant,
+                    key.fingerprint(),
+                    Some(key.clone()),
+                    permissions.clone(),
+                ),
+                package::PackageEntry::RevokeFlat {
+                    key_id,
+                    permissions,
+                } => (
+                    KeyEventKind::Revoke,
+                    key_id.clone(),
+                    None,
+                    permissions.clone(),
+                ),
+                _ => continue,
+            };
 
-            Ok(Json(PackageRecord {
-                record_id,
-                state: PackageRecordState::Published { registry_index },
-            }))
+            events.push(KeyEvent {
+                kind,
+                key_id,
+                key,
+                permissions,
+                signed_by: record.envelope.key_id().clone(),
+                record_id: record_id.clone(),
+                registry_index: record.registry_index,
+            });
         }
     }
+
+    Ok(Json(PackageKeyHistoryResponse { events }))
 }
 
+/// Lists the invites of a package log that may still be redeemed or
+/// granted.
+///
+/// Invites that expired before they were redeemed and invites whose
+/// redeeming key already holds the invite's permissions are omitted.
 #[debug_handler]
-async fn upload_content(
+async fn list_invites(
     State(config): State<Config>,
-    Path((log_id, record_id, digest)): Path<(LogId, RecordId, AnyHash)>,
+    Path(log_id): Path<LogId>,
     RegistryHeader(_registry_header): RegistryHeader,
-    body: Body,
-) -> Result<impl IntoResponse, PackageApiError> {
-    match config
+) -> Result<Json<PackageInvitesResponse>, PackageApiError> {
+    let state = match config.core_service.package_log_state(&log_id).await {
+        Ok(state) => state,
+        Err(CoreServiceError::DataStore(e)) => return Err(e.into()),
+        Err(e) => return Err(PackageApiError::internal_error(e)),
+    };
+
+    let now = SystemTime::now();
+    let invites = config
         .core_service
         .store()
-        .is_content_missing(&log_id, &record_id, &digest)
+        .list_package_invites(&log_id)
+        .await?
+        .into_iter()
+        .filter(|invite| match &invite.redemption {
+            Some(redemption) => {
+                let granted = state.key_permissions(&redemption.as_ref().key.fingerprint());
+                !invite
+                    .invite
+                    .as_ref()
+                    .permissions
+                    .iter()
+                    .all(|p| granted.is_some_and(|granted| granted.contains(p)))
+            }
+            None => !invite.invite.as_ref().is_expired(now),
+        })
+        .map(|invite| PackageInviteInfo {
+            invite: invite.invite,
+            redemption: invite.redemption,
+        })
+        .collect();
+
+    Ok(Json(PackageInvitesResponse { invites }))
+}
+
+/// Creates an invite of a package log.
+///
+/// The invite must be signed by a key of the package log that holds each
+/// of the invite's permissions.
+#[debug_handler]
+async fn create_invite(
+    State(config): State<Config>,
+    Path(log_id): Path<LogId>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    Json(body): Json<CreateInviteRequest>,
+) -> Result<Json<PackageInviteInfo>, PackageApiError> {
+    let invite = body.invite.as_ref();
+    if invite.log_id != log_id {
+        return Err(PackageApiError::bad_request(format!(
+            "the invite is for package log `{invite_log_id}`",
+            invite_log_id = invite.log_id
+        )));
+    }
+
+    if invite.id.is_empty()
+        || invite.id.len() > MAX_INVITE_ID_LEN
+        || !invite
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(PackageApiError::bad_request(format!(
+            "invite identifier `{id}` must be 1 to {MAX_INVITE_ID_LEN} alphanumeric, `-`, or `_` characters",
+            id = invite.id
+        )));
+    }
+
+    if invite.permissions.is_empty() {
+        return Err(PackageApiError::bad_request(
+            "the invite does not grant any permissions",
+        ));
+    }
+
+    if invite.is_expired(SystemTime::now()) {
+        return Err(PackageApiError::bad_request(
+            "the invite has already expired",
+        ));
+    }
+
+    let state = match config.core_service.package_log_state(&log_id).await {
+        Ok(state) => state,
+        Err(CoreServiceError::DataStore(e)) => return Err(e.into()),
+        Err(e) => return Err(PackageApiError::internal_error(e)),
+    };
+
+    let key_id = body.invite.key_id();
+    let Some(key) = state.public_key(key_id) else {
+        return Err(PackageApiError(PackageError::Unauthorized(format!(
+            "key id `{key_id}` is not a key of the package log"
+        ))));
+    };
+
+    PackageInvite::verify(key, &invite.encode(), body.invite.signature()).map_err(|_| {
+        PackageApiError(PackageError::Unauthorized(format!(
+            "the invite signature of key id `{key_id}` is invalid"
+        )))
+    })?;
+
+    let permissions = state.key_permissions(key_id);
+    if let Some(permission) = invite
+        .permissions
+        .iter()
+        .find(|p| !permissions.is_some_and(|permissions| permissions.contains(*p)))
+    {
+        return Err(PackageApiError(PackageError::Unauthorized(format!(
+            "key id `{key_id}` does not have the `{permission}` permission to grant"
+        ))));
+    }
+
+    if !config
+        .core_service
+        .store()
+        .store_package_invite(&body.invite)
+        .await?
+    {
+        return Err(PackageApiError::conflict(format!(
+            "package log `{log_id}` already has invite `{id}`",
+            id = invite.id
+        )));
+    }
+
+    tracing::info!(
+        "created invite `{id}` of package log `{log_id}`",
+        id = invite.id
+    );
+    Ok(Json(PackageInviteInfo {
+        invite: body.invite,
+        redemption: None,
+    }))
+}
+
+/// Redeems an invite of a package log with the key that signed the
+/// redemption.
+///
+/// The permissions of the invite are granted by a record published by a
+/// key of the package log once the redemption is seen.
+#[debug_handler]
+async fn redeem_invite(
+    State(config): State<Config>,
+    Path((log_id, invite_id)): Path<(LogId, String)>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    Json(body): Json<RedeemInviteRequest>,
+) -> Result<Json<PackageInviteInfo>, PackageApiError> {
+    let redemption = body.redemption.as_ref();
+    if redemption.log_id != log_id || redemption.invite_id != invite_id {
+        return Err(PackageApiError::bad_request(format!(
+            "the redemption is for invite `{redemption_id}` of package log `{redemption_log_id}`",
+            redemption_id = redemption.invite_id,
+            redemption_log_id = redemption.log_id
+        )));
+    }
+
+    InviteRedemption::verify_signed(&body.redemption)
+        .map_err(|e| PackageApiError(PackageError::Unauthorized(e.to_string())))?;
+
+    let store = config.core_service.store();
+    let invite = match store.get_package_invite(&log_id, &invite_id).await? {
+        Some(invite) if !invite.invite.as_ref().is_expired(SystemTime::now()) => invite,
+        _ => return Err(PackageApiError(PackageError::InviteNotFound(invite_id))),
+    };
+
+    if !store.redeem_package_invite(&body.redemption).await? {
+        return Err(PackageApiError::conflict(format!(
+            "invite `{invite_id}` has already been redeemed"
+        )));
+    }
+
+    tracing::info!(
+        "key id `{key_id}` redeemed invite `{invite_id}` of package log `{log_id}`",
+        key_id = body.redemption.key_id()
+    );
+    Ok(Json(PackageInviteInfo {
+        invite: invite.invite,
+        redemption: Some(body.redemption),
+    }))
+}
+
+#[debug_handler]
+async fn get_snapshot(
+    State(config): State<Config>,
+    Path(log_id): Path<LogId>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    Query(query): Query<PackageSnapshotQuery>,
+) -> Result<Json<PackageSnapshotResponse>, PackageApiError> {
+    let snapshot = match config
+        .core_service
+        .package_snapshot(&log_id, query.log_length)
         .await
     {
-        Ok(true) => {}
-        Ok(false) => {
-            return Err(PackageApiError::bad_request(
-                "content digest `{digest}` is not required for package record `{record_id}`",
+        Ok(snapshot) => snapshot,
+        Err(CoreServiceError::DataStore(e)) => return Err(e.into()),
+        Err(e) => return Err(PackageApiError::internal_error(e)),
+    };
+
+    // use the record ID as the fetch token, as for fetched logs
+    let fetch_token = snapshot.as_ref().record_id.to_string();
+    Ok(Json(PackageSnapshotResponse {
+        snapshot,
+        fetch_token,
+    }))
+}
+
+#[debug_handler]
+async fn publish_record(
+    State(config): State<Config>,
+    Path(log_id): Path<LogId>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    Json(body): Json<PublishRecordRequest<'static>>,
+) -> Result<impl IntoResponse, PackageApiError> {
+    let expected_log_id = LogId::package_log::<Sha256>(&body.package_name);
+    if expected_log_id != log_id {
+        return Err(PackageApiError::bad_request(format!(
+            "package log identifier `{expected_log_id}` derived from `{name}` does not match provided log identifier `{log_id}`",
+            name = body.package_name
+        )));
+    }
+
+    let record: ProtoEnvelope<package::PackageRecord> = body
+        .record
+        .into_owned()
+        .try_into()
+        .map_err(PackageApiError::bad_request)?;
+
+    let embargo_until = body
+        .embargo_until
+        .map(|until| {
+            UNIX_EPOCH
+                .checked_add(Duration::from_secs(until))
+                .ok_or_else(|| {
+                    PackageApiError::bad_request(format!(
+                        "embargo timestamp `{until}` is out of range"
+                    ))
+                })
+        })
+        .transpose()?;
+
+    // Content sources may only be specified if the registry is configured
+    // to fetch content from them, and only for the record's own content
+    if !body.content_sources.is_empty() {
+        let Some(policy) = &config.content_sources else {
+            return Err(PackageApiError::unsupported(
+                "specifying content sources is not supported",
             ));
+        };
+
+        let contents = record.as_ref().contents();
+        for (digest, sources) in &body.content_sources {
+            if !contents.contains(digest) {
+                return Err(PackageApiError::bad_request(format!(
+                    "content digest `{digest}` is not content of the package record"
+                )));
+            }
+
+            if sources.is_empty() {
+                return Err(PackageApiError::bad_request(format!(
+                    "no content sources were provided for content digest `{digest}`"
+                )));
+            }
+
+            for ContentSource::HttpGet { url, .. } in sources {
+                if !Url::parse(url).is_ok_and(|url| policy.allows(&url)) {
+                    return Err(PackageApiError::forbidden(format!(
+                        "content source `{url}` is not allowed by the registry"
+                    )));
+                }
+            }
         }
-        Err(DataStoreError::RecordNotPending(_)) => {
-            return Err(PackageApiError(PackageError::RecordNotSourcing))
-        }
-        Err(e) => return Err(e.into()),
     }
 
-    let tmp_path = NamedTempFile::new_in(&config.temp_dir)
-        .map_err(PackageApiError::internal_error)?
-        .into_temp_path();
+    // Verify the package name is unique in a case insensitive way and
+    // the namespace is defined in the operator log and not imported
+    // from another registry.
+    config
+        .core_service
+        .store()
+        .verify_can_publish_package(&LogId::operator_log::<Sha256>(), &body.package_name)
+        .await?;
 
-    tracing::debug!(
-        "uploading content for record `{record_id}` from `{log_id}` to `{path}`",
-        path = tmp_path.display()
-    );
+    // Packages of a suspended namespace cannot be published until the
+    // namespace is restored
+    if let Some(suspension) = config
+        .core_service
+        .store()
+        .get_namespace_suspension(body.package_name.namespace())
+        .await?
+    {
+        return Err(PackageApiError(PackageError::Rejection(format!(
+            "namespace `{namespace}` is suspended: {reason}",
+            namespace = suspension.namespace,
+            reason = suspension.reason
+        ))));
+    }
 
-    let res = process_content(
-        &tmp_path,
-        &digest,
-        body.into_data_stream(),
-        config.content_policy.as_deref(),
-    )
-    .await;
+    let record_id = RecordId::package_record::<Sha256>(&record);
 
-    // If the error was a rejection, transition the record itself to rejected
-    if let Err(PackageApiError(PackageError::Rejection(reason))) = &res {
+    // Preemptively perform the policy check on the record before storing it
+    // This is performed here so that we never store an unauthorized record
+    if let Some(policy) = &config.record_policy {
+        let res = policy.check(&body.package_name, &record);
         config
-            .core_service
-            .store()
-            .reject_package_record(
+            .record_policy_decision(
                 &log_id,
                 &record_id,
-                &format!("content with digest `{digest}` was rejected by policy: {reason}"),
+                PolicyKind::Record,
+                None,
+                res.as_ref().err().map(ToString::to_string),
             )
             .await?;
+
+        if config.policy_mode.is_enforcing() {
+            res?;
+        }
+    }
+
+    // Verify the signature on the record itself before storing it
+    config
+        .core_service
+        .store()
+        .verify_package_record_signature(&log_id, &record)
+        .await?;
+
+    // Content already present in storage (e.g. released by another package)
+    // does not need to be uploaded again
+    let mut missing = record.as_ref().contents();
+    missing.retain(|d| !config.content_present(d));
+
+    // As present content is not uploaded, check it against the content policy
+    // now; like record policies, this is performed before storing the record
+    // so that rejected content is never referenced
+    if let Some(policy) = &config.content_policy {
+        let present = record.as_ref().entries.iter().filter_map(|entry| {
+            entry
+                .content()
+                .filter(|content| !missing.contains(content))
+                .zip(entry.content_type())
+        });
+
+        for (digest, content_type) in present {
+            let res = config
+                .check_present_content(policy.as_ref(), &body.package_name, digest, content_type)
+                .await?;
+            config
+                .record_policy_decision(
+                    &log_id,
+                    &record_id,
+                    PolicyKind::Content,
+                    Some(digest),
+                    res.as_ref().err().map(ToString::to_string),
+                )
+                .await?;
+
+            if config.policy_mode.is_enforcing() {
+                res?;
+            }
+        }
     }
 
-    // Only persist the file if the content was successfully processed
-    res?;
+    // Likewise, check the interfaces of released content that is present
+    // against the previous release of the package
+    if config.semver.is_some() {
+        for entry in &record.as_ref().entries {
+            let package::PackageEntry::Release {
+                version,
+                content,
+                content_type,
+            } = entry
+            else {
+                continue;
+            };
+            if !content_type.is_wasm() {
+                continue;
+            }
+            if missing.contains(content) {
+                continue;
+            }
 
-    tmp_path
-        .persist(config.content_path(&digest))
-        .map_err(PackageApiError::internal_error)?;
+            if let Some(reason) = config
+                .check_semver(&log_id, &record_id, &body.package_name, version, content)
+                .await?
+            {
+                return Err(PackageApiError(PackageError::Rejection(reason)));
+            }
+        }
+    }
+
+    config
+        .core_service
+        .store()
+        .store_package_record(&log_id, &body.package_name, &record_id, &record, &missing)
+        .await?;
+
+    // Only content uploaded for the record is embargoed; content already
+    // present in storage may already be served. The embargo is stored after
+    // the record so that a record that fails to store leaves no embargo.
+    if let Some(until) = embargo_until {
+        if until > SystemTime::now() {
+            for digest in &missing {
+                config
+                    .core_service
+                    .store()
+                    .store_content_embargo(digest, until)
+                    .await?;
+            }
+        }
+    }
+
+    // Names of new packages are checked for typosquats after the record is
+    // stored so that a failed check never prevents a publish
+    if let Some(typosquat) = &config.typosquat {
+        let init = record
+            .as_ref()
+            .entries
+            .iter()
+            .any(|entry| matches!(entry, package::PackageEntry::Init { .. }));
+        if init {
+            if let Err(e) = config
+                .flag_similar_name(typosquat, &log_id, &body.package_name)
+                .await
+            {
+                tracing::error!(
+                    "failed to check package `{name}` for typosquats: {e}",
+                    name = body.package_name
+                );
+            }
+        }
+    }
+
+    // A record requiring approval is only submitted once it is approved
+    let requires_approval = config
+        .approvals
+        .as_ref()
+        .is_some_and(|approvals| approvals.requires_approval(&body.package_name));
+    if requires_approval {
+        config
+            .core_service
+            .store()
+            .store_pending_approval(&log_id, &record_id)
+            .await?;
+    }
+
+    // Content with declared sources is fetched from them rather than
+    // uploaded; a record whose content cannot be fetched is rejected
+    let sourced = missing
+        .iter()
+        .copied()
+        .filter(|digest| body.content_sources.contains_key(*digest))
+        .collect::<Vec<_>>();
+    for digest in sourced {
+        if let Err(e) = config
+            .fetch_content(&log_id, &record_id, digest, &body.content_sources[digest])
+            .await
+        {
+            match config
+                .core_service
+                .store()
+                .reject_package_record(
+                    &log_id,
+                    &record_id,
+                    &format!(
+                        "content with digest `{digest}` could not be fetched: {e}",
+                        e = e.0
+                    ),
+                )
+                .await
+            {
+                Ok(()) | Err(DataStoreError::RecordNotPending(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            return Err(e);
+        }
+
+        missing.shift_remove(digest);
+    }
+
+    // A staged record is not submitted until it is committed
+    if body.stage {
+        let expires_at = SystemTime::now() + config.staged_publish_ttl;
+        config
+            .core_service
+            .store()
+            .store_staged_publish(&log_id, &record_id, expires_at)
+            .await?;
+
+        let missing_content = config.build_missing_content(&log_id, &record_id, missing);
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(PackageRecord {
+                record_id,
+                state: staged_state(missing_content, expires_at),
+            }),
+        ));
+    }
+
+    if requires_approval {
+        tracing::info!(
+            "record `{record_id}` of package `{name}` awaits approval",
+            name = body.package_name
+        );
+        let missing_content = config.build_missing_content(&log_id, &record_id, missing);
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(PackageRecord {
+                record_id,
+                state: PackageRecordState::AwaitingApproval { missing_content },
+            }),
+        ));
+    }
+
+    // If there's no missing content, submit the record for processing now
+    if missing.is_empty() {
+        config
+            .core_service
+            .submit_package_record(log_id, record_id.clone())
+            .await;
+
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(PackageRecord {
+                record_id,
+                state: config.processing_state(),
+            }),
+        ));
+    }
+
+    let missing_content = config.build_missing_content(&log_id, &record_id, missing);
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(PackageRecord {
+            record_id,
+            state: PackageRecordState::Sourcing { missing_content },
+        }),
+    ))
+}
+
+#[debug_handler]
+async fn get_record(
+    State(config): State<Config>,
+    Path((log_id, record_id)): Path<(LogId, RecordId)>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<(HeaderMap, Json<PackageRecord>), PackageApiError> {
+    let (headers, record) = config.record_state(log_id, record_id).await?;
+    Ok((headers, Json(record)))
+}
+
+/// Lists the most recently submitted records of a package log with their
+/// states.
+///
+/// Records that are no longer retained by the registry are not listed.
+#[debug_handler]
+async fn list_records(
+    State(config): State<Config>,
+    Path(log_id): Path<LogId>,
+    Query(query): Query<ListRecordsQuery>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<PackageRecordsResponse>, PackageApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_RECORDS_LIMIT);
+    if limit == 0 || limit > MAX_RECORDS_LIMIT {
+        return Err(PackageApiError::bad_request(format!(
+            "invalid records limit value `{limit}`: must be between 1 and {MAX_RECORDS_LIMIT}"
+        )));
+    }
+
+    let record_ids = config
+        .core_service
+        .store()
+        .list_package_records(&log_id, query.status, limit)
+        .await?;
+
+    let mut records = Vec::with_capacity(record_ids.len());
+    for record_id in record_ids {
+        let (_, record) = config.record_state(log_id.clone(), record_id).await?;
+
+        // The state may be more specific than the status filtered by the store
+        // (e.g. validated records are still processing until checkpointed)
+        if query
+            .status
+            .is_some_and(|status| !status.matches(&record.state))
+        {
+            continue;
+        }
+
+        records.push(record);
+    }
+
+    Ok(Json(PackageRecordsResponse { records }))
+}
+
+#[debug_handler]
+async fn await_record(
+    State(config): State<Config>,
+    Path((log_id, record_id)): Path<(LogId, RecordId)>,
+    Query(query): Query<AwaitRecordQuery>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<(HeaderMap, Json<PackageRecord>), PackageApiError> {
+    let timeout = query
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(MAX_AWAIT_TIMEOUT)
+        .min(MAX_AWAIT_TIMEOUT);
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    let mut updates = config.core_service.subscribe_record_updates();
+    loop {
+        // Mark the current update as seen before checking the record so that
+        // an update made while checking is not missed
+        updates.borrow_and_update();
+
+        let (headers, record) = config
+            .record_state(log_id.clone(), record_id.clone())
+            .await?;
+        if !matches!(record.state, PackageRecordState::Processing { .. }) {
+            return Ok((headers, Json(record)));
+        }
+
+        match tokio::time::timeout_at(deadline, updates.changed()).await {
+            Ok(Ok(())) => continue,
+            // The wait timed out or the service is shutting down
+            Ok(Err(_)) | Err(_) => return Ok((headers, Json(record))),
+        }
+    }
+}
+
+#[debug_handler]
+async fn commit_record(
+    State(config): State<Config>,
+    Path((log_id, record_id)): Path<(LogId, RecordId)>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<impl IntoResponse, PackageApiError> {
+    let store = config.core_service.store();
+    if config.staged_expiry(&log_id, &record_id).await?.is_none() {
+        return Err(PackageApiError(PackageError::StagedPublishNotFound(
+            record_id,
+        )));
+    }
+
+    let record = store.get_package_record(&log_id, &record_id).await?;
+    if let RecordStatus::MissingContent(missing) = &record.status {
+        return Err(PackageApiError::bad_request(format!(
+            "staged record `{record_id}` cannot be committed as it is missing {count} content digest(s)",
+            count = missing.len()
+        )));
+    }
+
+    // Only the request that removes the staging submits the record
+    if !store.remove_staged_publish(&log_id, &record_id).await? {
+        return Err(PackageApiError(PackageError::StagedPublishNotFound(
+            record_id,
+        )));
+    }
+
+    // A record requiring approval is submitted when it is approved instead
+    match store.get_record_approval(&log_id, &record_id).await? {
+        Some(ApprovalStatus::Awaiting) => {
+            tracing::info!(
+                "committed staged record `{record_id}` from `{log_id}`, which awaits approval"
+            );
+            return Ok((
+                StatusCode::ACCEPTED,
+                Json(PackageRecord {
+                    record_id,
+                    state: PackageRecordState::AwaitingApproval {
+                        missing_content: IndexMap::new(),
+                    },
+                }),
+            ));
+        }
+        Some(ApprovalStatus::Approved(_)) => {}
+        None => {
+            tracing::info!("committing staged record `{record_id}` from `{log_id}`");
+            config
+                .core_service
+                .submit_package_record(log_id, record_id.clone())
+                .await;
+        }
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(PackageRecord {
+            record_id,
+            state: config.processing_state(),
+        }),
+    ))
+}
+
+#[debug_handler]
+async fn abandon_record(
+    State(config): State<Config>,
+    Path((log_id, record_id)): Path<(LogId, RecordId)>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<PackageRecord>, PackageApiError> {
+    let store = config.core_service.store();
+    if config.staged_expiry(&log_id, &record_id).await?.is_none()
+        || !store.remove_staged_publish(&log_id, &record_id).await?
+    {
+        return Err(PackageApiError(PackageError::StagedPublishNotFound(
+            record_id,
+        )));
+    }
+
+    let reason = "the staged publish was abandoned";
+    tracing::info!("abandoning staged record `{record_id}` from `{log_id}`");
+    store
+        .reject_package_record(&log_id, &record_id, reason)
+        .await?;
+
+    Ok(Json(PackageRecord {
+        record_id,
+        state: PackageRecordState::Rejected {
+            reason: reason.to_string(),
+        },
+    }))
+}
+
+/// Approves a record awaiting approval with the key that signed the
+/// approval, submitting the record for processing.
+///
+/// The approving key must differ from the key that signed the record and
+/// must either be an approver of the package's namespace or a key of the
+/// package log with the `release` permission.
+#[debug_handler]
+async fn approve_record(
+    State(config): State<Config>,
+    Path((log_id, record_id)): Path<(LogId, RecordId)>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    Json(body): Json<ApproveRecordRequest>,
+) -> Result<(StatusCode, Json<PackageRecord>), PackageApiError> {
+    let approval = body.approval.as_ref();
+    if approval.log_id != log_id || approval.record_id != record_id {
+        return Err(PackageApiError::bad_request(format!(
+            "the approval is for record `{approval_record_id}` of package log `{approval_log_id}`",
+            approval_record_id = approval.record_id,
+            approval_log_id = approval.log_id
+        )));
+    }
 
-    // If this is the last content needed, submit the record for processing now
-    if config
+    RecordApproval::verify_signed(&body.approval)
+        .map_err(|e| PackageApiError(PackageError::Unauthorized(e.to_string())))?;
+
+    let store = config.core_service.store();
+    if !config.awaiting_approval(&log_id, &record_id).await? {
+        return Err(PackageApiError(PackageError::PendingApprovalNotFound(
+            record_id,
+        )));
+    }
+
+    let record = store.get_package_record(&log_id, &record_id).await?;
+    let key_id = body.approval.key_id();
+    if key_id == record.envelope.key_id() {
+        return Err(PackageApiError(PackageError::Unauthorized(format!(
+            "key id `{key_id}` signed record `{record_id}` and cannot also approve it"
+        ))));
+    }
+
+    let name = store
+        .get_package_names(std::slice::from_ref(&log_id))
+        .await?
+        .swap_remove(&log_id)
+        .flatten()
+        .ok_or_else(|| PackageApiError(PackageError::LogNotFound(log_id.clone())))?;
+    let is_package_key = match config.core_service.package_log_state(&log_id).await {
+        Ok(state) => state
+            .key_permissions(key_id)
+            .is_some_and(|permissions| permissions.contains(&package::Permission::Release)),
+        Err(CoreServiceError::DataStore(DataStoreError::LogNotFound(_))) => false,
+        Err(CoreServiceError::DataStore(e)) => return Err(e.into()),
+        Err(e) => return Err(PackageApiError::internal_error(e)),
+    };
+    let is_approver = config
+        .approvals
+        .as_ref()
+        .is_some_and(|approvals| approvals.is_approver(&name, key_id));
+    if !is_package_key && !is_approver {
+        return Err(PackageApiError(PackageError::Unauthorized(format!(
+            "key id `{key_id}` is not authorized to approve records of package `{name}`"
+        ))));
+    }
+
+    // Only records that will be submitted by their approval may be approved
+    if config.staged_expiry(&log_id, &record_id).await?.is_some() {
+        return Err(PackageApiError::bad_request(format!(
+            "staged record `{record_id}` must be committed before it can be approved"
+        )));
+    }
+
+    match &record.status {
+        RecordStatus::MissingContent(missing) => {
+            return Err(PackageApiError::bad_request(format!(
+                "record `{record_id}` cannot be approved as it is missing {count} content digest(s)",
+                count = missing.len()
+            )));
+        }
+        RecordStatus::Pending => {}
+        _ => {
+            return Err(PackageApiError(PackageError::PendingApprovalNotFound(
+                record_id,
+            )))
+        }
+    }
+
+    // Only the request that approves the record submits it
+    if !store
+        .approve_package_record(&log_id, &record_id, key_id)
+        .await?
+    {
+        return Err(PackageApiError(PackageError::PendingApprovalNotFound(
+            record_id,
+        )));
+    }
+
+    tracing::info!("key id `{key_id}` approved record `{record_id}` from `{log_id}`");
+    config
+        .core_service
+        .submit_package_record(log_id, record_id.clone())
+        .await;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(PackageRecord {
+            record_id,
+            state: config.processing_state(),
+        }),
+    ))
+}
+
+#[debug_handler]
+async fn upload_content(
+    State(config): State<Config>,
+    Path((log_id, record_id, digest)): Path<(LogId, RecordId, AnyHash)>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<impl IntoResponse, PackageApiError> {
+    let declared_len = headers
+        .get(header::CONTENT_LENGTH)
+        .map(|v| {
+            v.to_str()
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .ok_or_else(|| PackageApiError::bad_request("invalid `Content-Length` header"))
+        })
+        .transpose()?;
+
+    // An expired staged record is rejected here, so it no longer accepts content
+    let staged = config.staged_expiry(&log_id, &record_id).await?;
+
+    // A record requiring approval is approved only once all of its content is
+    // present, so it is submitted when it is approved rather than here
+    let requires_approval = config
         .core_service
         .store()
-        .set_content_present(&log_id, &record_id, &digest)
+        .get_record_approval(&log_id, &record_id)
         .await?
+        .is_some();
+
+    match config
+        .core_service
+        .store()
+        .is_content_missing(&log_id, &record_id, &digest)
+        .await
     {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(PackageApiError::bad_request(
+                "content digest `{digest}` is not required for package record `{record_id}`",
+            ));
+        }
+        Err(DataStoreError::RecordNotPending(_)) => {
+            return Err(PackageApiError(PackageError::RecordNotSourcing))
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    let last = config
+        .receive_content(
+            &log_id,
+            &record_id,
+            &digest,
+            declared_len,
+            body.into_data_stream(),
+        )
+        .await?;
+
+    // If this is the last content needed, submit the record for processing
+    // now, unless it is staged to be committed later or requires approval
+    if last && staged.is_none() && !requires_approval {
         config
             .core_service
             .submit_package_record(log_id, record_id.clone())
@@ -385,18 +1881,115 @@ async fn upload_content(
     Ok(StatusCode::CREATED)
 }
 
-async fn process_content(
+/// Verifies content as it is streamed from an upload.
+///
+/// The digest of the content is computed as each chunk is received and the
+/// length of the content is checked against the declared length of the
+/// upload, so that invalid content is rejected as soon as possible and is
+/// never persisted.
+struct ContentVerifier<'a> {
+    digest: &'a AnyHash,
+    hasher: Hasher,
+    declared_len: Option<u64>,
+    len: u64,
+}
+
+impl<'a> ContentVerifier<'a> {
+    fn new(digest: &'a AnyHash, declared_len: Option<u64>) -> Self {
+        Self {
+            digest,
+            hasher: digest.algorithm().hasher(),
+            declared_len,
+            len: 0,
+        }
+    }
+
+    /// Verifies the next chunk of the content.
+    fn update(&mut self, chunk: &[u8]) -> Result<(), PackageApiError> {
+        self.len += chunk.len() as u64;
+        if let Some(declared) = self.declared_len {
+            if self.len > declared {
+                return Err(PackageApiError::bad_request(format!(
+                    "content with digest `{digest}` exceeds the declared length of {declared} bytes",
+                    digest = self.digest
+                )));
+            }
+        }
+
+        self.hasher.update(chunk);
+        Ok(())
+    }
+
+    /// Verifies the content once all of it has been received.
+    fn finalize(self) -> Result<(), PackageApiError> {
+        if let Some(declared) = self.declared_len {
+            if self.len != declared {
+                return Err(PackageApiError::bad_request(format!(
+                    "content with digest `{digest}` has a length of {len} bytes but a length of {declared} bytes was declared",
+                    digest = self.digest,
+                    len = self.len,
+                )));
+            }
+        }
+
+        let result = self.hasher.finalize();
+        if &result != self.digest {
+            return Err(PackageApiError::bad_request(format!(
+                "content digest `{result}` does not match expected digest `{digest}`",
+                digest = self.digest
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the state of a staged record.
+fn staged_state(
+    missing_content: IndexMap<AnyHash, MissingContent>,
+    expires_at: SystemTime,
+) -> PackageRecordState {
+    PackageRecordState::Staged {
+        missing_content,
+        expires_at: expires_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+    }
+}
+
+/// Writes the content to the given path, verifying it and checking it against
+/// the content policy.
+///
+/// Returns the policy rejection, if any; when the policy mode is enforcing,
+/// rejected content is still verified, but is not written to the path.
+///
+/// Content is verified before a rejection is returned so that content that
+/// does not match its digest never causes a record to be rejected.
+async fn process_content<E: std::fmt::Display>(
     path: &std::path::Path,
     digest: &AnyHash,
-    mut stream: BodyDataStream,
-    policy: Option<&dyn ContentPolicy>,
-) -> Result<(), PackageApiError> {
+    declared_len: Option<u64>,
+    mut stream: impl Stream<Item = Result<Bytes, E>> + Unpin,
+    policy: Option<(&dyn ContentPolicy, &PackageName, ContentType)>,
+    mode: PolicyMode,
+) -> Result<Option<ContentPolicyError>, PackageApiError> {
     let mut tmp_file = tokio::fs::File::create(&path)
         .await
         .map_err(PackageApiError::internal_error)?;
 
-    let mut hasher = digest.algorithm().hasher();
-    let mut policy = policy.map(|p| p.new_stream_policy(digest)).transpose()?;
+    let mut verifier = ContentVerifier::new(digest, declared_len);
+    let mut rejection = None;
+    let mut policy = match policy
+        .map(|(p, name, content_type)| p.new_stream_policy(name, digest, content_type))
+        .transpose()
+    {
+        Ok(policy) => policy,
+        Err(e) => {
+            rejection = Some(e);
+            None
+        }
+    };
 
     while let Some(chunk) = stream
         .next()
@@ -404,27 +1997,61 @@ async fn process_content(
         .transpose()
         .map_err(PackageApiError::internal_error)?
     {
-        if let Some(policy) = policy.as_mut() {
-            policy.check(&chunk)?;
+        verifier.update(&chunk)?;
+
+        if let Some(p) = policy.as_mut() {
+            if let Err(e) = p.check(&chunk) {
+                // Continue processing the content without the policy
+                rejection = Some(e);
+                policy = None;
+            }
+        }
+
+        if rejection.is_some() && mode.is_enforcing() {
+            continue;
         }
 
-        hasher.update(&chunk);
         tmp_file
             .write_all(&chunk)
             .await
             .map_err(PackageApiError::internal_error)?;
     }
 
-    let result = hasher.finalize();
-    if &result != digest {
-        return Err(PackageApiError::bad_request(format!(
-            "content digest `{result}` does not match expected digest `{digest}`",
-        )));
-    }
+    verifier.finalize()?;
 
     if let Some(mut policy) = policy {
-        policy.finalize()?;
+        if let Err(e) = policy.finalize() {
+            rejection = Some(e);
+        }
     }
 
-    Ok(())
+    Ok(rejection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warg_crypto::hash::HashAlgorithm;
+
+    #[test]
+    fn it_verifies_content() {
+        let digest = HashAlgorithm::Sha256.digest(b"content");
+
+        let mut verifier = ContentVerifier::new(&digest, Some(7));
+        assert!(verifier.update(b"con").is_ok());
+        assert!(verifier.update(b"tent").is_ok());
+        assert!(verifier.finalize().is_ok());
+
+        let mut verifier = ContentVerifier::new(&digest, None);
+        assert!(verifier.update(b"other").is_ok());
+        assert!(verifier.finalize().is_err());
+
+        // Content longer than declared is rejected as soon as it is received
+        let mut verifier = ContentVerifier::new(&digest, Some(3));
+        assert!(verifier.update(b"content").is_err());
+
+        let mut verifier = ContentVerifier::new(&digest, Some(8));
+        assert!(verifier.update(b"content").is_ok());
+        assert!(verifier.finalize().is_err());
+    }
 }
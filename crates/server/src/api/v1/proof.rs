@@ -1,10 +1,9 @@
-use super::{Json, RegistryHeader};
+use super::{problem_response, Json, RegistryHeader};
 use crate::services::{CoreService, CoreServiceError};
-use axum::{
-    debug_handler, extract::State, http::StatusCode, response::IntoResponse, routing::post, Router,
-};
+use axum::{debug_handler, extract::State, response::IntoResponse, routing::post, Router};
 use warg_api::v1::proof::{
-    ConsistencyRequest, ConsistencyResponse, InclusionRequest, InclusionResponse, ProofError,
+    ConsistencyRequest, ConsistencyResponse, InclusionRequest, InclusionResponse,
+    MultiProofResponse, ProofError,
 };
 use warg_protocol::registry::{RegistryIndex, RegistryLen};
 
@@ -22,6 +21,7 @@ impl Config {
         Router::new()
             .route("/consistency", post(prove_consistency))
             .route("/inclusion", post(prove_inclusion))
+            .route("/multi", post(prove_multi_inclusion))
             .with_state(self)
     }
 }
@@ -53,7 +53,7 @@ impl From<CoreServiceError> for ProofApiError {
 
 impl IntoResponse for ProofApiError {
     fn into_response(self) -> axum::response::Response {
-        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+        problem_response(self.0.status(), self.0)
     }
 }
 
@@ -94,3 +94,23 @@ async fn prove_inclusion(
         map: map_bundle.encode(),
     }))
 }
+
+#[debug_handler]
+async fn prove_multi_inclusion(
+    State(config): State<Config>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    Json(body): Json<InclusionRequest>,
+) -> Result<Json<MultiProofResponse>, ProofApiError> {
+    let log_length = body.log_length as RegistryLen;
+
+    let log_bundle = config
+        .core
+        .log_inclusion_proofs(log_length, &body.leafs)
+        .await?;
+    let map_proof = config.core.map_multi_proof(log_length, &body.leafs).await?;
+
+    Ok(Json(MultiProofResponse {
+        log: log_bundle.encode(),
+        map: map_proof.encode(),
+    }))
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+use clap::Parser;
+use warg_server::admin::{AdminCommand, AdminOptions};
+
+/// Manage a running Warg registry server through its admin API.
+#[derive(Parser, Debug)]
+struct Args {
+    #[clap(flatten)]
+    options: AdminOptions,
+
+    #[clap(subcommand)]
+    command: AdminCommand,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let client = args.options.into_client()?;
+    args.command.exec(&client).await
+}
@@ -1,13 +1,30 @@
 use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum};
-use secrecy::SecretString;
-use std::{net::SocketAddr, path::PathBuf};
+use secrecy::{ExposeSecret, SecretString};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
 use tokio::signal;
 use tracing_subscriber::filter::LevelFilter;
 use url::Url;
 use warg_crypto::signing::PrivateKey;
 use warg_protocol::operator;
-use warg_server::{args::get_opt_secret, policy::record::AuthorizedKeyPolicy, Config, Server};
+use warg_server::{
+    api::throttle::ThrottleConfig,
+    args::get_opt_secret,
+    listener::ListenersConfig,
+    policy::{
+        approval::ApprovalPolicy,
+        content::{WasmContentPolicy, WasmValidationConfig},
+        record::{
+            AuthorizedKeyPolicy, NameListPolicy, NameLists, NamespaceClaimPolicy,
+            RecordPolicyCollection, TeamPolicy, Teams,
+        },
+        semver::{SemverAction, SemverPolicy},
+        sources::ContentSourcePolicy,
+        PolicyMode,
+    },
+    services::{FederationConfig, RetentionConfig, ScrubConfig, TyposquatConfig},
+    Config, Server,
+};
 
 #[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum DataStoreKind {
@@ -17,6 +34,14 @@ enum DataStoreKind {
     Memory,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum SemverCheck {
+    /// Reject releases that make breaking interface changes without a major version bump.
+    Reject,
+    /// Log releases that make breaking interface changes without a major version bump.
+    Warn,
+}
+
 #[derive(Parser, Debug)]
 struct Args {
     /// Use verbose output
@@ -27,6 +52,17 @@ struct Args {
     #[arg(short, long, env = "WARG_LISTEN", default_value = "0.0.0.0:8090")]
     listen: SocketAddr,
 
+    /// The path of a Unix domain socket to listen on instead of the listen
+    /// address.
+    #[arg(long, env = "WARG_UNIX_SOCKET")]
+    unix_socket: Option<PathBuf>,
+
+    /// The path of a TOML file of listeners (TCP addresses with optional
+    /// TLS settings, or Unix domain sockets) to listen on instead of the
+    /// listen address and Unix domain socket.
+    #[arg(long, env = "WARG_LISTENERS_FILE")]
+    listeners_file: Option<PathBuf>,
+
     /// The content storage directory to use.
     #[arg(long, env = "WARG_CONTENT_DIR")]
     content_dir: PathBuf,
@@ -71,9 +107,201 @@ struct Args {
     #[arg(long, env = "WARG_AUTHORIZED_KEYS_FILE")]
     authorized_keys_file: Option<PathBuf>,
 
+    /// The URL of an Open Policy Agent query to delegate record policy decisions to.
+    ///
+    /// For example, `http://localhost:8181/v1/data/warg/allow`.
+    #[cfg(feature = "opa-policy")]
+    #[arg(long, env = "WARG_OPA_POLICY_URL")]
+    opa_policy_url: Option<Url>,
+
+    /// The path to a WebAssembly component to delegate record policy decisions to.
+    #[cfg(feature = "component-policy")]
+    #[arg(long, env = "WARG_POLICY_COMPONENT")]
+    policy_component: Option<PathBuf>,
+
+    /// The path to a file of reserved package names and blocked name patterns.
+    #[arg(long, env = "WARG_NAME_LISTS_FILE")]
+    name_lists_file: Option<PathBuf>,
+
+    /// The path to a file of the maintainer teams of each namespace's packages.
+    #[arg(long, env = "WARG_TEAMS_FILE")]
+    teams_file: Option<PathBuf>,
+
+    /// The path to a file of the namespaces whose records require approval by a second key.
+    #[arg(long, env = "WARG_APPROVALS_FILE")]
+    approvals_file: Option<PathBuf>,
+
+    /// The path to a file of the validation tiers and resource limits applied to WebAssembly content, per namespace.
+    #[arg(long, env = "WARG_WASM_VALIDATION_FILE")]
+    wasm_validation_file: Option<PathBuf>,
+
+    /// Check the interfaces of new releases against the previous release of their package.
+    #[arg(long, env = "WARG_SEMVER_CHECK")]
+    semver_check: Option<SemverCheck>,
+
+    /// A namespace whose releases are checked for breaking interface changes.
+    ///
+    /// If not specified, releases of every namespace are checked.
+    #[arg(
+        long = "semver-check-namespace",
+        env = "WARG_SEMVER_CHECK_NAMESPACES",
+        value_delimiter = ',',
+        requires = "semver_check"
+    )]
+    semver_check_namespaces: Vec<String>,
+
+    /// A URL prefix under which publishers may declare external sources of content.
+    ///
+    /// Content hosted under an allowed prefix is fetched by the server instead of uploaded.
+    /// If not specified, content sources cannot be declared.
+    #[arg(
+        long = "content-source-prefix",
+        env = "WARG_CONTENT_SOURCE_PREFIXES",
+        value_delimiter = ','
+    )]
+    content_source_prefixes: Vec<Url>,
+
+    /// The directory to export the registry to as a static registry.
+    ///
+    /// The registry is exported through the admin API or, with `static-export-on-checkpoint`, on
+    /// every checkpoint; the directory may then be served by any static host.
+    #[arg(long, env = "WARG_STATIC_EXPORT_DIR")]
+    static_export_dir: Option<PathBuf>,
+
+    /// Export the registry to the static export directory on every checkpoint.
+    ///
+    /// Reads may then be served entirely from the export (e.g. by a CDN), with this server only
+    /// accepting publishes.
+    #[arg(
+        long,
+        env = "WARG_STATIC_EXPORT_ON_CHECKPOINT",
+        requires = "static_export_dir"
+    )]
+    static_export_on_checkpoint: bool,
+
+    /// The token that authenticates requests to the admin API.
+    ///
+    /// If neither this nor `admin-token-file` is specified, the admin API is disabled.
+    #[arg(long, env = "WARG_ADMIN_TOKEN")]
+    admin_token: Option<SecretString>,
+
+    /// The path to the token that authenticates requests to the admin API.
+    #[arg(long, env = "WARG_ADMIN_TOKEN_FILE", conflicts_with = "admin_token")]
+    admin_token_file: Option<PathBuf>,
+
+    /// A namespace and the domain that keys claiming packages in it must prove control of.
+    ///
+    /// Specified as `NAMESPACE=DOMAIN`, for example `example=example.com`.
+    #[arg(
+        long = "namespace-domain",
+        env = "WARG_NAMESPACE_DOMAINS",
+        value_name = "NAMESPACE=DOMAIN",
+        value_delimiter = ',',
+        value_parser = parse_namespace_domain
+    )]
+    namespace_domains: Vec<(String, String)>,
+
+    /// A domain suffix that associates every namespace with a domain keys claiming packages in it must prove control of.
+    ///
+    /// For example, `com` associates the `example` namespace with `example.com`.
+    #[arg(long, env = "WARG_NAMESPACE_DOMAIN_SUFFIX")]
+    namespace_domain_suffix: Option<String>,
+
+    /// The URL of the DNS-over-HTTPS resolver used to verify namespace domains.
+    #[arg(long, env = "WARG_NAMESPACE_DNS_RESOLVER")]
+    namespace_dns_resolver: Option<Url>,
+
+    /// Flag new packages with names similar to existing packages for moderation.
+    #[arg(long, env = "WARG_TYPOSQUAT_DETECTION")]
+    typosquat_detection: bool,
+
+    /// The maximum edit distance between package names for a new package to be flagged.
+    #[arg(
+        long,
+        env = "WARG_TYPOSQUAT_MAX_DISTANCE",
+        requires = "typosquat_detection"
+    )]
+    typosquat_max_distance: Option<usize>,
+
+    /// The maximum number of requests served concurrently; further requests are shed with a `503` response.
+    #[arg(long, env = "WARG_MAX_CONCURRENT_REQUESTS")]
+    max_concurrent_requests: Option<usize>,
+
+    /// The number of requests each client IP address may make per quota window; further requests are rejected with a `429` response.
+    #[arg(long, env = "WARG_REQUEST_QUOTA")]
+    request_quota: Option<u64>,
+
+    /// The length of the request quota window, in seconds.
+    #[arg(
+        long,
+        env = "WARG_REQUEST_QUOTA_WINDOW",
+        default_value = "60",
+        requires = "request_quota"
+    )]
+    request_quota_window: u64,
+
+    /// Evaluate and record policy decisions without enforcing them.
+    #[arg(long, env = "WARG_POLICY_DRY_RUN")]
+    policy_dry_run: bool,
+
     /// The initial namespace defined for this registry.
     #[arg(long, env = "WARG_NAMESPACE")]
     namespace: Option<String>,
+
+    /// An initial namespace imported from another registry.
+    ///
+    /// Specified as `NAMESPACE=REGISTRY`, for example `wasi=registry.example.com`.
+    #[arg(
+        long = "import-namespace",
+        env = "WARG_IMPORT_NAMESPACES",
+        value_name = "NAMESPACE=REGISTRY",
+        value_delimiter = ',',
+        value_parser = parse_namespace_import
+    )]
+    import_namespaces: Vec<(String, String)>,
+
+    /// The path to a file of the upstream registries namespaces may be imported from and their pinned operator keys.
+    ///
+    /// If not specified, namespaces may be imported from any registry.
+    #[arg(long, env = "WARG_FEDERATION_FILE")]
+    federation_file: Option<PathBuf>,
+
+    /// The number of seconds between scrubs of stored content for corruption.
+    ///
+    /// If not specified, stored content is not scrubbed.
+    #[arg(long, env = "WARG_SCRUB_INTERVAL")]
+    scrub_interval: Option<u64>,
+
+    /// A webhook URL to post corrupt content findings to.
+    #[arg(long, env = "WARG_SCRUB_WEBHOOK", requires = "scrub_interval")]
+    scrub_webhook: Option<Url>,
+
+    /// An upstream registry URL to fetch corrupt content from again.
+    #[arg(
+        long = "scrub-upstream",
+        env = "WARG_SCRUB_UPSTREAMS",
+        value_delimiter = ',',
+        requires = "scrub_interval"
+    )]
+    scrub_upstreams: Vec<Url>,
+
+    /// The number of seconds to keep rejected and abandoned pending records before removing them.
+    ///
+    /// If not specified, such records are kept indefinitely.
+    #[arg(long, env = "WARG_RECORD_RETENTION")]
+    record_retention: Option<u64>,
+
+    /// The number of seconds between removals of expired records.
+    #[arg(
+        long,
+        env = "WARG_RECORD_RETENTION_INTERVAL",
+        requires = "record_retention"
+    )]
+    record_retention_interval: Option<u64>,
+
+    /// The number of seconds a staged publish may await a commit before it is rejected.
+    #[arg(long, env = "WARG_STAGED_PUBLISH_TTL")]
+    staged_publish_ttl: Option<u64>,
 }
 
 impl Args {
@@ -99,25 +327,220 @@ async fn main() -> Result<()> {
         get_opt_secret("operator-key", args.operator_key_file, args.operator_key)?;
     let operator_key =
         PrivateKey::decode(operator_key_str).context("failed to parse operator key")?;
-    let namespaces = args
+    let mut namespaces = args
         .namespace
         .as_ref()
         .map(|namespace| vec![(namespace.to_lowercase(), operator::NamespaceState::Defined)]);
+    for (namespace, registry) in &args.import_namespaces {
+        namespaces.get_or_insert_with(Vec::new).push((
+            namespace.to_lowercase(),
+            operator::NamespaceState::Imported {
+                registry: registry.clone(),
+            },
+        ));
+    }
 
     let mut config = Config::new(operator_key, namespaces, args.content_dir)
         .with_addr(args.listen)
         .with_shutdown(shutdown_signal());
 
+    if let Some(path) = args.unix_socket {
+        config = config.with_unix_socket(path);
+    }
+
+    if let Some(path) = args.listeners_file {
+        let data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read listener configuration from {path:?}"))?;
+        let listeners: ListenersConfig = toml::from_str(&data)
+            .with_context(|| format!("failed to decode listener configuration from {path:?}"))?;
+        listeners.validate()?;
+        for listener in listeners.listeners {
+            config = config.with_listener(listener);
+        }
+    }
+
     if let Some(url) = args.content_base_url {
         config = config.with_content_base_url(url);
     }
 
+    let mut record_policies = RecordPolicyCollection::new();
+    let mut has_record_policy = false;
+
     if let Some(path) = args.authorized_keys_file {
         let authorized_keys_data = std::fs::read_to_string(&path)
             .with_context(|| format!("failed to read authorized keys from {path:?}"))?;
         let authorized_key_policy: AuthorizedKeyPolicy = toml::from_str(&authorized_keys_data)
             .with_context(|| format!("failed to decode authorized keys from {path:?}"))?;
-        config = config.with_record_policy(authorized_key_policy);
+        record_policies.push(authorized_key_policy);
+        has_record_policy = true;
+    }
+
+    #[cfg(feature = "opa-policy")]
+    if let Some(url) = args.opa_policy_url {
+        use warg_server::policy::record::OpaRecordPolicy;
+        tracing::info!("using OPA record policy at `{url}`");
+        record_policies.push(OpaRecordPolicy::new(url));
+        has_record_policy = true;
+    }
+
+    #[cfg(feature = "component-policy")]
+    if let Some(path) = args.policy_component {
+        use warg_server::policy::record::ComponentRecordPolicy;
+        tracing::info!(
+            "using record policy component `{path}`",
+            path = path.display()
+        );
+        record_policies.push(ComponentRecordPolicy::from_file(&path)?);
+        has_record_policy = true;
+    }
+
+    if !args.namespace_domains.is_empty() || args.namespace_domain_suffix.is_some() {
+        let mut policy = NamespaceClaimPolicy::new();
+        for (namespace, domain) in args.namespace_domains {
+            tracing::info!("claims of namespace `{namespace}` must prove control of `{domain}`");
+            policy = policy.with_namespace_domain(namespace, domain);
+        }
+        if let Some(suffix) = args.namespace_domain_suffix {
+            policy = policy.with_domain_suffix(suffix);
+        }
+        if let Some(url) = args.namespace_dns_resolver {
+            policy = policy.with_dns_resolver(url);
+        }
+        record_policies.push(policy);
+        has_record_policy = true;
+    }
+
+    if has_record_policy {
+        config = config.with_record_policy(record_policies);
+    }
+
+    if let Some(path) = args.name_lists_file {
+        let data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read name lists from {path:?}"))?;
+        let lists: NameLists = toml::from_str(&data)
+            .with_context(|| format!("failed to decode name lists from {path:?}"))?;
+        config = config.with_name_lists(NameListPolicy::new(lists)?);
+    }
+
+    if let Some(path) = args.teams_file {
+        let data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read teams from {path:?}"))?;
+        let teams: Teams = toml::from_str(&data)
+            .with_context(|| format!("failed to decode teams from {path:?}"))?;
+        config = config.with_teams(TeamPolicy::new(teams)?);
+    }
+
+    if let Some(path) = args.approvals_file {
+        let data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read approvals from {path:?}"))?;
+        let approvals: ApprovalPolicy = toml::from_str(&data)
+            .with_context(|| format!("failed to decode approvals from {path:?}"))?;
+        approvals.validate()?;
+        config = config.with_approvals(approvals);
+    }
+
+    if let Some(path) = args.wasm_validation_file {
+        let data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read WebAssembly validation from {path:?}"))?;
+        let validation: WasmValidationConfig = toml::from_str(&data)
+            .with_context(|| format!("failed to decode WebAssembly validation from {path:?}"))?;
+        validation.validate()?;
+        config = config.with_content_policy(WasmContentPolicy::new().with_validation(validation));
+    }
+
+    if let Some(check) = args.semver_check {
+        let mut policy = SemverPolicy::new(match check {
+            SemverCheck::Reject => SemverAction::Reject,
+            SemverCheck::Warn => SemverAction::Warn,
+        });
+        for namespace in args.semver_check_namespaces {
+            policy = policy.with_namespace(namespace);
+        }
+        policy.validate()?;
+        config = config.with_semver_policy(policy);
+    }
+
+    if !args.content_source_prefixes.is_empty() {
+        let mut policy = ContentSourcePolicy::new();
+        for prefix in args.content_source_prefixes {
+            policy = policy.with_allowed_prefix(prefix);
+        }
+        policy.validate()?;
+        config = config.with_content_sources(policy);
+    }
+
+    if let Some(dir) = args.static_export_dir {
+        config = config.with_static_export_dir(dir);
+    }
+
+    if args.static_export_on_checkpoint {
+        config = config.with_static_export_on_checkpoint();
+    }
+
+    if let Some(path) = args.federation_file {
+        let data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read federation configuration from {path:?}"))?;
+        let federation: FederationConfig = toml::from_str(&data)
+            .with_context(|| format!("failed to decode federation configuration from {path:?}"))?;
+        for upstream in &federation.upstreams {
+            tracing::info!(
+                "namespaces may be imported from upstream registry `{registry}`",
+                registry = upstream.registry
+            );
+        }
+        config = config.with_federation(federation);
+    }
+
+    if args.admin_token.is_some() || args.admin_token_file.is_some() {
+        let token = get_opt_secret("admin-token", args.admin_token_file, args.admin_token)?;
+        config = config.with_admin_token(token.expose_secret().trim().to_string().into());
+    }
+
+    if args.max_concurrent_requests.is_some() || args.request_quota.is_some() {
+        let mut throttle = ThrottleConfig::new();
+        if let Some(max) = args.max_concurrent_requests {
+            throttle = throttle.with_max_concurrent_requests(max);
+        }
+        if let Some(limit) = args.request_quota {
+            throttle = throttle.with_quota(limit, Duration::from_secs(args.request_quota_window));
+        }
+        config = config.with_throttling(throttle);
+    }
+
+    if args.typosquat_detection {
+        let mut typosquat = TyposquatConfig::new();
+        if let Some(distance) = args.typosquat_max_distance {
+            typosquat = typosquat.with_max_distance(distance);
+        }
+        config = config.with_typosquat_detection(typosquat);
+    }
+
+    if let Some(interval) = args.scrub_interval {
+        let mut scrub = ScrubConfig::new(Duration::from_secs(interval));
+        if let Some(url) = args.scrub_webhook {
+            scrub = scrub.with_webhook(url);
+        }
+        for url in args.scrub_upstreams {
+            scrub = scrub.with_upstream(url);
+        }
+        config = config.with_content_scrubbing(scrub);
+    }
+
+    if let Some(retention) = args.record_retention {
+        let mut retention = RetentionConfig::new(Duration::from_secs(retention));
+        if let Some(interval) = args.record_retention_interval {
+            retention = retention.with_interval(Duration::from_secs(interval));
+        }
+        config = config.with_record_retention(retention);
+    }
+
+    if let Some(ttl) = args.staged_publish_ttl {
+        config = config.with_staged_publish_ttl(Duration::from_secs(ttl));
+    }
+
+    if args.policy_dry_run {
+        tracing::warn!("policies are running in dry-run mode and will not be enforced");
+        config = config.with_policy_mode(PolicyMode::DryRun);
     }
 
     let config = match args.data_store {
@@ -170,3 +593,21 @@ async fn shutdown_signal() {
         _ = terminate => {},
     }
 }
+
+fn parse_namespace_domain(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((namespace, domain)) if !namespace.is_empty() && !domain.is_empty() => {
+            Ok((namespace.to_string(), domain.to_string()))
+        }
+        _ => Err(format!("expected `NAMESPACE=DOMAIN` but found `{s}`")),
+    }
+}
+
+fn parse_namespace_import(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((namespace, registry)) if !namespace.is_empty() && !registry.is_empty() => {
+            Ok((namespace.to_string(), registry.to_string()))
+        }
+        _ => Err(format!("expected `NAMESPACE=REGISTRY` but found `{s}`")),
+    }
+}
@@ -1,16 +1,21 @@
-use super::{DataStore, DataStoreError};
+use super::{
+    Advisory, ApprovalStatus, ContentReference, ContentReferenceChanges, DataStore, DataStoreError,
+    ExpiredRecords, Invite, NamespaceSuspension, PackageFlag, PolicyDecision,
+};
 use futures::Stream;
 use indexmap::{IndexMap, IndexSet};
-use std::{pin::Pin, sync::Arc};
+use std::{pin::Pin, sync::Arc, time::SystemTime};
 use tokio::sync::RwLock;
-use warg_crypto::{hash::AnyHash, Encode, Signable};
+use warg_api::v1::package::RecordStatusFilter;
+use warg_crypto::{hash::AnyHash, signing::KeyID, Encode, Signable};
 use warg_protocol::{
+    metadata::ContentMetadata,
     operator,
-    package::{self, PackageEntry},
+    package::{self, InviteRedemption, PackageEntry, PackageInvite},
     registry::{
         LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
     },
-    ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
+    ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope, Version,
 };
 
 struct Entry<R> {
@@ -77,6 +82,18 @@ struct State {
     checkpoints: IndexMap<RegistryLen, SerdeEnvelope<TimestampedCheckpoint>>,
     records: IndexMap<LogId, IndexMap<RecordId, RecordStatus>>,
     log_leafs: IndexMap<RegistryIndex, LogLeaf>,
+    policy_decisions: Vec<PolicyDecision>,
+    content_metadata: IndexMap<AnyHash, ContentMetadata>,
+    content_references: IndexMap<AnyHash, Vec<(ContentReference, Version)>>,
+    staged_publishes: IndexMap<(LogId, RecordId), SystemTime>,
+    record_approvals: IndexMap<(LogId, RecordId), ApprovalStatus>,
+    content_embargoes: IndexMap<AnyHash, SystemTime>,
+    /// The time pending and rejected records were last updated.
+    record_updates: IndexMap<(LogId, RecordId), SystemTime>,
+    package_flags: IndexMap<LogId, PackageFlag>,
+    advisories: IndexMap<(PackageName, String), Advisory>,
+    namespace_suspensions: IndexMap<String, NamespaceSuspension>,
+    invites: IndexMap<(LogId, String), Invite>,
 }
 
 /// Represents an in-memory data store.
@@ -85,6 +102,9 @@ struct State {
 ///
 /// Note: this is mainly used for testing, so it is not very efficient as
 /// it shares a single RwLock for all operations.
+///
+/// Clones of the data store share the same underlying state.
+#[derive(Clone)]
 pub struct MemoryDataStore(Arc<RwLock<State>>);
 
 impl MemoryDataStore {
@@ -176,6 +196,269 @@ impl DataStore for MemoryDataStore {
             .collect::<Result<IndexMap<LogId, Option<PackageName>>, _>>()
     }
 
+    async fn get_namespaces(
+        &self,
+        operator_log_id: &LogId,
+    ) -> Result<Vec<(String, operator::NamespaceState)>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .operators
+            .get(operator_log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?
+            .state
+            .namespaces()
+            .map(|(namespace, state)| (namespace.to_string(), state.clone()))
+            .collect())
+    }
+
+    async fn list_package_names(
+        &self,
+        after: Option<&PackageName>,
+        limit: u16,
+    ) -> Result<Vec<PackageName>, DataStoreError> {
+        let state = self.0.read().await;
+
+        // A log is created by the first commit, even if its record is then
+        // rejected; only logs with a validated record are listed
+        let mut names = state
+            .packages
+            .iter()
+            .filter(|(_, log)| !log.entries.is_empty())
+            .filter_map(|(log_id, _)| state.package_names.get(log_id).cloned().flatten())
+            .filter(|name| after.map(|after| name > after).unwrap_or(true))
+            .collect::<Vec<_>>();
+        names.sort();
+        names.truncate(limit as usize);
+
+        Ok(names)
+    }
+
+    async fn store_policy_decision(&self, decision: &PolicyDecision) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        state.policy_decisions.push(decision.clone());
+        Ok(())
+    }
+
+    async fn get_policy_decisions(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Vec<PolicyDecision>, DataStoreError> {
+        let state = self.0.read().await;
+
+        Ok(state
+            .policy_decisions
+            .iter()
+            .filter(|d| &d.log_id == log_id && &d.record_id == record_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn store_content_metadata(
+        &self,
+        digest: &AnyHash,
+        metadata: &ContentMetadata,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        state
+            .content_metadata
+            .insert(digest.clone(), metadata.clone());
+        Ok(())
+    }
+
+    async fn get_content_metadata(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Option<ContentMetadata>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.content_metadata.get(digest).cloned())
+    }
+
+    async fn store_content_embargo(
+        &self,
+        digest: &AnyHash,
+        until: SystemTime,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        state.content_embargoes.insert(digest.clone(), until);
+        Ok(())
+    }
+
+    async fn get_content_embargo(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Option<SystemTime>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.content_embargoes.get(digest).copied())
+    }
+
+    async fn get_content_references(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Vec<ContentReference>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .content_references
+            .get(digest)
+            .map(|references| references.iter().map(|(r, _)| r.clone()).collect())
+            .unwrap_or_default())
+    }
+
+    async fn store_package_flag(&self, flag: &PackageFlag) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        state.package_flags.shift_remove(&flag.log_id);
+        state
+            .package_flags
+            .insert(flag.log_id.clone(), flag.clone());
+        Ok(())
+    }
+
+    async fn get_package_flag(
+        &self,
+        log_id: &LogId,
+    ) -> Result<Option<PackageFlag>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.package_flags.get(log_id).cloned())
+    }
+
+    async fn list_package_flags(&self) -> Result<Vec<PackageFlag>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.package_flags.values().cloned().collect())
+    }
+
+    async fn remove_package_flag(&self, log_id: &LogId) -> Result<bool, DataStoreError> {
+        let mut state = self.0.write().await;
+        Ok(state.package_flags.shift_remove(log_id).is_some())
+    }
+
+    async fn store_advisory(&self, advisory: &Advisory) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        let key = (advisory.package.clone(), advisory.advisory.id.clone());
+        state.advisories.shift_remove(&key);
+        state.advisories.insert(key, advisory.clone());
+        Ok(())
+    }
+
+    async fn list_advisories(
+        &self,
+        package: Option<&PackageName>,
+    ) -> Result<Vec<Advisory>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .advisories
+            .values()
+            .filter(|a| package.map_or(true, |p| &a.package == p))
+            .cloned()
+            .collect())
+    }
+
+    async fn remove_advisory(
+        &self,
+        package: &PackageName,
+        id: &str,
+    ) -> Result<bool, DataStoreError> {
+        let mut state = self.0.write().await;
+        Ok(state
+            .advisories
+            .shift_remove(&(package.clone(), id.to_string()))
+            .is_some())
+    }
+
+    async fn store_namespace_suspension(
+        &self,
+        suspension: &NamespaceSuspension,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        state
+            .namespace_suspensions
+            .shift_remove(&suspension.namespace);
+        state
+            .namespace_suspensions
+            .insert(suspension.namespace.clone(), suspension.clone());
+        Ok(())
+    }
+
+    async fn get_namespace_suspension(
+        &self,
+        namespace: &str,
+    ) -> Result<Option<NamespaceSuspension>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.namespace_suspensions.get(namespace).cloned())
+    }
+
+    async fn list_namespace_suspensions(&self) -> Result<Vec<NamespaceSuspension>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.namespace_suspensions.values().cloned().collect())
+    }
+
+    async fn remove_namespace_suspension(&self, namespace: &str) -> Result<bool, DataStoreError> {
+        let mut state = self.0.write().await;
+        Ok(state
+            .namespace_suspensions
+            .shift_remove(namespace)
+            .is_some())
+    }
+
+    async fn store_package_invite(
+        &self,
+        invite: &SerdeEnvelope<PackageInvite>,
+    ) -> Result<bool, DataStoreError> {
+        let mut state = self.0.write().await;
+        let key = (invite.as_ref().log_id.clone(), invite.as_ref().id.clone());
+        if state.invites.contains_key(&key) {
+            return Ok(false);
+        }
+
+        state.invites.insert(
+            key,
+            Invite {
+                invite: invite.clone(),
+                redemption: None,
+            },
+        );
+        Ok(true)
+    }
+
+    async fn get_package_invite(
+        &self,
+        log_id: &LogId,
+        invite_id: &str,
+    ) -> Result<Option<Invite>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .invites
+            .get(&(log_id.clone(), invite_id.to_string()))
+            .cloned())
+    }
+
+    async fn list_package_invites(&self, log_id: &LogId) -> Result<Vec<Invite>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .invites
+            .iter()
+            .filter(|((id, _), _)| id == log_id)
+            .map(|(_, invite)| invite.clone())
+            .collect())
+    }
+
+    async fn redeem_package_invite(
+        &self,
+        redemption: &SerdeEnvelope<InviteRedemption>,
+    ) -> Result<bool, DataStoreError> {
+        let mut state = self.0.write().await;
+        let key = (
+            redemption.as_ref().log_id.clone(),
+            redemption.as_ref().invite_id.clone(),
+        );
+        match state.invites.get_mut(&key) {
+            Some(invite) if invite.redemption.is_none() => {
+                invite.redemption = Some(redemption.clone());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
     async fn store_operator_record(
         &self,
         log_id: &LogId,
@@ -190,6 +473,10 @@ impl DataStore for MemoryDataStore {
             }),
         );
 
+        state
+            .record_updates
+            .insert((log_id.clone(), record_id.clone()), SystemTime::now());
+
         assert!(prev.is_none());
         Ok(())
     }
@@ -218,6 +505,9 @@ impl DataStore for MemoryDataStore {
             record,
             reason: reason.to_string(),
         });
+        state
+            .record_updates
+            .insert((log_id.clone(), record_id.clone()), SystemTime::now());
 
         Ok(())
     }
@@ -234,6 +524,7 @@ impl DataStore for MemoryDataStore {
             operators,
             records,
             log_leafs,
+            record_updates,
             ..
         } = &mut *state;
 
@@ -271,6 +562,7 @@ impl DataStore for MemoryDataStore {
                                 record_id: record_id.clone(),
                             },
                         );
+                        record_updates.swap_remove(&(log_id.clone(), record_id.clone()));
                         Ok(())
                     }
                     Err(e) => {
@@ -278,6 +570,8 @@ impl DataStore for MemoryDataStore {
                             record,
                             reason: e.to_string(),
                         });
+                        record_updates
+                            .insert((log_id.clone(), record_id.clone()), SystemTime::now());
                         Err(e)
                     }
                 }
@@ -313,6 +607,10 @@ impl DataStore for MemoryDataStore {
             .package_names
             .insert(log_id.clone(), Some(package_name.clone()));
 
+        state
+            .record_updates
+            .insert((log_id.clone(), record_id.clone()), SystemTime::now());
+
         assert!(prev.is_none());
         Ok(())
     }
@@ -341,6 +639,9 @@ impl DataStore for MemoryDataStore {
             record,
             reason: reason.to_string(),
         });
+        state
+            .record_updates
+            .insert((log_id.clone(), record_id.clone()), SystemTime::now());
 
         Ok(())
     }
@@ -357,6 +658,8 @@ impl DataStore for MemoryDataStore {
             packages,
             records,
             log_leafs,
+            content_references,
+            record_updates,
             ..
         } = &mut *state;
 
@@ -378,6 +681,26 @@ impl DataStore for MemoryDataStore {
                 {
                     Ok(state) => {
                         log.state = state;
+
+                        let changes = ContentReferenceChanges::new(record.as_ref());
+                        for (digest, version) in changes.added {
+                            content_references.entry(digest).or_default().push((
+                                ContentReference {
+                                    log_id: log_id.clone(),
+                                    record_id: record_id.clone(),
+                                },
+                                version,
+                            ));
+                        }
+                        for yanked in &changes.yanked {
+                            content_references.retain(|_, references| {
+                                references.retain(|(r, version)| {
+                                    &r.log_id != log_id || version != yanked
+                                });
+                                !references.is_empty()
+                            });
+                        }
+
                         let index = log.entries.len();
                         log.entries.push(Entry {
                             registry_index,
@@ -394,6 +717,7 @@ impl DataStore for MemoryDataStore {
                                 record_id: record_id.clone(),
                             },
                         );
+                        record_updates.swap_remove(&(log_id.clone(), record_id.clone()));
                         Ok(())
                     }
                     Err(e) => {
@@ -401,6 +725,8 @@ impl DataStore for MemoryDataStore {
                             record,
                             reason: e.to_string(),
                         });
+                        record_updates
+                            .insert((log_id.clone(), record_id.clone()), SystemTime::now());
                         Err(e)
                     }
                 }
@@ -409,6 +735,196 @@ impl DataStore for MemoryDataStore {
         }
     }
 
+    async fn store_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        expires_at: SystemTime,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+
+        match state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?
+        {
+            RecordStatus::Pending(PendingRecord::Package { .. }) => {}
+            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+
+        state
+            .staged_publishes
+            .insert((log_id.clone(), record_id.clone()), expires_at);
+        Ok(())
+    }
+
+    async fn get_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<SystemTime>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .staged_publishes
+            .get(&(log_id.clone(), record_id.clone()))
+            .copied())
+    }
+
+    async fn remove_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<bool, DataStoreError> {
+        let mut state = self.0.write().await;
+        Ok(state
+            .staged_publishes
+            .swap_remove(&(log_id.clone(), record_id.clone()))
+            .is_some())
+    }
+
+    async fn expire_staged_publishes(&self, before: SystemTime) -> Result<usize, DataStoreError> {
+        let mut state = self.0.write().await;
+        let State {
+            records,
+            staged_publishes,
+            record_updates,
+            ..
+        } = &mut *state;
+
+        let mut expired = 0;
+        staged_publishes.retain(|(log_id, record_id), expires_at| {
+            if *expires_at >= before {
+                return true;
+            }
+
+            let Some(status) = records
+                .get_mut(log_id)
+                .and_then(|log| log.get_mut(record_id))
+            else {
+                return false;
+            };
+
+            if let RecordStatus::Pending(PendingRecord::Package { record, .. }) = status {
+                *status = RecordStatus::Rejected(RejectedRecord::Package {
+                    record: record.take().unwrap(),
+                    reason: "the staged publish expired".to_string(),
+                });
+                record_updates.insert((log_id.clone(), record_id.clone()), SystemTime::now());
+                expired += 1;
+            }
+
+            false
+        });
+
+        Ok(expired)
+    }
+
+    async fn store_pending_approval(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+
+        match state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .get(record_id)
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?
+        {
+            RecordStatus::Pending(PendingRecord::Package { .. }) => {}
+            _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
+        }
+
+        state.record_approvals.insert(
+            (log_id.clone(), record_id.clone()),
+            ApprovalStatus::Awaiting,
+        );
+        Ok(())
+    }
+
+    async fn get_record_approval(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<ApprovalStatus>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .record_approvals
+            .get(&(log_id.clone(), record_id.clone()))
+            .cloned())
+    }
+
+    async fn approve_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        approver: &KeyID,
+    ) -> Result<bool, DataStoreError> {
+        let mut state = self.0.write().await;
+        match state
+            .record_approvals
+            .get_mut(&(log_id.clone(), record_id.clone()))
+        {
+            Some(status @ ApprovalStatus::Awaiting) => {
+                *status = ApprovalStatus::Approved(approver.clone());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn expire_records(&self, before: SystemTime) -> Result<ExpiredRecords, DataStoreError> {
+        let now = SystemTime::now();
+        let mut state = self.0.write().await;
+        let State {
+            records,
+            staged_publishes,
+            record_approvals,
+            record_updates,
+            ..
+        } = &mut *state;
+
+        let mut expired = ExpiredRecords::default();
+        record_updates.retain(|(log_id, record_id), updated| {
+            if *updated >= before {
+                return true;
+            }
+
+            let Some(log) = records.get_mut(log_id) else {
+                return false;
+            };
+
+            let key = (log_id.clone(), record_id.clone());
+            match log.get(record_id) {
+                Some(RecordStatus::Rejected(_)) => expired.rejected += 1,
+                // Records awaiting approval or a commit are kept until they
+                // are approved or rejected, or their staging expires
+                Some(RecordStatus::Pending(_))
+                    if staged_publishes
+                        .get(&key)
+                        .is_some_and(|expires_at| *expires_at > now)
+                        || record_approvals.contains_key(&key) =>
+                {
+                    return true
+                }
+                Some(RecordStatus::Pending(_)) => expired.pending += 1,
+                Some(RecordStatus::Validated(_)) => return false,
+                None => return false,
+            }
+
+            log.shift_remove(record_id);
+            staged_publishes.swap_remove(&key);
+            record_approvals.swap_remove(&key);
+            false
+        });
+
+        Ok(expired)
+    }
+
     async fn is_content_missing(
         &self,
         log_id: &LogId,
@@ -524,9 +1040,9 @@ impl DataStore for MemoryDataStore {
         };
 
         let start_log_idx = match since {
-            Some(since) => match &state.records[log_id][since] {
-                RecordStatus::Validated(record) => record.index + 1,
-                _ => unreachable!(),
+            Some(since) => match state.records.get(log_id).and_then(|r| r.get(since)) {
+                Some(RecordStatus::Validated(record)) => record.index + 1,
+                _ => return Err(DataStoreError::RecordNotFound(since.clone())),
             },
             None => 0,
         };
@@ -563,9 +1079,9 @@ impl DataStore for MemoryDataStore {
         };
 
         let start_log_idx = match since {
-            Some(since) => match &state.records[log_id][since] {
-                RecordStatus::Validated(record) => record.index + 1,
-                _ => unreachable!(),
+            Some(since) => match state.records.get(log_id).and_then(|r| r.get(since)) {
+                Some(RecordStatus::Validated(record)) => record.index + 1,
+                _ => return Err(DataStoreError::RecordNotFound(since.clone())),
             },
             None => 0,
         };
@@ -637,6 +1153,47 @@ impl DataStore for MemoryDataStore {
         })
     }
 
+    async fn list_package_records(
+        &self,
+        log_id: &LogId,
+        filter: Option<RecordStatusFilter>,
+        limit: u16,
+    ) -> Result<Vec<RecordId>, DataStoreError> {
+        let state = self.0.read().await;
+        let records = state
+            .records
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let mut record_ids = records
+            .iter()
+            .rev()
+            .filter(|(_, status)| {
+                matches!(
+                    (filter, status),
+                    (None, _)
+                        | (
+                            Some(RecordStatusFilter::Pending),
+                            RecordStatus::Pending(PendingRecord::Package { .. }),
+                        )
+                        | (
+                            Some(RecordStatusFilter::Rejected),
+                            RecordStatus::Rejected(RejectedRecord::Package { .. }),
+                        )
+                        | (
+                            Some(RecordStatusFilter::Published),
+                            RecordStatus::Validated(_)
+                        )
+                )
+            })
+            .take(limit as usize)
+            .map(|(record_id, _)| record_id.clone())
+            .collect::<Vec<_>>();
+
+        record_ids.reverse();
+        Ok(record_ids)
+    }
+
     async fn get_package_record(
         &self,
         log_id: &LogId,
@@ -1,17 +1,20 @@
 use futures::Stream;
 use indexmap::{IndexMap, IndexSet};
-use std::pin::Pin;
+use std::{pin::Pin, time::SystemTime};
 use thiserror::Error;
+use warg_api::v1::package::{RecordStatusFilter, VulnerabilityAdvisory};
 use warg_crypto::{
     hash::AnyHash,
     signing::{KeyID, Signature},
 };
 use warg_protocol::{
-    operator, package,
+    metadata::ContentMetadata,
+    operator,
+    package::{self, InviteRedemption, PackageInvite},
     registry::{
         LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
     },
-    ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
+    ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope, Version,
 };
 
 mod memory;
@@ -113,6 +116,162 @@ where
     pub registry_index: Option<RegistryIndex>,
 }
 
+/// Represents the kind of policy that made a decision.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PolicyKind {
+    /// The decision was made by a content policy.
+    Content,
+    /// The decision was made by a record policy.
+    Record,
+}
+
+impl PolicyKind {
+    /// Gets the name of the policy kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Content => "content",
+            Self::Record => "record",
+        }
+    }
+}
+
+/// Represents a decision made by a policy for a package record.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PolicyDecision {
+    /// The log of the package record the decision was made for.
+    pub log_id: LogId,
+    /// The package record the decision was made for.
+    pub record_id: RecordId,
+    /// The kind of policy that made the decision.
+    pub kind: PolicyKind,
+    /// The digest of the content checked by a content policy.
+    ///
+    /// This is `None` for record policy decisions.
+    pub digest: Option<AnyHash>,
+    /// Whether or not the decision was enforced.
+    ///
+    /// Decisions made in dry-run mode are not enforced.
+    pub enforced: bool,
+    /// The reason the policy rejected the record or content.
+    ///
+    /// This is `None` if the policy allowed the record or content.
+    pub rejection: Option<String>,
+}
+
+/// Represents a reference to content by a validated package record.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ContentReference {
+    /// The log of the package record referencing the content.
+    pub log_id: LogId,
+    /// The package record referencing the content.
+    pub record_id: RecordId,
+}
+
+/// Represents a package flagged for moderation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PackageFlag {
+    /// The log of the flagged package.
+    pub log_id: LogId,
+    /// The name of the flagged package.
+    pub name: PackageName,
+    /// The existing package the flagged package's name is similar to.
+    pub similar_to: PackageName,
+    /// The reason the package was flagged.
+    pub reason: String,
+    /// The time the package was flagged.
+    pub flagged_at: SystemTime,
+}
+
+/// Represents a suspended namespace.
+///
+/// Packages of a suspended namespace cannot be published and are hidden
+/// from listings, but their logs are preserved.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NamespaceSuspension {
+    /// The suspended namespace.
+    pub namespace: String,
+    /// The reason the namespace was suspended.
+    pub reason: String,
+    /// The time the namespace was suspended.
+    pub suspended_at: SystemTime,
+}
+
+/// Represents a vulnerability advisory published for a package.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Advisory {
+    /// The package the advisory was published for.
+    pub package: PackageName,
+    /// The advisory.
+    pub advisory: VulnerabilityAdvisory,
+    /// The time the advisory was published.
+    pub published_at: SystemTime,
+}
+
+/// Represents an invite of a package log.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Invite {
+    /// The signed invite.
+    pub invite: SerdeEnvelope<PackageInvite>,
+    /// The redemption of the invite, if it was redeemed.
+    pub redemption: Option<SerdeEnvelope<InviteRedemption>>,
+}
+
+/// Represents the approval of a package record requiring approval.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ApprovalStatus {
+    /// The record awaits approval.
+    Awaiting,
+    /// The record was approved by the key with the given identifier.
+    Approved(KeyID),
+}
+
+/// Represents the number of records removed by [`DataStore::expire_records`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct ExpiredRecords {
+    /// The number of rejected records removed.
+    pub rejected: usize,
+    /// The number of pending records removed.
+    pub pending: usize,
+}
+
+impl ExpiredRecords {
+    /// Gets the total number of records removed.
+    pub fn total(&self) -> usize {
+        self.rejected + self.pending
+    }
+}
+
+/// Represents the changes to content references made by a validated package
+/// record.
+#[derive(Default)]
+struct ContentReferenceChanges {
+    /// The content released or attested by the record for each version.
+    added: Vec<(AnyHash, Version)>,
+    /// The versions yanked by the record; references made for these
+    /// versions are removed.
+    yanked: Vec<Version>,
+}
+
+impl ContentReferenceChanges {
+    fn new(record: &package::PackageRecord) -> Self {
+        let mut changes = Self::default();
+        for entry in &record.entries {
+            match entry {
+                package::PackageEntry::Release {
+                    version, content, ..
+                }
+                | package::PackageEntry::Attest {
+                    version, content, ..
+                } => changes.added.push((content.clone(), version.clone())),
+                package::PackageEntry::Yank { version } => changes.yanked.push(version.clone()),
+                _ => {}
+            }
+        }
+
+        changes
+    }
+}
+
 /// Implemented by data stores.
 #[axum::async_trait]
 pub trait DataStore: Send + Sync {
@@ -204,6 +363,82 @@ pub trait DataStore: Send + Sync {
         registry_index: RegistryIndex,
     ) -> Result<(), DataStoreError>;
 
+    /// Stages the given package record until it is committed or abandoned.
+    ///
+    /// The record must be in a pending state; a staged record is not
+    /// submitted for processing once its content is present.
+    async fn store_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        expires_at: SystemTime,
+    ) -> Result<(), DataStoreError>;
+
+    /// Gets the time at which a staged package record expires.
+    ///
+    /// Returns `None` if the record is not staged.
+    async fn get_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<SystemTime>, DataStoreError>;
+
+    /// Removes the staging of the given package record.
+    ///
+    /// Returns false if the record was not staged.
+    async fn remove_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<bool, DataStoreError>;
+
+    /// Rejects the staged package records that expired before the given
+    /// time and removes their staging.
+    ///
+    /// Returns the number of records rejected.
+    async fn expire_staged_publishes(&self, before: SystemTime) -> Result<usize, DataStoreError>;
+
+    /// Requires the given package record to be approved before it is
+    /// submitted for processing.
+    ///
+    /// The record must be in a pending state.
+    async fn store_pending_approval(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<(), DataStoreError>;
+
+    /// Gets the approval of a package record.
+    ///
+    /// Returns `None` if the record does not require approval.
+    async fn get_record_approval(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<ApprovalStatus>, DataStoreError>;
+
+    /// Approves a package record awaiting approval by the given key.
+    ///
+    /// Returns false if the record is not awaiting approval.
+    async fn approve_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        approver: &KeyID,
+    ) -> Result<bool, DataStoreError>;
+
+    /// Removes rejected and pending records last updated before the given
+    /// time, along with their content, staging, and approval.
+    ///
+    /// Pending records awaiting approval are not removed until they are
+    /// approved or rejected; staged records are not removed until their
+    /// staging expires.
+    ///
+    /// Validated records are never removed. Content files referenced by the
+    /// removed records are not deleted, as they may be shared with other
+    /// records.
+    async fn expire_records(&self, before: SystemTime) -> Result<ExpiredRecords, DataStoreError>;
+
     /// Determines if the given content digest is missing for the record.
     ///
     /// The record must be in a pending state.
@@ -253,6 +488,172 @@ pub trait DataStore: Send + Sync {
         log_ids: &[LogId],
     ) -> Result<IndexMap<LogId, Option<PackageName>>, DataStoreError>;
 
+    /// Gets the namespaces defined or imported by an operator log, in the
+    /// order they were added to the log.
+    async fn get_namespaces(
+        &self,
+        operator_log_id: &LogId,
+    ) -> Result<Vec<(String, operator::NamespaceState)>, DataStoreError>;
+
+    /// Lists the names of packages known to the data store.
+    ///
+    /// Names are returned in ascending order; when `after` is provided,
+    /// only names that sort after it are returned.
+    async fn list_package_names(
+        &self,
+        after: Option<&PackageName>,
+        limit: u16,
+    ) -> Result<Vec<PackageName>, DataStoreError>;
+
+    /// Stores a decision made by a content or record policy.
+    async fn store_policy_decision(&self, decision: &PolicyDecision) -> Result<(), DataStoreError>;
+
+    /// Gets the policy decisions made for a package record, in the order
+    /// they were stored.
+    async fn get_policy_decisions(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Vec<PolicyDecision>, DataStoreError>;
+
+    /// Stores the metadata extracted from content with the given digest.
+    async fn store_content_metadata(
+        &self,
+        digest: &AnyHash,
+        metadata: &ContentMetadata,
+    ) -> Result<(), DataStoreError>;
+
+    /// Gets the metadata extracted from content with the given digest.
+    ///
+    /// Returns `None` if no metadata was extracted from the content.
+    async fn get_content_metadata(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Option<ContentMetadata>, DataStoreError>;
+
+    /// Embargoes content with the given digest until the given time.
+    ///
+    /// Embargoing content that is already embargoed replaces its embargo.
+    async fn store_content_embargo(
+        &self,
+        digest: &AnyHash,
+        until: SystemTime,
+    ) -> Result<(), DataStoreError>;
+
+    /// Gets the time until which content with the given digest is embargoed.
+    ///
+    /// Returns `None` if the content was never embargoed; the returned time
+    /// may have already passed.
+    async fn get_content_embargo(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Option<SystemTime>, DataStoreError>;
+
+    /// Gets the validated package records that reference content with the
+    /// given digest, in the order they were validated.
+    ///
+    /// References are maintained as records are committed: content released
+    /// or attested for a version is referenced until that version is yanked.
+    ///
+    /// Content without references may still be needed by pending records.
+    async fn get_content_references(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Vec<ContentReference>, DataStoreError>;
+
+    /// Stores a flag for moderation of a package, replacing any existing
+    /// flag of the package.
+    async fn store_package_flag(&self, flag: &PackageFlag) -> Result<(), DataStoreError>;
+
+    /// Gets the moderation flag of a package.
+    ///
+    /// Returns `None` if the package is not flagged.
+    async fn get_package_flag(&self, log_id: &LogId)
+        -> Result<Option<PackageFlag>, DataStoreError>;
+
+    /// Lists the flagged packages in the order they were flagged.
+    async fn list_package_flags(&self) -> Result<Vec<PackageFlag>, DataStoreError>;
+
+    /// Removes the moderation flag of a package.
+    ///
+    /// Returns false if the package was not flagged.
+    async fn remove_package_flag(&self, log_id: &LogId) -> Result<bool, DataStoreError>;
+
+    /// Stores a vulnerability advisory, replacing any existing advisory of
+    /// the package with the same identifier.
+    async fn store_advisory(&self, advisory: &Advisory) -> Result<(), DataStoreError>;
+
+    /// Lists vulnerability advisories in the order they were published.
+    ///
+    /// If a package is provided, only the advisories of the package are
+    /// listed.
+    async fn list_advisories(
+        &self,
+        package: Option<&PackageName>,
+    ) -> Result<Vec<Advisory>, DataStoreError>;
+
+    /// Removes a vulnerability advisory of a package.
+    ///
+    /// Returns false if the package has no advisory with the identifier.
+    async fn remove_advisory(
+        &self,
+        package: &PackageName,
+        id: &str,
+    ) -> Result<bool, DataStoreError>;
+
+    /// Stores the suspension of a namespace, replacing any existing
+    /// suspension of the namespace.
+    async fn store_namespace_suspension(
+        &self,
+        suspension: &NamespaceSuspension,
+    ) -> Result<(), DataStoreError>;
+
+    /// Gets the suspension of a namespace.
+    ///
+    /// Returns `None` if the namespace is not suspended.
+    async fn get_namespace_suspension(
+        &self,
+        namespace: &str,
+    ) -> Result<Option<NamespaceSuspension>, DataStoreError>;
+
+    /// Lists the suspended namespaces in the order they were suspended.
+    async fn list_namespace_suspensions(&self) -> Result<Vec<NamespaceSuspension>, DataStoreError>;
+
+    /// Removes the suspension of a namespace, restoring it.
+    ///
+    /// Returns false if the namespace was not suspended.
+    async fn remove_namespace_suspension(&self, namespace: &str) -> Result<bool, DataStoreError>;
+
+    /// Stores an invite of the package log the invite is for.
+    ///
+    /// Returns false if the package log already has an invite with the
+    /// identifier.
+    async fn store_package_invite(
+        &self,
+        invite: &SerdeEnvelope<PackageInvite>,
+    ) -> Result<bool, DataStoreError>;
+
+    /// Gets an invite of a package log.
+    ///
+    /// Returns `None` if the package log has no invite with the identifier.
+    async fn get_package_invite(
+        &self,
+        log_id: &LogId,
+        invite_id: &str,
+    ) -> Result<Option<Invite>, DataStoreError>;
+
+    /// Lists the invites of a package log in the order they were stored.
+    async fn list_package_invites(&self, log_id: &LogId) -> Result<Vec<Invite>, DataStoreError>;
+
+    /// Stores the redemption of the invite it redeems.
+    ///
+    /// Returns false if the package log has no unredeemed invite with the
+    /// identifier.
+    async fn redeem_package_invite(
+        &self,
+        redemption: &SerdeEnvelope<InviteRedemption>,
+    ) -> Result<bool, DataStoreError>;
+
     /// Gets a batch of log leafs starting with a registry log index.  
     async fn get_log_leafs_starting_with_registry_index(
         &self,
@@ -292,6 +693,19 @@ pub trait DataStore: Send + Sync {
         record_id: &RecordId,
     ) -> Result<Record<package::PackageRecord>, DataStoreError>;
 
+    /// Lists the identifiers of the most recently stored records of a
+    /// package log, in the order they were stored.
+    ///
+    /// The `pending` filter matches records that are not yet validated,
+    /// and the `published` filter matches validated records, whether or not
+    /// they are included in a checkpoint yet.
+    async fn list_package_records(
+        &self,
+        log_id: &LogId,
+        filter: Option<RecordStatusFilter>,
+        limit: u16,
+    ) -> Result<Vec<RecordId>, DataStoreError>;
+
     /// Verifies the signature of a package record.
     ///
     /// This is different from `validate_package_record` in that
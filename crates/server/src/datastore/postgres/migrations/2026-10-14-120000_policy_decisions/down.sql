@@ -0,0 +1,2 @@
+DROP TABLE policy_decisions;
+DROP TYPE policy_kind;
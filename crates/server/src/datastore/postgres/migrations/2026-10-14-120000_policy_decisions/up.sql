@@ -0,0 +1,16 @@
+CREATE TYPE policy_kind AS ENUM ('content', 'record');
+
+-- Stores the decisions made by content and record policies.
+-- Records are not referenced by foreign key as unauthorized records are never stored.
+CREATE TABLE policy_decisions (
+  id SERIAL PRIMARY KEY,
+  log_id TEXT NOT NULL,
+  record_id TEXT NOT NULL,
+  kind policy_kind NOT NULL,
+  digest TEXT,
+  enforced BOOLEAN NOT NULL,
+  rejection TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX policy_decisions_record_id ON policy_decisions (record_id);
@@ -0,0 +1,6 @@
+-- Stores the metadata extracted from uploaded content.
+CREATE TABLE content_metadata (
+  digest TEXT PRIMARY KEY,
+  license TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
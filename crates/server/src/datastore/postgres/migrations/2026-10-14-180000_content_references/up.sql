@@ -0,0 +1,20 @@
+-- Stores the references to content made by validated package records.
+-- A reference is removed when the version it was made for is yanked, so
+-- content without references is not needed by any package.
+CREATE TABLE content_references (
+  id SERIAL PRIMARY KEY,
+  record_id INTEGER NOT NULL REFERENCES records(id),
+  digest TEXT NOT NULL,
+  version TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX content_references_digest_idx ON content_references (digest);
+
+-- Records validated before references were stored are referenced without a
+-- version; as the versions are unknown, these references are never removed.
+INSERT INTO content_references (record_id, digest)
+SELECT contents.record_id, contents.digest
+FROM contents
+INNER JOIN records ON records.id = contents.record_id
+WHERE records.status = 'validated';
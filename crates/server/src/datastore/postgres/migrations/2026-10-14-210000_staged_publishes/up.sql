@@ -0,0 +1,8 @@
+-- Stores the package records staged to be committed by the publisher.
+-- A staged record is not processed once its content is present; it is
+-- rejected if it is not committed before it expires.
+CREATE TABLE staged_publishes (
+  record_id INTEGER PRIMARY KEY REFERENCES records(id),
+  expires_at TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Stores the times until which content is embargoed.
+-- Embargoed content is not served and releases of it are not listed until
+-- the embargo passes.
+CREATE TABLE content_embargoes (
+  digest TEXT PRIMARY KEY,
+  embargo_until TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
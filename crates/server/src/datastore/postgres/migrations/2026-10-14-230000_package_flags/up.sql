@@ -0,0 +1,11 @@
+-- Stores the packages flagged for moderation, such as suspected typosquats.
+CREATE TABLE package_flags (
+  log_id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  similar_to TEXT NOT NULL,
+  reason TEXT NOT NULL,
+  flagged_at TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX package_flags_flagged_at_idx ON package_flags (flagged_at);
@@ -0,0 +1,15 @@
+-- Stores the vulnerability advisories published for packages.
+CREATE TABLE advisories (
+  id SERIAL PRIMARY KEY,
+  package_name TEXT NOT NULL,
+  advisory_id TEXT NOT NULL,
+  versions TEXT NOT NULL,
+  severity TEXT NOT NULL,
+  summary TEXT NOT NULL,
+  url TEXT,
+  published_at TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  UNIQUE (package_name, advisory_id)
+);
+
+CREATE INDEX advisories_published_at_idx ON advisories (published_at);
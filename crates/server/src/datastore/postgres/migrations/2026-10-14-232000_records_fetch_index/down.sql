@@ -0,0 +1,2 @@
+DROP INDEX checkpoints_log_length_idx;
+DROP INDEX records_log_id_registry_log_index_idx;
@@ -0,0 +1,9 @@
+-- Supports keyset pagination of the published records of a log: fetch tokens
+-- resolve to the registry log index of a record, and the records of the log
+-- that follow it are read in registry log index order.
+CREATE INDEX records_log_id_registry_log_index_idx ON records (log_id, registry_log_index)
+  INCLUDE (record_id)
+  WHERE registry_log_index IS NOT NULL;
+
+-- Supports checking that a fetch is for a known checkpoint.
+CREATE INDEX checkpoints_log_length_idx ON checkpoints (log_length);
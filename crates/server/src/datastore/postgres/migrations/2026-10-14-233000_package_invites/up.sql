@@ -0,0 +1,10 @@
+-- Stores the invites of package logs and their redemptions.
+CREATE TABLE package_invites (
+  id SERIAL PRIMARY KEY,
+  log_id TEXT NOT NULL,
+  invite_id TEXT NOT NULL,
+  invite JSONB NOT NULL,
+  redemption JSONB,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  UNIQUE (log_id, invite_id)
+);
@@ -0,0 +1,8 @@
+-- Stores the approvals of package records in namespaces requiring approval.
+-- A record awaiting approval is not processed until a key other than the
+-- key that signed it approves it.
+CREATE TABLE record_approvals (
+  record_id INTEGER PRIMARY KEY REFERENCES records(id),
+  approved_by TEXT,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
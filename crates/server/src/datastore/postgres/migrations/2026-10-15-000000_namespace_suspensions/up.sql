@@ -0,0 +1,10 @@
+-- Stores the suspended namespaces, whose packages cannot be published and
+-- are hidden from listings.
+CREATE TABLE namespace_suspensions (
+  namespace TEXT PRIMARY KEY,
+  reason TEXT NOT NULL,
+  suspended_at TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX namespace_suspensions_suspended_at_idx ON namespace_suspensions (suspended_at);
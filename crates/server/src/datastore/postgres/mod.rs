@@ -1,8 +1,14 @@
 use self::models::{
-    CheckpointData, NewCheckpoint, NewContent, NewLog, NewRecord, ParsedText, RecordContent,
-    RecordStatus, TextRef,
+    AdvisoryData, CheckpointData, ContentMetadataData, NamespaceSuspensionData, NewAdvisory,
+    NewCheckpoint, NewContent, NewContentEmbargo, NewContentMetadata, NewContentReference, NewLog,
+    NewNamespaceSuspension, NewPackageFlag, NewPackageInvite, NewPolicyDecision, NewRecord,
+    NewRecordApproval, NewStagedPublish, PackageFlagData, PackageInviteData, ParsedText,
+    PolicyDecisionData, PolicyKind, RecordContent, RecordStatus, TextRef,
+};
+use super::{
+    Advisory, ApprovalStatus, ContentReference, ContentReferenceChanges, DataStore, DataStoreError,
+    ExpiredRecords, Invite, NamespaceSuspension, PackageFlag, PolicyDecision, Record,
 };
-use super::{DataStore, DataStoreError, Record};
 use anyhow::{anyhow, Result};
 use diesel::sql_types::{Nullable, Text};
 use diesel::{prelude::*, result::DatabaseErrorKind};
@@ -18,11 +24,13 @@ use diesel_migrations::{
 use futures::{Stream, StreamExt};
 use indexmap::{IndexMap, IndexSet};
 use secrecy::{ExposeSecret, SecretString};
-use std::pin::Pin;
-use warg_crypto::{hash::AnyHash, Decode, Encode, Signable};
+use std::{pin::Pin, time::SystemTime};
+use warg_api::v1::package::RecordStatusFilter;
+use warg_crypto::{hash::AnyHash, signing::KeyID, Decode, Encode, Signable};
 use warg_protocol::{
+    metadata::ContentMetadata,
     operator,
-    package::{self, PackageEntry},
+    package::{self, InviteRedemption, PackageEntry, PackageInvite},
     registry::{
         Checkpoint, LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen,
         TimestampedCheckpoint,
@@ -50,6 +58,9 @@ async fn get_records<R: Decode>(
         .optional()?
         .ok_or_else(|| DataStoreError::CheckpointNotFound(registry_log_length))?;
 
+    // Records are paginated by registry log index rather than by offset, so
+    // each page is a range scan of the `(log_id, registry_log_index)` index
+    // regardless of how far into the log it starts
     let mut query = schema::records::table
         .into_boxed()
         .select((
@@ -57,7 +68,7 @@ async fn get_records<R: Decode>(
             schema::records::content,
             schema::records::registry_log_index,
         ))
-        .order_by(schema::records::id.asc())
+        .order_by(schema::records::registry_log_index.asc())
         .limit(limit)
         .filter(
             schema::records::log_id
@@ -67,15 +78,21 @@ async fn get_records<R: Decode>(
         );
 
     if let Some(since) = since {
-        let record_id = schema::records::table
-            .select(schema::records::id)
-            .filter(schema::records::record_id.eq(TextRef(since)))
-            .first::<i32>(conn)
+        // A fetch token must be a published record of the log
+        let since_index = schema::records::table
+            .select(schema::records::registry_log_index)
+            .filter(
+                schema::records::record_id
+                    .eq(TextRef(since))
+                    .and(schema::records::log_id.eq(log_id)),
+            )
+            .first::<Option<i64>>(conn)
             .await
             .optional()?
+            .flatten()
             .ok_or_else(|| DataStoreError::RecordNotFound(since.clone()))?;
 
-        query = query.filter(schema::records::id.gt(record_id));
+        query = query.filter(schema::records::registry_log_index.gt(since_index));
     }
 
     query
@@ -212,6 +229,7 @@ async fn commit_record<V>(
     log_id: i32,
     record_id: &RecordId,
     registry_index: RegistryIndex,
+    references: impl FnOnce(&V::Record) -> ContentReferenceChanges + Send,
 ) -> Result<(), DataStoreError>
 where
     V: Validator + 'static,
@@ -249,7 +267,7 @@ where
             })?;
 
             // Validate the record
-            let validator = validator.0.validate(&record).map_err(Into::into)?;
+            let validator = validator.0.validate(&record)?;
 
             // Store the updated validation state
             diesel::update(schema::logs::table)
@@ -268,6 +286,47 @@ where
                 .execute(conn)
                 .await?;
 
+            // Update the references to content made by the record
+            let changes = references(record.as_ref());
+            if !changes.added.is_empty() {
+                diesel::insert_into(schema::content_references::table)
+                    .values(
+                        &changes
+                            .added
+                            .iter()
+                            .map(|(digest, version)| NewContentReference {
+                                record_id: id,
+                                digest: TextRef(digest),
+                                version: Some(TextRef(version)),
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                    .execute(conn)
+                    .await?;
+            }
+
+            if !changes.yanked.is_empty() {
+                diesel::delete(schema::content_references::table)
+                    .filter(
+                        schema::content_references::record_id.eq_any(
+                            schema::records::table
+                                .select(schema::records::id)
+                                .filter(schema::records::log_id.eq(log_id)),
+                        ),
+                    )
+                    .filter(
+                        schema::content_references::version.eq_any(
+                            changes
+                                .yanked
+                                .iter()
+                                .map(ToString::to_string)
+                                .collect::<Vec<_>>(),
+                        ),
+                    )
+                    .execute(conn)
+                    .await?;
+            }
+
             Ok(())
         }
         .scope_boxed()
@@ -557,6 +616,461 @@ impl DataStore for PostgresDataStore {
         Ok(map)
     }
 
+    async fn get_namespaces(
+        &self,
+        operator_log_id: &LogId,
+    ) -> Result<Vec<(String, operator::NamespaceState)>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        let validator = schema::logs::table
+            .select(schema::logs::validator)
+            .filter(schema::logs::log_id.eq(TextRef(operator_log_id)))
+            .first::<Json<operator::LogState>>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?;
+
+        Ok(validator
+            .namespaces()
+            .map(|(namespace, state)| (namespace.to_string(), state.clone()))
+            .collect())
+    }
+
+    async fn list_package_names(
+        &self,
+        after: Option<&PackageName>,
+        limit: u16,
+    ) -> Result<Vec<PackageName>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        let mut query = schema::logs::table
+            .into_boxed()
+            .select(schema::logs::name)
+            .filter(schema::logs::name.is_not_null())
+            .filter(diesel::dsl::exists(
+                schema::records::table
+                    .filter(schema::records::log_id.eq(schema::logs::id))
+                    .filter(schema::records::status.eq(RecordStatus::Validated)),
+            ))
+            .order_by(schema::logs::name.asc())
+            .limit(limit as i64);
+
+        if let Some(after) = after {
+            query = query.filter(schema::logs::name.gt(after.as_ref()));
+        }
+
+        Ok(query
+            .load::<Option<String>>(&mut conn)
+            .await?
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.parse().ok())
+            .collect())
+    }
+
+    async fn store_policy_decision(&self, decision: &PolicyDecision) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        diesel::insert_into(schema::policy_decisions::table)
+            .values(NewPolicyDecision {
+                log_id: TextRef(&decision.log_id),
+                record_id: TextRef(&decision.record_id),
+                kind: match decision.kind {
+                    super::PolicyKind::Content => PolicyKind::Content,
+                    super::PolicyKind::Record => PolicyKind::Record,
+                },
+                digest: decision.digest.as_ref().map(TextRef),
+                enforced: decision.enforced,
+                rejection: decision.rejection.as_deref(),
+            })
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_policy_decisions(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Vec<PolicyDecision>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        Ok(schema::policy_decisions::table
+            .select(PolicyDecisionData::as_select())
+            .filter(schema::policy_decisions::log_id.eq(TextRef(log_id)))
+            .filter(schema::policy_decisions::record_id.eq(TextRef(record_id)))
+            .order_by(schema::policy_decisions::id.asc())
+            .load::<PolicyDecisionData>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|d| PolicyDecision {
+                log_id: d.log_id.0.into(),
+                record_id: d.record_id.0.into(),
+                kind: match d.kind {
+                    PolicyKind::Content => super::PolicyKind::Content,
+                    PolicyKind::Record => super::PolicyKind::Record,
+                },
+                digest: d.digest.map(|d| d.0),
+                enforced: d.enforced,
+                rejection: d.rejection,
+            })
+            .collect())
+    }
+
+    async fn store_content_metadata(
+        &self,
+        digest: &AnyHash,
+        metadata: &ContentMetadata,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        // Metadata is derived from the content itself, so it never changes
+        diesel::insert_into(schema::content_metadata::table)
+            .values(NewContentMetadata {
+                digest: TextRef(digest),
+                license: metadata.license.as_deref(),
+            })
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_content_metadata(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Option<ContentMetadata>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        Ok(schema::content_metadata::table
+            .select(ContentMetadataData::as_select())
+            .filter(schema::content_metadata::digest.eq(TextRef(digest)))
+            .first::<ContentMetadataData>(&mut conn)
+            .await
+            .optional()?
+            .map(|m| ContentMetadata { license: m.license }))
+    }
+
+    async fn store_content_embargo(
+        &self,
+        digest: &AnyHash,
+        until: SystemTime,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let until: chrono::DateTime<chrono::Utc> = until.into();
+
+        diesel::insert_into(schema::content_embargoes::table)
+            .values(NewContentEmbargo {
+                digest: TextRef(digest),
+                embargo_until: until,
+            })
+            .on_conflict(schema::content_embargoes::digest)
+            .do_update()
+            .set(schema::content_embargoes::embargo_until.eq(until))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_content_embargo(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Option<SystemTime>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::content_embargoes::table
+            .select(schema::content_embargoes::embargo_until)
+            .filter(schema::content_embargoes::digest.eq(TextRef(digest)))
+            .first::<chrono::DateTime<chrono::Utc>>(&mut conn)
+            .await
+            .optional()?
+            .map(Into::into))
+    }
+
+    async fn store_package_flag(&self, flag: &PackageFlag) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let flagged_at: chrono::DateTime<chrono::Utc> = flag.flagged_at.into();
+
+        diesel::insert_into(schema::package_flags::table)
+            .values(NewPackageFlag {
+                log_id: TextRef(&flag.log_id),
+                name: TextRef(&flag.name),
+                similar_to: TextRef(&flag.similar_to),
+                reason: &flag.reason,
+                flagged_at,
+            })
+            .on_conflict(schema::package_flags::log_id)
+            .do_update()
+            .set((
+                schema::package_flags::similar_to.eq(TextRef(&flag.similar_to)),
+                schema::package_flags::reason.eq(&flag.reason),
+                schema::package_flags::flagged_at.eq(flagged_at),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_package_flag(
+        &self,
+        log_id: &LogId,
+    ) -> Result<Option<PackageFlag>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::package_flags::table
+            .select(PackageFlagData::as_select())
+            .filter(schema::package_flags::log_id.eq(TextRef(log_id)))
+            .first::<PackageFlagData>(&mut conn)
+            .await
+            .optional()?
+            .map(Into::into))
+    }
+
+    async fn list_package_flags(&self) -> Result<Vec<PackageFlag>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::package_flags::table
+            .select(PackageFlagData::as_select())
+            .order_by(schema::package_flags::flagged_at)
+            .load::<PackageFlagData>(&mut conn)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn remove_package_flag(&self, log_id: &LogId) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let removed = diesel::delete(schema::package_flags::table)
+            .filter(schema::package_flags::log_id.eq(TextRef(log_id)))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(removed > 0)
+    }
+
+    async fn store_advisory(&self, advisory: &Advisory) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let published_at: chrono::DateTime<chrono::Utc> = advisory.published_at.into();
+        let inner = &advisory.advisory;
+
+        diesel::insert_into(schema::advisories::table)
+            .values(NewAdvisory {
+                package_name: TextRef(&advisory.package),
+                advisory_id: &inner.id,
+                versions: TextRef(&inner.versions),
+                severity: inner.severity.as_str(),
+                summary: &inner.summary,
+                url: inner.url.as_deref(),
+                published_at,
+            })
+            .on_conflict((
+                schema::advisories::package_name,
+                schema::advisories::advisory_id,
+            ))
+            .do_update()
+            .set((
+                schema::advisories::versions.eq(TextRef(&inner.versions)),
+                schema::advisories::severity.eq(inner.severity.as_str()),
+                schema::advisories::summary.eq(&inner.summary),
+                schema::advisories::url.eq(inner.url.as_deref()),
+                schema::advisories::published_at.eq(published_at),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_advisories(
+        &self,
+        package: Option<&PackageName>,
+    ) -> Result<Vec<Advisory>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let mut query = schema::advisories::table
+            .select(AdvisoryData::as_select())
+            .order_by((schema::advisories::published_at, schema::advisories::id))
+            .into_boxed();
+        if let Some(package) = package {
+            query = query.filter(schema::advisories::package_name.eq(TextRef(package)));
+        }
+
+        Ok(query
+            .load::<AdvisoryData>(&mut conn)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn remove_advisory(
+        &self,
+        package: &PackageName,
+        id: &str,
+    ) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let removed = diesel::delete(schema::advisories::table)
+            .filter(schema::advisories::package_name.eq(TextRef(package)))
+            .filter(schema::advisories::advisory_id.eq(id))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(removed > 0)
+    }
+
+    async fn store_namespace_suspension(
+        &self,
+        suspension: &NamespaceSuspension,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let suspended_at: chrono::DateTime<chrono::Utc> = suspension.suspended_at.into();
+
+        diesel::insert_into(schema::namespace_suspensions::table)
+            .values(NewNamespaceSuspension {
+                namespace: &suspension.namespace,
+                reason: &suspension.reason,
+                suspended_at,
+            })
+            .on_conflict(schema::namespace_suspensions::namespace)
+            .do_update()
+            .set((
+                schema::namespace_suspensions::reason.eq(&suspension.reason),
+                schema::namespace_suspensions::suspended_at.eq(suspended_at),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_namespace_suspension(
+        &self,
+        namespace: &str,
+    ) -> Result<Option<NamespaceSuspension>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::namespace_suspensions::table
+            .select(NamespaceSuspensionData::as_select())
+            .filter(schema::namespace_suspensions::namespace.eq(namespace))
+            .first::<NamespaceSuspensionData>(&mut conn)
+            .await
+            .optional()?
+            .map(Into::into))
+    }
+
+    async fn list_namespace_suspensions(&self) -> Result<Vec<NamespaceSuspension>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::namespace_suspensions::table
+            .select(NamespaceSuspensionData::as_select())
+            .order_by(schema::namespace_suspensions::suspended_at)
+            .load::<NamespaceSuspensionData>(&mut conn)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn remove_namespace_suspension(&self, namespace: &str) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let removed = diesel::delete(schema::namespace_suspensions::table)
+            .filter(schema::namespace_suspensions::namespace.eq(namespace))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(removed > 0)
+    }
+
+    async fn store_package_invite(
+        &self,
+        invite: &SerdeEnvelope<PackageInvite>,
+    ) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let stored = diesel::insert_into(schema::package_invites::table)
+            .values(NewPackageInvite {
+                log_id: TextRef(&invite.as_ref().log_id),
+                invite_id: &invite.as_ref().id,
+                invite: &Json(invite.clone()),
+            })
+            .on_conflict((
+                schema::package_invites::log_id,
+                schema::package_invites::invite_id,
+            ))
+            .do_nothing()
+            .execute(&mut conn)
+            .await?;
+
+        Ok(stored > 0)
+    }
+
+    async fn get_package_invite(
+        &self,
+        log_id: &LogId,
+        invite_id: &str,
+    ) -> Result<Option<Invite>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::package_invites::table
+            .select(PackageInviteData::as_select())
+            .filter(schema::package_invites::log_id.eq(TextRef(log_id)))
+            .filter(schema::package_invites::invite_id.eq(invite_id))
+            .first::<PackageInviteData>(&mut conn)
+            .await
+            .optional()?
+            .map(Into::into))
+    }
+
+    async fn list_package_invites(&self, log_id: &LogId) -> Result<Vec<Invite>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::package_invites::table
+            .select(PackageInviteData::as_select())
+            .filter(schema::package_invites::log_id.eq(TextRef(log_id)))
+            .order_by(schema::package_invites::id)
+            .load::<PackageInviteData>(&mut conn)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn redeem_package_invite(
+        &self,
+        redemption: &SerdeEnvelope<InviteRedemption>,
+    ) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let redeemed = diesel::update(schema::package_invites::table)
+            .filter(schema::package_invites::log_id.eq(TextRef(&redemption.as_ref().log_id)))
+            .filter(schema::package_invites::invite_id.eq(&redemption.as_ref().invite_id))
+            .filter(schema::package_invites::redemption.is_null())
+            .set(schema::package_invites::redemption.eq(Json(redemption.clone())))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(redeemed > 0)
+    }
+
+    async fn get_content_references(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Vec<ContentReference>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        Ok(schema::content_references::table
+            .inner_join(schema::records::table.inner_join(schema::logs::table))
+            .select((schema::logs::log_id, schema::records::record_id))
+            .filter(schema::content_references::digest.eq(TextRef(digest)))
+            .order_by((
+                schema::records::registry_log_index.asc(),
+                schema::content_references::id.asc(),
+            ))
+            .load::<(ParsedText<AnyHash>, ParsedText<AnyHash>)>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|(log_id, record_id)| ContentReference {
+                log_id: log_id.0.into(),
+                record_id: record_id.0.into(),
+            })
+            .collect())
+    }
+
     async fn store_operator_record(
         &self,
         log_id: &LogId,
@@ -608,8 +1122,15 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        match commit_record::<operator::LogState>(conn.as_mut(), log_id, record_id, registry_index)
-            .await
+        // Operator records do not reference content
+        match commit_record::<operator::LogState>(
+            conn.as_mut(),
+            log_id,
+            record_id,
+            registry_index,
+            |_| ContentReferenceChanges::default(),
+        )
+        .await
         {
             Ok(()) => Ok(()),
             Err(e) => {
@@ -672,8 +1193,14 @@ impl DataStore for PostgresDataStore {
             .optional()?
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
-        match commit_record::<package::LogState>(conn.as_mut(), log_id, record_id, registry_index)
-            .await
+        match commit_record::<package::LogState>(
+            conn.as_mut(),
+            log_id,
+            record_id,
+            registry_index,
+            ContentReferenceChanges::new,
+        )
+        .await
         {
             Ok(()) => Ok(()),
             Err(e) => {
@@ -683,6 +1210,260 @@ impl DataStore for PostgresDataStore {
         }
     }
 
+    async fn store_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        expires_at: SystemTime,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let id = schema::records::table
+            .inner_join(schema::logs::table)
+            .select(schema::records::id)
+            .filter(
+                schema::records::status
+                    .eq(RecordStatus::Pending)
+                    .and(schema::logs::log_id.eq(TextRef(log_id)))
+                    .and(schema::records::record_id.eq(TextRef(record_id))),
+            )
+            .first::<i32>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
+
+        diesel::insert_into(schema::staged_publishes::table)
+            .values(NewStagedPublish {
+                record_id: id,
+                expires_at: expires_at.into(),
+            })
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<SystemTime>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::staged_publishes::table
+            .inner_join(schema::records::table.inner_join(schema::logs::table))
+            .select(schema::staged_publishes::expires_at)
+            .filter(
+                schema::logs::log_id
+                    .eq(TextRef(log_id))
+                    .and(schema::records::record_id.eq(TextRef(record_id))),
+            )
+            .first::<chrono::DateTime<chrono::Utc>>(&mut conn)
+            .await
+            .optional()?
+            .map(Into::into))
+    }
+
+    async fn remove_staged_publish(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let removed = diesel::delete(schema::staged_publishes::table)
+            .filter(
+                schema::staged_publishes::record_id.eq_any(
+                    schema::records::table
+                        .inner_join(schema::logs::table)
+                        .select(schema::records::id)
+                        .filter(
+                            schema::logs::log_id
+                                .eq(TextRef(log_id))
+                                .and(schema::records::record_id.eq(TextRef(record_id))),
+                        ),
+                ),
+            )
+            .execute(&mut conn)
+            .await?;
+
+        Ok(removed > 0)
+    }
+
+    async fn expire_staged_publishes(&self, before: SystemTime) -> Result<usize, DataStoreError> {
+        let before: chrono::DateTime<chrono::Utc> = before.into();
+        let mut conn = self.pool.get().await?;
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            async move {
+                let ids = diesel::delete(schema::staged_publishes::table)
+                    .filter(schema::staged_publishes::expires_at.lt(before))
+                    .returning(schema::staged_publishes::record_id)
+                    .get_results::<i32>(conn.as_mut())
+                    .await?;
+
+                Ok(diesel::update(schema::records::table)
+                    .filter(
+                        schema::records::id
+                            .eq_any(&ids)
+                            .and(schema::records::status.eq(RecordStatus::Pending)),
+                    )
+                    .set((
+                        schema::records::status.eq(RecordStatus::Rejected),
+                        schema::records::reason.eq("the staged publish expired"),
+                    ))
+                    .execute(conn.as_mut())
+                    .await?)
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    async fn store_pending_approval(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let id = schema::records::table
+            .inner_join(schema::logs::table)
+            .select(schema::records::id)
+            .filter(
+                schema::records::status
+                    .eq(RecordStatus::Pending)
+                    .and(schema::logs::log_id.eq(TextRef(log_id)))
+                    .and(schema::records::record_id.eq(TextRef(record_id))),
+            )
+            .first::<i32>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
+
+        diesel::insert_into(schema::record_approvals::table)
+            .values(NewRecordApproval { record_id: id })
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_record_approval(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Option<ApprovalStatus>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        Ok(schema::record_approvals::table
+            .inner_join(schema::records::table.inner_join(schema::logs::table))
+            .select(schema::record_approvals::approved_by)
+            .filter(
+                schema::logs::log_id
+                    .eq(TextRef(log_id))
+                    .and(schema::records::record_id.eq(TextRef(record_id))),
+            )
+            .first::<Option<String>>(&mut conn)
+            .await
+            .optional()?
+            .map(|approved_by| match approved_by {
+                Some(key_id) => ApprovalStatus::Approved(KeyID::from(key_id)),
+                None => ApprovalStatus::Awaiting,
+            }))
+    }
+
+    async fn approve_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        approver: &KeyID,
+    ) -> Result<bool, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let approved = diesel::update(schema::record_approvals::table)
+            .filter(
+                schema::record_approvals::approved_by.is_null().and(
+                    schema::record_approvals::record_id.eq_any(
+                        schema::records::table
+                            .inner_join(schema::logs::table)
+                            .select(schema::records::id)
+                            .filter(
+                                schema::logs::log_id
+                                    .eq(TextRef(log_id))
+                                    .and(schema::records::record_id.eq(TextRef(record_id))),
+                            ),
+                    ),
+                ),
+            )
+            .set(schema::record_approvals::approved_by.eq(approver.to_string()))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(approved > 0)
+    }
+
+    async fn expire_records(&self, before: SystemTime) -> Result<ExpiredRecords, DataStoreError> {
+        let now: chrono::DateTime<chrono::Utc> = SystemTime::now().into();
+        let before: chrono::DateTime<chrono::Utc> = before.into();
+        let mut conn = self.pool.get().await?;
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            async move {
+                let expired = schema::records::table
+                    .select((schema::records::id, schema::records::status))
+                    .filter(schema::records::updated_at.lt(before))
+                    // Records awaiting approval or a commit are kept until
+                    // they are approved or rejected, or their staging expires
+                    .filter(
+                        schema::records::status.eq(RecordStatus::Rejected).or(
+                            schema::records::status
+                                .eq(RecordStatus::Pending)
+                                .and(diesel::dsl::not(diesel::dsl::exists(
+                                    schema::staged_publishes::table.filter(
+                                        schema::staged_publishes::record_id
+                                            .eq(schema::records::id)
+                                            .and(schema::staged_publishes::expires_at.gt(now)),
+                                    ),
+                                )))
+                                .and(diesel::dsl::not(diesel::dsl::exists(
+                                    schema::record_approvals::table.filter(
+                                        schema::record_approvals::record_id.eq(schema::records::id),
+                                    ),
+                                ))),
+                        ),
+                    )
+                    .load::<(i32, RecordStatus)>(conn.as_mut())
+                    .await?;
+
+                if expired.is_empty() {
+                    return Ok(ExpiredRecords::default());
+                }
+
+                let ids = expired.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+                diesel::delete(schema::contents::table)
+                    .filter(schema::contents::record_id.eq_any(&ids))
+                    .execute(conn.as_mut())
+                    .await?;
+                diesel::delete(schema::staged_publishes::table)
+                    .filter(schema::staged_publishes::record_id.eq_any(&ids))
+                    .execute(conn.as_mut())
+                    .await?;
+                diesel::delete(schema::record_approvals::table)
+                    .filter(schema::record_approvals::record_id.eq_any(&ids))
+                    .execute(conn.as_mut())
+                    .await?;
+                diesel::delete(schema::records::table)
+                    .filter(schema::records::id.eq_any(&ids))
+                    .execute(conn.as_mut())
+                    .await?;
+
+                let rejected = expired
+                    .iter()
+                    .filter(|(_, status)| *status == RecordStatus::Rejected)
+                    .count();
+                Ok(ExpiredRecords {
+                    rejected,
+                    pending: expired.len() - rejected,
+                })
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
     async fn is_content_missing(
         &self,
         log_id: &LogId,
@@ -920,6 +1701,47 @@ impl DataStore for PostgresDataStore {
         get_record::<operator::LogState>(conn.as_mut(), log_id, record_id).await
     }
 
+    async fn list_package_records(
+        &self,
+        log_id: &LogId,
+        filter: Option<RecordStatusFilter>,
+        limit: u16,
+    ) -> Result<Vec<RecordId>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        let log_id = schema::logs::table
+            .select(schema::logs::id)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<i32>(conn.as_mut())
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let mut query = schema::records::table
+            .select(schema::records::record_id)
+            .filter(schema::records::log_id.eq(log_id))
+            .order_by(schema::records::id.desc())
+            .limit(limit as i64)
+            .into_boxed();
+
+        if let Some(filter) = filter {
+            query = query.filter(schema::records::status.eq(match filter {
+                RecordStatusFilter::Pending => RecordStatus::Pending,
+                RecordStatusFilter::Rejected => RecordStatus::Rejected,
+                RecordStatusFilter::Published => RecordStatus::Validated,
+            }));
+        }
+
+        let mut record_ids = query
+            .load::<ParsedText<AnyHash>>(conn.as_mut())
+            .await?
+            .into_iter()
+            .map(|record_id| record_id.0.into())
+            .collect::<Vec<_>>();
+
+        record_ids.reverse();
+        Ok(record_ids)
+    }
+
     async fn get_package_record(
         &self,
         log_id: &LogId,
@@ -1,4 +1,8 @@
-use super::schema::{checkpoints, contents, logs, records};
+use super::schema::{
+    advisories, checkpoints, content_embargoes, content_metadata, content_references, contents,
+    logs, namespace_suspensions, package_flags, package_invites, policy_decisions,
+    record_approvals, records, staged_publishes,
+};
 use chrono::{DateTime, Utc};
 use diesel::{
     deserialize::{self, FromSql},
@@ -10,11 +14,16 @@ use diesel::{
 use diesel_json::Json;
 use serde::Serialize;
 use std::{fmt::Display, io::Write, str::FromStr};
+use warg_api::v1::package::VulnerabilityAdvisory;
 use warg_crypto::{
     hash::AnyHash,
     signing::{KeyID, Signature},
 };
-use warg_protocol::registry::{LogId, RecordId};
+use warg_protocol::{
+    package::{InviteRedemption, PackageInvite},
+    registry::{LogId, PackageName, RecordId},
+    SerdeEnvelope, Version, VersionReq,
+};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, diesel_derive_enum::DbEnum)]
 #[ExistingTypePath = "crate::datastore::postgres::schema::sql_types::RecordStatus"]
@@ -24,6 +33,13 @@ pub enum RecordStatus {
     Validated,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, diesel_derive_enum::DbEnum)]
+#[ExistingTypePath = "crate::datastore::postgres::schema::sql_types::PolicyKind"]
+pub enum PolicyKind {
+    Content,
+    Record,
+}
+
 #[derive(FromSqlRow, AsExpression, Debug)]
 #[diesel(sql_type = sql_types::Text)]
 pub struct Text<T>(pub T);
@@ -122,3 +138,196 @@ pub struct NewContent<'a> {
     pub digest: TextRef<'a, AnyHash>,
     pub missing: bool,
 }
+
+#[derive(Insertable)]
+#[diesel(table_name = content_references)]
+pub struct NewContentReference<'a> {
+    pub record_id: i32,
+    pub digest: TextRef<'a, AnyHash>,
+    pub version: Option<TextRef<'a, Version>>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = staged_publishes)]
+pub struct NewStagedPublish {
+    pub record_id: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = record_approvals)]
+pub struct NewRecordApproval {
+    pub record_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = policy_decisions)]
+pub struct NewPolicyDecision<'a> {
+    pub log_id: TextRef<'a, LogId>,
+    pub record_id: TextRef<'a, RecordId>,
+    pub kind: PolicyKind,
+    pub digest: Option<TextRef<'a, AnyHash>>,
+    pub enforced: bool,
+    pub rejection: Option<&'a str>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = content_metadata)]
+pub struct NewContentMetadata<'a> {
+    pub digest: TextRef<'a, AnyHash>,
+    pub license: Option<&'a str>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = content_embargoes)]
+pub struct NewContentEmbargo<'a> {
+    pub digest: TextRef<'a, AnyHash>,
+    pub embargo_until: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = package_flags)]
+pub struct NewPackageFlag<'a> {
+    pub log_id: TextRef<'a, LogId>,
+    pub name: TextRef<'a, PackageName>,
+    pub similar_to: TextRef<'a, PackageName>,
+    pub reason: &'a str,
+    pub flagged_at: DateTime<Utc>,
+}
+
+/// Selects the package flag fields
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = package_flags)]
+pub struct PackageFlagData {
+    pub log_id: ParsedText<AnyHash>,
+    pub name: String,
+    pub similar_to: String,
+    pub reason: String,
+    pub flagged_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = namespace_suspensions)]
+pub struct NewNamespaceSuspension<'a> {
+    pub namespace: &'a str,
+    pub reason: &'a str,
+    pub suspended_at: DateTime<Utc>,
+}
+
+/// Selects the namespace suspension fields
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = namespace_suspensions)]
+pub struct NamespaceSuspensionData {
+    pub namespace: String,
+    pub reason: String,
+    pub suspended_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = package_invites)]
+pub struct NewPackageInvite<'a> {
+    pub log_id: TextRef<'a, LogId>,
+    pub invite_id: &'a str,
+    pub invite: &'a Json<SerdeEnvelope<PackageInvite>>,
+}
+
+/// Selects the package invite fields
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = package_invites)]
+pub struct PackageInviteData {
+    pub invite: Json<SerdeEnvelope<PackageInvite>>,
+    pub redemption: Option<Json<SerdeEnvelope<InviteRedemption>>>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = advisories)]
+pub struct NewAdvisory<'a> {
+    pub package_name: TextRef<'a, PackageName>,
+    pub advisory_id: &'a str,
+    pub versions: TextRef<'a, VersionReq>,
+    pub severity: &'a str,
+    pub summary: &'a str,
+    pub url: Option<&'a str>,
+    pub published_at: DateTime<Utc>,
+}
+
+/// Selects the advisory fields
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = advisories)]
+pub struct AdvisoryData {
+    pub package_name: String,
+    pub advisory_id: String,
+    pub versions: ParsedText<VersionReq>,
+    pub severity: String,
+    pub summary: String,
+    pub url: Option<String>,
+    pub published_at: DateTime<Utc>,
+}
+
+/// Selects the content metadata fields
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = content_metadata)]
+pub struct ContentMetadataData {
+    pub license: Option<String>,
+}
+
+/// Selects the policy decision fields
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = policy_decisions)]
+pub struct PolicyDecisionData {
+    pub log_id: ParsedText<AnyHash>,
+    pub record_id: ParsedText<AnyHash>,
+    pub kind: PolicyKind,
+    pub digest: Option<ParsedText<AnyHash>>,
+    pub enforced: bool,
+    pub rejection: Option<String>,
+}
+
+impl From<PackageFlagData> for super::PackageFlag {
+    fn from(data: PackageFlagData) -> Self {
+        Self {
+            log_id: data.log_id.0.into(),
+            // Package names are validated before they are stored
+            name: PackageName::new(data.name).unwrap(),
+            similar_to: PackageName::new(data.similar_to).unwrap(),
+            reason: data.reason,
+            flagged_at: data.flagged_at.into(),
+        }
+    }
+}
+
+impl From<NamespaceSuspensionData> for super::NamespaceSuspension {
+    fn from(data: NamespaceSuspensionData) -> Self {
+        Self {
+            namespace: data.namespace,
+            reason: data.reason,
+            suspended_at: data.suspended_at.into(),
+        }
+    }
+}
+
+impl From<PackageInviteData> for super::Invite {
+    fn from(data: PackageInviteData) -> Self {
+        Self {
+            invite: data.invite.0,
+            redemption: data.redemption.map(|redemption| redemption.0),
+        }
+    }
+}
+
+impl From<AdvisoryData> for super::Advisory {
+    fn from(data: AdvisoryData) -> Self {
+        Self {
+            // Package names and severities are validated before they are stored
+            package: PackageName::new(data.package_name).unwrap(),
+            advisory: VulnerabilityAdvisory {
+                id: data.advisory_id,
+                versions: data.versions.0,
+                severity: data.severity.parse().unwrap(),
+                summary: data.summary,
+                url: data.url,
+            },
+            published_at: data.published_at.into(),
+        }
+    }
+}
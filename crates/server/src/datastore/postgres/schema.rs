@@ -1,6 +1,10 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "policy_kind"))]
+    pub struct PolicyKind;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "record_status"))]
     pub struct RecordStatus;
@@ -21,6 +25,32 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    content_embargoes (digest) {
+        digest -> Text,
+        embargo_until -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    content_metadata (digest) {
+        digest -> Text,
+        license -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    content_references (id) {
+        id -> Int4,
+        record_id -> Int4,
+        digest -> Text,
+        version -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     contents (id) {
         id -> Int4,
@@ -43,6 +73,67 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    advisories (id) {
+        id -> Int4,
+        package_name -> Text,
+        advisory_id -> Text,
+        versions -> Text,
+        severity -> Text,
+        summary -> Text,
+        url -> Nullable<Text>,
+        published_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    package_invites (id) {
+        id -> Int4,
+        log_id -> Text,
+        invite_id -> Text,
+        invite -> Jsonb,
+        redemption -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    namespace_suspensions (namespace) {
+        namespace -> Text,
+        reason -> Text,
+        suspended_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    package_flags (log_id) {
+        log_id -> Text,
+        name -> Text,
+        similar_to -> Text,
+        reason -> Text,
+        flagged_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::PolicyKind;
+
+    policy_decisions (id) {
+        id -> Int4,
+        log_id -> Text,
+        record_id -> Text,
+        kind -> PolicyKind,
+        digest -> Nullable<Text>,
+        enforced -> Bool,
+        rejection -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::RecordStatus;
@@ -60,7 +151,41 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    record_approvals (record_id) {
+        record_id -> Int4,
+        approved_by -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    staged_publishes (record_id) {
+        record_id -> Int4,
+        expires_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(content_references -> records (record_id));
 diesel::joinable!(contents -> records (record_id));
+diesel::joinable!(record_approvals -> records (record_id));
 diesel::joinable!(records -> logs (log_id));
+diesel::joinable!(staged_publishes -> records (record_id));
 
-diesel::allow_tables_to_appear_in_same_query!(checkpoints, contents, logs, records,);
+diesel::allow_tables_to_appear_in_same_query!(
+    advisories,
+    checkpoints,
+    content_embargoes,
+    content_metadata,
+    content_references,
+    contents,
+    logs,
+    namespace_suspensions,
+    package_flags,
+    package_invites,
+    policy_decisions,
+    record_approvals,
+    records,
+    staged_publishes,
+);
@@ -1,24 +1,43 @@
-use crate::{api::create_router, datastore::MemoryDataStore};
-use anyhow::{Context, Result};
+use crate::{
+    api::{create_router, throttle::ThrottleConfig},
+    datastore::MemoryDataStore,
+};
+use anyhow::{bail, Context, Result};
 use axum::Router;
 use datastore::DataStore;
 use futures::Future;
-use policy::{content::ContentPolicy, record::RecordPolicy};
-use services::CoreService;
+use listener::{BoundListener, ListenerConfig};
+use policy::{
+    approval::ApprovalPolicy,
+    content::ContentPolicy,
+    record::{NameListPolicy, RecordPolicy, RecordPolicyCollection, TeamPolicy},
+    semver::SemverPolicy,
+    sources::ContentSourcePolicy,
+    PolicyMode,
+};
+use secrecy::SecretString;
+use services::{
+    ContentScrubber, CoreService, FederationConfig, RecordRetention, RetentionConfig, ScrubConfig,
+    StaticExporter, TyposquatConfig,
+};
 use std::{fs, net::SocketAddr, path::PathBuf, pin::Pin, sync::Arc, time::Duration};
-use tokio::{net::TcpListener, task::JoinHandle};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use url::Url;
-use warg_crypto::signing::PrivateKey;
-use warg_protocol::operator;
+use warg_crypto::{hash::Sha256, signing::PrivateKey};
+use warg_protocol::{operator, registry::LogId};
 
+pub mod admin;
 pub mod api;
 pub mod args;
 pub mod datastore;
+pub mod listener;
 pub mod policy;
 pub mod services;
 
 const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0:8090";
 const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_STAGED_PUBLISH_TTL: Duration = Duration::from_secs(24 * 60 * 60);
 
 type ShutdownFut = Pin<Box<dyn Future<Output = ()> + Send + Sync>>;
 
@@ -27,6 +46,8 @@ pub struct Config {
     operator_key: PrivateKey,
     namespaces: Option<Vec<(String, operator::NamespaceState)>>,
     addr: Option<SocketAddr>,
+    unix_socket: Option<PathBuf>,
+    listeners: Vec<ListenerConfig>,
     data_store: Option<Box<dyn DataStore>>,
     content_dir: PathBuf,
     content_base_url: Option<Url>,
@@ -34,6 +55,21 @@ pub struct Config {
     checkpoint_interval: Option<Duration>,
     content_policy: Option<Arc<dyn ContentPolicy>>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    policy_mode: PolicyMode,
+    content_scrub: Option<ScrubConfig>,
+    record_retention: Option<RetentionConfig>,
+    staged_publish_ttl: Option<Duration>,
+    name_lists: Option<NameListPolicy>,
+    teams: Option<TeamPolicy>,
+    approvals: Option<ApprovalPolicy>,
+    semver: Option<SemverPolicy>,
+    content_sources: Option<ContentSourcePolicy>,
+    static_export_dir: Option<PathBuf>,
+    static_export_on_checkpoint: bool,
+    admin_token: Option<SecretString>,
+    typosquat: Option<TyposquatConfig>,
+    federation: Option<FederationConfig>,
+    throttle: Option<ThrottleConfig>,
 }
 
 impl std::fmt::Debug for Config {
@@ -42,6 +78,8 @@ impl std::fmt::Debug for Config {
             .field("operator_key", &"<redacted>")
             .field("namespaces", &self.namespaces)
             .field("addr", &self.addr)
+            .field("unix_socket", &self.unix_socket)
+            .field("listeners", &self.listeners)
             .field(
                 "data_store",
                 &self.data_store.as_ref().map(|_| "dyn DataStore"),
@@ -57,6 +95,27 @@ impl std::fmt::Debug for Config {
                 "record_policy",
                 &self.record_policy.as_ref().map(|_| "dyn RecordPolicy"),
             )
+            .field("policy_mode", &self.policy_mode)
+            .field("content_scrub", &self.content_scrub)
+            .field("record_retention", &self.record_retention)
+            .field("staged_publish_ttl", &self.staged_publish_ttl)
+            .field("name_lists", &self.name_lists)
+            .field("teams", &self.teams)
+            .field("approvals", &self.approvals)
+            .field("semver", &self.semver)
+            .field("content_sources", &self.content_sources)
+            .field("static_export_dir", &self.static_export_dir)
+            .field(
+                "static_export_on_checkpoint",
+                &self.static_export_on_checkpoint,
+            )
+            .field(
+                "admin_token",
+                &self.admin_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field("typosquat", &self.typosquat)
+            .field("federation", &self.federation)
+            .field("throttle", &self.throttle)
             .finish()
     }
 }
@@ -72,6 +131,8 @@ impl Config {
             operator_key,
             namespaces,
             addr: None,
+            unix_socket: None,
+            listeners: Vec::new(),
             data_store: None,
             content_dir,
             content_base_url: None,
@@ -79,6 +140,21 @@ impl Config {
             checkpoint_interval: None,
             content_policy: None,
             record_policy: None,
+            policy_mode: PolicyMode::default(),
+            content_scrub: None,
+            record_retention: None,
+            staged_publish_ttl: None,
+            name_lists: None,
+            teams: None,
+            approvals: None,
+            semver: None,
+            content_sources: None,
+            static_export_dir: None,
+            static_export_on_checkpoint: false,
+            admin_token: None,
+            typosquat: None,
+            federation: None,
+            throttle: None,
         }
     }
 
@@ -88,9 +164,32 @@ impl Config {
         self
     }
 
+    /// Specify the path of a Unix domain socket for the server to listen on
+    /// instead of an address.
+    ///
+    /// A stale socket at the path is replaced; the socket is removed when the
+    /// server shuts down.
+    ///
+    /// Unix domain sockets are only supported on Unix platforms.
+    pub fn with_unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.unix_socket = Some(path.into());
+        self
+    }
+
+    /// Adds a listener for the server to listen on.
+    ///
+    /// If any listeners are added, the server listens on only those
+    /// listeners; the address and Unix domain socket of the configuration
+    /// are ignored.
+    pub fn with_listener(mut self, listener: ListenerConfig) -> Self {
+        self.listeners.push(listener);
+        self
+    }
+
     /// Specify the content base URL to use.
     ///
-    /// If not set, the content base URL will be derived from the server address.
+    /// If not set, the content base URL will be derived from the address of
+    /// the server's first listener.
     pub fn with_content_base_url(mut self, url: Url) -> Self {
         self.content_base_url = Some(url);
         self
@@ -140,6 +239,144 @@ impl Config {
         self.record_policy = Some(Arc::new(policy));
         self
     }
+
+    /// Sets a policy of reserved and blocked package names.
+    ///
+    /// The policy is checked before the record policy of the server, and its
+    /// lists may be managed through the admin API if an admin token is set.
+    pub fn with_name_lists(mut self, policy: NameListPolicy) -> Self {
+        self.name_lists = Some(policy);
+        self
+    }
+
+    /// Sets a policy of maintainer teams of packages.
+    ///
+    /// The policy is checked before the record policy of the server, and its
+    /// teams may be managed through the admin API if an admin token is set.
+    pub fn with_teams(mut self, policy: TeamPolicy) -> Self {
+        self.teams = Some(policy);
+        self
+    }
+
+    /// Requires records of packages in the namespaces of the given policy
+    /// to be approved by a second key before they are validated.
+    pub fn with_approvals(mut self, policy: ApprovalPolicy) -> Self {
+        self.approvals = Some(policy);
+        self
+    }
+
+    /// Checks the interfaces of new releases against the previous release
+    /// of their package, acting on breaking changes made without a major
+    /// version bump as configured by the given policy.
+    pub fn with_semver_policy(mut self, policy: SemverPolicy) -> Self {
+        self.semver = Some(policy);
+        self
+    }
+
+    /// Allows publishers to declare external sources of the content of
+    /// their records instead of uploading it.
+    ///
+    /// The server fetches the content from the declared sources allowed by
+    /// the given policy and verifies it as though it were uploaded.
+    pub fn with_content_sources(mut self, policy: ContentSourcePolicy) -> Self {
+        self.content_sources = Some(policy);
+        self
+    }
+
+    /// Enables the admin API, authenticating requests with the given bearer
+    /// token.
+    pub fn with_admin_token(mut self, token: SecretString) -> Self {
+        self.admin_token = Some(token);
+        self
+    }
+
+    /// Enables flagging new packages whose names are suspiciously similar to
+    /// the names of existing packages.
+    ///
+    /// Flagged packages are still published; flags are listed for moderation
+    /// through the admin API and reported to clients in package metadata.
+    pub fn with_typosquat_detection(mut self, config: TyposquatConfig) -> Self {
+        self.typosquat = Some(config);
+        self
+    }
+
+    /// Restricts the registries namespaces may be imported from to the
+    /// upstreams of the given federation configuration.
+    ///
+    /// The server refuses to start if its operator log imports a namespace
+    /// from another registry. The federation health of imported namespaces
+    /// is reported through the admin API if an admin token is set.
+    pub fn with_federation(mut self, config: FederationConfig) -> Self {
+        self.federation = Some(config);
+        self
+    }
+
+    /// Sets the mode in which the server's content and record policies operate.
+    ///
+    /// Defaults to [`PolicyMode::Enforce`].
+    pub fn with_policy_mode(mut self, mode: PolicyMode) -> Self {
+        self.policy_mode = mode;
+        self
+    }
+
+    /// Sets how long a staged package record may await a commit before it
+    /// is rejected.
+    ///
+    /// Defaults to 24 hours.
+    pub fn with_staged_publish_ttl(mut self, ttl: Duration) -> Self {
+        self.staged_publish_ttl = Some(ttl);
+        self
+    }
+
+    /// Enables a background job that scrubs stored content for corruption.
+    ///
+    /// Content that no longer matches its digest is quarantined and is no
+    /// longer served.
+    pub fn with_content_scrubbing(mut self, config: ScrubConfig) -> Self {
+        self.content_scrub = Some(config);
+        self
+    }
+
+    /// Enables a background job that removes rejected and abandoned pending
+    /// records once their retention window has passed.
+    ///
+    /// If this is not specified, such records are kept indefinitely. Expired
+    /// records may also be removed on demand through the admin API if an
+    /// admin token is set.
+    pub fn with_record_retention(mut self, config: RetentionConfig) -> Self {
+        self.record_retention = Some(config);
+        self
+    }
+
+    /// Sets the directory the registry is exported to as a static registry.
+    ///
+    /// The registry is exported on demand through the admin API if an admin
+    /// token is set; the directory may then be served by any static host.
+    pub fn with_static_export_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.static_export_dir = Some(dir.into());
+        self
+    }
+
+    /// Exports the registry to the static export directory whenever a
+    /// checkpoint of a new log length is stored.
+    ///
+    /// This allows reads to be served entirely by a static host (e.g. a CDN)
+    /// in front of the export, with the server only accepting publishes. A
+    /// static export directory must be set.
+    pub fn with_static_export_on_checkpoint(mut self) -> Self {
+        self.static_export_on_checkpoint = true;
+        self
+    }
+
+    /// Enables throttling of requests by per-client quotas and a limit on
+    /// concurrent requests.
+    ///
+    /// Throttled requests are answered with a problem details body that
+    /// tells clients when to retry.
+    pub fn with_throttling(mut self, config: ThrottleConfig) -> Self {
+        self.throttle = Some(config);
+        self
+    }
 }
 
 /// Represents the warg registry server.
@@ -166,17 +403,35 @@ impl Server {
     /// serving, call [`InitializedServer::serve`].
     ///
     /// Useful for tests that need full initialization before running.
-    pub async fn initialize(self) -> Result<InitializedServer> {
-        let addr = self
-            .config
-            .addr
-            .unwrap_or_else(|| DEFAULT_BIND_ADDRESS.parse().unwrap());
+    pub async fn initialize(mut self) -> Result<InitializedServer> {
+        if let Some(federation) = &self.config.federation {
+            federation.validate()?;
+            if let Some(namespaces) = &self.config.namespaces {
+                federation.check_imports(namespaces.iter().map(|(n, s)| (n.as_str(), s)))?;
+            }
+        }
 
-        tracing::debug!("binding server to address `{addr}`");
-        let listener = TcpListener::bind(addr)
-            .await
-            .with_context(|| format!("failed to bind to address `{addr}`"))?;
-        let addr = listener.local_addr()?;
+        if self.config.static_export_on_checkpoint && self.config.static_export_dir.is_none() {
+            bail!("exporting on every checkpoint requires a static export directory");
+        }
+
+        let configs = if self.config.listeners.is_empty() {
+            vec![match &self.config.unix_socket {
+                Some(path) => ListenerConfig::unix(path),
+                None => ListenerConfig::tcp(
+                    self.config
+                        .addr
+                        .unwrap_or_else(|| DEFAULT_BIND_ADDRESS.parse().unwrap()),
+                ),
+            }]
+        } else {
+            std::mem::take(&mut self.config.listeners)
+        };
+
+        let mut listeners = Vec::with_capacity(configs.len());
+        for config in &configs {
+            listeners.push(BoundListener::bind(config).await?);
+        }
 
         tracing::debug!(
             "using server configuration: {config:?}",
@@ -197,6 +452,15 @@ impl Server {
         )
         .await?;
 
+        // Previously stored operator records may import from other registries
+        if let Some(federation) = &self.config.federation {
+            let namespaces = core
+                .store()
+                .get_namespaces(&LogId::operator_log::<Sha256>())
+                .await?;
+            federation.check_imports(namespaces.iter().map(|(n, s)| (n.as_str(), s)))?;
+        }
+
         let temp_dir = self.config.content_dir.join("tmp");
         fs::create_dir_all(&temp_dir).with_context(|| {
             format!(
@@ -213,24 +477,116 @@ impl Server {
             )
         })?;
 
+        let docs_dir = self.config.content_dir.join("docs");
+        fs::create_dir_all(&docs_dir).with_context(|| {
+            format!(
+                "failed to create content docs directory `{path}`",
+                path = docs_dir.display()
+            )
+        })?;
+
         let content_base_url = self
             .config
             .content_base_url
-            .unwrap_or_else(|| Url::parse(&format!("http://{addr}")).unwrap());
+            .map_or_else(|| listeners[0].url(), Ok)?;
+
+        let scrub_handle = self.config.content_scrub.map(|config| {
+            tracing::info!("scrubbing content every {:?}", config.interval());
+            ContentScrubber::new(config, &self.config.content_dir).start()
+        });
+
+        let retention_handle = self.config.record_retention.clone().map(|config| {
+            tracing::info!(
+                "removing rejected and pending records older than {:?} every {:?}",
+                config.retention(),
+                config.interval()
+            );
+            RecordRetention::new(config, core.clone()).start()
+        });
+
+        let exporter = self.config.static_export_dir.map(|dir| {
+            Arc::new(StaticExporter::new(
+                core.clone(),
+                &self.config.content_dir,
+                dir,
+            ))
+        });
+
+        let export_handle = exporter
+            .as_ref()
+            .filter(|_| self.config.static_export_on_checkpoint)
+            .map(|exporter| {
+                tracing::info!(
+                    "exporting a static registry to `{dir}` on every checkpoint",
+                    dir = exporter.output_dir().display()
+                );
+                exporter.clone().start()
+            });
+
+        let admin = self.config.admin_token.map(|token| {
+            let mut admin = api::admin::Config::new(token, core.clone());
+            if let Some(policy) = &self.config.name_lists {
+                admin = admin.with_name_lists(policy.clone());
+            }
+            if let Some(policy) = &self.config.teams {
+                admin = admin.with_teams(policy.clone());
+            }
+            if let Some(federation) = self.config.federation {
+                admin = admin.with_federation(federation);
+            }
+            if let Some(retention) = self.config.record_retention {
+                admin = admin.with_retention(retention);
+            }
+            if let Some(exporter) = exporter {
+                admin = admin.with_static_exporter(exporter);
+            }
+            admin
+        });
+
+        let record_policy = match (self.config.name_lists, self.config.teams) {
+            (None, None) => self.config.record_policy,
+            (names, teams) => {
+                let mut policies = RecordPolicyCollection::new();
+                if let Some(names) = names {
+                    policies.push(names);
+                }
+                if let Some(teams) = teams {
+                    policies.push(teams);
+                }
+                if let Some(policy) = self.config.record_policy {
+                    policies.push(policy);
+                }
+                Some(Arc::new(policies) as Arc<dyn RecordPolicy>)
+            }
+        };
 
         let router = create_router(
             content_base_url,
             core,
             temp_dir,
             files_dir,
+            docs_dir,
             self.config.content_policy,
-            self.config.record_policy,
+            record_policy,
+            self.config.policy_mode,
+            self.config
+                .staged_publish_ttl
+                .unwrap_or(DEFAULT_STAGED_PUBLISH_TTL),
+            self.config.typosquat,
+            self.config.approvals,
+            self.config.semver,
+            self.config.content_sources,
+            admin,
+            self.config.throttle,
         );
 
         Ok(InitializedServer {
-            listener,
+            listeners,
             router,
             core_handle,
+            scrub_handle,
+            retention_handle,
+            export_handle,
             shutdown: self.config.shutdown,
         })
     }
@@ -238,34 +594,95 @@ impl Server {
 
 /// Represents an initialized warg registry server.
 pub struct InitializedServer {
-    listener: TcpListener,
+    listeners: Vec<BoundListener>,
     router: Router,
     core_handle: JoinHandle<()>,
+    scrub_handle: Option<JoinHandle<()>>,
+    retention_handle: Option<JoinHandle<()>>,
+    export_handle: Option<JoinHandle<()>>,
     shutdown: Option<ShutdownFut>,
 }
 
 impl InitializedServer {
-    /// Returns the listening address of the server. If a random listening
-    /// port was requested (i.e. `:0`), this returns the actual bound port.
+    /// Returns the listening address of the server's first TCP listener. If
+    /// a random listening port was requested (i.e. `:0`), this returns the
+    /// actual bound port.
+    ///
+    /// Returns an error if the server only listens on Unix domain sockets.
     pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
-        self.listener.local_addr()
+        self.listeners
+            .iter()
+            .find_map(BoundListener::local_addr)
+            .unwrap_or_else(|| {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "server is only listening on Unix domain sockets",
+                ))
+            })
+    }
+
+    /// Returns the URL that clients use to reach the server's first
+    /// listener, such as `http://127.0.0.1:8090/` or, for a listener on a
+    /// Unix domain socket, `http+unix://%2Ftmp%2Fwarg.sock/`.
+    pub fn url(&self) -> Result<Url> {
+        self.listeners[0].url()
+    }
+
+    /// Returns the URLs that clients use to reach each of the server's
+    /// listeners, in the order the listeners were configured.
+    pub fn urls(&self) -> Result<Vec<Url>> {
+        self.listeners.iter().map(BoundListener::url).collect()
+    }
+
+    /// Returns the router of the server's services.
+    ///
+    /// The router may be used to serve requests in-process when the server
+    /// is embedded, in addition to the requests served by
+    /// [`InitializedServer::serve`]. The server does not finish shutting
+    /// down until all clones of the router are dropped.
+    pub fn router(&self) -> Router {
+        self.router.clone()
     }
 
     /// Serves the server's services. On server shutdown, awaits completion of
     /// background task(s) before returning.
     pub async fn serve(self) -> Result<()> {
-        let addr = self.local_addr()?;
-
-        let server = axum::serve::serve(self.listener, self.router.into_make_service());
-
-        tracing::info!("listening on {addr}");
+        for url in self.urls()? {
+            tracing::info!("listening on {url}");
+        }
 
+        let token = CancellationToken::new();
         if let Some(shutdown) = self.shutdown {
             tracing::debug!("server is running with a shutdown signal");
-            server.with_graceful_shutdown(shutdown).await?;
+            let token = token.clone();
+            tokio::spawn(async move {
+                shutdown.await;
+                token.cancel();
+            });
         } else {
             tracing::debug!("server is running without a shutdown signal");
-            server.await?;
+        }
+
+        futures::future::try_join_all(
+            self.listeners
+                .into_iter()
+                .map(|listener| listener.serve(self.router.clone(), token.clone())),
+        )
+        .await?;
+
+        // The core service stops once the router's reference to it is dropped
+        drop(self.router);
+
+        if let Some(scrub_handle) = self.scrub_handle {
+            scrub_handle.abort();
+        }
+
+        if let Some(retention_handle) = self.retention_handle {
+            retention_handle.abort();
+        }
+
+        if let Some(export_handle) = self.export_handle {
+            export_handle.abort();
         }
 
         tracing::info!("waiting for core service to stop");
@@ -0,0 +1,482 @@
+//! Listeners of the registry server.
+//!
+//! A server may listen on several TCP addresses and Unix domain sockets at
+//! once, such as a plain TCP address or Unix domain socket for internal
+//! clients and a TLS address for external clients. Listeners may be
+//! configured from a TOML file of `[[listeners]]` tables:
+//!
+//! ```toml
+//! [[listeners]]
+//! unix_socket = "/run/warg/registry.sock"
+//!
+//! [[listeners]]
+//! addr = "0.0.0.0:8443"
+//! tls = { certificate = "/etc/warg/cert.pem", key = "/etc/warg/key.pem" }
+//! ```
+use anyhow::{bail, Context, Result};
+use axum::{body::Body, extract::ConnectInfo, Router};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+};
+use tokio_native_tls::TlsAcceptor;
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+use url::Url;
+
+/// A TLS protocol version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TlsVersion {
+    /// TLS 1.0.
+    #[serde(rename = "1.0")]
+    Tls10,
+    /// TLS 1.1.
+    #[serde(rename = "1.1")]
+    Tls11,
+    /// TLS 1.2.
+    #[serde(rename = "1.2")]
+    Tls12,
+}
+
+impl From<TlsVersion> for native_tls::Protocol {
+    fn from(version: TlsVersion) -> Self {
+        match version {
+            TlsVersion::Tls10 => Self::Tlsv10,
+            TlsVersion::Tls11 => Self::Tlsv11,
+            TlsVersion::Tls12 => Self::Tlsv12,
+        }
+    }
+}
+
+/// The TLS settings of a listener.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// The path of the PEM-encoded certificate chain of the listener.
+    pub certificate: PathBuf,
+    /// The path of the PEM-encoded PKCS #8 private key of the certificate.
+    pub key: PathBuf,
+    /// The minimum TLS version accepted by the listener; defaults to TLS 1.2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<TlsVersion>,
+}
+
+impl TlsConfig {
+    /// Creates TLS settings with the given certificate chain and private key
+    /// paths.
+    pub fn new(certificate: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        Self {
+            certificate: certificate.into(),
+            key: key.into(),
+            min_version: None,
+        }
+    }
+
+    /// Sets the minimum TLS version accepted by the listener.
+    pub fn with_min_version(mut self, version: TlsVersion) -> Self {
+        self.min_version = Some(version);
+        self
+    }
+
+    /// Creates the TLS acceptor of the settings.
+    fn acceptor(&self) -> Result<TlsAcceptor> {
+        let certificate = fs::read(&self.certificate).with_context(|| {
+            format!(
+                "failed to read TLS certificate `{path}`",
+                path = self.certificate.display()
+            )
+        })?;
+        let key = fs::read(&self.key).with_context(|| {
+            format!(
+                "failed to read TLS private key `{path}`",
+                path = self.key.display()
+            )
+        })?;
+        let identity = native_tls::Identity::from_pkcs8(&certificate, &key).with_context(|| {
+            format!(
+                "failed to load TLS certificate `{path}`",
+                path = self.certificate.display()
+            )
+        })?;
+
+        let acceptor = native_tls::TlsAcceptor::builder(identity)
+            .min_protocol_version(Some(self.min_version.unwrap_or(TlsVersion::Tls12).into()))
+            .build()
+            .context("failed to create TLS acceptor")?;
+        Ok(acceptor.into())
+    }
+}
+
+/// The configuration of a listener of the server.
+///
+/// A listener listens on either a TCP address or a Unix domain socket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListenerConfig {
+    /// The TCP address to listen on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addr: Option<SocketAddr>,
+    /// The path of the Unix domain socket to listen on.
+    ///
+    /// A stale socket at the path is replaced; the socket is removed when
+    /// the server shuts down.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix_socket: Option<PathBuf>,
+    /// The TLS settings of a TCP listener; if not set, the listener serves
+    /// plain HTTP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+}
+
+impl ListenerConfig {
+    /// Creates the configuration of a listener on the given TCP address.
+    pub fn tcp(addr: impl Into<SocketAddr>) -> Self {
+        Self {
+            addr: Some(addr.into()),
+            unix_socket: None,
+            tls: None,
+        }
+    }
+
+    /// Creates the configuration of a listener on the Unix domain socket at
+    /// the given path.
+    ///
+    /// Unix domain sockets are only supported on Unix platforms.
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self {
+            addr: None,
+            unix_socket: Some(path.into()),
+            tls: None,
+        }
+    }
+
+    /// Serves TLS on the listener with the given settings.
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Validates the configuration.
+    pub fn validate(&self) -> Result<()> {
+        match (&self.addr, &self.unix_socket) {
+            (Some(_), Some(_)) => {
+                bail!("a listener cannot have both an address and a Unix domain socket")
+            }
+            (None, None) => bail!("a listener must have either an address or a Unix domain socket"),
+            (None, Some(path)) if self.tls.is_some() => bail!(
+                "TLS is not supported by the listener on Unix domain socket `{path}`",
+                path = path.display()
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The configuration of the listeners of the server.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListenersConfig {
+    /// The listeners of the server.
+    pub listeners: Vec<ListenerConfig>,
+}
+
+impl ListenersConfig {
+    /// Validates the configuration.
+    pub fn validate(&self) -> Result<()> {
+        if self.listeners.is_empty() {
+            bail!("at least one listener must be configured");
+        }
+
+        for listener in &self.listeners {
+            listener.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The socket of a bound listener.
+enum Socket {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+/// A listener of the server that is bound to its socket.
+pub(crate) struct BoundListener {
+    socket: Socket,
+    tls: Option<TlsAcceptor>,
+}
+
+impl BoundListener {
+    /// Binds the listener of the given configuration.
+    pub async fn bind(config: &ListenerConfig) -> Result<Self> {
+        config.validate()?;
+
+        let tls = config.tls.as_ref().map(TlsConfig::acceptor).transpose()?;
+        let socket = match (&config.addr, &config.unix_socket) {
+            (Some(addr), _) => {
+                tracing::debug!("binding server to address `{addr}`");
+                Socket::Tcp(
+                    TcpListener::bind(addr)
+                        .await
+                        .with_context(|| format!("failed to bind to address `{addr}`"))?,
+                )
+            }
+            (None, Some(path)) => bind_unix(path)?,
+            (None, None) => unreachable!("listener configuration was validated"),
+        };
+
+        Ok(Self { socket, tls })
+    }
+
+    /// Returns the listening address of the listener, or `None` if it
+    /// listens on a Unix domain socket.
+    pub fn local_addr(&self) -> Option<std::io::Result<SocketAddr>> {
+        match &self.socket {
+            Socket::Tcp(listener) => Some(listener.local_addr()),
+            #[cfg(unix)]
+            Socket::Unix(..) => None,
+        }
+    }
+
+    /// Gets the URL that clients use to reach the listener.
+    pub fn url(&self) -> Result<Url> {
+        match &self.socket {
+            Socket::Tcp(listener) => Ok(Url::parse(&format!(
+                "{scheme}://{addr}",
+                scheme = if self.tls.is_some() { "https" } else { "http" },
+                addr = listener.local_addr()?
+            ))?),
+            #[cfg(unix)]
+            Socket::Unix(_, path) => {
+                let path = path.to_str().with_context(|| {
+                    format!(
+                        "Unix domain socket path `{path}` is not UTF-8",
+                        path = path.display()
+                    )
+                })?;
+                Ok(Url::parse(&format!(
+                    "http+unix://{host}/",
+                    host = percent_encoding::utf8_percent_encode(
+                        path,
+                        percent_encoding::NON_ALPHANUMERIC
+                    )
+                ))?)
+            }
+        }
+    }
+
+    /// Serves the given router on the listener until the shutdown token is
+    /// cancelled.
+    pub async fn serve(self, router: Router, shutdown: CancellationToken) -> Result<()> {
+        match (self.socket, self.tls) {
+            (Socket::Tcp(listener), None) => {
+                axum::serve::serve(
+                    listener,
+                    router.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await?;
+            }
+            (Socket::Tcp(listener), Some(acceptor)) => {
+                serve_tls(listener, acceptor, router, shutdown).await;
+            }
+            #[cfg(unix)]
+            (Socket::Unix(listener, path), _) => {
+                serve_unix(listener, router, shutdown).await;
+                if let Err(e) = fs::remove_file(&path) {
+                    tracing::warn!(
+                        "failed to remove Unix domain socket `{path}`: {e}",
+                        path = path.display()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Binds a listener to the Unix domain socket at the given path.
+#[cfg(unix)]
+fn bind_unix(path: &Path) -> Result<Socket> {
+    use std::os::unix::fs::FileTypeExt;
+
+    tracing::debug!(
+        "binding server to Unix domain socket `{path}`",
+        path = path.display()
+    );
+
+    // A socket left behind by a server that did not shut down cleanly
+    // would otherwise prevent binding
+    if fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        fs::remove_file(path).with_context(|| {
+            format!(
+                "failed to remove stale Unix domain socket `{path}`",
+                path = path.display()
+            )
+        })?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(path).with_context(|| {
+        format!(
+            "failed to bind to Unix domain socket `{path}`",
+            path = path.display()
+        )
+    })?;
+    Ok(Socket::Unix(listener, path.to_path_buf()))
+}
+
+/// Binds a listener to the Unix domain socket at the given path.
+#[cfg(not(unix))]
+fn bind_unix(path: &Path) -> Result<Socket> {
+    bail!(
+        "cannot bind to Unix domain socket `{path}`: Unix domain sockets are not supported on this platform",
+        path = path.display()
+    )
+}
+
+/// Serves the given router over TLS on a TCP listener until the shutdown
+/// token is cancelled.
+///
+/// Connections accepted before the shutdown are served to completion in the
+/// background.
+async fn serve_tls(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    router: Router,
+    shutdown: CancellationToken,
+) {
+    loop {
+        let (stream, remote) = tokio::select! {
+            res = listener.accept() => match res {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::error!("failed to accept TCP connection: {e}");
+                    continue;
+                }
+            },
+            _ = shutdown.cancelled() => return,
+        };
+
+        let acceptor = acceptor.clone();
+        let router = router.clone();
+        tokio::spawn(async move {
+            match acceptor.accept(stream).await {
+                Ok(stream) => serve_connection(stream, router, Some(remote)).await,
+                Err(e) => tracing::debug!("TLS handshake with `{remote}` failed: {e}"),
+            }
+        });
+    }
+}
+
+/// Serves the given router on a Unix domain socket until the shutdown token
+/// is cancelled.
+///
+/// Connections accepted before the shutdown are served to completion in the
+/// background.
+#[cfg(unix)]
+async fn serve_unix(
+    listener: tokio::net::UnixListener,
+    router: Router,
+    shutdown: CancellationToken,
+) {
+    loop {
+        let stream = tokio::select! {
+            res = listener.accept() => match res {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::error!("failed to accept Unix domain socket connection: {e}");
+                    continue;
+                }
+            },
+            _ = shutdown.cancelled() => return,
+        };
+
+        tokio::spawn(serve_connection(stream, router.clone(), None));
+    }
+}
+
+/// Serves the given router on an accepted connection.
+///
+/// The address of the remote peer, if any, is made available to the router
+/// as [`ConnectInfo`].
+async fn serve_connection<S>(stream: S, router: Router, remote: Option<SocketAddr>)
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let service = hyper::service::service_fn(move |mut request: hyper::Request<_>| {
+        if let Some(remote) = remote {
+            request.extensions_mut().insert(ConnectInfo(remote));
+        }
+        router.clone().oneshot(request.map(Body::new))
+    });
+
+    if let Err(e) = hyper::server::conn::http1::Builder::new()
+        .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+        .await
+    {
+        tracing::debug!("failed to serve connection: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_listeners() {
+        let config: ListenersConfig = toml::from_str(
+            r#"
+            [[listeners]]
+            unix_socket = "/run/warg/registry.sock"
+
+            [[listeners]]
+            addr = "0.0.0.0:8443"
+            tls = { certificate = "cert.pem", key = "key.pem", min_version = "1.2" }
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        assert_eq!(
+            config.listeners,
+            [
+                ListenerConfig::unix("/run/warg/registry.sock"),
+                ListenerConfig::tcp(([0, 0, 0, 0], 8443)).with_tls(
+                    TlsConfig::new("cert.pem", "key.pem").with_min_version(TlsVersion::Tls12)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_listeners() {
+        assert!(ListenersConfig::default().validate().is_err());
+        assert!(ListenerConfig {
+            addr: None,
+            unix_socket: None,
+            tls: None,
+        }
+        .validate()
+        .is_err());
+        assert!(ListenerConfig {
+            addr: Some(([127, 0, 0, 1], 8090).into()),
+            unix_socket: Some("registry.sock".into()),
+            tls: None,
+        }
+        .validate()
+        .is_err());
+        assert!(ListenerConfig::unix("registry.sock")
+            .with_tls(TlsConfig::new("cert.pem", "key.pem"))
+            .validate()
+            .is_err());
+    }
+}
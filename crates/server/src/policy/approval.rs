@@ -0,0 +1,87 @@
+//! Module for the dual-control approval of package records.
+
+use anyhow::{bail, Result};
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use warg_crypto::signing::KeyID;
+use warg_protocol::registry::PackageName;
+
+/// The approval requirements of the records of a namespace.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamespaceApproval {
+    /// The keys that may approve records of any package of the namespace.
+    ///
+    /// Keys of a package log with the `release` permission may also
+    /// approve records of the package.
+    pub approvers: IndexSet<KeyID>,
+}
+
+/// A policy that holds records of packages in the configured namespaces
+/// until they are approved.
+///
+/// A record awaiting approval is not validated until a key other than the
+/// key that signed the record approves it. Records of other namespaces are
+/// unaffected.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ApprovalPolicy {
+    /// The approval requirements of each namespace.
+    pub namespaces: IndexMap<String, NamespaceApproval>,
+}
+
+impl ApprovalPolicy {
+    /// Validates the namespaces of the policy.
+    pub fn validate(&self) -> Result<()> {
+        for namespace in self.namespaces.keys() {
+            if !PackageName::is_valid_namespace(namespace) {
+                bail!("namespace `{namespace}` is not a valid kebab-cased string");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Determines if records of the given package require approval.
+    pub fn requires_approval(&self, name: &PackageName) -> bool {
+        self.namespaces.contains_key(name.namespace())
+    }
+
+    /// Determines if the given key is a configured approver of records of
+    /// the given package.
+    pub fn is_approver(&self, name: &PackageName, key: &KeyID) -> bool {
+        self.namespaces
+            .get(name.namespace())
+            .is_some_and(|approval| approval.approvers.contains(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_requires_approval_for_namespaces() -> Result<()> {
+        let approver = KeyID::from("approver".to_string());
+        let policy: ApprovalPolicy = toml::from_str(
+            r#"
+            [example]
+            approvers = ["approver"]
+            "#,
+        )?;
+        policy.validate()?;
+
+        let name = PackageName::new("example:http")?;
+        assert!(policy.requires_approval(&name));
+        assert!(policy.is_approver(&name, &approver));
+        assert!(!policy.is_approver(&name, &KeyID::from("other".to_string())));
+
+        let other = PackageName::new("other:http")?;
+        assert!(!policy.requires_approval(&other));
+        assert!(!policy.is_approver(&other, &approver));
+
+        let invalid: ApprovalPolicy = toml::from_str("[\"Not Kebab\"]")?;
+        assert!(invalid.validate().is_err());
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 //! Module for server content policy implementations.
 use thiserror::Error;
 use warg_crypto::hash::AnyHash;
+use warg_protocol::{package::ContentType, registry::PackageName};
 
 mod wasm;
 
@@ -21,14 +22,17 @@ pub type ContentPolicyResult<T> = Result<T, ContentPolicyError>;
 pub trait ContentPolicy: Send + Sync {
     /// Creates a new stream policy for the given digest.
     ///
-    /// The digest is provided so that a policy can make decisions
-    /// based on the content's digest before any content is received.
+    /// The name of the package, the digest and the content type declared in
+    /// the package record are provided so that a policy can make decisions
+    /// based on them before any content is received.
     ///
     /// Upon success, returns a content stream policy that can be used
     /// to check the content as it is received.
     fn new_stream_policy(
         &self,
+        name: &PackageName,
         digest: &AnyHash,
+        content_type: ContentType,
     ) -> ContentPolicyResult<Box<dyn ContentStreamPolicy>>;
 }
 
@@ -71,13 +75,15 @@ impl ContentPolicyCollection {
 impl ContentPolicy for ContentPolicyCollection {
     fn new_stream_policy(
         &self,
+        name: &PackageName,
         digest: &AnyHash,
+        content_type: ContentType,
     ) -> ContentPolicyResult<Box<dyn ContentStreamPolicy>> {
         Ok(Box::new(ContentStreamPolicyCollection {
             policies: self
                 .policies
                 .iter()
-                .map(|p| p.new_stream_policy(digest))
+                .map(|p| p.new_stream_policy(name, digest, content_type))
                 .collect::<ContentPolicyResult<_>>()?,
         }))
     }
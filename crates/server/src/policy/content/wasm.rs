@@ -1,14 +1,267 @@
 use super::{ContentPolicy, ContentPolicyError, ContentPolicyResult, ContentStreamPolicy};
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use warg_crypto::hash::AnyHash;
+use warg_protocol::{package::ContentType, registry::PackageName};
 use wasmparser::{
-    Chunk, Encoding, FuncValidatorAllocations, Parser, ValidPayload, Validator, WasmFeatures,
+    Chunk, Encoding, FuncValidatorAllocations, Parser, Payload, ValidPayload, Validator,
+    WasmFeatures,
 };
 
-/// A policy that ensures all uploaded content is valid WebAssembly.
+/// The tier of validation applied to WebAssembly content.
+///
+/// Each tier includes the checks of the tiers before it.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum WasmValidationTier {
+    /// Only the header (magic number and version) of the content is checked.
+    Basic,
+    /// The content is fully validated.
+    #[default]
+    Full,
+    /// The content is fully validated and must be a component, conforming
+    /// to the component model.
+    Component,
+}
+
+impl WasmValidationTier {
+    /// Gets all validation tiers, from least to most strict.
+    pub fn all() -> [Self; 3] {
+        [Self::Basic, Self::Full, Self::Component]
+    }
+
+    /// Gets the name of the tier.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Basic => "basic",
+            Self::Full => "full",
+            Self::Component => "component",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for WasmValidationTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The code of the reason WebAssembly content was rejected.
+///
+/// The code is appended to the rejection reason in brackets (e.g.
+/// `[invalid-wasm]`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WasmRejectionCode {
+    /// The content does not have a WebAssembly header.
+    InvalidHeader,
+    /// The content is not valid WebAssembly.
+    InvalidWasm,
+    /// The content is a module, which is not allowed.
+    ModuleNotAllowed,
+    /// The content is a component, which is not allowed.
+    ComponentNotAllowed,
+    /// The content is not a component, as required by the component tier.
+    NotComponent,
+    /// The content exceeds a resource limit.
+    ResourceLimit,
+}
+
+impl WasmRejectionCode {
+    /// Gets all rejection codes.
+    pub fn all() -> [Self; 6] {
+        [
+            Self::InvalidHeader,
+            Self::InvalidWasm,
+            Self::ModuleNotAllowed,
+            Self::ComponentNotAllowed,
+            Self::NotComponent,
+            Self::ResourceLimit,
+        ]
+    }
+
+    /// Gets the string form of the code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidHeader => "invalid-header",
+            Self::InvalidWasm => "invalid-wasm",
+            Self::ModuleNotAllowed => "module-not-allowed",
+            Self::ComponentNotAllowed => "component-not-allowed",
+            Self::NotComponent => "not-component",
+            Self::ResourceLimit => "resource-limit",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for WasmRejectionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Limits on the resources declared by WebAssembly content.
+///
+/// The limits apply to every core module of the content, including modules
+/// nested in components. Limits that are not set are not checked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResourceLimits {
+    /// The maximum number of functions defined by the content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_functions: Option<u64>,
+    /// The maximum number of globals defined by the content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_globals: Option<u64>,
+    /// The maximum total size, in bytes, of the data segments of the content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_data_size: Option<u64>,
+    /// The maximum initial size, in pages, of each memory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_memory_pages: Option<u64>,
+    /// The maximum initial number of elements of each table.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_table_elements: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Determines if no limits are set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The validation applied to WebAssembly content.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WasmValidation {
+    /// The tier of validation.
+    pub tier: WasmValidationTier,
+    /// The resource limits the content is linted against.
+    ///
+    /// Resource limits are not checked at the basic tier.
+    #[serde(skip_serializing_if = "ResourceLimits::is_empty")]
+    pub limits: ResourceLimits,
+}
+
+/// The validation applied to WebAssembly content, per namespace.
+///
+/// Content of packages in namespaces that are not configured is validated
+/// with the default validation.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WasmValidationConfig {
+    /// The validation of namespaces that are not configured.
+    pub default: WasmValidation,
+    /// The validation of each namespace.
+    pub namespaces: IndexMap<String, WasmValidation>,
+}
+
+impl WasmValidationConfig {
+    /// Validates the configuration.
+    pub fn validate(&self) -> Result<()> {
+        let check = |namespace: Option<&str>, validation: &WasmValidation| {
+            if validation.tier == WasmValidationTier::Basic && !validation.limits.is_empty() {
+                match namespace {
+                    Some(namespace) => bail!(
+                        "resource limits of namespace `{namespace}` require the `full` or `component` validation tier"
+                    ),
+                    None => bail!("resource limits require the `full` or `component` validation tier"),
+                }
+            }
+
+            Ok(())
+        };
+
+        check(None, &self.default)?;
+        for (namespace, validation) in &self.namespaces {
+            if !PackageName::is_valid_namespace(namespace) {
+                bail!("namespace `{namespace}` is not a valid kebab-cased string");
+            }
+
+            check(Some(namespace), validation)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the validation of content of the given package.
+    pub fn validation(&self, name: &PackageName) -> &WasmValidation {
+        self.namespaces
+            .get(name.namespace())
+            .unwrap_or(&self.default)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Metrics {
+    checked: [AtomicU64; 3],
+    rejected: [AtomicU64; 3],
+    rejections: [AtomicU64; 6],
+}
+
+/// The metrics of the content checked by a [`WasmContentPolicy`].
+///
+/// Clones share the same metrics, so a handle obtained from the policy
+/// remains current after the policy is given to the server.
+#[derive(Debug, Default, Clone)]
+pub struct WasmValidationMetrics(Arc<Metrics>);
+
+impl WasmValidationMetrics {
+    /// Gets the number of contents checked at the given tier.
+    pub fn checked(&self, tier: WasmValidationTier) -> u64 {
+        self.0.checked[tier.index()].load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of contents rejected at the given tier.
+    pub fn rejected(&self, tier: WasmValidationTier) -> u64 {
+        self.0.rejected[tier.index()].load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of contents rejected with the given code.
+    pub fn rejections(&self, code: WasmRejectionCode) -> u64 {
+        self.0.rejections[code.index()].load(Ordering::Relaxed)
+    }
+
+    fn record(&self, tier: WasmValidationTier, code: Option<WasmRejectionCode>) {
+        self.0.checked[tier.index()].fetch_add(1, Ordering::Relaxed);
+        if let Some(code) = code {
+            self.0.rejected[tier.index()].fetch_add(1, Ordering::Relaxed);
+            self.0.rejections[code.index()].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A policy that ensures all uploaded content declared as WebAssembly is
+/// valid WebAssembly.
+///
+/// Content declared as a WIT package must be a component; content of other
+/// types (e.g. attestations and documentation archives) is not checked.
+///
+/// Content is validated at the [tier](WasmValidationTier) configured for
+/// the namespace of its package; by default, content is fully validated.
 pub struct WasmContentPolicy {
     allow_modules: bool,
     allow_components: bool,
     features: WasmFeatures,
+    validation: WasmValidationConfig,
+    metrics: WasmValidationMetrics,
 }
 
 impl WasmContentPolicy {
@@ -36,6 +289,17 @@ impl WasmContentPolicy {
         self.features = features;
         self
     }
+
+    /// Sets the validation applied to content, per namespace.
+    pub fn with_validation(mut self, validation: WasmValidationConfig) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Gets the metrics of the content checked by the policy.
+    pub fn metrics(&self) -> WasmValidationMetrics {
+        self.metrics.clone()
+    }
 }
 
 impl Default for WasmContentPolicy {
@@ -47,6 +311,8 @@ impl Default for WasmContentPolicy {
                 component_model: true,
                 ..Default::default()
             },
+            validation: Default::default(),
+            metrics: Default::default(),
         }
     }
 }
@@ -54,21 +320,123 @@ impl Default for WasmContentPolicy {
 impl ContentPolicy for WasmContentPolicy {
     fn new_stream_policy(
         &self,
+        name: &PackageName,
         _digest: &AnyHash,
+        content_type: ContentType,
     ) -> ContentPolicyResult<Box<dyn ContentStreamPolicy>> {
+        let validation = self.validation.validation(name);
+        let (allow_modules, allow_components) = match content_type {
+            ContentType::Component => (self.allow_modules, self.allow_components),
+            ContentType::WitPackage => (false, self.allow_components),
+            _ => return Ok(Box::new(UncheckedContentStreamPolicy)),
+        };
+
         Ok(Box::new(WasmContentStreamPolicy {
+            tier: validation.tier,
+            limits: validation.limits,
+            usage: ResourceUsage::default(),
+            metrics: self.metrics.clone(),
+            done: false,
             buffer: Vec::new(),
             parser: Parser::new(0),
             stack: Vec::new(),
             validator: wasmparser::Validator::new_with_features(self.features),
             allocs: FuncValidatorAllocations::default(),
-            allow_modules: self.allow_modules,
-            allow_components: self.allow_components,
+            allow_modules,
+            allow_components,
         }))
     }
 }
 
+/// A stream policy for content that is not WebAssembly.
+struct UncheckedContentStreamPolicy;
+
+impl ContentStreamPolicy for UncheckedContentStreamPolicy {
+    fn check(&mut self, _bytes: &[u8]) -> ContentPolicyResult<()> {
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> ContentPolicyResult<()> {
+        Ok(())
+    }
+}
+
+/// A rejection of content by a WebAssembly stream policy.
+type Rejection = (WasmRejectionCode, String);
+
+fn invalid(e: impl fmt::Display) -> Rejection {
+    (
+        WasmRejectionCode::InvalidWasm,
+        format!("content is not valid WebAssembly: {e}"),
+    )
+}
+
+/// The resources declared by WebAssembly content.
+#[derive(Default)]
+struct ResourceUsage {
+    functions: u64,
+    globals: u64,
+    data_size: u64,
+}
+
+impl ResourceUsage {
+    /// Updates the usage from a validated payload, checking it against the
+    /// given limits.
+    fn update(&mut self, payload: &Payload, limits: &ResourceLimits) -> Result<(), Rejection> {
+        fn check(resource: &str, value: u64, limit: Option<u64>) -> Result<(), Rejection> {
+            match limit {
+                Some(limit) if value > limit => Err((
+                    WasmRejectionCode::ResourceLimit,
+                    format!("content exceeds the limit of {limit} {resource} with {value}"),
+                )),
+                _ => Ok(()),
+            }
+        }
+
+        match payload {
+            Payload::FunctionSection(reader) => {
+                self.functions += u64::from(reader.count());
+                check("functions", self.functions, limits.max_functions)
+            }
+            Payload::GlobalSection(reader) => {
+                self.globals += u64::from(reader.count());
+                check("globals", self.globals, limits.max_globals)
+            }
+            Payload::DataSection(reader) => {
+                for data in reader.clone() {
+                    self.data_size += data.map_err(invalid)?.data.len() as u64;
+                }
+                check("bytes of data", self.data_size, limits.max_data_size)
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader.clone() {
+                    let pages = memory.map_err(invalid)?.initial;
+                    check("memory pages", pages, limits.max_memory_pages)?;
+                }
+                Ok(())
+            }
+            Payload::TableSection(reader) => {
+                for table in reader.clone() {
+                    let elements = table.map_err(invalid)?.ty.initial;
+                    check(
+                        "table elements",
+                        u64::from(elements),
+                        limits.max_table_elements,
+                    )?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 struct WasmContentStreamPolicy {
+    tier: WasmValidationTier,
+    limits: ResourceLimits,
+    usage: ResourceUsage,
+    metrics: WasmValidationMetrics,
+    done: bool,
     buffer: Vec<u8>,
     parser: Parser,
     stack: Vec<Parser>,
@@ -79,7 +447,53 @@ struct WasmContentStreamPolicy {
 }
 
 impl WasmContentStreamPolicy {
-    fn process(&mut self, bytes: &[u8], eof: bool) -> ContentPolicyResult<()> {
+    /// Checks the encoding of the content against the allowed encodings.
+    fn check_encoding(&self, encoding: Encoding) -> Result<(), Rejection> {
+        match encoding {
+            Encoding::Module if !self.allow_modules => Err((
+                WasmRejectionCode::ModuleNotAllowed,
+                "WebAssembly modules are not allowed".to_string(),
+            )),
+            Encoding::Component if !self.allow_components => Err((
+                WasmRejectionCode::ComponentNotAllowed,
+                "WebAssembly components are not allowed".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks only the header of the content, for the basic tier.
+    fn process_header(&mut self, bytes: &[u8], eof: bool) -> Result<(), Rejection> {
+        const HEADER_LEN: usize = 8;
+
+        let needed = HEADER_LEN - self.buffer.len();
+        self.buffer
+            .extend_from_slice(&bytes[..needed.min(bytes.len())]);
+        if self.buffer.len() < HEADER_LEN {
+            if eof {
+                return Err((
+                    WasmRejectionCode::InvalidHeader,
+                    "content does not have a WebAssembly header".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+
+        let encoding = if Parser::is_core_wasm(&self.buffer) {
+            Encoding::Module
+        } else if Parser::is_component(&self.buffer) {
+            Encoding::Component
+        } else {
+            return Err((
+                WasmRejectionCode::InvalidHeader,
+                "content does not have a WebAssembly header".to_string(),
+            ));
+        };
+
+        self.check_encoding(encoding)
+    }
+
+    fn process(&mut self, bytes: &[u8], eof: bool) -> Result<(), Rejection> {
         // Extend the buffer if we need to; otherwise, parse the given slice
         let buf = if !self.buffer.is_empty() {
             self.buffer.extend(bytes);
@@ -90,54 +504,44 @@ impl WasmContentStreamPolicy {
 
         let mut offset = 0;
         loop {
-            let (payload, consumed) = match self.parser.parse(&buf[offset..], eof).map_err(|e| {
-                ContentPolicyError::Rejection(format!("content is not valid WebAssembly: {e}"))
-            })? {
-                Chunk::NeedMoreData(_) => {
-                    // If the buffer is empty and there's still data in the given slice,
-                    // copy the remaining data to the buffer.
-                    // If there's still data remaining in the buffer, copy it to the
-                    // beginning of the buffer and truncate it.
-                    // Otherwise, clear the buffer.
-                    if self.buffer.is_empty() && offset < bytes.len() {
-                        self.buffer.extend_from_slice(&bytes[offset..]);
-                    } else if offset < self.buffer.len() {
-                        self.buffer.copy_within(offset.., 0);
-                        self.buffer.truncate(self.buffer.len() - offset);
-                    } else {
-                        self.buffer.clear();
+            let (payload, consumed) =
+                match self.parser.parse(&buf[offset..], eof).map_err(invalid)? {
+                    Chunk::NeedMoreData(_) => {
+                        // If the buffer is empty and there's still data in the given slice,
+                        // copy the remaining data to the buffer.
+                        // If there's still data remaining in the buffer, copy it to the
+                        // beginning of the buffer and truncate it.
+                        // Otherwise, clear the buffer.
+                        if self.buffer.is_empty() && offset < bytes.len() {
+                            self.buffer.extend_from_slice(&bytes[offset..]);
+                        } else if offset < self.buffer.len() {
+                            self.buffer.copy_within(offset.., 0);
+                            self.buffer.truncate(self.buffer.len() - offset);
+                        } else {
+                            self.buffer.clear();
+                        }
+                        return Ok(());
                     }
-                    return Ok(());
-                }
 
-                Chunk::Parsed { consumed, payload } => (payload, consumed),
-            };
+                    Chunk::Parsed { consumed, payload } => (payload, consumed),
+                };
 
             offset += consumed;
 
-            match &payload {
-                wasmparser::Payload::Version {
-                    encoding: Encoding::Module,
-                    ..
-                } if !self.allow_modules => {
-                    return Err(ContentPolicyError::Rejection(
-                        "WebAssembly modules are not allowed".to_string(),
-                    ))
-                }
-                wasmparser::Payload::Version {
-                    encoding: Encoding::Component,
-                    ..
-                } if !self.allow_components => {
-                    return Err(ContentPolicyError::Rejection(
-                        "WebAssembly components are not allowed".to_string(),
-                    ))
+            if let Payload::Version { encoding, .. } = &payload {
+                self.check_encoding(*encoding)?;
+                if self.stack.is_empty()
+                    && self.tier == WasmValidationTier::Component
+                    && *encoding != Encoding::Component
+                {
+                    return Err((
+                        WasmRejectionCode::NotComponent,
+                        "content must be a WebAssembly component".to_string(),
+                    ));
                 }
-                _ => {}
             }
 
-            match self.validator.payload(&payload).map_err(|e| {
-                ContentPolicyError::Rejection(format!("content is not valid WebAssembly: {e}"))
-            })? {
+            match self.validator.payload(&payload).map_err(invalid)? {
                 ValidPayload::Ok => {}
                 ValidPayload::Parser(p) => {
                     self.stack.push(self.parser.clone());
@@ -146,11 +550,7 @@ impl WasmContentStreamPolicy {
                 ValidPayload::Func(func, body) => {
                     let allocs = std::mem::take(&mut self.allocs);
                     let mut validator = func.into_validator(allocs);
-                    validator.validate(&body).map_err(|e| {
-                        ContentPolicyError::Rejection(format!(
-                            "content is not valid WebAssembly: {e}"
-                        ))
-                    })?;
+                    validator.validate(&body).map_err(invalid)?;
                     self.allocs = validator.into_allocations();
                 }
                 ValidPayload::End(_) => {
@@ -161,16 +561,203 @@ impl WasmContentStreamPolicy {
                     }
                 }
             }
+
+            self.usage.update(&payload, &self.limits)?;
+        }
+    }
+
+    /// Processes the given bytes, recording the outcome once the content
+    /// has been rejected or fully checked.
+    fn run(&mut self, bytes: &[u8], eof: bool) -> ContentPolicyResult<()> {
+        if self.done {
+            return Ok(());
+        }
+
+        let res = match self.tier {
+            WasmValidationTier::Basic => self.process_header(bytes, eof),
+            WasmValidationTier::Full | WasmValidationTier::Component => self.process(bytes, eof),
+        };
+
+        match res {
+            Ok(()) => {
+                if eof {
+                    self.done = true;
+                    self.metrics.record(self.tier, None);
+                    tracing::debug!(tier = self.tier.as_str(), "WebAssembly content accepted");
+                }
+                Ok(())
+            }
+            Err((code, message)) => {
+                self.done = true;
+                self.metrics.record(self.tier, Some(code));
+                tracing::debug!(
+                    tier = self.tier.as_str(),
+                    code = code.as_str(),
+                    "WebAssembly content rejected: {message}"
+                );
+                Err(ContentPolicyError::Rejection(format!("{message} [{code}]")))
+            }
         }
     }
 }
 
 impl ContentStreamPolicy for WasmContentStreamPolicy {
     fn check(&mut self, bytes: &[u8]) -> ContentPolicyResult<()> {
-        self.process(bytes, false)
+        self.run(bytes, false)
     }
 
     fn finalize(&mut self) -> ContentPolicyResult<()> {
-        self.process(&[], true)
+        self.run(&[], true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(
+        policy: &WasmContentPolicy,
+        name: &str,
+        content_type: ContentType,
+        bytes: &[u8],
+    ) -> ContentPolicyResult<()> {
+        // The digest is not checked by the policy
+        let digest: AnyHash =
+            "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+                .parse()
+                .unwrap();
+        let mut stream =
+            policy.new_stream_policy(&PackageName::new(name).unwrap(), &digest, content_type)?;
+        // Check the content in small chunks to exercise buffering
+        for chunk in bytes.chunks(3) {
+            stream.check(chunk)?;
+        }
+        stream.finalize()
+    }
+
+    fn reason(res: ContentPolicyResult<()>) -> String {
+        let ContentPolicyError::Rejection(reason) = res.unwrap_err();
+        reason
+    }
+
+    #[test]
+    fn it_validates_at_namespace_tiers() -> Result<()> {
+        let validation: WasmValidationConfig = toml::from_str(
+            r#"
+            [default]
+            tier = "full"
+
+            [namespaces.basic]
+            tier = "basic"
+
+            [namespaces.strict]
+            tier = "component"
+            "#,
+        )?;
+        validation.validate()?;
+        let policy = WasmContentPolicy::new().with_validation(validation);
+        let metrics = policy.metrics();
+
+        // An invalid module with a valid header only passes the basic tier
+        let invalid = b"\0asm\x01\0\0\0\x01";
+        check(&policy, "basic:pkg", ContentType::Component, invalid).unwrap();
+        assert!(
+            reason(check(&policy, "other:pkg", ContentType::Component, invalid))
+                .ends_with("[invalid-wasm]")
+        );
+        assert_eq!(
+            reason(check(
+                &policy,
+                "basic:pkg",
+                ContentType::Component,
+                b"not wasm"
+            )),
+            "content does not have a WebAssembly header [invalid-header]"
+        );
+
+        // The component tier requires components, which may nest modules
+        let module = wat::parse_str("(module)")?;
+        check(&policy, "other:pkg", ContentType::Component, &module).unwrap();
+        assert_eq!(
+            reason(check(
+                &policy,
+                "strict:pkg",
+                ContentType::Component,
+                &module
+            )),
+            "content must be a WebAssembly component [not-component]"
+        );
+        let component = wat::parse_str("(component (core module))")?;
+        check(&policy, "strict:pkg", ContentType::Component, &component).unwrap();
+
+        // Content that is not WebAssembly is not checked or counted
+        check(&policy, "strict:pkg", ContentType::DocsArchive, b"docs").unwrap();
+
+        assert_eq!(metrics.checked(WasmValidationTier::Basic), 2);
+        assert_eq!(metrics.rejected(WasmValidationTier::Basic), 1);
+        assert_eq!(metrics.checked(WasmValidationTier::Full), 2);
+        assert_eq!(metrics.rejected(WasmValidationTier::Full), 1);
+        assert_eq!(metrics.checked(WasmValidationTier::Component), 2);
+        assert_eq!(metrics.rejected(WasmValidationTier::Component), 1);
+        assert_eq!(metrics.rejections(WasmRejectionCode::InvalidHeader), 1);
+        assert_eq!(metrics.rejections(WasmRejectionCode::InvalidWasm), 1);
+        assert_eq!(metrics.rejections(WasmRejectionCode::NotComponent), 1);
+        assert_eq!(metrics.rejections(WasmRejectionCode::ResourceLimit), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn it_lints_resource_limits() -> Result<()> {
+        let validation: WasmValidationConfig = toml::from_str(
+            r#"
+            [default.limits]
+            max_functions = 1
+            max_memory_pages = 1
+            max_data_size = 4
+            "#,
+        )?;
+        validation.validate()?;
+        let policy = WasmContentPolicy::new().with_validation(validation);
+
+        let check = |wat: &str| {
+            check(
+                &policy,
+                "test:pkg",
+                ContentType::Component,
+                &wat::parse_str(wat).unwrap(),
+            )
+        };
+        check("(module (func) (memory 1) (data (i32.const 0) \"abcd\"))").unwrap();
+        assert_eq!(
+            reason(check("(module (func) (func))")),
+            "content exceeds the limit of 1 functions with 2 [resource-limit]"
+        );
+        assert_eq!(
+            reason(check("(module (memory 2))")),
+            "content exceeds the limit of 1 memory pages with 2 [resource-limit]"
+        );
+        // Limits apply to the modules nested in components
+        assert_eq!(
+            reason(check(
+                "(component (core module (func)) (core module (func)))"
+            )),
+            "content exceeds the limit of 1 functions with 2 [resource-limit]"
+        );
+        assert_eq!(
+            policy
+                .metrics()
+                .rejections(WasmRejectionCode::ResourceLimit),
+            3
+        );
+
+        let invalid: WasmValidationConfig = toml::from_str(
+            r#"
+            [namespaces.example]
+            tier = "basic"
+            limits = { max_functions = 1 }
+            "#,
+        )?;
+        assert!(invalid.validate().is_err());
+        Ok(())
     }
 }
@@ -1,4 +1,28 @@
 //! Module for server policy implementations.
 
+pub mod approval;
 pub mod content;
 pub mod record;
+pub mod semver;
+pub mod sources;
+
+/// Determines how the server acts on the decisions of its policies.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyMode {
+    /// Policy rejections are enforced and the offending records are rejected.
+    #[default]
+    Enforce,
+    /// Policies are evaluated and their decisions recorded, but rejections are
+    /// not enforced.
+    ///
+    /// This allows new policies to be trialed against live traffic before
+    /// they are enforced.
+    DryRun,
+}
+
+impl PolicyMode {
+    /// Determines if policy rejections are enforced in this mode.
+    pub fn is_enforcing(&self) -> bool {
+        matches!(self, Self::Enforce)
+    }
+}
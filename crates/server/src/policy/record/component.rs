@@ -0,0 +1,178 @@
+use super::{external::policy_input, RecordPolicy, RecordPolicyError, RecordPolicyResult};
+use anyhow::{Context, Result};
+use std::path::Path;
+use warg_protocol::{package::PackageRecord, registry::PackageName, ProtoEnvelope};
+use wasmtime::{
+    component::{Component, Linker},
+    Config, Engine, Store,
+};
+
+const DEFAULT_FUEL: u64 = 100_000_000;
+
+mod bindings {
+    wasmtime::component::bindgen!({
+        path: "wit/policy.wit",
+        world: "record-policy",
+    });
+}
+
+use bindings::Decision;
+
+/// A policy that delegates record decisions to a WebAssembly component.
+///
+/// The component must implement the `warg:policy/record-policy` world found
+/// in the `wit` directory of this crate and must not have any imports.
+///
+/// The record is passed to the component's `check` export as a JSON
+/// document; see the [external policy input](super::external) for its schema.
+///
+/// Each check runs in a new instance of the component and is limited by an
+/// amount of fuel; records are rejected if the component traps or runs out
+/// of fuel.
+pub struct ComponentRecordPolicy {
+    engine: Engine,
+    component: Component,
+    linker: Linker<()>,
+    fuel: u64,
+}
+
+impl ComponentRecordPolicy {
+    /// Creates a new component record policy from the given component bytes.
+    pub fn new(bytes: impl AsRef<[u8]>) -> Result<Self> {
+        let mut config = Config::new();
+        config.wasm_component_model(true).consume_fuel(true);
+
+        let engine = Engine::new(&config)?;
+        let component =
+            Component::new(&engine, bytes).context("failed to compile record policy component")?;
+        let linker = Linker::new(&engine);
+
+        Ok(Self {
+            engine,
+            component,
+            linker,
+            fuel: DEFAULT_FUEL,
+        })
+    }
+
+    /// Creates a new component record policy from the component at the given path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| {
+            format!(
+                "failed to read record policy component `{path}`",
+                path = path.display()
+            )
+        })?;
+        Self::new(bytes)
+    }
+
+    /// Sets the amount of fuel available to each check.
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    fn run(&self, input: &str) -> Result<Decision> {
+        let mut store = Store::new(&self.engine, ());
+        store.set_fuel(self.fuel)?;
+
+        let (policy, _) =
+            bindings::RecordPolicy::instantiate(&mut store, &self.component, &self.linker)?;
+        policy.call_check(&mut store, input)
+    }
+}
+
+impl RecordPolicy for ComponentRecordPolicy {
+    fn check(
+        &self,
+        name: &PackageName,
+        record: &ProtoEnvelope<PackageRecord>,
+    ) -> RecordPolicyResult<()> {
+        let input = policy_input(name, record).to_string();
+        match self.run(&input) {
+            Ok(Decision::Allow) => Ok(()),
+            Ok(Decision::Reject(reason)) => Err(RecordPolicyError::Rejection(reason)),
+            Ok(Decision::Unauthorized(reason)) => Err(RecordPolicyError::Unauthorized(reason)),
+            Err(e) => {
+                tracing::error!("record policy component failed: {e:?}");
+                Err(RecordPolicyError::Rejection(
+                    "the policy component failed to make a decision".into(),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::record::external::tests::test_record;
+    use wit_component::{ComponentEncoder, StringEncoding};
+    use wit_parser::{Resolve, UnresolvedPackage};
+
+    fn encode(wat: &str) -> Vec<u8> {
+        let mut resolve = Resolve::new();
+        let pkg = resolve
+            .push(
+                UnresolvedPackage::parse(
+                    Path::new("policy.wit"),
+                    include_str!("../../../wit/policy.wit"),
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        let world = resolve.select_world(pkg, Some("record-policy")).unwrap();
+
+        let mut module = wat::parse_str(wat).unwrap();
+        wit_component::embed_component_metadata(&mut module, &resolve, world, StringEncoding::UTF8)
+            .unwrap();
+
+        ComponentEncoder::default()
+            .module(&module)
+            .unwrap()
+            .validate(true)
+            .encode()
+            .unwrap()
+    }
+
+    #[test]
+    fn it_delegates_to_a_component() -> Result<()> {
+        // A component that rejects every record
+        let policy = ComponentRecordPolicy::new(encode(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 16) "\01\00\00\00\40\00\00\00\10\00\00\00")
+                (data (i32.const 64) "denied by policy")
+                (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32) i32.const 1024)
+                (func (export "check") (param i32 i32) (result i32) i32.const 16)
+            )"#,
+        ))?;
+
+        assert!(matches!(
+            policy.check(&"test:package".parse()?, &test_record()),
+            Err(RecordPolicyError::Rejection(reason)) if reason == "denied by policy"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_when_out_of_fuel() -> Result<()> {
+        let policy = ComponentRecordPolicy::new(encode(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32) i32.const 1024)
+                (func (export "check") (param i32 i32) (result i32) (loop br 0) unreachable)
+            )"#,
+        ))?
+        .with_fuel(10_000);
+
+        assert!(matches!(
+            policy.check(&"test:package".parse()?, &test_record()),
+            Err(RecordPolicyError::Rejection(reason)) if reason.contains("failed")
+        ));
+
+        Ok(())
+    }
+}
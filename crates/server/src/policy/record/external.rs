@@ -0,0 +1,171 @@
+//! Support for record policies that delegate decisions to an external
+//! policy engine.
+//!
+//! External policy engines are given a JSON document describing the record
+//! being published:
+//!
+//! ```json
+//! {
+//!   "packageName": "example:package",
+//!   "recordId": "sha256:...",
+//!   "keyId": "sha256:...",
+//!   "prev": "sha256:...",
+//!   "timestamp": 1700000000,
+//!   "entries": [
+//!     { "type": "init", "hashAlgorithm": "sha256", "key": "ecdsa-p256:..." },
+//!     { "type": "grantFlat", "key": "ecdsa-p256:...", "permissions": ["release"] },
+//!     { "type": "revokeFlat", "keyId": "sha256:...", "permissions": ["yank"] },
+//!     { "type": "release", "version": "1.0.0", "content": "sha256:...", "contentType": "component" },
+//!     { "type": "yank", "version": "1.0.0" },
+//!     { "type": "attest", "version": "1.0.0", "kind": "https://slsa.dev/provenance/v1", "content": "sha256:..." },
+//!     { "type": "deprecate", "message": "...", "replacement": "example:other" },
+//!     { "type": "undeprecate" }
+//!   ]
+//! }
+//! ```
+//!
+//! The `prev` field is `null` for the first record of a package log, the
+//! `replacement` field is `null` for a deprecation without a replacement, and
+//! the `timestamp` field is in seconds since the Unix epoch.
+use serde_json::{json, Value};
+use std::time::SystemTime;
+use warg_crypto::hash::Sha256;
+use warg_protocol::{
+    package::{PackageEntry, PackageRecord},
+    registry::{PackageName, RecordId},
+    ProtoEnvelope,
+};
+
+/// Builds the policy input document for a package record.
+pub(crate) fn policy_input(name: &PackageName, record: &ProtoEnvelope<PackageRecord>) -> Value {
+    let contents = record.as_ref();
+    let entries = contents
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            Some(match entry {
+                PackageEntry::Init {
+                    hash_algorithm,
+                    key,
+                } => json!({
+                    "type": "init",
+                    "hashAlgorithm": hash_algorithm.to_string(),
+                    "key": key.to_string(),
+                }),
+                PackageEntry::GrantFlat { key, permissions } => json!({
+                    "type": "grantFlat",
+                    "key": key.to_string(),
+                    "permissions": permissions,
+                }),
+                PackageEntry::RevokeFlat {
+                    key_id,
+                    permissions,
+                } => json!({
+                    "type": "revokeFlat",
+                    "keyId": key_id.to_string(),
+                    "permissions": permissions,
+                }),
+                PackageEntry::Release {
+                    version,
+                    content,
+                    content_type,
+                } => json!({
+                    "type": "release",
+                    "version": version.to_string(),
+                    "content": content.to_string(),
+                    "contentType": content_type,
+                }),
+                PackageEntry::Yank { version } => json!({
+                    "type": "yank",
+                    "version": version.to_string(),
+                }),
+                PackageEntry::Attest {
+                    version,
+                    kind,
+                    content,
+                } => json!({
+                    "type": "attest",
+                    "version": version.to_string(),
+                    "kind": kind,
+                    "content": content.to_string(),
+                }),
+                PackageEntry::Deprecate {
+                    message,
+                    replacement,
+                } => json!({
+                    "type": "deprecate",
+                    "message": message,
+                    "replacement": replacement.as_ref().map(ToString::to_string),
+                }),
+                PackageEntry::Undeprecate => json!({ "type": "undeprecate" }),
+                _ => return None,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "packageName": name.as_ref(),
+        "recordId": RecordId::package_record::<Sha256>(record).to_string(),
+        "keyId": record.key_id().to_string(),
+        "prev": contents.prev.as_ref().map(ToString::to_string),
+        "timestamp": contents
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        "entries": entries,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use warg_crypto::{hash::HashAlgorithm, signing::PrivateKey};
+    use warg_protocol::package::{ContentType, PACKAGE_RECORD_VERSION};
+
+    pub(crate) fn test_record() -> ProtoEnvelope<PackageRecord> {
+        let key = PrivateKey::decode(
+            "ecdsa-p256:2CV1EpLaSYEn4In4OAEDAj5O4Hzu8AFAxgHXuG310Ew=".to_string(),
+        )
+        .unwrap();
+
+        ProtoEnvelope::signed_contents(
+            &key,
+            PackageRecord {
+                prev: None,
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1700000000),
+                entries: vec![
+                    PackageEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha256,
+                        key: key.public_key(),
+                    },
+                    PackageEntry::Release {
+                        version: "1.0.0".parse().unwrap(),
+                        content: "sha256:0000000000000000000000000000000000000000000000000000000000000000"
+                            .parse()
+                            .unwrap(),
+                        content_type: ContentType::Component,
+                    },
+                ],
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn it_builds_policy_input() {
+        let record = test_record();
+        let input = policy_input(&"test:package".parse().unwrap(), &record);
+
+        assert_eq!(input["packageName"], "test:package");
+        assert_eq!(input["keyId"], record.key_id().to_string());
+        assert_eq!(input["prev"], Value::Null);
+        assert_eq!(input["timestamp"], 1700000000);
+        assert_eq!(input["entries"][0]["type"], "init");
+        assert_eq!(input["entries"][0]["hashAlgorithm"], "sha256");
+        assert_eq!(input["entries"][1]["type"], "release");
+        assert_eq!(input["entries"][1]["version"], "1.0.0");
+        assert_eq!(input["entries"][1]["contentType"], "component");
+    }
+}
@@ -1,10 +1,33 @@
 //! Module for server record policy implementations.
+use std::sync::Arc;
 use thiserror::Error;
 use warg_protocol::{package::PackageRecord, registry::PackageName, ProtoEnvelope};
 
 mod authorization;
 pub use authorization::*;
 
+mod namespace;
+pub use namespace::*;
+
+mod names;
+pub use names::*;
+
+mod teams;
+pub use teams::*;
+
+#[cfg(any(feature = "opa-policy", feature = "component-policy"))]
+pub mod external;
+
+#[cfg(feature = "opa-policy")]
+mod opa;
+#[cfg(feature = "opa-policy")]
+pub use opa::*;
+
+#[cfg(feature = "component-policy")]
+mod component;
+#[cfg(feature = "component-policy")]
+pub use component::*;
+
 /// Represents a record policy error.
 #[derive(Debug, Error)]
 pub enum RecordPolicyError {
@@ -32,6 +55,16 @@ pub trait RecordPolicy: Send + Sync {
     ) -> RecordPolicyResult<()>;
 }
 
+impl<P: RecordPolicy + ?Sized> RecordPolicy for Arc<P> {
+    fn check(
+        &self,
+        name: &PackageName,
+        record: &ProtoEnvelope<PackageRecord>,
+    ) -> RecordPolicyResult<()> {
+        self.as_ref().check(name, record)
+    }
+}
+
 /// Represents a collection of record policies.
 ///
 /// Record policies are checked in order of their addition
@@ -0,0 +1,208 @@
+use super::{RecordPolicy, RecordPolicyError, RecordPolicyResult};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use warg_protocol::{
+    package::{PackageEntry, PackageRecord},
+    registry::PackageName,
+    ProtoEnvelope,
+};
+
+/// The reserved names and blocked patterns of a [`NameListPolicy`].
+///
+/// Names and patterns are matched against package names, which are always
+/// lowercase; patterns are matched case insensitively.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NameLists {
+    /// The reserved namespaces (e.g. `wasi`) and package names (e.g.
+    /// `example:core`) that may not be claimed.
+    pub reserved: Vec<String>,
+    /// The patterns of package names that may not be claimed.
+    ///
+    /// A `*` in a pattern matches any sequence of characters and a `?`
+    /// matches any single character (e.g. `*:*badword*` or `wasl:*`).
+    pub blocked: Vec<String>,
+}
+
+impl NameLists {
+    /// Validates the names and patterns of the lists.
+    pub fn validate(&self) -> Result<()> {
+        for name in &self.reserved {
+            let valid = match name.split_once(':') {
+                Some(_) => PackageName::new(name.as_str()).is_ok(),
+                None => PackageName::new(format!("{name}:name")).is_ok(),
+            };
+            if !valid {
+                bail!("reserved name `{name}` is not a valid namespace or package name");
+            }
+        }
+
+        if let Some(pattern) = self.blocked.iter().find(|p| p.trim().is_empty()) {
+            bail!("blocked pattern `{pattern}` is empty");
+        }
+
+        Ok(())
+    }
+
+    /// Gets the reason a package name may not be claimed, if any.
+    fn rejection(&self, name: &PackageName) -> Option<String> {
+        let full = name.as_ref();
+        if self
+            .reserved
+            .iter()
+            .any(|reserved| reserved == full || reserved == name.namespace())
+        {
+            return Some(format!("package name `{name}` is reserved"));
+        }
+
+        if self
+            .blocked
+            .iter()
+            .any(|pattern| wildcard_match(&pattern.to_lowercase(), full))
+        {
+            return Some(format!("package name `{name}` is blocked"));
+        }
+
+        None
+    }
+}
+
+/// A policy that prevents packages from being claimed with reserved or
+/// blocked names.
+///
+/// Only records that initialize a package log are checked, so packages
+/// published before a name was reserved or blocked are unaffected.
+///
+/// Clones of the policy share the same lists, allowing the lists to be
+/// updated while the policy is in use.
+#[derive(Debug, Default, Clone)]
+pub struct NameListPolicy {
+    lists: Arc<RwLock<NameLists>>,
+}
+
+impl NameListPolicy {
+    /// Creates a new name list policy with the given lists.
+    pub fn new(lists: NameLists) -> Result<Self> {
+        lists.validate()?;
+        Ok(Self {
+            lists: Arc::new(RwLock::new(lists)),
+        })
+    }
+
+    /// Gets the current lists of the policy.
+    pub fn lists(&self) -> NameLists {
+        self.lists.read().unwrap().clone()
+    }
+
+    /// Replaces the lists of the policy.
+    pub fn set_lists(&self, lists: NameLists) -> Result<()> {
+        lists.validate()?;
+        *self.lists.write().unwrap() = lists;
+        Ok(())
+    }
+}
+
+impl RecordPolicy for NameListPolicy {
+    fn check(
+        &self,
+        name: &PackageName,
+        record: &ProtoEnvelope<PackageRecord>,
+    ) -> RecordPolicyResult<()> {
+        if !record
+            .as_ref()
+            .entries
+            .iter()
+            .any(|entry| matches!(entry, PackageEntry::Init { .. }))
+        {
+            return Ok(());
+        }
+
+        match self.lists.read().unwrap().rejection(name) {
+            Some(reason) => Err(RecordPolicyError::Unauthorized(reason)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Matches a string against a pattern where `*` matches any sequence of
+/// characters and `?` matches any single character.
+fn wildcard_match(pattern: &str, s: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let s = s.chars().collect::<Vec<_>>();
+    let (mut p, mut i) = (0, 0);
+    let mut backtrack = None;
+
+    while i < s.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == s[i]) {
+            p += 1;
+            i += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, i));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` match one more character
+            p = star + 1;
+            i = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_wildcards() {
+        assert!(wildcard_match("wasl:*", "wasl:io"));
+        assert!(wildcard_match("*:*bad*", "example:not-bad-at-all"));
+        assert!(wildcard_match("exampl?:core", "example:core"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("wasl:*", "wasi:io"));
+        assert!(!wildcard_match("exampl?:core", "exampl:core"));
+        assert!(!wildcard_match("*:bad", "example:bad-name"));
+    }
+
+    #[test]
+    fn it_rejects_reserved_and_blocked_names() -> Result<()> {
+        let lists = NameLists {
+            reserved: vec!["wasi".into(), "example:core".into()],
+            blocked: vec!["wasl:*".into(), "*:*BadWord*".into()],
+        };
+        let name = |s: &str| PackageName::new(s).unwrap();
+
+        assert!(lists
+            .rejection(&name("wasi:io"))
+            .unwrap()
+            .contains("reserved"));
+        assert!(lists
+            .rejection(&name("example:core"))
+            .unwrap()
+            .contains("reserved"));
+        assert!(lists
+            .rejection(&name("wasl:io"))
+            .unwrap()
+            .contains("blocked"));
+        assert!(lists
+            .rejection(&name("example:my-badword"))
+            .unwrap()
+            .contains("blocked"));
+        assert!(lists.rejection(&name("example:other")).is_none());
+
+        let policy = NameListPolicy::new(lists)?;
+        policy.set_lists(NameLists::default())?;
+        assert_eq!(policy.lists(), NameLists::default());
+        assert!(policy
+            .set_lists(NameLists {
+                reserved: vec!["not a name".into()],
+                ..Default::default()
+            })
+            .is_err());
+        Ok(())
+    }
+}
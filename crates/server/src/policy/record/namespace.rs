@@ -0,0 +1,446 @@
+use super::{RecordPolicy, RecordPolicyError, RecordPolicyResult};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use url::Url;
+use warg_crypto::signing::KeyID;
+use warg_protocol::{
+    package::{PackageEntry, PackageRecord},
+    registry::PackageName,
+    ProtoEnvelope,
+};
+
+const DEFAULT_DNS_RESOLVER: &str = "https://cloudflare-dns.com/dns-query";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The prefix of the TXT records that prove control of a domain.
+const TXT_PREFIX: &str = "warg-key=";
+
+/// The path of the well-known document that proves control of a domain.
+const WELL_KNOWN_PATH: &str = ".well-known/warg-keys";
+
+/// The TXT record type in DNS queries.
+const TXT_RECORD_TYPE: u16 = 16;
+
+#[derive(Deserialize)]
+struct DnsAnswer {
+    #[serde(rename = "type")]
+    kind: u16,
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct DnsResponse {
+    #[serde(default, rename = "Answer")]
+    answer: Vec<DnsAnswer>,
+}
+
+/// A policy that requires keys claiming a package in a namespace to prove
+/// control of the domain associated with the namespace.
+///
+/// A key proves control of a domain (e.g. `example.com` for the `example`
+/// namespace) by either:
+///
+/// * a DNS TXT record at `_warg.<domain>` with the value `warg-key=<key id>`.
+/// * a document at `https://<domain>/.well-known/warg-keys` listing the key
+///   ID on its own line.
+///
+/// Only records that initialize a package log are checked; once a package
+/// has been claimed, the package log's own permissions apply. Namespaces
+/// without an associated domain are not checked.
+///
+/// DNS records are resolved with a [DNS-over-HTTPS][doh] resolver using the
+/// JSON API. Successful verifications are cached so that publishers are not
+/// checked for every new package; expired verifications are pruned as new
+/// ones are cached.
+///
+/// As record policies are synchronous, this policy must be used from a
+/// multi-threaded Tokio runtime; claims checked from any other context are
+/// rejected.
+///
+/// [doh]: https://developers.cloudflare.com/1.1.1.1/encryption/dns-over-https/make-api-requests/dns-json/
+pub struct NamespaceClaimPolicy {
+    client: reqwest::Client,
+    domains: IndexMap<String, String>,
+    domain_suffix: Option<String>,
+    resolver: Url,
+    timeout: Duration,
+    cache_ttl: Duration,
+    verified: Mutex<IndexMap<(String, KeyID), Instant>>,
+}
+
+impl Default for NamespaceClaimPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NamespaceClaimPolicy {
+    /// Creates a new namespace claim policy.
+    ///
+    /// By default, no namespaces are checked.
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            domains: IndexMap::new(),
+            domain_suffix: None,
+            resolver: DEFAULT_DNS_RESOLVER.parse().unwrap(),
+            timeout: DEFAULT_TIMEOUT,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            verified: Default::default(),
+        }
+    }
+
+    /// Associates a namespace with the domain its publishers must control.
+    pub fn with_namespace_domain(
+        mut self,
+        namespace: impl Into<String>,
+        domain: impl Into<String>,
+    ) -> Self {
+        self.domains.insert(namespace.into(), domain.into());
+        self
+    }
+
+    /// Associates every namespace without an explicit domain with the domain
+    /// formed by appending the given suffix (e.g. `com` associates the
+    /// `example` namespace with `example.com`).
+    pub fn with_domain_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.domain_suffix = Some(suffix.into());
+        self
+    }
+
+    /// Sets the URL of the DNS-over-HTTPS resolver used to resolve TXT
+    /// records.
+    ///
+    /// Defaults to Cloudflare's resolver.
+    pub fn with_dns_resolver(mut self, url: Url) -> Self {
+        self.resolver = url;
+        self
+    }
+
+    /// Sets the timeout for DNS queries and well-known document requests.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how long a successful verification is cached.
+    ///
+    /// Defaults to one hour.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Gets the domain associated with the given namespace.
+    fn domain(&self, namespace: &str) -> Option<String> {
+        self.domains.get(namespace).cloned().or_else(|| {
+            self.domain_suffix
+                .as_ref()
+                .map(|suffix| format!("{namespace}.{suffix}"))
+        })
+    }
+
+    fn is_cached(&self, namespace: &str, key_id: &KeyID) -> bool {
+        let verified = self.verified.lock().unwrap();
+        verified
+            .get(&(namespace.to_string(), key_id.clone()))
+            .is_some_and(|at| at.elapsed() < self.cache_ttl)
+    }
+
+    /// Determines if the given key has proven control of the domain.
+    async fn verify(&self, domain: &str, key_id: &KeyID) -> bool {
+        match self.txt_records(domain).await {
+            Ok(records) if txt_records_contain(&records, key_id) => return true,
+            Ok(_) => {}
+            Err(e) => tracing::warn!("failed to resolve TXT records of `_warg.{domain}`: {e}"),
+        }
+
+        match self.well_known(domain).await {
+            Ok(document) => well_known_contains(&document, key_id),
+            Err(e) => {
+                tracing::warn!("failed to fetch the well-known keys of `{domain}`: {e}");
+                false
+            }
+        }
+    }
+
+    async fn txt_records(&self, domain: &str) -> Result<Vec<String>, reqwest::Error> {
+        let response = self
+            .client
+            .get(self.resolver.clone())
+            .query(&[
+                ("name", format!("_warg.{domain}").as_str()),
+                ("type", "TXT"),
+            ])
+            .header(reqwest::header::ACCEPT, "application/dns-json")
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<DnsResponse>()
+            .await?;
+
+        Ok(response
+            .answer
+            .into_iter()
+            .filter(|answer| answer.kind == TXT_RECORD_TYPE)
+            .map(|answer| answer.data)
+            .collect())
+    }
+
+    async fn well_known(&self, domain: &str) -> Result<String, reqwest::Error> {
+        self.client
+            .get(format!("https://{domain}/{WELL_KNOWN_PATH}"))
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
+    }
+}
+
+/// Determines if the given TXT record data contains a proof for the key.
+///
+/// The data of a TXT record is one or more quoted character strings that
+/// are concatenated.
+fn txt_records_contain(records: &[String], key_id: &KeyID) -> bool {
+    records.iter().any(|data| {
+        let value = if data.contains('"') {
+            data.split('"').skip(1).step_by(2).collect::<String>()
+        } else {
+            data.clone()
+        };
+
+        value
+            .strip_prefix(TXT_PREFIX)
+            .is_some_and(|value| value.trim() == key_id.to_string())
+    })
+}
+
+/// Determines if the given well-known document lists the key.
+///
+/// Blank lines and lines starting with `#` are ignored.
+fn well_known_contains(document: &str, key_id: &KeyID) -> bool {
+    let key_id = key_id.to_string();
+    document
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .any(|line| line == key_id)
+}
+
+impl RecordPolicy for NamespaceClaimPolicy {
+    fn check(
+        &self,
+        name: &PackageName,
+        record: &ProtoEnvelope<PackageRecord>,
+    ) -> RecordPolicyResult<()> {
+        if !record
+            .as_ref()
+            .entries
+            .iter()
+            .any(|entry| matches!(entry, PackageEntry::Init { .. }))
+        {
+            return Ok(());
+        }
+
+        let namespace = name.namespace();
+        let Some(domain) = self.domain(namespace) else {
+            return Ok(());
+        };
+
+        let key_id = record.key_id();
+        if self.is_cached(namespace, key_id) {
+            return Ok(());
+        }
+
+        // Blocking on the verification requires a multi-threaded runtime
+        let handle = match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => handle,
+            _ => {
+                return Err(RecordPolicyError::Rejection(format!(
+                    "cannot verify control of domain `{domain}` outside of a multi-threaded runtime"
+                )))
+            }
+        };
+        if !tokio::task::block_in_place(|| handle.block_on(self.verify(&domain, key_id))) {
+            return Err(RecordPolicyError::Unauthorized(format!(
+                "key `{key_id}` has not proven control of domain `{domain}` to claim packages in namespace `{namespace}`"
+            )));
+        }
+
+        let mut verified = self.verified.lock().unwrap();
+        verified.retain(|_, at| at.elapsed() < self.cache_ttl);
+        verified.insert((namespace.to_string(), key_id.clone()), Instant::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, routing::get, Json, Router};
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+    use warg_crypto::{hash::HashAlgorithm, signing::PrivateKey};
+    use warg_protocol::package::PACKAGE_RECORD_VERSION;
+
+    fn test_record(key: &PrivateKey, entries: Vec<PackageEntry>) -> ProtoEnvelope<PackageRecord> {
+        ProtoEnvelope::signed_contents(
+            key,
+            PackageRecord {
+                prev: None,
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries,
+            },
+        )
+        .unwrap()
+    }
+
+    fn init(key: &PrivateKey) -> PackageEntry {
+        PackageEntry::Init {
+            hash_algorithm: HashAlgorithm::Sha256,
+            key: key.public_key(),
+        }
+    }
+
+    async fn spawn_resolver(proven: KeyID) -> Url {
+        let router = Router::new().route(
+            "/dns-query",
+            get(move |Query(query): Query<HashMap<String, String>>| {
+                let proven = proven.clone();
+                async move {
+                    let answer = match query.get("name").map(String::as_str) {
+                        Some("_warg.example.invalid") => vec![
+                            serde_json::json!({ "type": 16, "data": "\"v=spf1 -all\"" }),
+                            serde_json::json!({ "type": 16, "data": format!("\"{TXT_PREFIX}\" \"{proven}\"") }),
+                        ],
+                        _ => Vec::new(),
+                    };
+                    Json(serde_json::json!({ "Status": 0, "Answer": answer }))
+                }
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{addr}/dns-query").parse().unwrap()
+    }
+
+    #[test]
+    fn it_parses_proofs() {
+        let key_id = KeyID::from("sha256:abc".to_string());
+        let other = KeyID::from("sha256:def".to_string());
+
+        let records = vec!["\"warg-key=sha256:\" \"abc\"".to_string()];
+        assert!(txt_records_contain(&records, &key_id));
+        assert!(!txt_records_contain(&records, &other));
+        assert!(txt_records_contain(
+            &["warg-key=sha256:abc".to_string()],
+            &key_id
+        ));
+
+        let document = "# warg keys\n\nsha256:abc\n";
+        assert!(well_known_contains(document, &key_id));
+        assert!(!well_known_contains(document, &other));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn it_requires_claims_to_prove_domain_control() {
+        let proven = PrivateKey::decode(
+            "ecdsa-p256:2CV1EpLaSYEn4In4OAEDAj5O4Hzu8AFAxgHXuG310Ew=".to_string(),
+        )
+        .unwrap();
+        let unproven = PrivateKey::decode(
+            "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string(),
+        )
+        .unwrap();
+
+        let policy = NamespaceClaimPolicy::new()
+            .with_namespace_domain("example", "example.invalid")
+            .with_dns_resolver(spawn_resolver(proven.public_key().fingerprint()).await)
+            .with_timeout(Duration::from_secs(1));
+        let name: PackageName = "example:package".parse().unwrap();
+
+        assert!(policy
+            .check(&name, &test_record(&proven, vec![init(&proven)]))
+            .is_ok());
+        assert!(policy.is_cached("example", &proven.public_key().fingerprint()));
+        assert!(matches!(
+            policy.check(&name, &test_record(&unproven, vec![init(&unproven)])),
+            Err(RecordPolicyError::Unauthorized(message)) if message.contains("example.invalid")
+        ));
+
+        // Records that do not claim a package and unassociated namespaces are not checked
+        let yank = PackageEntry::Yank {
+            version: "1.0.0".parse().unwrap(),
+        };
+        assert!(policy
+            .check(&name, &test_record(&unproven, vec![yank]))
+            .is_ok());
+        assert!(policy
+            .check(
+                &"other:package".parse().unwrap(),
+                &test_record(&unproven, vec![init(&unproven)])
+            )
+            .is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn it_prunes_expired_verifications() {
+        let proven = PrivateKey::decode(
+            "ecdsa-p256:2CV1EpLaSYEn4In4OAEDAj5O4Hzu8AFAxgHXuG310Ew=".to_string(),
+        )
+        .unwrap();
+
+        let policy = NamespaceClaimPolicy::new()
+            .with_namespace_domain("example", "example.invalid")
+            .with_namespace_domain("other", "example.invalid")
+            .with_dns_resolver(spawn_resolver(proven.public_key().fingerprint()).await)
+            .with_timeout(Duration::from_secs(1))
+            .with_cache_ttl(Duration::ZERO);
+
+        for name in ["example:package", "other:package"] {
+            assert!(policy
+                .check(
+                    &name.parse().unwrap(),
+                    &test_record(&proven, vec![init(&proven)])
+                )
+                .is_ok());
+        }
+
+        // Only the latest verification remains cached
+        let verified = policy.verified.lock().unwrap();
+        assert_eq!(verified.len(), 1);
+        assert!(verified.contains_key(&("other".to_string(), proven.public_key().fingerprint())));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_claims_on_a_current_thread_runtime() {
+        let key = PrivateKey::decode(
+            "ecdsa-p256:2CV1EpLaSYEn4In4OAEDAj5O4Hzu8AFAxgHXuG310Ew=".to_string(),
+        )
+        .unwrap();
+
+        let policy =
+            NamespaceClaimPolicy::new().with_namespace_domain("example", "example.invalid");
+        assert!(matches!(
+            policy.check(
+                &"example:package".parse().unwrap(),
+                &test_record(&key, vec![init(&key)])
+            ),
+            Err(RecordPolicyError::Rejection(message)) if message.contains("multi-threaded")
+        ));
+    }
+}
@@ -0,0 +1,210 @@
+use super::{external::policy_input, RecordPolicy, RecordPolicyError, RecordPolicyResult};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use url::Url;
+use warg_protocol::{package::PackageRecord, registry::PackageName, ProtoEnvelope};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The decision result returned by an OPA policy.
+///
+/// A policy may either return a boolean or an object with a reason.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OpaDecision {
+    Allow(bool),
+    #[serde(rename_all = "camelCase")]
+    Detailed {
+        allow: bool,
+        #[serde(default)]
+        reason: Option<String>,
+        #[serde(default)]
+        unauthorized: bool,
+    },
+}
+
+#[derive(Deserialize)]
+struct OpaResponse {
+    result: Option<OpaDecision>,
+}
+
+/// A policy that delegates record decisions to an [Open Policy Agent][opa]
+/// server.
+///
+/// The record is sent as the `input` of a query to the OPA data API (e.g.
+/// `http://localhost:8181/v1/data/warg/allow`); see the
+/// [external policy input](super::external) for its schema.
+///
+/// The policy result may either be a boolean or an object of the form
+/// `{ "allow": bool, "reason": string, "unauthorized": bool }`.
+///
+/// Records are rejected if the OPA server cannot be reached or the policy
+/// result is undefined.
+///
+/// As record policies are synchronous, this policy must be used from a
+/// multi-threaded Tokio runtime; records checked from any other context are
+/// rejected.
+///
+/// [opa]: https://www.openpolicyagent.org/
+pub struct OpaRecordPolicy {
+    client: reqwest::Client,
+    url: Url,
+    timeout: Duration,
+}
+
+impl OpaRecordPolicy {
+    /// Creates a new OPA record policy that queries the given URL.
+    pub fn new(url: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Sets the timeout for queries to the OPA server.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn query(&self, input: serde_json::Value) -> Result<Option<OpaDecision>, reqwest::Error> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .timeout(self.timeout)
+            .json(&json!({ "input": input }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<OpaResponse>()
+            .await?;
+
+        Ok(response.result)
+    }
+}
+
+impl RecordPolicy for OpaRecordPolicy {
+    fn check(
+        &self,
+        name: &PackageName,
+        record: &ProtoEnvelope<PackageRecord>,
+    ) -> RecordPolicyResult<()> {
+        // Blocking on the query requires a multi-threaded runtime
+        let handle = match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => handle,
+            _ => {
+                return Err(RecordPolicyError::Rejection(
+                    "the policy engine cannot be queried outside of a multi-threaded runtime"
+                        .into(),
+                ))
+            }
+        };
+
+        let input = policy_input(name, record);
+        let decision =
+            tokio::task::block_in_place(|| handle.block_on(self.query(input))).map_err(|e| {
+                tracing::error!("failed to query OPA policy at `{url}`: {e}", url = self.url);
+                RecordPolicyError::Rejection("the policy engine could not be queried".into())
+            })?;
+
+        match decision {
+            Some(OpaDecision::Allow(true)) | Some(OpaDecision::Detailed { allow: true, .. }) => {
+                Ok(())
+            }
+            Some(OpaDecision::Allow(false)) => Err(RecordPolicyError::Rejection(format!(
+                "record for package `{name}` was denied by policy"
+            ))),
+            Some(OpaDecision::Detailed {
+                reason,
+                unauthorized,
+                ..
+            }) => {
+                let reason = reason
+                    .unwrap_or_else(|| format!("record for package `{name}` was denied by policy"));
+                if unauthorized {
+                    Err(RecordPolicyError::Unauthorized(reason))
+                } else {
+                    Err(RecordPolicyError::Rejection(reason))
+                }
+            }
+            None => Err(RecordPolicyError::Rejection(
+                "the policy decision was undefined".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::record::external::tests::test_record;
+    use axum::{routing::post, Json, Router};
+    use serde_json::Value;
+
+    async fn spawn_opa() -> Url {
+        let router = Router::new().route(
+            "/v1/data/warg/allow",
+            post(|Json(body): Json<Value>| async move {
+                Json(match body["input"]["packageName"].as_str() {
+                    Some("test:allowed") => json!({ "result": true }),
+                    Some("test:denied") => json!({ "result": false }),
+                    Some("test:unauthorized") => json!({
+                        "result": { "allow": false, "reason": "not yours", "unauthorized": true }
+                    }),
+                    _ => json!({}),
+                })
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{addr}/v1/data/warg/allow").parse().unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn it_delegates_to_opa() {
+        let policy = OpaRecordPolicy::new(spawn_opa().await);
+        let record = test_record();
+
+        assert!(policy
+            .check(&"test:allowed".parse().unwrap(), &record)
+            .is_ok());
+        assert!(matches!(
+            policy.check(&"test:denied".parse().unwrap(), &record),
+            Err(RecordPolicyError::Rejection(_))
+        ));
+        assert!(matches!(
+            policy.check(&"test:unauthorized".parse().unwrap(), &record),
+            Err(RecordPolicyError::Unauthorized(reason)) if reason == "not yours"
+        ));
+        assert!(matches!(
+            policy.check(&"test:undefined".parse().unwrap(), &record),
+            Err(RecordPolicyError::Rejection(reason)) if reason.contains("undefined")
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_records_on_a_current_thread_runtime() {
+        let policy = OpaRecordPolicy::new(spawn_opa().await);
+        assert!(matches!(
+            policy.check(&"test:allowed".parse().unwrap(), &test_record()),
+            Err(RecordPolicyError::Rejection(reason)) if reason.contains("multi-threaded")
+        ));
+    }
+
+    #[test]
+    fn it_rejects_records_outside_a_runtime() {
+        let policy =
+            OpaRecordPolicy::new("http://127.0.0.1:8181/v1/data/warg/allow".parse().unwrap());
+        assert!(matches!(
+            policy.check(&"test:allowed".parse().unwrap(), &test_record()),
+            Err(RecordPolicyError::Rejection(reason)) if reason.contains("multi-threaded")
+        ));
+    }
+}
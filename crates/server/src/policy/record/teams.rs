@@ -0,0 +1,216 @@
+use super::{RecordPolicy, RecordPolicyError, RecordPolicyResult};
+use anyhow::{bail, Result};
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use warg_crypto::signing::KeyID;
+use warg_protocol::{package::PackageRecord, registry::PackageName, ProtoEnvelope};
+
+/// A named group of keys that maintains packages of a namespace.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Team {
+    /// The keys of the team's members.
+    pub keys: IndexSet<KeyID>,
+    /// The names of the packages of the namespace maintained by the team
+    /// (e.g. `http` for `example:http`).
+    ///
+    /// A `*` maintains every package of the namespace.
+    pub packages: Vec<String>,
+}
+
+impl Team {
+    fn maintains(&self, name: &PackageName) -> bool {
+        self.packages.iter().any(|p| p == "*" || p == name.name())
+    }
+}
+
+/// The maintainer teams of a [`TeamPolicy`], keyed by namespace and then by
+/// team name.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Teams {
+    /// The teams of each namespace.
+    pub namespaces: IndexMap<String, IndexMap<String, Team>>,
+}
+
+impl Teams {
+    /// Validates the namespaces, team names, and packages of the teams.
+    pub fn validate(&self) -> Result<()> {
+        for (namespace, teams) in &self.namespaces {
+            for (name, team) in teams {
+                validate_team(namespace, name, team)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the teams maintaining the given package.
+    fn maintainers<'a>(&'a self, name: &'a PackageName) -> impl Iterator<Item = &'a Team> + 'a {
+        self.namespaces
+            .get(name.namespace())
+            .into_iter()
+            .flat_map(|teams| teams.values())
+            .filter(move |team| team.maintains(name))
+    }
+}
+
+fn validate_team(namespace: &str, name: &str, team: &Team) -> Result<()> {
+    if !PackageName::is_valid_namespace(namespace) {
+        bail!("namespace `{namespace}` is not a valid kebab-cased string");
+    }
+
+    if !PackageName::is_valid_namespace(name) {
+        bail!("team name `{name}` is not a valid kebab-cased string");
+    }
+
+    for package in &team.packages {
+        if package != "*" && PackageName::new(format!("{namespace}:{package}")).is_err() {
+            bail!("team `{name}` maintains package `{package}`, which is not a valid package name");
+        }
+    }
+
+    Ok(())
+}
+
+/// A policy that ensures records of a package maintained by teams are
+/// signed by a member of one of the teams.
+///
+/// Packages not maintained by a team are unaffected. As team membership is
+/// checked for every record, a key removed from a team can no longer
+/// publish to the team's packages without revoking the key in each
+/// package log.
+///
+/// Clones of the policy share the same teams, allowing the teams to be
+/// updated while the policy is in use.
+#[derive(Debug, Default, Clone)]
+pub struct TeamPolicy {
+    teams: Arc<RwLock<Teams>>,
+}
+
+impl TeamPolicy {
+    /// Creates a new team policy with the given teams.
+    pub fn new(teams: Teams) -> Result<Self> {
+        teams.validate()?;
+        Ok(Self {
+            teams: Arc::new(RwLock::new(teams)),
+        })
+    }
+
+    /// Gets the current teams of the policy.
+    pub fn teams(&self) -> Teams {
+        self.teams.read().unwrap().clone()
+    }
+
+    /// Adds or replaces a team of the given namespace.
+    pub fn set_team(&self, namespace: &str, name: &str, team: Team) -> Result<()> {
+        validate_team(namespace, name, &team)?;
+        self.teams
+            .write()
+            .unwrap()
+            .namespaces
+            .entry(namespace.to_string())
+            .or_default()
+            .insert(name.to_string(), team);
+        Ok(())
+    }
+
+    /// Removes a team of the given namespace.
+    ///
+    /// Returns `false` if the namespace has no such team.
+    pub fn remove_team(&self, namespace: &str, name: &str) -> bool {
+        let mut teams = self.teams.write().unwrap();
+        let Some(namespace_teams) = teams.namespaces.get_mut(namespace) else {
+            return false;
+        };
+
+        let removed = namespace_teams.shift_remove(name).is_some();
+        if namespace_teams.is_empty() {
+            teams.namespaces.shift_remove(namespace);
+        }
+
+        removed
+    }
+
+    fn key_authorized(&self, key: &KeyID, name: &PackageName) -> bool {
+        let teams = self.teams.read().unwrap();
+        let mut maintainers = teams.maintainers(name).peekable();
+        maintainers.peek().is_none() || maintainers.any(|team| team.keys.contains(key))
+    }
+}
+
+impl RecordPolicy for TeamPolicy {
+    fn check(
+        &self,
+        name: &PackageName,
+        record: &ProtoEnvelope<PackageRecord>,
+    ) -> RecordPolicyResult<()> {
+        let key = record.key_id();
+        if !self.key_authorized(key, name) {
+            return Err(RecordPolicyError::Unauthorized(format!(
+                "key id `{key}` is not a member of a team maintaining package `{name}`"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_authorizes_team_members() -> Result<()> {
+        let core_key = KeyID::from("core-key".to_string());
+        let http_key = KeyID::from("http-key".to_string());
+        let other_key = KeyID::from("other-key".to_string());
+        let name = |s: &str| PackageName::new(s).unwrap();
+
+        let policy = TeamPolicy::new(Teams::default())?;
+        policy.set_team(
+            "example",
+            "core",
+            Team {
+                keys: [core_key.clone()].into(),
+                packages: vec!["*".into()],
+            },
+        )?;
+        policy.set_team(
+            "example",
+            "http",
+            Team {
+                keys: [http_key.clone()].into(),
+                packages: vec!["http".into()],
+            },
+        )?;
+
+        assert!(policy.key_authorized(&core_key, &name("example:http")));
+        assert!(policy.key_authorized(&http_key, &name("example:http")));
+        assert!(policy.key_authorized(&core_key, &name("example:io")));
+        assert!(!policy.key_authorized(&http_key, &name("example:io")));
+        assert!(!policy.key_authorized(&other_key, &name("example:http")));
+        assert!(policy.key_authorized(&other_key, &name("other:http")));
+
+        assert!(policy.remove_team("example", "core"));
+        assert!(!policy.remove_team("example", "core"));
+        assert!(policy.key_authorized(&other_key, &name("example:io")));
+        assert!(!policy.key_authorized(&core_key, &name("example:http")));
+
+        assert!(policy
+            .set_team("example", "Not Kebab", Team::default())
+            .is_err());
+        assert!(policy
+            .set_team(
+                "example",
+                "core",
+                Team {
+                    packages: vec!["not a name".into()],
+                    ..Default::default()
+                }
+            )
+            .is_err());
+        Ok(())
+    }
+}
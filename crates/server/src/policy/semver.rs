@@ -0,0 +1,151 @@
+//! Module for checking the semver compatibility of released interfaces.
+
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use serde::{Deserialize, Serialize};
+use warg_protocol::{
+    interface::{ContentInterfaces, InterfaceChange},
+    registry::PackageName,
+    Version,
+};
+
+/// The action taken for a release that makes breaking interface changes
+/// under a non-major version bump.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverAction {
+    /// The record of the release is rejected.
+    #[default]
+    Reject,
+    /// The breaking changes are logged, but the release is published.
+    Warn,
+}
+
+/// A policy that checks the interfaces of each release against the
+/// interfaces of the previous release of the package.
+///
+/// A release may only make breaking interface changes when the leftmost
+/// non-zero component of its version is bumped; see
+/// [`allows_breaking_changes`](warg_protocol::interface::allows_breaking_changes).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SemverPolicy {
+    /// The action taken for incompatible releases.
+    pub action: SemverAction,
+    /// The namespaces whose releases are checked.
+    ///
+    /// If empty, releases of every namespace are checked.
+    pub namespaces: IndexSet<String>,
+}
+
+impl SemverPolicy {
+    /// Creates a new semver policy with the given action.
+    pub fn new(action: SemverAction) -> Self {
+        Self {
+            action,
+            namespaces: IndexSet::new(),
+        }
+    }
+
+    /// Restricts the policy to releases of the given namespace.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespaces.insert(namespace.into());
+        self
+    }
+
+    /// Validates the namespaces of the policy.
+    pub fn validate(&self) -> Result<()> {
+        for namespace in &self.namespaces {
+            if !PackageName::is_valid_namespace(namespace) {
+                bail!("namespace `{namespace}` is not a valid kebab-cased string");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Determines if releases of the given package are checked.
+    pub fn applies_to(&self, name: &PackageName) -> bool {
+        self.namespaces.is_empty() || self.namespaces.contains(name.namespace())
+    }
+
+    /// Checks the interfaces of a release against the interfaces of the
+    /// previous release.
+    ///
+    /// Returns a description of the incompatible changes, if any.
+    pub fn check(
+        &self,
+        previous_version: &Version,
+        previous: &ContentInterfaces,
+        version: &Version,
+        interfaces: &ContentInterfaces,
+    ) -> Option<String> {
+        let changes = previous.incompatible_changes(previous_version, interfaces, version);
+        if changes.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "version {version} makes breaking interface changes from version {previous_version} without a major version bump: {changes}",
+            changes = changes
+                .iter()
+                .map(InterfaceChange::to_string)
+                .collect::<Vec<_>>()
+                .join("; ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use warg_protocol::interface::InterfaceSignature;
+
+    fn interfaces(functions: &[(&str, &str)]) -> ContentInterfaces {
+        ContentInterfaces {
+            imports: BTreeMap::new(),
+            exports: [(
+                "example:pkg/api".to_string(),
+                InterfaceSignature {
+                    types: BTreeMap::new(),
+                    functions: functions
+                        .iter()
+                        .map(|(name, sig)| (name.to_string(), sig.to_string()))
+                        .collect(),
+                },
+            )]
+            .into(),
+        }
+    }
+
+    #[test]
+    fn it_checks_breaking_changes() -> Result<()> {
+        let policy: SemverPolicy = toml::from_str(
+            r#"
+            action = "warn"
+            namespaces = ["example"]
+            "#,
+        )?;
+        policy.validate()?;
+        assert_eq!(policy.action, SemverAction::Warn);
+        assert!(policy.applies_to(&PackageName::new("example:pkg")?));
+        assert!(!policy.applies_to(&PackageName::new("other:pkg")?));
+        assert!(SemverPolicy::default().applies_to(&PackageName::new("other:pkg")?));
+
+        let old = interfaces(&[("f", "func()")]);
+        let added = interfaces(&[("f", "func()"), ("g", "func()")]);
+        let removed = interfaces(&[]);
+
+        let v1 = "1.0.0".parse()?;
+        assert_eq!(policy.check(&v1, &old, &"1.1.0".parse()?, &added), None);
+        assert!(policy
+            .check(&v1, &old, &"1.1.0".parse()?, &removed)
+            .is_some_and(|reason| reason.contains("function `f` was removed")));
+        assert_eq!(policy.check(&v1, &old, &"2.0.0".parse()?, &removed), None);
+
+        let invalid = SemverPolicy::new(SemverAction::Reject).with_namespace("Not Kebab");
+        assert!(invalid.validate().is_err());
+        Ok(())
+    }
+}
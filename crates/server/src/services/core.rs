@@ -1,4 +1,5 @@
 use std::{
+    collections::HashSet,
     sync::Arc,
     time::{Duration, SystemTime},
 };
@@ -7,7 +8,7 @@ use futures::{pin_mut, StreamExt};
 use indexmap::IndexMap;
 use thiserror::Error;
 use tokio::{
-    sync::{mpsc, RwLock},
+    sync::{mpsc, oneshot, watch, RwLock},
     task::JoinHandle,
     time::MissedTickBehavior,
 };
@@ -17,15 +18,16 @@ use warg_crypto::{
 };
 use warg_protocol::{
     operator,
+    package::{self, PackageSnapshot},
     registry::{
-        Checkpoint, LogId, LogLeaf, MapLeaf, RecordId, RegistryIndex, RegistryLen,
+        Checkpoint, LogId, LogLeaf, MapLeaf, PackageName, RecordId, RegistryIndex, RegistryLen,
         TimestampedCheckpoint,
     },
-    ProtoEnvelope, SerdeEnvelope,
+    ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
 };
 use warg_transparency::{
     log::{LogBuilder, LogData, LogProofBundle, Node, VecLog},
-    map::{Map, MapProofBundle},
+    map::{Map, MapMultiProof, MapProofBundle, Proof as MapProof},
 };
 
 use crate::datastore::{DataStore, DataStoreError};
@@ -36,6 +38,12 @@ pub struct CoreService<Digest: SupportedDigest = Sha256> {
 
     // Channel sender used by `submit_package_record` to serialize submissions.
     submit_entry_tx: mpsc::Sender<LogLeaf>,
+
+    // Channel sender used by `create_checkpoint` to request a checkpoint.
+    checkpoint_tx: mpsc::Sender<oneshot::Sender<Checkpoint>>,
+
+    // The interval at which checkpoints are created.
+    checkpoint_interval: Duration,
 }
 
 impl<Digest: SupportedDigest> CoreService<Digest> {
@@ -53,21 +61,26 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
             operator_key,
             store,
             state: Default::default(),
+            record_updates: watch::Sender::new(()),
+            checkpoints: watch::Sender::new(0),
         };
         inner.initialize(namespaces).await?;
 
         // Spawn state update task
         let inner = Arc::new(inner);
         let (submit_entry_tx, submit_entry_rx) = tokio::sync::mpsc::channel(4);
-        let handle = tokio::spawn(
-            inner
-                .clone()
-                .process_state_updates(submit_entry_rx, checkpoint_interval),
-        );
+        let (checkpoint_tx, checkpoint_rx) = tokio::sync::mpsc::channel(4);
+        let handle = tokio::spawn(inner.clone().process_state_updates(
+            submit_entry_rx,
+            checkpoint_rx,
+            checkpoint_interval,
+        ));
 
         let svc = Self {
             inner,
             submit_entry_tx,
+            checkpoint_tx,
+            checkpoint_interval,
         };
         Ok((svc, handle))
     }
@@ -114,6 +127,30 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
         log_length: RegistryLen,
         entries: &[RegistryIndex],
     ) -> Result<MapProofBundle<Digest, LogId, MapLeaf>, CoreServiceError> {
+        Ok(MapProofBundle::bundle(
+            self.map_proofs(log_length, entries).await?,
+        ))
+    }
+
+    /// Constructs a map multi-proof for the given entries at the given map tree root.
+    ///
+    /// Peer hashes shared by the inclusion proofs of the entries are only
+    /// included once.
+    pub async fn map_multi_proof(
+        &self,
+        log_length: RegistryLen,
+        entries: &[RegistryIndex],
+    ) -> Result<MapMultiProof<Digest, LogId, MapLeaf>, CoreServiceError> {
+        Ok(MapMultiProof::new(
+            self.map_proofs(log_length, entries).await?,
+        ))
+    }
+
+    async fn map_proofs(
+        &self,
+        log_length: RegistryLen,
+        entries: &[RegistryIndex],
+    ) -> Result<Vec<MapProof<Digest, LogId, MapLeaf>>, CoreServiceError> {
         let state = self.inner.state.read().await;
 
         let (map_root, map) = state
@@ -128,7 +165,7 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
             .await
             .map_err(CoreServiceError::DataStore)?;
 
-        let proofs = indexes
+        indexes
             .iter()
             .map(|log_leaf| {
                 let LogLeaf { log_id, record_id } = log_leaf;
@@ -150,9 +187,29 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
 
                 Ok(proof)
             })
-            .collect::<Result<Vec<_>, CoreServiceError>>()?;
+            .collect::<Result<Vec<_>, CoreServiceError>>()
+    }
 
-        Ok(MapProofBundle::bundle(proofs))
+    /// Gets the interval at which the service creates checkpoints.
+    pub fn checkpoint_interval(&self) -> Duration {
+        self.checkpoint_interval
+    }
+
+    /// Subscribes to notifications of record state changes.
+    ///
+    /// The receiver is notified whenever a submitted record is processed or
+    /// a checkpoint is stored; receivers should then check the state of the
+    /// records they are interested in.
+    pub fn subscribe_record_updates(&self) -> watch::Receiver<()> {
+        self.inner.record_updates.subscribe()
+    }
+
+    /// Subscribes to notifications of stored checkpoints.
+    ///
+    /// The receiver holds the log length of the latest stored checkpoint and
+    /// is notified only when a checkpoint of a new log length is stored.
+    pub fn subscribe_checkpoints(&self) -> watch::Receiver<RegistryLen> {
+        self.inner.checkpoints.subscribe()
     }
 
     /// Gets the data store associated with the transparency service.
@@ -160,6 +217,211 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
         self.inner.store.as_ref()
     }
 
+    /// Computes the state of a package log as of the latest checkpoint.
+    ///
+    /// Returns `DataStoreError::LogNotFound` if the package has no
+    /// published records.
+    pub async fn package_state(
+        &self,
+        name: &PackageName,
+    ) -> Result<package::LogState, CoreServiceError> {
+        self.package_log_state(&LogId::package_log::<Digest>(name))
+            .await
+    }
+
+    /// Computes the state of the package log with the given identifier as
+    /// of the latest checkpoint.
+    ///
+    /// Returns `DataStoreError::LogNotFound` if the package has no
+    /// published records.
+    pub async fn package_log_state(
+        &self,
+        log_id: &LogId,
+    ) -> Result<package::LogState, CoreServiceError> {
+        let (_, state, _) = self.validate_package_log(log_id, None, |_| {}).await?;
+        Ok(state)
+    }
+
+    /// Gets the records of the package log with the given identifier that
+    /// initialize the log or grant or revoke permissions of its keys, as of
+    /// the latest checkpoint.
+    ///
+    /// Returns `DataStoreError::LogNotFound` if the package has no
+    /// published records.
+    pub async fn package_key_records(
+        &self,
+        log_id: &LogId,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, CoreServiceError> {
+        let mut records = Vec::new();
+        self.validate_package_log(log_id, None, |record| {
+            if record.envelope.as_ref().entries.iter().any(|entry| {
+                matches!(
+                    entry,
+                    package::PackageEntry::Init { .. }
+                        | package::PackageEntry::GrantFlat { .. }
+                        | package::PackageEntry::RevokeFlat { .. }
+                )
+            }) {
+                records.push(record.clone());
+            }
+        })
+        .await?;
+        Ok(records)
+    }
+
+    /// Computes a snapshot of the package log with the given identifier as
+    /// of the checkpoint with the given log length, or the latest checkpoint
+    /// if not given, signed by the operator key.
+    ///
+    /// Returns `DataStoreError::LogNotFound` if the package has no
+    /// published records.
+    pub async fn package_snapshot(
+        &self,
+        log_id: &LogId,
+        log_length: Option<RegistryLen>,
+    ) -> Result<SerdeEnvelope<PackageSnapshot>, CoreServiceError> {
+        let (log_length, state, head_index) = self
+            .validate_package_log(log_id, log_length, |_| {})
+            .await?;
+        let snapshot = match head_index {
+            Some(index) => PackageSnapshot::new(log_id.clone(), log_length, index, &state)
+                .map_err(|e| CoreServiceError::SnapshotFailure(e.into()))?,
+            None => None,
+        }
+        .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        SerdeEnvelope::signed_contents(&self.inner.operator_key, snapshot)
+            .map_err(|e| CoreServiceError::SnapshotFailure(e.into()))
+    }
+
+    /// Validates the records of a package log as of the checkpoint with the
+    /// given log length, or the latest checkpoint if not given.
+    ///
+    /// Each record is passed to `visit` once it has been validated.
+    ///
+    /// Returns the log length of the checkpoint, the state of the package
+    /// log, and the registry index of its head record.
+    async fn validate_package_log(
+        &self,
+        log_id: &LogId,
+        log_length: Option<RegistryLen>,
+        mut visit: impl FnMut(&PublishedProtoEnvelope<package::PackageRecord>),
+    ) -> Result<(RegistryLen, package::LogState, Option<RegistryIndex>), CoreServiceError> {
+        let store = self.store();
+        let log_length = match log_length {
+            Some(log_length) => log_length,
+            None => {
+                store
+                    .get_latest_checkpoint()
+                    .await?
+                    .as_ref()
+                    .checkpoint
+                    .log_length
+            }
+        };
+
+        let mut state = package::LogState::new();
+        let mut head_index = None;
+        let mut since = None;
+        loop {
+            let records = store
+                .get_package_records(log_id, log_length, since.as_ref(), u16::MAX)
+                .await?;
+            let more = records.len() == u16::MAX as usize;
+
+            for record in &records {
+                state = state
+                    .validate(&record.envelope)
+                    .map_err(DataStoreError::from)?;
+                head_index = Some(record.registry_index);
+                visit(record);
+            }
+
+            match records.last() {
+                Some(last) if more => {
+                    since = Some(RecordId::package_record::<Digest>(&last.envelope));
+                }
+                _ => break,
+            }
+        }
+
+        Ok((log_length, state, head_index))
+    }
+
+    /// Determines if content with the given digest is under an embargo.
+    ///
+    /// Embargoed content is not served and releases of it are not listed.
+    pub async fn is_embargoed(&self, digest: &AnyHash) -> Result<bool, DataStoreError> {
+        Ok(self
+            .store()
+            .get_content_embargo(digest)
+            .await?
+            .is_some_and(|until| until > SystemTime::now()))
+    }
+
+    /// Gets the releases of a package state that may be listed, in package
+    /// log order.
+    ///
+    /// Releases of embargoed content are omitted until the embargo passes.
+    pub async fn listed_releases<'a>(
+        &self,
+        state: &'a package::LogState,
+    ) -> Result<Vec<&'a package::Release>, DataStoreError> {
+        let mut releases = Vec::new();
+        for release in state.releases() {
+            match release.content() {
+                Some(content) if self.is_embargoed(content).await? => {}
+                _ => releases.push(release),
+            }
+        }
+
+        Ok(releases)
+    }
+
+    /// Lists the names of the packages that may be listed, in ascending
+    /// order.
+    ///
+    /// Packages of suspended namespaces are omitted until the namespace is
+    /// restored.
+    pub async fn listed_package_names(
+        &self,
+        after: Option<&PackageName>,
+        limit: u16,
+    ) -> Result<Vec<PackageName>, DataStoreError> {
+        let suspended = self
+            .store()
+            .list_namespace_suspensions()
+            .await?
+            .into_iter()
+            .map(|s| s.namespace)
+            .collect::<HashSet<_>>();
+        if suspended.is_empty() {
+            return self.store().list_package_names(after, limit).await;
+        }
+
+        // Keep reading pages until enough names remain after omitting
+        // suspended namespaces
+        let mut names = Vec::new();
+        let mut after = after.cloned();
+        loop {
+            let page = self
+                .store()
+                .list_package_names(after.as_ref(), limit)
+                .await?;
+            let last = page.len() < limit as usize;
+            after = page.last().cloned();
+            names.extend(
+                page.into_iter()
+                    .filter(|name| !suspended.contains(name.namespace())),
+            );
+
+            if last || names.len() >= limit as usize {
+                names.truncate(limit as usize);
+                return Ok(names);
+            }
+        }
+    }
+
     /// Submits a package record to be processed.
     pub async fn submit_package_record(&self, log_id: LogId, record_id: RecordId) {
         self.submit_entry_tx
@@ -167,6 +429,16 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
             .await
             .unwrap()
     }
+
+    /// Creates a checkpoint of the records processed so far without waiting
+    /// for the checkpoint interval.
+    ///
+    /// Returns the checkpoint once it has been stored.
+    pub async fn create_checkpoint(&self) -> Checkpoint {
+        let (tx, rx) = oneshot::channel();
+        self.checkpoint_tx.send(tx).await.unwrap();
+        rx.await.unwrap()
+    }
 }
 
 struct Inner<Digest: SupportedDigest> {
@@ -178,6 +450,12 @@ struct Inner<Digest: SupportedDigest> {
 
     // In-memory transparency state.
     state: RwLock<State<Digest>>,
+
+    // Notifies subscribers when the state of records may have changed.
+    record_updates: watch::Sender<()>,
+
+    // Holds the log length of the latest stored checkpoint.
+    checkpoints: watch::Sender<RegistryLen>,
 }
 
 impl<Digest: SupportedDigest> Inner<Digest> {
@@ -290,6 +568,7 @@ impl<Digest: SupportedDigest> Inner<Digest> {
     async fn process_state_updates(
         self: Arc<Self>,
         mut submit_entry_rx: mpsc::Receiver<LogLeaf>,
+        mut checkpoint_rx: mpsc::Receiver<oneshot::Sender<Checkpoint>>,
         checkpoint_interval: Duration,
     ) {
         let mut checkpoint = self
@@ -299,6 +578,7 @@ impl<Digest: SupportedDigest> Inner<Digest> {
             .unwrap()
             .into_contents()
             .checkpoint;
+        self.checkpoints.send_replace(checkpoint.log_length);
 
         let mut checkpoint_interval = tokio::time::interval(checkpoint_interval);
         checkpoint_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
@@ -310,6 +590,11 @@ impl<Digest: SupportedDigest> Inner<Digest> {
                     None => break, // Channel closed
                 },
                 _ = checkpoint_interval.tick() => self.update_checkpoint(&mut checkpoint).await,
+                Some(reply) = checkpoint_rx.recv() => {
+                    self.update_checkpoint(&mut checkpoint).await;
+                    // The requester may have stopped waiting for the checkpoint
+                    let _ = reply.send(checkpoint.clone());
+                }
             }
         }
     }
@@ -342,10 +627,11 @@ impl<Digest: SupportedDigest> Inner<Digest> {
                     tracing::error!("failed to validate package record `{record_id}`: {e}");
                 }
             }
-            return;
+        } else {
+            state.push_entry(entry.clone());
         }
 
-        state.push_entry(entry.clone());
+        self.record_updates.send_replace(());
     }
 
     // Store a checkpoint including the given new entries
@@ -359,8 +645,18 @@ impl<Digest: SupportedDigest> Inner<Digest> {
             }
         }
 
-        if let Err(err) = self.sign_and_store_checkpoint(checkpoint.clone()).await {
-            tracing::error!("Error storing checkpoint {checkpoint:?}: {err:?}");
+        match self.sign_and_store_checkpoint(checkpoint.clone()).await {
+            Ok(()) => {
+                self.record_updates.send_replace(());
+                self.checkpoints.send_if_modified(|log_length| {
+                    let modified = *log_length != checkpoint.log_length;
+                    *log_length = checkpoint.log_length;
+                    modified
+                });
+            }
+            Err(err) => {
+                tracing::error!("Error storing checkpoint {checkpoint:?}: {err:?}");
+            }
         }
     }
 
@@ -432,4 +728,6 @@ pub enum CoreServiceError {
     DataStore(#[from] DataStoreError),
     #[error("initialization failed: {0}")]
     InitializationFailure(String),
+    #[error("failed to create package snapshot: {0}")]
+    SnapshotFailure(anyhow::Error),
 }
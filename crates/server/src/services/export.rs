@@ -0,0 +1,398 @@
+use super::CoreService;
+use anyhow::{Context, Result};
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tempfile::NamedTempFile;
+use tokio::{sync::Mutex, task::JoinHandle};
+use warg_api::{
+    v1::{
+        fetch::PublishedRecord,
+        proof::{ConsistencyResponse, InclusionResponse},
+        static_registry::{self, StaticLog, StaticPackageNames},
+    },
+    DiscoveryDocument, RegistryLayout, DISCOVERY_PATH,
+};
+use warg_crypto::{
+    hash::{AnyHash, HashAlgorithm, Sha256},
+    signing::SignatureAlgorithm,
+};
+use warg_protocol::{
+    registry::{LogId, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint},
+    Record, SerdeEnvelope,
+};
+
+/// The number of records or log leafs read from the data store at a time.
+const PAGE_SIZE: u16 = 1000;
+
+/// The result of a static registry export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticExport {
+    /// The log length of the exported checkpoint.
+    pub log_length: RegistryLen,
+    /// The number of logs exported, including the operator log.
+    pub logs: usize,
+    /// The number of content files exported.
+    pub content: usize,
+}
+
+/// Exports the registry as a static registry.
+///
+/// A static registry is a read-only set of files laid out as described by
+/// [`static_registry`] that may be served by any static host; clients verify
+/// it with the proofs precomputed for the latest checkpoint.
+///
+/// The checkpoint is written last, so a client never reads a checkpoint
+/// whose logs have not been written. Content under an embargo is not
+/// exported. Concurrent exports are run one at a time.
+pub struct StaticExporter {
+    core: CoreService,
+    files_dir: PathBuf,
+    output_dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl StaticExporter {
+    /// Creates an exporter of the registry of the given core service and
+    /// content directory to the given output directory.
+    pub fn new(core: CoreService, content_dir: &Path, output_dir: PathBuf) -> Self {
+        Self {
+            core,
+            files_dir: content_dir.join("files"),
+            output_dir,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Gets the directory the registry is exported to.
+    pub fn output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    /// Starts exporting the registry whenever a checkpoint of a new log
+    /// length is stored, so that the export always reflects the latest
+    /// checkpoint.
+    ///
+    /// The registry is also exported once when started.
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut checkpoints = self.core.subscribe_checkpoints();
+
+            loop {
+                match self.export().await {
+                    Ok(export) => tracing::info!(
+                        log_length = export.log_length,
+                        logs = export.logs,
+                        content = export.content,
+                        "static export complete"
+                    ),
+                    Err(e) => tracing::error!("static export failed: {e:#}"),
+                }
+
+                if checkpoints.changed().await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Exports the latest checkpoint of the registry.
+    pub async fn export(&self) -> Result<StaticExport> {
+        let _lock = self.lock.lock().await;
+        let checkpoint = self.core.store().get_latest_checkpoint().await?;
+        let log_length = checkpoint.as_ref().checkpoint.log_length;
+
+        let heads = self.log_heads(log_length).await?;
+        let operator_log_id = LogId::operator_log::<Sha256>();
+
+        let mut digests = IndexSet::new();
+        for log_id in heads.keys() {
+            let records = if log_id == &operator_log_id {
+                self.operator_records(log_length).await?
+            } else {
+                self.package_records(log_id, log_length, &mut digests)
+                    .await?
+            };
+
+            let path = if log_id == &operator_log_id {
+                static_registry::OPERATOR_LOG_PATH.to_string()
+            } else {
+                static_registry::package_log(log_id)
+            };
+            self.write_json(&path, &StaticLog { records })?;
+        }
+
+        let package_ids = heads
+            .keys()
+            .filter(|log_id| *log_id != &operator_log_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        let packages = self
+            .core
+            .store()
+            .get_package_names(&package_ids)
+            .await?
+            .into_iter()
+            .filter_map(|(log_id, name)| Some((log_id, name?)))
+            .collect();
+        self.write_json(
+            static_registry::PACKAGE_NAMES_PATH,
+            &StaticPackageNames { packages },
+        )?;
+
+        for (log_id, index) in &heads {
+            let log = self
+                .core
+                .log_inclusion_proofs(log_length, &[*index])
+                .await?;
+            let map = self
+                .core
+                .map_inclusion_proofs(log_length, &[*index])
+                .await?;
+            self.write_json(
+                &static_registry::inclusion_proof(log_id),
+                &InclusionResponse {
+                    log: log.encode(),
+                    map: map.encode(),
+                },
+            )?;
+        }
+
+        self.write_consistency_proofs(log_length).await?;
+
+        let mut content = 0;
+        for digest in &digests {
+            if self.export_content(digest).await? {
+                content += 1;
+            }
+        }
+
+        self.write_json(
+            DISCOVERY_PATH,
+            &DiscoveryDocument {
+                hash_algorithms: vec![HashAlgorithm::Sha256.to_string()],
+                signature_algorithms: vec![SignatureAlgorithm::EcdsaP256.to_string()],
+                layout: RegistryLayout::Static,
+                ..Default::default()
+            },
+        )?;
+        self.write_json::<SerdeEnvelope<TimestampedCheckpoint>>(
+            static_registry::CHECKPOINT_PATH,
+            &checkpoint,
+        )?;
+
+        Ok(StaticExport {
+            log_length,
+            logs: heads.len(),
+            content,
+        })
+    }
+
+    /// Gets the registry index of the head of each log included in the
+    /// checkpoint of the given log length, in order of the first record of
+    /// each log.
+    async fn log_heads(&self, log_length: RegistryLen) -> Result<IndexMap<LogId, RegistryIndex>> {
+        let mut heads = IndexMap::new();
+        let mut start = 0;
+        while start < log_length {
+            let leafs = self
+                .core
+                .store()
+                .get_log_leafs_starting_with_registry_index(
+                    start,
+                    (log_length - start).min(PAGE_SIZE as usize),
+                )
+                .await?;
+            let Some((last, _)) = leafs.last() else {
+                break;
+            };
+
+            start = last + 1;
+            for (index, leaf) in leafs {
+                heads.insert(leaf.log_id, index);
+            }
+        }
+
+        Ok(heads)
+    }
+
+    async fn operator_records(&self, log_length: RegistryLen) -> Result<Vec<PublishedRecord>> {
+        let log_id = LogId::operator_log::<Sha256>();
+        let mut records = Vec::new();
+        let mut since = None;
+        loop {
+            let page = self
+                .core
+                .store()
+                .get_operator_records(&log_id, log_length, since.as_ref(), PAGE_SIZE)
+                .await?;
+            let last = page.len() < PAGE_SIZE as usize;
+            for envelope in page {
+                let record_id = RecordId::operator_record::<Sha256>(&envelope.envelope);
+                records.push(PublishedRecord {
+                    envelope: envelope.into(),
+                    fetch_token: record_id.to_string(),
+                });
+                since = Some(record_id);
+            }
+
+            if last {
+                return Ok(records);
+            }
+        }
+    }
+
+    async fn package_records(
+        &self,
+        log_id: &LogId,
+        log_length: RegistryLen,
+        digests: &mut IndexSet<AnyHash>,
+    ) -> Result<Vec<PublishedRecord>> {
+        let mut records = Vec::new();
+        let mut since = None;
+        loop {
+            let page = self
+                .core
+                .store()
+                .get_package_records(log_id, log_length, since.as_ref(), PAGE_SIZE)
+                .await?;
+            let last = page.len() < PAGE_SIZE as usize;
+            for envelope in page {
+                digests.extend(envelope.envelope.as_ref().contents().into_iter().cloned());
+
+                let record_id = RecordId::package_record::<Sha256>(&envelope.envelope);
+                records.push(PublishedRecord {
+                    envelope: envelope.into(),
+                    fetch_token: record_id.to_string(),
+                });
+                since = Some(record_id);
+            }
+
+            if last {
+                return Ok(records);
+            }
+        }
+    }
+
+    /// Writes a consistency proof from every previously exported checkpoint
+    /// to the checkpoint of the given log length.
+    ///
+    /// Clients of a static registry only see exported checkpoints, so these
+    /// are the only checkpoints consistency must be proven from.
+    async fn write_consistency_proofs(&self, log_length: RegistryLen) -> Result<()> {
+        for from in self.exported_log_lengths()? {
+            if from == 0 || from >= log_length {
+                continue;
+            }
+
+            let proof = self.core.log_consistency_proof(from, log_length).await?;
+            self.write_json(
+                &static_registry::consistency_proof(from),
+                &ConsistencyResponse {
+                    proof: proof.encode(),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the log lengths of the checkpoints previously exported to the
+    /// output directory.
+    fn exported_log_lengths(&self) -> Result<BTreeSet<RegistryLen>> {
+        let mut lengths = BTreeSet::new();
+
+        let checkpoint = self.output_dir.join(static_registry::CHECKPOINT_PATH);
+        if checkpoint.is_file() {
+            let checkpoint: SerdeEnvelope<TimestampedCheckpoint> =
+                serde_json::from_slice(&fs::read(&checkpoint)?).with_context(|| {
+                    format!(
+                        "failed to parse exported checkpoint `{path}`",
+                        path = checkpoint.display()
+                    )
+                })?;
+            lengths.insert(checkpoint.as_ref().checkpoint.log_length);
+        }
+
+        // The proofs of earlier exports are named by the log length they
+        // prove consistency from
+        let proofs = self
+            .output_dir
+            .join(static_registry::CONSISTENCY_PROOFS_DIR);
+        if proofs.is_dir() {
+            for entry in fs::read_dir(&proofs)? {
+                let path = entry?.path();
+                if let Some(length) = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.parse().ok())
+                {
+                    lengths.insert(length);
+                }
+            }
+        }
+
+        Ok(lengths)
+    }
+
+    /// Copies a content file to the export.
+    ///
+    /// Returns `false` if the content is missing or under an embargo.
+    async fn export_content(&self, digest: &AnyHash) -> Result<bool> {
+        if self.core.is_embargoed(digest).await? {
+            return Ok(false);
+        }
+
+        let source = self.files_dir.join(digest.to_string().replace(':', "-"));
+        if !source.is_file() {
+            tracing::warn!("content `{digest}` is missing and was not exported");
+            return Ok(false);
+        }
+
+        let path = self.output_dir.join(static_registry::content(digest));
+        if !path.is_file() {
+            create_parent(&path)?;
+            fs::copy(&source, &path).with_context(|| {
+                format!("failed to copy content to `{path}`", path = path.display())
+            })?;
+        }
+
+        Ok(true)
+    }
+
+    /// Writes a file of the export as JSON.
+    ///
+    /// The file is written to a temporary file that is then renamed, so a
+    /// static host never serves a partially written file.
+    fn write_json<T: Serialize>(&self, path: &str, value: &T) -> Result<()> {
+        let path = self.output_dir.join(path);
+        let parent = create_parent(&path)?;
+
+        let file = NamedTempFile::new_in(parent)?;
+        serde_json::to_writer(file.as_file(), value)?;
+        file.persist(&path).with_context(|| {
+            format!(
+                "failed to write static registry file `{path}`",
+                path = path.display()
+            )
+        })?;
+        Ok(())
+    }
+}
+
+fn create_parent(path: &Path) -> Result<&Path> {
+    let parent = path.parent().unwrap();
+    fs::create_dir_all(parent).with_context(|| {
+        format!(
+            "failed to create directory `{parent}`",
+            parent = parent.display()
+        )
+    })?;
+    Ok(parent)
+}
@@ -0,0 +1,301 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+use warg_api::v1::paths;
+use warg_crypto::{signing, Encode, Signable};
+use warg_protocol::{
+    operator::NamespaceState,
+    registry::{RegistryLen, TimestampedCheckpoint},
+    SerdeEnvelope,
+};
+
+/// The timeout of requests to upstream registries.
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An upstream registry that namespaces may be imported from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Upstream {
+    /// The registry named by namespace-import operator records (e.g.
+    /// `registry.example.com`).
+    pub registry: String,
+    /// The URL of the registry; defaults to `https://<registry>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<Url>,
+    /// The pinned operator keys that may sign the registry's checkpoints.
+    pub operator_keys: Vec<signing::PublicKey>,
+}
+
+impl Upstream {
+    /// Creates an upstream registry with the given pinned operator key.
+    pub fn new(registry: impl Into<String>, operator_key: signing::PublicKey) -> Self {
+        Self {
+            registry: registry.into(),
+            url: None,
+            operator_keys: vec![operator_key],
+        }
+    }
+
+    /// Sets the URL of the registry.
+    pub fn with_url(mut self, url: Url) -> Self {
+        self.url = Some(url);
+        self
+    }
+
+    /// Pins another operator key of the registry.
+    pub fn with_operator_key(mut self, key: signing::PublicKey) -> Self {
+        self.operator_keys.push(key);
+        self
+    }
+
+    /// Gets the URL of the registry.
+    pub fn url(&self) -> Result<Url> {
+        match &self.url {
+            Some(url) => Ok(url.clone()),
+            None => Ok(Url::parse(&format!(
+                "https://{registry}",
+                registry = self.registry
+            ))?),
+        }
+    }
+}
+
+/// The federation trust configuration of a registry.
+///
+/// Namespaces may only be imported from the configured upstream registries;
+/// the server refuses to start with a namespace-import operator record that
+/// names another registry.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FederationConfig {
+    /// The upstream registries that namespaces may be imported from.
+    pub upstreams: Vec<Upstream>,
+}
+
+/// The federation status of an imported namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FederationStatus {
+    /// The upstream registry served a checkpoint signed by a pinned key.
+    Healthy,
+    /// The namespace is imported from a registry that is not a configured
+    /// upstream.
+    Untrusted,
+    /// The upstream registry could not be reached.
+    Unreachable,
+    /// The upstream registry served a checkpoint that is not signed by a
+    /// pinned key.
+    InvalidCheckpoint,
+}
+
+/// The federation health of an imported namespace.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceHealth {
+    /// The imported namespace.
+    pub namespace: String,
+    /// The registry the namespace is imported from.
+    pub registry: String,
+    /// The federation status of the namespace.
+    pub status: FederationStatus,
+    /// The log length of the upstream registry's latest checkpoint, if it
+    /// was fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_length: Option<RegistryLen>,
+    /// A message describing an unhealthy status.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl FederationConfig {
+    /// Creates a federation configuration without upstream registries.
+    ///
+    /// Namespaces cannot be imported until an upstream is added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an upstream registry that namespaces may be imported from.
+    pub fn with_upstream(mut self, upstream: Upstream) -> Self {
+        self.upstreams.push(upstream);
+        self
+    }
+
+    /// Validates the upstream registries of the configuration.
+    pub fn validate(&self) -> Result<()> {
+        for (i, upstream) in self.upstreams.iter().enumerate() {
+            if upstream.registry.trim().is_empty() {
+                bail!("upstream registry name cannot be empty");
+            }
+            if upstream.operator_keys.is_empty() {
+                bail!(
+                    "upstream registry `{registry}` has no pinned operator keys",
+                    registry = upstream.registry
+                );
+            }
+            if self.upstreams[..i]
+                .iter()
+                .any(|other| other.registry.eq_ignore_ascii_case(&upstream.registry))
+            {
+                bail!(
+                    "upstream registry `{registry}` is configured more than once",
+                    registry = upstream.registry
+                );
+            }
+            upstream.url()?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the upstream registry with the given name.
+    pub fn upstream(&self, registry: &str) -> Option<&Upstream> {
+        self.upstreams
+            .iter()
+            .find(|upstream| upstream.registry.eq_ignore_ascii_case(registry))
+    }
+
+    /// Checks that the given namespaces are only imported from upstream
+    /// registries.
+    pub fn check_imports<'a>(
+        &self,
+        namespaces: impl IntoIterator<Item = (&'a str, &'a NamespaceState)>,
+    ) -> Result<()> {
+        for (namespace, state) in namespaces {
+            if let NamespaceState::Imported { registry } = state {
+                if self.upstream(registry).is_none() {
+                    bail!(
+                        "namespace `{namespace}` is imported from registry `{registry}`, which is not a trusted upstream"
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the federation health of a namespace imported from the given
+    /// registry.
+    pub async fn check_health(
+        &self,
+        client: &reqwest::Client,
+        namespace: &str,
+        registry: &str,
+    ) -> NamespaceHealth {
+        let mut health = NamespaceHealth {
+            namespace: namespace.to_string(),
+            registry: registry.to_string(),
+            status: FederationStatus::Untrusted,
+            log_length: None,
+            message: None,
+        };
+
+        let Some(upstream) = self.upstream(registry) else {
+            health.message = Some(format!("registry `{registry}` is not a trusted upstream"));
+            return health;
+        };
+
+        let checkpoint = match fetch_checkpoint(client, upstream).await {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                health.status = FederationStatus::Unreachable;
+                health.message = Some(format!("{e:#}"));
+                return health;
+            }
+        };
+
+        health.log_length = Some(checkpoint.as_ref().checkpoint.log_length);
+        match verify_checkpoint(upstream, &checkpoint) {
+            Ok(()) => health.status = FederationStatus::Healthy,
+            Err(e) => {
+                health.status = FederationStatus::InvalidCheckpoint;
+                health.message = Some(e.to_string());
+            }
+        }
+
+        health
+    }
+}
+
+async fn fetch_checkpoint(
+    client: &reqwest::Client,
+    upstream: &Upstream,
+) -> Result<SerdeEnvelope<TimestampedCheckpoint>> {
+    Ok(client
+        .get(upstream.url()?.join(paths::fetch_checkpoint())?)
+        .timeout(UPSTREAM_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+fn verify_checkpoint(
+    upstream: &Upstream,
+    checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+) -> Result<()> {
+    let key_id = checkpoint.key_id();
+    let Some(key) = upstream
+        .operator_keys
+        .iter()
+        .find(|key| &key.fingerprint() == key_id)
+    else {
+        bail!("the checkpoint is signed by key `{key_id}`, which is not pinned");
+    };
+
+    TimestampedCheckpoint::verify(key, &checkpoint.as_ref().encode(), checkpoint.signature())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warg_crypto::signing::PrivateKey;
+
+    fn key() -> signing::PublicKey {
+        PrivateKey::decode("ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk=".to_string())
+            .unwrap()
+            .public_key()
+    }
+
+    #[test]
+    fn it_checks_imports() -> Result<()> {
+        let config =
+            FederationConfig::new().with_upstream(Upstream::new("Upstream.example", key()));
+        config.validate()?;
+        assert_eq!(
+            config.upstream("upstream.example").unwrap().url()?.as_str(),
+            "https://upstream.example/"
+        );
+
+        let trusted = NamespaceState::Imported {
+            registry: "upstream.example".to_string(),
+        };
+        let untrusted = NamespaceState::Imported {
+            registry: "elsewhere.example".to_string(),
+        };
+        config.check_imports([("wasi", &trusted), ("test", &NamespaceState::Defined)])?;
+        assert!(config
+            .check_imports([("other", &untrusted)])
+            .unwrap_err()
+            .to_string()
+            .contains("not a trusted upstream"));
+
+        assert!(FederationConfig::new()
+            .with_upstream(Upstream {
+                registry: "upstream.example".to_string(),
+                url: None,
+                operator_keys: Vec::new(),
+            })
+            .validate()
+            .is_err());
+        assert!(config
+            .clone()
+            .with_upstream(Upstream::new("upstream.example", key()))
+            .validate()
+            .is_err());
+        Ok(())
+    }
+}
@@ -1,3 +1,18 @@
 mod core;
+mod export;
+mod federation;
+mod osv;
+mod retention;
+mod scrub;
+mod typosquat;
 
 pub use self::core::{CoreService, CoreServiceError};
+pub use self::export::{StaticExport, StaticExporter};
+pub use self::federation::{FederationConfig, FederationStatus, NamespaceHealth, Upstream};
+pub use self::osv::{
+    from_osv, to_osv, OsvAffected, OsvEvent, OsvPackage, OsvRange, OsvRecord, OsvReference,
+    OsvSpecific, OSV_ECOSYSTEM,
+};
+pub use self::retention::{RecordRetention, RetentionConfig};
+pub use self::scrub::{ContentScrubber, ScrubConfig, ScrubFinding, ScrubReport};
+pub use self::typosquat::{Similarity, TyposquatConfig};
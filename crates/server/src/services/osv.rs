@@ -0,0 +1,690 @@
+use crate::datastore::Advisory;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use indexmap::IndexMap;
+use semver::{Comparator, Op};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, time::SystemTime};
+use warg_api::v1::package::{AdvisorySeverity, VulnerabilityAdvisory};
+use warg_protocol::{registry::PackageName, Version, VersionReq};
+
+/// The OSV ecosystem of packages in the registry.
+///
+/// Affected packages of other ecosystems are ignored when importing.
+pub const OSV_ECOSYSTEM: &str = "Warg";
+
+/// The version of the OSV schema records are exported with.
+const OSV_SCHEMA_VERSION: &str = "1.6.0";
+
+/// A vulnerability record in the [OSV format](https://ossf.github.io/osv-schema/).
+///
+/// Only the fields used by the registry are represented; other fields are
+/// ignored when importing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsvRecord {
+    /// The version of the OSV schema of the record.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// The identifier of the vulnerability.
+    pub id: String,
+    /// The time the record was last modified, in RFC 3339 format.
+    pub modified: String,
+    /// The time the record was published, in RFC 3339 format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    /// Other identifiers of the vulnerability.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// A summary of the vulnerability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// The details of the vulnerability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// The packages affected by the vulnerability.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub affected: Vec<OsvAffected>,
+    /// References to more information about the vulnerability.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<OsvReference>,
+    /// Database specific information about the vulnerability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database_specific: Option<OsvSpecific>,
+}
+
+/// A package affected by an [`OsvRecord`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsvAffected {
+    /// The affected package.
+    pub package: OsvPackage,
+    /// The ranges of affected versions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranges: Vec<OsvRange>,
+    /// The affected versions, in addition to the ranges.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+    /// Ecosystem specific information about the affected package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecosystem_specific: Option<OsvSpecific>,
+    /// Database specific information about the affected package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database_specific: Option<OsvSpecific>,
+}
+
+/// A package of an [`OsvAffected`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsvPackage {
+    /// The ecosystem of the package.
+    pub ecosystem: String,
+    /// The name of the package.
+    pub name: String,
+}
+
+/// A range of affected versions of an [`OsvAffected`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsvRange {
+    /// The type of the range (e.g. `SEMVER`).
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The events of the range.
+    pub events: Vec<OsvEvent>,
+}
+
+/// An event of an [`OsvRange`]; exactly one field is expected to be set.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsvEvent {
+    /// The version in which the vulnerability was introduced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introduced: Option<String>,
+    /// The version in which the vulnerability was fixed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<String>,
+    /// The last version affected by the vulnerability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_affected: Option<String>,
+}
+
+/// A reference of an [`OsvRecord`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsvReference {
+    /// The type of the reference (e.g. `ADVISORY` or `WEB`).
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The URL of the reference.
+    pub url: String,
+}
+
+/// Specific information of an [`OsvRecord`] or [`OsvAffected`].
+///
+/// Only the severity is used, in the style of GitHub advisories (e.g.
+/// `HIGH` or `MODERATE`).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsvSpecific {
+    /// The severity of the vulnerability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+}
+
+/// Exports advisories as OSV records.
+///
+/// Advisories with the same identifier are exported as a single record
+/// affecting each of their packages.
+pub fn to_osv(advisories: &[Advisory]) -> Result<Vec<OsvRecord>> {
+    let mut records: IndexMap<&str, OsvRecord> = IndexMap::new();
+    for advisory in advisories {
+        let inner = &advisory.advisory;
+        let time = rfc3339(advisory.published_at);
+        let record = records
+            .entry(inner.id.as_str())
+            .or_insert_with(|| OsvRecord {
+                schema_version: Some(OSV_SCHEMA_VERSION.to_string()),
+                id: inner.id.clone(),
+                modified: time.clone(),
+                published: Some(time.clone()),
+                aliases: Vec::new(),
+                summary: Some(inner.summary.clone()),
+                details: None,
+                affected: Vec::new(),
+                references: Vec::new(),
+                database_specific: None,
+            });
+
+        // Advisories are listed in publication order
+        record.modified = time;
+        if let Some(url) = &inner.url {
+            if !record.references.iter().any(|r| &r.url == url) {
+                record.references.push(OsvReference {
+                    kind: "ADVISORY".to_string(),
+                    url: url.clone(),
+                });
+            }
+        }
+
+        record.affected.push(OsvAffected {
+            package: OsvPackage {
+                ecosystem: OSV_ECOSYSTEM.to_string(),
+                name: advisory.package.to_string(),
+            },
+            ranges: vec![OsvRange {
+                kind: "SEMVER".to_string(),
+                events: events(&inner.versions)
+                    .with_context(|| format!("failed to export advisory `{id}`", id = inner.id))?,
+            }],
+            versions: Vec::new(),
+            ecosystem_specific: None,
+            database_specific: Some(OsvSpecific {
+                severity: Some(osv_severity(inner.severity).to_string()),
+            }),
+        });
+    }
+
+    Ok(records.into_values().collect())
+}
+
+/// Imports the advisories of the registry's packages from an OSV record.
+///
+/// An affected package with more than one range of affected versions is
+/// imported as an advisory per range, with the identifiers suffixed by
+/// `#1`, `#2`, etc.
+///
+/// Advisories without a severity are imported with a medium severity.
+pub fn from_osv(record: &OsvRecord) -> Result<Vec<Advisory>> {
+    let summary = record
+        .summary
+        .clone()
+        .or_else(|| {
+            record
+                .details
+                .as_deref()
+                .and_then(|d| d.lines().next())
+                .map(str::to_string)
+        })
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| record.id.clone());
+    let url = ["ADVISORY", "WEB"]
+        .iter()
+        .find_map(|kind| record.references.iter().find(|r| r.kind == *kind))
+        .or_else(|| record.references.first())
+        .map(|r| r.url.clone());
+    let published_at = record
+        .published
+        .as_deref()
+        .map(parse_rfc3339)
+        .or_else(|| Some(parse_rfc3339(&record.modified)))
+        .transpose()?
+        .unwrap_or_else(SystemTime::now);
+
+    let mut advisories = Vec::new();
+    for affected in &record.affected {
+        if !affected
+            .package
+            .ecosystem
+            .eq_ignore_ascii_case(OSV_ECOSYSTEM)
+        {
+            continue;
+        }
+
+        let package = PackageName::new(affected.package.name.as_str())
+            .with_context(|| format!("invalid package name in OSV record `{}`", record.id))?;
+        let severity = [
+            &affected.database_specific,
+            &affected.ecosystem_specific,
+            &record.database_specific,
+        ]
+        .into_iter()
+        .find_map(|specific| specific.as_ref()?.severity.as_deref())
+        .map(parse_osv_severity)
+        .transpose()?
+        .unwrap_or(AdvisorySeverity::Medium);
+
+        let mut intervals = Vec::new();
+        for range in &affected.ranges {
+            if range.kind == "SEMVER" || range.kind == "ECOSYSTEM" {
+                intervals.extend(intervals_of(&range.events)?);
+            }
+        }
+        for version in &affected.versions {
+            let version = parse_version(version)?;
+            let bound = Some(Bound::inclusive(version));
+            intervals.push((bound.clone(), bound));
+        }
+
+        let count = intervals.len();
+        for (i, (lower, upper)) in intervals.into_iter().enumerate() {
+            let id = if count == 1 {
+                record.id.clone()
+            } else {
+                format!("{id}#{n}", id = record.id, n = i + 1)
+            };
+            advisories.push(Advisory {
+                package: package.clone(),
+                advisory: VulnerabilityAdvisory {
+                    id,
+                    versions: version_req(lower.as_ref(), upper.as_ref())?,
+                    severity,
+                    summary: summary.clone(),
+                    url: url.clone(),
+                },
+                published_at,
+            });
+        }
+    }
+
+    Ok(advisories)
+}
+
+/// A bound of an interval of versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bound {
+    version: Version,
+    inclusive: bool,
+}
+
+impl Bound {
+    fn inclusive(version: Version) -> Self {
+        Self {
+            version,
+            inclusive: true,
+        }
+    }
+
+    fn exclusive(version: Version) -> Self {
+        Self {
+            version,
+            inclusive: false,
+        }
+    }
+}
+
+type Interval = (Option<Bound>, Option<Bound>);
+
+/// Converts a version requirement into the events of an OSV range.
+///
+/// An exclusive lower bound (e.g. `>1.2.3`) cannot be represented in OSV,
+/// so it is exported as introduced in the next patch version.
+fn events(req: &VersionReq) -> Result<Vec<OsvEvent>> {
+    let mut lower: Option<Bound> = None;
+    let mut upper: Option<Bound> = None;
+    for comparator in &req.comparators {
+        let (l, u) = comparator_bounds(comparator)?;
+        lower = match (lower, l) {
+            (Some(a), Some(b)) => Some(stricter(a, b, Ordering::Greater)),
+            (a, b) => a.or(b),
+        };
+        upper = match (upper, u) {
+            (Some(a), Some(b)) => Some(stricter(a, b, Ordering::Less)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    let introduced = match lower {
+        Some(Bound {
+            version,
+            inclusive: true,
+        }) => version.to_string(),
+        Some(Bound { version, .. }) => {
+            Version::new(version.major, version.minor, version.patch + 1).to_string()
+        }
+        None => "0".to_string(),
+    };
+
+    let mut events = vec![OsvEvent {
+        introduced: Some(introduced),
+        ..Default::default()
+    }];
+    match upper {
+        Some(Bound {
+            version,
+            inclusive: true,
+        }) => events.push(OsvEvent {
+            last_affected: Some(version.to_string()),
+            ..Default::default()
+        }),
+        Some(Bound { version, .. }) => events.push(OsvEvent {
+            fixed: Some(version.to_string()),
+            ..Default::default()
+        }),
+        None => {}
+    }
+
+    Ok(events)
+}
+
+/// Picks the stricter of two bounds, where the stricter version is the one
+/// ordered as given from the other.
+fn stricter(a: Bound, b: Bound, order: Ordering) -> Bound {
+    match a.version.cmp(&b.version) {
+        Ordering::Equal if a.inclusive => b,
+        Ordering::Equal => a,
+        o if o == order => a,
+        _ => b,
+    }
+}
+
+/// Gets the lower and upper bounds of the versions matching a comparator.
+fn comparator_bounds(c: &Comparator) -> Result<Interval> {
+    let floor = Version {
+        major: c.major,
+        minor: c.minor.unwrap_or(0),
+        patch: c.patch.unwrap_or(0),
+        pre: c.pre.clone(),
+        build: Default::default(),
+    };
+    // The first version after those matching a partial version
+    let next = match (c.minor, c.patch) {
+        (Some(minor), Some(patch)) => Version::new(c.major, minor, patch + 1),
+        (Some(minor), None) => Version::new(c.major, minor + 1, 0),
+        _ => Version::new(c.major + 1, 0, 0),
+    };
+    let full = c.patch.is_some();
+
+    Ok(match c.op {
+        Op::Exact if full => (
+            Some(Bound::inclusive(floor.clone())),
+            Some(Bound::inclusive(floor)),
+        ),
+        Op::Exact | Op::Wildcard => (Some(Bound::inclusive(floor)), Some(Bound::exclusive(next))),
+        Op::Greater if full => (Some(Bound::exclusive(floor)), None),
+        Op::Greater => (Some(Bound::inclusive(next)), None),
+        Op::GreaterEq => (Some(Bound::inclusive(floor)), None),
+        Op::Less => (None, Some(Bound::exclusive(floor))),
+        Op::LessEq if full => (None, Some(Bound::inclusive(floor))),
+        Op::LessEq => (None, Some(Bound::exclusive(next))),
+        Op::Tilde => {
+            let upper = match c.minor {
+                Some(minor) => Version::new(c.major, minor + 1, 0),
+                None => Version::new(c.major + 1, 0, 0),
+            };
+            (Some(Bound::inclusive(floor)), Some(Bound::exclusive(upper)))
+        }
+        Op::Caret => {
+            let upper = match (c.major, c.minor, c.patch) {
+                (0, Some(0), Some(patch)) => Version::new(0, 0, patch + 1),
+                (0, Some(minor), _) => Version::new(0, minor + 1, 0),
+                (major, _, _) => Version::new(major + 1, 0, 0),
+            };
+            (Some(Bound::inclusive(floor)), Some(Bound::exclusive(upper)))
+        }
+        _ => bail!("unsupported version comparator `{c}`"),
+    })
+}
+
+/// Gets the intervals of affected versions from the events of an OSV range.
+fn intervals_of(events: &[OsvEvent]) -> Result<Vec<Interval>> {
+    enum Event {
+        Introduced(Option<Version>),
+        Fixed(Version),
+        LastAffected(Version),
+    }
+
+    let mut parsed = Vec::with_capacity(events.len());
+    for event in events {
+        if let Some(v) = &event.introduced {
+            parsed.push(Event::Introduced(if v == "0" {
+                None
+            } else {
+                Some(parse_version(v)?)
+            }));
+        } else if let Some(v) = &event.fixed {
+            parsed.push(Event::Fixed(parse_version(v)?));
+        } else if let Some(v) = &event.last_affected {
+            parsed.push(Event::LastAffected(parse_version(v)?));
+        }
+    }
+
+    // Events are not required to be sorted
+    let zero = Version::new(0, 0, 0);
+    parsed.sort_by(|a, b| {
+        let version = |e: &Event| match e {
+            Event::Introduced(v) => v.clone().unwrap_or_else(|| zero.clone()),
+            Event::Fixed(v) | Event::LastAffected(v) => v.clone(),
+        };
+        version(a).cmp(&version(b))
+    });
+
+    let mut intervals = Vec::new();
+    let mut start = None;
+    for event in parsed {
+        match event {
+            Event::Introduced(v) => {
+                if start.is_none() {
+                    start = Some(v.map(Bound::inclusive));
+                }
+            }
+            Event::Fixed(v) => {
+                if let Some(lower) = start.take() {
+                    intervals.push((lower, Some(Bound::exclusive(v))));
+                }
+            }
+            Event::LastAffected(v) => {
+                if let Some(lower) = start.take() {
+                    intervals.push((lower, Some(Bound::inclusive(v))));
+                }
+            }
+        }
+    }
+
+    if let Some(lower) = start {
+        intervals.push((lower, None));
+    }
+
+    Ok(intervals)
+}
+
+/// Creates a version requirement matching an interval of versions.
+fn version_req(lower: Option<&Bound>, upper: Option<&Bound>) -> Result<VersionReq> {
+    let mut comparators = Vec::new();
+    if let Some(lower) = lower {
+        let op = if lower.inclusive { ">=" } else { ">" };
+        comparators.push(format!("{op}{v}", v = lower.version));
+    }
+    if let Some(upper) = upper {
+        let op = if upper.inclusive { "<=" } else { "<" };
+        comparators.push(format!("{op}{v}", v = upper.version));
+    }
+
+    if comparators.is_empty() {
+        return Ok(VersionReq::STAR);
+    }
+
+    Ok(comparators.join(", ").parse()?)
+}
+
+fn parse_version(version: &str) -> Result<Version> {
+    version
+        .parse()
+        .with_context(|| format!("invalid version `{version}` in OSV record"))
+}
+
+fn osv_severity(severity: AdvisorySeverity) -> &'static str {
+    match severity {
+        AdvisorySeverity::Low => "LOW",
+        AdvisorySeverity::Medium => "MODERATE",
+        AdvisorySeverity::High => "HIGH",
+        AdvisorySeverity::Critical => "CRITICAL",
+    }
+}
+
+fn parse_osv_severity(severity: &str) -> Result<AdvisorySeverity> {
+    match severity.to_ascii_uppercase().as_str() {
+        "LOW" => Ok(AdvisorySeverity::Low),
+        "MODERATE" | "MEDIUM" => Ok(AdvisorySeverity::Medium),
+        "HIGH" => Ok(AdvisorySeverity::High),
+        "CRITICAL" => Ok(AdvisorySeverity::Critical),
+        _ => Err(anyhow!("invalid severity `{severity}` in OSV record")),
+    }
+}
+
+fn rfc3339(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn parse_rfc3339(time: &str) -> Result<SystemTime> {
+    Ok(DateTime::parse_from_rfc3339(time)
+        .with_context(|| format!("invalid time `{time}` in OSV record"))?
+        .with_timezone(&Utc)
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events_of(req: &str) -> Vec<(Option<String>, Option<String>, Option<String>)> {
+        events(&req.parse().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.introduced, e.fixed, e.last_affected))
+            .collect()
+    }
+
+    fn s(s: &str) -> Option<String> {
+        Some(s.to_string())
+    }
+
+    #[test]
+    fn it_converts_version_requirements_to_events() {
+        assert_eq!(
+            events_of(">=1.0.0, <1.2.3"),
+            [(s("1.0.0"), None, None), (None, s("1.2.3"), None)]
+        );
+        assert_eq!(
+            events_of("^0.2.1"),
+            [(s("0.2.1"), None, None), (None, s("0.3.0"), None)]
+        );
+        assert_eq!(
+            events_of("<=1.4.0"),
+            [(s("0"), None, None), (None, None, s("1.4.0"))]
+        );
+        assert_eq!(
+            events_of("=1.2"),
+            [(s("1.2.0"), None, None), (None, s("1.3.0"), None)]
+        );
+        assert_eq!(events_of("*"), [(s("0"), None, None)]);
+        assert_eq!(
+            events_of(">=1.0.0, ~1.1, <1.1.5"),
+            [(s("1.1.0"), None, None), (None, s("1.1.5"), None)]
+        );
+    }
+
+    #[test]
+    fn it_round_trips_advisories() -> Result<()> {
+        let advisory = Advisory {
+            package: PackageName::new("test:pkg")?,
+            advisory: VulnerabilityAdvisory {
+                id: "TEST-1".to_string(),
+                versions: ">=1.0.0, <1.2.3".parse()?,
+                severity: AdvisorySeverity::Critical,
+                summary: "a vulnerability".to_string(),
+                url: Some("https://example.com/TEST-1".to_string()),
+            },
+            published_at: parse_rfc3339("2026-01-02T03:04:05Z")?,
+        };
+
+        let records = to_osv(std::slice::from_ref(&advisory))?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].published.as_deref(),
+            Some("2026-01-02T03:04:05Z")
+        );
+        assert_eq!(from_osv(&records[0])?, [advisory]);
+        Ok(())
+    }
+
+    #[test]
+    fn it_imports_osv_records() -> Result<()> {
+        let record = OsvRecord {
+            schema_version: None,
+            id: "TEST-2".to_string(),
+            modified: "2026-01-02T03:04:05Z".to_string(),
+            published: None,
+            aliases: Vec::new(),
+            summary: None,
+            details: Some("first line\nsecond line".to_string()),
+            affected: vec![
+                OsvAffected {
+                    package: OsvPackage {
+                        ecosystem: "npm".to_string(),
+                        name: "unrelated".to_string(),
+                    },
+                    ranges: Vec::new(),
+                    versions: vec!["1.0.0".to_string()],
+                    ecosystem_specific: None,
+                    database_specific: None,
+                },
+                OsvAffected {
+                    package: OsvPackage {
+                        ecosystem: "warg".to_string(),
+                        name: "test:pkg".to_string(),
+                    },
+                    ranges: vec![OsvRange {
+                        kind: "SEMVER".to_string(),
+                        events: vec![
+                            OsvEvent {
+                                fixed: s("2.0.1"),
+                                ..Default::default()
+                            },
+                            OsvEvent {
+                                introduced: s("2.0.0"),
+                                ..Default::default()
+                            },
+                            OsvEvent {
+                                introduced: s("0"),
+                                ..Default::default()
+                            },
+                            OsvEvent {
+                                fixed: s("1.0.5"),
+                                ..Default::default()
+                            },
+                        ],
+                    }],
+                    versions: Vec::new(),
+                    ecosystem_specific: None,
+                    database_specific: Some(OsvSpecific {
+                        severity: s("moderate"),
+                    }),
+                },
+            ],
+            references: vec![
+                OsvReference {
+                    kind: "WEB".to_string(),
+                    url: "https://example.com/web".to_string(),
+                },
+                OsvReference {
+                    kind: "ADVISORY".to_string(),
+                    url: "https://example.com/advisory".to_string(),
+                },
+            ],
+            database_specific: None,
+        };
+
+        let advisories = from_osv(&record)?;
+        let summary = advisories
+            .iter()
+            .map(|a| {
+                (
+                    a.advisory.id.as_str(),
+                    a.advisory.versions.to_string(),
+                    a.advisory.severity,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("TEST-2#1", "<1.0.5".to_string(), AdvisorySeverity::Medium),
+                (
+                    "TEST-2#2",
+                    ">=2.0.0, <2.0.1".to_string(),
+                    AdvisorySeverity::Medium
+                ),
+            ]
+        );
+        assert_eq!(advisories[0].advisory.summary, "first line");
+        assert_eq!(
+            advisories[0].advisory.url.as_deref(),
+            Some("https://example.com/advisory")
+        );
+        Ok(())
+    }
+}
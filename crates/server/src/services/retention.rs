@@ -0,0 +1,99 @@
+use super::CoreService;
+use crate::datastore::{DataStoreError, ExpiredRecords};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+/// The default interval at which expired records are removed.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The configuration of the record retention job.
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    retention: Duration,
+    interval: Duration,
+}
+
+impl RetentionConfig {
+    /// Creates a retention configuration that keeps rejected and pending
+    /// records for the given duration after they were last updated.
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Gets how long rejected and pending records are kept.
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Gets the interval at which expired records are removed.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Sets the interval at which expired records are removed.
+    ///
+    /// Defaults to one hour.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// Periodically removes rejected records and abandoned pending records
+/// from the data store once their retention window has passed.
+///
+/// Staged records that were not committed before their staging expired are
+/// rejected first, so they are removed once their retention window passes.
+///
+/// Validated records are never removed.
+pub struct RecordRetention {
+    config: RetentionConfig,
+    core: CoreService,
+}
+
+impl RecordRetention {
+    /// Creates a retention job for the data store of the given core service.
+    pub fn new(config: RetentionConfig, core: CoreService) -> Self {
+        Self { config, core }
+    }
+
+    /// Starts removing expired records in the background at the configured
+    /// interval.
+    pub fn start(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.interval);
+
+            loop {
+                interval.tick().await;
+                match self.expire().await {
+                    Ok(expired) => tracing::info!(
+                        rejected = expired.rejected,
+                        pending = expired.pending,
+                        "record retention complete"
+                    ),
+                    Err(e) => tracing::error!("record retention failed: {e}"),
+                }
+            }
+        })
+    }
+
+    /// Removes the records whose retention window has passed once.
+    pub async fn expire(&self) -> Result<ExpiredRecords, DataStoreError> {
+        let staged = self
+            .core
+            .store()
+            .expire_staged_publishes(SystemTime::now())
+            .await?;
+        if staged > 0 {
+            tracing::info!("{staged} expired staged records were rejected");
+        }
+
+        let before = SystemTime::now()
+            .checked_sub(self.config.retention)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        self.core.store().expire_records(before).await
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tempfile::NamedTempFile;
+use tokio::task::JoinHandle;
+use url::Url;
+use warg_api::v1::{content::ContentSourcesResponse, paths, ContentSource};
+use warg_crypto::hash::{AnyHash, Digest, Hash, HashAlgorithm, Sha256};
+
+/// The configuration of the content scrubbing job.
+#[derive(Debug, Clone)]
+pub struct ScrubConfig {
+    interval: Duration,
+    webhook: Option<Url>,
+    upstreams: Vec<Url>,
+}
+
+impl ScrubConfig {
+    /// Creates a scrubbing configuration that scrubs content at the given
+    /// interval.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            webhook: None,
+            upstreams: Vec::new(),
+        }
+    }
+
+    /// Gets the interval at which content is scrubbed.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Sets a webhook URL to post findings to as JSON.
+    pub fn with_webhook(mut self, url: Url) -> Self {
+        self.webhook = Some(url);
+        self
+    }
+
+    /// Adds an upstream registry to fetch corrupt content from again.
+    ///
+    /// Upstreams are tried in the order they were added.
+    pub fn with_upstream(mut self, url: Url) -> Self {
+        self.upstreams.push(url);
+        self
+    }
+}
+
+/// A content file that did not match its digest.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrubFinding {
+    /// The digest the content is stored under.
+    pub digest: AnyHash,
+    /// The digest of the stored content.
+    pub actual: AnyHash,
+    /// The path the corrupt content was moved to.
+    pub quarantined: PathBuf,
+    /// Whether the content was fetched again from an upstream registry.
+    pub refetched: bool,
+}
+
+/// The result of a content scrub.
+#[derive(Debug, Default, Clone)]
+pub struct ScrubReport {
+    /// The number of content files checked.
+    pub checked: usize,
+    /// The content files that did not match their digests.
+    pub findings: Vec<ScrubFinding>,
+}
+
+/// Periodically re-hashes stored content files against their digests.
+///
+/// Corrupt files are moved to a quarantine directory so they are no longer
+/// served, reported to the configured webhook, and optionally fetched again
+/// from upstream registries.
+pub struct ContentScrubber {
+    config: ScrubConfig,
+    files_dir: PathBuf,
+    temp_dir: PathBuf,
+    quarantine_dir: PathBuf,
+    client: reqwest::Client,
+}
+
+impl ContentScrubber {
+    /// Creates a scrubber for the content directory of a server.
+    pub fn new(config: ScrubConfig, content_dir: &Path) -> Self {
+        Self {
+            config,
+            files_dir: content_dir.join("files"),
+            temp_dir: content_dir.join("tmp"),
+            quarantine_dir: content_dir.join("quarantine"),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Starts scrubbing content in the background at the configured
+    /// interval.
+    pub fn start(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.interval);
+            // The first tick completes immediately; scrub after a full interval
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                match self.scrub().await {
+                    Ok(report) => tracing::info!(
+                        checked = report.checked,
+                        corrupt = report.findings.len(),
+                        "content scrub complete"
+                    ),
+                    Err(e) => tracing::error!("content scrub failed: {e:#}"),
+                }
+            }
+        })
+    }
+
+    /// Scrubs all content files once.
+    pub async fn scrub(&self) -> Result<ScrubReport> {
+        let mut report = ScrubReport::default();
+        for (digest, path) in self.content_files()? {
+            report.checked += 1;
+
+            let actual = {
+                let path = path.clone();
+                tokio::task::spawn_blocking(move || hash_file(&path)).await?
+            };
+            let actual = match actual {
+                Ok(actual) if actual == digest => continue,
+                Ok(actual) => actual,
+                // The file may have been removed since it was listed
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("failed to read content `{path}`", path = path.display())
+                    })
+                }
+            };
+
+            tracing::error!("content `{digest}` is corrupt (found digest `{actual}`)");
+            let quarantined = self.quarantine(&path)?;
+            let refetched = self.refetch(&digest, &path).await;
+            let finding = ScrubFinding {
+                digest,
+                actual,
+                quarantined,
+                refetched,
+            };
+
+            self.alert(&finding).await;
+            report.findings.push(finding);
+        }
+
+        Ok(report)
+    }
+
+    /// Lists the content files and the digests they are stored under.
+    fn content_files(&self) -> Result<Vec<(AnyHash, PathBuf)>> {
+        let entries = match fs::read_dir(&self.files_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!(
+                        "failed to read content directory `{path}`",
+                        path = self.files_dir.display()
+                    )
+                })
+            }
+        };
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+
+            // Content files are named `<algorithm>-<hex digest>`
+            let digest = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.split_once('-'))
+                .and_then(|(algorithm, digest)| format!("{algorithm}:{digest}").parse().ok());
+            if let Some(digest) = digest {
+                files.push((digest, entry.path()));
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn quarantine(&self, path: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(&self.quarantine_dir).with_context(|| {
+            format!(
+                "failed to create quarantine directory `{path}`",
+                path = self.quarantine_dir.display()
+            )
+        })?;
+
+        let quarantined = self.quarantine_dir.join(path.file_name().unwrap());
+        fs::rename(path, &quarantined).with_context(|| {
+            format!(
+                "failed to quarantine content `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        Ok(quarantined)
+    }
+
+    /// Fetches content from the upstream registries, storing it at the given
+    /// path once it matches its digest.
+    async fn refetch(&self, digest: &AnyHash, path: &Path) -> bool {
+        for upstream in &self.config.upstreams {
+            match self.fetch_from(upstream, digest, path).await {
+                Ok(true) => {
+                    tracing::info!("fetched content `{digest}` again from `{upstream}`");
+                    return true;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!("failed to fetch content `{digest}` from `{upstream}`: {e:#}")
+                }
+            }
+        }
+
+        false
+    }
+
+    async fn fetch_from(&self, upstream: &Url, digest: &AnyHash, path: &Path) -> Result<bool> {
+        let sources = self
+            .client
+            .get(upstream.join(&paths::content_sources(digest))?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ContentSourcesResponse>()
+            .await?;
+
+        for source in sources
+            .content_sources
+            .get(digest)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        {
+            let ContentSource::HttpGet { url, .. } = source;
+            let bytes = self
+                .client
+                .get(upstream.join(url)?)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?;
+            if hash_bytes(&bytes) != *digest {
+                tracing::warn!("upstream `{upstream}` served corrupt content for `{digest}`");
+                continue;
+            }
+
+            fs::create_dir_all(&self.temp_dir)?;
+            let mut file = NamedTempFile::new_in(&self.temp_dir)?;
+            io::Write::write_all(&mut file, &bytes)?;
+            file.persist(path)?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    async fn alert(&self, finding: &ScrubFinding) {
+        let Some(webhook) = &self.config.webhook else {
+            return;
+        };
+
+        let result = self
+            .client
+            .post(webhook.clone())
+            .json(finding)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!("failed to send content scrub alert to `{webhook}`: {e}");
+        }
+    }
+}
+
+fn hash_file(path: &Path) -> io::Result<AnyHash> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(Hash::<Sha256>::from(hasher.finalize()).into())
+}
+
+fn hash_bytes(bytes: &[u8]) -> AnyHash {
+    AnyHash::new(HashAlgorithm::Sha256, Sha256::digest(bytes).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_quarantines_corrupt_content() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let files_dir = dir.path().join("files");
+        fs::create_dir_all(&files_dir)?;
+
+        let good = hash_bytes(b"good");
+        let corrupt = hash_bytes(b"original");
+        let name = |digest: &AnyHash| digest.to_string().replace(':', "-");
+        fs::write(files_dir.join(name(&good)), b"good")?;
+        fs::write(files_dir.join(name(&corrupt)), b"rotted")?;
+
+        let scrubber = ContentScrubber::new(ScrubConfig::new(Duration::from_secs(60)), dir.path());
+        let report = scrubber.scrub().await?;
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.findings.len(), 1);
+
+        let finding = &report.findings[0];
+        assert_eq!(finding.digest, corrupt);
+        assert_eq!(finding.actual, hash_bytes(b"rotted"));
+        assert!(!finding.refetched);
+        assert!(finding.quarantined.is_file());
+        assert!(!files_dir.join(name(&corrupt)).exists());
+        assert!(files_dir.join(name(&good)).is_file());
+
+        assert!(scrubber.scrub().await?.findings.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use warg_protocol::registry::PackageName;
+
+/// The configuration of typosquat detection for new packages.
+///
+/// A new package is flagged for moderation if its name is confusable with,
+/// or within a small edit distance of, the name of an existing package.
+/// Flagged packages are not rejected.
+#[derive(Debug, Clone)]
+pub struct TyposquatConfig {
+    max_distance: usize,
+    min_length: usize,
+}
+
+impl Default for TyposquatConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A suspicious similarity between a new package name and an existing one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Similarity {
+    /// The existing package the name is similar to.
+    pub similar_to: PackageName,
+    /// A description of the similarity.
+    pub reason: String,
+}
+
+impl TyposquatConfig {
+    /// Creates a typosquat detection configuration that flags names within an
+    /// edit distance of one of an existing name.
+    pub fn new() -> Self {
+        Self {
+            max_distance: 1,
+            min_length: 4,
+        }
+    }
+
+    /// Sets the maximum edit distance between names for a new name to be
+    /// flagged.
+    ///
+    /// Defaults to 1.
+    pub fn with_max_distance(mut self, distance: usize) -> Self {
+        self.max_distance = distance;
+        self
+    }
+
+    /// Sets the minimum length of the names (excluding namespaces) compared
+    /// by edit distance, as short names are often within a small edit
+    /// distance of each other.
+    ///
+    /// Confusable names are flagged regardless of length. Defaults to 4.
+    pub fn with_min_length(mut self, length: usize) -> Self {
+        self.min_length = length;
+        self
+    }
+
+    /// Finds an existing package the given name is suspiciously similar to.
+    ///
+    /// Confusable names are preferred over names within the edit distance,
+    /// and closer names over farther ones.
+    pub fn find_similar<'a>(
+        &self,
+        name: &PackageName,
+        existing: impl IntoIterator<Item = &'a PackageName>,
+    ) -> Option<Similarity> {
+        let new = name.as_ref();
+        let new_skeleton = skeleton(new);
+        let mut nearest: Option<(usize, &PackageName)> = None;
+
+        for other in existing {
+            let other_str = other.as_ref();
+            if other_str == new {
+                continue;
+            }
+
+            if skeleton(other_str) == new_skeleton {
+                return Some(Similarity {
+                    similar_to: other.clone(),
+                    reason: format!("`{name}` is confusable with `{other}`"),
+                });
+            }
+
+            if name.name().len().min(other.name().len()) < self.min_length {
+                continue;
+            }
+
+            let distance = edit_distance(new, other_str);
+            if distance <= self.max_distance && nearest.map_or(true, |(d, _)| distance < d) {
+                nearest = Some((distance, other));
+            }
+        }
+
+        nearest.map(|(distance, other)| Similarity {
+            similar_to: other.clone(),
+            reason: format!("`{name}` is within an edit distance of {distance} of `{other}`"),
+        })
+    }
+}
+
+/// Maps a name to a skeleton in which visually confusable characters and
+/// sequences are replaced with a common representative.
+fn skeleton(name: &str) -> String {
+    let mut skeleton = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '-' => {}
+            '0' => skeleton.push('o'),
+            '1' | 'i' => skeleton.push('l'),
+            '3' => skeleton.push('e'),
+            '5' => skeleton.push('s'),
+            c => skeleton.push(c),
+        }
+    }
+
+    skeleton.replace("rn", "m").replace("vv", "w")
+}
+
+/// Computes the edit distance between two strings, counting insertions,
+/// deletions, substitutions, and transpositions of adjacent characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<PackageName> {
+        names
+            .iter()
+            .map(|n| PackageName::new(*n).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn it_computes_edit_distance() {
+        assert_eq!(edit_distance("wasi:http", "wasi:http"), 0);
+        assert_eq!(edit_distance("wasi:http", "wasi:htp"), 1);
+        assert_eq!(edit_distance("wasi:http", "wasi:htpt"), 1);
+        assert_eq!(edit_distance("wasi:http", "wasi:hxxp"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn it_finds_similar_names() {
+        let config = TyposquatConfig::new();
+        let existing = names(&["wasi:http", "wasi:io", "example:modern"]);
+        let find = |name: &str| config.find_similar(&PackageName::new(name).unwrap(), &existing);
+
+        assert_eq!(find("wasi:htpt").unwrap().similar_to.as_ref(), "wasi:http");
+        assert!(find("wasi:htpt").unwrap().reason.contains("edit distance"));
+        assert_eq!(find("wasl:io").unwrap().similar_to.as_ref(), "wasi:io");
+        assert!(find("wasl:io").unwrap().reason.contains("confusable"));
+        assert_eq!(
+            find("example:rnodern").unwrap().similar_to.as_ref(),
+            "example:modern"
+        );
+
+        // Short names are only flagged when confusable
+        assert!(find("wasi:ip").is_none());
+        assert!(find("wasi:http").is_none());
+        assert!(find("example:unrelated").is_none());
+    }
+}
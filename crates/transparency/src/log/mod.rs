@@ -6,10 +6,10 @@
 //!
 //! Implementations:
 //! * [`InOrderLog`] -
-//! The only implementation in this module is ,
-//! which is a [`VerifiableLog`] whose contents are structured
-//! using binary in-order interval numbering as described in
-//! [Dat - Distributed Dataset Synchronization and Versioning][2].
+//!   The only implementation in this module is ,
+//!   which is a [`VerifiableLog`] whose contents are structured
+//!   using binary in-order interval numbering as described in
+//!   [Dat - Distributed Dataset Synchronization and Versioning][2].
 
 mod node;
 /// Logic for constructing and validating proofs
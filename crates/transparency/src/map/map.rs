@@ -164,7 +164,7 @@ where
 impl<D, K, V> Map<D, K, V>
 where
     D: SupportedDigest,
-    K: VisitBytes + Clone + ?Sized,
+    K: VisitBytes + Clone,
     V: VisitBytes + Clone,
 {
     /// The hash of the root of the tree.
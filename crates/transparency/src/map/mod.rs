@@ -13,6 +13,7 @@
 mod fork;
 mod link;
 mod map;
+mod multi_proof;
 mod node;
 mod path;
 mod proof;
@@ -20,6 +21,7 @@ mod proof_bundle;
 mod singleton;
 
 pub use map::Map;
+pub use multi_proof::MultiProof as MapMultiProof;
 pub use proof::Proof;
 pub use proof_bundle::ProofBundle as MapProofBundle;
 
@@ -0,0 +1,167 @@
+use alloc::vec::Vec;
+use anyhow::{bail, Error};
+use indexmap::IndexSet;
+use prost::Message;
+use warg_crypto::{
+    hash::{Hash, SupportedDigest},
+    VisitBytes,
+};
+use warg_protobuf::transparency as protobuf;
+
+use crate::map::proof::Proof;
+
+/// A compact collection of inclusion proofs in the same map
+///
+/// The proofs of keys in the same map share the peers of their common
+/// path prefix, so each distinct peer hash is stored once and the proofs
+/// refer to the hashes by index.
+pub struct MultiProof<D, K, V>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    hashes: IndexSet<Hash<D>>,
+    proofs: Vec<Proof<D, K, V>>,
+}
+
+impl<D, K, V> MultiProof<D, K, V>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    /// Combines inclusion proofs into a multi-proof
+    pub fn new(proofs: Vec<Proof<D, K, V>>) -> Self {
+        let hashes = proofs
+            .iter()
+            .flat_map(|proof| proof.peers.iter().flatten().cloned())
+            .collect();
+        MultiProof { hashes, proofs }
+    }
+
+    /// Gets the number of distinct peer hashes of the multi-proof
+    pub fn hash_count(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Splits a multi-proof into its constituent inclusion proofs
+    pub fn into_proofs(self) -> Vec<Proof<D, K, V>> {
+        self.proofs
+    }
+
+    /// Turn a multi-proof into bytes using protobuf
+    pub fn encode(self) -> Vec<u8> {
+        let proto: protobuf::MapMultiProof = self.into();
+        proto.encode_to_vec()
+    }
+
+    /// Parse a multi-proof from bytes using protobuf
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let proto = protobuf::MapMultiProof::decode(bytes)?;
+        let proof = proto.try_into()?;
+        Ok(proof)
+    }
+}
+
+impl<D, K, V> From<MultiProof<D, K, V>> for protobuf::MapMultiProof
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    fn from(value: MultiProof<D, K, V>) -> Self {
+        let proofs = value
+            .proofs
+            .iter()
+            .map(|proof| protobuf::MapMultiProofPath {
+                peers: proof
+                    .peers
+                    .iter()
+                    .map(|peer| match peer {
+                        Some(hash) => value.hashes.get_index_of(hash).unwrap() as u32 + 1,
+                        None => 0,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        protobuf::MapMultiProof {
+            hashes: value
+                .hashes
+                .iter()
+                .map(|hash| hash.bytes().to_vec())
+                .collect(),
+            proofs,
+        }
+    }
+}
+
+impl<D, K, V> TryFrom<protobuf::MapMultiProof> for MultiProof<D, K, V>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    type Error = Error;
+
+    fn try_from(value: protobuf::MapMultiProof) -> Result<Self, Self::Error> {
+        let hashes = value
+            .hashes
+            .into_iter()
+            .map(Hash::<D>::try_from)
+            .collect::<Result<IndexSet<_>, _>>()?;
+
+        let mut proofs = Vec::with_capacity(value.proofs.len());
+        for path in value.proofs {
+            let mut peers = Vec::with_capacity(path.peers.len());
+            for index in path.peers {
+                peers.push(match index {
+                    0 => None,
+                    n => match hashes.get_index(n as usize - 1) {
+                        Some(hash) => Some(hash.clone()),
+                        None => bail!("multi-proof peer index {n} is out of bounds"),
+                    },
+                });
+            }
+            proofs.push(Proof::new(peers));
+        }
+
+        Ok(MultiProof { hashes, proofs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Map;
+    use warg_crypto::hash::Sha256;
+
+    #[test]
+    fn test_multi_proof_round_trip() {
+        let keys = ["foo", "bar", "baz", "qux", "quux"];
+        let mut map = Map::<Sha256, &str, &str>::default();
+        for key in keys {
+            map = map.insert(key, key);
+        }
+
+        let proofs = keys.iter().map(|key| map.prove(*key).unwrap()).collect();
+        let multi = MultiProof::new(proofs);
+        let total: usize = keys
+            .iter()
+            .map(|key| map.prove(*key).unwrap().peers.iter().flatten().count())
+            .sum();
+        assert!(multi.hash_count() < total);
+
+        let decoded = MultiProof::<Sha256, &str, &str>::decode(&multi.encode()).unwrap();
+        for (key, proof) in keys.iter().zip(decoded.into_proofs()) {
+            assert_eq!(map.root(), &proof.evaluate(key, key));
+        }
+
+        let invalid = protobuf::MapMultiProof {
+            hashes: Vec::new(),
+            proofs: vec![protobuf::MapMultiProofPath { peers: vec![1] }],
+        };
+        assert!(MultiProof::<Sha256, &str, &str>::decode(&invalid.encode_to_vec()).is_err());
+    }
+}
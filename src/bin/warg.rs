@@ -3,9 +3,9 @@ use clap::Parser;
 use std::process::exit;
 use tracing_subscriber::EnvFilter;
 use warg_cli::commands::{
-    BundleCommand, ClearCommand, ConfigCommand, DependenciesCommand, DownloadCommand, InfoCommand,
-    KeyCommand, LockCommand, LoginCommand, LogoutCommand, PublishCommand, ResetCommand,
-    UpdateCommand,
+    AuditCommand, BundleCommand, ClearCommand, ConfigCommand, DependenciesCommand, DownloadCommand,
+    ExportCommand, InfoCommand, InviteCommand, KeyCommand, LockCommand, LoginCommand,
+    LogoutCommand, MonitorCommand, PublishCommand, ResetCommand, UpdateCommand, VendorCommand,
 };
 use warg_client::ClientError;
 
@@ -26,17 +26,22 @@ enum WargCli {
     Config(ConfigCommand),
     Info(InfoCommand),
     Key(KeyCommand),
+    Invite(InviteCommand),
     Lock(LockCommand),
+    Vendor(VendorCommand),
     Bundle(BundleCommand),
     Dependencies(DependenciesCommand),
     Download(DownloadCommand),
+    Export(ExportCommand),
     Update(UpdateCommand),
     #[clap(subcommand)]
     Publish(PublishCommand),
     Reset(ResetCommand),
     Clear(ClearCommand),
+    Audit(AuditCommand),
     Login(LoginCommand),
     Logout(LogoutCommand),
+    Monitor(MonitorCommand),
 }
 
 #[tokio::main]
@@ -49,16 +54,21 @@ async fn main() -> Result<()> {
         WargCli::Config(cmd) => cmd.exec().await,
         WargCli::Info(cmd) => cmd.exec().await,
         WargCli::Key(cmd) => cmd.exec().await,
+        WargCli::Invite(cmd) => cmd.exec().await,
         WargCli::Lock(cmd) => cmd.exec().await,
+        WargCli::Vendor(cmd) => cmd.exec().await,
         WargCli::Bundle(cmd) => cmd.exec().await,
         WargCli::Dependencies(cmd) => cmd.exec().await,
         WargCli::Download(cmd) => cmd.exec().await,
+        WargCli::Export(cmd) => cmd.exec().await,
         WargCli::Update(cmd) => cmd.exec().await,
         WargCli::Publish(cmd) => cmd.exec().await,
         WargCli::Reset(cmd) => cmd.exec().await,
         WargCli::Clear(cmd) => cmd.exec().await,
+        WargCli::Audit(cmd) => cmd.exec().await,
         WargCli::Login(cmd) => cmd.exec().await,
         WargCli::Logout(cmd) => cmd.exec().await,
+        WargCli::Monitor(cmd) => cmd.exec().await,
     } {
         if let Some(e) = e.downcast_ref::<ClientError>() {
             describe_client_error(e).await?;
@@ -3,43 +3,57 @@
 use anyhow::Result;
 use clap::Args;
 use std::path::PathBuf;
+use warg_client::api::CheckpointClock;
 use warg_client::keyring::Keyring;
+use warg_client::project::ProjectConfig;
 use warg_client::storage::RegistryDomain;
 use warg_client::{ClientError, Config, FileSystemClient, StorageLockResult};
 use warg_crypto::signing::PrivateKey;
 
+mod audit;
 mod bundle;
 mod clear;
 mod config;
 mod dependencies;
 mod download;
+mod export;
 mod info;
+mod invite;
 mod key;
 mod lock;
 mod login;
 mod logout;
+mod monitor;
 mod publish;
 mod reset;
 mod update;
+mod vendor;
 
+pub use self::audit::*;
 pub use self::bundle::*;
 pub use self::clear::*;
 pub use self::config::*;
 pub use self::dependencies::*;
 pub use self::download::*;
+pub use self::export::*;
 pub use self::info::*;
+pub use self::invite::*;
 pub use self::key::*;
 pub use self::lock::*;
 pub use self::login::*;
 pub use self::logout::*;
+pub use self::monitor::*;
 pub use self::publish::*;
 pub use self::reset::*;
 pub use self::update::*;
+pub use self::vendor::*;
 
 /// Common options for commands.
 #[derive(Args)]
 pub struct CommonOptions {
     /// The URL of the registry to use.
+    ///
+    /// If not specified, the registry of the nearest `.warg.json` project configuration is used.
     #[clap(long, value_name = "URL")]
     pub registry: Option<String>,
     /// The path to the client configuration file to use.
@@ -49,6 +63,10 @@ pub struct CommonOptions {
     /// If no configuration file is found, a default configuration is used.
     #[clap(long, value_name = "CONFIG")]
     pub config: Option<PathBuf>,
+    /// Accept registry checkpoints regardless of how their timestamps
+    /// compare to the local clock.
+    #[clap(long)]
+    pub ignore_checkpoint_time: bool,
 }
 
 impl CommonOptions {
@@ -65,33 +83,50 @@ impl CommonOptions {
             .unwrap_or_default())
     }
 
+    /// Reads the project configuration (`.warg.json`) of the current directory.
+    pub fn read_project_config(&self) -> Result<ProjectConfig> {
+        Ok(ProjectConfig::from_current_dir()?.unwrap_or_default())
+    }
+
     /// Creates the warg client to use.
+    ///
+    /// The registry of the project configuration is used if no registry was specified.
     pub async fn create_client(&self, config: &Config) -> Result<FileSystemClient, ClientError> {
-        let client =
-            match FileSystemClient::try_new_with_config(self.registry.as_deref(), config, None)
-                .await?
-            {
-                StorageLockResult::Acquired(client) => Ok(client),
-                StorageLockResult::NotAcquired(path) => {
-                    println!(
-                        "blocking on lock for directory `{path}`...",
-                        path = path.display()
-                    );
+        let project = self.read_project_config()?;
+        let registry = self.registry.as_deref().or(project.registry.as_deref());
+        let client = match FileSystemClient::try_new_with_config(registry, config, None).await? {
+            StorageLockResult::Acquired(client) => Ok(client),
+            StorageLockResult::NotAcquired(path) => {
+                println!(
+                    "blocking on lock for directory `{path}`...",
+                    path = path.display()
+                );
 
-                    FileSystemClient::new_with_config(self.registry.as_deref(), config, None).await
-                }
-            }?;
-        Ok(client)
+                FileSystemClient::new_with_config(registry, config, None).await
+            }
+        }?;
+        let client = client.with_project_config(&project)?;
+        Ok(if self.ignore_checkpoint_time {
+            client.with_checkpoint_clock(CheckpointClock::disabled())
+        } else {
+            client
+        })
     }
 
     /// Gets the signing key for the given registry URL.
+    ///
+    /// If no registry is given, the signing key of the project configuration is used.
     pub async fn signing_key(
         &self,
         registry_domain: Option<&RegistryDomain>,
     ) -> Result<PrivateKey> {
         let config = self.read_config()?;
+        let project = self.read_project_config()?;
         let key = Keyring::from_config(&config)?.get_signing_key(
-            registry_domain.map(|domain| domain.to_string()).as_deref(),
+            registry_domain
+                .map(|domain| domain.to_string())
+                .or(project.signing_key)
+                .as_deref(),
             &config.keys,
             config.home_url.as_deref(),
         )?;
@@ -0,0 +1,45 @@
+use super::CommonOptions;
+use anyhow::{bail, Result};
+use clap::Args;
+
+/// Verify the integrity of the local content cache.
+#[derive(Args)]
+pub struct AuditCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Remove corrupt content and download it again.
+    #[clap(long)]
+    pub repair: bool,
+}
+
+impl AuditCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+
+        println!("auditing local content cache...");
+        let audit = client.audit_content_cache(self.repair).await?;
+        for corrupt in &audit.corrupted {
+            println!(
+                "content `{digest}` is corrupt{repaired}",
+                digest = corrupt.digest,
+                repaired = if corrupt.repaired { " (repaired)" } else { "" }
+            );
+        }
+
+        println!(
+            "checked {checked} content blob(s), {corrupted} corrupt",
+            checked = audit.checked,
+            corrupted = audit.corrupted.len()
+        );
+
+        if audit.corrupted.iter().any(|c| !c.repaired) {
+            bail!("the content cache has corrupt content");
+        }
+
+        Ok(())
+    }
+}
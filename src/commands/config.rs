@@ -2,7 +2,15 @@ use super::CommonOptions;
 use anyhow::{bail, Context, Result};
 use clap::Args;
 use std::path::PathBuf;
-use warg_client::{keyring::Keyring, Config, RegistryUrl};
+use warg_client::{
+    api::AdvisorySeverity,
+    keyring::Keyring,
+    policy::{AdvisoryPolicy, SigningPolicy},
+    storage::StorageLayout,
+    Config, RegistryUrl,
+};
+use warg_crypto::signing::KeyID;
+use warg_protocol::registry::PackageName;
 
 /// Creates a new warg configuration file.
 #[derive(Args)]
@@ -41,6 +49,76 @@ pub struct ConfigCommand {
     #[clap(long, value_name = "NAMESPACE_PATH")]
     pub namespace_path: Option<PathBuf>,
 
+    /// The path to a policy component to check against resolved packages.
+    #[clap(long, value_name = "POLICY")]
+    pub package_policy: Option<PathBuf>,
+
+    /// An SPDX license identifier that downloaded packages may be licensed
+    /// under; may be specified multiple times.
+    #[clap(long = "allow-license", value_name = "LICENSE")]
+    pub allowed_licenses: Vec<String>,
+
+    /// Deny downloads of package versions with a vulnerability advisory of
+    /// at least this severity: `low`, `medium`, `high`, or `critical`.
+    #[clap(long, value_name = "SEVERITY")]
+    pub deny_advisories: Option<AdvisorySeverity>,
+
+    /// The identifier of a vulnerability advisory to waive when denying
+    /// downloads; may be specified multiple times.
+    #[clap(
+        long = "waive-advisory",
+        value_name = "ID",
+        requires = "deny_advisories"
+    )]
+    pub waived_advisories: Vec<String>,
+
+    /// A key that may sign the records of packages in a namespace, as
+    /// `<NAMESPACE>=<KEY_ID>`; may be specified multiple times.
+    #[clap(long = "trusted-key", value_name = "NAMESPACE=KEY_ID", value_parser = trusted_key_parser)]
+    pub trusted_keys: Vec<(String, KeyID)>,
+
+    /// The maximum number of records to request per page when fetching logs.
+    #[clap(long, value_name = "SIZE", value_parser = clap::value_parser!(u16).range(1..))]
+    pub fetch_page_size: Option<u16>,
+
+    /// The maximum number of content downloads in progress at once.
+    #[clap(long, value_name = "COUNT")]
+    pub max_parallel_downloads: Option<usize>,
+
+    /// The maximum number of content uploads in progress at once.
+    #[clap(long, value_name = "COUNT")]
+    pub max_parallel_uploads: Option<usize>,
+
+    /// The maximum number of requests to registries awaiting a response at
+    /// once.
+    #[clap(long, value_name = "COUNT")]
+    pub max_in_flight_requests: Option<usize>,
+
+    /// The maximum number of CPU-bound verifications run at once.
+    #[clap(long, value_name = "COUNT")]
+    pub verification_workers: Option<usize>,
+
+    /// The maximum number of seconds since a package was last verified
+    /// against its registry for it to be downloaded without updating it
+    /// first.
+    #[clap(long, value_name = "SECONDS")]
+    pub max_checkpoint_age: Option<u64>,
+
+    /// The layout of paths in the storage directories: `legacy` or
+    /// `hashed`.
+    #[clap(long, value_name = "LAYOUT")]
+    pub storage_layout: Option<StorageLayout>,
+
+    /// The path to a project directory to store registry state and content
+    /// in (e.g. `.warg`).
+    #[clap(long, value_name = "WORKSPACE")]
+    pub workspace_dir: Option<PathBuf>,
+
+    /// Read content that is not in the workspace directory from the content
+    /// directory.
+    #[clap(long)]
+    pub workspace_fallback: Option<bool>,
+
     /// The backend to use for keyring access
     #[clap(long, value_name = "KEYRING_BACKEND", value_parser = keyring_backend_parser, long_help = keyring_backend_help())]
     pub keyring_backend: Option<String>,
@@ -89,6 +167,26 @@ impl ConfigCommand {
                 auto_accept_federation_hints: self.auto_accept_federation_hints.unwrap_or_default(),
                 disable_interactive: false,
                 keyring_backend: self.keyring_backend,
+                package_policy: self.package_policy.map(|p| cwd.join(p)),
+                license_allowlist: (!self.allowed_licenses.is_empty())
+                    .then_some(self.allowed_licenses),
+                advisory_policy: self.deny_advisories.map(|severity| {
+                    AdvisoryPolicy::new(severity).with_waived(self.waived_advisories)
+                }),
+                signing_policy: signing_policy(self.trusted_keys),
+                user_agent_suffix: None,
+                request_headers: Default::default(),
+                connect: Default::default(),
+                checkpoint_clock: Default::default(),
+                max_checkpoint_age_secs: self.max_checkpoint_age,
+                fetch_page_size: self.fetch_page_size,
+                max_parallel_downloads: self.max_parallel_downloads,
+                max_parallel_uploads: self.max_parallel_uploads,
+                max_in_flight_requests: self.max_in_flight_requests,
+                verification_workers: self.verification_workers,
+                storage_layout: self.storage_layout.unwrap_or_default(),
+                workspace_dir: self.workspace_dir.map(|p| cwd.join(p)),
+                workspace_fallback: self.workspace_fallback.unwrap_or_default(),
             }
         } else {
             let mut config = self.common.read_config()?;
@@ -126,6 +224,46 @@ impl ConfigCommand {
             if self.keyring_backend.is_some() {
                 config.keyring_backend = self.keyring_backend;
             }
+            if self.package_policy.is_some() {
+                config.package_policy = self.package_policy.map(|p| cwd.join(p));
+            }
+            if !self.allowed_licenses.is_empty() {
+                config.license_allowlist = Some(self.allowed_licenses);
+            }
+            if let Some(severity) = self.deny_advisories {
+                config.advisory_policy =
+                    Some(AdvisoryPolicy::new(severity).with_waived(self.waived_advisories));
+            }
+            if !self.trusted_keys.is_empty() {
+                config.signing_policy = signing_policy(self.trusted_keys);
+            }
+            if let Some(layout) = self.storage_layout {
+                config.storage_layout = layout;
+            }
+            if self.fetch_page_size.is_some() {
+                config.fetch_page_size = self.fetch_page_size;
+            }
+            if self.max_checkpoint_age.is_some() {
+                config.max_checkpoint_age_secs = self.max_checkpoint_age;
+            }
+            if self.max_parallel_downloads.is_some() {
+                config.max_parallel_downloads = self.max_parallel_downloads;
+            }
+            if self.max_parallel_uploads.is_some() {
+                config.max_parallel_uploads = self.max_parallel_uploads;
+            }
+            if self.max_in_flight_requests.is_some() {
+                config.max_in_flight_requests = self.max_in_flight_requests;
+            }
+            if self.verification_workers.is_some() {
+                config.verification_workers = self.verification_workers;
+            }
+            if self.workspace_dir.is_some() {
+                config.workspace_dir = self.workspace_dir.map(|p| cwd.join(p));
+            }
+            if let Some(workspace_fallback) = self.workspace_fallback {
+                config.workspace_fallback = workspace_fallback;
+            }
 
             config
         };
@@ -150,6 +288,28 @@ impl ConfigCommand {
     }
 }
 
+/// Creates a signing policy from the given trusted keys, if any.
+fn signing_policy(trusted_keys: Vec<(String, KeyID)>) -> Option<SigningPolicy> {
+    (!trusted_keys.is_empty()).then(|| {
+        trusted_keys
+            .into_iter()
+            .fold(SigningPolicy::new(), |policy, (namespace, key)| {
+                policy.with_namespace_keys(namespace, [key])
+            })
+    })
+}
+
+fn trusted_key_parser(s: &str) -> Result<(String, KeyID), String> {
+    match s.split_once('=') {
+        Some((namespace, key)) if PackageName::is_valid_namespace(namespace) && !key.is_empty() => {
+            Ok((namespace.to_string(), KeyID::from(key.to_string())))
+        }
+        _ => Err(format!(
+            "`{s}` is not a valid trusted key; expected `<NAMESPACE>=<KEY_ID>`"
+        )),
+    }
+}
+
 pub(crate) fn keyring_backend_parser(s: &str) -> Result<String, String> {
     if Keyring::SUPPORTED_BACKENDS.contains(&s) {
         Ok(s.to_string())
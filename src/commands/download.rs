@@ -3,8 +3,8 @@ use anyhow::Result;
 use clap::Args;
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use std::path::PathBuf;
-use warg_client::ClientError;
-use warg_protocol::{registry::PackageName, VersionReq};
+use warg_client::{api::AdvisorySeverity, policy::AdvisoryPolicy, ClientError};
+use warg_protocol::{package::ReleaseChannel, registry::PackageName, VersionReq};
 
 /// Download a warg registry package.
 #[derive(Args)]
@@ -19,39 +19,118 @@ pub struct DownloadCommand {
     /// The version requirement of the package to download; defaults to `*`.
     #[clap(long, short, value_name = "VERSION")]
     pub version: Option<String>,
+    /// The release channel to download from: `stable`, `pre-release`, or a
+    /// pre-release identifier such as `nightly`.
+    ///
+    /// With a channel, the version is a requirement rather than an exact version.
+    #[clap(long, value_name = "CHANNEL")]
+    pub channel: Option<ReleaseChannel>,
     /// The output path for the file. If not specified, just downloads to local cache.
     #[clap(long, short = 'o')]
     pub output: Option<PathBuf>,
+    /// Fail if the downloaded version has a vulnerability advisory of at
+    /// least this severity: `low`, `medium`, `high`, or `critical`.
+    ///
+    /// Overrides the severity of the configured advisory policy; advisories
+    /// waived by the configuration remain waived.
+    #[clap(long, value_name = "SEVERITY")]
+    pub deny_advisories: Option<AdvisorySeverity>,
 }
 
 impl DownloadCommand {
     /// Executes the command.
     pub async fn exec(self) -> Result<()> {
-        let config = self.common.read_config()?;
+        let mut config = self.common.read_config()?;
+        if let Some(severity) = self.deny_advisories {
+            let waived = config
+                .advisory_policy
+                .take()
+                .map(|policy| policy.waived)
+                .unwrap_or_default();
+            config.advisory_policy = Some(AdvisoryPolicy::new(severity).with_waived(waived));
+        }
         let client = self.common.create_client(&config).await?;
 
         println!("Downloading `{name}`...", name = self.name);
 
-        // if user specifies exact verion, then set the `VersionReq` to exact match
-        let version = match &self.version {
-            Some(version) => VersionReq::parse(&format!("={}", version))?,
-            None => VersionReq::STAR,
+        let download = match &self.channel {
+            Some(channel) => {
+                let version = match &self.version {
+                    Some(version) => VersionReq::parse(version)?,
+                    None => VersionReq::STAR,
+                };
+                client
+                    .download_from_channel(&self.name, &version, channel)
+                    .await?
+                    .ok_or_else(|| ClientError::PackageVersionRequirementDoesNotExist {
+                        name: self.name.clone(),
+                        version,
+                    })?
+            }
+            None => {
+                // if user specifies exact verion, then set the `VersionReq` to exact match
+                let version = match &self.version {
+                    Some(version) => VersionReq::parse(&format!("={}", version))?,
+                    None => VersionReq::STAR,
+                };
+                client
+                    .download(&self.name, &version)
+                    .await?
+                    .ok_or_else(|| ClientError::PackageVersionRequirementDoesNotExist {
+                        name: self.name.clone(),
+                        version,
+                    })?
+            }
         };
 
-        let download = client
-            .download(&self.name, &version)
-            .await?
-            .ok_or_else(|| ClientError::PackageVersionRequirementDoesNotExist {
-                name: self.name.clone(),
-                version,
-            })?;
-
         println!(
             "Downloaded version: {version}\nDigest: {digest}\n",
             version = download.version,
             digest = download.digest
         );
 
+        if let Some(deprecation) = &download.deprecation {
+            match &deprecation.replacement {
+                Some(replacement) => println!(
+                    "warning: package `{name}` is deprecated in favor of `{replacement}`: {message}\n",
+                    name = self.name,
+                    message = deprecation.message
+                ),
+                None => println!(
+                    "warning: package `{name}` is deprecated: {message}\n",
+                    name = self.name,
+                    message = deprecation.message
+                ),
+            }
+        }
+
+        for advisory in &download.advisories {
+            println!(
+                "warning: version {version} of package `{name}` is affected by {severity} severity advisory `{id}`: {summary}",
+                version = download.version,
+                name = self.name,
+                severity = advisory.severity,
+                id = advisory.id,
+                summary = advisory.summary
+            );
+            if let Some(url) = &advisory.url {
+                println!("  see {url}");
+            }
+            println!();
+        }
+
+        // The advisory is informational, so failing to fetch it is not an error
+        if let Ok(metadata) = client.package_metadata(&self.name).await {
+            if let Some(advisory) = metadata.advisory {
+                println!(
+                    "warning: package `{name}` may be impersonating `{similar_to}` and is awaiting moderation: {reason}\n",
+                    name = self.name,
+                    similar_to = advisory.similar_to,
+                    reason = advisory.reason
+                );
+            }
+        }
+
         // use the `output` path specified or ask the use if wants to save in the current working
         // directory
         let default_file_name = format!("{name}.wasm", name = self.name.name());
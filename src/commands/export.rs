@@ -0,0 +1,31 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+use warg_client::export::export_archive;
+
+/// Export the archived logs of a monitored registry as CSV files.
+#[derive(Args)]
+pub struct ExportCommand {
+    /// The archive directory of a registry monitored with `warg monitor`.
+    #[clap(value_name = "ARCHIVE")]
+    pub archive: PathBuf,
+
+    /// The directory to write the CSV files to.
+    #[clap(long, short, value_name = "DIR", default_value = ".")]
+    pub output: PathBuf,
+}
+
+impl ExportCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let summary = export_archive(&self.archive, &self.output)?;
+        println!(
+            "exported {checkpoints} checkpoint(s), {records} record(s), and {entries} entry(ies) to `{path}`",
+            checkpoints = summary.checkpoints,
+            records = summary.records,
+            entries = summary.entries,
+            path = self.output.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use super::CommonOptions;
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use itertools::Itertools;
+use std::time::Duration;
+use warg_protocol::{package::Permission, registry::PackageName};
+
+const DEFAULT_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Onboard maintainers of a package through invites.
+#[derive(Args)]
+pub struct InviteCommand {
+    /// The subcommand to execute.
+    #[clap(subcommand)]
+    pub command: InviteSubcommand,
+}
+
+impl InviteCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        match self.command {
+            InviteSubcommand::Create(cmd) => cmd.exec().await,
+            InviteSubcommand::Redeem(cmd) => cmd.exec().await,
+            InviteSubcommand::Grant(cmd) => cmd.exec().await,
+        }
+    }
+}
+
+/// The subcommand to execute.
+#[derive(Subcommand)]
+pub enum InviteSubcommand {
+    /// Creates an invite for a new maintainer of a package.
+    Create(InviteCreateCommand),
+    /// Redeems an invite of a package with your signing key.
+    Redeem(InviteRedeemCommand),
+    /// Grants permissions to the keys that redeemed your invites of a package.
+    Grant(InviteGrantCommand),
+}
+
+/// Creates an invite for a new maintainer of a package.
+#[derive(Args)]
+pub struct InviteCreateCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The permission(s) granted by the invite.
+    #[clap(
+        long = "permission",
+        value_delimiter = ',',
+        default_value = "release,yank"
+    )]
+    pub permissions: Vec<Permission>,
+    /// The number of hours until the invite expires.
+    #[clap(long, value_name = "HOURS", default_value_t = 7 * 24)]
+    pub expires_in: u64,
+}
+
+impl InviteCreateCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let invite = client
+            .create_invite(
+                &signing_key,
+                &self.name,
+                &self.permissions,
+                Duration::from_secs(self.expires_in * 60 * 60),
+            )
+            .await?;
+
+        println!(
+            "created invite `{id}` granting ({permissions_str}) for package `{name}`",
+            id = invite.id,
+            permissions_str = invite.permissions.iter().join(","),
+            name = self.name
+        );
+        println!(
+            "the invite may be redeemed with `warg invite redeem --name {name} {id}`",
+            name = self.name,
+            id = invite.id
+        );
+        Ok(())
+    }
+}
+
+/// Redeems an invite of a package with your signing key.
+#[derive(Args)]
+pub struct InviteRedeemCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The identifier of the invite.
+    #[clap(value_name = "INVITE")]
+    pub invite: String,
+}
+
+impl InviteRedeemCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let invite = client
+            .redeem_invite(&signing_key, &self.name, &self.invite)
+            .await?;
+
+        println!(
+            "redeemed invite `{id}` with key ID `{key_id}`; ({permissions_str}) for package `{name}` will be granted by the key that created the invite",
+            id = invite.id,
+            key_id = signing_key.public_key().fingerprint(),
+            permissions_str = invite.permissions.iter().join(","),
+            name = self.name
+        );
+        Ok(())
+    }
+}
+
+/// Grants permissions to the keys that redeemed your invites of a package.
+#[derive(Args)]
+pub struct InviteGrantCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl InviteGrantCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let Some(grant) = client.grant_invites(&signing_key, &self.name).await? else {
+            println!(
+                "no redeemed invites of package `{name}` await a grant",
+                name = self.name
+            );
+            return Ok(());
+        };
+
+        if self.no_wait {
+            println!(
+                "submitted record `{record_id}` for publishing",
+                record_id = grant.record_id
+            );
+        } else {
+            client
+                .wait_for_publish(&self.name, &grant.record_id, DEFAULT_WAIT_INTERVAL)
+                .await?;
+        }
+
+        for info in &grant.invites {
+            if let Some(redemption) = &info.redemption {
+                println!(
+                    "granted ({permissions_str}) to key ID `{key_id}` for package `{name}` (invite `{id}`)",
+                    permissions_str = info.invite.as_ref().permissions.iter().join(","),
+                    key_id = redemption.as_ref().key.fingerprint(),
+                    name = self.name,
+                    id = info.invite.as_ref().id
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
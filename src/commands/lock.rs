@@ -1,6 +1,8 @@
 use super::CommonOptions;
 use anyhow::Result;
 use clap::Args;
+use std::path::PathBuf;
+use warg_client::lockfile::LOCKFILE_NAME;
 use warg_protocol::registry::PackageName;
 
 /// Print Dependency Tree
@@ -13,6 +15,11 @@ pub struct LockCommand {
     /// Only show information for the specified package.
     #[clap(value_name = "PACKAGE")]
     pub package: PackageName,
+
+    /// Also write a lockfile recording the registry and version of each
+    /// resolved dependency.
+    #[clap(long, value_name = "PATH", num_args = 0..=1, default_missing_value = LOCKFILE_NAME)]
+    pub lockfile: Option<PathBuf>,
 }
 
 impl LockCommand {
@@ -24,6 +31,17 @@ impl LockCommand {
         let info = client.package(&self.package).await?;
         client.lock_component(&info).await?;
 
+        if let Some(path) = &self.lockfile {
+            let lockfile = client.lock_dependencies(&info).await?;
+            lockfile.write_to_file(path)?;
+            println!(
+                "locked {packages} package(s) from {registries} registry(ies) in `{path}`",
+                packages = lockfile.packages.len(),
+                registries = lockfile.registries.len(),
+                path = path.display()
+            );
+        }
+
         Ok(())
     }
 }
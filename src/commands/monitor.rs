@@ -0,0 +1,99 @@
+use super::CommonOptions;
+use anyhow::{bail, Result};
+use clap::Args;
+use std::{path::PathBuf, time::Duration};
+use warg_client::{
+    monitor::{LogAlertSink, Monitor, WebhookAlertSink},
+    RegistryUrl,
+};
+
+/// Monitor registries for misbehavior and archive their logs.
+#[derive(Args)]
+pub struct MonitorCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The URLs of the registries to monitor.
+    ///
+    /// If not specified, the registry of the common options is monitored.
+    #[clap(value_name = "URL")]
+    pub registries: Vec<String>,
+
+    /// The directory to archive checkpoints, records, and alerts to.
+    ///
+    /// Each registry is archived to a subdirectory; monitoring resumes from a previous archive.
+    #[clap(long, value_name = "DIR")]
+    pub archive: PathBuf,
+
+    /// A webhook URL to post alerts to as JSON.
+    #[clap(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// The number of seconds to wait between polls of the registries.
+    #[clap(long, value_name = "SECONDS", default_value_t = 60)]
+    pub interval: u64,
+
+    /// Poll the registries once and exit, failing if any alerts were raised.
+    #[clap(long)]
+    pub once: bool,
+}
+
+impl MonitorCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let mut registries = self.registries;
+        if registries.is_empty() {
+            let config = self.common.read_config()?;
+            let project = self.common.read_project_config()?;
+            match self
+                .common
+                .registry
+                .or(project.registry)
+                .or(config.home_url)
+            {
+                Some(registry) => registries.push(registry),
+                None => bail!("no registry to monitor was specified"),
+            }
+        }
+
+        let mut monitors = Vec::with_capacity(registries.len());
+        for registry in &registries {
+            let url = RegistryUrl::new(registry)?;
+            let mut monitor = Monitor::new(url.to_string(), self.archive.join(url.safe_label()))?
+                .with_alert_sink(LogAlertSink);
+            if let Some(webhook) = &self.webhook {
+                monitor = monitor.with_alert_sink(WebhookAlertSink::new(webhook.as_str())?);
+            }
+
+            println!(
+                "monitoring registry `{url}` from log length {log_length}...",
+                log_length = monitor.checkpoint().map_or(0, |c| c.log_length)
+            );
+            monitors.push(monitor);
+        }
+
+        loop {
+            let mut alerts = 0;
+            for monitor in &mut monitors {
+                match monitor.poll().await {
+                    Ok(raised) => alerts += raised.len(),
+                    Err(e) if !self.once => {
+                        tracing::warn!("failed to poll registry `{url}`: {e}", url = monitor.url());
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            if self.once {
+                if alerts > 0 {
+                    bail!("{alerts} alert(s) were raised");
+                }
+
+                return Ok(());
+            }
+
+            tokio::time::sleep(Duration::from_secs(self.interval)).await;
+        }
+    }
+}
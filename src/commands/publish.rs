@@ -4,10 +4,16 @@ use clap::{Args, Subcommand};
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use futures::TryStreamExt;
 use itertools::Itertools;
-use std::{future::Future, path::PathBuf, time::Duration};
+use std::{
+    future::Future,
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
 use tokio::io::BufReader;
 use tokio_util::io::ReaderStream;
+use url::Url;
 use warg_client::{
+    api::ContentSource,
     storage::{ContentStorage as _, PublishEntry, PublishInfo, RegistryStorage as _},
     FileSystemClient,
 };
@@ -16,7 +22,8 @@ use warg_crypto::{
     signing::{KeyID, PublicKey},
 };
 use warg_protocol::{
-    package::Permission,
+    metadata::{ReleaseLink, ReleaseNotes, DOCS_ARCHIVE_KIND, RELEASE_NOTES_KIND},
+    package::{ContentType, Permission},
     registry::{PackageName, RecordId},
     Version,
 };
@@ -66,6 +73,16 @@ pub enum PublishCommand {
     Release(PublishReleaseCommand),
     /// Yank a package version.
     Yank(PublishYankCommand),
+    /// Attach an attestation to a package version.
+    Attest(PublishAttestCommand),
+    /// Attach release notes to a package version.
+    Notes(PublishNotesCommand),
+    /// Attach a documentation archive to a package version.
+    Docs(PublishDocsCommand),
+    /// Deprecate a package.
+    Deprecate(PublishDeprecateCommand),
+    /// Remove the deprecation of a package.
+    Undeprecate(PublishUndeprecateCommand),
     /// Grant permissions for the package.
     Grant(PublishGrantCommand),
     /// Revoke permissions for the package.
@@ -80,6 +97,12 @@ pub enum PublishCommand {
     Submit(PublishSubmitCommand),
     /// Wait for a pending publish to complete.
     Wait(PublishWaitCommand),
+    /// Commit a staged publish.
+    Commit(PublishCommitCommand),
+    /// Abandon a staged publish.
+    Abandon(PublishAbandonCommand),
+    /// Approve a publish awaiting approval.
+    Approve(PublishApproveCommand),
 }
 
 impl PublishCommand {
@@ -89,6 +112,11 @@ impl PublishCommand {
             Self::Init(cmd) => cmd.exec().await,
             Self::Release(cmd) => cmd.exec().await,
             Self::Yank(cmd) => cmd.exec().await,
+            Self::Attest(cmd) => cmd.exec().await,
+            Self::Notes(cmd) => cmd.exec().await,
+            Self::Docs(cmd) => cmd.exec().await,
+            Self::Deprecate(cmd) => cmd.exec().await,
+            Self::Undeprecate(cmd) => cmd.exec().await,
             Self::Grant(cmd) => cmd.exec().await,
             Self::Revoke(cmd) => cmd.exec().await,
             Self::Start(cmd) => cmd.exec().await,
@@ -96,6 +124,9 @@ impl PublishCommand {
             Self::Abort(cmd) => cmd.exec().await,
             Self::Submit(cmd) => cmd.exec().await,
             Self::Wait(cmd) => cmd.exec().await,
+            Self::Commit(cmd) => cmd.exec().await,
+            Self::Abandon(cmd) => cmd.exec().await,
+            Self::Approve(cmd) => cmd.exec().await,
         }
     }
 }
@@ -179,6 +210,153 @@ pub struct PublishReleaseCommand {
     #[clap(long, short, value_name = "VERSION")]
     pub version: Version,
     /// The path to the package being published.
+    #[clap(value_name = "PATH", required_unless_present = "digest")]
+    pub path: Option<PathBuf>,
+    /// The artifact type of the content being published (`component`,
+    /// `wit-package` or `docs-archive`).
+    #[clap(long, value_name = "TYPE", default_value = "component")]
+    pub content_type: ContentType,
+    /// A URL the content is hosted at, which the registry fetches the
+    /// content from instead of it being uploaded.
+    ///
+    /// Sources are tried in the order they are specified.
+    #[clap(long = "source", value_name = "URL")]
+    pub sources: Vec<Url>,
+    /// The digest of content hosted at the given sources, to release it
+    /// without a local copy of the content.
+    #[clap(
+        long,
+        value_name = "DIGEST",
+        requires = "sources",
+        conflicts_with = "path"
+    )]
+    pub digest: Option<AnyHash>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishReleaseCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        // Content sources are declared when the record is submitted, so the
+        // release cannot be added to a pending publish
+        if !self.sources.is_empty() && client.registry().load_publish().await?.is_some() {
+            bail!("content sources cannot be specified for a release added to a pending publish");
+        }
+
+        let path = self.path.clone();
+        let digest = self.digest.clone();
+        let version = self.version.clone();
+        let content_type = self.content_type;
+        match enqueue(&client, &self.name, move |c| async move {
+            // Content hosted elsewhere is released by its digest alone, as
+            // the registry fetches it from its sources
+            let content = match (path, digest) {
+                (Some(path), _) => {
+                    c.content()
+                        .store_content(
+                            Box::pin(
+                                ReaderStream::new(BufReader::new(
+                                    tokio::fs::File::open(&path).await.with_context(|| {
+                                        format!("failed to open `{path}`", path = path.display())
+                                    })?,
+                                ))
+                                .map_err(|e| anyhow!(e)),
+                            ),
+                            None,
+                        )
+                        .await?
+                }
+                (None, Some(digest)) => digest,
+                (None, None) => bail!("either a path or a digest of the content is required"),
+            };
+
+            Ok(PublishEntry::Release {
+                version,
+                content,
+                content_type,
+            })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let info = PublishInfo {
+                    name: self.name.clone(),
+                    head: None,
+                    entries: vec![entry],
+                };
+                let record_id = match &info.entries[0] {
+                    PublishEntry::Release { content, .. } if !self.sources.is_empty() => {
+                        let sources = self
+                            .sources
+                            .iter()
+                            .map(|url| ContentSource::HttpGet {
+                                url: url.to_string(),
+                                accept_ranges: false,
+                                size: None,
+                            })
+                            .collect();
+                        client
+                            .publish_with_content_sources(
+                                &signing_key,
+                                info.clone(),
+                                [(content.clone(), sources)].into_iter().collect(),
+                            )
+                            .await?
+                    }
+                    _ => client.publish_with_info(&signing_key, info).await?,
+                };
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "published version {version} of package `{name}`",
+                        version = self.version,
+                        name = self.name
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added release of version {version} for package `{name}` to pending publish",
+                    version = self.version,
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Attach an attestation to a package release.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishAttestCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name being attested.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The version of the package being attested.
+    #[clap(long, short, value_name = "VERSION")]
+    pub version: Version,
+    /// The kind of the attestation (e.g. `https://slsa.dev/provenance/v1`).
+    #[clap(long, short, value_name = "KIND")]
+    pub kind: String,
+    /// The path to the attestation being attached.
     #[clap(value_name = "PATH")]
     pub path: PathBuf,
     /// Whether to wait for the publish to complete.
@@ -186,7 +364,7 @@ pub struct PublishReleaseCommand {
     pub no_wait: bool,
 }
 
-impl PublishReleaseCommand {
+impl PublishAttestCommand {
     /// Executes the command.
     pub async fn exec(self) -> Result<()> {
         let config = self.common.read_config()?;
@@ -196,6 +374,7 @@ impl PublishReleaseCommand {
 
         let path = self.path.clone();
         let version = self.version.clone();
+        let kind = self.kind.clone();
         match enqueue(&client, &self.name, move |c| async move {
             let content = c
                 .content()
@@ -212,7 +391,11 @@ impl PublishReleaseCommand {
                 )
                 .await?;
 
-            Ok(PublishEntry::Release { version, content })
+            Ok(PublishEntry::Attest {
+                version,
+                kind,
+                content,
+            })
         })
         .await?
         {
@@ -236,7 +419,8 @@ impl PublishReleaseCommand {
                         .await?;
 
                     println!(
-                        "published version {version} of package `{name}`",
+                        "attached `{kind}` attestation to version {version} of package `{name}`",
+                        kind = self.kind,
                         version = self.version,
                         name = self.name
                     );
@@ -244,7 +428,219 @@ impl PublishReleaseCommand {
             }
             None => {
                 println!(
-                    "added release of version {version} for package `{name}` to pending publish",
+                    "added `{kind}` attestation of version {version} for package `{name}` to pending publish",
+                    kind = self.kind,
+                    version = self.version,
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_link(s: &str) -> Result<ReleaseLink> {
+    match s.split_once('=') {
+        Some((title, url)) if !title.is_empty() && !url.is_empty() => Ok(ReleaseLink {
+            title: title.to_string(),
+            url: url.to_string(),
+        }),
+        _ => bail!("expected a link of the form `TITLE=URL`"),
+    }
+}
+
+/// Attach release notes to a package release.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishNotesCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name of the release.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The version of the release.
+    #[clap(long, short, value_name = "VERSION")]
+    pub version: Version,
+    /// The path to a Markdown file of the release notes.
+    #[clap(long, value_name = "PATH")]
+    pub notes: Option<PathBuf>,
+    /// A link related to the release, such as a changelog.
+    #[clap(long = "link", value_name = "TITLE=URL", value_parser = parse_link)]
+    pub links: Vec<ReleaseLink>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishNotesCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        if self.notes.is_none() && self.links.is_empty() {
+            bail!("either `--notes` or `--link` must be specified");
+        }
+
+        let notes = ReleaseNotes {
+            notes: self
+                .notes
+                .as_ref()
+                .map(|path| {
+                    std::fs::read_to_string(path)
+                        .with_context(|| format!("failed to read `{path}`", path = path.display()))
+                })
+                .transpose()?,
+            links: self.links,
+        };
+
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let version = self.version.clone();
+        match enqueue(&client, &self.name, move |c| async move {
+            let bytes = notes.to_content()?;
+            let content = c
+                .content()
+                .store_content(
+                    Box::pin(futures::stream::once(async { Ok(bytes.into()) })),
+                    None,
+                )
+                .await?;
+
+            Ok(PublishEntry::Attest {
+                version,
+                kind: RELEASE_NOTES_KIND.to_string(),
+                content,
+            })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "attached release notes to version {version} of package `{name}`",
+                        version = self.version,
+                        name = self.name
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added release notes of version {version} for package `{name}` to pending publish",
+                    version = self.version,
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Attach a documentation archive to a package release.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishDocsCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name of the release.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The version of the release.
+    #[clap(long, short, value_name = "VERSION")]
+    pub version: Version,
+    /// The path to a gzip-compressed tar archive of the documentation, with
+    /// an `index.html` at its root.
+    #[clap(value_name = "PATH")]
+    pub path: PathBuf,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishDocsCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let path = self.path.clone();
+        let version = self.version.clone();
+        match enqueue(&client, &self.name, move |c| async move {
+            let content = c
+                .content()
+                .store_content(
+                    Box::pin(
+                        ReaderStream::new(BufReader::new(
+                            tokio::fs::File::open(&path).await.with_context(|| {
+                                format!("failed to open `{path}`", path = path.display())
+                            })?,
+                        ))
+                        .map_err(|e| anyhow!(e)),
+                    ),
+                    None,
+                )
+                .await?;
+
+            Ok(PublishEntry::Attest {
+                version,
+                kind: DOCS_ARCHIVE_KIND.to_string(),
+                content,
+            })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "attached documentation to version {version} of package `{name}`",
+                        version = self.version,
+                        name = self.name
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added documentation of version {version} for package `{name}` to pending publish",
                     version = self.version,
                     name = self.name
                 );
@@ -340,6 +736,144 @@ Yank `{version}` of `{package}`?",
     }
 }
 
+/// Deprecate a package in a warg registry.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishDeprecateCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name being deprecated.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The reason the package is deprecated.
+    #[clap(long, short, value_name = "MESSAGE")]
+    pub message: String,
+    /// The name of the package that replaces the deprecated package.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub replacement: Option<PackageName>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishDeprecateCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let message = self.message.clone();
+        let replacement = self.replacement.clone();
+        match enqueue(&client, &self.name, move |_| async move {
+            Ok(PublishEntry::Deprecate {
+                message,
+                replacement,
+            })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!("deprecated package `{name}`", name = self.name);
+                }
+            }
+            None => {
+                println!(
+                    "added deprecation of package `{name}` to pending publish",
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Remove the deprecation of a package in a warg registry.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishUndeprecateCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name being undeprecated.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishUndeprecateCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        match enqueue(&client, &self.name, move |_| async move {
+            Ok(PublishEntry::Undeprecate)
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "removed the deprecation of package `{name}`",
+                        name = self.name
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added removal of the deprecation of package `{name}` to pending publish",
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Publish a package to a warg registry.
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -570,12 +1104,35 @@ impl PublishListCommand {
                         PublishEntry::Init => {
                             println!("initialize package");
                         }
-                        PublishEntry::Release { version, content } => {
-                            println!("release {version} with content digest `{content}`")
+                        PublishEntry::Release {
+                            version,
+                            content,
+                            content_type,
+                        } => {
+                            println!(
+                                "release {version} with {content_type} content digest `{content}`"
+                            )
                         }
                         PublishEntry::Yank { version } => {
                             println!("yank {version}")
                         }
+                        PublishEntry::Attest {
+                            version,
+                            kind,
+                            content,
+                        } => println!(
+                            "attest {version} with `{kind}` attestation digest `{content}`"
+                        ),
+                        PublishEntry::Deprecate {
+                            message,
+                            replacement,
+                        } => match replacement {
+                            Some(replacement) => {
+                                println!("deprecate in favor of `{replacement}`: {message}")
+                            }
+                            None => println!("deprecate: {message}"),
+                        },
+                        PublishEntry::Undeprecate => println!("undeprecate"),
                         PublishEntry::Grant { key, permissions } => println!(
                             "grant ({permissions_str}) to `{key_id}`",
                             permissions_str = permissions.iter().join(","),
@@ -636,6 +1193,14 @@ pub struct PublishSubmitCommand {
     /// Whether to wait for the publish to complete.
     #[clap(long)]
     pub no_wait: bool,
+    /// Stage the publish in the registry to be committed later.
+    #[clap(long, conflicts_with = "no_wait")]
+    pub stage: bool,
+    /// Embargo the published content until the given time, in seconds since the Unix epoch.
+    ///
+    /// The registry does not serve the content or list its releases until the embargo passes.
+    #[clap(long, value_name = "TIMESTAMP", conflicts_with = "stage")]
+    pub embargo_until: Option<u64>,
 }
 
 impl PublishSubmitCommand {
@@ -652,11 +1217,28 @@ impl PublishSubmitCommand {
                 );
 
                 let signing_key = self.common.signing_key(None).await?;
-                let record_id = client.publish_with_info(&signing_key, info.clone()).await?;
+                let record_id = if self.stage {
+                    client.stage_with_info(&signing_key, info.clone()).await?
+                } else if let Some(until) = self.embargo_until {
+                    client
+                        .publish_embargoed_with_info(
+                            &signing_key,
+                            info.clone(),
+                            UNIX_EPOCH + Duration::from_secs(until),
+                        )
+                        .await?
+                } else {
+                    client.publish_with_info(&signing_key, info.clone()).await?
+                };
 
                 client.registry().store_publish(None).await?;
 
-                if self.no_wait {
+                if self.stage {
+                    println!(
+                        "staged record `{record_id}`; run `warg publish commit {name} {record_id}` to publish it",
+                        name = info.name
+                    );
+                } else if self.no_wait {
                     println!("submitted record `{record_id}` for publishing");
                 } else {
                     client
@@ -675,6 +1257,15 @@ impl PublishSubmitCommand {
                             PublishEntry::Yank { version } => {
                                 println!("yanked version {version} of package `{name}`")
                             }
+                            PublishEntry::Attest { version, kind, .. } => println!(
+                                "attached `{kind}` attestation to version {version} of package `{name}`"
+                            ),
+                            PublishEntry::Deprecate { .. } => {
+                                println!("deprecated package `{name}`")
+                            }
+                            PublishEntry::Undeprecate => {
+                                println!("removed the deprecation of package `{name}`")
+                            }
                             PublishEntry::Grant { key, permissions } => {
                                 println!(
                                     "granted ({permissions_str}) to `{key_id}`",
@@ -740,3 +1331,133 @@ impl PublishWaitCommand {
         Ok(())
     }
 }
+
+/// Commit a staged publish.
+#[derive(Args)]
+pub struct PublishCommitCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The name of the staged package.
+    #[clap(value_name = "PACKAGE")]
+    pub name: PackageName,
+
+    /// The identifier of the staged package record.
+    #[clap(value_name = "RECORD")]
+    pub record_id: AnyHash,
+
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishCommitCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let record_id = RecordId::from(self.record_id);
+
+        client.commit_staged(&self.name, &record_id).await?;
+
+        if self.no_wait {
+            println!("committed record `{record_id}` for publishing");
+        } else {
+            client
+                .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                .await?;
+
+            println!(
+                "record `{record_id}` of package `{name}` has been published",
+                name = self.name
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Abandon a staged publish.
+#[derive(Args)]
+pub struct PublishAbandonCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The name of the staged package.
+    #[clap(value_name = "PACKAGE")]
+    pub name: PackageName,
+
+    /// The identifier of the staged package record.
+    #[clap(value_name = "RECORD")]
+    pub record_id: AnyHash,
+}
+
+impl PublishAbandonCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let record_id = RecordId::from(self.record_id);
+
+        client.abandon_staged(&self.name, &record_id).await?;
+
+        println!(
+            "abandoned staged record `{record_id}` of package `{name}`",
+            name = self.name
+        );
+
+        Ok(())
+    }
+}
+
+/// Approve a publish awaiting approval.
+#[derive(Args)]
+pub struct PublishApproveCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The name of the package.
+    #[clap(value_name = "PACKAGE")]
+    pub name: PackageName,
+
+    /// The identifier of the package record awaiting approval.
+    #[clap(value_name = "RECORD")]
+    pub record_id: AnyHash,
+
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishApproveCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+        let record_id = RecordId::from(self.record_id);
+
+        client
+            .approve_record(&signing_key, &self.name, &record_id)
+            .await?;
+
+        if self.no_wait {
+            println!("approved record `{record_id}` for publishing");
+        } else {
+            client
+                .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                .await?;
+
+            println!(
+                "record `{record_id}` of package `{name}` has been published",
+                name = self.name
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use super::CommonOptions;
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+use warg_client::{
+    lockfile::{Lockfile, LOCKFILE_NAME},
+    vendor::VendorManifest,
+};
+
+/// Copy the locked dependencies of a project into a vendor directory.
+#[derive(Args)]
+pub struct VendorCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The path to the lockfile of the dependencies to vendor.
+    #[clap(long, value_name = "PATH", default_value = LOCKFILE_NAME)]
+    pub lockfile: PathBuf,
+
+    /// Only verify the content of the vendor directory against its
+    /// manifest, without contacting a registry.
+    #[clap(long)]
+    pub verify: bool,
+
+    /// The path to the vendor directory.
+    #[clap(value_name = "DIR", default_value = "vendor")]
+    pub dir: PathBuf,
+}
+
+impl VendorCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        if self.verify {
+            let manifest = VendorManifest::from_dir(&self.dir)?;
+            manifest.verify(&self.dir)?;
+            println!(
+                "verified {packages} vendored package(s) in `{dir}`",
+                packages = manifest.packages.len(),
+                dir = self.dir.display()
+            );
+            return Ok(());
+        }
+
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+
+        let lockfile = Lockfile::from_file(&self.lockfile)?;
+        let manifest = client.vendor(&lockfile, &self.dir).await?;
+        println!(
+            "vendored {packages} package(s) into `{dir}`",
+            packages = manifest.packages.len(),
+            dir = self.dir.display()
+        );
+
+        Ok(())
+    }
+}
@@ -1,11 +1,69 @@
 use self::support::*;
 use anyhow::{bail, Context, Result};
-use std::{fs, time::Duration};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use p256::ecdsa::{signature::Signer, DerSignature};
+use rand_core::OsRng;
+use std::{
+    fs,
+    net::Ipv4Addr,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use warg_api::{
+    v1::{
+        capabilities::{AuthMode, CapabilitiesResponse, UploadProtocol},
+        paths,
+        problem::ProblemCode,
+    },
+    ApiVersion,
+};
 use warg_client::{
-    storage::{ContentStorage, PublishEntry, PublishInfo, RegistryStorage},
-    Config, FileSystemClient, StorageLockResult,
+    api,
+    audit::SigningAuditQuery,
+    deadline,
+    export::export_archive,
+    limits::ConcurrencyLimits,
+    middleware::{Middleware, Next},
+    mirror::MirrorDivergence,
+    monitor::{AlertSink, Monitor, MonitorAlert},
+    policy::{PackagePolicy, PolicyViolation, ResolvedPackage, SemverCheck, SigningPolicy},
+    progress::SyncProgress,
+    provenance::{self, ProvenancePolicy},
+    storage::{
+        ContentStorage, FileSystemContentStorage, FileSystemNamespaceMapStorage,
+        FileSystemRegistryStorage, NamespaceMapStorage, PublishEntry, PublishInfo, RegistryStorage,
+        SigningOutcome, StorageLayout,
+    },
+    verification::{CheckOutcome, VerificationAnomaly},
+    Client, ClientError, Config, DynClient, FileSystemClient, RegistryUrl, StorageLockResult,
+};
+use warg_crypto::{
+    hash::{AnyHash, Hash, HashAlgorithm, Sha256},
+    signing::generate_p256_pair,
+};
+use warg_protocol::{
+    metadata::{ReleaseLink, ReleaseNotes, RELEASE_NOTES_KIND},
+    package::{ContentType, ReleaseChannel},
+    registry::{LogId, PackageName, RecordId},
+};
+use warg_server::api::throttle::ThrottleConfig;
+use x509_cert::{
+    builder::{Builder, CertificateBuilder, Profile},
+    der::{
+        asn1::{Ia5String, ObjectIdentifier, Utf8StringRef},
+        oid::AssociatedOid,
+        Encode,
+    },
+    ext::{
+        pkix::{name::GeneralName, SubjectAltName},
+        AsExtension, Extension,
+    },
+    name::Name,
+    serial_number::SerialNumber,
+    spki::SubjectPublicKeyInfoOwned,
+    time::Validity,
 };
-use warg_protocol::registry::PackageName;
 
 pub mod support;
 
@@ -16,6 +74,27 @@ async fn create_client(config: &Config) -> Result<FileSystemClient> {
     }
 }
 
+/// Spawns a server that only serves the given JSON documents at the given paths.
+///
+/// Returns the URL of the server.
+async fn spawn_json_server(documents: Vec<(&str, serde_json::Value)>) -> Result<String> {
+    let mut router = axum::Router::new();
+    for (path, document) in documents {
+        router = router.route(
+            &format!("/{path}"),
+            axum::routing::get(move || {
+                let document = document.clone();
+                async move { axum::Json(document) }
+            }),
+        );
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    Ok(format!("http://{addr}"))
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn client_incrementally_fetches() -> Result<()> {
     const RELEASE_COUNT: usize = 10;
@@ -66,6 +145,7 @@ async fn client_incrementally_fetches() -> Result<()> {
                     entries: vec![PublishEntry::Release {
                         version: format!("0.{i}.0").parse().unwrap(),
                         content: digest.clone(),
+                        content_type: ContentType::Component,
                     }],
                 },
             )
@@ -115,3 +195,2830 @@ async fn client_incrementally_fetches() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_stores_synced_batches() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let names = [
+        PackageName::new("test:first")?,
+        PackageName::new("test:second")?,
+    ];
+    for name in &names {
+        publish_component(&client, name, "0.1.0", "(component)", true, &signing_key).await?;
+    }
+
+    // Sync one package log at a time into separate storage, failing on the
+    // last package
+    let mut other_config = config.clone();
+    other_config.registries_dir = Some(root.join("other-registries"));
+    other_config.content_dir = Some(root.join("other-content"));
+    other_config.namespace_map_path = Some(root.join("other-namespaces"));
+    let other = create_client(&other_config).await?.with_sync_batch_size(1);
+    let missing = PackageName::new("test:missing")?;
+    match other.fetch_packages([&names[0], &names[1], &missing]).await {
+        Err(ClientError::PackageDoesNotExist { name, .. }) => assert_eq!(name, missing),
+        res => bail!("expected the sync to fail, got {res:?}"),
+    }
+
+    // The batches synced before the failure were stored
+    for name in &names {
+        let info = other
+            .registry()
+            .load_package(None, name)
+            .await?
+            .context("expected package to be stored")?;
+        assert!(info.checkpoint.is_some());
+        assert!(info.state.release(&"0.1.0".parse()?).is_some());
+    }
+    assert!(other
+        .registry()
+        .load_package(None, &missing)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_diffs_storage_state() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:diffed")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let mut other_config = config.clone();
+    other_config.registries_dir = Some(root.join("other-registries"));
+    other_config.content_dir = Some(root.join("other-content"));
+    other_config.namespace_map_path = Some(root.join("other-namespaces"));
+    let other = create_client(&other_config).await?;
+    other.fetch_packages([&name]).await?;
+
+    publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key).await?;
+    client.fetch_packages([&name]).await?;
+
+    // The version published after the other storage was synced is missing from it
+    let diff = client.diff_state(other.registry()).await?;
+    assert!(diff.only_in_other.is_empty());
+    assert!(diff.mismatched.is_empty());
+    assert_eq!(diff.only_in_self.len(), 1);
+    assert_eq!(diff.only_in_self[0].name, name);
+    assert_eq!(diff.only_in_self[0].version.to_string(), "0.2.0");
+    assert!(diff.only_in_self[0].digest.is_some());
+
+    let diff = other.diff_state(client.registry()).await?;
+    assert_eq!(diff.only_in_other.len(), 1);
+    assert!(diff.only_in_self.is_empty());
+
+    other.update().await?;
+    assert!(client.diff_state(other.registry()).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_diffs_releases() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:diffed-releases")?;
+    publish_wit(
+        &client,
+        &name,
+        "1.0.0",
+        "package test:diffed;
+        interface greet {
+            greet: func(name: string) -> string;
+            farewell: func();
+        }",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    let mut metadata = wasm_metadata::RegistryMetadata::default();
+    metadata.set_license(Some("MIT".to_string()));
+    let content = metadata.add_to_wasm(&encode_wit(
+        "package test:diffed;
+        interface greet {
+            greet: func(name: string, excited: bool) -> string;
+            farewell: func();
+            wave: func();
+        }",
+    )?)?;
+    publish(&client, &name, "1.1.0", content, false, &signing_key).await?;
+
+    let diff = client
+        .diff_releases(&name, &"1.0.0".parse()?, &"1.1.0".parse()?)
+        .await?;
+    assert_eq!(
+        diff.interface_changes
+            .iter()
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>(),
+        [
+            "function `greet` of export `test:diffed/greet` changed from \
+             `func(name: string) -> string` to `func(name: string, excited: bool) -> string`",
+            "function `wave` was added to export `test:diffed/greet`",
+        ]
+    );
+    assert!(diff.is_breaking());
+    assert!(diff.size_delta() > 0);
+    assert_eq!(diff.metadata_changes.len(), 1);
+    assert_eq!(diff.metadata_changes[0].field, "license");
+    assert_eq!(diff.metadata_changes[0].from, None);
+    assert_eq!(diff.metadata_changes[0].to.as_deref(), Some("MIT"));
+
+    // A release compared with itself has no differences
+    let diff = client
+        .diff_releases(&name, &"1.1.0".parse()?, &"1.1.0".parse()?)
+        .await?;
+    assert!(diff.interface_changes.is_empty());
+    assert!(diff.metadata_changes.is_empty());
+    assert_eq!(diff.size_delta(), 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_fetches_custom_sections() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:sections")?;
+    let mut metadata = wasm_metadata::RegistryMetadata::default();
+    metadata.set_license(Some("MIT".to_string()));
+    let content = metadata.add_to_wasm(&wat::parse_str(
+        r#"(component (@custom "other" "ignored") (core module))"#,
+    )?)?;
+    let digest = publish(&client, &name, "1.0.0", content, true, &signing_key).await?;
+
+    // Only the requested sections are downloaded
+    client.clear_content_cache().await?;
+    let sections = client
+        .fetch_custom_sections(&name, &"1.0.0".parse()?, &["registry-metadata"])
+        .await?;
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].name, "registry-metadata");
+    assert_eq!(
+        wasm_metadata::RegistryMetadata::from_bytes(&sections[0].data, 0)?
+            .get_license()
+            .map(String::as_str),
+        Some("MIT")
+    );
+    assert!(client.content().content_location(&digest).is_none());
+
+    // Sections of stored content are read locally
+    client.download_exact(&name, &"1.0.0".parse()?).await?;
+    let local = client
+        .fetch_custom_sections(&name, &"1.0.0".parse()?, &["registry-metadata"])
+        .await?;
+    assert_eq!(local.len(), 1);
+    assert_eq!(local[0].data, sections[0].data);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_checks_semver_before_publishing() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config)
+        .await?
+        .with_semver_check(SemverCheck::Reject);
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:semver-preflight")?;
+    publish_wit(
+        &client,
+        &name,
+        "0.1.0",
+        "package test:preflight;\ninterface api { f: func(); }",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    let breaking = "package test:preflight;\ninterface api { f: func(x: u32); }";
+    match publish_wit(&client, &name, "0.1.1", breaking, false, &signing_key).await {
+        Err(e) => match e.downcast_ref::<ClientError>() {
+            Some(ClientError::IncompatibleRelease {
+                version, changes, ..
+            }) => {
+                assert_eq!(version.to_string(), "0.1.1");
+                assert_eq!(changes.len(), 1);
+            }
+            _ => bail!("expected an incompatible release error, got {e:?}"),
+        },
+        Ok(_) => bail!("expected publish to fail"),
+    }
+
+    // A minor version bump of a 0.x release may break interfaces
+    publish_wit(&client, &name, "0.2.0", breaking, false, &signing_key).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_recovers_torn_storage_files() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:torn")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+    client.fetch_packages([&name]).await?;
+    drop(client);
+
+    // Simulate a crash in the middle of writes to storage
+    let registry_dir = config
+        .registries_dir
+        .as_ref()
+        .unwrap()
+        .join(RegistryUrl::new(config.home_url.as_ref().unwrap())?.safe_label());
+    let package_path = registry_dir.join("package-logs").join(
+        LogId::package_log::<Sha256>(&name)
+            .to_string()
+            .replace(':', "/"),
+    );
+    assert!(package_path.is_file());
+    fs::write(&package_path, r#"{"name":"test:to"#)?;
+    let temp_path = registry_dir.join(".tmp-interrupted");
+    fs::write(&temp_path, "{")?;
+    let content_temp_path = config.content_dir.as_ref().unwrap().join("temp/partial");
+    fs::create_dir_all(content_temp_path.parent().unwrap())?;
+    fs::write(&content_temp_path, "partial")?;
+
+    // Temporary files are removed, but state files are not read
+    let client = create_client(&config).await?;
+    assert!(package_path.is_file());
+    assert!(!temp_path.exists());
+    assert!(!content_temp_path.exists());
+
+    // The torn package state is treated as absent and fetched again
+    let info = client.package(&name).await?;
+    assert_eq!(info.state.releases().count(), 1);
+    serde_json::from_slice::<serde_json::Value>(&fs::read(&package_path)?)?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_uses_workspace_storage() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:workspace")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+    drop(client);
+
+    // Content is read from the global content directory as a fallback
+    let workspace_dir = root.join("project").join(".warg");
+    let config = Config {
+        workspace_dir: Some(workspace_dir.clone()),
+        workspace_fallback: true,
+        ..config
+    };
+    let client = create_client(&config).await?;
+    let download = client
+        .download(&name, &"1.0.0".parse()?)
+        .await?
+        .context("missing download")?;
+    assert!(download
+        .path
+        .starts_with(config.content_dir.as_ref().unwrap()));
+    assert!(workspace_dir.join("registries").is_dir());
+    drop(client);
+
+    // Without the fallback, content is downloaded into the workspace
+    let config = Config {
+        workspace_fallback: false,
+        ..config
+    };
+    let client = create_client(&config).await?;
+    let download = client
+        .download(&name, &"1.0.0".parse()?)
+        .await?
+        .context("missing download")?;
+    assert!(download.path.starts_with(workspace_dir.join("content")));
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_uses_hashed_storage_layout() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+    let config = Config {
+        storage_layout: StorageLayout::Hashed,
+        ..config
+    };
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:hashed")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+    client.fetch_packages([&name]).await?;
+    drop(client);
+
+    let label = RegistryUrl::new(config.home_url.as_ref().unwrap())?.safe_label();
+    let registries_dir = config.registries_dir.as_ref().unwrap();
+    assert!(!registries_dir.join(&label).exists());
+
+    let dir_name = StorageLayout::Hashed.registry_dir_name(&label);
+    assert!(dir_name.len() <= 49);
+    assert!(!dir_name.contains(':'));
+
+    let id = LogId::package_log::<Sha256>(&name).to_string();
+    let (algorithm, hex) = id.split_once(':').unwrap();
+    let package_path = registries_dir
+        .join(&dir_name)
+        .join("package-logs")
+        .join(algorithm)
+        .join(&hex[..2])
+        .join(&hex[2..]);
+    assert!(package_path.is_file());
+
+    // The packages of the hashed layout are loaded again
+    let client = create_client(&config).await?;
+    let info = client.package(&name).await?;
+    assert_eq!(info.state.releases().count(), 1);
+    let packages = client.registry().load_all_packages().await?;
+    assert_eq!(packages.values().map(Vec::len).sum::<usize>(), 1);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_syncs_package_from_snapshot() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:snapshot")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+    for version in ["1.1.0", "1.2.0"] {
+        publish_component(&client, &name, version, "(component)", false, &signing_key).await?;
+    }
+
+    // The registry serves a signed snapshot at the latest checkpoint
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let log_length = api
+        .latest_checkpoint(None)
+        .await?
+        .as_ref()
+        .checkpoint
+        .log_length;
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let response = api.get_package_snapshot(None, &log_id, log_length).await?;
+    let snapshot = response.snapshot.as_ref();
+    assert_eq!(snapshot.log_id, log_id);
+    assert_eq!(snapshot.log_length, log_length);
+    assert_eq!(snapshot.state()?.releases().count(), 3);
+    assert_eq!(response.fetch_token, snapshot.record_id.to_string());
+
+    // A client with separate storage starts the package from the snapshot
+    let snapshot_config = Config {
+        registries_dir: Some(root.join("snapshot-registries")),
+        content_dir: Some(root.join("snapshot-content")),
+        namespace_map_path: Some(root.join("snapshot-namespaces")),
+        ..config.clone()
+    };
+    let snapshot_client = create_client(&snapshot_config)
+        .await?
+        .with_package_snapshots(true);
+    let info = snapshot_client.package(&name).await?;
+    assert_eq!(info.state, client.package(&name).await?.state);
+    assert_eq!(info.head_registry_index, Some(snapshot.registry_index));
+
+    // Later records are fetched and validated from the head of the snapshot
+    publish_component(&client, &name, "2.0.0", "(component)", false, &signing_key).await?;
+    snapshot_client.fetch_packages([&name]).await?;
+    let info = snapshot_client.package(&name).await?;
+    assert_eq!(info.state.releases().count(), 4);
+    assert_eq!(info.state, client.package(&name).await?.state);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_audits_content_cache() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:audited")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+    let download = client
+        .download(&name, &"0.1.0".parse()?)
+        .await?
+        .context("expected a download")?;
+
+    let audit = client.audit_content_cache(false).await?;
+    assert!(audit.is_intact());
+    assert!(audit.checked >= 1);
+
+    // Corrupt the cached content
+    let path = client
+        .content()
+        .content_location(&download.digest)
+        .context("expected cached content")?;
+    fs::write(&path, b"corrupt")?;
+
+    let audit = client.audit_content_cache(false).await?;
+    assert_eq!(audit.corrupted.len(), 1);
+    assert_eq!(audit.corrupted[0].digest, download.digest);
+    assert!(audit.corrupted[0].actual.is_some());
+    assert!(!audit.corrupted[0].repaired);
+
+    let audit = client.audit_content_cache(true).await?;
+    assert_eq!(audit.corrupted.len(), 1);
+    assert!(audit.corrupted[0].repaired);
+
+    assert!(client.audit_content_cache(false).await?.is_intact());
+    assert_eq!(fs::read(&path)?, wat::parse_str("(component)")?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_fetches_logs_in_pages() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:paged")?;
+    for (i, version) in ["0.1.0", "0.2.0", "0.3.0"].into_iter().enumerate() {
+        publish_component(&client, &name, version, "(component)", i == 0, &signing_key).await?;
+    }
+
+    let mut other_config = config.clone();
+    other_config.registries_dir = Some(root.join("other-registries"));
+    other_config.content_dir = Some(root.join("other-content"));
+    other_config.namespace_map_path = Some(root.join("other-namespaces"));
+    other_config.fetch_page_size = Some(1);
+    let other = create_client(&other_config).await?;
+
+    let info = other.fetch_package(&name).await?;
+    assert_eq!(info.state.releases().count(), 3);
+    assert_eq!(
+        info.head_registry_index,
+        client.fetch_package(&name).await?.head_registry_index
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_fetches_logs_with_streaming() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let names = [
+        PackageName::new("test:streamed")?,
+        PackageName::new("test:other-streamed")?,
+    ];
+    for name in &names {
+        for (i, version) in ["0.1.0", "0.2.0"].into_iter().enumerate() {
+            publish_component(&client, name, version, "(component)", i == 0, &signing_key).await?;
+        }
+    }
+
+    let mut other_config = config.clone();
+    other_config.registries_dir = Some(root.join("other-registries"));
+    other_config.content_dir = Some(root.join("other-content"));
+    other_config.namespace_map_path = Some(root.join("other-namespaces"));
+    other_config.fetch_page_size = Some(1);
+    let other = create_client(&other_config)
+        .await?
+        .with_streaming_fetch(true);
+
+    let packages = other.fetch_packages(&names).await?;
+    for (package, name) in packages.iter().zip(&names) {
+        assert_eq!(package.state.releases().count(), 2);
+        assert_eq!(
+            package.head_registry_index,
+            client.fetch_package(name).await?.head_registry_index
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_reports_sync_progress() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let names = [
+        PackageName::new("test:first")?,
+        PackageName::new("test:second")?,
+    ];
+    for name in &names {
+        publish_component(&client, name, "0.1.0", "(component)", true, &signing_key).await?;
+    }
+
+    let mut other_config = config.clone();
+    other_config.registries_dir = Some(root.join("other-registries"));
+    other_config.content_dir = Some(root.join("other-content"));
+    other_config.namespace_map_path = Some(root.join("other-namespaces"));
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let other = create_client(&other_config).await?.with_sync_progress({
+        let reports = reports.clone();
+        move |progress| reports.lock().unwrap().push(*progress)
+    });
+    other.fetch_packages(&names).await?;
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(
+        reports.last(),
+        Some(&SyncProgress {
+            registries: 1,
+            registries_synced: 1,
+            packages: 2,
+            packages_synced: 2,
+        })
+    );
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].packages_synced, 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_caches_verified_proofs() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let names = [
+        PackageName::new("test:first")?,
+        PackageName::new("test:second")?,
+    ];
+    for name in &names {
+        publish_component(&client, name, "0.1.0", "(component)", true, &signing_key).await?;
+    }
+
+    let mut other_config = config.clone();
+    other_config.registries_dir = Some(root.join("other-registries"));
+    other_config.content_dir = Some(root.join("other-content"));
+    other_config.namespace_map_path = Some(root.join("other-namespaces"));
+    let other = create_client(&other_config).await?;
+
+    // The operator log and the first package are proven
+    let first = other.fetch_package(&names[0]).await?;
+    let proofs = other
+        .registry()
+        .load_verified_proofs(None)
+        .await?
+        .context("expected verified proofs to be stored")?;
+    assert_eq!(Some(&proofs.checkpoint), first.checkpoint.as_ref());
+    assert_eq!(proofs.inclusions.len(), 2);
+
+    // Only the second package is proven at the same checkpoint
+    other.fetch_package(&names[1]).await?;
+    let proofs = other
+        .registry()
+        .load_verified_proofs(None)
+        .await?
+        .context("expected verified proofs to be stored")?;
+    assert_eq!(Some(&proofs.checkpoint), first.checkpoint.as_ref());
+    assert_eq!(proofs.inclusions.len(), 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_reports_verification() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:verified")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Publish from a client with separate storage so the first client's
+    // checkpoint is behind the latest checkpoint
+    let mut other_config = config.clone();
+    other_config.registries_dir = Some(root.join("other-registries"));
+    other_config.content_dir = Some(root.join("other-content"));
+    other_config.namespace_map_path = Some(root.join("other-namespaces"));
+    let other = create_client(&other_config).await?;
+    publish_component(&other, &name, "0.2.0", "(component)", false, &signing_key).await?;
+
+    let report = client.verification_report(&name).await?;
+    assert!(
+        report.is_verified(),
+        "unexpected anomalies: {:?}",
+        report.anomalies
+    );
+    assert!(report.inclusion.is_verified());
+    assert!(report.consistency.is_verified());
+    assert_eq!(report.checkpoints.len(), 2);
+    assert!(report.checkpoints.iter().all(|c| c.signature.is_verified()));
+    assert_eq!(report.records.len(), 2);
+    assert!(report.records.iter().all(|r| r.validation.is_verified()));
+
+    // The first record is covered by both checkpoints, the second only by the latest
+    let latest = report.checkpoints[0].checkpoint.log_length;
+    let stored = report.checkpoints[1].checkpoint.log_length;
+    assert_eq!(report.records[0].covered_by, [latest, stored]);
+    assert_eq!(report.records[1].covered_by, [latest]);
+
+    let signers = report.signers();
+    assert_eq!(signers.len(), 1);
+    assert_eq!(
+        signers[&signing_key.public_key().fingerprint()].len(),
+        report.records.len()
+    );
+
+    assert!(matches!(
+        client
+            .verification_report(&PackageName::new("test:unknown")?)
+            .await,
+        Err(ClientError::PackageDoesNotExist { .. })
+    ));
+
+    Ok(())
+}
+
+/// The identity of the test's GitHub Actions release workflow.
+const WORKFLOW_IDENTITY: &str =
+    "https://github.com/example/repo/.github/workflows/release.yml@refs/tags/v1.0.0";
+
+/// The Fulcio certificate extension of the OIDC issuer.
+struct OidcIssuer(&'static str);
+
+impl AssociatedOid for OidcIssuer {
+    const OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.57264.1.8");
+}
+
+impl x509_cert::der::Encode for OidcIssuer {
+    fn encoded_len(&self) -> x509_cert::der::Result<x509_cert::der::Length> {
+        Utf8StringRef::new(self.0)?.encoded_len()
+    }
+
+    fn encode(&self, writer: &mut impl x509_cert::der::Writer) -> x509_cert::der::Result<()> {
+        Utf8StringRef::new(self.0)?.encode(writer)
+    }
+}
+
+impl AsExtension for OidcIssuer {
+    fn critical(&self, _: &Name, _: &[Extension]) -> bool {
+        false
+    }
+}
+
+/// Creates a root certificate and a Sigstore bundle of provenance for the
+/// given content signed by a certificate issued by the root.
+fn provenance_bundle(content: &AnyHash) -> Result<(Vec<u8>, Vec<u8>)> {
+    let validity = Validity::from_now(Duration::from_secs(600))?;
+    let root_key = p256::ecdsa::SigningKey::random(&mut OsRng);
+    let root_name = Name::from_str("CN=test root")?;
+    let root = CertificateBuilder::new(
+        Profile::Root,
+        SerialNumber::from(1u32),
+        validity,
+        root_name.clone(),
+        SubjectPublicKeyInfoOwned::from_key(*root_key.verifying_key())?,
+        &root_key,
+    )?
+    .build::<DerSignature>()?;
+
+    let leaf_key = p256::ecdsa::SigningKey::random(&mut OsRng);
+    let mut builder = CertificateBuilder::new(
+        Profile::Leaf {
+            issuer: root_name,
+            enable_key_agreement: false,
+            enable_key_encipherment: false,
+        },
+        SerialNumber::from(2u32),
+        validity,
+        Name::default(),
+        SubjectPublicKeyInfoOwned::from_key(*leaf_key.verifying_key())?,
+        &root_key,
+    )?;
+    builder.add_extension(&SubjectAltName(vec![
+        GeneralName::UniformResourceIdentifier(Ia5String::new(WORKFLOW_IDENTITY)?),
+    ]))?;
+    builder.add_extension(&OidcIssuer(provenance::GITHUB_ACTIONS_ISSUER))?;
+    let leaf = builder.build::<DerSignature>()?;
+
+    let content = content.to_string();
+    let (algorithm, digest) = content.split_once(':').unwrap();
+    let statement = serde_json::to_vec(&serde_json::json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": [{ "name": "component.wasm", "digest": { algorithm: digest } }],
+        "predicateType": provenance::PROVENANCE_KIND,
+        "predicate": {},
+    }))?;
+    let payload_type = "application/vnd.in-toto+json";
+    let mut message = format!(
+        "DSSEv1 {} {payload_type} {} ",
+        payload_type.len(),
+        statement.len()
+    )
+    .into_bytes();
+    message.extend_from_slice(&statement);
+    let signature: DerSignature = leaf_key.sign(&message);
+
+    let bundle = serde_json::to_vec(&serde_json::json!({
+        "mediaType": "application/vnd.dev.sigstore.bundle.v0.3+json",
+        "verificationMaterial": {
+            "certificate": { "rawBytes": STANDARD.encode(leaf.to_der()?) },
+        },
+        "dsseEnvelope": {
+            "payload": STANDARD.encode(&statement),
+            "payloadType": payload_type,
+            "signatures": [{ "sig": STANDARD.encode(signature.to_bytes()) }],
+        },
+    }))?;
+
+    Ok((root.to_der()?, bundle))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_verifies_provenance() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:provenance")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+    publish_component(
+        &client,
+        &name,
+        "2.0.0",
+        "(component (core module))",
+        false,
+        &signing_key,
+    )
+    .await?;
+
+    let info = client.package(&name).await?;
+    let content = info
+        .state
+        .release(&"1.0.0".parse()?)
+        .and_then(|r| r.content())
+        .context("missing release content")?;
+    let (root, bundle) = provenance_bundle(content)?;
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move { Ok(bundle.into()) })),
+            None,
+        )
+        .await?;
+
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Attest {
+                    version: "1.0.0".parse()?,
+                    kind: provenance::PROVENANCE_KIND.to_string(),
+                    content: digest,
+                }],
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let policy =
+        ProvenancePolicy::new("https://github.com/example/repo/.github/workflows/release.yml@*")
+            .with_trusted_root_der(&root)?;
+    let client = client.with_provenance_policy(policy);
+    let report = client.verification_report(&name).await?;
+    assert_eq!(report.provenance.len(), 2);
+    assert!(report.provenance[0].outcome.is_verified());
+    assert_eq!(report.provenance[0].record_id.as_ref(), Some(&record_id));
+    assert_eq!(
+        report.provenance[0].identity.as_deref(),
+        Some(WORKFLOW_IDENTITY)
+    );
+    assert!(!report.provenance[1].outcome.is_verified());
+    assert!(matches!(
+        report.anomalies.as_slice(),
+        [VerificationAnomaly::ProvenanceNotVerified { version, .. }] if version.to_string() == "2.0.0"
+    ));
+
+    // Provenance from another workflow or an untrusted root is not verified
+    let policy =
+        ProvenancePolicy::new("https://github.com/example/repo/.github/workflows/ci.yml@*")
+            .with_trusted_root_der(&root)?;
+    let client = client.with_provenance_policy(policy);
+    let report = client.verification_report(&name).await?;
+    match &report.provenance[0].outcome {
+        CheckOutcome::Failed(reason) => assert!(reason.contains("does not match"), "{reason}"),
+        outcome => bail!("expected a failed provenance check, got {outcome:?}"),
+    }
+
+    let (other_root, _) = provenance_bundle(content)?;
+    let policy = ProvenancePolicy::new("*").with_trusted_root_der(&other_root)?;
+    let client = client.with_provenance_policy(policy);
+    let report = client.verification_report(&name).await?;
+    match &report.provenance[0].outcome {
+        CheckOutcome::Failed(reason) => assert!(reason.contains("trusted root"), "{reason}"),
+        outcome => bail!("expected a failed provenance check, got {outcome:?}"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_discovers_registry() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+    let registry_url = config.home_url.clone().unwrap();
+
+    // The registry itself advertises its supported algorithms
+    let api = api::Client::new(registry_url.as_str(), None)?;
+    let document = api
+        .discovery()
+        .await?
+        .context("expected a discovery document")?;
+    assert!(document.hash_algorithms.contains(&"sha256".to_string()));
+    assert!(document.api_url.is_none());
+
+    // A bare domain may point to the registry API hosted elsewhere
+    let url = spawn_json_server(vec![(
+        warg_api::DISCOVERY_PATH,
+        serde_json::json!({
+            "apiUrl": registry_url,
+            "federation": [{ "namespace": "other", "registry": "other.example.com" }],
+        }),
+    )])
+    .await?;
+    let api = api::Client::new(url.as_str(), None)?;
+    assert_eq!(api.api_url().await?.to_string(), format!("{registry_url}/"));
+    api.latest_checkpoint(None).await?;
+
+    let client = create_client(&warg_client::Config {
+        home_url: Some(url),
+        ..config.clone()
+    })
+    .await?;
+    assert_eq!(
+        client.get_warg_registry("other").await?,
+        Some("other.example.com".parse()?)
+    );
+    assert_eq!(client.get_warg_registry("test").await?, None);
+
+    // Resolutions are cached until the namespace map is changed by the client
+    client
+        .namespace_map()
+        .store_namespace("other".to_string(), "stale.example.com".parse()?)
+        .await?;
+    assert_eq!(
+        client.get_warg_registry("other").await?,
+        Some("other.example.com".parse()?)
+    );
+    client
+        .store_namespace("other".to_string(), "mapped.example.com".parse()?)
+        .await?;
+    assert_eq!(
+        client.get_warg_registry("other").await?,
+        Some("mapped.example.com".parse()?)
+    );
+
+    // Registries with algorithms unsupported by the client are rejected
+    let url = spawn_json_server(vec![(
+        warg_api::DISCOVERY_PATH,
+        serde_json::json!({ "hashAlgorithms": ["sha512"] }),
+    )])
+    .await?;
+    assert!(matches!(
+        api::Client::new(url.as_str(), None)?
+            .latest_checkpoint(None)
+            .await,
+        Err(api::ClientError::UnsupportedAlgorithm { kind: "hash", .. })
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_caches_discovery() -> Result<()> {
+    // The discovery document is unavailable on the first request
+    let requests = Arc::new(Mutex::new(0));
+    let router = axum::Router::new().route(
+        &format!("/{path}", path = warg_api::DISCOVERY_PATH),
+        axum::routing::get({
+            let requests = requests.clone();
+            move || {
+                let requests = requests.clone();
+                async move {
+                    let mut requests = requests.lock().unwrap();
+                    *requests += 1;
+                    if *requests == 1 {
+                        return Err(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+                    }
+                    Ok(axum::Json(
+                        serde_json::json!({ "hashAlgorithms": ["sha256"] }),
+                    ))
+                }
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{addr}", addr = listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    // Failures are not cached
+    let api = api::Client::new(url.as_str(), None)?;
+    assert!(api.discovery().await?.is_none());
+    assert!(api.discovery().await?.is_some());
+    assert!(api.discovery().await?.is_some());
+    assert_eq!(*requests.lock().unwrap(), 2);
+
+    // Discoveries are cached per client
+    let api = api::Client::new(url.as_str(), None)?;
+    assert!(api.discovery().await?.is_some());
+    assert_eq!(*requests.lock().unwrap(), 3);
+
+    // Discoveries expire
+    let api = api::Client::new(url.as_str(), None)?.with_discovery_ttl(Duration::ZERO);
+    assert!(api.discovery().await?.is_some());
+    assert!(api.discovery().await?.is_some());
+    assert_eq!(*requests.lock().unwrap(), 5);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_negotiates_capabilities() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let api = api::Client::new(config.home_url.as_deref().unwrap(), None)?;
+    let capabilities = api.capabilities().await?;
+    assert_eq!(capabilities.upload_protocols, [UploadProtocol::HttpPost]);
+    assert_eq!(capabilities.api_versions["fetch"], [ApiVersion::V1]);
+    assert!(!capabilities.search);
+
+    // Servers without the capabilities API have no capabilities
+    let url = spawn_json_server(Vec::new()).await?;
+    let api = api::Client::new(url.as_str(), None)?;
+    assert_eq!(*api.capabilities().await?, CapabilitiesResponse::default());
+    assert_eq!(api.api_version(api::Api::Fetch).await?, ApiVersion::V1);
+
+    // APIs are negotiated independently of each other
+    let url = spawn_json_server(vec![(
+        paths::capabilities(),
+        serde_json::json!({ "apiVersions": { "fetch": ["v2"], "proof": ["v1", "v2"] } }),
+    )])
+    .await?;
+    let api = api::Client::new(url.as_str(), None)?;
+    assert!(matches!(
+        api.latest_checkpoint(None).await,
+        Err(api::ClientError::UnsupportedApiVersion("fetch"))
+    ));
+    assert_eq!(api.api_version(api::Api::Proof).await?, ApiVersion::V1);
+    assert_eq!(api.api_version(api::Api::Package).await?, ApiVersion::V1);
+
+    // Unknown capabilities from newer servers are ignored
+    let capabilities: CapabilitiesResponse = serde_json::from_value(serde_json::json!({
+        "uploadProtocols": ["httpPut", "carrierPigeon"],
+        "authModes": ["bearer"],
+        "telepathy": true,
+    }))?;
+    assert_eq!(
+        capabilities.upload_protocols,
+        [UploadProtocol::HttpPut, UploadProtocol::Unknown]
+    );
+    assert_eq!(capabilities.auth_modes, [AuthMode::Bearer]);
+
+    // Publishing uploads content with the negotiated protocol
+    let client = create_client(&config).await?;
+    publish_component(
+        &client,
+        &PackageName::new("test:negotiated")?,
+        "1.0.0",
+        "(component)",
+        true,
+        &support::test_signing_key(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_reports_throttling() -> Result<()> {
+    let root = root().await?;
+
+    // Requests beyond the quota are rejected with a retry hint
+    let quota_root = root.join("quota");
+    let (_server, config) = spawn_server_with_config(
+        &quota_root,
+        warg_server::Config::new(
+            test_operator_key(),
+            test_namespaces(),
+            quota_root.join("server"),
+        )
+        .with_throttling(ThrottleConfig::new().with_quota(3, Duration::from_secs(60))),
+    )
+    .await?;
+    let api = api::Client::new(config.home_url.as_deref().unwrap(), None)?;
+    let mut error = None;
+    for _ in 0..4 {
+        if let Err(e) = api.latest_checkpoint(None).await {
+            error = Some(e);
+            break;
+        }
+    }
+    match error {
+        Some(api::ClientError::QuotaExceeded {
+            quota: Some(quota),
+            retry_after: Some(retry_after),
+            ..
+        }) => {
+            assert_eq!(quota.limit, 3);
+            assert_eq!(quota.window_ms, 60_000);
+            assert!(retry_after <= Duration::from_secs(60));
+        }
+        e => bail!("expected a quota exceeded error, got {e:?}"),
+    }
+
+    // Requests beyond the concurrency limit are shed
+    let shed_root = root.join("shed");
+    let (_server, config) = spawn_server_with_config(
+        &shed_root,
+        warg_server::Config::new(
+            test_operator_key(),
+            test_namespaces(),
+            shed_root.join("server"),
+        )
+        .with_throttling(
+            ThrottleConfig::new()
+                .with_max_concurrent_requests(0)
+                .with_overload_retry_after(Duration::from_secs(5)),
+        ),
+    )
+    .await?;
+    let api = api::Client::new(config.home_url.as_deref().unwrap(), None)?;
+    let e = api.latest_checkpoint(None).await.unwrap_err();
+    assert!(e.is_throttled());
+    assert_eq!(e.retry_after(), Some(Duration::from_secs(5)));
+    assert!(matches!(
+        e,
+        api::ClientError::Unavailable {
+            code: ProblemCode::Overloaded,
+            ..
+        }
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_sends_request_metadata() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    let registry_url = config.home_url.clone().unwrap();
+
+    // Record the headers of the discovery request
+    let headers = Arc::new(Mutex::new(None));
+    let router = {
+        let headers = headers.clone();
+        axum::Router::new().route(
+            &format!("/{path}", path = warg_api::DISCOVERY_PATH),
+            axum::routing::get(move |request_headers: axum::http::HeaderMap| async move {
+                *headers.lock().unwrap() = Some(request_headers);
+                axum::Json(serde_json::json!({ "apiUrl": registry_url }))
+            }),
+        )
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let client = create_client(&warg_client::Config {
+        home_url: Some(format!("http://{addr}")),
+        user_agent_suffix: Some("my-tool/1.0".to_string()),
+        request_headers: [("x-build-id".to_string(), "1234".to_string())].into(),
+        ..config
+    })
+    .await?;
+    client.get_warg_registry("other").await?;
+
+    let headers = headers
+        .lock()
+        .unwrap()
+        .take()
+        .context("expected a discovery request")?;
+    let user_agent = headers["user-agent"].to_str()?;
+    assert!(user_agent.starts_with("warg-client/"));
+    assert!(user_agent.ends_with(" my-tool/1.0"));
+    assert_eq!(headers["x-build-id"], "1234");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_uses_connect_options() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    let url: reqwest::Url = config.home_url.as_deref().unwrap().parse()?;
+    let port = url.port().context("expected a port")?;
+
+    // Connections go to the overridden address rather than the resolved one
+    let api = api::Client::new(format!("http://localhost:{port}"), None)?.with_connect_options(
+        api::ConnectOptions::default()
+            .with_dns_override("localhost", [Ipv4Addr::new(127, 0, 0, 2).into()]),
+    )?;
+    assert!(api.latest_checkpoint(None).await.is_err());
+
+    let client = create_client(&warg_client::Config {
+        home_url: Some(format!("http://localhost:{port}")),
+        connect: api::ConnectOptions::default()
+            .with_dns_override("localhost", [Ipv4Addr::LOCALHOST.into()]),
+        ..config.clone()
+    })
+    .await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:connect")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Connections are restricted to an address family
+    let api = api::Client::new(format!("http://localhost:{port}"), None)?.with_connect_options(
+        api::ConnectOptions::default().with_ip_family(api::IpFamily::Ipv4Only),
+    )?;
+    api.latest_checkpoint(None).await?;
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_uses_unix_socket() -> Result<()> {
+    let root = root().await?;
+    let socket = root.join("registry.sock");
+    let (_server, config) = spawn_server_with_config(
+        &root,
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_unix_socket(&socket),
+    )
+    .await?;
+    assert_eq!(
+        config.home_url.as_deref(),
+        Some(
+            warg_client::transport::unix_socket_url(&socket)?
+                .as_str()
+                .trim_end_matches('/')
+        )
+    );
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:unix")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Content is downloaded over the socket too
+    fs::remove_dir_all(config.content_dir.as_ref().unwrap())?;
+    client
+        .download(&name, &"0.1.0".parse()?)
+        .await?
+        .context("missing download")?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_verifies_mirror() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:mirrored")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // A registry is trivially a consistent mirror of itself
+    let report = client
+        .verify_mirror(config.home_url.as_deref().unwrap())
+        .await?;
+    assert!(
+        report.is_consistent(),
+        "unexpected divergences: {:?}",
+        report.divergences
+    );
+
+    // An unrelated registry has a different operator log and therefore diverges
+    let (_upstream, upstream_config) =
+        spawn_server(&root.join("upstream"), None, None, None).await?;
+    let report = client
+        .verify_mirror(upstream_config.home_url.as_deref().unwrap())
+        .await?;
+    assert!(!report.is_consistent());
+    assert!(matches!(
+        report.divergences[0],
+        MirrorDivergence::CheckpointAhead { .. } | MirrorDivergence::InconsistentCheckpoint { .. }
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_reports_logs_not_found_by_upstream() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:withdrawn")?;
+    let log_id = LogId::package_log::<Sha256>(&name);
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+    publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key).await?;
+
+    // An upstream that serves a record of each log per page and no longer
+    // finds the package log after the first page
+    let registry_url = config.home_url.clone().unwrap();
+    let fetches = Arc::new(Mutex::new(0));
+    let router = axum::Router::new()
+        .route(
+            &format!("/{path}", path = paths::fetch_logs()),
+            axum::routing::post({
+                let registry_url = registry_url.clone();
+                let log_id = log_id.clone();
+                move |axum::Json(mut body): axum::Json<serde_json::Value>| async move {
+                    *fetches.lock().unwrap() += 1;
+                    if *fetches.lock().unwrap() == 2 {
+                        return (
+                            axum::http::StatusCode::NOT_FOUND,
+                            axum::Json(
+                                serde_json::to_value(warg_api::v1::fetch::FetchError::LogNotFound(
+                                    log_id,
+                                ))
+                                .unwrap(),
+                            ),
+                        );
+                    }
+
+                    body["limit"] = 1.into();
+                    let response = reqwest::Client::new()
+                        .post(format!("{registry_url}/{path}", path = paths::fetch_logs()))
+                        .json(&body)
+                        .send()
+                        .await
+                        .unwrap();
+                    (
+                        response.status(),
+                        axum::Json(response.json().await.unwrap()),
+                    )
+                }
+            }),
+        )
+        .fallback(move |uri: axum::http::Uri| async move {
+            let response = reqwest::get(format!("{registry_url}{uri}")).await.unwrap();
+            let content_type = response.headers()[reqwest::header::CONTENT_TYPE].clone();
+            (
+                response.status(),
+                [(reqwest::header::CONTENT_TYPE, content_type)],
+                response.bytes().await.unwrap(),
+            )
+        });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let upstream_url = format!("http://{addr}", addr = listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    // The records served before the log was not found are still compared
+    let report = client.verify_mirror(upstream_url).await?;
+    assert_eq!(
+        report.divergences,
+        [
+            MirrorDivergence::LogNotFound {
+                log_id: log_id.clone(),
+                name: name.clone(),
+                upstream: true,
+                records: 1,
+            },
+            MirrorDivergence::RecordCountMismatch {
+                log_id,
+                name: Some(name),
+                mirror: 2,
+                upstream: 1,
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+/// An alert sink that collects the alerts it is sent.
+#[derive(Default, Clone)]
+struct CollectingAlertSink(Arc<Mutex<Vec<MonitorAlert>>>);
+
+#[async_trait::async_trait]
+impl AlertSink for CollectingAlertSink {
+    async fn send(&self, _registry: &RegistryUrl, alert: &MonitorAlert) -> anyhow::Result<()> {
+        self.0.lock().unwrap().push(alert.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_monitors_registry() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+    let url = config.home_url.clone().unwrap();
+    let archive = root.join("monitor");
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:monitored")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let sink = CollectingAlertSink::default();
+    let mut monitor = Monitor::new(url.as_str(), &archive)?.with_alert_sink(sink.clone());
+    assert!(monitor.poll().await?.is_empty());
+    let checkpoint = monitor
+        .checkpoint()
+        .cloned()
+        .context("expected a checkpoint")?;
+
+    publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key).await?;
+    assert!(monitor.poll().await?.is_empty());
+    let latest = monitor
+        .checkpoint()
+        .cloned()
+        .context("expected a checkpoint")?;
+    assert!(latest.log_length > checkpoint.log_length);
+
+    // Every record of the log is archived
+    let records = fs::read_to_string(archive.join("records.jsonl"))?;
+    assert_eq!(records.lines().count(), latest.log_length);
+    assert_eq!(
+        fs::read_to_string(archive.join("checkpoints.jsonl"))?
+            .lines()
+            .count(),
+        2
+    );
+
+    // The archive can be exported for analysis
+    let export = root.join("export");
+    let summary = export_archive(&archive, &export)?;
+    assert_eq!(summary.checkpoints, 2);
+    assert_eq!(summary.records, latest.log_length);
+    let entries = fs::read_to_string(export.join("entries.csv"))?;
+    assert_eq!(entries.lines().count(), summary.entries + 1);
+    assert_eq!(
+        entries
+            .lines()
+            .filter(|line| line.contains(",release,"))
+            .count(),
+        2
+    );
+
+    // Monitoring resumes from the archive
+    let mut monitor = Monitor::new(url.as_str(), &archive)?.with_alert_sink(sink.clone());
+    assert_eq!(monitor.checkpoint(), Some(&latest));
+    assert!(monitor.poll().await?.is_empty());
+    assert!(sink.0.lock().unwrap().is_empty());
+
+    // A registry with a shorter log at the same archive appears rewound
+    let (_other, other_config) = spawn_server(&root.join("other"), None, None, None).await?;
+    let mut monitor = Monitor::new(other_config.home_url.as_deref().unwrap(), &archive)?
+        .with_alert_sink(sink.clone());
+    let alerts = monitor.poll().await?;
+    assert!(
+        matches!(alerts.as_slice(), [MonitorAlert::CheckpointRewind { from, .. }] if *from == latest.log_length),
+        "unexpected alerts: {alerts:?}"
+    );
+    assert_eq!(*sink.0.lock().unwrap(), alerts);
+    assert_eq!(monitor.checkpoint(), Some(&latest));
+
+    // The rejected checkpoint is not alerted on again
+    assert!(monitor.poll().await?.is_empty());
+    assert_eq!(sink.0.lock().unwrap().len(), 1);
+
+    Ok(())
+}
+
+/// A package policy that bans a single package.
+struct BannedPackagePolicy(PackageName);
+
+impl PackagePolicy for BannedPackagePolicy {
+    fn check(&self, package: &ResolvedPackage) -> anyhow::Result<Vec<PolicyViolation>> {
+        if package.name != &self.0 {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![PolicyViolation {
+            rule: "banned-package".to_string(),
+            message: format!("package `{name}` is banned", name = package.name),
+        }])
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_checks_package_policy() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let allowed = PackageName::new("test:allowed")?;
+    let banned = PackageName::new("test:banned")?;
+    for name in [&allowed, &banned] {
+        publish_component(&client, name, "1.0.0", "(component)", true, &signing_key).await?;
+    }
+
+    let client = client.with_package_policy(BannedPackagePolicy(banned.clone()));
+    assert!(client
+        .download(&allowed, &"1.0.0".parse()?)
+        .await?
+        .is_some());
+
+    match client.download(&banned, &"1.0.0".parse()?).await {
+        Err(ClientError::PackagePolicyViolation {
+            name,
+            version,
+            violations,
+        }) => {
+            assert_eq!(name, banned);
+            assert_eq!(version.to_string(), "1.0.0");
+            assert_eq!(violations.len(), 1);
+            assert_eq!(violations[0].rule, "banned-package");
+        }
+        res => bail!("expected a policy violation, got {res:?}"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_downloads_from_release_channels() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:channels")?;
+    for (version, wat, init) in [
+        ("1.0.0", "(component)", true),
+        ("1.1.0-beta.1", "(component (core module))", false),
+        (
+            "1.1.0-nightly.2",
+            "(component (core module) (core module))",
+            false,
+        ),
+    ] {
+        publish_component(&client, &name, version, wat, init, &signing_key).await?;
+    }
+
+    let req = "^1.0".parse()?;
+    let latest = |channel: &str| {
+        let client = &client;
+        let name = &name;
+        let req = &req;
+        let channel = channel.parse::<ReleaseChannel>().unwrap();
+        async move {
+            anyhow::Ok(
+                client
+                    .download_from_channel(name, req, &channel)
+                    .await?
+                    .map(|download| download.version.to_string()),
+            )
+        }
+    };
+
+    assert_eq!(latest("stable").await?.as_deref(), Some("1.0.0"));
+    assert_eq!(
+        latest("pre-release").await?.as_deref(),
+        Some("1.1.0-nightly.2")
+    );
+    assert_eq!(latest("beta").await?.as_deref(), Some("1.1.0-beta.1"));
+    assert_eq!(latest("rc").await?, None);
+
+    // Plain requirements keep semver's pre-release matching
+    let download = client.download(&name, &req).await?.unwrap();
+    assert_eq!(download.version.to_string(), "1.0.0");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_checks_license_allowlist() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let mit = PackageName::new("test:mit")?;
+    let gpl = PackageName::new("test:gpl")?;
+    let unlicensed = PackageName::new("test:unlicensed")?;
+    publish(
+        &client,
+        &mit,
+        "1.0.0",
+        licensed_component("MIT OR GPL-3.0-only")?,
+        true,
+        &signing_key,
+    )
+    .await?;
+    publish(
+        &client,
+        &gpl,
+        "1.0.0",
+        licensed_component("GPL-3.0-only")?,
+        true,
+        &signing_key,
+    )
+    .await?;
+    publish_component(
+        &client,
+        &unlicensed,
+        "1.0.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    let client = client.with_license_allowlist(["MIT", "Apache-2.0"]);
+    assert!(client.download(&mit, &"1.0.0".parse()?).await?.is_some());
+
+    match client.download_exact(&gpl, &"1.0.0".parse()?).await {
+        Err(ClientError::LicenseNotAllowed { name, license, .. }) => {
+            assert_eq!(name, gpl);
+            assert_eq!(license.as_deref(), Some("GPL-3.0-only"));
+        }
+        res => bail!("expected a license error, got {res:?}"),
+    }
+
+    match client
+        .download_as_stream(&unlicensed, &"1.0.0".parse()?)
+        .await
+    {
+        Err(ClientError::LicenseNotAllowed { license, .. }) => assert_eq!(license, None),
+        Err(e) => bail!("expected a license error, got {e:?}"),
+        Ok(_) => bail!("expected a license error"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_enforces_signing_policy() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:signed")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+    drop(client);
+
+    // Records signed by a trusted key of the namespace are validated
+    let trusted = Config {
+        registries_dir: Some(root.join("trusted")),
+        signing_policy: Some(
+            SigningPolicy::new()
+                .with_namespace_keys("test", [signing_key.public_key().fingerprint()]),
+        ),
+        ..config.clone()
+    };
+    let client = create_client(&trusted).await?;
+    client.fetch_packages([&name]).await?;
+    drop(client);
+
+    // Records signed by another key are rejected
+    let (other_key, _) = generate_p256_pair();
+    let untrusted = Config {
+        registries_dir: Some(root.join("untrusted")),
+        signing_policy: Some(
+            SigningPolicy::new().with_namespace_keys("test", [other_key.fingerprint()]),
+        ),
+        ..config
+    };
+    let client = create_client(&untrusted).await?;
+    match client.fetch_packages([&name]).await {
+        Err(ClientError::UntrustedSigningKey { name: n, key_id }) => {
+            assert_eq!(n, name);
+            assert_eq!(key_id, signing_key.public_key().fingerprint());
+        }
+        res => bail!("expected an untrusted signing key error, got {res:?}"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_keeps_pinned_content() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let pinned = PackageName::new("test:pinned")?;
+    let unpinned = PackageName::new("test:unpinned")?;
+    let pinned_digest =
+        publish_component(&client, &pinned, "1.0.0", "(component)", true, &signing_key).await?;
+    let unpinned_digest = publish_component(
+        &client,
+        &unpinned,
+        "1.0.0",
+        "(component (core module))",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    client.pin_content(&pinned_digest).await?;
+    client.clear_content_cache().await?;
+    assert!(client.content().content_location(&pinned_digest).is_some());
+    assert!(client
+        .content()
+        .content_location(&unpinned_digest)
+        .is_none());
+
+    client.unpin_content(&pinned_digest).await?;
+    client.clear_content_cache().await?;
+    assert!(client.content().content_location(&pinned_digest).is_none());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_checks_available_storage() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:sized")?;
+    let content = wat::parse_str("(component)")?;
+    let size = content.len() as u64;
+    let digest = publish(&client, &name, "1.0.0", content, true, &signing_key).await?;
+
+    // The registry reports the size of the content
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let sources = api.content_sources(None, &digest).await?.content_sources;
+    match sources.get(&digest).map(Vec::as_slice) {
+        Some([api::ContentSource::HttpGet { size: Some(s), .. }]) => assert_eq!(*s, size),
+        sources => bail!("expected a sized content source, got {sources:?}"),
+    }
+
+    client.clear_content_cache().await?;
+    let client = client.with_storage_reserve(u64::MAX);
+    match client.download(&name, &"1.0.0".parse()?).await {
+        Err(ClientError::InsufficientStorage {
+            digest: d,
+            required,
+            available,
+        }) => {
+            assert_eq!(d, digest);
+            assert_eq!(required, u64::MAX);
+            assert!(available < required);
+        }
+        res => bail!("expected an insufficient storage error, got {res:?}"),
+    }
+    assert!(client.content().content_location(&digest).is_none());
+
+    let client = client.with_storage_reserve(0);
+    let download = client
+        .download(&name, &"1.0.0".parse()?)
+        .await?
+        .context("missing download")?;
+    assert_eq!(fs::metadata(download.path)?.len(), size);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_downloads_attestations() -> Result<()> {
+    const PROVENANCE: &str = "https://slsa.dev/provenance/v1";
+
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:attested")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+
+    // The provenance is not wasm, so it would be rejected by the content policy
+    let provenance = br#"{"_type":"https://in-toto.io/Statement/v1"}"#;
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move {
+                Ok(provenance.as_slice().into())
+            })),
+            None,
+        )
+        .await?;
+
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Attest {
+                    version: "1.0.0".parse()?,
+                    kind: PROVENANCE.to_string(),
+                    content: digest.clone(),
+                }],
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+    drop(client);
+
+    // Remove the client's content so the attestation is downloaded
+    fs::remove_dir_all(config.content_dir.as_ref().unwrap())
+        .context("failed to remove content directory")?;
+    let client = create_client(&config).await?;
+
+    let attestations = client
+        .download_attestations(&name, &"1.0.0".parse()?)
+        .await?;
+    assert_eq!(attestations.len(), 1);
+    assert_eq!(attestations[0].kind, PROVENANCE);
+    assert_eq!(attestations[0].digest, digest);
+    assert_eq!(attestations[0].by, signing_key.public_key().fingerprint());
+    assert_eq!(fs::read(&attestations[0].path)?, provenance);
+
+    assert!(matches!(
+        client.download_attestations(&name, &"2.0.0".parse()?).await,
+        Err(ClientError::PackageVersionDoesNotExist { .. })
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_publishes_release_notes() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:notes")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+    publish_component(
+        &client,
+        &name,
+        "1.1.0",
+        "(component (core module))",
+        false,
+        &signing_key,
+    )
+    .await?;
+
+    let notes = ReleaseNotes {
+        notes: Some("Fixed a bug.".to_string()),
+        links: vec![ReleaseLink {
+            title: "Changelog".to_string(),
+            url: "https://example.com/changelog".to_string(),
+        }],
+    };
+    let version = "1.1.0".parse()?;
+    let record_id = client
+        .publish_release_notes(&signing_key, &name, &version, &notes)
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let metadata = client.package_metadata(&name).await?;
+    assert_eq!(metadata.releases.len(), 2);
+    assert_eq!(metadata.releases[0].version.to_string(), "1.0.0");
+    assert_eq!(metadata.releases[0].release_notes, None);
+    assert_eq!(metadata.releases[1].version, version);
+    assert_eq!(metadata.releases[1].release_notes.as_ref(), Some(&notes));
+
+    // Release notes that are not a valid document are not accepted
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async { Ok("not json".into()) })),
+            None,
+        )
+        .await?;
+    let res = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Attest {
+                    version: version.clone(),
+                    kind: RELEASE_NOTES_KIND.to_string(),
+                    content: digest,
+                }],
+            },
+        )
+        .await;
+    assert!(res.is_err(), "expected invalid release notes to fail");
+
+    assert!(matches!(
+        client
+            .package_metadata(&PackageName::new("test:missing")?)
+            .await,
+        Err(ClientError::PackageDoesNotExist { .. })
+    ));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_reports_deprecations() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:deprecated")?;
+    let replacement = PackageName::new("test:replacement")?;
+    publish_component(&client, &name, "1.0.0", "(component)", true, &signing_key).await?;
+
+    let publish = |entry| {
+        client.publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![entry],
+            },
+        )
+    };
+
+    let record_id = publish(PublishEntry::Deprecate {
+        message: "this package is unmaintained".to_string(),
+        replacement: Some(replacement.clone()),
+    })
+    .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    // The release of a deprecated package is still downloaded
+    let download = client.download(&name, &"1.0.0".parse()?).await?.unwrap();
+    let deprecation = download.deprecation.context("expected a deprecation")?;
+    assert_eq!(deprecation.message, "this package is unmaintained");
+    assert_eq!(deprecation.replacement, Some(replacement));
+    assert_eq!(deprecation.by, signing_key.public_key().fingerprint());
+
+    let record_id = publish(PublishEntry::Undeprecate).await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let download = client.download_exact(&name, &"1.0.0".parse()?).await?;
+    assert!(download.deprecation.is_none());
+
+    // A package that is not deprecated cannot be undeprecated
+    let err = publish(PublishEntry::Undeprecate).await.unwrap_err();
+    assert!(err.to_string().contains("not deprecated"), "{err}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_times_out_waiting_for_publish() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let name: PackageName = "test:pending".parse()?;
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let record_id = RecordId::from(AnyHash::from(Hash::<Sha256>::of("pending")));
+
+    // A registry that never finishes processing the record
+    let url = spawn_json_server(vec![(
+        &paths::package_record(&log_id, &record_id),
+        serde_json::json!({ "recordId": record_id, "state": "processing", "retryAfterMs": 10 }),
+    )])
+    .await?;
+    let client = create_client(&Config {
+        home_url: Some(url),
+        ..config.clone()
+    })
+    .await?;
+
+    let timeout = Duration::from_millis(200);
+    let start = std::time::Instant::now();
+    match client
+        .wait_for_publish_with_timeout(&name, &record_id, Duration::from_secs(5), timeout)
+        .await
+    {
+        Err(ClientError::PublishTimedOut {
+            record_id: id,
+            timeout: t,
+            ..
+        }) => {
+            assert_eq!(id, record_id);
+            assert_eq!(t, timeout);
+        }
+        res => bail!("expected the wait to time out, got {res:?}"),
+    }
+
+    // The registry's retry hint is used instead of the initial interval
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_awaits_records() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let api = api::Client::new(config.home_url.as_deref().unwrap(), None)?;
+    assert!(api.capabilities().await?.events);
+
+    let client = create_client(&config).await?;
+    let name: PackageName = "test:awaited".parse()?;
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move {
+                Ok(wat::parse_str("(component)")?.into())
+            })),
+            None,
+        )
+        .await?;
+    let record_id = client
+        .publish_with_info(
+            &support::test_signing_key(),
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![
+                    PublishEntry::Init,
+                    PublishEntry::Release {
+                        version: "1.0.0".parse()?,
+                        content: digest,
+                        content_type: ContentType::Component,
+                    },
+                ],
+            },
+        )
+        .await?;
+
+    let record = client.await_record(&name, &record_id).await?;
+    assert_eq!(record.record_id, record_id);
+    assert!(matches!(
+        record.state,
+        api::PackageRecordState::Published { .. }
+    ));
+
+    Ok(())
+}
+
+/// A middleware that records the paths of requests and tags them with a
+/// header.
+#[derive(Default, Clone)]
+struct RecordingMiddleware(Arc<Mutex<Vec<String>>>);
+
+#[async_trait::async_trait]
+impl Middleware for RecordingMiddleware {
+    async fn handle(
+        &self,
+        mut request: reqwest::Request,
+        next: Next<'_>,
+    ) -> Result<reqwest::Response, api::ClientError> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(request.url().path().to_string());
+        request
+            .headers_mut()
+            .insert("x-test-middleware", "recorded".parse().unwrap());
+        next.run(request).await
+    }
+}
+
+/// A middleware that fails checkpoint requests without sending them.
+struct FaultMiddleware;
+
+#[async_trait::async_trait]
+impl Middleware for FaultMiddleware {
+    async fn handle(
+        &self,
+        request: reqwest::Request,
+        next: Next<'_>,
+    ) -> Result<reqwest::Response, api::ClientError> {
+        if request.url().path().ends_with("/fetch/checkpoint") {
+            return Err(api::ClientError::LocalTransport(anyhow::anyhow!(
+                "injected fault"
+            )));
+        }
+        next.run(request).await
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_runs_middleware() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let recorder = RecordingMiddleware::default();
+    let client = create_client(&config)
+        .await?
+        .with_middleware(recorder.clone());
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:middleware")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let paths = recorder.0.lock().unwrap().clone();
+    assert!(paths.iter().any(|p| p.ends_with("/fetch/checkpoint")));
+    assert!(paths.iter().any(|p| p.ends_with("/record")));
+
+    // Middleware may respond without sending the request
+    let api = api::Client::new(config.home_url.as_deref().unwrap(), None)?
+        .with_middleware(recorder.clone())
+        .with_middleware(FaultMiddleware);
+    match api.latest_checkpoint(None).await {
+        Err(api::ClientError::LocalTransport(e)) => {
+            assert_eq!(e.to_string(), "injected fault")
+        }
+        _ => bail!("expected the request to fail"),
+    }
+
+    // Outer middleware still sees the failed request
+    assert!(recorder
+        .0
+        .lock()
+        .unwrap()
+        .last()
+        .unwrap()
+        .ends_with("/fetch/checkpoint"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_records_signing_audit() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let key_id = signing_key.public_key().fingerprint();
+    let name = PackageName::new("test:audited")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+    publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key).await?;
+
+    // A record that is never signed is not recorded
+    assert!(
+        publish_component(&client, &name, "0.3.0", "(component)", true, &signing_key)
+            .await
+            .is_err()
+    );
+
+    let entries = client.signing_audit(&SigningAuditQuery::default()).await?;
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|e| e.package == name
+        && e.key_id == key_id
+        && e.outcome == SigningOutcome::Submitted));
+    assert!(matches!(
+        entries[0].entries.as_slice(),
+        [PublishEntry::Init, PublishEntry::Release { version, .. }] if version.to_string() == "0.1.0"
+    ));
+    assert_eq!(entries[1].prev.as_ref(), Some(&entries[0].record_id));
+
+    // The trail is persisted in client storage
+    drop(client);
+    let client = create_client(&config).await?;
+    let entries = client
+        .signing_audit(&SigningAuditQuery {
+            record_id: Some(entries[1].record_id.clone()),
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(entries.len(), 1);
+    assert!(matches!(
+        entries[0].entries.as_slice(),
+        [PublishEntry::Release { version, .. }] if version.to_string() == "0.2.0"
+    ));
+
+    // Queries filter by key and time
+    let other_key = generate_p256_pair().1;
+    assert!(client
+        .signing_audit(&SigningAuditQuery {
+            key_id: Some(other_key.public_key().fingerprint()),
+            ..Default::default()
+        })
+        .await?
+        .is_empty());
+    assert!(client
+        .signing_audit(&SigningAuditQuery {
+            since: Some(std::time::SystemTime::now() + Duration::from_secs(60)),
+            ..Default::default()
+        })
+        .await?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_validates_checkpoint_time() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    let url = config.home_url.as_deref().unwrap();
+
+    let checkpoint = api::Client::new(url, None)?.latest_checkpoint(None).await?;
+    let checkpoint = checkpoint.as_ref();
+
+    // A clock behind the registry's sees the checkpoint in the future
+    let now = std::time::SystemTime::now();
+    let behind = now - Duration::from_secs(3600);
+    match api::CheckpointClock::default().validate(checkpoint, behind) {
+        Err(api::ClientError::CheckpointInFuture { timestamp, .. }) => {
+            assert_eq!(timestamp, checkpoint.timestamp)
+        }
+        _ => bail!("expected the checkpoint to be in the future"),
+    }
+    api::CheckpointClock::default()
+        .with_max_skew(Duration::from_secs(7200))
+        .validate(checkpoint, behind)?;
+    api::CheckpointClock::disabled().validate(checkpoint, behind)?;
+
+    // A clock ahead of the registry's sees the checkpoint as stale
+    let ahead = now + Duration::from_secs(3600);
+    api::CheckpointClock::default().validate(checkpoint, ahead)?;
+    match api::CheckpointClock::default()
+        .with_max_staleness(Duration::from_secs(60))
+        .validate(checkpoint, ahead)
+    {
+        Err(api::ClientError::StaleCheckpoint { timestamp, .. }) => {
+            assert_eq!(timestamp, checkpoint.timestamp)
+        }
+        _ => bail!("expected the checkpoint to be stale"),
+    }
+
+    // A timestamp too large to be represented is in the future
+    let mut far = checkpoint.clone();
+    far.timestamp = u64::MAX;
+    match api::CheckpointClock::default().validate(&far, now) {
+        Err(api::ClientError::CheckpointInFuture { timestamp, .. }) => {
+            assert_eq!(timestamp, u64::MAX)
+        }
+        _ => bail!("expected the checkpoint to be in the future"),
+    }
+    api::CheckpointClock::disabled().validate(&far, now)?;
+
+    // The latest checkpoint is validated when fetched
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:clock")?;
+    publish_component(
+        &create_client(&config).await?,
+        &name,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let api = api::Client::new(url, None)?
+        .with_checkpoint_clock(api::CheckpointClock::default().with_max_staleness(Duration::ZERO));
+    assert!(matches!(
+        api.latest_checkpoint(None).await,
+        Err(api::ClientError::StaleCheckpoint { .. })
+    ));
+
+    let client = create_client(&config)
+        .await?
+        .with_checkpoint_clock(api::CheckpointClock::default().with_max_staleness(Duration::ZERO));
+    match client.update().await {
+        Err(ClientError::Api(api::ClientError::StaleCheckpoint { .. })) => {}
+        _ => bail!("expected the checkpoint to be stale"),
+    }
+    drop(client);
+    create_client(&config)
+        .await?
+        .with_checkpoint_clock(api::CheckpointClock::disabled())
+        .update()
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_refreshes_outdated_packages_before_download() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let publisher = create_client(&config).await?;
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:fresh")?;
+    publish_component(
+        &publisher,
+        &name,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+    drop(publisher);
+
+    // Download with a separate client storage so the publisher's updates
+    // are not seen
+    let consumer_config = Config {
+        registries_dir: Some(root.join("consumer-registries")),
+        ..config.clone()
+    };
+    let consumer = create_client(&consumer_config).await?;
+    let download = consumer
+        .download(&name, &"*".parse()?)
+        .await?
+        .context("missing download")?;
+    assert_eq!(download.version.to_string(), "0.1.0");
+    drop(consumer);
+
+    let publisher = create_client(&config).await?;
+    publish_component(
+        &publisher,
+        &name,
+        "0.2.0",
+        "(component)",
+        false,
+        &signing_key,
+    )
+    .await?;
+    drop(publisher);
+
+    // The stored package is resolved without a maximum age
+    let consumer = create_client(&consumer_config).await?;
+    let download = consumer
+        .download(&name, &"*".parse()?)
+        .await?
+        .context("missing download")?;
+    assert_eq!(download.version.to_string(), "0.1.0");
+
+    // The stored package is updated when it was verified too long ago
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let consumer = consumer.with_max_checkpoint_age(Duration::from_millis(50));
+    let download = consumer
+        .download(&name, &"*".parse()?)
+        .await?
+        .context("missing download")?;
+    assert_eq!(download.version.to_string(), "0.2.0");
+
+    // The update is recorded in client storage
+    let info = consumer
+        .registry()
+        .load_package(None, &name)
+        .await?
+        .unwrap();
+    assert!(info.verified_at.unwrap().elapsed()? < Duration::from_secs(60));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_supports_dyn_storage() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:erased")?;
+    let client = create_client(&config).await?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+    drop(client);
+
+    // A client with shared content storage is of a different type
+    let content = Arc::new(FileSystemContentStorage::lock(root.join("shared-content"))?);
+    let shared = Client::builder()
+        .with_url(config.home_url.as_ref().unwrap())
+        .with_registry_storage(FileSystemRegistryStorage::lock(
+            root.join("shared-registries").join("home"),
+        )?)
+        .with_content_storage(content.clone())
+        .with_namespace_map_storage(FileSystemNamespaceMapStorage::new(
+            root.join("shared-namespaces.json"),
+        ))
+        .with_disable_interactive(true)
+        .build()?;
+
+    let clients: Vec<DynClient> = vec![create_client(&config).await?.into_dyn(), shared.into_dyn()];
+    for client in &clients {
+        let download = client.download_exact(&name, &"0.1.0".parse()?).await?;
+        assert!(client
+            .content()
+            .content_location(&download.digest)
+            .is_some());
+    }
+
+    let digest = clients[1]
+        .package(&name)
+        .await?
+        .state
+        .release(&"0.1.0".parse()?)
+        .and_then(|r| r.content().cloned())
+        .context("expected release content")?;
+    assert!(content.content_location(&digest).is_some());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_builder_applies_settings() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+    let url = config.home_url.as_ref().unwrap();
+
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:built")?;
+    let client = create_client(&config).await?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+    drop(client);
+
+    let builder = || {
+        Client::builder()
+            .with_url(url)
+            .with_content_storage(
+                FileSystemContentStorage::lock(root.join("built-content")).unwrap(),
+            )
+            .with_namespace_map_storage(FileSystemNamespaceMapStorage::new(
+                root.join("built-namespaces.json"),
+            ))
+            .with_disable_interactive(true)
+    };
+
+    // The storages are required
+    assert!(builder().build().is_err());
+
+    let registries = root.join("built-registries").join("home");
+    let client = builder()
+        .with_registry_storage(FileSystemRegistryStorage::lock(&registries)?)
+        .with_retry_policy(api::RetryPolicy::none())
+        .with_timeout(Duration::from_secs(30))
+        .with_connect_timeout(Duration::from_secs(5))
+        .with_user_agent_suffix("builder-test/1.0")
+        .build()?;
+    client.download_exact(&name, &"0.1.0".parse()?).await?;
+    drop(client);
+
+    // An offline client sends no requests
+    let client = builder()
+        .with_registry_storage(FileSystemRegistryStorage::lock(&registries)?)
+        .with_offline(true)
+        .build()?;
+    match client.update().await {
+        Err(ClientError::Api(e)) => assert!(e.is_offline(), "unexpected error: {e}"),
+        res => panic!("expected an offline error, got {res:?}"),
+    }
+
+    // Packages already in storage are still available
+    let info = client
+        .registry()
+        .load_package(None, &name)
+        .await?
+        .context("expected the package in storage")?;
+    assert_eq!(info.name, name);
+
+    Ok(())
+}
+
+/// A middleware that delays requests and records their timeouts.
+#[derive(Default, Clone)]
+struct SlowMiddleware {
+    delay: Arc<Mutex<Duration>>,
+    timeouts: Arc<Mutex<Vec<Option<Duration>>>>,
+}
+
+#[async_trait::async_trait]
+impl Middleware for SlowMiddleware {
+    async fn handle(
+        &self,
+        request: reqwest::Request,
+        next: Next<'_>,
+    ) -> Result<reqwest::Response, api::ClientError> {
+        self.timeouts
+            .lock()
+            .unwrap()
+            .push(request.timeout().copied());
+        let delay = *self.delay.lock().unwrap();
+        tokio::time::sleep(delay).await;
+        next.run(request).await
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_requires_content_source_support() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    let client = create_client(&config).await?;
+    let signing_key = test_signing_key();
+
+    // Content sources are not declared to a registry that does not accept them
+    let digest = HashAlgorithm::Sha256.digest(b"hosted elsewhere");
+    let info = PublishInfo {
+        name: PackageName::new("test:sourced")?,
+        head: None,
+        entries: vec![
+            PublishEntry::Init,
+            PublishEntry::Release {
+                version: "1.0.0".parse()?,
+                content: digest.clone(),
+                content_type: ContentType::Component,
+            },
+        ],
+    };
+    let sources = [(
+        digest,
+        vec![api::ContentSource::HttpGet {
+            url: "https://example.com/component.wasm".to_string(),
+            accept_ranges: false,
+            size: None,
+        }],
+    )]
+    .into_iter()
+    .collect();
+    match client
+        .publish_with_content_sources(&signing_key, info, sources)
+        .await
+    {
+        Err(ClientError::ContentSourcesNotSupported) => Ok(()),
+        res => bail!("expected content sources to be unsupported, got {res:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_enforces_operation_deadlines() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let signing_key = support::test_signing_key();
+    let name = PackageName::new("test:deadline")?;
+    let slow = SlowMiddleware::default();
+    let client = create_client(&config).await?.with_middleware(slow.clone());
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Requests of an operation with a deadline time out at the deadline
+    slow.timeouts.lock().unwrap().clear();
+    deadline::timeout(Duration::from_secs(60), client.update()).await?;
+    let timeouts = slow.timeouts.lock().unwrap().clone();
+    assert!(!timeouts.is_empty());
+    assert!(timeouts
+        .iter()
+        .all(|t| t.is_some_and(|t| t <= Duration::from_secs(60))));
+
+    // Requests without a deadline have no timeout
+    slow.timeouts.lock().unwrap().clear();
+    client.update().await?;
+    assert!(slow.timeouts.lock().unwrap().iter().all(Option::is_none));
+
+    // An operation that runs past its deadline fails
+    *slow.delay.lock().unwrap() = Duration::from_secs(5);
+    let start = std::time::Instant::now();
+    match deadline::timeout(Duration::from_millis(200), client.update()).await {
+        Err(ClientError::DeadlineExceeded) => {}
+        res => bail!("expected the deadline to be exceeded, got {res:?}"),
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Nested deadlines end at the earlier deadline
+    let outer = std::time::Instant::now() + Duration::from_millis(200);
+    let res = deadline::within(
+        outer,
+        deadline::timeout(Duration::from_secs(60), async {
+            assert_eq!(deadline::current(), Some(outer));
+            client.update().await
+        }),
+    )
+    .await;
+    assert!(matches!(res, Err(ClientError::DeadlineExceeded)));
+    assert_eq!(deadline::current(), None);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn client_ffi_publishes_downloads_and_verifies() -> Result<()> {
+    use std::{
+        ffi::{CStr, CString},
+        ptr,
+    };
+    use warg_client_ffi::*;
+
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
+
+    let ffi_config = Config {
+        registries_dir: Some(root.join("ffi-registries")),
+        content_dir: Some(root.join("ffi-content")),
+        namespace_map_path: Some(root.join("ffi-namespaces")),
+        ..config.clone()
+    };
+    let config_path = root.join("ffi-config.json");
+    ffi_config.write_to_file(&config_path)?;
+
+    let content_path = root.join("ffi-component.wasm");
+    fs::write(&content_path, wat::parse_str("(component)")?)?;
+
+    let signing_key = support::test_signing_key().encode();
+    let c = |s: &str| CString::new(s).unwrap();
+    let last_error = || unsafe { CStr::from_ptr(warg_last_error_message()) }.to_string_lossy();
+
+    // The bindings block, so they are called outside of the test's runtime
+    tokio::task::spawn_blocking(move || unsafe {
+        let mut client = ptr::null_mut();
+        let code = warg_client_new(
+            ptr::null(),
+            c(config_path.to_str().unwrap()).as_ptr(),
+            &mut client,
+        );
+        assert_eq!(code, WargErrorCode::Ok);
+        assert!(warg_last_error_message().is_null());
+
+        let mut record_id = ptr::null_mut();
+        let code = warg_publish(
+            client,
+            c("test:ffi").as_ptr(),
+            c("1.0.0").as_ptr(),
+            c(content_path.to_str().unwrap()).as_ptr(),
+            c(&signing_key).as_ptr(),
+            true,
+            &mut record_id,
+        );
+        assert_eq!(code, WargErrorCode::Ok, "{}", last_error());
+        assert!(CStr::from_ptr(record_id)
+            .to_str()
+            .unwrap()
+            .starts_with("sha256:"));
+        warg_string_free(record_id);
+
+        let mut download = ptr::null_mut();
+        let code = warg_download(
+            client,
+            c("test:ffi").as_ptr(),
+            c("^1.0").as_ptr(),
+            &mut download,
+        );
+        assert_eq!(code, WargErrorCode::Ok, "{}", last_error());
+        assert_eq!(
+            CStr::from_ptr(warg_download_version(download)).to_str(),
+            Ok("1.0.0")
+        );
+        let path = CStr::from_ptr(warg_download_path(download))
+            .to_str()
+            .unwrap();
+        assert_eq!(
+            fs::read(path).unwrap(),
+            wat::parse_str("(component)").unwrap()
+        );
+        warg_download_free(download);
+
+        let mut download = ptr::null_mut();
+        let code = warg_download(
+            client,
+            c("test:ffi").as_ptr(),
+            c("^2.0").as_ptr(),
+            &mut download,
+        );
+        assert_eq!(code, WargErrorCode::NotFound);
+        assert!(download.is_null());
+        assert!(last_error().contains("no version"));
+
+        let mut report = ptr::null_mut();
+        let code = warg_verify(client, c("test:ffi").as_ptr(), &mut report);
+        assert_eq!(code, WargErrorCode::Ok, "{}", last_error());
+        let json: serde_json::Value =
+            serde_json::from_slice(CStr::from_ptr(report).to_bytes()).unwrap();
+        assert_eq!(json["anomalies"], serde_json::json!([]));
+        warg_string_free(report);
+
+        let code = warg_verify(client, c("test:unknown").as_ptr(), ptr::null_mut());
+        assert_eq!(code, WargErrorCode::NotFound);
+
+        let code = warg_verify(client, c("not a name").as_ptr(), ptr::null_mut());
+        assert_eq!(code, WargErrorCode::InvalidArgument);
+
+        warg_client_free(client);
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// A middleware that records the peak number of requests in flight at once.
+#[derive(Default, Clone)]
+struct ConcurrencyMiddleware {
+    in_flight: Arc<Mutex<usize>>,
+    peak: Arc<Mutex<usize>>,
+}
+
+#[async_trait::async_trait]
+impl Middleware for ConcurrencyMiddleware {
+    async fn handle(
+        &self,
+        request: reqwest::Request,
+        next: Next<'_>,
+    ) -> Result<reqwest::Response, api::ClientError> {
+        {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            *in_flight += 1;
+            let mut peak = self.peak.lock().unwrap();
+            *peak = (*peak).max(*in_flight);
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let res = next.run(request).await;
+        *self.in_flight.lock().unwrap() -= 1;
+        res
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn client_limits_concurrency() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    let config = Config {
+        max_in_flight_requests: Some(1),
+        ..config
+    };
+
+    let signing_key = support::test_signing_key();
+    let first = PackageName::new("test:first")?;
+    let second = PackageName::new("test:second")?;
+    {
+        let client = create_client(&config).await?;
+        publish_component(&client, &first, "0.1.0", "(component)", true, &signing_key).await?;
+        publish_component(&client, &second, "0.1.0", "(component)", true, &signing_key).await?;
+    }
+
+    let requirement = "0.1.0".parse()?;
+    let download = |limits: Option<ConcurrencyLimits>| {
+        let config = &config;
+        let first = &first;
+        let second = &second;
+        let requirement = &requirement;
+        async move {
+            fs::remove_dir_all(config.registries_dir.as_ref().unwrap())?;
+            fs::remove_dir_all(config.content_dir.as_ref().unwrap())?;
+
+            let middleware = ConcurrencyMiddleware::default();
+            let mut client = create_client(config)
+                .await?
+                .with_middleware(middleware.clone());
+            if let Some(limits) = limits {
+                client = client.with_concurrency_limits(limits);
+            }
+
+            let (a, b) = tokio::join!(
+                client.download(first, requirement),
+                client.download(second, requirement)
+            );
+            a?.context("first package should download")?;
+            b?.context("second package should download")?;
+            let peak = *middleware.peak.lock().unwrap();
+            anyhow::Ok(peak)
+        }
+    };
+
+    // Concurrent operations of a client share the limits of its configuration
+    assert_eq!(download(None).await?, 1);
+
+    // Limits set at runtime replace those of the configuration
+    assert!(download(Some(ConcurrencyLimits::default())).await? > 1);
+
+    Ok(())
+}
+
+/// A middleware that records the peak number of content uploads in flight
+/// at once, optionally failing them.
+#[derive(Default, Clone)]
+struct UploadMiddleware {
+    in_flight: Arc<Mutex<usize>>,
+    peak: Arc<Mutex<usize>>,
+    fail: bool,
+}
+
+#[async_trait::async_trait]
+impl Middleware for UploadMiddleware {
+    async fn handle(
+        &self,
+        request: reqwest::Request,
+        next: Next<'_>,
+    ) -> Result<reqwest::Response, api::ClientError> {
+        if !request.url().path().contains("/content/") {
+            return next.run(request).await;
+        }
+
+        if self.fail {
+            return Err(api::ClientError::LocalTransport(anyhow::anyhow!(
+                "injected upload failure"
+            )));
+        }
+
+        {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            *in_flight += 1;
+            let mut peak = self.peak.lock().unwrap();
+            *peak = (*peak).max(*in_flight);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let res = next.run(request).await;
+        *self.in_flight.lock().unwrap() -= 1;
+        res
+    }
+}
+
+/// Stores components parsed from WAT in the content storage of a client.
+async fn store_components(client: &FileSystemClient, contents: &[&str]) -> Result<Vec<AnyHash>> {
+    let mut digests = Vec::new();
+    for wat in contents {
+        let bytes = wat::parse_str(wat)?;
+        digests.push(
+            client
+                .content()
+                .store_content(
+                    Box::pin(futures::stream::once(async move { Ok(bytes.into()) })),
+                    None,
+                )
+                .await?,
+        );
+    }
+    Ok(digests)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn client_uploads_content_in_parallel() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    let signing_key = support::test_signing_key();
+
+    let info = |name: &PackageName, digests: Vec<AnyHash>| PublishInfo {
+        name: name.clone(),
+        head: None,
+        entries: std::iter::once(PublishEntry::Init)
+            .chain(
+                digests
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, content)| PublishEntry::Release {
+                        version: format!("1.{i}.0").parse().unwrap(),
+                        content,
+                        content_type: ContentType::Component,
+                    }),
+            )
+            .collect(),
+    };
+
+    // The missing content of a publish is uploaded concurrently
+    let uploads = UploadMiddleware::default();
+    let client = create_client(&config)
+        .await?
+        .with_middleware(uploads.clone());
+    let name = PackageName::new("test:parallel")?;
+    let digests = store_components(
+        &client,
+        &[
+            "(component)",
+            "(component (core module))",
+            "(component (core module) (core module))",
+        ],
+    )
+    .await?;
+    let record_id = client
+        .publish_with_info(&signing_key, info(&name, digests))
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+    assert!(*uploads.peak.lock().unwrap() > 1);
+    drop(client);
+
+    // Every failed upload is reported
+    let client = create_client(&config)
+        .await?
+        .with_middleware(UploadMiddleware {
+            fail: true,
+            ..Default::default()
+        });
+    let name = PackageName::new("test:failed-uploads")?;
+    let digests = store_components(
+        &client,
+        &[
+            "(component (core module) (core module) (core module))",
+            "(component (core module) (core module) (core module) (core module))",
+        ],
+    )
+    .await?;
+    match client
+        .publish_with_info(&signing_key, info(&name, digests.clone()))
+        .await
+    {
+        Err(ClientError::ContentUploadsFailed { failures, .. }) => {
+            let mut failed = failures
+                .into_iter()
+                .map(|(digest, _)| digest)
+                .collect::<Vec<_>>();
+            failed.sort();
+            let mut expected = digests;
+            expected.sort();
+            assert_eq!(failed, expected);
+        }
+        res => bail!("expected content uploads to fail, got {res:?}"),
+    }
+
+    Ok(())
+}
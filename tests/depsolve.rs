@@ -2,20 +2,27 @@ use self::support::*;
 use anyhow::{Context, Result};
 use std::time::Duration;
 use warg_client::{
+    lockfile::{Lockfile, LOCKFILE_NAME},
     storage::{
         ContentStorage, FileSystemContentStorage, FileSystemNamespaceMapStorage,
         FileSystemRegistryStorage, PublishEntry, PublishInfo, RegistryStorage,
     },
+    vendor::VendorManifest,
+    verification::ClosureReport,
     Client,
 };
-use warg_crypto::signing::PrivateKey;
-use warg_protocol::registry::{PackageName, RecordId};
+use warg_crypto::{signing::PrivateKey, Encode, Signable};
+use warg_protocol::{
+    package::ContentType,
+    registry::{PackageName, RecordId},
+};
 
 pub mod support;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn depsolve() -> Result<()> {
-    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    let root = root().await?;
+    let (_server, config) = spawn_server(&root, None, None, None).await?;
 
     let client = create_client(&config).await?;
     let signing_key = support::test_signing_key();
@@ -101,6 +108,69 @@ async fn depsolve() -> Result<()> {
         wasmprinter::print_bytes(&locked_bytes)?,
         wasmprinter::print_bytes(expected_locked)?
     );
+
+    let lockfile = client.lock_dependencies(&info).await?;
+    let registry = client.url().registry_domain().to_string();
+    assert_eq!(
+        lockfile.registries.keys().collect::<Vec<_>>(),
+        [&registry],
+        "dependencies should be locked to the home registry"
+    );
+    let mut locked = lockfile
+        .packages
+        .iter()
+        .map(|p| p.name.to_string())
+        .collect::<Vec<_>>();
+    locked.sort();
+    assert_eq!(locked, ["test:add", "test:five", "test:inc", "test:meet"]);
+    for package in &lockfile.packages {
+        assert_eq!(package.registry, registry);
+        assert_eq!(package.version.to_string(), "1.0.0");
+        assert!(client.content().content_location(&package.digest).is_some());
+    }
+    let lockfile_path = root.join(LOCKFILE_NAME);
+    lockfile.write_to_file(&lockfile_path)?;
+    assert_eq!(Lockfile::from_file(&lockfile_path)?, lockfile);
+
+    // Vendoring downloads the locked content that is not in client storage
+    client.clear_content_cache().await?;
+    let vendor_dir = root.join("vendor");
+    let manifest = client.vendor(&lockfile, &vendor_dir).await?;
+    assert_eq!(manifest.packages.len(), lockfile.packages.len());
+    assert_eq!(
+        manifest
+            .package(&PackageName::new("test:add")?)
+            .unwrap()
+            .path,
+        "test/add-1.0.0.wasm"
+    );
+    assert_eq!(VendorManifest::from_dir(&vendor_dir)?, manifest);
+    manifest.verify(&vendor_dir)?;
+
+    // The dependency closure verifies and the report is signed
+    let report = client.verify_closure(&lockfile, &signing_key).await?;
+    assert_eq!(report.key_id(), &signing_key.public_key().fingerprint());
+    ClosureReport::verify(
+        &signing_key.public_key(),
+        &report.as_ref().encode(),
+        report.signature(),
+    )?;
+    let report = report.into_contents();
+    assert!(report.is_verified(), "closure should be verified");
+    assert_eq!(report.packages.len(), lockfile.packages.len());
+
+    // A lockfile with a tampered digest does not verify
+    let mut tampered = lockfile.clone();
+    tampered.packages[0].digest = tampered.packages[1].digest.clone();
+    let report = client
+        .verify_closure(&tampered, &signing_key)
+        .await?
+        .into_contents();
+    assert!(!report.is_verified(), "closure should not be verified");
+    assert!(!report.packages[0].release.is_verified());
+    assert!(report.packages[0].content.is_verified());
+    assert!(report.packages[1].is_verified());
+
     let bundled_bytes = client.bundle_component(&info).await?;
     let expected_bundled = wat::parse_file("tests/components/meet_bundled.wat")?;
     assert_eq!(
@@ -151,6 +221,7 @@ async fn publish_package(
                 entries: vec![PublishEntry::Release {
                     version: "1.0.0".to_string().parse().unwrap(),
                     content: add_digest.clone(),
+                    content_type: ContentType::Component,
                 }],
             },
         )
@@ -3,6 +3,20 @@
 use super::{support::*, *};
 use anyhow::Result;
 use warg_client::api;
+use warg_protocol::{operator, registry::RecordId};
+use warg_server::{
+    datastore::{DataStore, MemoryDataStore, PolicyKind},
+    listener::{ListenerConfig, TlsConfig},
+    policy::{
+        approval::{ApprovalPolicy, NamespaceApproval},
+        content::WasmContentPolicy,
+        record::{AuthorizedKeyPolicy, NameListPolicy, NameLists, Team, TeamPolicy},
+        semver::{SemverAction, SemverPolicy},
+        sources::ContentSourcePolicy,
+        PolicyMode,
+    },
+    services::{FederationConfig, RetentionConfig, TyposquatConfig, Upstream},
+};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_starts_with_initial_checkpoint() -> Result<()> {
@@ -29,6 +43,161 @@ async fn it_publishes_a_component() -> Result<()> {
     Ok(())
 }
 
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_serves_embedded_clients() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_embedded_server(
+        &root,
+        "memory-embedded",
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server")),
+    )
+    .await?;
+
+    // Requests and content downloads are served in-process
+    test_initial_checkpoint(&config).await?;
+    test_component_publishing(&config).await
+}
+
+/// Writes a self-signed certificate for `localhost` and its private key.
+#[cfg(unix)]
+fn self_signed_certificate(
+    root: &std::path::Path,
+) -> Result<(std::path::PathBuf, std::path::PathBuf)> {
+    use openssl::{
+        asn1::Asn1Time,
+        hash::MessageDigest,
+        pkey::PKey,
+        rsa::Rsa,
+        x509::{X509NameBuilder, X509},
+    };
+
+    let key = PKey::from_rsa(Rsa::generate(2048)?)?;
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_text("CN", "localhost")?;
+    let name = name.build();
+
+    let mut builder = X509::builder()?;
+    builder.set_version(2)?;
+    builder.set_subject_name(&name)?;
+    builder.set_issuer_name(&name)?;
+    builder.set_pubkey(&key)?;
+    builder.set_not_before(&*Asn1Time::days_from_now(0)?)?;
+    builder.set_not_after(&*Asn1Time::days_from_now(1)?)?;
+    builder.sign(&key, MessageDigest::sha256())?;
+
+    let certificate_path = root.join("cert.pem");
+    let key_path = root.join("key.pem");
+    std::fs::write(&certificate_path, builder.build().to_pem()?)?;
+    std::fs::write(&key_path, key.private_key_to_pem_pkcs8()?)?;
+    Ok((certificate_path, key_path))
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_serves_multiple_listeners() -> Result<()> {
+    let root = root().await?;
+    let (certificate, key) = self_signed_certificate(&root)?;
+    let (server, config) = spawn_server_with_config(
+        &root,
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_listener(ListenerConfig::tcp(([127, 0, 0, 1], 0)))
+            .with_listener(ListenerConfig::unix(root.join("registry.sock")))
+            .with_listener(
+                ListenerConfig::tcp(([127, 0, 0, 1], 0)).with_tls(TlsConfig::new(certificate, key)),
+            ),
+    )
+    .await?;
+
+    let [tcp, unix, tls] = server.urls() else {
+        panic!("expected three listener URLs, found {:?}", server.urls());
+    };
+    assert_eq!(tcp.scheme(), "http");
+    assert_eq!(unix.scheme(), "http+unix");
+    assert_eq!(tls.scheme(), "https");
+
+    // Clients are configured with the first listener
+    assert_eq!(
+        config.home_url.as_deref(),
+        Some(tcp.as_str().trim_end_matches('/'))
+    );
+    test_initial_checkpoint(&config).await?;
+    test_initial_checkpoint(&warg_client::Config {
+        home_url: Some(unix.to_string()),
+        ..config.clone()
+    })
+    .await?;
+
+    // The certificate is self-signed, so check the TLS listener directly
+    let response = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()?
+        .get(tls.join(warg_api::v1::paths::fetch_checkpoint())?)
+        .send()
+        .await?;
+    assert!(response.status().is_success());
+
+    // Plain HTTP is not served on the TLS listener
+    assert!(reqwest::get(format!(
+        "http://{host}:{port}/{path}",
+        host = tls.host_str().unwrap(),
+        port = tls.port().unwrap(),
+        path = warg_api::v1::paths::fetch_checkpoint()
+    ))
+    .await
+    .is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_returns_problem_details() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_problem_details(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_lists_namespaces() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_namespace_listing(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_gets_operator_info() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_operator_info(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_proves_inclusion_with_multi_proofs() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_multi_proofs(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_gets_package_key_history() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_package_key_history(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_onboards_maintainers_with_invites() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_package_invites(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_streams_fetched_logs() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_streaming_fetch_logs(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_fetches_checkpoints_conditionally() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_conditional_checkpoint_fetch(&config).await
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_yanks_a_package() -> Result<()> {
     let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
@@ -69,6 +238,18 @@ async fn it_rejects_non_wasm_content() -> Result<()> {
     test_wasm_content_policy(&config).await
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_validates_content_types() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_content_types(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_hosts_docs() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_docs_hosting(&config).await
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_rejects_unauthorized_signing_key() -> Result<()> {
     let (_server, config) = spawn_server(
@@ -85,6 +266,414 @@ async fn it_rejects_unauthorized_signing_key() -> Result<()> {
     test_unauthorized_signing_key(&config).await
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_records_dry_run_policy_decisions() -> Result<()> {
+    let root = root().await?;
+    let store = MemoryDataStore::new();
+    let authorized_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_content_policy(WasmContentPolicy::default())
+            .with_record_policy(
+                AuthorizedKeyPolicy::new()
+                    .with_namespace_key("test", authorized_key.public_key().fingerprint())?,
+            )
+            .with_policy_mode(PolicyMode::DryRun)
+            .with_data_store(store.clone());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+
+    // Publish non-wasm content with an unauthorized key; neither rejection is enforced
+    let name = PackageName::new("test:dry-run")?;
+    let client = create_client(&config).await?;
+    let digest = publish(
+        &client,
+        &name,
+        "0.1.0",
+        b"not wasm".to_vec(),
+        true,
+        &test_signing_key(),
+    )
+    .await?;
+
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let checkpoint = store.get_latest_checkpoint().await?.into_contents();
+    let records = store
+        .get_package_records(&log_id, checkpoint.checkpoint.log_length, None, 10)
+        .await?;
+    assert_eq!(records.len(), 1, "expected the record to be published");
+    let record_id = RecordId::package_record::<Sha256>(&records[0].envelope);
+
+    let decisions = store.get_policy_decisions(&log_id, &record_id).await?;
+    assert_eq!(decisions.len(), 2, "expected a record and content decision");
+    assert!(decisions.iter().all(|d| !d.enforced));
+
+    assert_eq!(decisions[0].kind, PolicyKind::Record);
+    assert!(decisions[0]
+        .rejection
+        .as_deref()
+        .is_some_and(|r| r.contains("not authorized to publish")));
+
+    assert_eq!(decisions[1].kind, PolicyKind::Content);
+    assert_eq!(decisions[1].digest.as_ref(), Some(&digest));
+    assert!(decisions[1].rejection.is_some());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_stores_content_licenses() -> Result<()> {
+    let root = root().await?;
+    let store = MemoryDataStore::new();
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_data_store(store.clone());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+
+    let client = create_client(&config).await?;
+    let signing_key = test_signing_key();
+    let licensed = publish(
+        &client,
+        &PackageName::new("test:licensed")?,
+        "0.1.0",
+        licensed_component("mit/apache2")?,
+        true,
+        &signing_key,
+    )
+    .await?;
+    let unlicensed = publish_component(
+        &client,
+        &PackageName::new("test:unlicensed")?,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    assert_eq!(
+        store
+            .get_content_metadata(&licensed)
+            .await?
+            .and_then(|m| m.license)
+            .as_deref(),
+        Some("MIT OR Apache-2.0")
+    );
+    assert!(store.get_content_metadata(&unlicensed).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_maintains_content_references() -> Result<()> {
+    let root = root().await?;
+    let store = MemoryDataStore::new();
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_data_store(store.clone());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_content_references(&config, &store).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_expires_rejected_and_pending_records() -> Result<()> {
+    let root = root().await?;
+    let store = MemoryDataStore::new();
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_data_store(store.clone());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_record_retention(&config, &store).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_commits_staged_publishes() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_staged_publishes(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_expires_staged_publishes() -> Result<()> {
+    let root = root().await?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_staged_publish_ttl(Duration::from_secs(1));
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_staged_publish_expiry(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_lists_validated_package_names() -> Result<()> {
+    let root = root().await?;
+    let store = MemoryDataStore::new();
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_data_store(store.clone());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_package_name_listing(&config, &store).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_paginates_records_by_fetch_token() -> Result<()> {
+    let root = root().await?;
+    let store = MemoryDataStore::new();
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_data_store(store.clone());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_fetch_token_pagination(&config, &store).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_enforces_name_lists() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_name_lists(NameListPolicy::new(NameLists {
+                reserved: vec!["test:reserved".into()],
+                blocked: Vec::new(),
+            })?)
+            .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_name_lists(&config, ADMIN_TOKEN).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_enforces_teams() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let teams = TeamPolicy::new(Default::default())?;
+    teams.set_team(
+        "test",
+        "core",
+        Team {
+            keys: [test_signing_key().public_key().fingerprint()].into(),
+            packages: vec!["*".into()],
+        },
+    )?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_teams(teams)
+            .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_teams(&config, ADMIN_TOKEN).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_requires_record_approvals() -> Result<()> {
+    let root = root().await?;
+    let approver = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_approvals(ApprovalPolicy {
+                namespaces: [(
+                    "test".to_string(),
+                    NamespaceApproval {
+                        approvers: [approver.public_key().fingerprint()].into(),
+                    },
+                )]
+                .into(),
+            });
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_record_approvals(&config, &approver).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_incompatible_releases() -> Result<()> {
+    let root = root().await?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_semver_policy(SemverPolicy::new(SemverAction::Reject).with_namespace("test"));
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_semver_policy(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_flags_typosquats() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_typosquat_detection(TyposquatConfig::new())
+            .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_typosquat_flags(&config, ADMIN_TOKEN).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_advisories() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_advisories(&config, ADMIN_TOKEN).await?;
+    test_osv_advisories(&config, ADMIN_TOKEN).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_runs_admin_operations() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_record_retention(RetentionConfig::new(Duration::ZERO))
+            .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_admin_client(&config, ADMIN_TOKEN).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_suspends_namespaces() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_namespace_suspension(&config, ADMIN_TOKEN).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_fetches_content_from_sources() -> Result<()> {
+    // Serve the content from both an allowed and a disallowed path
+    let content = wat::parse_str("(component)")?;
+    let router = axum::Router::new()
+        .route(
+            "/content/component",
+            axum::routing::get({
+                let content = content.clone();
+                move || async move { content }
+            }),
+        )
+        .route(
+            "/other/component",
+            axum::routing::get(move || async move { content }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let sources = Url::parse(&format!("http://{addr}/", addr = listener.local_addr()?))?;
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let root = root().await?;
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_content_policy(WasmContentPolicy::default())
+            .with_content_sources(
+                ContentSourcePolicy::new().with_allowed_prefix(sources.join("content/")?),
+            );
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_content_sources(&config, &sources).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_exports_static_registries() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let static_dir = root.join("static");
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_static_export_dir(&static_dir)
+            .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+
+    // Serve the export from a static file server
+    let router =
+        axum::Router::new().fallback_service(tower_http::services::ServeDir::new(static_dir));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let static_url = Url::parse(&format!("http://{addr}/", addr = listener.local_addr()?))?;
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    test_static_registry(&config, ADMIN_TOKEN, &static_url).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_exports_static_registries_on_checkpoint() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let static_dir = root.join("static");
+    let config =
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_static_export_dir(&static_dir)
+            .with_static_export_on_checkpoint()
+            .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+
+    let router =
+        axum::Router::new().fallback_service(tower_http::services::ServeDir::new(static_dir));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let static_url = Url::parse(&format!("http://{addr}/", addr = listener.local_addr()?))?;
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    test_static_export_on_checkpoint(&config, ADMIN_TOKEN, &static_url).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_checks_federation_health() -> Result<()> {
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    let root = root().await?;
+    let (_upstream, upstream_config) = spawn_server_with_config(
+        &root,
+        warg_server::Config::new(
+            test_operator_key(),
+            test_namespaces(),
+            root.join("upstream"),
+        ),
+    )
+    .await?;
+    let upstream_url = Url::parse(upstream_config.home_url.as_ref().unwrap())?;
+
+    // The second upstream pins a key that does not sign the upstream's checkpoints
+    let federation = FederationConfig::new()
+        .with_upstream(
+            Upstream::new("upstream.example", test_operator_key().public_key())
+                .with_url(upstream_url.clone()),
+        )
+        .with_upstream(
+            Upstream::new("unpinned.example", test_signing_key().public_key())
+                .with_url(upstream_url),
+        );
+    let imported = |registry: &str| operator::NamespaceState::Imported {
+        registry: registry.to_string(),
+    };
+
+    // Imports from registries that are not upstreams are refused
+    let untrusted = warg_server::Config::new(
+        test_operator_key(),
+        Some(vec![("other".to_string(), imported("elsewhere.example"))]),
+        root.join("untrusted"),
+    )
+    .with_addr(([127, 0, 0, 1], 0))
+    .with_federation(federation.clone());
+    match warg_server::Server::new(untrusted).initialize().await {
+        Err(e) => assert!(e.to_string().contains("not a trusted upstream"), "{e}"),
+        Ok(_) => bail!("expected the server to refuse an untrusted import"),
+    }
+
+    let config = warg_server::Config::new(
+        test_operator_key(),
+        Some(vec![
+            ("test".to_string(), operator::NamespaceState::Defined),
+            ("wasi".to_string(), imported("upstream.example")),
+            ("other".to_string(), imported("unpinned.example")),
+        ]),
+        root.join("server"),
+    )
+    .with_federation(federation)
+    .with_admin_token(ADMIN_TOKEN.to_string().into());
+    let (_server, config) = spawn_server_with_config(&root, config).await?;
+    test_federation_health(&config, ADMIN_TOKEN).await
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_rejects_unknown_signing_key() -> Result<()> {
     let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
@@ -97,6 +686,18 @@ async fn it_rejects_invalid_signature() -> Result<()> {
     test_invalid_signature(&config).await
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_mismatched_content() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_mismatched_content_upload(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_deduplicates_content() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_content_deduplication(&config).await
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_formats_custom_content_urls() -> Result<()> {
     let (_server, config) = spawn_server(
@@ -114,3 +715,21 @@ async fn it_get_ledger() -> Result<()> {
     let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
     test_get_ledger(&config).await
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_renders_a_version_badge() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_package_badge(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_embargoes_releases() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_embargoed_release(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_lists_package_records() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_list_records(&config).await
+}
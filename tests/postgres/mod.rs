@@ -5,7 +5,11 @@ use anyhow::{Context, Result};
 use testresult::TestResult;
 use warg_client::api;
 use warg_protocol::registry::RegistryLen;
-use warg_server::datastore::{DataStore, PostgresDataStore};
+use warg_server::{
+    datastore::{DataStore, PostgresDataStore},
+    policy::approval::{ApprovalPolicy, NamespaceApproval},
+    services::TyposquatConfig,
+};
 
 fn data_store() -> Result<Box<dyn DataStore>> {
     Ok(Box::new(PostgresDataStore::new(
@@ -30,6 +34,8 @@ fn data_store() -> Result<Box<dyn DataStore>> {
 /// out to multiple tests.
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_works_with_postgres() -> TestResult {
+    const ADMIN_TOKEN: &str = "admin-token";
+
     let root = root().await?;
     let (server, config) = spawn_server(
         &root,
@@ -44,23 +50,36 @@ async fn it_works_with_postgres() -> TestResult {
 
     // This should be the same set of tests as in `tests/memory/mod.rs`
     test_initial_checkpoint(&config).await?;
+    test_operator_info(&config).await?;
+    test_namespace_listing(&config).await?;
     test_component_publishing(&config).await?;
     test_package_yanking(&config).await?;
     test_wit_publishing(&config).await?;
     test_wasm_content_policy(&config).await?;
+    test_content_types(&config).await?;
+    test_docs_hosting(&config).await?;
     test_unauthorized_signing_key(&config).await?;
     // This is tested below where a different server is used that
     // allows any signing key
     //test_unknown_signing_key(&config).await?;
     test_invalid_signature(&config).await?;
+    test_mismatched_content_upload(&config).await?;
+    test_content_deduplication(&config).await?;
+    test_problem_details(&config).await?;
     test_fetch_package_names(&config).await?;
     test_get_ledger(&config).await?;
+    test_conditional_checkpoint_fetch(&config).await?;
 
     let mut packages = vec![
         PackageName::new("test:component")?,
         PackageName::new("test:yankee")?,
         PackageName::new("test:wit-package")?,
         PackageName::new("test:unauthorized-key")?,
+        PackageName::new("test:mismatched-content")?,
+        PackageName::new("test:original")?,
+        PackageName::new("test:duplicate")?,
+        PackageName::new("test:typed-content")?,
+        PackageName::new("test:documented")?,
     ];
 
     // There should be two log entries in the registry
@@ -68,11 +87,14 @@ async fn it_works_with_postgres() -> TestResult {
     let ts_checkpoint = client.latest_checkpoint(None).await?;
     assert_eq!(
         ts_checkpoint.as_ref().checkpoint.log_length,
-        packages.len() as RegistryLen + 2, /* publishes + initial checkpoint + yank */
-        "expected {len} packages plus the initial checkpoint and yank",
+        packages.len() as RegistryLen + 3, /* publishes + initial checkpoint + yank + docs */
+        "expected {len} packages plus the initial checkpoint, yank and docs",
         len = packages.len()
     );
 
+    // A connection the client opened but never used would otherwise keep
+    // the server from shutting down
+    drop(client);
     drop(server);
 
     // Restart the server and ensure the data is still there
@@ -86,10 +108,11 @@ async fn it_works_with_postgres() -> TestResult {
     let ts_checkpoint = client.latest_checkpoint(None).await?;
     assert_eq!(
         ts_checkpoint.as_ref().checkpoint.log_length,
-        packages.len() as RegistryLen + 2, /* publishes + initial checkpoint + yank*/
-        "expected {len} packages plus the initial checkpoint and yank",
+        packages.len() as RegistryLen + 3, /* publishes + initial checkpoint + yank + docs */
+        "expected {len} packages plus the initial checkpoint, yank and docs",
         len = packages.len()
     );
+    drop(client);
 
     // Delete the client cache to force a complete download of all packages below
     fs::remove_dir_all(root.join("content"))?;
@@ -100,7 +123,8 @@ async fn it_works_with_postgres() -> TestResult {
 
     // Finally, after a restart, ensure the packages can be downloaded
     for package in packages {
-        if package.name() == "yankee" {
+        // These packages have no 0.1.0 release to download
+        if package.name() == "yankee" || package.name() == "documented" {
             continue;
         }
         client
@@ -112,7 +136,7 @@ async fn it_works_with_postgres() -> TestResult {
     // Restart the server for the custom content URL test
     drop(client);
     drop(server);
-    let (_server, config) = spawn_server(
+    let (server, config) = spawn_server(
         &root,
         Some("https://example.com".parse().unwrap()),
         Some(data_store()?),
@@ -121,6 +145,66 @@ async fn it_works_with_postgres() -> TestResult {
     .await?;
 
     test_custom_content_url(&config).await?;
+    test_content_references(&config, data_store()?.as_ref()).await?;
+    test_embargoed_release(&config).await?;
+    test_record_retention(&config, data_store()?.as_ref()).await?;
+    test_list_records(&config).await?;
+    test_staged_publishes(&config).await?;
+    test_fetch_token_pagination(&config, data_store()?.as_ref()).await?;
+    test_package_name_listing(&config, data_store()?.as_ref()).await?;
+    test_multi_proofs(&config).await?;
+    test_streaming_fetch_logs(&config).await?;
+    test_package_key_history(&config).await?;
+    test_package_invites(&config).await?;
+
+    // Restart the server to expire staged publishes after a second
+    drop(server);
+    let (server, config) = spawn_server_with_config(
+        &root,
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_boxed_data_store(data_store()?)
+            .with_staged_publish_ttl(Duration::from_secs(1)),
+    )
+    .await?;
+
+    test_staged_publish_expiry(&config).await?;
+
+    // Restart the server to require approvals for the `test` namespace
+    drop(server);
+    let approver = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+    let (server, config) = spawn_server_with_config(
+        &root,
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_boxed_data_store(data_store()?)
+            .with_approvals(ApprovalPolicy {
+                namespaces: [(
+                    "test".to_string(),
+                    NamespaceApproval {
+                        approvers: [approver.public_key().fingerprint()].into(),
+                    },
+                )]
+                .into(),
+            }),
+    )
+    .await?;
+
+    test_record_approvals(&config, &approver).await?;
+
+    // Restart the server for the tests of admin operations
+    drop(server);
+    let (_server, config) = spawn_server_with_config(
+        &root,
+        warg_server::Config::new(test_operator_key(), test_namespaces(), root.join("server"))
+            .with_boxed_data_store(data_store()?)
+            .with_typosquat_detection(TyposquatConfig::new())
+            .with_admin_token(ADMIN_TOKEN.to_string().into()),
+    )
+    .await?;
+
+    test_namespace_suspension(&config, ADMIN_TOKEN).await?;
+    test_typosquat_flags(&config, ADMIN_TOKEN).await?;
+    test_advisories(&config, ADMIN_TOKEN).await?;
+    test_osv_advisories(&config, ADMIN_TOKEN).await?;
 
     Ok(())
 }
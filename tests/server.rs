@@ -1,5 +1,6 @@
 use self::support::*;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
 use rand_core::OsRng;
 use reqwest::StatusCode;
 use std::{
@@ -10,25 +11,38 @@ use std::{
 use url::Url;
 use warg_api::v1::{
     content::{ContentSource, ContentSourcesResponse},
-    fetch::{FetchPackageNamesRequest, FetchPackageNamesResponse},
+    fetch::{
+        FetchError, FetchLogsRequest, FetchLogsStreamItem, FetchPackageNamesRequest,
+        FetchPackageNamesResponse, FETCH_LOGS_STREAM_CONTENT_TYPE,
+    },
     ledger::{LedgerSource, LedgerSourceContentType, LedgerSourcesResponse},
-    package::PublishRecordRequest,
+    package::{KeyEventKind, PackageError, PublishRecordRequest},
     paths,
+    problem::{ProblemCode, ProblemDetails, PROBLEM_CONTENT_TYPE},
+    proof::{InclusionRequest, InclusionResponse, MultiProofResponse},
+    static_registry,
 };
 use warg_client::{
     api,
-    storage::{PublishEntry, PublishInfo},
-    ClientError, Config,
+    operator::NamespaceKind,
+    policy::AdvisoryPolicy,
+    storage::{ContentStorage, PublishEntry, PublishInfo},
+    ClientError, Config, FileSystemClient,
 };
 use warg_crypto::{
-    hash::{HashAlgorithm, Sha256},
+    hash::{AnyHash, HashAlgorithm, Sha256},
     signing::PrivateKey,
     Encode, Signable,
 };
 use warg_protocol::{
-    package::{PackageEntry, PackageRecord, PACKAGE_RECORD_VERSION},
-    registry::{LogId, PackageName},
-    ProtoEnvelope, ProtoEnvelopeBody, Version,
+    operator::Permission,
+    package::{self, ContentType, PackageEntry, PackageRecord, PACKAGE_RECORD_VERSION},
+    registry::{LogId, PackageName, RecordId, TimestampedCheckpoint},
+    ProtoEnvelope, ProtoEnvelopeBody, SerdeEnvelope, Version,
+};
+use warg_server::{
+    admin::AdminClient,
+    datastore::{DataStore, DataStoreError, ExpiredRecords, RecordStatus},
 };
 use wit_component::DecodedWasm;
 
@@ -227,7 +241,7 @@ async fn test_wasm_content_policy(config: &Config) -> Result<()> {
             assert_eq!(name, rejected_name);
             assert_eq!(
                 reason,
-                "content is not valid WebAssembly: unexpected end-of-file (at offset 0x0)"
+                "content is not valid WebAssembly: unexpected end-of-file (at offset 0x0) [invalid-wasm]"
             );
 
             // Waiting on the publish should fail with a rejection as well
@@ -245,7 +259,7 @@ async fn test_wasm_content_policy(config: &Config) -> Result<()> {
                     assert_eq!(record_id, other);
                     assert_eq!(
                         reason,
-                        "content with digest `sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855` was rejected by policy: content is not valid WebAssembly: unexpected end-of-file (at offset 0x0)"
+                        "content with digest `sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855` was rejected by policy: content is not valid WebAssembly: unexpected end-of-file (at offset 0x0) [invalid-wasm]"
                     );
                 }
                 _ => panic!("expected a content policy rejection error"),
@@ -257,6 +271,179 @@ async fn test_wasm_content_policy(config: &Config) -> Result<()> {
     Ok(())
 }
 
+async fn test_content_types(config: &Config) -> Result<()> {
+    let name = PackageName::new("test:typed-content")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+
+    let release = |version: &'static str, content: Vec<u8>, content_type| {
+        let client = &client;
+        let name = &name;
+        let signing_key = &signing_key;
+        async move {
+            let digest = client
+                .content()
+                .store_content(
+                    Box::pin(futures::stream::once(async move { Ok(content.into()) })),
+                    None,
+                )
+                .await?;
+            let mut entries = Vec::new();
+            if version == "0.1.0" {
+                entries.push(PublishEntry::Init);
+            }
+            entries.push(PublishEntry::Release {
+                version: version.parse()?,
+                content: digest,
+                content_type,
+            });
+            let record_id = client
+                .publish_with_info(
+                    signing_key,
+                    PublishInfo {
+                        name: name.clone(),
+                        head: None,
+                        entries,
+                    },
+                )
+                .await?;
+            client
+                .wait_for_publish(name, &record_id, Duration::from_millis(100))
+                .await?;
+            Result::<_>::Ok(())
+        }
+    };
+
+    // A documentation archive is not checked as WebAssembly
+    let archive = docs_archive(&[("index.html", "<h1>typed</h1>")])?;
+    release("0.1.0", archive, ContentType::DocsArchive).await?;
+
+    // A WIT package must be a component
+    let module = wat::parse_str("(module (func (export \"typed\")))")?;
+    match release("0.2.0", module, ContentType::WitPackage)
+        .await
+        .expect_err("expected publish to fail")
+        .downcast::<ClientError>()
+    {
+        Ok(ClientError::PublishRejected { reason, .. }) => {
+            assert!(
+                reason.contains("WebAssembly modules are not allowed"),
+                "unexpected reason: {reason}"
+            );
+        }
+        _ => panic!("expected a content policy rejection error"),
+    }
+
+    // Clients may download only the artifact types they need
+    let version = "0.1.0".parse()?;
+    assert!(client
+        .download_artifacts(&name, &version, &[ContentType::Component])
+        .await?
+        .is_empty());
+    let artifacts = client
+        .download_artifacts(&name, &version, &ContentType::all())
+        .await?;
+    assert_eq!(artifacts.len(), 1);
+    assert_eq!(artifacts[0].content_type, ContentType::DocsArchive);
+    assert_eq!(
+        client
+            .package(&name)
+            .await?
+            .state
+            .release(&version)
+            .unwrap()
+            .content_type,
+        ContentType::DocsArchive
+    );
+
+    Ok(())
+}
+
+fn docs_archive(files: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, contents.as_bytes())?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+async fn test_docs_hosting(config: &Config) -> Result<()> {
+    let name = PackageName::new("test:documented")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(
+        &client,
+        &name,
+        "1.0.0",
+        "(component (core module (func (export \"documented\"))))",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    let version = "1.0.0".parse()?;
+    let docs_url =
+        Url::parse(config.home_url.as_ref().unwrap())?.join(&format!("docs/{name}/1.0.0"))?;
+    assert_eq!(
+        reqwest::get(docs_url.clone()).await?.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    // An archive without an index is rejected
+    let archive = docs_archive(&[("api.html", "<h1>api</h1>")])?;
+    assert!(client
+        .publish_docs(&signing_key, &name, &version, archive.into())
+        .await
+        .is_err());
+
+    let archive = docs_archive(&[
+        ("index.html", "<h1>documented</h1>"),
+        ("api/index.html", "<h1>api</h1>"),
+        ("style.css", "h1 { color: red }"),
+    ])?;
+    let record_id = client
+        .publish_docs(&signing_key, &name, &version, archive.into())
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    // Requests without a trailing slash are redirected to the index
+    let response = reqwest::get(docs_url.clone()).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.url().path(), format!("/docs/{name}/1.0.0/"));
+    assert_eq!(response.text().await?, "<h1>documented</h1>");
+
+    let response = reqwest::get(docs_url.join("1.0.0/api")?).await?;
+    assert_eq!(response.url().path(), format!("/docs/{name}/1.0.0/api/"));
+    assert_eq!(response.text().await?, "<h1>api</h1>");
+
+    let response = reqwest::get(docs_url.join("1.0.0/style.css")?).await?;
+    assert_eq!(response.headers()["content-type"], "text/css");
+    assert_eq!(
+        reqwest::get(docs_url.join("1.0.0/missing.html")?)
+            .await?
+            .status(),
+        StatusCode::NOT_FOUND
+    );
+
+    // The archive is an artifact of the release
+    let artifacts = client
+        .download_artifacts(&name, &version, &[ContentType::DocsArchive])
+        .await?;
+    assert_eq!(artifacts.len(), 1);
+    assert_eq!(artifacts[0].content_type, ContentType::DocsArchive);
+
+    Ok(())
+}
+
 async fn test_unauthorized_signing_key(config: &Config) -> Result<()> {
     const PACKAGE_NAME: &str = "test:unauthorized-key";
     const PACKAGE_VERSION: &str = "0.1.0";
@@ -265,7 +452,7 @@ async fn test_unauthorized_signing_key(config: &Config) -> Result<()> {
     let name = PackageName::new(PACKAGE_NAME)?;
     let client = create_client(config).await?;
     let signing_key = test_signing_key();
-    publish_component(
+    let digest = publish_component(
         &client,
         &name,
         PACKAGE_VERSION,
@@ -278,13 +465,17 @@ async fn test_unauthorized_signing_key(config: &Config) -> Result<()> {
     // Next, we're going to publish a new record signed by a different key
     let signing_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
 
-    let message = format!(
-        "{:#}",
-        publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key,)
+    // The client rejects the record before it is signed
+    assert!(matches!(
+        publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key)
             .await
             .expect_err("expected publish to fail")
-    );
+            .downcast::<ClientError>()?,
+        ClientError::InvalidPublish { .. }
+    ));
 
+    let message =
+        publish_unchecked_release(config, &client, &name, "0.2.0", digest, &signing_key).await?;
     assert!(
         message.contains("not authorized to publish to package `test:unauthorized-key`"),
         "unexpected error message: {message}"
@@ -301,7 +492,7 @@ async fn test_unknown_signing_key(config: &Config) -> Result<()> {
     let name = PackageName::new(PACKAGE_NAME)?;
     let client = create_client(config).await?;
     let signing_key = test_signing_key();
-    publish_component(
+    let digest = publish_component(
         &client,
         &name,
         PACKAGE_VERSION,
@@ -315,13 +506,17 @@ async fn test_unknown_signing_key(config: &Config) -> Result<()> {
     // The new key is not currently known to the package log.
     let signing_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
 
-    let message = format!(
-        "{:#}",
-        publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key,)
+    // The client rejects the record before it is signed
+    assert!(matches!(
+        publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key)
             .await
             .expect_err("expected publish to fail")
-    );
+            .downcast::<ClientError>()?,
+        ClientError::InvalidPublish { .. }
+    ));
 
+    let message =
+        publish_unchecked_release(config, &client, &name, "0.2.0", digest, &signing_key).await?;
     assert!(
         message.contains("unknown key id"),
         "unexpected error message: {message}"
@@ -330,6 +525,59 @@ async fn test_unknown_signing_key(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Publishes a release of a package with the registry API directly, bypassing
+/// the checks the client makes before signing a record.
+///
+/// Returns the message of the expected rejection.
+async fn publish_unchecked_release(
+    config: &Config,
+    client: &FileSystemClient,
+    name: &PackageName,
+    version: &str,
+    content: AnyHash,
+    signing_key: &PrivateKey,
+) -> Result<String> {
+    let head = client
+        .package(name)
+        .await?
+        .state
+        .head()
+        .clone()
+        .context("expected the package log to have a head")?;
+    let record = ProtoEnvelope::signed_contents(
+        signing_key,
+        PackageRecord {
+            prev: Some(head.digest),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![PackageEntry::Release {
+                version: version.parse()?,
+                content,
+                content_type: ContentType::Component,
+            }],
+        },
+    )?;
+
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    match client
+        .publish_package_record(
+            None,
+            &LogId::package_log::<Sha256>(name),
+            PublishRecordRequest {
+                package_name: Cow::Borrowed(name),
+                record: Cow::Owned(ProtoEnvelopeBody::from(record)),
+                content_sources: Default::default(),
+                stage: false,
+                embargo_until: None,
+            },
+        )
+        .await
+    {
+        Ok(_) => bail!("expected publish to fail"),
+        Err(e) => Ok(format!("{e:#}")),
+    }
+}
+
 async fn test_invalid_signature(config: &Config) -> Result<()> {
     const PACKAGE_NAME: &str = "test:invalid-signature";
 
@@ -358,6 +606,8 @@ async fn test_invalid_signature(config: &Config) -> Result<()> {
         package_name: Cow::Borrowed(&name),
         record: Cow::Owned(ProtoEnvelopeBody::from(record)),
         content_sources: Default::default(),
+        stage: false,
+        embargo_until: None,
     };
 
     // Update the signature to one that does not match the contents
@@ -386,6 +636,285 @@ async fn test_invalid_signature(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Submits a record initializing a package with a single release directly
+/// to the registry, without uploading its content.
+async fn submit_initial_release(
+    config: &Config,
+    name: &PackageName,
+    version: &str,
+    content: AnyHash,
+) -> Result<warg_api::v1::package::PackageRecord> {
+    let signing_key = test_signing_key();
+    let record = ProtoEnvelope::signed_contents(
+        &signing_key,
+        PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: signing_key.public_key(),
+                },
+                PackageEntry::Release {
+                    version: version.parse()?,
+                    content,
+                    content_type: ContentType::Component,
+                },
+            ],
+        },
+    )?;
+
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    Ok(client
+        .publish_package_record(
+            None,
+            &LogId::package_log::<Sha256>(name),
+            PublishRecordRequest {
+                package_name: Cow::Borrowed(name),
+                record: Cow::Owned(ProtoEnvelopeBody::from(record)),
+                content_sources: Default::default(),
+                stage: false,
+                embargo_until: None,
+            },
+        )
+        .await?)
+}
+
+async fn test_mismatched_content_upload(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:mismatched-content";
+
+    let name = PackageName::new(PACKAGE_NAME)?;
+    // Use content not published by other tests so that it must be uploaded
+    let content = wat::parse_str("(component (core module))")?;
+    let digest = HashAlgorithm::Sha256.digest(&content);
+
+    let record = submit_initial_release(config, &name, "0.1.0", digest).await?;
+    let (_, missing) = record
+        .missing_content()
+        .next()
+        .context("expected the record to be missing content")?;
+    let api::UploadEndpoint::Http { url, .. } = &missing.upload[0];
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join(url)?;
+
+    // Content that does not match the digest is rejected
+    let response = reqwest::Client::new()
+        .post(url.clone())
+        .body(b"(component)".to_vec())
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.text().await?;
+    assert_eq!(
+        status,
+        StatusCode::BAD_REQUEST,
+        "unexpected response: {body}"
+    );
+    assert!(
+        body.contains("does not match expected digest"),
+        "unexpected response body: {body}"
+    );
+
+    // The record was not rejected, so the content can still be uploaded
+    let response = reqwest::Client::new()
+        .post(url)
+        .body(content)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    create_client(config)
+        .await?
+        .wait_for_publish(&name, &record.record_id, Duration::from_millis(100))
+        .await?;
+
+    Ok(())
+}
+
+async fn test_problem_details(config: &Config) -> Result<()> {
+    let url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let http = reqwest::Client::new();
+
+    // API errors have a stable code and identify the entity concerned
+    let log_id = LogId::package_log::<Sha256>(&PackageName::new("test:missing")?);
+    let response = http
+        .get(url.join(&paths::package_metadata(&log_id))?)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers()[reqwest::header::CONTENT_TYPE],
+        PROBLEM_CONTENT_TYPE
+    );
+    let details: ProblemDetails = response.json().await?;
+    assert_eq!(details.code, ProblemCode::LogNotFound);
+    assert_eq!(details.type_uri, "https://warg.io/problems/log-not-found");
+    assert_eq!(details.status, 404);
+    assert_eq!(details.id, Some(log_id.to_string()));
+
+    // Malformed requests and unknown paths are reported as problems too
+    let response = http
+        .post(url.join(paths::fetch_logs())?)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body("{")
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let details: ProblemDetails = response.json().await?;
+    assert_eq!(details.code, ProblemCode::BadRequest);
+    assert!(details.detail.is_some());
+
+    let response = http.get(url.join("v1/unknown")?).send().await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let details: ProblemDetails = response.json().await?;
+    assert_eq!(details.code, ProblemCode::NotFound);
+
+    // The client surfaces problems as typed errors
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    match client.get_package_metadata(None, &log_id).await {
+        Err(api::ClientError::Package(PackageError::LogNotFound(id))) => assert_eq!(id, log_id),
+        res => bail!("expected a log not found error, got {res:?}"),
+    }
+
+    Ok(())
+}
+
+async fn test_content_deduplication(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let digest = publish_component(
+        &client,
+        &PackageName::new("test:original")?,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Content already present on the registry does not need to be uploaded
+    let name = PackageName::new("test:duplicate")?;
+    let record = submit_initial_release(config, &name, "0.1.0", digest.clone()).await?;
+    assert_eq!(record.missing_content().count(), 0);
+
+    client
+        .wait_for_publish(&name, &record.record_id, Duration::from_millis(100))
+        .await?;
+    let download = client
+        .download(&name, &"0.1.0".parse()?)
+        .await?
+        .context("failed to resolve package")?;
+    assert_eq!(download.digest, digest);
+
+    Ok(())
+}
+
+async fn test_content_references(config: &Config, store: &dyn DataStore) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:referenced";
+
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+
+    // Use content not published by other tests so that only this package references it
+    let content = wat::parse_str("(component (core module (func)))")?;
+    let digest = publish(&client, &name, "0.1.0", content.clone(), true, &signing_key).await?;
+    publish(&client, &name, "0.2.0", content, false, &signing_key).await?;
+
+    let package = client.package(&name).await?;
+    let record_ids = ["0.1.0", "0.2.0"]
+        .into_iter()
+        .map(|version| {
+            Ok(package
+                .state
+                .release(&version.parse()?)
+                .context("expected the release to exist")?
+                .record_id
+                .clone())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let references = store.get_content_references(&digest).await?;
+    assert!(references.iter().all(|r| r.log_id == log_id));
+    assert_eq!(
+        references
+            .into_iter()
+            .map(|r| r.record_id)
+            .collect::<Vec<_>>(),
+        record_ids
+    );
+
+    // Yanking a version removes the references made for it
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Yank {
+                    version: "0.1.0".parse()?,
+                }],
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    assert_eq!(
+        store
+            .get_content_references(&digest)
+            .await?
+            .into_iter()
+            .map(|r| r.record_id)
+            .collect::<Vec<_>>(),
+        record_ids[1..]
+    );
+
+    Ok(())
+}
+
+async fn test_namespace_listing(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    assert_eq!(
+        client.namespaces().await?,
+        [api::Namespace {
+            name: "test".to_string(),
+            state: api::NamespaceState::Defined,
+            suspension: None,
+        }]
+    );
+
+    Ok(())
+}
+
+async fn test_operator_info(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    let info = client.operator_info().await?;
+    let operator_key_id = test_operator_key().public_key().fingerprint();
+
+    assert!(info.checkpoint.is_some());
+    assert_eq!(
+        info.namespace("test").map(|ns| &ns.kind),
+        Some(&NamespaceKind::Defined)
+    );
+    assert_eq!(
+        info.keys.iter().map(|k| &k.key_id).collect::<Vec<_>>(),
+        [&operator_key_id]
+    );
+    assert!(info.keys[0].permissions.contains(&Permission::Commit));
+    assert_eq!(
+        info.checkpoint_key_history
+            .iter()
+            .map(|c| (&c.key_id, c.granted))
+            .collect::<Vec<_>>(),
+        [(&operator_key_id, true)]
+    );
+
+    Ok(())
+}
+
 async fn test_custom_content_url(config: &Config) -> Result<()> {
     const PACKAGE_NAME: &str = "test:custom-content-url";
     const PACKAGE_VERSION: &str = "0.1.0";
@@ -470,6 +999,56 @@ async fn test_fetch_package_names(config: &Config) -> Result<()> {
     Ok(())
 }
 
+async fn test_conditional_checkpoint_fetch(config: &Config) -> Result<()> {
+    let url = Url::parse(config.home_url.as_ref().unwrap())?
+        .join(paths::fetch_checkpoint())
+        .unwrap();
+
+    let client = reqwest::Client::new();
+    let response = client.get(url.clone()).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .context("expected an ETag header")?
+        .clone();
+    let last_modified = response
+        .headers()
+        .get(reqwest::header::LAST_MODIFIED)
+        .context("expected a Last-Modified header")?
+        .clone();
+
+    // The checkpoint has not changed since the last request
+    let response = client
+        .get(url.clone())
+        .header(reqwest::header::IF_NONE_MATCH, etag)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = client
+        .get(url.clone())
+        .header(reqwest::header::IF_MODIFIED_SINCE, last_modified)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = client
+        .get(url)
+        .header(reqwest::header::IF_NONE_MATCH, "\"0-0\"")
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The API client returns its cached checkpoint when not modified
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let first = client.latest_checkpoint(None).await?;
+    let second = client.latest_checkpoint(None).await?;
+    assert_eq!(first.as_ref(), second.as_ref());
+
+    Ok(())
+}
+
 async fn test_get_ledger(config: &Config) -> Result<()> {
     let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
 
@@ -549,3 +1128,1840 @@ async fn test_get_ledger(config: &Config) -> Result<()> {
 
     Ok(())
 }
+
+async fn test_multi_proofs(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let mut names = Vec::new();
+    for name in ["first", "second", "third", "fourth"] {
+        let name = PackageName::new(format!("test:multi-proof-{name}"))?;
+        publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+        names.push(name);
+    }
+
+    // The client verifies inclusion with multi-proofs
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    assert!(api.capabilities().await?.multi_proofs);
+    client.update().await?;
+
+    let log_length = api
+        .latest_checkpoint(None)
+        .await?
+        .as_ref()
+        .checkpoint
+        .log_length;
+
+    // Only the head records of logs are included in the map, so only the
+    // leafs of the packages published above are proven
+    let mut leafs = Vec::new();
+    for name in &names {
+        leafs.push(
+            client
+                .package(name)
+                .await?
+                .head_registry_index
+                .context("expected the package to have a head registry index")?,
+        );
+    }
+    let request = InclusionRequest { log_length, leafs };
+
+    let home_url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let http = reqwest::Client::new();
+    let response = http
+        .post(home_url.join(paths::prove_inclusion())?)
+        .json(&request)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let bundle = response.json::<InclusionResponse>().await?;
+
+    let response = http
+        .post(home_url.join(paths::prove_multi_inclusion())?)
+        .json(&request)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let multi = response.json::<MultiProofResponse>().await?;
+
+    // Shared map peers are only included once
+    assert_eq!(multi.log, bundle.log);
+    assert!(
+        multi.map.len() < bundle.map.len(),
+        "expected the multi-proof ({multi} bytes) to be smaller than the bundle ({bundle} bytes)",
+        multi = multi.map.len(),
+        bundle = bundle.map.len()
+    );
+
+    // Unknown leafs are not found
+    let response = http
+        .post(home_url.join(paths::prove_multi_inclusion())?)
+        .json(&InclusionRequest {
+            log_length,
+            leafs: vec![log_length + 100],
+        })
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+async fn test_package_key_history(config: &Config) -> Result<()> {
+    let name = PackageName::new("test:key-history")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Grant and then revoke the permissions of another key
+    let other_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng)).public_key();
+    for entry in [
+        PublishEntry::Grant {
+            key: other_key.clone(),
+            permissions: vec![package::Permission::Release, package::Permission::Yank],
+        },
+        PublishEntry::Revoke {
+            key_id: other_key.fingerprint(),
+            permissions: vec![package::Permission::Yank],
+        },
+    ] {
+        let record_id = client
+            .publish_with_info(
+                &signing_key,
+                PublishInfo {
+                    name: name.clone(),
+                    head: None,
+                    entries: vec![entry],
+                },
+            )
+            .await?;
+        client
+            .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+            .await?;
+    }
+
+    let history = client.package_key_history(&name).await?;
+    let events = history
+        .events
+        .iter()
+        .map(|event| (event.kind, event.key_id.clone(), event.permissions.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        [
+            (
+                KeyEventKind::Init,
+                signing_key.public_key().fingerprint(),
+                package::Permission::all().to_vec()
+            ),
+            (
+                KeyEventKind::Grant,
+                other_key.fingerprint(),
+                vec![package::Permission::Release, package::Permission::Yank]
+            ),
+            (
+                KeyEventKind::Revoke,
+                other_key.fingerprint(),
+                vec![package::Permission::Yank]
+            ),
+        ]
+    );
+    assert!(history
+        .events
+        .iter()
+        .all(|event| event.signed_by == signing_key.public_key().fingerprint()));
+    assert_eq!(history.events[2].key, None);
+    assert!(history.events[0].registry_index < history.events[1].registry_index);
+    assert_ne!(history.events[1].record_id, history.events[2].record_id);
+
+    // Unknown packages are not found
+    match client
+        .package_key_history(&PackageName::new("test:no-key-history")?)
+        .await
+    {
+        Err(ClientError::PackageDoesNotExist { .. }) => {}
+        res => bail!("expected the package to not exist, got {res:?}"),
+    }
+
+    Ok(())
+}
+
+async fn test_package_invites(config: &Config) -> Result<()> {
+    let name = PackageName::new("test:invited")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let other_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Only keys of the package log may create invites
+    let e = client
+        .create_invite(
+            &other_key,
+            &name,
+            &[package::Permission::Release],
+            Duration::from_secs(60),
+        )
+        .await
+        .expect_err("expected the invite to be rejected");
+    assert!(
+        format!("{e:#}").contains("is not a key of the package log"),
+        "unexpected error: {e:#}"
+    );
+
+    let invite = client
+        .create_invite(
+            &signing_key,
+            &name,
+            &[package::Permission::Release],
+            Duration::from_secs(60 * 60),
+        )
+        .await?;
+
+    // Nothing to grant until the invite is redeemed
+    assert!(client.grant_invites(&signing_key, &name).await?.is_none());
+
+    let redeemed = client.redeem_invite(&other_key, &name, &invite.id).await?;
+    assert_eq!(redeemed, invite);
+    let e = client
+        .redeem_invite(&other_key, &name, &invite.id)
+        .await
+        .expect_err("expected the invite to already be redeemed");
+    assert!(
+        format!("{e:#}").contains("has already been redeemed"),
+        "unexpected error: {e:#}"
+    );
+    match client.redeem_invite(&other_key, &name, "unknown").await {
+        Err(ClientError::Api(api::ClientError::Package(
+            warg_api::v1::package::PackageError::InviteNotFound(id),
+        ))) if id == "unknown" => {}
+        res => bail!("expected the invite to not exist, got {res:?}"),
+    }
+
+    // The owner grants the invite's permissions to the redeeming key
+    let grant = client
+        .grant_invites(&signing_key, &name)
+        .await?
+        .context("expected the redeemed invite to be granted")?;
+    assert_eq!(grant.invites.len(), 1);
+    client
+        .wait_for_publish(&name, &grant.record_id, Duration::from_millis(100))
+        .await?;
+
+    publish_component(&client, &name, "0.2.0", "(component)", false, &other_key).await?;
+
+    // Granted invites are no longer listed
+    assert!(client.grant_invites(&signing_key, &name).await?.is_none());
+
+    Ok(())
+}
+
+async fn test_streaming_fetch_logs(config: &Config) -> Result<()> {
+    let name = PackageName::new("test:streamed-logs")?;
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    for (i, version) in ["0.1.0", "0.2.0"].into_iter().enumerate() {
+        publish_component(&client, &name, version, "(component)", i == 0, &signing_key).await?;
+    }
+
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let log_length = api
+        .latest_checkpoint(None)
+        .await?
+        .as_ref()
+        .checkpoint
+        .log_length;
+
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join(paths::fetch_logs())?;
+    let fetch = |packages: IndexMap<LogId, Option<String>>| {
+        let url = url.clone();
+        async move {
+            let response = reqwest::Client::new()
+                .post(url)
+                .header(reqwest::header::ACCEPT, FETCH_LOGS_STREAM_CONTENT_TYPE)
+                .json(&FetchLogsRequest {
+                    log_length,
+                    limit: None,
+                    operator: None,
+                    packages: Cow::Owned(packages),
+                })
+                .send()
+                .await?;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers()[reqwest::header::CONTENT_TYPE],
+                FETCH_LOGS_STREAM_CONTENT_TYPE
+            );
+            response
+                .text()
+                .await?
+                .lines()
+                .map(|line| Ok(serde_json::from_str::<FetchLogsStreamItem>(line)?))
+                .collect::<Result<Vec<_>>>()
+        }
+    };
+
+    // The records of each log are streamed in order and followed by the end item
+    let items = fetch([(log_id.clone(), None)].into()).await?;
+    let mut operator = 0;
+    let mut package = 0;
+    for item in &items[..items.len() - 1] {
+        match item {
+            FetchLogsStreamItem::Operator(_) if package == 0 => operator += 1,
+            FetchLogsStreamItem::Package { log_id: id, .. } if id == &log_id => package += 1,
+            item => bail!("unexpected stream item: {item:?}"),
+        }
+    }
+    assert!(operator > 0);
+    assert_eq!(package, 2);
+    assert!(matches!(
+        items.last(),
+        Some(FetchLogsStreamItem::End { more: false })
+    ));
+
+    // An unknown log ends the stream with an error item
+    let unknown = LogId::package_log::<Sha256>(&PackageName::new("test:unknown-streamed")?);
+    let items = fetch([(log_id, None), (unknown.clone(), None)].into()).await?;
+    assert!(matches!(
+        items.last(),
+        Some(FetchLogsStreamItem::Error(FetchError::LogNotFound(id))) if id == &unknown
+    ));
+
+    Ok(())
+}
+
+async fn test_package_badge(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:badge";
+
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+    publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key).await?;
+
+    // Yank the latest release so the badge should fall back to the previous one
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Yank {
+                    version: "0.2.0".parse()?,
+                }],
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let client = reqwest::Client::new();
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join(&paths::package_badge(&name))?;
+    let response = client.get(url).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .unwrap(),
+        "image/svg+xml"
+    );
+    let body = response.text().await?;
+    assert!(body.contains("v0.1.0"), "unexpected badge: {body}");
+    assert!(!body.contains("v0.2.0"), "unexpected badge: {body}");
+
+    // An unknown package still renders a badge, but with a 404 status
+    let url = Url::parse(config.home_url.as_ref().unwrap())?
+        .join(&paths::package_badge(&PackageName::new("test:unknown")?))?;
+    let response = client.get(url).send().await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.text().await?.contains("not found"));
+
+    Ok(())
+}
+
+async fn test_embargoed_release(config: &Config) -> Result<()> {
+    let name = PackageName::new("test:embargoed")?;
+    let client = create_client(config).await?;
+    // Use content not published by other tests so that it must be uploaded
+    let content = wat::parse_str("(component (core module (func (export \"embargoed\"))))")?;
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move { Ok(content.into()) })),
+            None,
+        )
+        .await?;
+
+    let until = SystemTime::now() + Duration::from_secs(2);
+    let record_id = client
+        .publish_embargoed_with_info(
+            &test_signing_key(),
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![
+                    PublishEntry::Init,
+                    PublishEntry::Release {
+                        version: "0.1.0".parse()?,
+                        content: digest.clone(),
+                        content_type: ContentType::Component,
+                    },
+                ],
+            },
+            until,
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let home_url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let api = api::Client::new(home_url.as_str(), None)?;
+    let file_url = home_url.join(&format!(
+        "content/{file}",
+        file = digest.to_string().replace(':', "-")
+    ))?;
+    let badge_url = home_url.join(&paths::package_badge(&name))?;
+
+    // The record is published, but its content is not served or listed
+    assert!(api.content_sources(None, &digest).await.is_err());
+    let response = reqwest::get(file_url.clone()).await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = reqwest::get(badge_url.clone()).await?.text().await?;
+    assert!(body.contains("no releases"), "unexpected badge: {body}");
+
+    // Once the embargo passes, the content is served and listed
+    if let Ok(remaining) = until.duration_since(SystemTime::now()) {
+        tokio::time::sleep(remaining + Duration::from_millis(100)).await;
+    }
+    assert!(api.content_sources(None, &digest).await.is_ok());
+    let response = reqwest::get(file_url).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = reqwest::get(badge_url).await?.text().await?;
+    assert!(body.contains("v0.1.0"), "unexpected badge: {body}");
+
+    // An embargo that cannot be represented as a time is rejected
+    let name = PackageName::new("test:embargo-overflow")?;
+    let signing_key = test_signing_key();
+    let record = ProtoEnvelope::signed_contents(
+        &signing_key,
+        PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![PackageEntry::Init {
+                hash_algorithm: warg_crypto::hash::HashAlgorithm::Sha256,
+                key: signing_key.public_key(),
+            }],
+        },
+    )?;
+    match api
+        .publish_package_record(
+            None,
+            &LogId::package_log::<Sha256>(&name),
+            PublishRecordRequest {
+                package_name: Cow::Borrowed(&name),
+                record: Cow::Owned(ProtoEnvelopeBody::from(record)),
+                content_sources: Default::default(),
+                stage: false,
+                embargo_until: Some(u64::MAX),
+            },
+        )
+        .await
+    {
+        Err(api::ClientError::Package(PackageError::Message { status: 400, .. })) => {}
+        Err(e) => bail!("expected an out of range embargo to be rejected, got {e:?}"),
+        Ok(_) => bail!("expected an out of range embargo to be rejected"),
+    }
+
+    Ok(())
+}
+
+async fn test_record_retention(config: &Config, store: &dyn DataStore) -> Result<()> {
+    let name = PackageName::new("test:retained")?;
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move {
+                Ok(wat::parse_str("(component)")?.into())
+            })),
+            None,
+        )
+        .await?;
+    let stage = |version: &'static str| {
+        client.stage_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Release {
+                    version: version.parse().unwrap(),
+                    content: digest.clone(),
+                    content_type: ContentType::Component,
+                }],
+            },
+        )
+    };
+
+    // One record is left staged and the other is rejected
+    let staged = stage("0.2.0").await?;
+    let rejected = stage("0.3.0").await?;
+    client.abandon_staged(&name, &rejected).await?;
+    assert!(matches!(
+        client
+            .wait_for_publish(&name, &rejected, Duration::from_millis(100))
+            .await,
+        Err(ClientError::PublishRejected { .. })
+    ));
+
+    // A record that is neither staged nor awaiting approval is left pending
+    // by not uploading its content
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let head = client
+        .package(&name)
+        .await?
+        .state
+        .head()
+        .clone()
+        .context("expected the package log to have a head")?;
+    let publish_pending = |version: &'static str| {
+        let record = ProtoEnvelope::signed_contents(
+            &signing_key,
+            PackageRecord {
+                prev: Some(head.digest.clone()),
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries: vec![PackageEntry::Release {
+                    version: version.parse().unwrap(),
+                    content: HashAlgorithm::Sha256.digest(version.as_bytes()),
+                    content_type: ContentType::Component,
+                }],
+            },
+        );
+        let api = &api;
+        let log_id = &log_id;
+        let name = &name;
+        async move {
+            let record = api
+                .publish_package_record(
+                    None,
+                    log_id,
+                    PublishRecordRequest {
+                        package_name: Cow::Borrowed(name),
+                        record: Cow::Owned(ProtoEnvelopeBody::from(record?)),
+                        content_sources: Default::default(),
+                        stage: false,
+                        embargo_until: None,
+                    },
+                )
+                .await?;
+            anyhow::Ok(record.record_id)
+        }
+    };
+    let pending = publish_pending("0.4.0").await?;
+
+    // Records updated after the cutoff are kept
+    assert_eq!(
+        store.expire_records(SystemTime::UNIX_EPOCH).await?.total(),
+        0
+    );
+    store.get_package_record(&log_id, &pending).await?;
+
+    let expired = store.expire_records(SystemTime::now()).await?;
+    assert!(
+        expired.pending >= 1,
+        "unexpected expired records: {expired:?}"
+    );
+    assert!(
+        expired.rejected >= 1,
+        "unexpected expired records: {expired:?}"
+    );
+    for record_id in [&pending, &rejected] {
+        assert!(matches!(
+            store.get_package_record(&log_id, record_id).await,
+            Err(DataStoreError::RecordNotFound(_))
+        ));
+    }
+
+    // Staged records are kept until they are committed or abandoned
+    store.get_package_record(&log_id, &staged).await?;
+
+    // Records awaiting approval are kept until they are approved or rejected
+    let awaiting = publish_pending("0.5.0").await?;
+    store.store_pending_approval(&log_id, &awaiting).await?;
+    assert_eq!(store.expire_records(SystemTime::now()).await?.total(), 0);
+    store.get_package_record(&log_id, &awaiting).await?;
+
+    // Records whose staging expired are no longer kept
+    let lapsed = publish_pending("0.6.0").await?;
+    let expires_at = SystemTime::now() - Duration::from_secs(1);
+    store
+        .store_staged_publish(&log_id, &lapsed, expires_at)
+        .await?;
+    assert_eq!(
+        store.expire_records(SystemTime::now()).await?,
+        ExpiredRecords {
+            rejected: 0,
+            pending: 1
+        }
+    );
+    assert!(matches!(
+        store.get_package_record(&log_id, &lapsed).await,
+        Err(DataStoreError::RecordNotFound(_))
+    ));
+
+    // Expired staging is rejected without the record being accessed
+    let unpolled = publish_pending("0.7.0").await?;
+    store
+        .store_staged_publish(&log_id, &unpolled, expires_at)
+        .await?;
+    assert_eq!(store.expire_staged_publishes(SystemTime::now()).await?, 1);
+    assert_eq!(store.get_staged_publish(&log_id, &unpolled).await?, None);
+    match store.get_package_record(&log_id, &unpolled).await?.status {
+        RecordStatus::Rejected(reason) => assert!(reason.contains("expired")),
+        status => bail!("expected the record to be rejected, got {status:?}"),
+    }
+    assert_eq!(
+        store.expire_records(SystemTime::now()).await?,
+        ExpiredRecords {
+            rejected: 1,
+            pending: 0
+        }
+    );
+
+    // Validated records are never removed
+    let package = client.package(&name).await?;
+    let head = package
+        .state
+        .head()
+        .clone()
+        .context("expected the package log to have a head")?;
+    store.get_package_record(&log_id, &head.digest).await?;
+    assert_eq!(store.expire_records(SystemTime::now()).await?.total(), 0);
+
+    Ok(())
+}
+
+async fn test_admin_client(config: &Config, admin_token: &str) -> Result<()> {
+    let name = PackageName::new("test:administered")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let home_url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let unauthorized = AdminClient::new(home_url.clone(), "wrong".to_string().into())?;
+    match unauthorized.create_checkpoint().await {
+        Err(e) => assert!(e.to_string().contains("401"), "{e}"),
+        Ok(_) => bail!("expected the admin request to be unauthorized"),
+    }
+
+    // A created checkpoint is the latest checkpoint and covers the published record
+    let admin = AdminClient::new(home_url, admin_token.to_string().into())?;
+    let checkpoint = admin.create_checkpoint().await?;
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let latest = api.latest_checkpoint(None).await?;
+    assert!(checkpoint.as_ref().checkpoint.log_length >= 2);
+    assert_eq!(
+        latest.as_ref().checkpoint.log_length,
+        checkpoint.as_ref().checkpoint.log_length
+    );
+
+    // Garbage collection removes the expired rejected record
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move {
+                Ok(wat::parse_str("(component)")?.into())
+            })),
+            None,
+        )
+        .await?;
+    let record_id = client
+        .stage_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Release {
+                    version: "0.2.0".parse()?,
+                    content: digest,
+                    content_type: ContentType::Component,
+                }],
+            },
+        )
+        .await?;
+    client.abandon_staged(&name, &record_id).await?;
+    let collected = admin.collect_garbage().await?;
+    assert!(
+        collected.rejected >= 1,
+        "unexpected collection: {collected:?}"
+    );
+
+    assert!(admin
+        .list_package_flags()
+        .await?
+        .as_array()
+        .unwrap()
+        .is_empty());
+    assert!(admin
+        .remove_advisory(&name, "TEST-0000")
+        .await
+        .unwrap_err()
+        .to_string()
+        .contains("404"));
+
+    Ok(())
+}
+
+async fn test_namespace_suspension(config: &Config, admin_token: &str) -> Result<()> {
+    let name = PackageName::new("test:suspended")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let admin = AdminClient::new(
+        Url::parse(config.home_url.as_ref().unwrap())?,
+        admin_token.to_string().into(),
+    )?;
+    assert!(admin
+        .suspend_namespace("unknown", "dispute")
+        .await
+        .unwrap_err()
+        .to_string()
+        .contains("404"));
+    admin.suspend_namespace("test", "ownership dispute").await?;
+
+    // The suspension is surfaced in the namespace listing
+    let namespaces = client.namespaces().await?;
+    let suspension = namespaces[0]
+        .suspension
+        .as_ref()
+        .context("expected the namespace to be suspended")?;
+    assert_eq!(suspension.reason, "ownership dispute");
+    assert_eq!(
+        admin.list_namespace_suspensions().await?[0]["namespace"],
+        "test"
+    );
+
+    // Publishing is rejected, but the existing log is preserved
+    match publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key).await {
+        Err(e) => match e.downcast::<ClientError>()? {
+            ClientError::PublishRejected { reason, .. } => {
+                assert!(reason.contains("suspended"), "{reason}")
+            }
+            e => bail!("expected the publish to be rejected, got {e}"),
+        },
+        Ok(_) => bail!("expected the publish to be rejected"),
+    }
+    client.update().await?;
+    client.download_exact(&name, &"0.1.0".parse()?).await?;
+
+    // A restored namespace accepts publishes again
+    admin.restore_namespace("test").await?;
+    assert!(admin
+        .restore_namespace("test")
+        .await
+        .unwrap_err()
+        .to_string()
+        .contains("404"));
+    assert!(client.namespaces().await?[0].suspension.is_none());
+    publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key).await?;
+
+    Ok(())
+}
+
+async fn test_content_sources(config: &Config, sources: &Url) -> Result<()> {
+    let name = PackageName::new("test:sourced")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    assert!(api.capabilities().await?.content_sources);
+
+    // The content is not in client storage, so it cannot be uploaded
+    let content = wat::parse_str("(component)")?;
+    let digest = HashAlgorithm::Sha256.digest(&content);
+    let info = PublishInfo {
+        name: name.clone(),
+        head: None,
+        entries: vec![
+            PublishEntry::Init,
+            PublishEntry::Release {
+                version: "0.1.0".parse()?,
+                content: digest.clone(),
+                content_type: ContentType::Component,
+            },
+        ],
+    };
+    let source = |path: &str| -> Result<IndexMap<AnyHash, Vec<ContentSource>>> {
+        Ok([(
+            digest.clone(),
+            vec![ContentSource::HttpGet {
+                url: sources.join(path)?.to_string(),
+                accept_ranges: false,
+                size: None,
+            }],
+        )]
+        .into())
+    };
+
+    // Sources outside of the allowed prefixes are rejected
+    let e = client
+        .publish_with_content_sources(&signing_key, info.clone(), source("other/component")?)
+        .await
+        .unwrap_err();
+    assert!(e.to_string().contains("is not allowed"), "{e}");
+
+    // A record whose content cannot be fetched is rejected
+    match client
+        .publish_with_content_sources(&signing_key, info.clone(), source("content/missing")?)
+        .await
+    {
+        Err(ClientError::Api(api::ClientError::Package(PackageError::Message {
+            message, ..
+        }))) => assert!(message.contains("failed to fetch content"), "{message}"),
+        res => bail!("expected the content to fail to be fetched, got {res:?}"),
+    }
+
+    // Otherwise, the registry fetches and verifies the content itself
+    let record_id = client
+        .publish_with_content_sources(&signing_key, info, source("content/component")?)
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let download = client.download_exact(&name, &"0.1.0".parse()?).await?;
+    assert_eq!(download.digest, digest);
+    assert_eq!(fs::read(&download.path)?, content);
+
+    Ok(())
+}
+
+async fn test_static_registry(config: &Config, admin_token: &str, static_url: &Url) -> Result<()> {
+    let name = PackageName::new("test:static")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let admin = AdminClient::new(
+        Url::parse(config.home_url.as_ref().unwrap())?,
+        admin_token.to_string().into(),
+    )?;
+    let checkpoint = admin.create_checkpoint().await?;
+    let export = admin.export_static().await?;
+    assert_eq!(export.log_length, checkpoint.as_ref().checkpoint.log_length);
+    assert_eq!(export.content, 1);
+
+    // A client of the static registry verifies and downloads from the exported files
+    let static_root = config
+        .content_dir
+        .as_ref()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("static-client");
+    let static_config = Config {
+        home_url: Some(static_url.to_string()),
+        registries_dir: Some(static_root.join("registries")),
+        content_dir: Some(static_root.join("content")),
+        namespace_map_path: Some(static_root.join("namespaces")),
+        ..config.clone()
+    };
+    let static_client = create_client(&static_config).await?;
+    assert!(
+        api::Client::new(static_url.as_str(), None)?
+            .is_static()
+            .await?
+    );
+    let download = static_client
+        .download_exact(&name, &"0.1.0".parse()?)
+        .await?;
+    assert_eq!(fs::read(&download.path)?, wat::parse_str("(component)")?);
+
+    // A static registry is read-only
+    match publish_component(
+        &static_client,
+        &name,
+        "0.2.0",
+        "(component)",
+        false,
+        &signing_key,
+    )
+    .await
+    {
+        Err(e) => assert!(e.to_string().contains("static registry"), "{e}"),
+        Ok(_) => bail!("expected publishing to a static registry to fail"),
+    }
+
+    // Later exports are proven consistent with the checkpoint the client has seen
+    publish_component(
+        &client,
+        &name,
+        "0.2.0",
+        "(component (core module))",
+        false,
+        &signing_key,
+    )
+    .await?;
+    admin.create_checkpoint().await?;
+    admin.export_static().await?;
+
+    static_client.update().await?;
+    let download = static_client
+        .download_exact(&name, &"0.2.0".parse()?)
+        .await?;
+    assert_eq!(
+        fs::read(&download.path)?,
+        wat::parse_str("(component (core module))")?
+    );
+
+    Ok(())
+}
+
+async fn test_static_export_on_checkpoint(
+    config: &Config,
+    admin_token: &str,
+    static_url: &Url,
+) -> Result<()> {
+    let name = PackageName::new("test:exported")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let admin = AdminClient::new(
+        Url::parse(config.home_url.as_ref().unwrap())?,
+        admin_token.to_string().into(),
+    )?;
+    let checkpoint = admin.create_checkpoint().await?;
+    let log_length = checkpoint.as_ref().checkpoint.log_length;
+
+    // The checkpoint is exported without an explicit export
+    let checkpoint_url = static_url.join(static_registry::CHECKPOINT_PATH)?;
+    let deadline = SystemTime::now() + Duration::from_secs(10);
+    loop {
+        let response = reqwest::get(checkpoint_url.clone()).await?;
+        if response.status().is_success() {
+            let exported: SerdeEnvelope<TimestampedCheckpoint> = response.json().await?;
+            if exported.as_ref().checkpoint.log_length == log_length {
+                break;
+            }
+        }
+
+        if SystemTime::now() >= deadline {
+            bail!("checkpoint of log length {log_length} was not exported");
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    let static_root = config
+        .content_dir
+        .as_ref()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("exported-client");
+    let static_config = Config {
+        home_url: Some(static_url.to_string()),
+        registries_dir: Some(static_root.join("registries")),
+        content_dir: Some(static_root.join("content")),
+        namespace_map_path: Some(static_root.join("namespaces")),
+        ..config.clone()
+    };
+    let static_client = create_client(&static_config).await?;
+    let download = static_client
+        .download_exact(&name, &"0.1.0".parse()?)
+        .await?;
+    assert_eq!(fs::read(&download.path)?, wat::parse_str("(component)")?);
+
+    Ok(())
+}
+
+async fn test_list_records(config: &Config) -> Result<()> {
+    let name = PackageName::new("test:listed")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move {
+                Ok(wat::parse_str("(component)")?.into())
+            })),
+            None,
+        )
+        .await?;
+    let stage = |version: &'static str| {
+        client.stage_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Release {
+                    version: version.parse().unwrap(),
+                    content: digest.clone(),
+                    content_type: ContentType::Component,
+                }],
+            },
+        )
+    };
+
+    let staged = stage("0.2.0").await?;
+    let rejected = stage("0.3.0").await?;
+    client.abandon_staged(&name, &rejected).await?;
+
+    // All records are listed in submission order
+    let records = client.list_records(&name, None).await?;
+    assert_eq!(records.len(), 3);
+    assert!(matches!(
+        records[0].state,
+        api::PackageRecordState::Published { .. }
+    ));
+    assert_eq!(records[1].record_id, staged);
+    assert!(matches!(
+        records[1].state,
+        api::PackageRecordState::Staged { .. }
+    ));
+    assert_eq!(records[2].record_id, rejected);
+    match &records[2].state {
+        api::PackageRecordState::Rejected { reason } => {
+            assert!(!reason.is_empty(), "expected a rejection reason")
+        }
+        _ => bail!("expected record `{rejected}` to be rejected"),
+    }
+
+    // Records are filtered by their state
+    for (filter, expected) in [
+        (api::RecordStatusFilter::Published, &records[0].record_id),
+        (api::RecordStatusFilter::Pending, &staged),
+        (api::RecordStatusFilter::Rejected, &rejected),
+    ] {
+        let records = client.list_records(&name, Some(filter)).await?;
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| &record.record_id)
+                .collect::<Vec<_>>(),
+            [expected],
+            "unexpected records for filter {filter:?}"
+        );
+    }
+
+    // Records of an unknown package are not found
+    assert!(matches!(
+        client
+            .list_records(&PackageName::new("test:unlisted")?, None)
+            .await,
+        Err(ClientError::PackageDoesNotExist { .. })
+    ));
+
+    Ok(())
+}
+
+/// Stages the release of a component, returning the staged record.
+async fn stage_release(
+    client: &FileSystemClient,
+    name: &PackageName,
+    version: &str,
+    init: bool,
+) -> Result<RecordId> {
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move {
+                Ok(wat::parse_str("(component)")?.into())
+            })),
+            None,
+        )
+        .await?;
+
+    let mut entries = Vec::with_capacity(2);
+    if init {
+        entries.push(PublishEntry::Init);
+    }
+    entries.push(PublishEntry::Release {
+        version: version.parse()?,
+        content: digest,
+        content_type: ContentType::Component,
+    });
+
+    Ok(client
+        .stage_with_info(
+            &test_signing_key(),
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries,
+            },
+        )
+        .await?)
+}
+
+async fn test_staged_publishes(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    let name = PackageName::new("test:staged")?;
+
+    // A staged record is not published until it is committed
+    let record_id = stage_release(&client, &name, "1.0.0", true).await?;
+    let record = client.await_record(&name, &record_id).await?;
+    match record.state {
+        api::PackageRecordState::Staged {
+            missing_content, ..
+        } => assert!(missing_content.is_empty()),
+        _ => bail!("expected the record to be staged"),
+    }
+    match client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await
+    {
+        Err(ClientError::PublishStaged { record_id: id, .. }) => assert_eq!(id, record_id),
+        res => bail!("expected the publish to be staged, got {res:?}"),
+    }
+    // The log of a staged record is either unknown or empty, depending on
+    // the data store
+    assert!(matches!(
+        client.fetch_package(&name).await,
+        Err(ClientError::PackageDoesNotExist { .. } | ClientError::PackageLogEmpty { .. })
+    ));
+
+    client.commit_staged(&name, &record_id).await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+    let package = client.package(&name).await?;
+    assert!(package.state.release(&"1.0.0".parse()?).is_some());
+
+    // An abandoned record is rejected and cannot be committed
+    let record_id = stage_release(&client, &name, "2.0.0", false).await?;
+    client.abandon_staged(&name, &record_id).await?;
+    assert!(matches!(
+        client
+            .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+            .await,
+        Err(ClientError::PublishRejected { .. })
+    ));
+    assert!(matches!(
+        client.commit_staged(&name, &record_id).await,
+        Err(ClientError::StagedPublishNotFound { .. })
+    ));
+
+    Ok(())
+}
+
+/// Expects a server with a staged publish TTL of one second.
+async fn test_staged_publish_expiry(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    let name = PackageName::new("test:expiring")?;
+
+    // A record that is not committed before its TTL is rejected
+    let record_id = stage_release(&client, &name, "1.0.0", true).await?;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    match client.commit_staged(&name, &record_id).await {
+        Err(ClientError::StagedPublishNotFound { .. }) => {}
+        res => bail!("expected the staged publish to have expired, got {res:?}"),
+    }
+    match client.await_record(&name, &record_id).await?.state {
+        api::PackageRecordState::Rejected { reason } => assert!(reason.contains("expired")),
+        _ => bail!("expected the record to be rejected"),
+    }
+
+    Ok(())
+}
+
+async fn test_package_name_listing(config: &Config, store: &dyn DataStore) -> Result<()> {
+    let listed = PackageName::new("test:name-listed")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &listed, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Store a record that is left pending and one rejected when committed,
+    // as a log must start with an init entry
+    let record = |version: &str| {
+        ProtoEnvelope::signed_contents(
+            &signing_key,
+            PackageRecord {
+                prev: None,
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries: vec![PackageEntry::Release {
+                    version: version.parse().unwrap(),
+                    content: HashAlgorithm::Sha256.digest(version.as_bytes()),
+                    content_type: ContentType::Component,
+                }],
+            },
+        )
+    };
+    let pending = PackageName::new("test:name-pending")?;
+    let rejected = PackageName::new("test:name-rejected")?;
+    for name in [&pending, &rejected] {
+        let log_id = LogId::package_log::<Sha256>(name);
+        let record = record("0.1.0")?;
+        let record_id = RecordId::package_record::<Sha256>(&record);
+        store
+            .store_package_record(&log_id, name, &record_id, &record, &Default::default())
+            .await?;
+        if name == &rejected {
+            assert!(store
+                .commit_package_record(&log_id, &record_id, 0)
+                .await
+                .is_err());
+        }
+    }
+
+    // Only packages with a validated record are listed
+    let names = store.list_package_names(None, u16::MAX).await?;
+    assert!(names.contains(&listed), "unexpected names: {names:?}");
+    assert!(!names.contains(&pending), "unexpected names: {names:?}");
+    assert!(!names.contains(&rejected), "unexpected names: {names:?}");
+
+    Ok(())
+}
+
+async fn test_fetch_token_pagination(config: &Config, store: &dyn DataStore) -> Result<()> {
+    let name = PackageName::new("test:paginated")?;
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    for (i, version) in ["0.1.0", "0.2.0", "0.3.0"].into_iter().enumerate() {
+        publish_component(&client, &name, version, "(component)", i == 0, &signing_key).await?;
+    }
+
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let log_length = api
+        .latest_checkpoint(None)
+        .await?
+        .as_ref()
+        .checkpoint
+        .log_length;
+
+    // Paging one record at a time visits every record in registry order
+    let all = store
+        .get_package_records(&log_id, log_length, None, 100)
+        .await?;
+    assert_eq!(all.len(), 3);
+
+    let mut since = None;
+    let mut paged = Vec::new();
+    loop {
+        let page = store
+            .get_package_records(&log_id, log_length, since.as_ref(), 1)
+            .await?;
+        let Some(record) = page.into_iter().next() else {
+            break;
+        };
+        since = Some(RecordId::package_record::<Sha256>(&record.envelope));
+        paged.push(record.registry_index);
+    }
+    assert_eq!(
+        paged,
+        all.iter().map(|r| r.registry_index).collect::<Vec<_>>()
+    );
+    assert!(paged.windows(2).all(|w| w[0] < w[1]));
+
+    // A token of another log is not found
+    let other = PackageName::new("test:other-paginated")?;
+    publish_component(&client, &other, "0.1.0", "(component)", true, &signing_key).await?;
+    let other_id = LogId::package_log::<Sha256>(&other);
+    let log_length = api
+        .latest_checkpoint(None)
+        .await?
+        .as_ref()
+        .checkpoint
+        .log_length;
+    let other_records = store
+        .get_package_records(&other_id, log_length, None, 1)
+        .await?;
+    let token = RecordId::package_record::<Sha256>(&other_records[0].envelope);
+    assert!(matches!(
+        store
+            .get_package_records(&log_id, log_length, Some(&token), 1)
+            .await,
+        Err(DataStoreError::RecordNotFound(_))
+    ));
+
+    Ok(())
+}
+
+async fn test_name_lists(config: &Config, admin_token: &str) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let name = PackageName::new("test:reserved")?;
+
+    // Claiming a reserved name is unauthorized
+    let e = publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key)
+        .await
+        .expect_err("expected publish to fail");
+    assert!(
+        format!("{e:#}").contains("is reserved"),
+        "unexpected error: {e:#}"
+    );
+
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join("admin/names")?;
+    let http = reqwest::Client::new();
+    let response = http.get(url.clone()).send().await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = http
+        .get(url.clone())
+        .bearer_auth("not-the-token")
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let lists: serde_json::Value = http
+        .get(url.clone())
+        .bearer_auth(admin_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(lists["reserved"], serde_json::json!(["test:reserved"]));
+
+    // Invalid lists are rejected
+    let response = http
+        .put(url.clone())
+        .bearer_auth(admin_token)
+        .json(&serde_json::json!({ "reserved": ["not a name"] }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Once the name is no longer reserved, it may be claimed
+    http.put(url)
+        .bearer_auth(admin_token)
+        .json(&serde_json::json!({ "reserved": [], "blocked": ["*:*squat*"] }))
+        .send()
+        .await?
+        .error_for_status()?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let e = publish_component(
+        &client,
+        &PackageName::new("test:squatted")?,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await
+    .expect_err("expected publish to fail");
+    assert!(
+        format!("{e:#}").contains("is blocked"),
+        "unexpected error: {e:#}"
+    );
+
+    Ok(())
+}
+
+async fn test_teams(config: &Config, admin_token: &str) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let other_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+
+    // Only members of the team maintaining the namespace's packages may publish
+    publish_component(
+        &client,
+        &PackageName::new("test:team-owned")?,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+    let name = PackageName::new("test:other-owned")?;
+    let e = publish_component(&client, &name, "0.1.0", "(component)", true, &other_key)
+        .await
+        .expect_err("expected publish to fail");
+    assert!(
+        format!("{e:#}").contains("is not a member of a team"),
+        "unexpected error: {e:#}"
+    );
+
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join("admin/teams")?;
+    let http = reqwest::Client::new();
+    let teams: serde_json::Value = http
+        .get(url.clone())
+        .bearer_auth(admin_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(teams["test"]["core"]["packages"], serde_json::json!(["*"]));
+
+    // Invalid teams are rejected
+    let response = http
+        .put(url.join("teams/test/Not%20Kebab")?)
+        .bearer_auth(admin_token)
+        .json(&serde_json::json!({ "keys": [], "packages": ["*"] }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Once the key joins a team maintaining the package, it may publish
+    http.put(url.join("teams/test/other")?)
+        .bearer_auth(admin_token)
+        .json(&serde_json::json!({
+            "keys": [other_key.public_key().fingerprint().to_string()],
+            "packages": ["other-owned"],
+        }))
+        .send()
+        .await?
+        .error_for_status()?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &other_key).await?;
+
+    let response = http
+        .delete(url.join("teams/test/other")?)
+        .bearer_auth(admin_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = http
+        .delete(url.join("teams/test/other")?)
+        .bearer_auth(admin_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+async fn test_record_approvals(config: &Config, approver: &PrivateKey) -> Result<()> {
+    let name = PackageName::new("test:approved")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let other_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+
+    // Records of the namespace await approval once their content is uploaded
+    let e = publish_component(
+        &client,
+        &name,
+        "0.1.0",
+        "(component (core module (memory 1)))",
+        true,
+        &signing_key,
+    )
+    .await
+    .expect_err("expected the record to await approval");
+    let record_id = match e.downcast::<ClientError>()? {
+        ClientError::PublishAwaitingApproval { record_id, .. } => record_id,
+        e => bail!("expected the record to await approval, got {e}"),
+    };
+    let record = client.await_record(&name, &record_id).await?;
+    match record.state {
+        api::PackageRecordState::AwaitingApproval { missing_content } => {
+            assert!(missing_content.is_empty())
+        }
+        _ => bail!("expected the record to await approval"),
+    }
+
+    // The signer of a record cannot approve it, nor can unauthorized keys
+    let e = client
+        .approve_record(&signing_key, &name, &record_id)
+        .await
+        .expect_err("expected the approval to be rejected");
+    assert!(
+        format!("{e:#}").contains("cannot also approve it"),
+        "unexpected error: {e:#}"
+    );
+    let e = client
+        .approve_record(&other_key, &name, &record_id)
+        .await
+        .expect_err("expected the approval to be rejected");
+    assert!(
+        format!("{e:#}").contains("is not authorized to approve"),
+        "unexpected error: {e:#}"
+    );
+
+    client.approve_record(approver, &name, &record_id).await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+    match client.approve_record(approver, &name, &record_id).await {
+        Err(ClientError::PendingApprovalNotFound { .. }) => {}
+        res => bail!("expected the record to not await approval, got {res:?}"),
+    }
+
+    // Keys of the package log with the release permission may also approve
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Grant {
+                    key: other_key.public_key(),
+                    permissions: vec![package::Permission::Release],
+                }],
+            },
+        )
+        .await?;
+    client.approve_record(approver, &name, &record_id).await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Yank {
+                    version: "0.1.0".parse()?,
+                }],
+            },
+        )
+        .await?;
+    client.approve_record(&other_key, &name, &record_id).await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    Ok(())
+}
+
+async fn test_federation_health(config: &Config, admin_token: &str) -> Result<()> {
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join("admin/federation")?;
+    let http = reqwest::Client::new();
+    let response = http.get(url.clone()).send().await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let health: Vec<serde_json::Value> = http
+        .get(url)
+        .bearer_auth(admin_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(health.len(), 2, "unexpected health: {health:?}");
+
+    let wasi = health
+        .iter()
+        .find(|h| h["namespace"] == "wasi")
+        .context("missing health of `wasi`")?;
+    assert_eq!(wasi["registry"], "upstream.example");
+    assert_eq!(wasi["status"], "healthy");
+    assert!(wasi["logLength"].as_u64().is_some_and(|len| len > 0));
+
+    let other = health
+        .iter()
+        .find(|h| h["namespace"] == "other")
+        .context("missing health of `other`")?;
+    assert_eq!(other["status"], "invalidCheckpoint");
+    assert!(other["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("not pinned")));
+
+    Ok(())
+}
+
+async fn test_semver_policy(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let name = PackageName::new("test:semver-checked")?;
+    let wit = |functions: &str| {
+        format!("package test:semver-checked;\ninterface api {{\n{functions}\n}}")
+    };
+
+    publish_wit(
+        &client,
+        &name,
+        "1.0.0",
+        &wit("f: func();\ng: func();"),
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // A breaking change without a major version bump is rejected on upload
+    let breaking = wit("f: func();");
+    let e = publish_wit(&client, &name, "1.1.0", &breaking, false, &signing_key)
+        .await
+        .expect_err("expected publish to fail");
+    assert!(
+        format!("{e:#}").contains("function `g` was removed from export `test:semver-checked/api`"),
+        "unexpected error: {e:#}"
+    );
+
+    // Content that is already present is checked when the record is published
+    let e = publish_wit(&client, &name, "1.1.1", &breaking, false, &signing_key)
+        .await
+        .expect_err("expected publish to fail");
+    assert!(
+        format!("{e:#}").contains("without a major version bump"),
+        "unexpected error: {e:#}"
+    );
+
+    // Compatible changes and breaking changes with a major version bump are allowed
+    publish_wit(
+        &client,
+        &name,
+        "1.1.0",
+        &wit("f: func();\ng: func();\nh: func();"),
+        false,
+        &signing_key,
+    )
+    .await?;
+    publish_wit(&client, &name, "2.0.0", &breaking, false, &signing_key).await?;
+
+    Ok(())
+}
+
+async fn test_typosquat_flags(config: &Config, admin_token: &str) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let original = PackageName::new("test:typosquat")?;
+    let similar = PackageName::new("test:typosqaut")?;
+
+    publish_component(
+        &client,
+        &original,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+    assert!(client.package_metadata(&original).await?.advisory.is_none());
+
+    // A similar name is published, but flagged for moderation
+    publish_component(
+        &client,
+        &similar,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+    let advisory = client
+        .package_metadata(&similar)
+        .await?
+        .advisory
+        .expect("expected an advisory");
+    assert_eq!(advisory.similar_to, original);
+    assert!(advisory.reason.contains("edit distance"));
+
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join("admin/flags")?;
+    let http = reqwest::Client::new();
+    let flags: serde_json::Value = http
+        .get(url.clone())
+        .bearer_auth(admin_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let log_id = LogId::package_log::<Sha256>(&similar);
+    assert_eq!(flags.as_array().map(Vec::len), Some(1));
+    assert_eq!(flags[0]["logId"], log_id.to_string());
+    assert_eq!(flags[0]["similarTo"], original.to_string());
+
+    // Clearing the flag removes the advisory
+    let flag_url = url.join(&format!("flags/{log_id}"))?;
+    let response = http
+        .delete(flag_url.clone())
+        .bearer_auth(admin_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = http
+        .delete(flag_url)
+        .bearer_auth(admin_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(client.package_metadata(&similar).await?.advisory.is_none());
+
+    Ok(())
+}
+
+async fn test_advisories(config: &Config, admin_token: &str) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let name = PackageName::new("test:vulnerable")?;
+    let log_id = LogId::package_log::<Sha256>(&name);
+
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+    publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key).await?;
+
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join("admin/advisories")?;
+    let http = reqwest::Client::new();
+    let response = http
+        .put(url.clone())
+        .json(&serde_json::json!({}))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    http.put(url.clone())
+        .bearer_auth(admin_token)
+        .json(&serde_json::json!({
+            "package": name,
+            "id": "TEST-2026-0001",
+            "versions": "<0.2.0",
+            "severity": "high",
+            "summary": "a test vulnerability",
+            "url": "https://example.com/TEST-2026-0001",
+        }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let advisories = api.get_package_advisories(None, &log_id).await?.advisories;
+    assert_eq!(advisories.len(), 1);
+    assert_eq!(advisories[0].id, "TEST-2026-0001");
+    assert_eq!(advisories[0].severity, api::AdvisorySeverity::High);
+
+    // Advisories are fetched with updates and apply to affected versions only
+    client.update().await?;
+    let download = client.download_exact(&name, &"0.1.0".parse()?).await?;
+    assert_eq!(download.advisories, advisories);
+    let download = client.download_exact(&name, &"0.2.0".parse()?).await?;
+    assert!(download.advisories.is_empty());
+
+    // An advisory policy denies affected versions unless the advisory is waived
+    let client = client.with_advisory_policy(AdvisoryPolicy::new(api::AdvisorySeverity::High));
+    match client.download_exact(&name, &"0.1.0".parse()?).await {
+        Err(ClientError::AdvisoryPolicyViolation {
+            name: denied_name,
+            advisories: denied,
+            ..
+        }) => {
+            assert_eq!(denied_name, name);
+            assert_eq!(denied, advisories);
+        }
+        res => bail!("expected an advisory policy violation, got {res:?}"),
+    }
+    client.download_exact(&name, &"0.2.0".parse()?).await?;
+
+    let client = client.with_advisory_policy(
+        AdvisoryPolicy::new(api::AdvisorySeverity::Low).with_waived(["TEST-2026-0001"]),
+    );
+    client.download_exact(&name, &"0.1.0".parse()?).await?;
+
+    let advisory_url = url.join(&format!("advisories/{name}/TEST-2026-0001"))?;
+    let response = http
+        .delete(advisory_url.clone())
+        .bearer_auth(admin_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = http
+        .delete(advisory_url)
+        .bearer_auth(admin_token)
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    client.update().await?;
+    let download = client.download_exact(&name, &"0.1.0".parse()?).await?;
+    assert!(download.advisories.is_empty());
+
+    Ok(())
+}
+
+async fn test_osv_advisories(config: &Config, admin_token: &str) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let name = PackageName::new("test:osv")?;
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    let home_url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let http = reqwest::Client::new();
+
+    // Invalid records import nothing
+    let response = http
+        .post(home_url.join("admin/advisories/osv")?)
+        .bearer_auth(admin_token)
+        .json(&serde_json::json!([
+            {
+                "id": "OSV-TEST-1",
+                "modified": "2026-01-02T03:04:05Z",
+                "affected": [{ "package": { "ecosystem": "Warg", "name": "test:osv" }, "versions": ["1.0.0"] }],
+            },
+            {
+                "id": "OSV-TEST-2",
+                "modified": "not a time",
+            },
+        ]))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let imported: serde_json::Value = http
+        .post(home_url.join("admin/advisories/osv")?)
+        .bearer_auth(admin_token)
+        .json(&serde_json::json!({
+            "schema_version": "1.6.0",
+            "id": "OSV-TEST-1",
+            "modified": "2026-01-02T03:04:05Z",
+            "summary": "an imported vulnerability",
+            "affected": [
+                {
+                    "package": { "ecosystem": "npm", "name": "unrelated" },
+                    "versions": ["1.0.0"],
+                },
+                {
+                    "package": { "ecosystem": "Warg", "name": "test:osv" },
+                    "ranges": [{
+                        "type": "SEMVER",
+                        "events": [{ "introduced": "0" }, { "fixed": "0.2.0" }],
+                    }],
+                    "database_specific": { "severity": "CRITICAL" },
+                },
+            ],
+            "references": [{ "type": "ADVISORY", "url": "https://example.com/OSV-TEST-1" }],
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    assert_eq!(imported.as_array().map(Vec::len), Some(1));
+
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let advisories = api
+        .get_package_advisories(None, &LogId::package_log::<Sha256>(&name))
+        .await?
+        .advisories;
+    assert_eq!(advisories.len(), 1);
+    assert_eq!(advisories[0].versions.to_string(), "<0.2.0");
+    assert_eq!(advisories[0].severity, api::AdvisorySeverity::Critical);
+    assert_eq!(
+        advisories[0].url.as_deref(),
+        Some("https://example.com/OSV-TEST-1")
+    );
+
+    let records: serde_json::Value = http
+        .get(home_url.join("v1/advisories/osv")?)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let record = records
+        .as_array()
+        .and_then(|r| r.iter().find(|r| r["id"] == "OSV-TEST-1"))
+        .context("expected an exported record")?;
+    assert_eq!(record["affected"][0]["package"]["name"], "test:osv");
+    assert_eq!(
+        record["affected"][0]["ranges"][0]["events"],
+        serde_json::json!([{ "introduced": "0" }, { "fixed": "0.2.0" }])
+    );
+    assert_eq!(
+        record["affected"][0]["database_specific"]["severity"],
+        "CRITICAL"
+    );
+
+    Ok(())
+}
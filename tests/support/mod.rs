@@ -18,7 +18,7 @@ use warg_crypto::{
     hash::AnyHash,
     signing::{KeyID, PrivateKey},
 };
-use warg_protocol::{operator, registry::PackageName};
+use warg_protocol::{operator, package::ContentType, registry::PackageName};
 use warg_server::{
     datastore::DataStore,
     policy::{content::WasmContentPolicy, record::AuthorizedKeyPolicy},
@@ -52,12 +52,26 @@ pub async fn create_client(config: &warg_client::Config) -> Result<FileSystemCli
 
 pub struct ServerInstance {
     task: Option<JoinHandle<()>>,
+    local_service: Option<String>,
+    urls: Vec<Url>,
     shutdown: CancellationToken,
     _subscriber_guard: DefaultGuard,
 }
 
+impl ServerInstance {
+    /// Gets the URLs of the server's listeners.
+    pub fn urls(&self) -> &[Url] {
+        &self.urls
+    }
+}
+
 impl Drop for ServerInstance {
     fn drop(&mut self) {
+        // The server does not stop while its in-process service is registered
+        if let Some(name) = &self.local_service {
+            warg_client::transport::unregister_local_service(name);
+        }
+
         futures::executor::block_on(async move {
             self.shutdown.cancel();
             self.task.take().unwrap().await.unwrap();
@@ -118,13 +132,7 @@ pub async fn spawn_server(
     data_store: Option<Box<dyn DataStore>>,
     authorized_keys: Option<Vec<(String, KeyID)>>,
 ) -> Result<(ServerInstance, warg_client::Config)> {
-    let _subscriber_guard = thread_test_logging();
-
-    let shutdown = CancellationToken::new();
     let mut config = Config::new(test_operator_key(), test_namespaces(), root.join("server"))
-        .with_addr(([127, 0, 0, 1], 0))
-        .with_shutdown(shutdown.clone().cancelled_owned())
-        .with_checkpoint_interval(Duration::from_millis(100))
         .with_content_policy(WasmContentPolicy::default()); // For the tests, we assume only wasm content is allowed.
 
     if let Some(content_url) = content_base_url {
@@ -144,10 +152,60 @@ pub async fn spawn_server(
         config = config.with_boxed_data_store(store);
     }
 
+    spawn_server_with_config(root, config).await
+}
+
+/// Spawns a server with the given configuration as a background task.
+///
+/// The server listens on a random local port, unless the configuration
+/// specifies a Unix domain socket, and checkpoints frequently.
+pub async fn spawn_server_with_config(
+    root: &Path,
+    config: Config,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    spawn(root, config, None).await
+}
+
+/// Spawns a server embedded in the test process as a background task.
+///
+/// Clients reach the server through the in-process service of the given
+/// name; the server itself only listens on a Unix domain socket.
+#[cfg(unix)]
+pub async fn spawn_embedded_server(
+    root: &Path,
+    name: &str,
+    config: Config,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    let config = config
+        .with_unix_socket(root.join(format!("{name}.sock")))
+        .with_content_base_url(format!("http+local://{name}/").parse()?);
+    spawn(root, config, Some(name)).await
+}
+
+async fn spawn(
+    root: &Path,
+    config: Config,
+    local_service: Option<&str>,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    let _subscriber_guard = thread_test_logging();
+
+    let shutdown = CancellationToken::new();
+    let config = config
+        .with_addr(([127, 0, 0, 1], 0))
+        .with_shutdown(shutdown.clone().cancelled_owned())
+        .with_checkpoint_interval(Duration::from_millis(100));
+
     let server = Server::new(config).initialize().await?;
 
-    let addr = server.local_addr()?;
-    tracing::debug!("Test server running at {addr}");
+    let urls = server.urls()?;
+    let url = match local_service {
+        Some(name) => {
+            warg_client::transport::register_local_service(name, server.router());
+            format!("http+local://{name}")
+        }
+        None => server.url()?.as_str().trim_end_matches('/').to_string(),
+    };
+    tracing::debug!("Test server running at {url}");
 
     let task = tokio::spawn(async move {
         let _subscriber_guard = thread_test_logging();
@@ -156,12 +214,14 @@ pub async fn spawn_server(
 
     let instance = ServerInstance {
         task: Some(task),
+        local_service: local_service.map(ToString::to_string),
+        urls,
         shutdown,
         _subscriber_guard,
     };
 
     let config = warg_client::Config {
-        home_url: Some(format!("http://{addr}")),
+        home_url: Some(url),
         registries_dir: Some(root.join("registries")),
         content_dir: Some(root.join("content")),
         namespace_map_path: Some(root.join("namespaces")),
@@ -171,6 +231,23 @@ pub async fn spawn_server(
         auto_accept_federation_hints: false,
         disable_interactive: true,
         keyring_backend: None,
+        package_policy: None,
+        license_allowlist: None,
+        advisory_policy: None,
+        signing_policy: None,
+        user_agent_suffix: None,
+        request_headers: Default::default(),
+        connect: Default::default(),
+        checkpoint_clock: Default::default(),
+        max_checkpoint_age_secs: None,
+        fetch_page_size: None,
+        max_parallel_downloads: None,
+        max_parallel_uploads: None,
+        max_in_flight_requests: None,
+        verification_workers: None,
+        storage_layout: Default::default(),
+        workspace_dir: None,
+        workspace_fallback: false,
     };
 
     Ok((instance, config))
@@ -199,6 +276,7 @@ pub async fn publish(
     entries.push(PublishEntry::Release {
         version: version.parse().unwrap(),
         content: digest.clone(),
+        content_type: ContentType::Component,
     });
 
     let record_id = client
@@ -238,6 +316,13 @@ pub async fn publish_component(
     .await
 }
 
+/// Creates an empty component with the given license in its registry metadata.
+pub fn licensed_component(license: &str) -> Result<Vec<u8>> {
+    let mut metadata = wasm_metadata::RegistryMetadata::default();
+    metadata.set_license(Some(license.to_string()));
+    metadata.add_to_wasm(&wat::parse_str("(component)")?)
+}
+
 pub async fn publish_wit(
     client: &FileSystemClient,
     name: &PackageName,
@@ -246,16 +331,12 @@ pub async fn publish_wit(
     init: bool,
     signing_key: &PrivateKey,
 ) -> Result<AnyHash> {
+    publish(client, name, version, encode_wit(wit)?, init, signing_key).await
+}
+
+/// Encodes the given WIT package as a binary-encoded WIT package.
+pub fn encode_wit(wit: &str) -> Result<Vec<u8>> {
     let mut resolve = Resolve::new();
     let pkg = resolve.push(UnresolvedPackage::parse(Path::new("foo.wit"), wit)?)?;
-
-    publish(
-        client,
-        name,
-        version,
-        wit_component::encode(Some(true), &resolve, pkg)?,
-        init,
-        signing_key,
-    )
-    .await
+    wit_component::encode(Some(true), &resolve, pkg)
 }